@@ -74,6 +74,7 @@ impl Client {
         let conn_options = ConnectionOptions {
             timeout: config.timeout,
             connect_timeout: config.connect_timeout,
+            ..Default::default()
         };
         let conn_pool = ConnectionManager::new(
             1,
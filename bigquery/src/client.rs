@@ -6,13 +6,15 @@ use std::marker::PhantomData;
 use std::sync::Arc;
 
 use google_cloud_gax::conn::{ConnectionOptions, Environment};
+use google_cloud_gax::grpc::transport::{Certificate, Identity};
 use google_cloud_gax::retry::RetrySetting;
 use google_cloud_googleapis::cloud::bigquery::storage::v1::{
     read_session, CreateReadSessionRequest, DataFormat, ReadSession,
 };
 use google_cloud_token::TokenSourceProvider;
 
-use crate::grpc::apiv1::conn_pool::{ReadConnectionManager, DOMAIN};
+use crate::grpc::apiv1::bigquery_client::StreamingReadClient;
+use crate::grpc::apiv1::conn_pool::{ReadConnectionManager, WriteConnectionManager, DOMAIN};
 use crate::http::bigquery_client::BigqueryClient;
 use crate::http::bigquery_dataset_client::BigqueryDatasetClient;
 use crate::http::bigquery_job_client::BigqueryJobClient;
@@ -23,14 +25,25 @@ use crate::http::bigquery_table_client::BigqueryTableClient;
 use crate::http::bigquery_tabledata_client::BigqueryTabledataClient;
 use crate::http::job::get_query_results::GetQueryResultsRequest;
 use crate::http::job::query::QueryRequest;
-use crate::http::job::{is_script, is_select_query, JobConfiguration, JobReference, JobStatistics, JobType};
-use crate::http::table::TableReference;
+use crate::http::job::{
+    is_script, is_select_query, Job, JobConfiguration, JobReference, JobState, JobStatistics, JobType,
+};
+use crate::http::table::{TableReference, TableSchema};
 use crate::query::{QueryOption, QueryResult};
 use crate::storage;
-use crate::{http, query};
+use crate::storage_write::{StorageWriter, StorageWriterOption};
+use crate::{http, job, query};
 
 const JOB_RETRY_REASONS: [&str; 3] = ["backendError", "rateLimitExceeded", "internalError"];
 
+fn is_retryable_job_error(e: &http::error::Error) -> bool {
+    match e {
+        http::error::Error::HttpClient(_) => true,
+        http::error::Error::Response(r) => r.is_retryable(&JOB_RETRY_REASONS),
+        _ => false,
+    }
+}
+
 #[derive(Debug)]
 pub struct ClientConfig {
     http: reqwest_middleware::ClientWithMiddleware,
@@ -38,6 +51,7 @@ pub struct ClientConfig {
     token_source_provider: Box<dyn TokenSourceProvider>,
     environment: Environment,
     streaming_read_config: ChannelConfig,
+    streaming_write_config: ChannelConfig,
     debug: bool,
 }
 
@@ -47,6 +61,10 @@ pub struct ChannelConfig {
     pub num_channels: usize,
     pub connect_timeout: Option<Duration>,
     pub timeout: Option<Duration>,
+    /// The PEM encoded root certificates trusted in addition to the roots selected by the features.
+    pub ca_certificate: Option<Certificate>,
+    /// The client certificate and key for the mutual TLS.
+    pub identity: Option<Identity>,
 }
 
 impl Default for ChannelConfig {
@@ -55,21 +73,35 @@ impl Default for ChannelConfig {
             num_channels: 4,
             connect_timeout: Some(Duration::from_secs(30)),
             timeout: None,
+            ca_certificate: None,
+            identity: None,
         }
     }
 }
 
+/// default_http_client selects rustls when the `rustls-tls` feature is enabled alongside `default-tls`.
+fn default_http_client() -> reqwest::Client {
+    let builder = reqwest::Client::builder();
+    #[cfg(feature = "rustls-tls")]
+    let builder = builder.use_rustls_tls();
+    builder.build().unwrap()
+}
+
 impl ClientConfig {
     pub fn new(
         http_token_source_provider: Box<dyn TokenSourceProvider>,
         grpc_token_source_provider: Box<dyn TokenSourceProvider>,
     ) -> Self {
         Self {
-            http: reqwest_middleware::ClientBuilder::new(reqwest::Client::default()).build(),
+            http: reqwest_middleware::ClientBuilder::new(default_http_client()).build(),
             bigquery_endpoint: "https://bigquery.googleapis.com".into(),
             token_source_provider: http_token_source_provider,
             environment: Environment::GoogleCloud(grpc_token_source_provider),
             streaming_read_config: ChannelConfig::default(),
+            streaming_write_config: ChannelConfig {
+                num_channels: 1,
+                ..Default::default()
+            },
             debug: false,
         }
     }
@@ -81,6 +113,10 @@ impl ClientConfig {
         self.streaming_read_config = value;
         self
     }
+    pub fn with_streaming_write_config(mut self, value: ChannelConfig) -> Self {
+        self.streaming_write_config = value;
+        self
+    }
     pub fn with_http_client(mut self, value: reqwest_middleware::ClientWithMiddleware) -> Self {
         self.http = value;
         self
@@ -170,6 +206,7 @@ pub struct Client {
     row_access_policy_client: BigqueryRowAccessPolicyClient,
     model_client: BigqueryModelClient,
     streaming_read_client_conn_pool: Arc<ReadConnectionManager>,
+    streaming_write_client_conn_pool: Arc<WriteConnectionManager>,
 }
 
 impl Client {
@@ -187,10 +224,24 @@ impl Client {
         let conn_options = ConnectionOptions {
             timeout: read_config.timeout,
             connect_timeout: read_config.connect_timeout,
+            ca_certificate: read_config.ca_certificate,
+            identity: read_config.identity,
+            ..Default::default()
         };
 
         let streaming_read_client_conn_pool =
             ReadConnectionManager::new(read_config.num_channels, &config.environment, DOMAIN, &conn_options).await?;
+
+        let write_config = config.streaming_write_config;
+        let conn_options = ConnectionOptions {
+            timeout: write_config.timeout,
+            connect_timeout: write_config.connect_timeout,
+            ca_certificate: write_config.ca_certificate,
+            identity: write_config.identity,
+            ..Default::default()
+        };
+        let streaming_write_client_conn_pool =
+            WriteConnectionManager::new(write_config.num_channels, &config.environment, DOMAIN, &conn_options).await?;
         Ok(Self {
             dataset_client: BigqueryDatasetClient::new(client.clone()),
             table_client: BigqueryTableClient::new(client.clone()),
@@ -200,6 +251,7 @@ impl Client {
             row_access_policy_client: BigqueryRowAccessPolicyClient::new(client.clone()),
             model_client: BigqueryModelClient::new(client.clone()),
             streaming_read_client_conn_pool: Arc::new(streaming_read_client_conn_pool),
+            streaming_write_client_conn_pool: Arc::new(streaming_write_client_conn_pool),
         })
     }
 
@@ -301,21 +353,19 @@ impl Client {
         T: http::query::value::StructDecodable + storage::value::StructDecodable,
     {
         let result = self.job_client.query(project_id, &request).await?;
-        let (total_rows, page_token, rows, force_first_fetch) = if result.job_complete {
+        let (total_rows, schema, page_token, rows, force_first_fetch) = if result.job_complete {
             (
                 result.total_rows.unwrap_or_default(),
+                result.schema,
                 result.page_token,
                 result.rows.unwrap_or_default(),
                 false,
             )
         } else {
-            (
-                self.wait_for_query(&result.job_reference, &option.retry, &request.timeout_ms)
-                    .await?,
-                None,
-                vec![],
-                true,
-            )
+            let (total_rows, schema) = self
+                .wait_for_query(&result.job_reference, &option, &request.timeout_ms)
+                .await?;
+            (total_rows, schema, None, vec![], true)
         };
 
         //use storage api instead of rest API
@@ -335,34 +385,77 @@ impl Client {
                 .new_storage_row_iterator_from_job::<T>(job.job_reference, job.statistics, job.configuration)
                 .await?;
             return Ok(query::Iterator {
-                inner: QueryResult::Storage(iter),
+                inner: QueryResult::Storage(Box::new(iter)),
                 total_size: total_rows,
+                job_reference: result.job_reference,
+                schema,
             });
         }
 
         let http_query_iterator = http::query::Iterator {
             client: self.job_client.clone(),
-            project_id: result.job_reference.project_id,
-            job_id: result.job_reference.job_id,
+            project_id: result.job_reference.project_id.clone(),
+            job_id: result.job_reference.job_id.clone(),
             request: GetQueryResultsRequest {
                 start_index: 0,
                 page_token,
                 max_results: request.max_results,
                 timeout_ms: request.timeout_ms,
-                location: result.job_reference.location,
+                location: result.job_reference.location.clone(),
                 format_options: request.format_options,
             },
             chunk: VecDeque::from(rows),
             total_size: total_rows,
             force_first_fetch,
+            schema: schema.clone().map(Arc::new),
             _marker: PhantomData,
         };
         Ok(query::Iterator {
             inner: QueryResult::Http(http_query_iterator),
             total_size: total_rows,
+            job_reference: result.job_reference,
+            schema,
         })
     }
 
+    /// Run query job and get the result rows as a stream.
+    /// The stream waits for the job completion and then fetches the pages of the results lazily.
+    /// The results too large for a response are read from the destination table of the job,
+    /// by the page token or by the Storage Read API when it is enabled by `QueryOption`.
+    /// ```rust
+    /// use futures_util::StreamExt;
+    /// use google_cloud_bigquery::http::job::query::QueryRequest;
+    /// use google_cloud_bigquery::query::row::Row;
+    /// use google_cloud_bigquery::client::Client;
+    /// use google_cloud_bigquery::query::QueryOption;
+    /// use std::time::Duration;
+    ///
+    /// async fn run(client: &Client, project_id: &str) {
+    ///     let request = QueryRequest {
+    ///         query: "SELECT * FROM dataset.table".to_string(),
+    ///         ..Default::default()
+    ///     };
+    ///     let option = QueryOption::default().with_timeout(Duration::from_secs(60));
+    ///     let mut stream = client.query_stream::<Row>(project_id, request, option).await.unwrap();
+    ///     println!("job={:?} total_rows={}", stream.job_reference, stream.total_rows);
+    ///     while let Some(row) = stream.next().await {
+    ///         let row = row.unwrap();
+    ///         let col1 = row.column::<String>(0);
+    ///     }
+    /// }
+    /// ```
+    pub async fn query_stream<T>(
+        &self,
+        project_id: &str,
+        request: QueryRequest,
+        option: QueryOption,
+    ) -> Result<query::RowStream<T>, QueryError>
+    where
+        T: http::query::value::StructDecodable + storage::value::StructDecodable + Send + 'static,
+    {
+        Ok(self.query_with_option(project_id, request, option).await?.into_stream())
+    }
+
     async fn new_storage_row_iterator_from_job<T>(
         &self,
         mut job: JobReference,
@@ -416,9 +509,9 @@ impl Client {
     async fn wait_for_query(
         &self,
         job: &JobReference,
-        builder: &ExponentialBuilder,
+        option: &QueryOption,
         timeout_ms: &Option<i64>,
-    ) -> Result<i64, query::run::Error> {
+    ) -> Result<(i64, Option<TableSchema>), query::run::Error> {
         // Use get_query_results only to wait for completion, not to read results.
         let request = GetQueryResultsRequest {
             max_results: Some(0),
@@ -434,20 +527,117 @@ impl Client {
                 .await
                 .map_err(query::run::Error::Http)?;
             if result.job_complete {
-                Ok(result.total_rows)
+                Ok((result.total_rows, result.schema))
             } else {
                 Err(query::run::Error::JobIncomplete)
             }
         };
-        action
-            .retry(builder)
-            .when(|e: &query::run::Error| match e {
-                query::run::Error::JobIncomplete => true,
-                query::run::Error::Http(http::error::Error::HttpClient(_)) => true,
-                query::run::Error::Http(http::error::Error::Response(r)) => r.is_retryable(&JOB_RETRY_REASONS),
-                _ => false,
-            })
-            .await
+        let wait = action.retry(&option.retry).when(|e: &query::run::Error| match e {
+            query::run::Error::JobIncomplete => true,
+            query::run::Error::Http(e) => is_retryable_job_error(e),
+            _ => false,
+        });
+        match option.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, wait)
+                .await
+                .map_err(|_| query::run::Error::Timeout)?,
+            None => wait.await,
+        }
+    }
+
+    /// Create the load job from Google Cloud Storage.
+    /// The job is created with the id generated by the client, so the retries of the transient failures are idempotent.
+    /// ```rust
+    /// use google_cloud_bigquery::client::Client;
+    /// use google_cloud_bigquery::http::job::WriteDisposition;
+    /// use google_cloud_bigquery::http::table::{SourceFormat, TableReference};
+    /// use google_cloud_bigquery::job::{LoadJob, WaitOption};
+    ///
+    /// async fn run(client: &Client, table: TableReference) {
+    ///     let load = LoadJob::new(vec!["gs://bucket/data/*.parquet".to_string()], table)
+    ///         .with_source_format(SourceFormat::Parquet)
+    ///         .with_write_disposition(WriteDisposition::WriteTruncate)
+    ///         .with_job_id_prefix("daily_load_");
+    ///     let job = client.load("project", load).await.unwrap();
+    ///     let done = client.wait_for_job(&job.job_reference, WaitOption::default()).await.unwrap();
+    /// }
+    /// ```
+    pub async fn load(&self, project_id: &str, job: job::LoadJob) -> Result<Job, job::Error> {
+        self.create_job(job.build(project_id)).await
+    }
+
+    /// Create the extract job to Google Cloud Storage.
+    /// The job is created with the id generated by the client, so the retries of the transient failures are idempotent.
+    pub async fn extract(&self, project_id: &str, job: job::ExtractJob) -> Result<Job, job::Error> {
+        self.create_job(job.build(project_id)).await
+    }
+
+    async fn create_job(&self, job: Job) -> Result<Job, job::Error> {
+        let action = || async {
+            match self.job_client.create(&job).await {
+                // The job is already created by the previous attempt.
+                Err(http::error::Error::Response(r)) if r.code == 409 => {
+                    let request = GetJobRequest {
+                        location: job.job_reference.location.clone(),
+                    };
+                    self.job_client
+                        .get(&job.job_reference.project_id, &job.job_reference.job_id, &request)
+                        .await
+                }
+                result => result,
+            }
+        };
+        let created = action
+            .retry(&ExponentialBuilder::default())
+            .when(is_retryable_job_error)
+            .await?;
+        job::check_status(&created).map_err(|e| *e)?;
+        Ok(created)
+    }
+
+    /// Wait for the job completion by polling `jobs.get`.
+    /// The transient failures of the polling are retried, and the job failure is returned as `job::Error::JobFailed`.
+    /// ```rust
+    /// use std::time::Duration;
+    /// use google_cloud_bigquery::client::Client;
+    /// use google_cloud_bigquery::http::job::JobReference;
+    /// use google_cloud_bigquery::job::{Error, WaitOption};
+    ///
+    /// async fn run(client: &Client, job: &JobReference) {
+    ///     let option = WaitOption::default().with_timeout(Duration::from_secs(600));
+    ///     match client.wait_for_job(job, option).await {
+    ///         Ok(job) => println!("done {:?}", job.statistics),
+    ///         Err(Error::JobFailed { error, errors, .. }) => println!("failed {:?} {:?}", error, errors),
+    ///         Err(e) => println!("could not wait for the job {:?}", e),
+    ///     }
+    /// }
+    /// ```
+    pub async fn wait_for_job(&self, job: &JobReference, option: job::WaitOption) -> Result<Job, job::Error> {
+        let request = GetJobRequest {
+            location: job.location.clone(),
+        };
+        let action = || async {
+            tracing::debug!("waiting for job completion {:?}", job);
+            let result = self.job_client.get(&job.project_id, &job.job_id, &request).await?;
+            if result.status.state == JobState::Done {
+                Ok(result)
+            } else {
+                Err(job::Error::JobIncomplete(job.clone()))
+            }
+        };
+        let wait = action.retry(&option.retry).when(|e: &job::Error| match e {
+            job::Error::JobIncomplete(_) => true,
+            job::Error::Http(e) => is_retryable_job_error(e),
+            _ => false,
+        });
+        let result = match option.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, wait)
+                .await
+                .map_err(|_| job::Error::Timeout(job.clone()))?,
+            None => wait.await,
+        }?;
+        job::check_status(&result).map_err(|e| *e)?;
+        Ok(result)
     }
 
     /// Read table data by BigQuery Storage Read API.
@@ -478,8 +668,47 @@ impl Client {
         T: storage::value::StructDecodable,
     {
         let option = option.unwrap_or_default();
+        let mut client = self.streaming_read_client_conn_pool.conn();
+        let read_session = Self::create_read_session(&mut client, table, &option).await?;
+        storage::Iterator::new(client, read_session, option.read_rows_retry_setting).await
+    }
 
+    /// Read table data as the Arrow record batches by BigQuery Storage Read API.
+    /// The streams of the read session are read concurrently, so the order of the rows is not preserved.
+    /// ```rust
+    /// use futures_util::TryStreamExt;
+    /// use google_cloud_bigquery::client::{Client, ReadTableOption};
+    /// use google_cloud_bigquery::http::table::TableReference;
+    ///
+    /// async fn run(client: &Client, table: &TableReference) {
+    ///     let option = ReadTableOption::default().with_max_stream_count(4);
+    ///     let mut stream = client.read_table_arrow(table, Some(option)).await.unwrap();
+    ///     while let Some(batch) = stream.try_next().await.unwrap() {
+    ///         let rows = batch.num_rows();
+    ///     }
+    /// }
+    /// ```
+    pub async fn read_table_arrow(
+        &self,
+        table: &TableReference,
+        option: Option<ReadTableOption>,
+    ) -> Result<storage::RecordBatchStream, storage::Error> {
+        let option = option.unwrap_or_default();
         let mut client = self.streaming_read_client_conn_pool.conn();
+        let read_session = Self::create_read_session(&mut client, table, &option).await?;
+        storage::RecordBatchStream::new(
+            &self.streaming_read_client_conn_pool,
+            read_session,
+            option.read_rows_retry_setting,
+        )
+        .map_err(|e| *e)
+    }
+
+    async fn create_read_session(
+        client: &mut StreamingReadClient,
+        table: &TableReference,
+        option: &ReadTableOption,
+    ) -> Result<ReadSession, storage::Error> {
         let read_session = client
             .create_read_session(
                 CreateReadSessionRequest {
@@ -489,23 +718,67 @@ impl Client {
                         expire_time: None,
                         data_format: DataFormat::Arrow.into(),
                         table: table.resource(),
-                        table_modifiers: option.session_table_modifiers,
-                        read_options: option.session_read_options,
+                        table_modifiers: option.session_table_modifiers.clone(),
+                        read_options: option.session_read_options.clone(),
                         streams: vec![],
                         estimated_total_bytes_scanned: 0,
                         estimated_total_physical_file_size: 0,
                         estimated_row_count: 0,
                         trace_id: "".to_string(),
-                        schema: option.session_schema,
+                        schema: option.session_schema.clone(),
                     }),
-                    max_stream_count: 0,
+                    max_stream_count: option.max_stream_count,
                     preferred_min_stream_count: 0,
                 },
-                option.session_retry_setting,
+                option.session_retry_setting.clone(),
             )
             .await?
             .into_inner();
-        storage::Iterator::new(client, read_session, option.read_rows_retry_setting).await
+        Ok(read_session)
+    }
+
+    /// Create the writer appending the rows to the default stream of the table by the Storage Write API.
+    /// The descriptor describes the protocol buffer message of the rows matching the table schema.
+    /// ```rust
+    /// use prost_types::{field_descriptor_proto, DescriptorProto, FieldDescriptorProto};
+    /// use google_cloud_bigquery::client::Client;
+    /// use google_cloud_bigquery::http::table::TableReference;
+    ///
+    /// #[derive(Clone, PartialEq, prost::Message)]
+    /// struct Data {
+    ///     #[prost(string, tag = "1")]
+    ///     col_string: String,
+    /// }
+    ///
+    /// async fn run(client: &Client, table: &TableReference) {
+    ///     let descriptor = DescriptorProto {
+    ///         name: Some("Data".to_string()),
+    ///         field: vec![FieldDescriptorProto {
+    ///             name: Some("col_string".to_string()),
+    ///             number: Some(1),
+    ///             label: Some(field_descriptor_proto::Label::Optional.into()),
+    ///             r#type: Some(field_descriptor_proto::Type::String.into()),
+    ///             ..Default::default()
+    ///         }],
+    ///         ..Default::default()
+    ///     };
+    ///     let mut writer = client.default_storage_writer(table, descriptor, None);
+    ///     let rows = vec![Data { col_string: "test".to_string() }];
+    ///     let results = writer.append(&rows).await.unwrap();
+    /// }
+    /// ```
+    pub fn default_storage_writer(
+        &self,
+        table: &TableReference,
+        descriptor: prost_types::DescriptorProto,
+        option: Option<StorageWriterOption>,
+    ) -> StorageWriter {
+        StorageWriter::new(
+            self.streaming_write_client_conn_pool.conn(),
+            table,
+            descriptor,
+            option.unwrap_or_default(),
+        )
     }
 }
 
@@ -514,6 +787,7 @@ pub struct ReadTableOption {
     session_read_options: Option<read_session::TableReadOptions>,
     session_table_modifiers: Option<read_session::TableModifiers>,
     session_schema: Option<read_session::Schema>,
+    max_stream_count: i32,
     session_retry_setting: Option<RetrySetting>,
     read_rows_retry_setting: Option<RetrySetting>,
 }
@@ -534,6 +808,13 @@ impl ReadTableOption {
         self
     }
 
+    /// The max number of the streams read concurrently by `read_table_arrow`.
+    /// The server chooses the number if it is 0, which is the default.
+    pub fn with_max_stream_count(mut self, value: i32) -> Self {
+        self.max_stream_count = value;
+        self
+    }
+
     pub fn with_session_retry_setting(mut self, value: RetrySetting) -> Self {
         self.session_retry_setting = Some(value);
         self
@@ -548,21 +829,31 @@ impl ReadTableOption {
 #[cfg(test)]
 mod tests {
     use bigdecimal::BigDecimal;
+    use futures_util::{StreamExt, TryStreamExt};
     use serial_test::serial;
     use std::ops::AddAssign;
+    use std::str::FromStr;
     use std::time::Duration;
 
+    use time::macros::datetime;
     use time::{Date, OffsetDateTime, Time};
 
     use google_cloud_googleapis::cloud::bigquery::storage::v1::read_session::TableReadOptions;
+    use prost_types::{field_descriptor_proto, DescriptorProto, FieldDescriptorProto};
 
     use crate::client::{Client, ClientConfig, ReadTableOption};
-    use crate::http::bigquery_client::test::{create_table_schema, dataset_name, TestData};
+    use crate::http::bigquery_client::test::{bucket_name, create_table_schema, dataset_name, TestData};
     use crate::http::job::query::QueryRequest;
-    use crate::http::table::{Table, TableReference};
+    use crate::http::job::{JobState, WriteDisposition};
+    use crate::http::query::parameter::Struct;
+    use crate::http::table::{
+        DestinationFormat, SourceFormat, Table, TableFieldSchema, TableFieldType, TableReference, TableSchema,
+    };
     use crate::http::tabledata::insert_all::{InsertAllRequest, Row};
-    use crate::query;
+    use crate::job::{ExtractJob, LoadJob, WaitOption};
     use crate::query::QueryOption;
+    use crate::storage_write::AppendResult;
+    use crate::{job, query};
 
     #[ctor::ctor]
     fn init() {
@@ -882,6 +1173,31 @@ mod tests {
         assert_data(&now, data_as_row);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_read_table_arrow() {
+        let dataset = dataset_name("table");
+        let (client, project_id) = create_client().await;
+        let now = OffsetDateTime::from_unix_timestamp(OffsetDateTime::now_utc().unix_timestamp()).unwrap();
+        let table = format!("test_read_table_arrow_{}", now.unix_timestamp());
+        insert(&client, &project_id, &dataset, &table, 3, &now).await;
+
+        let table = TableReference {
+            project_id,
+            dataset_id: dataset.to_string(),
+            table_id: table.to_string(),
+        };
+        let option = ReadTableOption::default().with_max_stream_count(2);
+        let stream = client.read_table_arrow(&table, Some(option.clone())).await.unwrap();
+        let batches: Vec<_> = stream.try_collect().await.unwrap();
+        assert_eq!(batches.iter().map(|batch| batch.num_rows()).sum::<usize>(), 3);
+
+        let stream = client.read_table_arrow(&table, Some(option)).await.unwrap();
+        let mut data: Vec<TestData> = stream.into_rows().try_collect().await.unwrap();
+        data.sort_by(|a, b| a.col_string.cmp(&b.col_string));
+        assert_data(&now, data);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     #[serial]
     async fn test_query_job_incomplete_from_storage() {
@@ -924,6 +1240,279 @@ mod tests {
         assert_eq!(data.len(), SIZE);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_query_stream() {
+        let dataset = dataset_name("table");
+        let (client, project_id) = create_client().await;
+        let now = OffsetDateTime::now_utc();
+        let table = format!("test_query_stream_{}", now.unix_timestamp());
+        const SIZE: usize = 100;
+        insert(&client, &project_id, &dataset, &table, SIZE, &now).await;
+
+        let stream = client
+            .query_stream::<query::row::Row>(
+                &project_id,
+                QueryRequest {
+                    timeout_ms: Some(5),
+                    use_query_cache: Some(false),
+                    max_results: Some(30),
+                    query: format!("SELECT col_string FROM {}.{}", dataset, table),
+                    ..Default::default()
+                },
+                QueryOption::default().with_timeout(Duration::from_secs(60)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(stream.total_rows, SIZE as i64);
+        assert_eq!(stream.job_reference.project_id, project_id);
+        let fields = stream.schema.clone().unwrap().fields;
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields[0].name, "col_string");
+        let data: Vec<String> = stream
+            .map(|row| row.unwrap().column::<String>(0).unwrap())
+            .collect()
+            .await;
+        assert_eq!(data.len(), SIZE);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_query_with_parameters() {
+        let (client, project_id) = create_client().await;
+        let now = OffsetDateTime::from_unix_timestamp(OffsetDateTime::now_utc().unix_timestamp()).unwrap();
+        let numeric = BigDecimal::from_str("-1.23").unwrap();
+        let date = Date::from_calendar_date(2024, time::Month::January, 2).unwrap();
+        let item = Struct::default().field("name", &"it's").field("count", &3_i64);
+        let request = QueryRequest {
+            query: "SELECT @s, @i, @f, @n, @b, @t, @d, @y, @a, @item.name, @item.count, @null".to_string(),
+            ..Default::default()
+        }
+        .with_named_parameter("s", &"a\"b")
+        .with_named_parameter("i", &-10_i64)
+        .with_named_parameter("f", &1.5_f64)
+        .with_named_parameter("n", &numeric)
+        .with_named_parameter("b", &true)
+        .with_named_parameter("t", &now)
+        .with_named_parameter("d", &date)
+        .with_named_parameter("y", &vec![0_u8, 1, 2])
+        .with_named_parameter("a", &vec![1_i64, 2])
+        .with_named_parameter("item", &item)
+        .with_named_parameter("null", &None::<String>);
+        let mut iter = client.query::<query::row::Row>(&project_id, request).await.unwrap();
+        let row = iter.next().await.unwrap().unwrap();
+        assert_eq!(row.column::<String>(0).unwrap(), "a\"b");
+        assert_eq!(row.column::<i64>(1).unwrap(), -10);
+        assert_eq!(row.column::<f64>(2).unwrap(), 1.5);
+        assert_eq!(row.column::<BigDecimal>(3).unwrap(), numeric);
+        assert!(row.column::<bool>(4).unwrap());
+        assert_eq!(row.column::<OffsetDateTime>(5).unwrap(), now);
+        assert_eq!(row.column::<Date>(6).unwrap(), date);
+        assert_eq!(row.column::<Vec<u8>>(7).unwrap(), vec![0_u8, 1, 2]);
+        assert_eq!(row.column::<Vec<i64>>(8).unwrap(), vec![1, 2]);
+        assert_eq!(row.column::<String>(9).unwrap(), "it's");
+        assert_eq!(row.column::<i64>(10).unwrap(), 3);
+        assert_eq!(row.column::<Option<String>>(11).unwrap(), None);
+
+        let request = QueryRequest {
+            query: "SELECT ? + ?".to_string(),
+            ..Default::default()
+        }
+        .with_positional_parameter(&1_i64)
+        .with_positional_parameter(&2_i64);
+        let mut iter = client.query::<query::row::Row>(&project_id, request).await.unwrap();
+        let row = iter.next().await.unwrap().unwrap();
+        assert_eq!(row.column::<i64>(0).unwrap(), 3);
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct DecodedItem {
+        name: String,
+        count: i64,
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Decoded {
+        #[serde(rename = "str")]
+        string: String,
+        num: i64,
+        numeric: BigDecimal,
+        #[serde(with = "time::serde::rfc3339")]
+        ts: OffsetDateTime,
+        items: Vec<DecodedItem>,
+        empty: Option<String>,
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_query_decode() {
+        let (client, project_id) = create_client().await;
+        let request = QueryRequest {
+            query:
+                "SELECT 'a' AS str, 1 AS num, NUMERIC '-1.5' AS numeric, TIMESTAMP '2024-01-02 03:04:05.123456' AS ts, \
+                [STRUCT('x' AS name, 2 AS count)] AS items, CAST(NULL AS STRING) AS empty"
+                    .to_string(),
+            ..Default::default()
+        };
+        let mut iter = client.query::<query::row::Row>(&project_id, request).await.unwrap();
+        let row = iter.next().await.unwrap().unwrap();
+        let decoded: Decoded = row.decode().unwrap();
+        assert_eq!(
+            decoded,
+            Decoded {
+                string: "a".to_string(),
+                num: 1,
+                numeric: BigDecimal::from_str("-1.5").unwrap(),
+                ts: datetime!(2024-01-02 03:04:05.123456 UTC),
+                items: vec![DecodedItem {
+                    name: "x".to_string(),
+                    count: 2,
+                }],
+                empty: None,
+            }
+        );
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct WriteData {
+        #[prost(string, tag = "1")]
+        col_string: String,
+        #[prost(int64, tag = "2")]
+        col_int64: i64,
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_default_storage_writer() {
+        let dataset = dataset_name("table");
+        let (client, project_id) = create_client().await;
+        let now = OffsetDateTime::now_utc();
+        let mut table = Table::default();
+        table.table_reference.project_id = project_id.to_string();
+        table.table_reference.dataset_id = dataset.to_string();
+        table.table_reference.table_id = format!("test_default_storage_writer_{}", now.unix_timestamp());
+        table.schema = Some(TableSchema {
+            fields: vec![
+                TableFieldSchema {
+                    name: "col_string".to_string(),
+                    data_type: TableFieldType::String,
+                    ..Default::default()
+                },
+                TableFieldSchema {
+                    name: "col_int64".to_string(),
+                    data_type: TableFieldType::Int64,
+                    ..Default::default()
+                },
+            ],
+        });
+        let table = client.table_client.create(&table).await.unwrap();
+
+        let field = |name: &str, number: i32, field_type: field_descriptor_proto::Type| FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(field_descriptor_proto::Label::Optional.into()),
+            r#type: Some(field_type.into()),
+            ..Default::default()
+        };
+        let descriptor = DescriptorProto {
+            name: Some("WriteData".to_string()),
+            field: vec![
+                field("col_string", 1, field_descriptor_proto::Type::String),
+                field("col_int64", 2, field_descriptor_proto::Type::Int64),
+            ],
+            ..Default::default()
+        };
+        let mut writer = client.default_storage_writer(&table.table_reference, descriptor, None);
+        let rows: Vec<WriteData> = (0..10)
+            .map(|i| WriteData {
+                col_string: format!("test_{i}"),
+                col_int64: i,
+            })
+            .collect();
+        let results = writer.append(&rows[..5]).await.unwrap();
+        assert_eq!(
+            results,
+            vec![AppendResult {
+                rows: 0..5,
+                offset: None
+            }]
+        );
+        let results = writer.append(&rows[5..]).await.unwrap();
+        assert_eq!(results.len(), 1);
+
+        let mut iter = client
+            .query::<query::row::Row>(
+                &project_id,
+                QueryRequest {
+                    query: format!(
+                        "SELECT COUNT(*), SUM(col_int64) FROM {}.{}",
+                        dataset, table.table_reference.table_id
+                    ),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let row = iter.next().await.unwrap().unwrap();
+        assert_eq!(row.column::<i64>(0).unwrap(), 10);
+        assert_eq!(row.column::<i64>(1).unwrap(), 45);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_load_and_extract() {
+        let dataset = dataset_name("job");
+        let (client, project_id) = create_client().await;
+        let bucket = bucket_name(&project_id, "job");
+        let table = TableReference {
+            project_id: project_id.to_string(),
+            dataset_id: dataset.to_string(),
+            table_id: "external_data_load".to_string(),
+        };
+        let load = LoadJob::new(vec![format!("gs://{bucket}/external_data.csv")], table.clone())
+            .with_source_format(SourceFormat::Csv)
+            .with_field_delimiter("|")
+            .with_autodetect(true)
+            .with_write_disposition(WriteDisposition::WriteTruncate)
+            .with_job_id_prefix("test_load_")
+            .with_location("us-central1");
+        let job = client.load(&project_id, load).await.unwrap();
+        assert!(job.job_reference.job_id.starts_with("test_load_"));
+        let option = WaitOption::default().with_timeout(Duration::from_secs(300));
+        let done = client.wait_for_job(&job.job_reference, option.clone()).await.unwrap();
+        assert_eq!(done.status.state, JobState::Done);
+
+        let extract = ExtractJob::new(table, vec![format!("gs://{bucket}/extracted_data_*.json")])
+            .with_destination_format(DestinationFormat::NewlineDelimitedJson)
+            .with_location("us-central1");
+        let job = client.extract(&project_id, extract).await.unwrap();
+        let done = client.wait_for_job(&job.job_reference, option.clone()).await.unwrap();
+        assert_eq!(done.status.state, JobState::Done);
+
+        // the job fails because the source does not exist
+        let load = LoadJob::new(
+            vec![format!("gs://{bucket}/not_found.csv")],
+            TableReference {
+                project_id: project_id.to_string(),
+                dataset_id: dataset.to_string(),
+                table_id: "not_found_load".to_string(),
+            },
+        )
+        .with_autodetect(true)
+        .with_location("us-central1");
+        let result = match client.load(&project_id, load).await {
+            Ok(job) => client.wait_for_job(&job.job_reference, option).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Err(job::Error::JobFailed { error, errors, .. }) => {
+                assert_eq!(error.reason.as_deref(), Some("notFound"));
+                assert!(!errors.is_empty());
+            }
+            r => unreachable!("job failure is expected: {r:?}"),
+        }
+    }
+
     fn assert_data(now: &OffsetDateTime, data: Vec<TestData>) {
         for (i, d) in data.iter().enumerate() {
             assert_eq!(&TestData::default(i, *now + Duration::from_secs(i as u64)), d);
@@ -23,6 +23,7 @@ fn default_setting() -> RetrySetting {
     }
 }
 
+#[derive(Clone)]
 pub struct StreamingReadClient {
     inner: BigQueryReadClient<Channel>,
 }
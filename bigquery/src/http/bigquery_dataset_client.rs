@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use futures_util::{stream, Stream, TryStreamExt};
+
 use crate::http::bigquery_client::BigqueryClient;
 use crate::http::dataset;
 use crate::http::dataset::list::{DatasetOverview, ListDatasetsRequest, ListDatasetsResponse};
@@ -24,6 +26,8 @@ impl BigqueryDatasetClient {
     }
 
     /// https://cloud.google.com/bigquery/docs/reference/rest/v2/datasets/patch
+    /// The etag of the metadata is sent as `If-Match` unless it is empty,
+    /// so the patch fails with `412 Precondition Failed` if the dataset was modified after it was read.
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn patch(&self, metadata: &Dataset) -> Result<Dataset, Error> {
         let builder = dataset::patch::build(self.inner.endpoint(), self.inner.http(), metadata);
@@ -51,18 +55,38 @@ impl BigqueryDatasetClient {
         project_id: &str,
         req: Option<&ListDatasetsRequest>,
     ) -> Result<Vec<DatasetOverview>, Error> {
-        let mut page_token: Option<String> = None;
-        let mut datasets = vec![];
-        loop {
+        self.list_stream(project_id, req).try_collect().await
+    }
+
+    /// list_stream returns the datasets as a stream fetching the next page lazily.
+    /// ```rust
+    /// use futures_util::TryStreamExt;
+    /// use google_cloud_bigquery::http::bigquery_dataset_client::BigqueryDatasetClient;
+    ///
+    /// async fn run(client: BigqueryDatasetClient) {
+    ///     let mut datasets = Box::pin(client.list_stream("project", None));
+    ///     while let Some(dataset) = datasets.try_next().await.unwrap() {
+    ///         println!("{}", dataset.id);
+    ///     }
+    /// }
+    /// ```
+    pub fn list_stream<'a>(
+        &'a self,
+        project_id: &'a str,
+        req: Option<&'a ListDatasetsRequest>,
+    ) -> impl Stream<Item = Result<DatasetOverview, Error>> + 'a {
+        // None is the end of the pages
+        stream::try_unfold(Some(None), move |page_token: Option<Option<String>>| async move {
+            let page_token = match page_token {
+                Some(page_token) => page_token,
+                None => return Ok(None),
+            };
             let builder = dataset::list::build(self.inner.endpoint(), self.inner.http(), project_id, req, page_token);
             let response: ListDatasetsResponse = self.inner.send(builder).await?;
-            datasets.extend(response.datasets);
-            if response.next_page_token.is_none() {
-                break;
-            }
-            page_token = response.next_page_token;
-        }
-        Ok(datasets)
+            let datasets = stream::iter(response.datasets.into_iter().map(Ok::<_, Error>));
+            Ok::<_, Error>(Some((datasets, response.next_page_token.map(Some))))
+        })
+        .try_flatten()
     }
 }
 
@@ -71,6 +95,7 @@ mod test {
     use std::collections::HashMap;
     use std::sync::Arc;
 
+    use futures_util::TryStreamExt;
     use serial_test::serial;
 
     use crate::http::bigquery_client::test::{create_client, dataset_name};
@@ -143,6 +168,16 @@ mod test {
         // test list
         let result = client.list(project.as_str(), None).await.unwrap();
         assert!(result.len() >= 2);
+        let req = ListDatasetsRequest {
+            max_results: Some(1),
+            ..Default::default()
+        };
+        let paged: Vec<_> = client
+            .list_stream(project.as_str(), Some(&req))
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(result, paged);
         let result = client
             .list(
                 project.as_str(),
@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use futures_util::{stream, Stream, TryStreamExt};
+
 use crate::http::bigquery_client::BigqueryClient;
 use crate::http::error::Error;
 use crate::http::table;
@@ -62,6 +64,9 @@ impl BigqueryTableClient {
     }
 
     /// https://cloud.google.com/bigquery/docs/reference/rest/v2/tables/patch
+    /// The etag of the metadata is sent as `If-Match` unless it is empty,
+    /// so the patch fails with `412 Precondition Failed` if the table was modified after it was read.
+    /// Use [TableSchema::merge](crate::http::table::TableSchema::merge) to add the columns.
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn patch(&self, metadata: &Table) -> Result<Table, Error> {
         let builder = table::patch::build(self.inner.endpoint(), self.inner.http(), metadata);
@@ -143,9 +148,22 @@ impl BigqueryTableClient {
         dataset_id: &str,
         req: &ListTablesRequest,
     ) -> Result<Vec<TableOverview>, Error> {
-        let mut page_token: Option<String> = None;
-        let mut tables = vec![];
-        loop {
+        self.list_stream(project_id, dataset_id, req).try_collect().await
+    }
+
+    /// list_stream returns the tables as a stream fetching the next page lazily.
+    pub fn list_stream<'a>(
+        &'a self,
+        project_id: &'a str,
+        dataset_id: &'a str,
+        req: &'a ListTablesRequest,
+    ) -> impl Stream<Item = Result<TableOverview, Error>> + 'a {
+        // None is the end of the pages
+        stream::try_unfold(Some(None), move |page_token: Option<Option<String>>| async move {
+            let page_token = match page_token {
+                Some(page_token) => page_token,
+                None => return Ok(None),
+            };
             let builder = table::list::build(
                 self.inner.endpoint(),
                 self.inner.http(),
@@ -155,13 +173,10 @@ impl BigqueryTableClient {
                 page_token,
             );
             let response: ListTablesResponse = self.inner.send(builder).await?;
-            tables.extend(response.tables);
-            if response.next_page_token.is_none() {
-                break;
-            }
-            page_token = response.next_page_token;
-        }
-        Ok(tables)
+            let tables = stream::iter(response.tables.into_iter().map(Ok::<_, Error>));
+            Ok::<_, Error>(Some((tables, response.next_page_token.map(Some))))
+        })
+        .try_flatten()
     }
 }
 
@@ -4,6 +4,7 @@ use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
 
 use crate::http::dataset::DatasetReference;
 use crate::http::job::{DmlStats, JobReference, SessionInfo};
+use crate::http::query::parameter::Encodable;
 use crate::http::table::TableSchema;
 use crate::http::tabledata::list::Tuple;
 use crate::http::types::{ConnectionProperty, DataFormatOptions, ErrorProto, QueryParameter};
@@ -152,7 +153,45 @@ pub struct QueryResponse {
     pub dml_stats: Option<DmlStats>,
 }
 
+impl QueryRequest {
+    /// with_named_parameter adds the parameter referred as `@name` in the query.
+    /// The query must use GoogleSQL to use the parameters.
+    /// ```
+    /// use google_cloud_bigquery::http::job::query::QueryRequest;
+    ///
+    /// let request = QueryRequest {
+    ///     query: "SELECT * FROM dataset.table WHERE name = @name AND count IN UNNEST(@counts)".to_string(),
+    ///     ..Default::default()
+    /// }
+    /// .with_named_parameter("name", &"wrench")
+    /// .with_named_parameter("counts", &vec![1_i64, 2, 3]);
+    /// ```
+    pub fn with_named_parameter(mut self, name: &str, value: &impl Encodable) -> Self {
+        self.use_legacy_sql = false;
+        self.parameter_mode = Some("NAMED".to_string());
+        self.query_parameters.push(QueryParameter::named(name, value));
+        self
+    }
+
+    /// with_positional_parameter adds the parameter referred as `?` in the query.
+    /// The query must use GoogleSQL to use the parameters.
+    pub fn with_positional_parameter(mut self, value: &impl Encodable) -> Self {
+        self.use_legacy_sql = false;
+        self.parameter_mode = Some("POSITIONAL".to_string());
+        self.query_parameters.push(QueryParameter::positional(value));
+        self
+    }
+}
+
 pub fn build(base_url: &str, client: &Client, project_id: &str, data: &QueryRequest) -> RequestBuilder {
     let url = format!("{}/projects/{}/queries", base_url, project_id);
+    // The parameters are not supported by legacy SQL.
+    if data.use_legacy_sql && !data.query_parameters.is_empty() {
+        let data = QueryRequest {
+            use_legacy_sql: false,
+            ..data.clone()
+        };
+        return client.post(url).json(&data);
+    }
     client.post(url).json(data)
 }
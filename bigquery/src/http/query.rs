@@ -1,10 +1,12 @@
 use std::collections::VecDeque;
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 use crate::http::bigquery_job_client::BigqueryJobClient;
 use crate::http::error::Error as HttpError;
 use crate::http::job::get_query_results::GetQueryResultsRequest;
 use crate::http::query::value::StructDecodable;
+use crate::http::table::TableSchema;
 use crate::http::tabledata::list::Tuple;
 
 #[derive(thiserror::Error, Debug)]
@@ -22,6 +24,7 @@ pub struct Iterator<T: StructDecodable> {
     pub(crate) request: GetQueryResultsRequest,
     pub(crate) chunk: VecDeque<Tuple>,
     pub(crate) force_first_fetch: bool,
+    pub(crate) schema: Option<Arc<TableSchema>>,
     pub total_size: i64,
     pub(crate) _marker: PhantomData<T>,
 }
@@ -30,7 +33,11 @@ impl<T: StructDecodable> Iterator<T> {
     pub async fn next(&mut self) -> Result<Option<T>, Error> {
         loop {
             if let Some(v) = self.chunk.pop_front() {
-                return Ok(T::decode(v).map(Some)?);
+                let row = match &self.schema {
+                    Some(schema) => T::decode_with_schema(v, schema),
+                    None => T::decode(v),
+                };
+                return Ok(row.map(Some)?);
             }
             if self.force_first_fetch {
                 self.force_first_fetch = false
@@ -52,7 +59,10 @@ impl<T: StructDecodable> Iterator<T> {
 }
 
 pub mod row {
+    use std::sync::Arc;
+
     use crate::http::query::value::StructDecodable;
+    use crate::http::table::TableSchema;
     use crate::http::tabledata::list::{Cell, Tuple};
 
     #[derive(thiserror::Error, Debug)]
@@ -61,10 +71,15 @@ pub mod row {
         UnexpectedColumnIndex(usize),
         #[error(transparent)]
         Value(#[from] super::value::Error),
+        #[error("no schema found to decode the row by the column names")]
+        NoSchemaFound,
+        #[error(transparent)]
+        Decode(#[from] super::de::Error),
     }
 
     pub struct Row {
         inner: Vec<Cell>,
+        schema: Option<Arc<TableSchema>>,
     }
 
     impl Row {
@@ -72,11 +87,31 @@ pub mod row {
             let cell: &Cell = self.inner.get(index).ok_or(Error::UnexpectedColumnIndex(index))?;
             Ok(T::decode(&cell.v)?)
         }
+
+        /// decode decodes the row into the type implementing `serde::Deserialize` by the column names.
+        /// See `crate::http::query::de` for the conversion of the values.
+        pub fn decode<'a, T: serde::Deserialize<'a>>(&'a self) -> Result<T, Error> {
+            let schema = self.schema.as_ref().ok_or(Error::NoSchemaFound)?;
+            Ok(super::de::from_cells(&schema.fields, &self.inner)?)
+        }
     }
 
     impl StructDecodable for Row {
         fn decode(value: Tuple) -> Result<Self, crate::http::query::value::Error> {
-            Ok(Self { inner: value.f })
+            Ok(Self {
+                inner: value.f,
+                schema: None,
+            })
+        }
+
+        fn decode_with_schema(
+            value: Tuple,
+            schema: &Arc<TableSchema>,
+        ) -> Result<Self, crate::http::query::value::Error> {
+            Ok(Self {
+                inner: value.f,
+                schema: Some(schema.clone()),
+            })
         }
     }
 }
@@ -85,17 +120,25 @@ pub mod value {
     use std::num::ParseIntError;
     use std::ops::AddAssign;
     use std::str::FromStr;
+    use std::sync::Arc;
     use std::time::Duration;
 
     use base64::prelude::BASE64_STANDARD;
     use base64::Engine;
     use bigdecimal::BigDecimal;
     use time::error::ComponentRange;
+    use time::format_description::FormatItem;
     use time::macros::format_description;
-    use time::{Date, OffsetDateTime, Time};
+    use time::{Date, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
 
+    use crate::http::table::TableSchema;
     use crate::http::tabledata::list::{Tuple, Value};
 
+    const DATE_FORMAT: &[FormatItem<'static>] = format_description!("[year]-[month]-[day]");
+    const TIME_FORMAT: &[FormatItem<'static>] = format_description!("[hour]:[minute]:[second].[subsecond digits:6]");
+    const TIMESTAMP_FORMAT: &[FormatItem<'static>] =
+        format_description!("[year]-[month]-[day] [hour]:[minute]:[second].[subsecond digits:6]");
+
     #[derive(thiserror::Error, Debug)]
     pub enum Error {
         #[error("invalid type")]
@@ -122,6 +165,29 @@ pub mod value {
 
     pub trait StructDecodable: Sized {
         fn decode(value: Tuple) -> Result<Self, Error>;
+
+        /// decode_with_schema is used when the schema of the rows is known.
+        /// The rows keeping the schema can be decoded by the column names.
+        fn decode_with_schema(value: Tuple, _schema: &Arc<TableSchema>) -> Result<Self, Error> {
+            Self::decode(value)
+        }
+    }
+
+    /// Scalar is the conversion between the value and the string representation of the GoogleSQL scalar type.
+    /// It is shared by the decoding of the rows and the encoding of the query parameters.
+    pub trait Scalar: Sized {
+        /// The name of the GoogleSQL type like `INT64`.
+        const TYPE: &'static str;
+        fn to_value(&self) -> String;
+        fn from_value(value: &str) -> Result<Self, Error>;
+    }
+
+    fn decode_scalar<T: Scalar>(value: &Value) -> Result<T, Error> {
+        match value {
+            Value::String(v) => T::from_value(v),
+            Value::Null => Err(Error::UnexpectedNullValue),
+            _ => Err(Error::InvalidType),
+        }
     }
 
     impl<T: StructDecodable> Decodable for T {
@@ -134,104 +200,175 @@ pub mod value {
         }
     }
 
+    impl Scalar for String {
+        const TYPE: &'static str = "STRING";
+        fn to_value(&self) -> String {
+            self.clone()
+        }
+        fn from_value(value: &str) -> Result<Self, Error> {
+            Ok(value.to_string())
+        }
+    }
+
+    impl Scalar for Vec<u8> {
+        const TYPE: &'static str = "BYTES";
+        fn to_value(&self) -> String {
+            BASE64_STANDARD.encode(self)
+        }
+        fn from_value(value: &str) -> Result<Self, Error> {
+            Ok(BASE64_STANDARD.decode(value)?)
+        }
+    }
+
+    impl Scalar for bool {
+        const TYPE: &'static str = "BOOL";
+        fn to_value(&self) -> String {
+            self.to_string()
+        }
+        fn from_value(value: &str) -> Result<Self, Error> {
+            value.parse::<bool>().map_err(|_| Error::FromString(value.to_string()))
+        }
+    }
+
+    impl Scalar for f64 {
+        const TYPE: &'static str = "FLOAT64";
+        fn to_value(&self) -> String {
+            if self.is_nan() {
+                "NaN".to_string()
+            } else if self.is_infinite() {
+                let sign = if self.is_sign_positive() { "" } else { "-" };
+                format!("{sign}Infinity")
+            } else {
+                self.to_string()
+            }
+        }
+        fn from_value(value: &str) -> Result<Self, Error> {
+            value.parse::<f64>().map_err(|_| Error::FromString(value.to_string()))
+        }
+    }
+
+    impl Scalar for i64 {
+        const TYPE: &'static str = "INT64";
+        fn to_value(&self) -> String {
+            self.to_string()
+        }
+        fn from_value(value: &str) -> Result<Self, Error> {
+            value.parse::<i64>().map_err(|_| Error::FromString(value.to_string()))
+        }
+    }
+
+    impl Scalar for BigDecimal {
+        const TYPE: &'static str = "NUMERIC";
+        fn to_value(&self) -> String {
+            self.to_string()
+        }
+        fn from_value(value: &str) -> Result<Self, Error> {
+            Ok(BigDecimal::from_str(value)?)
+        }
+    }
+
+    impl Scalar for OffsetDateTime {
+        const TYPE: &'static str = "TIMESTAMP";
+        fn to_value(&self) -> String {
+            let utc = self.to_offset(UtcOffset::UTC);
+            // the format description is valid for any date time
+            format!("{}+00:00", utc.format(TIMESTAMP_FORMAT).unwrap_or_default())
+        }
+        fn from_value(value: &str) -> Result<Self, Error> {
+            // The rows have the timestamp as the seconds since the epoch.
+            if let Ok(f) = value.parse::<f64>() {
+                let sec = f.trunc();
+                // Timestamps in BigQuery have microsecond precision, so we must
+                // return a round number of microseconds.
+                let micro = ((f - sec) * 1000000.0 + 0.5).trunc();
+                return Ok(OffsetDateTime::from_unix_timestamp_nanos(
+                    sec as i128 * 1_000_000_000 + micro as i128 * 1000,
+                )?);
+            }
+            let value = value.strip_suffix("+00:00").unwrap_or(value);
+            Ok(PrimitiveDateTime::parse(value, TIMESTAMP_FORMAT)?.assume_utc())
+        }
+    }
+
+    impl Scalar for Date {
+        const TYPE: &'static str = "DATE";
+        fn to_value(&self) -> String {
+            // the format description is valid for any date
+            self.format(DATE_FORMAT).unwrap_or_default()
+        }
+        fn from_value(value: &str) -> Result<Self, Error> {
+            Ok(Date::parse(value, DATE_FORMAT)?)
+        }
+    }
+
+    impl Scalar for Time {
+        const TYPE: &'static str = "TIME";
+        fn to_value(&self) -> String {
+            // the format description is valid for any time
+            self.format(TIME_FORMAT).unwrap_or_default()
+        }
+        fn from_value(value: &str) -> Result<Self, Error> {
+            let split: Vec<&str> = value.split('.').collect();
+            let mut time = Time::parse(split[0], format_description!("[hour]:[minute]:[second]"))?;
+            if split.len() > 1 {
+                let micro: u64 = split[1].parse()?;
+                time.add_assign(Duration::from_micros(micro))
+            }
+            Ok(time)
+        }
+    }
+
     impl Decodable for String {
         fn decode(value: &Value) -> Result<Self, Error> {
-            match value {
-                Value::String(v) => Ok(v.to_string()),
-                Value::Null => Err(Error::UnexpectedNullValue),
-                _ => Err(Error::InvalidType),
-            }
+            decode_scalar(value)
         }
     }
 
     impl Decodable for Vec<u8> {
         fn decode(value: &Value) -> Result<Self, Error> {
-            match value {
-                Value::String(v) => Ok(BASE64_STANDARD.decode(v)?),
-                Value::Null => Err(Error::UnexpectedNullValue),
-                _ => Err(Error::InvalidType),
-            }
+            decode_scalar(value)
         }
     }
 
     impl Decodable for bool {
         fn decode(value: &Value) -> Result<Self, Error> {
-            match value {
-                Value::String(v) => Ok(v.parse::<bool>().map_err(|_| Error::FromString(v.clone()))?),
-                Value::Null => Err(Error::UnexpectedNullValue),
-                _ => Err(Error::InvalidType),
-            }
+            decode_scalar(value)
         }
     }
 
     impl Decodable for f64 {
         fn decode(value: &Value) -> Result<Self, Error> {
-            match value {
-                Value::String(v) => Ok(v.parse::<f64>().map_err(|_| Error::FromString(v.clone()))?),
-                Value::Null => Err(Error::UnexpectedNullValue),
-                _ => Err(Error::InvalidType),
-            }
+            decode_scalar(value)
         }
     }
 
     impl Decodable for i64 {
         fn decode(value: &Value) -> Result<Self, Error> {
-            match value {
-                Value::String(v) => Ok(v.parse::<i64>().map_err(|_| Error::FromString(v.clone()))?),
-                Value::Null => Err(Error::UnexpectedNullValue),
-                _ => Err(Error::InvalidType),
-            }
+            decode_scalar(value)
         }
     }
 
     impl Decodable for BigDecimal {
         fn decode(value: &Value) -> Result<Self, Error> {
-            match value {
-                Value::String(v) => Ok(BigDecimal::from_str(v)?),
-                Value::Null => Err(Error::UnexpectedNullValue),
-                _ => Err(Error::InvalidType),
-            }
+            decode_scalar(value)
         }
     }
 
     impl Decodable for OffsetDateTime {
         fn decode(value: &Value) -> Result<Self, Error> {
-            let f = f64::decode(value)?;
-            let sec = f.trunc();
-            // Timestamps in BigQuery have microsecond precision, so we must
-            // return a round number of microseconds.
-            let micro = ((f - sec) * 1000000.0 + 0.5).trunc();
-            Ok(OffsetDateTime::from_unix_timestamp_nanos(
-                sec as i128 * 1_000_000_000 + micro as i128 * 1000,
-            )?)
+            decode_scalar(value)
         }
     }
 
     impl Decodable for Date {
         fn decode(value: &Value) -> Result<Self, Error> {
-            match value {
-                Value::String(v) => Ok(Date::parse(v, format_description!("[year]-[month]-[day]"))?),
-                Value::Null => Err(Error::UnexpectedNullValue),
-                _ => Err(Error::InvalidType),
-            }
+            decode_scalar(value)
         }
     }
 
     impl Decodable for Time {
         fn decode(value: &Value) -> Result<Self, Error> {
-            match value {
-                Value::String(v) => {
-                    let split: Vec<&str> = v.split('.').collect();
-                    let mut time = Time::parse(split[0], format_description!("[hour]:[minute]:[second]"))?;
-                    if split.len() > 1 {
-                        let micro: u64 = split[1].parse()?;
-                        time.add_assign(Duration::from_micros(micro))
-                    }
-                    Ok(time)
-                }
-                Value::Null => Err(Error::UnexpectedNullValue),
-                _ => Err(Error::InvalidType),
-            }
+            decode_scalar(value)
         }
     }
 
@@ -266,3 +403,715 @@ pub mod value {
         }
     }
 }
+
+pub mod parameter {
+    use std::collections::HashMap;
+
+    use bigdecimal::BigDecimal;
+    use time::{Date, OffsetDateTime, Time};
+
+    use crate::http::query::value::Scalar;
+    use crate::http::types::{QueryParameter, QueryParameterStructType, QueryParameterType, QueryParameterValue};
+
+    /// Encodable is the value of the query parameter.
+    /// The scalar values are converted by `Scalar` same as the decoding of the rows.
+    pub trait Encodable {
+        fn parameter_type(&self) -> QueryParameterType;
+        fn parameter_value(&self) -> QueryParameterValue;
+    }
+
+    fn scalar_type(name: &str) -> QueryParameterType {
+        QueryParameterType {
+            parameter_type: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn scalar_value(value: Option<String>) -> QueryParameterValue {
+        QueryParameterValue {
+            value,
+            ..Default::default()
+        }
+    }
+
+    macro_rules! impl_scalar_encodable {
+        ($($t:ty),*) => {
+            $(
+                impl Encodable for $t {
+                    fn parameter_type(&self) -> QueryParameterType {
+                        scalar_type(<$t as Scalar>::TYPE)
+                    }
+                    fn parameter_value(&self) -> QueryParameterValue {
+                        scalar_value(Some(self.to_value()))
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_scalar_encodable!(String, Vec<u8>, bool, f64, i64, BigDecimal, OffsetDateTime, Date, Time);
+
+    impl Encodable for &str {
+        fn parameter_type(&self) -> QueryParameterType {
+            scalar_type(String::TYPE)
+        }
+        fn parameter_value(&self) -> QueryParameterValue {
+            scalar_value(Some(self.to_string()))
+        }
+    }
+
+    /// None is the NULL value of the type.
+    impl<T: Scalar> Encodable for Option<T> {
+        fn parameter_type(&self) -> QueryParameterType {
+            scalar_type(T::TYPE)
+        }
+        fn parameter_value(&self) -> QueryParameterValue {
+            scalar_value(self.as_ref().map(|v| v.to_value()))
+        }
+    }
+
+    impl<T: Scalar> Encodable for Vec<T> {
+        fn parameter_type(&self) -> QueryParameterType {
+            QueryParameterType {
+                parameter_type: "ARRAY".to_string(),
+                array_type: Some(Box::new(scalar_type(T::TYPE))),
+                struct_types: None,
+            }
+        }
+        fn parameter_value(&self) -> QueryParameterValue {
+            QueryParameterValue {
+                array_values: Some(self.iter().map(|v| scalar_value(Some(v.to_value()))).collect()),
+                ..Default::default()
+            }
+        }
+    }
+
+    /// Struct is the STRUCT value of the query parameter.
+    /// ```
+    /// use google_cloud_bigquery::http::query::parameter::Struct;
+    ///
+    /// let value = Struct::default().field("name", &"wrench").field("count", &3_i64);
+    /// ```
+    #[derive(Clone, PartialEq, Debug, Default)]
+    pub struct Struct {
+        fields: Vec<(String, QueryParameterType, QueryParameterValue)>,
+    }
+
+    impl Struct {
+        pub fn field(mut self, name: &str, value: &impl Encodable) -> Self {
+            self.fields
+                .push((name.to_string(), value.parameter_type(), value.parameter_value()));
+            self
+        }
+    }
+
+    impl Encodable for Struct {
+        fn parameter_type(&self) -> QueryParameterType {
+            QueryParameterType {
+                parameter_type: "STRUCT".to_string(),
+                array_type: None,
+                struct_types: Some(
+                    self.fields
+                        .iter()
+                        .map(|(name, field_type, _)| QueryParameterStructType {
+                            name: Some(name.clone()),
+                            field_type: field_type.clone(),
+                            description: None,
+                        })
+                        .collect(),
+                ),
+            }
+        }
+        fn parameter_value(&self) -> QueryParameterValue {
+            QueryParameterValue {
+                struct_values: Some(
+                    self.fields
+                        .iter()
+                        .map(|(name, _, value)| (name.clone(), value.clone()))
+                        .collect::<HashMap<_, _>>(),
+                ),
+                ..Default::default()
+            }
+        }
+    }
+
+    /// The elements must have the same fields. The type of the empty array is the struct without fields.
+    impl Encodable for Vec<Struct> {
+        fn parameter_type(&self) -> QueryParameterType {
+            QueryParameterType {
+                parameter_type: "ARRAY".to_string(),
+                array_type: Some(Box::new(self.first().cloned().unwrap_or_default().parameter_type())),
+                struct_types: None,
+            }
+        }
+        fn parameter_value(&self) -> QueryParameterValue {
+            QueryParameterValue {
+                array_values: Some(self.iter().map(|v| v.parameter_value()).collect()),
+                ..Default::default()
+            }
+        }
+    }
+
+    impl QueryParameter {
+        /// named creates the parameter referred as `@name` in the query.
+        pub fn named(name: &str, value: &impl Encodable) -> Self {
+            Self {
+                name: Some(name.to_string()),
+                parameter_type: value.parameter_type(),
+                parameter_value: value.parameter_value(),
+            }
+        }
+
+        /// positional creates the parameter referred as `?` in the query.
+        pub fn positional(value: &impl Encodable) -> Self {
+            Self {
+                name: None,
+                parameter_type: value.parameter_type(),
+                parameter_value: value.parameter_value(),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bigdecimal::BigDecimal;
+        use serde_json::json;
+        use std::str::FromStr;
+        use time::macros::{date, datetime, time};
+        use time::{Date, OffsetDateTime, Time};
+
+        use crate::http::query::parameter::Struct;
+        use crate::http::query::value::Scalar;
+        use crate::http::types::QueryParameter;
+
+        #[test]
+        fn test_scalar_round_trip() {
+            fn round_trip<T: Scalar + PartialEq + std::fmt::Debug>(value: T, expected: &str) {
+                assert_eq!(value.to_value(), expected);
+                assert_eq!(T::from_value(expected).unwrap(), value);
+            }
+            round_trip("a'b".to_string(), "a'b");
+            round_trip(vec![0_u8, 1, 2], "AAEC");
+            round_trip(true, "true");
+            round_trip(1.5_f64, "1.5");
+            round_trip(f64::NEG_INFINITY, "-Infinity");
+            round_trip(-10_i64, "-10");
+            round_trip(BigDecimal::from_str("123.456").unwrap(), "123.456");
+            round_trip(datetime!(2024-01-02 03:04:05.123456 +09:00), "2024-01-01 18:04:05.123456+00:00");
+            round_trip(date!(2024 - 01 - 02), "2024-01-02");
+            round_trip(time!(03:04:05.5), "03:04:05.500000");
+            assert_eq!(
+                OffsetDateTime::from_value("1704164645.123456").unwrap(),
+                datetime!(2024-01-02 03:04:05.123456 UTC)
+            );
+        }
+
+        #[test]
+        fn test_parameter_json() {
+            let param = QueryParameter::named("count", &10_i64);
+            assert_eq!(
+                serde_json::to_value(&param).unwrap(),
+                json!({
+                    "name": "count",
+                    "parameterType": {"type": "INT64", "arrayType": null, "structTypes": null},
+                    "parameterValue": {"value": "10", "arrayValues": null, "structValues": null}
+                })
+            );
+
+            let param = QueryParameter::positional(&None::<Date>);
+            assert_eq!(param.parameter_type.parameter_type, "DATE");
+            assert_eq!(param.parameter_value.value, None);
+
+            let param = QueryParameter::positional(&vec![Time::MIDNIGHT]);
+            assert_eq!(param.parameter_type.parameter_type, "ARRAY");
+            assert_eq!(param.parameter_type.array_type.unwrap().parameter_type, "TIME");
+            let values = param.parameter_value.array_values.unwrap();
+            assert_eq!(values[0].value.as_deref(), Some("00:00:00.000000"));
+
+            let value = Struct::default()
+                .field("name", &"wrench")
+                .field("tags", &vec!["a".to_string()]);
+            let param = QueryParameter::named("item", &vec![value]);
+            let item_type = param.parameter_type.array_type.unwrap();
+            assert_eq!(item_type.parameter_type, "STRUCT");
+            let fields = item_type.struct_types.unwrap();
+            assert_eq!(fields[0].name.as_deref(), Some("name"));
+            assert_eq!(fields[0].field_type.parameter_type, "STRING");
+            assert_eq!(fields[1].field_type.parameter_type, "ARRAY");
+            let values = param.parameter_value.array_values.unwrap();
+            let item = values[0].struct_values.as_ref().unwrap();
+            assert_eq!(item["name"].value.as_deref(), Some("wrench"));
+            assert_eq!(item["tags"].array_values.as_ref().unwrap()[0].value.as_deref(), Some("a"));
+        }
+    }
+}
+
+/// de decodes the rows into the types implementing `serde::Deserialize` by the names of the columns.
+/// The values are converted by the types of the columns in the schema.
+/// * INT64 to i64, FLOAT64 to f64 and BOOL to bool
+/// * BYTES to the bytes decoded from base64, `Vec<u8>` is supported
+/// * TIMESTAMP to the RFC 3339 string, use `time::serde::rfc3339` for `OffsetDateTime`
+/// * RECORD to the struct and REPEATED to the sequence
+/// * the other types like NUMERIC, DATE and JSON to the string
+pub mod de {
+    use std::fmt::Display;
+    use std::iter::{Enumerate, Zip};
+    use std::slice::Iter;
+
+    use serde::de::value::{BorrowedStrDeserializer, SeqDeserializer};
+    use serde::de::{Deserialize, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+    use serde::forward_to_deserialize_any;
+    use time::format_description::well_known::Rfc3339;
+    use time::OffsetDateTime;
+
+    use crate::http::query::value::Scalar;
+    use crate::http::table::{TableFieldMode, TableFieldSchema, TableFieldType};
+    use crate::http::tabledata::list::{Cell, Value};
+
+    #[derive(thiserror::Error, Debug)]
+    pub enum Error {
+        /// The column is the path of the column like `col_struct.f1` or `col_array[1]`.
+        #[error("failed to decode column {column}: {message}: value={value}")]
+        Column {
+            column: String,
+            value: String,
+            message: String,
+        },
+        #[error("{0}")]
+        Custom(String),
+    }
+
+    impl serde::de::Error for Error {
+        fn custom<T: Display>(msg: T) -> Self {
+            Error::Custom(msg.to_string())
+        }
+    }
+
+    impl Error {
+        fn in_column(self, column: &str, value: &Value) -> Self {
+            match self {
+                Error::Custom(message) => Error::Column {
+                    column: column.to_string(),
+                    value: raw(value),
+                    message,
+                },
+                Error::Column {
+                    column: inner,
+                    value,
+                    message,
+                } => Error::Column {
+                    column: if inner.starts_with('[') {
+                        format!("{column}{inner}")
+                    } else {
+                        format!("{column}.{inner}")
+                    },
+                    value,
+                    message,
+                },
+            }
+        }
+    }
+
+    fn raw(value: &Value) -> String {
+        match value {
+            Value::String(v) => v.to_string(),
+            Value::Null => "null".to_string(),
+            _ => serde_json::to_string(value).unwrap_or_default(),
+        }
+    }
+
+    /// from_cells decodes the cells of the row by the fields of the schema.
+    /// The struct is decoded by the names of the columns and the tuple is decoded by the positions of the columns.
+    pub fn from_cells<'a, T>(fields: &'a [TableFieldSchema], cells: &'a [Cell]) -> Result<T, Error>
+    where
+        T: Deserialize<'a>,
+    {
+        T::deserialize(RowDeserializer { fields, cells })
+    }
+
+    struct RowDeserializer<'a> {
+        fields: &'a [TableFieldSchema],
+        cells: &'a [Cell],
+    }
+
+    impl<'de> serde::Deserializer<'de> for RowDeserializer<'de> {
+        type Error = Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_map(StructAccess {
+                columns: self.fields.iter().zip(self.cells.iter()),
+                value: None,
+            })
+        }
+
+        fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_seq(TupleAccess {
+                columns: self.fields.iter().zip(self.cells.iter()),
+            })
+        }
+
+        fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+            self.deserialize_seq(visitor)
+        }
+
+        fn deserialize_tuple_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            _len: usize,
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            self.deserialize_seq(visitor)
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct map struct enum identifier ignored_any
+        }
+    }
+
+    struct StructAccess<'a> {
+        columns: Zip<Iter<'a, TableFieldSchema>, Iter<'a, Cell>>,
+        value: Option<(&'a TableFieldSchema, &'a Value)>,
+    }
+
+    impl<'de> MapAccess<'de> for StructAccess<'de> {
+        type Error = Error;
+
+        fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+            match self.columns.next() {
+                Some((field, cell)) => {
+                    self.value = Some((field, &cell.v));
+                    seed.deserialize(BorrowedStrDeserializer::new(&field.name)).map(Some)
+                }
+                None => Ok(None),
+            }
+        }
+
+        fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+            let (field, value) = self
+                .value
+                .take()
+                .ok_or_else(|| Error::Custom("value is read before the key".to_string()))?;
+            seed.deserialize(ValueDeserializer::new(field, value))
+                .map_err(|e| e.in_column(&field.name, value))
+        }
+    }
+
+    struct TupleAccess<'a> {
+        columns: Zip<Iter<'a, TableFieldSchema>, Iter<'a, Cell>>,
+    }
+
+    impl<'de> SeqAccess<'de> for TupleAccess<'de> {
+        type Error = Error;
+
+        fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+            match self.columns.next() {
+                Some((field, cell)) => seed
+                    .deserialize(ValueDeserializer::new(field, &cell.v))
+                    .map(Some)
+                    .map_err(|e| e.in_column(&field.name, &cell.v)),
+                None => Ok(None),
+            }
+        }
+    }
+
+    struct ArrayAccess<'a> {
+        field: &'a TableFieldSchema,
+        elements: Enumerate<Iter<'a, Cell>>,
+    }
+
+    impl<'de> SeqAccess<'de> for ArrayAccess<'de> {
+        type Error = Error;
+
+        fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+            match self.elements.next() {
+                Some((index, cell)) => {
+                    let element = ValueDeserializer {
+                        field: self.field,
+                        value: &cell.v,
+                        repeated: false,
+                    };
+                    seed.deserialize(element)
+                        .map(Some)
+                        .map_err(|e| e.in_column(&format!("[{index}]"), &cell.v))
+                }
+                None => Ok(None),
+            }
+        }
+    }
+
+    struct ValueDeserializer<'a> {
+        field: &'a TableFieldSchema,
+        value: &'a Value,
+        /// The elements of the REPEATED column have the schema of the column.
+        repeated: bool,
+    }
+
+    impl<'a> ValueDeserializer<'a> {
+        fn new(field: &'a TableFieldSchema, value: &'a Value) -> Self {
+            Self {
+                field,
+                value,
+                repeated: field.mode == Some(TableFieldMode::Repeated),
+            }
+        }
+
+        fn is_bytes(&self) -> bool {
+            !self.repeated && self.field.data_type == TableFieldType::Bytes
+        }
+    }
+
+    fn scalar<T: Scalar>(value: &str) -> Result<T, Error> {
+        T::from_value(value).map_err(|e| Error::Custom(e.to_string()))
+    }
+
+    impl<'de> serde::Deserializer<'de> for ValueDeserializer<'de> {
+        type Error = Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.value {
+                Value::Null => visitor.visit_unit(),
+                Value::Array(elements) if self.repeated => visitor.visit_seq(ArrayAccess {
+                    field: self.field,
+                    elements: elements.iter().enumerate(),
+                }),
+                Value::Struct(tuple) if !self.repeated => match &self.field.fields {
+                    Some(fields) => visitor.visit_map(StructAccess {
+                        columns: fields.iter().zip(tuple.f.iter()),
+                        value: None,
+                    }),
+                    None => Err(Error::Custom("no fields found in the schema of the record".to_string())),
+                },
+                Value::String(v) if !self.repeated => match self.field.data_type {
+                    TableFieldType::Integer | TableFieldType::Int64 => visitor.visit_i64(scalar(v)?),
+                    TableFieldType::Float | TableFieldType::Float64 => visitor.visit_f64(scalar(v)?),
+                    TableFieldType::Boolean | TableFieldType::Bool => visitor.visit_bool(scalar(v)?),
+                    TableFieldType::Bytes => visitor.visit_byte_buf(scalar(v)?),
+                    TableFieldType::Timestamp => {
+                        let timestamp: OffsetDateTime = scalar(v)?;
+                        let timestamp = timestamp.format(&Rfc3339).map_err(|e| Error::Custom(e.to_string()))?;
+                        visitor.visit_string(timestamp)
+                    }
+                    _ => visitor.visit_borrowed_str(v),
+                },
+                _ => Err(Error::Custom(format!(
+                    "the value does not match the column type {:?}",
+                    self.field.data_type
+                ))),
+            }
+        }
+
+        fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.value {
+                Value::Null => visitor.visit_none(),
+                _ => visitor.visit_some(self),
+            }
+        }
+
+        fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self.value {
+                // `Vec<u8>` is decoded as the sequence.
+                Value::String(v) if self.is_bytes() => {
+                    let bytes: Vec<u8> = scalar(v)?;
+                    visitor.visit_seq(SeqDeserializer::new(bytes.into_iter()))
+                }
+                _ => self.deserialize_any(visitor),
+            }
+        }
+
+        fn deserialize_newtype_struct<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            visitor.visit_newtype_struct(self)
+        }
+
+        fn deserialize_enum<V: Visitor<'de>>(
+            self,
+            _name: &'static str,
+            _variants: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Error> {
+            match self.value {
+                Value::String(v) => visitor.visit_enum(BorrowedStrDeserializer::new(v)),
+                _ => self.deserialize_any(visitor),
+            }
+        }
+
+        fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            visitor.visit_unit()
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use bigdecimal::BigDecimal;
+        use serde_json::json;
+        use std::str::FromStr;
+        use time::macros::datetime;
+        use time::OffsetDateTime;
+
+        use crate::http::query::de::{from_cells, Error};
+        use crate::http::table::{TableFieldMode, TableFieldSchema, TableFieldType};
+        use crate::http::tabledata::list::Tuple;
+
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Nested {
+            name: String,
+            count: Option<i64>,
+        }
+
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Data {
+            #[serde(rename = "col_string")]
+            string: String,
+            col_int: i32,
+            col_float: f64,
+            col_bool: bool,
+            col_bytes: Vec<u8>,
+            #[serde(with = "time::serde::rfc3339")]
+            col_timestamp: OffsetDateTime,
+            col_numeric: BigDecimal,
+            col_null: Option<String>,
+            col_repeated: Vec<i64>,
+            col_record: Nested,
+            col_records: Vec<Nested>,
+        }
+
+        fn field(name: &str, data_type: TableFieldType) -> TableFieldSchema {
+            TableFieldSchema {
+                name: name.to_string(),
+                data_type,
+                ..Default::default()
+            }
+        }
+
+        fn repeated(mut field: TableFieldSchema) -> TableFieldSchema {
+            field.mode = Some(TableFieldMode::Repeated);
+            field
+        }
+
+        fn record(name: &str) -> TableFieldSchema {
+            TableFieldSchema {
+                fields: Some(vec![
+                    field("name", TableFieldType::String),
+                    field("count", TableFieldType::Int64),
+                ]),
+                ..field(name, TableFieldType::Record)
+            }
+        }
+
+        fn schema() -> Vec<TableFieldSchema> {
+            vec![
+                field("col_string", TableFieldType::String),
+                field("col_int", TableFieldType::Int64),
+                field("col_float", TableFieldType::Float64),
+                field("col_bool", TableFieldType::Bool),
+                field("col_bytes", TableFieldType::Bytes),
+                field("col_timestamp", TableFieldType::Timestamp),
+                field("col_numeric", TableFieldType::Numeric),
+                field("col_null", TableFieldType::String),
+                repeated(field("col_repeated", TableFieldType::Int64)),
+                record("col_record"),
+                repeated(record("col_records")),
+                field("col_unknown", TableFieldType::String),
+            ]
+        }
+
+        fn row(int: &str, count: &str) -> Tuple {
+            serde_json::from_value(json!({"f": [
+                {"v": "abc"},
+                {"v": int},
+                {"v": "1.5"},
+                {"v": "true"},
+                {"v": "AQI="},
+                {"v": "1.700000000123456E9"},
+                {"v": "-12.345"},
+                {"v": null},
+                {"v": [{"v": "1"}, {"v": "2"}]},
+                {"v": {"f": [{"v": "n1"}, {"v": null}]}},
+                {"v": [{"v": {"f": [{"v": "n2"}, {"v": "1"}]}}, {"v": {"f": [{"v": "n3"}, {"v": count}]}}]},
+                {"v": "ignored"}
+            ]}))
+            .unwrap()
+        }
+
+        #[test]
+        fn test_from_cells() {
+            let fields = schema();
+            let row = row("10", "2");
+            let data: Data = from_cells(&fields, &row.f).unwrap();
+            assert_eq!(
+                data,
+                Data {
+                    string: "abc".to_string(),
+                    col_int: 10,
+                    col_float: 1.5,
+                    col_bool: true,
+                    col_bytes: vec![1, 2],
+                    col_timestamp: datetime!(2023-11-14 22:13:20.123456 UTC),
+                    col_numeric: BigDecimal::from_str("-12.345").unwrap(),
+                    col_null: None,
+                    col_repeated: vec![1, 2],
+                    col_record: Nested {
+                        name: "n1".to_string(),
+                        count: None,
+                    },
+                    col_records: vec![
+                        Nested {
+                            name: "n2".to_string(),
+                            count: Some(1),
+                        },
+                        Nested {
+                            name: "n3".to_string(),
+                            count: Some(2),
+                        },
+                    ],
+                }
+            );
+
+            // the tuple is decoded by the position
+            let (string, int): (&str, i64) = from_cells(&fields[..2], &row.f[..2]).unwrap();
+            assert_eq!(string, "abc");
+            assert_eq!(int, 10);
+        }
+
+        #[test]
+        fn test_from_cells_error() {
+            let fields = schema();
+            match from_cells::<Data>(&fields, &row("ten", "2").f) {
+                Err(Error::Column { column, value, .. }) => {
+                    assert_eq!(column, "col_int");
+                    assert_eq!(value, "ten");
+                }
+                r => unreachable!("column error is expected: {r:?}"),
+            }
+            match from_cells::<Data>(&fields, &row("10", "two").f) {
+                Err(Error::Column { column, value, .. }) => {
+                    assert_eq!(column, "col_records[1].count");
+                    assert_eq!(value, "two");
+                }
+                r => unreachable!("column error is expected: {r:?}"),
+            }
+
+            #[derive(serde::Deserialize, Debug)]
+            struct Required {
+                #[allow(dead_code)]
+                col_null: String,
+            }
+            match from_cells::<Required>(&fields, &row("10", "2").f) {
+                Err(Error::Column { column, value, .. }) => {
+                    assert_eq!(column, "col_null");
+                    assert_eq!(value, "null");
+                }
+                r => unreachable!("column error is expected: {r:?}"),
+            }
+        }
+    }
+}
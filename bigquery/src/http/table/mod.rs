@@ -486,6 +486,148 @@ pub struct TableFieldSchema {
     pub default_value_expression: Option<String>,
 }
 
+impl TableFieldType {
+    /// canonical returns the standard name of the alias like `INTEGER` for `INT64`.
+    pub fn canonical(&self) -> TableFieldType {
+        match self {
+            TableFieldType::Bool => TableFieldType::Boolean,
+            TableFieldType::Int64 => TableFieldType::Integer,
+            TableFieldType::Float64 => TableFieldType::Float,
+            TableFieldType::Struct => TableFieldType::Record,
+            TableFieldType::Decimal => TableFieldType::Numeric,
+            TableFieldType::Bigdecimal => TableFieldType::Bignumeric,
+            v => v.clone(),
+        }
+    }
+}
+
+impl TableFieldSchema {
+    /// new returns the NULLABLE field.
+    /// ```
+    /// use google_cloud_bigquery::http::table::{TableFieldMode, TableFieldSchema, TableFieldType, TableSchema};
+    ///
+    /// let schema = TableSchema::new(vec![
+    ///     TableFieldSchema::new("id", TableFieldType::String).with_mode(TableFieldMode::Required),
+    ///     TableFieldSchema::new("created_at", TableFieldType::Timestamp).with_description("creation time"),
+    ///     TableFieldSchema::record(
+    ///         "items",
+    ///         vec![
+    ///             TableFieldSchema::new("name", TableFieldType::String),
+    ///             TableFieldSchema::new("price", TableFieldType::Numeric),
+    ///         ],
+    ///     )
+    ///     .with_mode(TableFieldMode::Repeated),
+    /// ]);
+    /// ```
+    pub fn new(name: impl Into<String>, data_type: TableFieldType) -> Self {
+        Self {
+            name: name.into(),
+            data_type,
+            ..Default::default()
+        }
+    }
+
+    /// record returns the NULLABLE RECORD field with the nested fields.
+    pub fn record(name: impl Into<String>, fields: Vec<TableFieldSchema>) -> Self {
+        Self {
+            fields: Some(fields),
+            ..Self::new(name, TableFieldType::Record)
+        }
+    }
+
+    pub fn with_mode(mut self, value: TableFieldMode) -> Self {
+        self.mode = Some(value);
+        self
+    }
+
+    pub fn with_description(mut self, value: impl Into<String>) -> Self {
+        self.description = Some(value.into());
+        self
+    }
+
+    /// mode_or_default returns the mode with NULLABLE as the default.
+    pub fn mode_or_default(&self) -> TableFieldMode {
+        self.mode.clone().unwrap_or_default()
+    }
+
+    fn merge(&self, other: &TableFieldSchema, path: &str) -> Result<TableFieldSchema, SchemaError> {
+        if self.data_type.canonical() != other.data_type.canonical() {
+            return Err(SchemaError::TypeChanged {
+                field: path.to_string(),
+                from: self.data_type.clone(),
+                to: other.data_type.clone(),
+            });
+        }
+        let mode = match (self.mode_or_default(), other.mode_or_default()) {
+            (from, to) if from == to => self.mode.clone(),
+            // relaxing REQUIRED to NULLABLE is allowed
+            (TableFieldMode::Required, TableFieldMode::Nullable) => other.mode.clone(),
+            (from, to) => {
+                return Err(SchemaError::ModeChanged {
+                    field: path.to_string(),
+                    from,
+                    to,
+                })
+            }
+        };
+        let fields = match (&self.fields, &other.fields) {
+            (Some(current), Some(desired)) => Some(merge_fields(current, desired, &format!("{path}."))?),
+            (current, _) => current.clone(),
+        };
+        Ok(TableFieldSchema {
+            mode,
+            fields,
+            description: other.description.clone().or_else(|| self.description.clone()),
+            ..self.clone()
+        })
+    }
+}
+
+/// SchemaError is the schema change rejected by BigQuery.
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    #[error("type of {field} can't be changed from {from:?} to {to:?}")]
+    TypeChanged {
+        field: String,
+        from: TableFieldType,
+        to: TableFieldType,
+    },
+    #[error("mode of {field} can't be changed from {from:?} to {to:?}")]
+    ModeChanged {
+        field: String,
+        from: TableFieldMode,
+        to: TableFieldMode,
+    },
+    #[error("the new field {0} must be NULLABLE or REPEATED")]
+    RequiredFieldAdded(String),
+}
+
+fn merge_fields(
+    current: &[TableFieldSchema],
+    desired: &[TableFieldSchema],
+    prefix: &str,
+) -> Result<Vec<TableFieldSchema>, SchemaError> {
+    let mut merged = Vec::with_capacity(current.len().max(desired.len()));
+    for field in current {
+        let path = format!("{prefix}{}", field.name);
+        match desired.iter().find(|f| f.name.eq_ignore_ascii_case(&field.name)) {
+            Some(other) => merged.push(field.merge(other, &path)?),
+            // the columns can't be dropped by the patch, so keep the missing ones
+            None => merged.push(field.clone()),
+        }
+    }
+    for field in desired {
+        if current.iter().any(|f| f.name.eq_ignore_ascii_case(&field.name)) {
+            continue;
+        }
+        if field.mode_or_default() == TableFieldMode::Required {
+            return Err(SchemaError::RequiredFieldAdded(format!("{prefix}{}", field.name)));
+        }
+        merged.push(field.clone());
+    }
+    Ok(merged)
+}
+
 #[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TimePartitionType {
@@ -544,6 +686,34 @@ pub struct TableSchema {
     pub fields: Vec<TableFieldSchema>,
 }
 
+impl TableSchema {
+    pub fn new(fields: Vec<TableFieldSchema>) -> Self {
+        Self { fields }
+    }
+
+    /// merge returns the schema to patch the table from this schema to the desired one.
+    /// The fields not in the desired schema are kept because the patch can't drop the columns.
+    /// The new fields including the nested ones must be NULLABLE or REPEATED,
+    /// and the existing fields can only relax the mode from REQUIRED to NULLABLE.
+    /// ```
+    /// use google_cloud_bigquery::http::bigquery_table_client::BigqueryTableClient;
+    /// use google_cloud_bigquery::http::table::{TableFieldSchema, TableFieldType, TableSchema};
+    ///
+    /// async fn run(client: BigqueryTableClient) {
+    ///     let mut table = client.get("project", "dataset", "table").await.unwrap();
+    ///     let desired = TableSchema::new(vec![TableFieldSchema::new("new_column", TableFieldType::String)]);
+    ///     table.schema = Some(table.schema.unwrap_or_default().merge(&desired).unwrap());
+    ///     // the etag of the table fetched prevents overwriting the concurrent updates
+    ///     client.patch(&table).await.unwrap();
+    /// }
+    /// ```
+    pub fn merge(&self, desired: &TableSchema) -> Result<TableSchema, SchemaError> {
+        Ok(TableSchema {
+            fields: merge_fields(&self.fields, &desired.fields, "")?,
+        })
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Clustering {
@@ -771,3 +941,78 @@ pub struct Table {
     /// Staleness encoded as a string encoding of sql IntervalValue type.
     pub max_staleness: Option<String>,
 }
+
+#[cfg(test)]
+mod test {
+    use crate::http::table::{SchemaError, TableFieldMode, TableFieldSchema, TableFieldType, TableSchema};
+
+    fn current() -> TableSchema {
+        TableSchema::new(vec![
+            TableFieldSchema::new("id", TableFieldType::String).with_mode(TableFieldMode::Required),
+            TableFieldSchema::new("count", TableFieldType::Int64),
+            TableFieldSchema::record("item", vec![TableFieldSchema::new("name", TableFieldType::String)]),
+        ])
+    }
+
+    #[test]
+    fn test_merge_schema() {
+        let desired = TableSchema::new(vec![
+            TableFieldSchema::new("id", TableFieldType::String),
+            TableFieldSchema::new("count", TableFieldType::Integer).with_description("the count"),
+            TableFieldSchema::record(
+                "item",
+                vec![
+                    TableFieldSchema::new("name", TableFieldType::String),
+                    TableFieldSchema::new("price", TableFieldType::Numeric),
+                ],
+            ),
+            TableFieldSchema::new("tags", TableFieldType::String).with_mode(TableFieldMode::Repeated),
+        ]);
+        let merged = current().merge(&desired).unwrap();
+        assert_eq!(
+            merged,
+            TableSchema::new(vec![
+                TableFieldSchema::new("id", TableFieldType::String),
+                TableFieldSchema::new("count", TableFieldType::Int64).with_description("the count"),
+                TableFieldSchema::record(
+                    "item",
+                    vec![
+                        TableFieldSchema::new("name", TableFieldType::String),
+                        TableFieldSchema::new("price", TableFieldType::Numeric),
+                    ],
+                ),
+                TableFieldSchema::new("tags", TableFieldType::String).with_mode(TableFieldMode::Repeated),
+            ])
+        );
+
+        // the missing fields are kept
+        let merged = current().merge(&TableSchema::default()).unwrap();
+        assert_eq!(merged, current());
+    }
+
+    #[test]
+    fn test_merge_schema_error() {
+        let desired = TableSchema::new(vec![TableFieldSchema::new("count", TableFieldType::String)]);
+        assert!(matches!(
+            current().merge(&desired).unwrap_err(),
+            SchemaError::TypeChanged { field, .. } if field == "count"
+        ));
+
+        let desired = TableSchema::new(vec![
+            TableFieldSchema::new("count", TableFieldType::Int64).with_mode(TableFieldMode::Repeated)
+        ]);
+        assert!(matches!(
+            current().merge(&desired).unwrap_err(),
+            SchemaError::ModeChanged { field, .. } if field == "count"
+        ));
+
+        let desired = TableSchema::new(vec![TableFieldSchema::record(
+            "item",
+            vec![TableFieldSchema::new("price", TableFieldType::Numeric).with_mode(TableFieldMode::Required)],
+        )]);
+        assert_eq!(
+            current().merge(&desired).unwrap_err(),
+            SchemaError::RequiredFieldAdded("item.price".to_string())
+        );
+    }
+}
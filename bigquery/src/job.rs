@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use backon::ExponentialBuilder;
+
+use crate::http;
+use crate::http::job::{
+    CreateDisposition, Job, JobConfiguration, JobConfigurationExtract, JobConfigurationExtractSource,
+    JobConfigurationLoad, JobReference, JobType, SchemaUpdateOption, WriteDisposition,
+};
+use crate::http::model::ModelReference;
+use crate::http::table::{
+    Clustering, DestinationFormat, RangePartitioning, SourceFormat, TableReference, TableSchema, TimePartitioning,
+};
+use crate::http::types::ErrorProto;
+
+/// The prefix of the job id generated by the client.
+const DEFAULT_JOB_ID_PREFIX: &str = "job_";
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Http(#[from] http::error::Error),
+    /// The job completed with the error result. `errors` contains all the errors of the job.
+    #[error("job failed: job={job:?}, error={error:?}, errors={errors:?}")]
+    JobFailed {
+        job: JobReference,
+        error: ErrorProto,
+        errors: Vec<ErrorProto>,
+    },
+    #[error("job is not completed: job={0:?}")]
+    JobIncomplete(JobReference),
+    #[error("timed out waiting for the job: job={0:?}")]
+    Timeout(JobReference),
+}
+
+/// WaitOption is the option to poll `jobs.get` until the job is done.
+#[derive(Debug, Clone)]
+pub struct WaitOption {
+    /// Exponential back off between the polls and the retries of the transient failures.
+    pub(crate) retry: ExponentialBuilder,
+    /// The max duration to wait for the job completion.
+    pub(crate) timeout: Option<Duration>,
+}
+
+impl Default for WaitOption {
+    fn default() -> Self {
+        Self {
+            retry: ExponentialBuilder::default()
+                .with_min_delay(Duration::from_millis(500))
+                .with_max_delay(Duration::from_secs(30))
+                .with_max_times(usize::MAX),
+            timeout: None,
+        }
+    }
+}
+
+impl WaitOption {
+    pub fn with_retry(mut self, builder: ExponentialBuilder) -> Self {
+        self.retry = builder;
+        self
+    }
+    pub fn with_timeout(mut self, value: Duration) -> Self {
+        self.timeout = Some(value);
+        self
+    }
+}
+
+/// The settings common to the jobs created by the client.
+#[derive(Debug, Clone)]
+struct JobOption {
+    job_id_prefix: String,
+    location: Option<String>,
+    labels: Option<HashMap<String, String>>,
+    job_timeout_ms: Option<i64>,
+}
+
+impl Default for JobOption {
+    fn default() -> Self {
+        Self {
+            job_id_prefix: DEFAULT_JOB_ID_PREFIX.to_string(),
+            location: None,
+            labels: None,
+            job_timeout_ms: None,
+        }
+    }
+}
+
+impl JobOption {
+    /// The job id is generated once, so the retried submissions of the job are deduplicated by BigQuery.
+    fn build(self, project_id: &str, job: JobType) -> Job {
+        Job {
+            job_reference: JobReference {
+                project_id: project_id.to_string(),
+                job_id: format!("{}{}", self.job_id_prefix, uuid::Uuid::new_v4()),
+                location: self.location,
+            },
+            configuration: JobConfiguration {
+                job,
+                job_timeout_ms: self.job_timeout_ms,
+                labels: self.labels,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+/// LoadJob loads the data from Google Cloud Storage into the table.
+/// https://cloud.google.com/bigquery/docs/reference/rest/v2/Job#jobconfigurationload
+#[derive(Debug, Clone)]
+pub struct LoadJob {
+    config: JobConfigurationLoad,
+    option: JobOption,
+}
+
+impl LoadJob {
+    pub fn new(source_uris: Vec<String>, destination_table: TableReference) -> Self {
+        Self {
+            config: JobConfigurationLoad {
+                source_uris,
+                destination_table,
+                ..Default::default()
+            },
+            option: JobOption::default(),
+        }
+    }
+
+    pub fn with_source_format(mut self, value: SourceFormat) -> Self {
+        self.config.source_format = Some(value);
+        self
+    }
+
+    pub fn with_schema(mut self, value: TableSchema) -> Self {
+        self.config.schema = Some(value);
+        self
+    }
+
+    /// Infer the schema and the options of the CSV and JSON sources.
+    pub fn with_autodetect(mut self, value: bool) -> Self {
+        self.config.autodetect = Some(value);
+        self
+    }
+
+    pub fn with_write_disposition(mut self, value: WriteDisposition) -> Self {
+        self.config.write_disposition = Some(value);
+        self
+    }
+
+    pub fn with_create_disposition(mut self, value: CreateDisposition) -> Self {
+        self.config.create_disposition = Some(value);
+        self
+    }
+
+    pub fn with_schema_update_options(mut self, value: Vec<SchemaUpdateOption>) -> Self {
+        self.config.schema_update_options = Some(value);
+        self
+    }
+
+    pub fn with_time_partitioning(mut self, value: TimePartitioning) -> Self {
+        self.config.time_partitioning = Some(value);
+        self
+    }
+
+    pub fn with_range_partitioning(mut self, value: RangePartitioning) -> Self {
+        self.config.range_partitioning = Some(value);
+        self
+    }
+
+    pub fn with_clustering(mut self, value: Clustering) -> Self {
+        self.config.clustering = Some(value);
+        self
+    }
+
+    /// The field delimiter of the CSV files.
+    pub fn with_field_delimiter(mut self, value: impl Into<String>) -> Self {
+        self.config.field_delimiter = Some(value.into());
+        self
+    }
+
+    /// The number of the header rows of the CSV files.
+    pub fn with_skip_leading_rows(mut self, value: i64) -> Self {
+        self.config.skip_leading_rows = Some(value);
+        self
+    }
+
+    pub fn with_max_bad_records(mut self, value: i64) -> Self {
+        self.config.max_bad_records = Some(value);
+        self
+    }
+
+    pub fn with_ignore_unknown_values(mut self, value: bool) -> Self {
+        self.config.ignore_unknown_values = Some(value);
+        self
+    }
+
+    /// Replace the whole configuration to set the options without the builder.
+    pub fn with_config(mut self, value: JobConfigurationLoad) -> Self {
+        self.config = value;
+        self
+    }
+
+    /// The prefix of the job id generated by the client. The default is `job_`.
+    pub fn with_job_id_prefix(mut self, value: impl Into<String>) -> Self {
+        self.option.job_id_prefix = value.into();
+        self
+    }
+
+    pub fn with_location(mut self, value: impl Into<String>) -> Self {
+        self.option.location = Some(value.into());
+        self
+    }
+
+    pub fn with_labels(mut self, value: HashMap<String, String>) -> Self {
+        self.option.labels = Some(value);
+        self
+    }
+
+    pub fn with_job_timeout(mut self, value: Duration) -> Self {
+        self.option.job_timeout_ms = Some(value.as_millis() as i64);
+        self
+    }
+
+    /// build creates the job with the id generated by the client.
+    pub fn build(self, project_id: &str) -> Job {
+        self.option.build(project_id, JobType::Load(self.config))
+    }
+}
+
+/// ExtractJob exports the table or the model to Google Cloud Storage.
+/// https://cloud.google.com/bigquery/docs/reference/rest/v2/Job#jobconfigurationextract
+#[derive(Debug, Clone)]
+pub struct ExtractJob {
+    config: JobConfigurationExtract,
+    option: JobOption,
+}
+
+impl ExtractJob {
+    pub fn new(source_table: TableReference, destination_uris: Vec<String>) -> Self {
+        Self::new_with_source(JobConfigurationExtractSource::SourceTable(source_table), destination_uris)
+    }
+
+    pub fn new_model(source_model: ModelReference, destination_uris: Vec<String>) -> Self {
+        Self::new_with_source(JobConfigurationExtractSource::SourceModel(source_model), destination_uris)
+    }
+
+    fn new_with_source(source: JobConfigurationExtractSource, destination_uris: Vec<String>) -> Self {
+        Self {
+            config: JobConfigurationExtract {
+                destination_uris,
+                source,
+                ..Default::default()
+            },
+            option: JobOption::default(),
+        }
+    }
+
+    pub fn with_destination_format(mut self, value: DestinationFormat) -> Self {
+        self.config.destination_format = Some(value);
+        self
+    }
+
+    /// The compression of the exported files: DEFLATE, GZIP, NONE, SNAPPY or ZSTD.
+    pub fn with_compression(mut self, value: impl Into<String>) -> Self {
+        self.config.compression = Some(value.into());
+        self
+    }
+
+    pub fn with_field_delimiter(mut self, value: impl Into<String>) -> Self {
+        self.config.field_delimiter = Some(value.into());
+        self
+    }
+
+    pub fn with_print_header(mut self, value: bool) -> Self {
+        self.config.print_header = Some(value);
+        self
+    }
+
+    pub fn with_use_avro_logical_types(mut self, value: bool) -> Self {
+        self.config.use_avro_logical_types = Some(value);
+        self
+    }
+
+    /// The prefix of the job id generated by the client. The default is `job_`.
+    pub fn with_job_id_prefix(mut self, value: impl Into<String>) -> Self {
+        self.option.job_id_prefix = value.into();
+        self
+    }
+
+    pub fn with_location(mut self, value: impl Into<String>) -> Self {
+        self.option.location = Some(value.into());
+        self
+    }
+
+    pub fn with_labels(mut self, value: HashMap<String, String>) -> Self {
+        self.option.labels = Some(value);
+        self
+    }
+
+    pub fn with_job_timeout(mut self, value: Duration) -> Self {
+        self.option.job_timeout_ms = Some(value.as_millis() as i64);
+        self
+    }
+
+    /// build creates the job with the id generated by the client.
+    pub fn build(self, project_id: &str) -> Job {
+        self.option.build(project_id, JobType::Extract(self.config))
+    }
+}
+
+/// check_status returns the error if the job is done with the error result.
+pub(crate) fn check_status(job: &Job) -> Result<(), Box<Error>> {
+    match &job.status.error_result {
+        Some(error) => Err(Box::new(Error::JobFailed {
+            job: job.job_reference.clone(),
+            error: error.clone(),
+            errors: job.status.errors.clone().unwrap_or_default(),
+        })),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::job::{Job, JobState, JobStatus, JobType};
+    use crate::http::table::{SourceFormat, TableReference};
+    use crate::http::types::ErrorProto;
+    use crate::job::{check_status, Error, LoadJob};
+
+    #[test]
+    fn test_build_load_job() {
+        let job = LoadJob::new(vec!["gs://bucket/data.csv".to_string()], TableReference::default())
+            .with_source_format(SourceFormat::Csv)
+            .with_autodetect(true)
+            .with_job_id_prefix("load_")
+            .with_location("US");
+        let job1 = job.clone().build("project");
+        let job2 = job.build("project");
+        assert!(job1.job_reference.job_id.starts_with("load_"));
+        assert_ne!(job1.job_reference.job_id, job2.job_reference.job_id);
+        assert_eq!(job1.job_reference.location.as_deref(), Some("US"));
+        match job1.configuration.job {
+            JobType::Load(config) => {
+                assert_eq!(config.source_format, Some(SourceFormat::Csv));
+                assert_eq!(config.autodetect, Some(true));
+            }
+            _ => unreachable!("load job is expected"),
+        }
+    }
+
+    #[test]
+    fn test_check_status() {
+        let error = ErrorProto {
+            reason: Some("invalid".to_string()),
+            location: None,
+            message: Some("error".to_string()),
+        };
+        let mut job = Job {
+            status: JobStatus {
+                error_result: None,
+                errors: Some(vec![error.clone()]),
+                state: JobState::Done,
+            },
+            ..Default::default()
+        };
+        // errors without error_result are not fatal
+        assert!(check_status(&job).is_ok());
+
+        job.status.error_result = Some(error.clone());
+        match check_status(&job).map_err(|e| *e) {
+            Err(Error::JobFailed { error: e, errors, .. }) => {
+                assert_eq!(e, error);
+                assert_eq!(errors, vec![error]);
+            }
+            _ => unreachable!("job failure is expected"),
+        }
+    }
+}
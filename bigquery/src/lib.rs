@@ -55,6 +55,26 @@
 //! }
 //! ```
 //!
+//! The results can also be read as a stream. The pages are fetched lazily.
+//! ```rust
+//! use futures_util::StreamExt;
+//! use google_cloud_bigquery::http::job::query::QueryRequest;
+//! use google_cloud_bigquery::query::row::Row;
+//! use google_cloud_bigquery::query::QueryOption;
+//! use google_cloud_bigquery::client::Client;
+//!
+//! async fn run(client: &Client, project_id: &str) {
+//!     let request = QueryRequest {
+//!         query: "SELECT * FROM dataset.table".to_string(),
+//!         ..Default::default()
+//!     };
+//!     let mut stream = client.query_stream::<Row>(project_id, request, QueryOption::default()).await.unwrap();
+//!     while let Some(row) = stream.next().await {
+//!         let col1 = row.unwrap().column::<String>(0);
+//!     }
+//! }
+//! ```
+//!
 //! #### Read Table
 //! ```rust
 //! use google_cloud_bigquery::storage::row::Row;
@@ -75,6 +95,26 @@
 //! }
 //! ```
 //!
+//! Read the table as the Arrow record batches. The streams of the read session are read concurrently and resumed at the last offset after the transient failures.
+//! ```rust
+//! use futures_util::TryStreamExt;
+//! use google_cloud_bigquery::client::{Client, ReadTableOption};
+//! use google_cloud_bigquery::http::table::TableReference;
+//! use google_cloud_bigquery::storage::row::Row;
+//!
+//! async fn run(client: &Client, table: &TableReference) {
+//!     let option = ReadTableOption::default().with_max_stream_count(4);
+//!     let mut stream = client.read_table_arrow(table, Some(option.clone())).await.unwrap();
+//!     while let Some(batch) = stream.try_next().await.unwrap() {
+//!         let rows = batch.num_rows();
+//!     }
+//!
+//!     // decode the record batches into the rows
+//!     let stream = client.read_table_arrow(table, Some(option)).await.unwrap();
+//!     let rows: Vec<Row> = stream.into_rows().try_collect().await.unwrap();
+//! }
+//! ```
+//!
 //! #### Values
 //! Default supported types to decode by `row.column::<T>()` are
 //! * String (for STRING)
@@ -91,6 +131,26 @@
 //! * Option (for all NULLABLE)
 //! * Vec (for ARRAY)
 //!
+//! The rows read by the REST API can be decoded into the types implementing `serde::Deserialize` by the column names.
+//! ```rust
+//! use google_cloud_bigquery::query::row::Row;
+//! use time::OffsetDateTime;
+//!
+//! #[derive(serde::Deserialize)]
+//! struct Item {
+//!     #[serde(rename = "item_name")]
+//!     name: String,
+//!     count: i64,
+//!     #[serde(with = "time::serde::rfc3339")]
+//!     updated_at: OffsetDateTime,
+//!     tags: Vec<String>,
+//! }
+//!
+//! fn decode(row: &Row) -> Item {
+//!     row.decode::<Item>().unwrap()
+//! }
+//! ```
+//!
 //! ### Insert Data
 //!
 //! #### Table data API
@@ -172,6 +232,36 @@
 //! }
 //! ```
 //!
+//! Or use the builders to create the job with the id generated by the client and wait for the completion.
+//! ```rust
+//! use std::time::Duration;
+//! use google_cloud_bigquery::client::Client;
+//! use google_cloud_bigquery::http::job::WriteDisposition;
+//! use google_cloud_bigquery::http::table::{DestinationFormat, SourceFormat, TableReference};
+//! use google_cloud_bigquery::job::{Error, ExtractJob, LoadJob, WaitOption};
+//!
+//! async fn run(client: &Client, project_id: &str, table: TableReference) {
+//!     let load = LoadJob::new(vec!["gs://bucket/data/*.csv".to_string()], table.clone())
+//!         .with_source_format(SourceFormat::Csv)
+//!         .with_skip_leading_rows(1)
+//!         .with_autodetect(true)
+//!         .with_write_disposition(WriteDisposition::WriteTruncate)
+//!         .with_job_id_prefix("load_");
+//!     let job = client.load(project_id, load).await.unwrap();
+//!     let option = WaitOption::default().with_timeout(Duration::from_secs(600));
+//!     match client.wait_for_job(&job.job_reference, option.clone()).await {
+//!         Ok(job) => println!("loaded {:?}", job.statistics),
+//!         Err(Error::JobFailed { error, errors, .. }) => println!("failed {:?} {:?}", error, errors),
+//!         Err(e) => println!("could not wait for the job {:?}", e),
+//!     }
+//!
+//!     let extract = ExtractJob::new(table, vec!["gs://bucket/export/*.json".to_string()])
+//!         .with_destination_format(DestinationFormat::NewlineDelimitedJson);
+//!     let job = client.extract(project_id, extract).await.unwrap();
+//!     client.wait_for_job(&job.job_reference, option).await.unwrap();
+//! }
+//! ```
+//!
 //! ## Features
 //! ### HTTP API
 //! * [x] [job](https://cloud.google.com/bigquery/docs/reference/rest/v2/jobs)
@@ -183,10 +273,12 @@
 //! * [x] [rowAccessPolicy](https://cloud.google.com/bigquery/docs/reference/rest/v2/rowAccessPolicies)
 //! ### Streaming
 //! * [x] [Storage Read API](https://cloud.google.com/bigquery/docs/reference/storage)
-//! * [ ] [Storage Write API](https://cloud.google.com/bigquery/docs/write-api)
+//! * [x] [Storage Write API](https://cloud.google.com/bigquery/docs/write-api) (default stream)
 
 pub mod client;
 pub mod grpc;
 pub mod http;
+pub mod job;
 pub mod query;
 pub mod storage;
+pub mod storage_write;
@@ -1,5 +1,12 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
 pub use backon::*;
+use futures_util::{Stream, StreamExt};
 
+use crate::http::job::JobReference;
+use crate::http::table::TableSchema;
 use crate::{http, storage};
 
 #[derive(Debug, Clone)]
@@ -8,6 +15,8 @@ pub struct QueryOption {
     pub(crate) retry: ExponentialBuilder,
     /// true: use storage api is page token is empty
     pub(crate) enable_storage_read: bool,
+    /// The max duration to wait for the job completion.
+    pub(crate) timeout: Option<Duration>,
 }
 
 impl Default for QueryOption {
@@ -15,6 +24,7 @@ impl Default for QueryOption {
         Self {
             enable_storage_read: false,
             retry: ExponentialBuilder::default().with_max_times(usize::MAX),
+            timeout: None,
         }
     }
 }
@@ -28,6 +38,10 @@ impl QueryOption {
         self.enable_storage_read = value;
         self
     }
+    pub fn with_timeout(mut self, value: Duration) -> Self {
+        self.timeout = Some(value);
+        self
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -40,12 +54,16 @@ pub enum Error {
 
 pub enum QueryResult<T: http::query::value::StructDecodable + storage::value::StructDecodable> {
     Http(http::query::Iterator<T>),
-    Storage(storage::Iterator<T>),
+    Storage(Box<storage::Iterator<T>>),
 }
 
 pub struct Iterator<T: http::query::value::StructDecodable + storage::value::StructDecodable> {
     pub(crate) inner: QueryResult<T>,
     pub total_size: i64,
+    /// The job running the query.
+    pub job_reference: JobReference,
+    /// The schema of the results.
+    pub schema: Option<TableSchema>,
 }
 
 impl<T: http::query::value::StructDecodable + storage::value::StructDecodable> Iterator<T> {
@@ -55,9 +73,57 @@ impl<T: http::query::value::StructDecodable + storage::value::StructDecodable> I
             QueryResult::Http(ref mut v) => v.next().await?,
         })
     }
+
+    /// into_stream converts the iterator into the stream fetching the pages lazily.
+    /// The stream ends after the first error.
+    pub fn into_stream(self) -> RowStream<T>
+    where
+        T: Send + 'static,
+    {
+        let total_rows = self.total_size;
+        let job_reference = self.job_reference.clone();
+        let schema = self.schema.clone();
+        let inner = futures_util::stream::unfold(Some(self), |iter| async move {
+            let mut iter = iter?;
+            match iter.next().await {
+                Ok(Some(row)) => Some((Ok(row), Some(iter))),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), None)),
+            }
+        });
+        RowStream {
+            inner: Box::pin(inner),
+            total_rows,
+            job_reference,
+            schema,
+        }
+    }
+}
+
+/// RowStream is the stream of the query results returned by `Client::query_stream`.
+pub struct RowStream<T> {
+    inner: Pin<Box<dyn Stream<Item = Result<T, Error>> + Send>>,
+    /// The total number of rows in the results.
+    pub total_rows: i64,
+    /// The job running the query.
+    pub job_reference: JobReference,
+    /// The schema of the results.
+    pub schema: Option<TableSchema>,
+}
+
+impl<T> Stream for RowStream<T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
 }
 
 pub mod row {
+    use std::sync::Arc;
+
+    use crate::http::query::value::StructDecodable;
+    use crate::http::table::TableSchema;
     use crate::http::tabledata::list::Tuple;
     use crate::{http, storage};
     use arrow::array::ArrayRef;
@@ -68,6 +134,8 @@ pub mod row {
         Http(#[from] http::query::row::Error),
         #[error(transparent)]
         Storage(#[from] storage::row::Error),
+        #[error("decoding by the column names is not supported for the rows read by the Storage Read API")]
+        DecodeNotSupported,
     }
 
     pub enum RowType {
@@ -89,12 +157,27 @@ pub mod row {
                 RowType::Storage(row) => row.column(index)?,
             })
         }
+
+        /// decode decodes the row into the type implementing `serde::Deserialize` by the column names.
+        /// It is supported for the rows read by the REST API.
+        pub fn decode<'a, T: serde::Deserialize<'a>>(&'a self) -> Result<T, Error> {
+            match &self.inner {
+                RowType::Http(row) => Ok(row.decode()?),
+                RowType::Storage(_) => Err(Error::DecodeNotSupported),
+            }
+        }
     }
 
-    impl http::query::value::StructDecodable for Row {
+    impl StructDecodable for Row {
         fn decode(value: Tuple) -> Result<Self, http::query::value::Error> {
             Ok(Self {
-                inner: RowType::Http(http::query::row::Row::decode(value)?),
+                inner: RowType::Http(<http::query::row::Row as StructDecodable>::decode(value)?),
+            })
+        }
+
+        fn decode_with_schema(value: Tuple, schema: &Arc<TableSchema>) -> Result<Self, http::query::value::Error> {
+            Ok(Self {
+                inner: RowType::Http(http::query::row::Row::decode_with_schema(value, schema)?),
             })
         }
     }
@@ -115,5 +198,7 @@ pub mod run {
         Http(#[from] crate::http::error::Error),
         #[error("Retry exceeded with job incomplete")]
         JobIncomplete,
+        #[error("Timed out waiting for job completion")]
+        Timeout,
     }
 }
@@ -1,18 +1,25 @@
 use std::collections::VecDeque;
-use std::io::{BufReader, Cursor};
+use std::io::Cursor;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
 use arrow::error::ArrowError;
 use arrow::ipc::reader::StreamReader;
+use arrow::record_batch::RecordBatch;
 pub use arrow::*;
+use futures_util::{Stream, StreamExt, TryStreamExt};
 
-use google_cloud_gax::grpc::{Status, Streaming};
-use google_cloud_gax::retry::RetrySetting;
+use google_cloud_gax::grpc::{Code, Status, Streaming};
+use google_cloud_gax::retry::{Condition, Retry, RetrySetting};
 use google_cloud_googleapis::cloud::bigquery::storage::v1::read_rows_response::{Rows, Schema};
+use google_cloud_googleapis::cloud::bigquery::storage::v1::read_session::Schema as SessionSchema;
 use google_cloud_googleapis::cloud::bigquery::storage::v1::{
-    ArrowSchema, ReadRowsRequest, ReadRowsResponse, ReadSession,
+    ArrowRecordBatch, ArrowSchema, ReadRowsRequest, ReadRowsResponse, ReadSession,
 };
 
 use crate::grpc::apiv1::bigquery_client::StreamingReadClient;
+use crate::grpc::apiv1::conn_pool::ReadConnectionManager;
 use crate::storage::value::StructDecodable;
 
 #[derive(thiserror::Error, Debug)]
@@ -40,7 +47,7 @@ where
     retry: Option<RetrySetting>,
     // mutable
     stream_index: usize,
-    current_stream: Streaming<ReadRowsResponse>,
+    current_stream: Option<ResumableStream>,
     chunk: VecDeque<T>,
     schema: Option<ArrowSchema>,
 }
@@ -50,20 +57,14 @@ where
     T: StructDecodable,
 {
     pub async fn new(
-        mut client: StreamingReadClient,
+        client: StreamingReadClient,
         session: ReadSession,
         retry: Option<RetrySetting>,
     ) -> Result<Self, Error> {
-        let current_stream = client
-            .read_rows(
-                ReadRowsRequest {
-                    read_stream: session.streams[0].name.to_string(),
-                    offset: 0,
-                },
-                retry.clone(),
-            )
-            .await?
-            .into_inner();
+        let current_stream = session
+            .streams
+            .first()
+            .map(|stream| ResumableStream::new(client.clone(), stream.name.to_string(), retry.clone()));
         Ok(Self {
             client,
             session,
@@ -80,7 +81,12 @@ where
             if let Some(row) = self.chunk.pop_front() {
                 return Ok(Some(row));
             }
-            if let Some(rows) = self.current_stream.message().await? {
+            // The table has no stream if it is empty.
+            let current_stream = match self.current_stream.as_mut() {
+                Some(current_stream) => current_stream,
+                None => return Ok(None),
+            };
+            if let Some(rows) = current_stream.next().await? {
                 if self.schema.is_none() {
                     match rows.schema.ok_or(Error::NoSchemaFound)? {
                         Schema::ArrowSchema(schema) => self.schema = Some(schema),
@@ -88,9 +94,10 @@ where
                     }
                 };
                 if let Some(rows) = rows.rows {
-                    self.chunk = rows_to_chunk(self.schema.clone().unwrap(), rows)?;
+                    self.chunk = rows_to_chunk(self.schema.clone().unwrap(), rows).map_err(|e| *e)?;
                     return Ok(self.chunk.pop_front());
                 }
+                continue;
             }
 
             if self.stream_index == self.session.streams.len() - 1 {
@@ -99,41 +106,185 @@ where
                 self.stream_index += 1
             }
             let stream = &self.session.streams[self.stream_index].name;
-            self.current_stream = self
-                .client
-                .read_rows(
-                    ReadRowsRequest {
-                        read_stream: stream.to_string(),
-                        offset: 0,
-                    },
-                    self.retry.clone(),
-                )
-                .await?
-                .into_inner();
+            self.current_stream = Some(ResumableStream::new(
+                self.client.clone(),
+                stream.to_string(),
+                self.retry.clone(),
+            ));
+        }
+    }
+}
+
+fn default_resume_setting() -> RetrySetting {
+    RetrySetting {
+        from_millis: 50,
+        max_delay: Some(Duration::from_secs(60)),
+        factor: 1u64,
+        take: 20,
+        // The long-running streams are sometimes reset with INTERNAL.
+        codes: vec![Code::Unavailable, Code::Unknown, Code::Internal],
+    }
+}
+
+/// ResumableStream reads a stream of the read session.
+/// The stream is read again at the last offset after the transient failures.
+struct ResumableStream {
+    client: StreamingReadClient,
+    read_stream: String,
+    retry: Option<RetrySetting>,
+    offset: i64,
+    current: Option<Streaming<ReadRowsResponse>>,
+    finished: bool,
+}
+
+impl ResumableStream {
+    fn new(client: StreamingReadClient, read_stream: String, retry: Option<RetrySetting>) -> Self {
+        Self {
+            client,
+            read_stream,
+            retry,
+            offset: 0,
+            current: None,
+            finished: false,
+        }
+    }
+
+    async fn next(&mut self) -> Result<Option<ReadRowsResponse>, Error> {
+        if self.finished {
+            return Ok(None);
+        }
+        let setting = self.retry.clone().unwrap_or_else(default_resume_setting);
+        let mut backoff = setting.strategy();
+        loop {
+            let mut current = match self.current.take() {
+                Some(current) => current,
+                None => self
+                    .client
+                    .read_rows(
+                        ReadRowsRequest {
+                            read_stream: self.read_stream.to_string(),
+                            offset: self.offset,
+                        },
+                        self.retry.clone(),
+                    )
+                    .await?
+                    .into_inner(),
+            };
+            match current.message().await {
+                Ok(Some(response)) => {
+                    self.offset += response.row_count;
+                    self.current = Some(current);
+                    return Ok(Some(response));
+                }
+                Ok(None) => {
+                    self.finished = true;
+                    return Ok(None);
+                }
+                Err(status) => {
+                    if !setting.condition().should_retry(&status) {
+                        return Err(status.into());
+                    }
+                    let duration = backoff.next().ok_or(status)?;
+                    tracing::debug!("resume {} at offset {} after {:?}", self.read_stream, self.offset, duration);
+                    tokio::time::sleep(duration).await;
+                }
+            }
         }
     }
 }
 
-fn rows_to_chunk<T>(schema: ArrowSchema, rows: Rows) -> Result<VecDeque<T>, Error>
+/// RecordBatchStream reads the streams of the read session concurrently.
+/// The rows of the different streams are not ordered.
+pub struct RecordBatchStream {
+    inner: Pin<Box<dyn Stream<Item = Result<RecordBatch, Error>> + Send>>,
+    /// The read session. The number of the streams is `session.streams.len()`.
+    pub session: ReadSession,
+}
+
+impl RecordBatchStream {
+    pub(crate) fn new(
+        conn_pool: &ReadConnectionManager,
+        session: ReadSession,
+        retry: Option<RetrySetting>,
+    ) -> Result<Self, Box<Error>> {
+        let schema = match &session.schema {
+            Some(SessionSchema::ArrowSchema(schema)) => schema.serialized_schema.clone(),
+            // The schema is not set when the table is empty.
+            None if session.streams.is_empty() => vec![],
+            _ => return Err(Box::new(Error::InvalidSchemaFormat)),
+        };
+        let streams = session.streams.iter().map(|stream| {
+            let stream = ResumableStream::new(conn_pool.conn(), stream.name.to_string(), retry.clone());
+            let schema = schema.clone();
+            Box::pin(futures_util::stream::try_unfold(stream, move |mut stream| {
+                let schema = schema.clone();
+                async move {
+                    let batches = match stream.next().await? {
+                        Some(response) => match response.rows {
+                            Some(Rows::ArrowRecordBatch(rows)) => decode_record_batches(&schema, &rows)?,
+                            Some(_) => return Err(Error::InvalidDataFormat),
+                            None => vec![],
+                        },
+                        None => return Ok(None),
+                    };
+                    Ok(Some((
+                        futures_util::stream::iter(batches.into_iter().map(Ok::<_, Error>)),
+                        stream,
+                    )))
+                }
+            }))
+        });
+        Ok(Self {
+            inner: Box::pin(futures_util::stream::select_all(streams).try_flatten()),
+            session,
+        })
+    }
+
+    /// into_rows decodes the record batches into the rows.
+    #[allow(clippy::result_large_err)]
+    pub fn into_rows<T>(self) -> impl Stream<Item = Result<T, Error>> + Send
+    where
+        T: StructDecodable + Send,
+    {
+        self.map_ok(|batch| {
+            futures_util::stream::iter(
+                (0..batch.num_rows()).map(move |row_no| T::decode_arrow(batch.columns(), row_no).map_err(Error::from)),
+            )
+        })
+        .try_flatten()
+    }
+}
+
+impl Stream for RecordBatchStream {
+    type Item = Result<RecordBatch, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+fn decode_record_batches(schema: &[u8], rows: &ArrowRecordBatch) -> Result<Vec<RecordBatch>, ArrowError> {
+    let mut rows_with_schema = schema.to_vec();
+    rows_with_schema.extend_from_slice(&rows.serialized_record_batch);
+    StreamReader::try_new(Cursor::new(rows_with_schema), None)?.collect()
+}
+
+fn rows_to_chunk<T>(schema: ArrowSchema, rows: Rows) -> Result<VecDeque<T>, Box<Error>>
 where
     T: StructDecodable,
 {
     match rows {
         Rows::ArrowRecordBatch(rows) => {
-            let mut rows_with_schema = schema.serialized_schema;
-            rows_with_schema.extend_from_slice(&rows.serialized_record_batch);
-            let rows = Cursor::new(rows_with_schema);
-            let rows: StreamReader<BufReader<Cursor<Vec<u8>>>> = StreamReader::try_new(rows, None)?;
             let mut chunk: VecDeque<T> = VecDeque::new();
-            for row in rows {
-                let row = row?;
+            let batches = decode_record_batches(&schema.serialized_schema, &rows).map_err(|e| Box::new(e.into()))?;
+            for row in batches {
                 for row_no in 0..row.num_rows() {
-                    chunk.push_back(T::decode_arrow(row.columns(), row_no)?)
+                    chunk.push_back(T::decode_arrow(row.columns(), row_no).map_err(|e| Box::new(e.into()))?)
                 }
             }
             Ok(chunk)
         }
-        _ => Err(Error::InvalidDataFormat),
+        _ => Err(Box::new(Error::InvalidDataFormat)),
     }
 }
 
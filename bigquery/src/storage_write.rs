@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::Duration;
+
+use prost::Message;
+use prost_types::DescriptorProto;
+use tokio::sync::mpsc;
+
+use google_cloud_gax::create_request;
+use google_cloud_gax::grpc::{Code, Status, Streaming};
+use google_cloud_gax::retry::{invoke_fn, RetrySetting};
+use google_cloud_googleapis::cloud::bigquery::storage::v1::append_rows_request::{ProtoData, Rows};
+use google_cloud_googleapis::cloud::bigquery::storage::v1::append_rows_response::Response;
+use google_cloud_googleapis::cloud::bigquery::storage::v1::{
+    AppendRowsRequest, AppendRowsResponse, ProtoRows, ProtoSchema, RowError,
+};
+use google_cloud_googleapis::rpc;
+
+use crate::grpc::apiv1::bigquery_client::StreamingWriteClient;
+use crate::http::table::TableReference;
+
+/// The max size of an AppendRows request.
+pub const MAX_REQUEST_SIZE: usize = 10 * 1024 * 1024;
+
+/// The room for the fields of the request other than the rows and the schema.
+const REQUEST_OVERHEAD: usize = 1024;
+
+fn default_setting() -> RetrySetting {
+    RetrySetting {
+        from_millis: 50,
+        max_delay: Some(Duration::from_secs(10)),
+        factor: 1u64,
+        take: 5,
+        codes: vec![Code::Unavailable, Code::Unknown, Code::Aborted, Code::Internal],
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    GRPC(#[from] Status),
+    #[error("row is larger than the request limit: index={index}, size={size}")]
+    RowTooLarge { index: usize, size: usize },
+    #[error("rows are rejected: code={}, message={}, row_errors={row_errors:?}", .status.code, .status.message)]
+    Rejected {
+        status: rpc::Status,
+        /// The errors of the rows. The index is the one in the rows passed to `append`.
+        row_errors: Vec<RowError>,
+        /// The requests appended before the rejected one.
+        appended: Vec<AppendResult>,
+    },
+}
+
+/// AppendResult is the result of an AppendRows request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AppendResult {
+    /// The indices of the rows appended by the request in the rows passed to `append`.
+    pub rows: Range<usize>,
+    /// The offset of the first row. It is not set for the default stream.
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct StorageWriterOption {
+    retry_setting: Option<RetrySetting>,
+    trace_id: String,
+}
+
+impl StorageWriterOption {
+    /// The retry of the requests on the transient failures. The connection is reestablished for the retry.
+    pub fn with_retry_setting(mut self, value: RetrySetting) -> Self {
+        self.retry_setting = Some(value);
+        self
+    }
+
+    /// The id of the writer shown in the logs of BigQuery.
+    pub fn with_trace_id(mut self, value: impl Into<String>) -> Self {
+        self.trace_id = value.into();
+        self
+    }
+}
+
+struct Connection {
+    sender: mpsc::UnboundedSender<AppendRowsRequest>,
+    responses: Streaming<AppendRowsResponse>,
+}
+
+impl Connection {
+    async fn receive(&mut self) -> Result<AppendRowsResponse, Status> {
+        self.responses
+            .message()
+            .await?
+            .ok_or_else(|| Status::unavailable("append rows stream is closed"))
+    }
+}
+
+/// StorageWriter appends the rows to the default stream of the table by the Storage Write API.
+/// The rows are the protocol buffer messages described by the descriptor matching the table schema.
+/// The rows are committed immediately and the delivery is at least once, the retried rows may be duplicated.
+/// https://cloud.google.com/bigquery/docs/write-api#default_stream
+pub struct StorageWriter {
+    client: StreamingWriteClient,
+    write_stream: String,
+    descriptor: DescriptorProto,
+    option: StorageWriterOption,
+    connection: Option<Connection>,
+}
+
+impl StorageWriter {
+    pub(crate) fn new(
+        client: StreamingWriteClient,
+        table: &TableReference,
+        descriptor: DescriptorProto,
+        option: StorageWriterOption,
+    ) -> Self {
+        Self {
+            client,
+            write_stream: format!("{}/streams/_default", table.resource()),
+            descriptor,
+            option,
+            connection: None,
+        }
+    }
+
+    /// append appends the messages. They are split into the requests under `MAX_REQUEST_SIZE`.
+    pub async fn append<M: Message>(&mut self, rows: &[M]) -> Result<Vec<AppendResult>, Error> {
+        self.append_serialized(rows.iter().map(|row| row.encode_to_vec()).collect())
+            .await
+    }
+
+    /// append_serialized appends the serialized messages like the dynamic messages.
+    pub async fn append_serialized(&mut self, rows: Vec<Vec<u8>>) -> Result<Vec<AppendResult>, Error> {
+        let sizes: Vec<usize> = rows.iter().map(|row| row.len()).collect();
+        let batches = split_batches(&sizes, self.max_rows_size()).map_err(|e| *e)?;
+        let mut rows = rows.into_iter();
+        let mut results = Vec::with_capacity(batches.len());
+        for batch in batches {
+            let serialized_rows = rows.by_ref().take(batch.len()).collect();
+            let response = self.send_with_retry(ProtoRows { serialized_rows }).await?;
+            if !response.row_errors.is_empty() || matches!(response.response, Some(Response::Error(_))) {
+                let status = match response.response {
+                    Some(Response::Error(status)) => status,
+                    _ => rpc::Status {
+                        code: Code::InvalidArgument as i32,
+                        message: "row errors".to_string(),
+                        details: vec![],
+                    },
+                };
+                let row_errors = response
+                    .row_errors
+                    .into_iter()
+                    .map(|mut e| {
+                        e.index += batch.start as i64;
+                        e
+                    })
+                    .collect();
+                return Err(Error::Rejected {
+                    status,
+                    row_errors,
+                    appended: results,
+                });
+            }
+            let offset = match response.response {
+                Some(Response::AppendResult(result)) => result.offset,
+                _ => None,
+            };
+            results.push(AppendResult { rows: batch, offset });
+        }
+        Ok(results)
+    }
+
+    /// The max size of the rows in a request. The first request of a connection has the schema.
+    fn max_rows_size(&self) -> usize {
+        MAX_REQUEST_SIZE.saturating_sub(self.descriptor.encoded_len() + self.write_stream.len() + REQUEST_OVERHEAD)
+    }
+
+    async fn send_with_retry(&mut self, rows: ProtoRows) -> Result<AppendRowsResponse, Status> {
+        let setting = self.option.retry_setting.clone().unwrap_or_else(default_setting);
+        let rows = &rows;
+        invoke_fn(
+            Some(setting),
+            |writer: &mut Self| async move {
+                match writer.send(rows.clone()).await {
+                    Ok(response) => Ok(response),
+                    Err(e) => {
+                        tracing::debug!("append rows failed, the connection is reestablished: {e:?}");
+                        writer.connection = None;
+                        Err((e, writer))
+                    }
+                }
+            },
+            self,
+        )
+        .await
+    }
+
+    async fn send(&mut self, rows: ProtoRows) -> Result<AppendRowsResponse, Status> {
+        let mut request = AppendRowsRequest {
+            write_stream: "".to_string(),
+            offset: None,
+            trace_id: self.option.trace_id.clone(),
+            missing_value_interpretations: HashMap::new(),
+            rows: Some(Rows::ProtoRows(ProtoData {
+                writer_schema: None,
+                rows: Some(rows),
+            })),
+        };
+        match self.connection.as_mut() {
+            Some(connection) => {
+                connection
+                    .sender
+                    .send(request)
+                    .map_err(|_| Status::unavailable("append rows stream is closed"))?;
+                connection.receive().await
+            }
+            None => {
+                // The first request of the connection has the stream and the schema.
+                request.write_stream = self.write_stream.clone();
+                if let Some(Rows::ProtoRows(data)) = request.rows.as_mut() {
+                    data.writer_schema = Some(ProtoSchema {
+                        proto_descriptor: Some(self.descriptor.clone()),
+                    });
+                }
+                let (sender, receiver) = mpsc::unbounded_channel();
+                // Sends before the call because the call waits for the response headers.
+                let _ = sender.send(request);
+                let requests = futures_util::stream::unfold(receiver, |mut receiver| async move {
+                    receiver.recv().await.map(|request| (request, receiver))
+                });
+                let request = create_request(format!("write_stream={}", self.write_stream), requests);
+                let responses = self.client.append_rows(request).await?.into_inner();
+                self.connection.insert(Connection { sender, responses }).receive().await
+            }
+        }
+    }
+}
+
+/// split_batches splits the rows into the ranges whose encoded size is under the max size.
+fn split_batches(sizes: &[usize], max_size: usize) -> Result<Vec<Range<usize>>, Box<Error>> {
+    let mut batches = vec![];
+    let mut start = 0;
+    let mut batch_size = 0;
+    for (index, size) in sizes.iter().enumerate() {
+        // The encoded size of the bytes field is the tag, the length and the value.
+        let encoded = 1 + prost::encoding::encoded_len_varint(*size as u64) + size;
+        if encoded > max_size {
+            return Err(Box::new(Error::RowTooLarge { index, size: *size }));
+        }
+        if batch_size + encoded > max_size {
+            batches.push(start..index);
+            start = index;
+            batch_size = 0;
+        }
+        batch_size += encoded;
+    }
+    if start < sizes.len() {
+        batches.push(start..sizes.len());
+    }
+    Ok(batches)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use crate::storage_write::{split_batches, Error};
+
+    #[test]
+    fn test_split_batches() {
+        assert_eq!(split_batches(&[], 100).unwrap(), Vec::<Range<usize>>::new());
+        // each row is 12 bytes with the tag and the length
+        assert_eq!(split_batches(&[10, 10, 10], 36).unwrap(), vec![0..3]);
+        assert_eq!(split_batches(&[10, 10, 10], 35).unwrap(), vec![0..2, 2..3]);
+        assert_eq!(split_batches(&[10, 0, 10], 14).unwrap(), vec![0..2, 2..3]);
+        assert!(matches!(
+            split_batches(&[10, 40], 36).map_err(|e| *e),
+            Err(Error::RowTooLarge { index: 1, size: 40 })
+        ));
+    }
+}
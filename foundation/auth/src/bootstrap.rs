@@ -0,0 +1,310 @@
+//! Shared credentials and settings of the clients of the service crates.
+//!
+//! `ClientBootstrap` resolves the credentials once. The resolved `Bootstrap` is passed to
+//! `ClientConfig::with_bootstrap` of each service crate, so all the clients share one token source,
+//! one project id and one quota project.
+//!
+//! ```no_run
+//! use google_cloud_auth::bootstrap::ClientBootstrap;
+//!
+//! # async fn run() -> Result<(), google_cloud_auth::error::Error> {
+//! let bootstrap = ClientBootstrap::new()
+//!     .with_credentials_file("/path/to/service-account.json")
+//!     .with_quota_project("billing-project")
+//!     .with_endpoint_overrides([("spanner", "spanner.us-central1.rep.googleapis.com")])
+//!     .build()
+//!     .await?;
+//! let project_id = bootstrap.project_id();
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use google_cloud_token::{TokenSource, TokenSourceProvider};
+
+use crate::credentials::CredentialsFile;
+use crate::error::Error;
+use crate::project::Config;
+use crate::token::DefaultTokenSourceProvider;
+
+/// The scope covering all the services, because one token source is shared by all the clients.
+const SCOPES: [&str; 1] = ["https://www.googleapis.com/auth/cloud-platform"];
+
+/// The environment variables of the emulator hosts read by `ClientBootstrap::build`.
+const EMULATOR_HOST_VARIABLES: [(&str, &str); 3] = [
+    ("spanner", "SPANNER_EMULATOR_HOST"),
+    ("pubsub", "PUBSUB_EMULATOR_HOST"),
+    ("storage", "STORAGE_EMULATOR_HOST"),
+];
+
+enum Credentials {
+    Adc,
+    File(String),
+    Json(String),
+    Provider(Box<dyn TokenSourceProvider>),
+}
+
+/// ClientBootstrap is the builder of `Bootstrap`. The credentials are resolved by ADC by default.
+pub struct ClientBootstrap {
+    credentials: Credentials,
+    project_id: Option<String>,
+    quota_project: Option<String>,
+    endpoints: HashMap<String, String>,
+}
+
+impl Default for ClientBootstrap {
+    fn default() -> Self {
+        Self {
+            credentials: Credentials::Adc,
+            project_id: None,
+            quota_project: None,
+            endpoints: HashMap::new(),
+        }
+    }
+}
+
+impl ClientBootstrap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// with_adc resolves the credentials by the Application Default Credentials.
+    pub fn with_adc(mut self) -> Self {
+        self.credentials = Credentials::Adc;
+        self
+    }
+
+    /// with_credentials_file uses the credentials file like the service account key at the path.
+    pub fn with_credentials_file(mut self, path: impl Into<String>) -> Self {
+        self.credentials = Credentials::File(path.into());
+        self
+    }
+
+    /// with_credentials_json uses the content of the credentials file.
+    pub fn with_credentials_json(mut self, json: impl Into<String>) -> Self {
+        self.credentials = Credentials::Json(json.into());
+        self
+    }
+
+    /// with_token_provider uses the token source provider instead of resolving the credentials.
+    /// The project id is not resolved, so set it by `with_project_id` if the clients require it.
+    pub fn with_token_provider(mut self, provider: Box<dyn TokenSourceProvider>) -> Self {
+        self.credentials = Credentials::Provider(provider);
+        self
+    }
+
+    /// with_project_id overrides the project id resolved from the credentials.
+    pub fn with_project_id(mut self, project_id: impl Into<String>) -> Self {
+        self.project_id = Some(project_id.into());
+        self
+    }
+
+    /// with_quota_project sets the project billed for the quota of the requests of all the clients.
+    pub fn with_quota_project(mut self, project_id: impl Into<String>) -> Self {
+        self.quota_project = Some(project_id.into());
+        self
+    }
+
+    /// with_endpoint_overrides overrides the endpoints by the service name like `spanner`, `pubsub` and `storage`.
+    pub fn with_endpoint_overrides<K, V>(mut self, endpoints: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.endpoints.extend(
+            endpoints
+                .into_iter()
+                .map(|(service, endpoint)| (service.into(), endpoint.into())),
+        );
+        self
+    }
+
+    /// build resolves the credentials and reads the emulator hosts from the environment variables
+    /// `SPANNER_EMULATOR_HOST`, `PUBSUB_EMULATOR_HOST` and `STORAGE_EMULATOR_HOST`.
+    /// The credentials are resolved even if the emulators are used, so use `with_token_provider` with
+    /// `NopeTokenSourceProvider` where no credentials are available.
+    pub async fn build(self) -> Result<Bootstrap, Error> {
+        let config = Config {
+            audience: None,
+            scopes: Some(&SCOPES),
+            sub: None,
+        };
+        let (provider, project_id, credentials): Resolved = match self.credentials {
+            Credentials::Adc => resolved(DefaultTokenSourceProvider::new(config).await?),
+            Credentials::File(path) => {
+                let credentials = CredentialsFile::new_from_file(path).await?;
+                resolved(DefaultTokenSourceProvider::new_with_credentials(config, Box::new(credentials)).await?)
+            }
+            Credentials::Json(json) => {
+                let credentials = CredentialsFile::new_from_str(&json).await?;
+                resolved(DefaultTokenSourceProvider::new_with_credentials(config, Box::new(credentials)).await?)
+            }
+            Credentials::Provider(provider) => (provider, None, None),
+        };
+        let emulator_hosts = EMULATOR_HOST_VARIABLES
+            .iter()
+            .filter_map(|(service, variable)| match std::env::var(variable) {
+                Ok(host) if !host.trim().is_empty() => Some((service.to_string(), host.trim().to_string())),
+                _ => None,
+            })
+            .collect();
+        Ok(Bootstrap {
+            provider: Arc::from(provider),
+            project_id: self.project_id.or(project_id),
+            quota_project: self.quota_project,
+            endpoints: self.endpoints,
+            emulator_hosts,
+            credentials,
+        })
+    }
+}
+
+type Resolved = (Box<dyn TokenSourceProvider>, Option<String>, Option<Box<CredentialsFile>>);
+
+fn resolved(provider: DefaultTokenSourceProvider) -> Resolved {
+    let project_id = provider.project_id.clone();
+    let credentials = provider.source_credentials.clone();
+    (Box::new(provider), project_id, credentials)
+}
+
+/// Bootstrap is the credentials and the settings shared by the clients of the service crates.
+pub struct Bootstrap {
+    provider: Arc<dyn TokenSourceProvider>,
+    project_id: Option<String>,
+    quota_project: Option<String>,
+    endpoints: HashMap<String, String>,
+    emulator_hosts: HashMap<String, String>,
+    credentials: Option<Box<CredentialsFile>>,
+}
+
+impl Debug for Bootstrap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Bootstrap")
+            .field("project_id", &self.project_id)
+            .field("quota_project", &self.quota_project)
+            .field("endpoints", &self.endpoints)
+            .field("emulator_hosts", &self.emulator_hosts)
+            .finish()
+    }
+}
+
+impl Bootstrap {
+    /// token_source_provider returns the provider sharing the token source with all the other clients.
+    pub fn token_source_provider(&self) -> Box<dyn TokenSourceProvider> {
+        Box::new(SharedTokenSourceProvider {
+            inner: self.provider.clone(),
+        })
+    }
+
+    /// project_id returns the project id set by `with_project_id` or resolved from the credentials.
+    pub fn project_id(&self) -> Option<&str> {
+        self.project_id.as_deref()
+    }
+
+    pub fn quota_project(&self) -> Option<&str> {
+        self.quota_project.as_deref()
+    }
+
+    /// endpoint returns the endpoint override of the service like `spanner`.
+    pub fn endpoint(&self, service: &str) -> Option<&str> {
+        self.endpoints.get(service).map(String::as_str)
+    }
+
+    /// emulator_host returns the emulator host of the service read from the environment variable.
+    /// The clients connect to the emulator without the credentials when it is set.
+    pub fn emulator_host(&self, service: &str) -> Option<&str> {
+        self.emulator_hosts.get(service).map(String::as_str)
+    }
+
+    /// credentials returns the credentials file used to create the token source, if any.
+    pub fn credentials(&self) -> Option<&CredentialsFile> {
+        self.credentials.as_deref()
+    }
+}
+
+#[derive(Debug)]
+struct SharedTokenSourceProvider {
+    inner: Arc<dyn TokenSourceProvider>,
+}
+
+impl TokenSourceProvider for SharedTokenSourceProvider {
+    fn token_source(&self) -> Arc<dyn TokenSource> {
+        self.inner.token_source()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+
+    use google_cloud_token::{TokenSource, TokenSourceProvider};
+
+    use crate::bootstrap::ClientBootstrap;
+
+    #[derive(Debug)]
+    struct StaticTokenSource;
+
+    #[async_trait]
+    impl TokenSource for StaticTokenSource {
+        async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            Ok("Bearer token".to_string())
+        }
+    }
+
+    #[derive(Debug)]
+    struct StaticTokenSourceProvider {
+        ts: Arc<StaticTokenSource>,
+    }
+
+    impl TokenSourceProvider for StaticTokenSourceProvider {
+        fn token_source(&self) -> Arc<dyn TokenSource> {
+            self.ts.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_build_with_token_provider() {
+        let bootstrap = temp_env::async_with_vars(
+            [
+                ("SPANNER_EMULATOR_HOST", Some(" localhost:9010 ")),
+                ("PUBSUB_EMULATOR_HOST", Some("")),
+                ("STORAGE_EMULATOR_HOST", None),
+            ],
+            ClientBootstrap::new()
+                .with_token_provider(Box::new(StaticTokenSourceProvider {
+                    ts: Arc::new(StaticTokenSource),
+                }))
+                .with_project_id("project")
+                .with_quota_project("quota-project")
+                .with_endpoint_overrides([("pubsub", "pubsub.example.com")])
+                .build(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(bootstrap.project_id(), Some("project"));
+        assert_eq!(bootstrap.quota_project(), Some("quota-project"));
+        assert_eq!(bootstrap.endpoint("pubsub"), Some("pubsub.example.com"));
+        assert_eq!(bootstrap.endpoint("spanner"), None);
+        assert_eq!(bootstrap.emulator_host("spanner"), Some("localhost:9010"));
+        assert_eq!(bootstrap.emulator_host("pubsub"), None);
+        assert_eq!(bootstrap.emulator_host("storage"), None);
+        assert!(bootstrap.credentials().is_none());
+
+        // all the clients share the token source
+        let ts1 = bootstrap.token_source_provider().token_source();
+        let ts2 = bootstrap.token_source_provider().token_source();
+        assert!(Arc::ptr_eq(&ts1, &ts2));
+        assert_eq!(ts1.token().await.unwrap(), "Bearer token");
+    }
+
+    #[tokio::test]
+    async fn test_build_with_invalid_credentials_json() {
+        let result = ClientBootstrap::new().with_credentials_json("{").build().await;
+        assert!(result.is_err());
+    }
+}
@@ -51,4 +51,17 @@ pub enum Error {
 
     #[error("No target_audience Found in the private claims")]
     NoTargetAudienceFound,
+
+    /// The domain-wide delegation requires the token exchange with the scopes, so the self-signed JWT can not be used.
+    #[error("scopes is required to act as the subject {0}")]
+    ScopeRequiredForSubject(String),
+
+    /// The token endpoint rejected the JWT assertion like `unauthorized_client` for the scopes not delegated.
+    #[error("token endpoint responded with {status}: error={error}, description={description}, scopes={scopes}")]
+    TokenExchange {
+        status: u16,
+        error: String,
+        description: String,
+        scopes: String,
+    },
 }
@@ -0,0 +1,33 @@
+/// client_builder returns the builder of the HTTPS client with the TLS stack selected by the features.
+/// rustls takes precedence when both `default-tls` and `rustls-tls` are enabled by the feature unification.
+///
+/// The additional root certificates and the client identity for the mutual TLS are set on the builder.
+///
+/// ```no_run
+/// # fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let pem = std::fs::read("ca.pem")?;
+/// let client = google_cloud_auth::http::client_builder()
+///     .add_root_certificate(reqwest::Certificate::from_pem(&pem)?)
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn client_builder() -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder();
+    #[cfg(feature = "rustls-tls")]
+    let builder = builder.use_rustls_tls();
+    builder
+}
+
+#[cfg(all(test, any(feature = "default-tls", feature = "rustls-tls")))]
+mod tests {
+    use reqwest::Certificate;
+
+    use crate::http::client_builder;
+
+    #[test]
+    fn test_client_builder() {
+        let certificate = Certificate::from_pem(include_bytes!("../testdata/cert.pem")).unwrap();
+        assert!(client_builder().add_root_certificate(certificate).build().is_ok());
+    }
+}
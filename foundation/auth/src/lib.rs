@@ -1,5 +1,7 @@
+pub mod bootstrap;
 pub mod credentials;
 pub mod error;
+pub mod http;
 pub mod idtoken;
 mod misc;
 pub mod project;
@@ -19,6 +19,8 @@ const EXTERNAL_ACCOUNT_KEY: &str = "external_account";
 pub struct Config<'a> {
     pub audience: Option<&'a str>,
     pub scopes: Option<&'a [&'a str]>,
+    /// The user impersonated by the service account with the domain-wide delegation.
+    /// It is placed in the `sub` claim of the JWT assertion and requires the scopes.
     pub sub: Option<&'a str>,
 }
 
@@ -118,6 +120,18 @@ async fn credentials_from_json_with_params(
 ) -> Result<Box<dyn TokenSource>, error::Error> {
     match credentials.tp.as_str() {
         SERVICE_ACCOUNT_KEY => {
+            if let Some(sub) = config.sub {
+                // The domain-wide delegation requires the token exchange, so the self-signed JWT is never used.
+                if config.scopes.is_none() {
+                    return Err(error::Error::ScopeRequiredForSubject(sub.to_string()));
+                }
+                let source = OAuth2ServiceAccountTokenSource::new(
+                    credentials,
+                    config.scopes_to_string(" ").as_str(),
+                    Some(sub),
+                )?;
+                return Ok(Box::new(source));
+            }
             match config.audience {
                 None => {
                     if config.scopes.is_none() {
@@ -3,7 +3,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 
-use google_cloud_token::{TokenSource, TokenSourceProvider};
+use google_cloud_token::{Token as TokenWithExpiry, TokenCallback, TokenSource, TokenSourceProvider};
 
 use crate::credentials::CredentialsFile;
 use crate::error::Error;
@@ -43,6 +43,15 @@ impl Token {
     }
 }
 
+impl From<&Token> for TokenWithExpiry {
+    fn from(token: &Token) -> Self {
+        Self {
+            value: token.value(),
+            expires_at: token.expiry,
+        }
+    }
+}
+
 pub struct DefaultTokenSourceProvider {
     ts: Arc<DefaultTokenSource>,
     pub project_id: Option<String>,
@@ -108,4 +117,18 @@ impl TokenSource for DefaultTokenSource {
         let token = self.inner.token().await?;
         Ok(format!("Bearer {0}", token.access_token))
     }
+
+    async fn token_with_expiry(&self) -> Result<TokenWithExpiry, Box<dyn std::error::Error + Send + Sync>> {
+        let token = self.inner.token().await?;
+        Ok((&token).into())
+    }
+
+    async fn force_refresh(&self) -> Result<TokenWithExpiry, Box<dyn std::error::Error + Send + Sync>> {
+        let token = self.inner.force_refresh().await?;
+        Ok((&token).into())
+    }
+
+    fn on_refresh(&self, callback: TokenCallback) {
+        self.inner.on_refresh(callback)
+    }
 }
@@ -51,6 +51,8 @@ struct RequestBody<'a> {
 #[async_trait]
 impl TokenSource for UserAccountTokenSource {
     async fn token(&self) -> Result<Token, Error> {
+        // The expiry is counted from the time before the request, so the token is refreshed before it expires.
+        let now = time::OffsetDateTime::now_utc();
         let data = RequestBody {
             client_id: &self.client_id,
             client_secret: &self.client_secret,
@@ -67,6 +69,6 @@ impl TokenSource for UserAccountTokenSource {
             .json::<InternalToken>()
             .await?;
 
-        return Ok(it.to_token(time::OffsetDateTime::now_utc()));
+        return Ok(it.to_token(now));
     }
 }
@@ -36,6 +36,8 @@ impl ComputeTokenSource {
 #[async_trait]
 impl TokenSource for ComputeTokenSource {
     async fn token(&self) -> Result<Token, Error> {
+        // The expiry is counted from the time before the request, so the token is refreshed before it expires.
+        let now = time::OffsetDateTime::now_utc();
         let it = self
             .client
             .get(self.token_url.to_string())
@@ -44,6 +46,6 @@ impl TokenSource for ComputeTokenSource {
             .await?
             .json::<InternalToken>()
             .await?;
-        return Ok(it.to_token(time::OffsetDateTime::now_utc()));
+        return Ok(it.to_token(now));
     }
 }
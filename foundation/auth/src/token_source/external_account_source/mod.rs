@@ -61,6 +61,8 @@ impl ExternalAccountTokenSource {
 #[async_trait]
 impl TokenSource for ExternalAccountTokenSource {
     async fn token(&self) -> Result<Token, crate::error::Error> {
+        // The expiry is counted from the time before the request, so the token is refreshed before it expires.
+        let now = OffsetDateTime::now_utc();
         let subject_token_source = subject_token_source(self.audience.clone(), self.source.clone()).await?;
 
         let mut builder = self.client.post(&self.url);
@@ -89,7 +91,7 @@ impl TokenSource for ExternalAccountTokenSource {
             return Err(Error::UnexpectedStatusOnGetSubjectToken(status, detail).into());
         }
         let it = response.json::<InternalToken>().await?;
-        Ok(it.to_token(OffsetDateTime::now_utc()))
+        Ok(it.to_token(now))
     }
 }
 
@@ -2,6 +2,7 @@ use std::fmt::Debug;
 use std::time::Duration;
 
 use async_trait::async_trait;
+use google_cloud_token::TokenCallback;
 use jsonwebtoken;
 use serde::Deserialize;
 
@@ -21,10 +22,19 @@ pub mod external_account_source;
 #[async_trait]
 pub trait TokenSource: Send + Sync + Debug {
     async fn token(&self) -> Result<Token, Error>;
+
+    /// force_refresh mints the new token without using the cached token.
+    async fn force_refresh(&self) -> Result<Token, Error> {
+        self.token().await
+    }
+
+    /// on_refresh registers the callback invoked when the new token is minted.
+    /// Only the token sources that cache the token invoke the callback.
+    fn on_refresh(&self, _callback: TokenCallback) {}
 }
 
 pub(crate) fn default_http_client() -> reqwest::Client {
-    reqwest::Client::builder()
+    crate::http::client_builder()
         .timeout(Duration::from_secs(3))
         .build()
         .unwrap()
@@ -83,6 +93,7 @@ impl InternalIdToken {
 mod tests {
     use crate::credentials::CredentialsFile;
     use crate::error::Error;
+    use crate::project::{create_token_source_from_credentials, Config};
     use crate::token_source::service_account_token_source::{
         OAuth2ServiceAccountTokenSource, ServiceAccountTokenSource,
     };
@@ -99,6 +110,22 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_subject_requires_scopes() {
+        let credentials = CredentialsFile::new_from_str(
+            r#"{"type": "service_account", "client_email": "sa@example.iam.gserviceaccount.com"}"#,
+        )
+        .await
+        .unwrap();
+        let config = Config {
+            audience: Some("https://spanner.googleapis.com/"),
+            scopes: None,
+            sub: Some("user@example.com"),
+        };
+        let result = create_token_source_from_credentials(&credentials, &config).await;
+        assert!(matches!(result, Err(Error::ScopeRequiredForSubject(sub)) if sub == "user@example.com"));
+    }
+
     #[tokio::test]
     async fn test_oauth2_token_source() -> Result<(), Error> {
         let credentials = CredentialsFile::new().await?;
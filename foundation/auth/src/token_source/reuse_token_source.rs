@@ -1,14 +1,26 @@
+use std::fmt::{Debug, Formatter};
+
 use async_trait::async_trait;
 
+use google_cloud_token::TokenCallback;
+
 use crate::error::Error;
 use crate::token::Token;
 use crate::token_source::TokenSource;
 
-#[derive(Debug)]
 pub struct ReuseTokenSource {
     target: Box<dyn TokenSource>,
     current_token: std::sync::RwLock<Token>,
     guard: tokio::sync::Mutex<()>,
+    callbacks: std::sync::RwLock<Vec<TokenCallback>>,
+}
+
+impl Debug for ReuseTokenSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReuseTokenSource")
+            .field("target", &self.target)
+            .finish_non_exhaustive()
+    }
 }
 
 impl ReuseTokenSource {
@@ -17,6 +29,7 @@ impl ReuseTokenSource {
             target,
             current_token: std::sync::RwLock::new(token),
             guard: tokio::sync::Mutex::new(()),
+            callbacks: std::sync::RwLock::new(vec![]),
         }
     }
 }
@@ -34,11 +47,16 @@ impl TokenSource for ReuseTokenSource {
         if let Some(token) = self.r_lock_token() {
             return Ok(token);
         }
+        self.refresh().await
+    }
 
-        let token = self.target.token().await?;
-        tracing::debug!("token refresh success : expiry={:?}", token.expiry);
-        *self.current_token.write().unwrap() = token.clone();
-        Ok(token)
+    async fn force_refresh(&self) -> Result<Token, Error> {
+        let _locking = self.guard.lock().await;
+        self.refresh().await
+    }
+
+    fn on_refresh(&self, callback: TokenCallback) {
+        self.callbacks.write().unwrap().push(callback);
     }
 }
 
@@ -51,12 +69,29 @@ impl ReuseTokenSource {
             None
         }
     }
+
+    /// refresh must be called with the guard locked.
+    async fn refresh(&self) -> Result<Token, Error> {
+        let token = self.target.token().await?;
+        tracing::debug!("token refresh success : expiry={:?}", token.expiry);
+        *self.current_token.write().unwrap() = token.clone();
+
+        let callbacks = self.callbacks.read().unwrap().clone();
+        if !callbacks.is_empty() {
+            let minted = (&token).into();
+            for callback in callbacks {
+                callback(&minted);
+            }
+        }
+        Ok(token)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use std::fmt::Debug;
-    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
 
     use async_trait::async_trait;
     use time::OffsetDateTime;
@@ -88,6 +123,49 @@ mod test {
         let _ = tracing_subscriber::fmt().with_env_filter(filter).try_init();
     }
 
+    #[derive(Debug, Default)]
+    struct CountingTokenSource {
+        count: AtomicUsize,
+    }
+    #[async_trait]
+    impl TokenSource for CountingTokenSource {
+        async fn token(&self) -> Result<Token, Error> {
+            let count = self.count.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(Token {
+                access_token: count.to_string(),
+                token_type: "Bearer".to_string(),
+                expiry: Some(OffsetDateTime::now_utc() + time::Duration::seconds(100)),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_force_refresh() {
+        let target = Box::<CountingTokenSource>::default();
+        let first = target.token().await.unwrap();
+        let ts = ReuseTokenSource::new(target, first);
+
+        let minted = Arc::new(Mutex::new(vec![]));
+        let minted_clone = minted.clone();
+        ts.on_refresh(Arc::new(move |token: &google_cloud_token::Token| {
+            minted_clone.lock().unwrap().push(token.clone());
+        }));
+
+        // valid token is reused
+        assert_eq!(ts.token().await.unwrap().access_token, "1");
+        assert!(minted.lock().unwrap().is_empty());
+
+        // force refresh mints the new token and notifies it
+        let refreshed = ts.force_refresh().await.unwrap();
+        assert_eq!(refreshed.access_token, "2");
+        assert_eq!(ts.token().await.unwrap().access_token, "2");
+
+        let minted = minted.lock().unwrap();
+        assert_eq!(minted.len(), 1);
+        assert_eq!(minted[0].value, "Bearer 2");
+        assert_eq!(minted[0].expires_at, refreshed.expiry);
+    }
+
     #[tokio::test]
     async fn test_all_valid() {
         let ts = Box::new(EmptyTokenSource {
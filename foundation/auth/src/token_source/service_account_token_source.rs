@@ -97,6 +97,31 @@ impl TokenSource for ServiceAccountTokenSource {
     }
 }
 
+/// The error response of the token endpoint.
+/// https://datatracker.ietf.org/doc/html/rfc6749#section-5.2
+#[derive(Deserialize, Default)]
+struct OAuth2ErrorResponse {
+    #[serde(default)]
+    error: String,
+    #[serde(default)]
+    error_description: String,
+}
+
+/// token_exchange_error converts the error response of the token endpoint with the requested scopes,
+/// because the unauthorized scopes are the common cause of the domain-wide delegation failure.
+fn token_exchange_error(status: u16, body: &str, scopes: &str) -> Error {
+    let response: OAuth2ErrorResponse = serde_json::from_str(body).unwrap_or_else(|_| OAuth2ErrorResponse {
+        error: body.to_string(),
+        error_description: String::new(),
+    });
+    Error::TokenExchange {
+        status,
+        error: response.error,
+        description: response.error_description,
+        scopes: scopes.to_string(),
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Clone, Deserialize)]
 struct OAuth2Token {
@@ -113,6 +138,7 @@ pub struct OAuth2ServiceAccountTokenSource {
     pub pk_id: String,
     pub scopes: String,
     pub token_url: String,
+    /// The user impersonated with the domain-wide delegation.
     pub sub: Option<String>,
 
     pub client: reqwest::Client,
@@ -192,6 +218,12 @@ impl TokenSource for OAuth2ServiceAccountTokenSource {
             ("assertion", request_token.as_str()),
         ];
 
+        let response = self.client.post(self.token_url.as_str()).form(&form).send().await?;
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            return Err(token_exchange_error(status, &response.text().await?, &self.scopes));
+        }
+
         match self.use_id_token {
             true => {
                 let audience = claims
@@ -200,25 +232,45 @@ impl TokenSource for OAuth2ServiceAccountTokenSource {
                     .ok_or(Error::NoTargetAudienceFound)?
                     .as_str()
                     .ok_or(Error::NoTargetAudienceFound)?;
-                Ok(self
-                    .client
-                    .post(self.token_url.as_str())
-                    .form(&form)
-                    .send()
-                    .await?
-                    .json::<InternalIdToken>()
-                    .await?
-                    .to_token(audience)?)
+                Ok(response.json::<InternalIdToken>().await?.to_token(audience)?)
+            }
+            false => Ok(response.json::<InternalToken>().await?.to_token(iat)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::token_source::service_account_token_source::token_exchange_error;
+
+    #[test]
+    fn test_token_exchange_error() {
+        let body = r#"{"error":"unauthorized_client","error_description":"Client is unauthorized to retrieve access tokens using this method, or client not authorized for any of the scopes requested."}"#;
+        let scopes = "https://www.googleapis.com/auth/admin.directory.user.readonly";
+        let err = token_exchange_error(401, body, scopes);
+        match &err {
+            Error::TokenExchange {
+                status,
+                error,
+                scopes: s,
+                ..
+            } => {
+                assert_eq!(*status, 401);
+                assert_eq!(error, "unauthorized_client");
+                assert_eq!(s, scopes);
+            }
+            _ => unreachable!("token exchange error is expected"),
+        }
+        assert!(err.to_string().contains(scopes));
+
+        // not a json
+        match token_exchange_error(502, "Bad Gateway", scopes) {
+            Error::TokenExchange { error, description, .. } => {
+                assert_eq!(error, "Bad Gateway");
+                assert!(description.is_empty());
             }
-            false => Ok(self
-                .client
-                .post(self.token_url.as_str())
-                .form(&form)
-                .send()
-                .await?
-                .json::<InternalToken>()
-                .await?
-                .to_token(iat)),
+            _ => unreachable!("token exchange error is expected"),
         }
     }
 }
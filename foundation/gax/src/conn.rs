@@ -2,13 +2,13 @@ use std::fmt::Debug;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 use http::header::AUTHORIZATION;
 use http::{HeaderValue, Request};
 use tonic::body::BoxBody;
-use tonic::transport::{Channel as TonicChannel, ClientTlsConfig, Endpoint};
+use tonic::transport::{Certificate, Channel as TonicChannel, ClientTlsConfig, Endpoint, Identity};
 use tonic::{Code, Status};
 use tower::filter::{AsyncFilter, AsyncFilterLayer, AsyncPredicate};
 use tower::util::Either;
@@ -16,16 +16,22 @@ use tower::{BoxError, ServiceBuilder};
 
 use google_cloud_token::{TokenSource, TokenSourceProvider};
 
+const QUOTA_PROJECT: &str = "x-goog-user-project";
+
 pub type Channel = Either<AsyncFilter<TonicChannel, AsyncAuthInterceptor>, TonicChannel>;
 
 #[derive(Clone, Debug)]
 pub struct AsyncAuthInterceptor {
     token_source: Arc<dyn TokenSource>,
+    quota_project: Option<HeaderValue>,
 }
 
 impl AsyncAuthInterceptor {
-    fn new(token_source: Arc<dyn TokenSource>) -> Self {
-        Self { token_source }
+    fn new(token_source: Arc<dyn TokenSource>, quota_project: Option<HeaderValue>) -> Self {
+        Self {
+            token_source,
+            quota_project,
+        }
     }
 }
 
@@ -35,6 +41,7 @@ impl AsyncPredicate<Request<BoxBody>> for AsyncAuthInterceptor {
 
     fn check(&mut self, request: Request<BoxBody>) -> Self::Future {
         let ts = self.token_source.clone();
+        let quota_project = self.quota_project.clone();
         Box::pin(async move {
             let token = ts
                 .token()
@@ -44,6 +51,9 @@ impl AsyncPredicate<Request<BoxBody>> for AsyncAuthInterceptor {
                 .map_err(|e| Status::new(Code::Unauthenticated, format!("token error: {e:?}")))?;
             let (mut parts, body) = request.into_parts();
             parts.headers.insert(AUTHORIZATION, token_header);
+            if let Some(quota_project) = quota_project {
+                parts.headers.insert(QUOTA_PROJECT, quota_project);
+            }
             Ok(Request::from_parts(parts, body))
         })
     }
@@ -59,6 +69,9 @@ pub enum Error {
 
     #[error("invalid emulator host: {0}")]
     InvalidEmulatorHOST(String),
+
+    #[error("invalid quota project: {0}")]
+    InvalidQuotaProject(String),
 }
 
 #[derive(Debug)]
@@ -91,6 +104,14 @@ where
 pub struct ConnectionOptions {
     pub timeout: Option<Duration>,
     pub connect_timeout: Option<Duration>,
+    /// The PEM encoded root certificates trusted in addition to the roots selected by the features.
+    /// It is not used for the emulator.
+    pub ca_certificate: Option<Certificate>,
+    /// The client certificate and key for the mutual TLS. It is not used for the emulator.
+    pub identity: Option<Identity>,
+    /// The project billed for the quota of the requests. It is sent as `x-goog-user-project`.
+    /// It is not used for the emulator.
+    pub quota_project: Option<String>,
 }
 
 impl ConnectionOptions {
@@ -105,11 +126,44 @@ impl ConnectionOptions {
         };
         endpoint
     }
+
+    fn tls_config(&self, domain_name: impl Into<String>) -> ClientTlsConfig {
+        let mut tls_config = ClientTlsConfig::new().domain_name(domain_name);
+        if let Some(ca_certificate) = &self.ca_certificate {
+            tls_config = tls_config.ca_certificate(ca_certificate.clone());
+        }
+        if let Some(identity) = &self.identity {
+            tls_config = tls_config.identity(identity.clone());
+        }
+        tls_config
+    }
+}
+
+/// PooledChannel is the channel of the pool. It is replaced with the new connection after the transport failure.
+#[derive(Debug)]
+struct PooledChannel {
+    channel: RwLock<Channel>,
+    generation: AtomicUsize,
+}
+
+impl PooledChannel {
+    fn new(channel: Channel) -> Self {
+        Self {
+            channel: RwLock::new(channel),
+            generation: AtomicUsize::new(0),
+        }
+    }
+
+    fn channel(&self) -> Channel {
+        self.channel.read().unwrap().clone()
+    }
 }
 
 #[derive(Debug)]
 pub struct ConnectionManager {
-    inner: AtomicRing<Channel>,
+    inner: AtomicRing<Arc<PooledChannel>>,
+    endpoint: Endpoint,
+    auth: Option<AsyncAuthInterceptor>,
 }
 
 impl<'a> ConnectionManager {
@@ -120,74 +174,82 @@ impl<'a> ConnectionManager {
         environment: &Environment,
         conn_options: &'a ConnectionOptions,
     ) -> Result<Self, Error> {
-        let conns = match environment {
+        let (pool_size, endpoint, auth) = match environment {
             Environment::GoogleCloud(ts_provider) => {
-                Self::create_connections(pool_size, domain_name, audience, ts_provider.as_ref(), conn_options).await?
+                let endpoint = TonicChannel::from_static(audience).tls_config(conn_options.tls_config(domain_name))?;
+                let quota_project = match &conn_options.quota_project {
+                    Some(project) => Some(
+                        HeaderValue::from_str(project).map_err(|_| Error::InvalidQuotaProject(project.to_string()))?,
+                    ),
+                    None => None,
+                };
+                // use GCP token per call
+                let auth = AsyncAuthInterceptor::new(ts_provider.token_source(), quota_project);
+                (pool_size, endpoint, Some(auth))
+            }
+            Environment::Emulator(host) => {
+                let endpoint = TonicChannel::from_shared(format!("http://{host}").into_bytes())
+                    .map_err(|_| Error::InvalidEmulatorHOST(host.to_string()))?;
+                (1, endpoint, None)
             }
-            Environment::Emulator(host) => Self::create_emulator_connections(host, conn_options).await?,
         };
+        let endpoint = conn_options.apply(endpoint);
+        let mut conns = Vec::with_capacity(pool_size);
+        for _i_ in 0..pool_size {
+            let con = Self::connect(endpoint.clone(), auth.clone()).await?;
+            conns.push(Arc::new(PooledChannel::new(con)));
+        }
         Ok(Self {
             inner: AtomicRing {
                 index: AtomicUsize::new(0),
                 values: conns,
             },
+            endpoint,
+            auth,
         })
     }
 
-    async fn create_connections(
-        pool_size: usize,
-        domain_name: impl Into<String>,
-        audience: &'static str,
-        ts_provider: &dyn TokenSourceProvider,
-        conn_options: &'a ConnectionOptions,
-    ) -> Result<Vec<Channel>, Error> {
-        let tls_config = ClientTlsConfig::new().domain_name(domain_name);
-        let mut conns = Vec::with_capacity(pool_size);
-
-        let ts = ts_provider.token_source();
+    async fn connect(
+        endpoint: Endpoint,
+        auth: Option<AsyncAuthInterceptor>,
+    ) -> Result<Channel, tonic::transport::Error> {
+        let channel = endpoint.connect().await?;
+        Ok(ServiceBuilder::new()
+            .option_layer(auth.map(AsyncFilterLayer::new))
+            .service(channel))
+    }
 
-        for _i_ in 0..pool_size {
-            let endpoint = TonicChannel::from_static(audience).tls_config(tls_config.clone())?;
-            let endpoint = conn_options.apply(endpoint);
-
-            let con = Self::connect(endpoint).await?;
-            // use GCP token per call
-            let auth_layer = Some(AsyncFilterLayer::new(AsyncAuthInterceptor::new(Arc::clone(&ts))));
-            let auth_con = ServiceBuilder::new().option_layer(auth_layer).service(con);
-            conns.push(auth_con);
-        }
-        Ok(conns)
+    pub fn num(&self) -> usize {
+        self.inner.values.len()
     }
 
-    async fn create_emulator_connections(
-        host: &str,
-        conn_options: &'a ConnectionOptions,
-    ) -> Result<Vec<Channel>, Error> {
-        let mut conns = Vec::with_capacity(1);
-        let endpoint = TonicChannel::from_shared(format!("http://{host}").into_bytes())
-            .map_err(|_| Error::InvalidEmulatorHOST(host.to_string()))?;
-        let endpoint = conn_options.apply(endpoint);
+    pub fn conn(&self) -> Channel {
+        self.inner.next().channel()
+    }
 
-        let con = Self::connect(endpoint).await?;
-        conns.push(
-            ServiceBuilder::new()
-                .option_layer::<AsyncFilterLayer<AsyncAuthInterceptor>>(None)
-                .service(con),
-        );
-        Ok(conns)
+    /// conn_at returns the channel at the index instead of the next one of the round-robin.
+    pub fn conn_at(&self, index: usize) -> Channel {
+        self.pooled_channel(index).channel()
     }
 
-    async fn connect(endpoint: Endpoint) -> Result<TonicChannel, tonic::transport::Error> {
-        let channel = endpoint.connect().await?;
-        Ok(channel)
+    /// replace reconnects the channel at the index, for example after the transport failure.
+    /// The channels already returned keep using the old connection, so the users compare the `generation`
+    /// read before getting the channel with the current one to find the channels to be replaced.
+    pub async fn replace(&self, index: usize) -> Result<(), Error> {
+        let channel = Self::connect(self.endpoint.clone(), self.auth.clone()).await?;
+        let pooled = self.pooled_channel(index);
+        *pooled.channel.write().unwrap() = channel;
+        pooled.generation.fetch_add(1, Ordering::SeqCst);
+        Ok(())
     }
 
-    pub fn num(&self) -> usize {
-        self.inner.values.len()
+    /// generation returns the number of the times the channel at the index has been replaced.
+    pub fn generation(&self, index: usize) -> usize {
+        self.pooled_channel(index).generation.load(Ordering::SeqCst)
     }
 
-    pub fn conn(&self) -> Channel {
-        self.inner.next()
+    fn pooled_channel(&self, index: usize) -> &PooledChannel {
+        &self.inner.values[index % self.inner.values.len()]
     }
 }
 
@@ -195,8 +257,20 @@ impl<'a> ConnectionManager {
 mod test {
     use std::collections::HashSet;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use http::header::AUTHORIZATION;
+    use http::{HeaderValue, Request};
+    use tonic::body::empty_body;
+    use tonic::transport::{Certificate, Channel, Identity};
+    use tower::filter::AsyncPredicate;
 
-    use crate::conn::AtomicRing;
+    use google_cloud_token::TokenSource;
+
+    use crate::conn::{AsyncAuthInterceptor, AtomicRing, ConnectionManager, ConnectionOptions, Environment};
+
+    const CERT: &[u8] = include_bytes!("../testdata/cert.pem");
+    const KEY: &[u8] = include_bytes!("../testdata/key.pem");
 
     #[test]
     fn test_atomic_ring() {
@@ -217,4 +291,68 @@ mod test {
         assert!(!values.insert(cm.next()));
         assert_eq!(3, cm.index.load(Ordering::SeqCst));
     }
+
+    #[tokio::test]
+    async fn test_replace() {
+        // the listener accepts the connections without the server because no request is sent.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let environment = Environment::Emulator(listener.local_addr().unwrap().to_string());
+        let cm = ConnectionManager::new(4, "", "", &environment, &ConnectionOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(cm.num(), 1);
+        assert_eq!(cm.generation(0), 0);
+
+        cm.replace(0).await.unwrap();
+        cm.replace(1).await.unwrap();
+        assert_eq!(cm.generation(0), 2);
+        assert_eq!(cm.generation(1), 2);
+        let _ = cm.conn();
+        let _ = cm.conn_at(0);
+    }
+
+    #[test]
+    fn test_tls_config() {
+        let options = ConnectionOptions {
+            ca_certificate: Some(Certificate::from_pem(CERT)),
+            identity: Some(Identity::from_pem(CERT, KEY)),
+            ..Default::default()
+        };
+        assert!(Channel::from_static("https://localhost")
+            .tls_config(options.tls_config("localhost"))
+            .is_ok());
+
+        // the certificate is not the private key of the identity
+        let options = ConnectionOptions {
+            identity: Some(Identity::from_pem(CERT, CERT)),
+            ..Default::default()
+        };
+        assert!(Channel::from_static("https://localhost")
+            .tls_config(options.tls_config("localhost"))
+            .is_err());
+    }
+
+    #[derive(Debug)]
+    struct StaticTokenSource;
+
+    #[async_trait::async_trait]
+    impl TokenSource for StaticTokenSource {
+        async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            Ok("Bearer token".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auth_interceptor() {
+        let quota_project = HeaderValue::from_static("quota-project");
+        let mut interceptor = AsyncAuthInterceptor::new(Arc::new(StaticTokenSource), Some(quota_project));
+        let request = interceptor.check(Request::new(empty_body())).await.unwrap();
+        assert_eq!(request.headers()[AUTHORIZATION], "Bearer token");
+        assert_eq!(request.headers()["x-goog-user-project"], "quota-project");
+
+        let mut interceptor = AsyncAuthInterceptor::new(Arc::new(StaticTokenSource), None);
+        let request = interceptor.check(Request::new(empty_body())).await.unwrap();
+        assert_eq!(request.headers()[AUTHORIZATION], "Bearer token");
+        assert!(!request.headers().contains_key("x-goog-user-project"));
+    }
 }
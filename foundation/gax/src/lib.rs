@@ -1,3 +1,8 @@
+#[cfg(not(any(feature = "webpki-roots", feature = "native-roots")))]
+compile_error!(
+    "either the `webpki-roots` or the `native-roots` feature must be enabled to verify the TLS certificates"
+);
+
 pub mod conn;
 pub mod grpc;
 pub mod retry;
@@ -1,7 +1,9 @@
+use std::collections::VecDeque;
 use std::future::Future;
 use std::iter::Take;
 use std::time::Duration;
 
+use futures_util::{Stream, StreamExt};
 pub use tokio_retry::strategy::ExponentialBackoff;
 pub use tokio_retry::Condition;
 use tokio_retry::{Action, RetryIf};
@@ -124,13 +126,368 @@ where
     }
 }
 
+/// StreamRetry decides whether the broken server-streaming call is re-established.
+/// The back off is reset every time the stream makes progress, so the long-lived stream
+/// recovers from the failures as long as it receives the items between them.
+pub struct StreamRetry {
+    setting: RetrySetting,
+    strategy: Take<ExponentialBackoff>,
+}
+
+impl StreamRetry {
+    pub fn new(setting: RetrySetting) -> Self {
+        Self {
+            strategy: setting.strategy(),
+            setting,
+        }
+    }
+
+    /// next waits for the back off if the stream should be re-established, otherwise returns the error.
+    pub async fn next(&mut self, status: Status) -> Result<(), Status> {
+        if !self.setting.condition().should_retry(&status) {
+            return Err(status);
+        }
+        let duration = self.strategy.next().ok_or(status)?;
+        tokio::time::sleep(duration).await;
+        tracing::trace!("retry stream");
+        Ok(())
+    }
+
+    /// reset resets the back off after the stream received the item.
+    pub fn reset(&mut self) {
+        self.strategy = self.setting.strategy();
+    }
+}
+
+/// The max number of the items buffered while waiting for the resume state.
+/// When more items arrive without the resume state, they are yielded and the stream is not re-established
+/// until the next resume state arrives, because the yielded items would be received again.
+pub const MAX_BUFFERED_ITEMS: usize = 1024;
+
+/// Reconnect re-establishes the broken server-streaming call of `ResumableStream`.
+pub trait Reconnect<S, R> {
+    /// reconnect creates the stream from the resume state. None starts the stream from the beginning.
+    fn reconnect(&mut self, resume: Option<R>) -> impl Future<Output = Result<S, Status>> + Send;
+}
+
+/// ResumableStream re-establishes the server-streaming call when the retryable error is detected,
+/// including the error of re-establishing the call itself.
+/// The stream is re-established from the latest resume state extracted from the items by `resume_state`,
+/// and from the beginning until the first resume state arrives.
+/// The server sends the items after the resume state again, so the items are buffered until the item
+/// with the resume state arrives, and the buffered items are discarded when the stream is broken.
+///
+/// Use `invoke_stream` unless the stream is re-established with the values borrowed by the caller.
+pub struct ResumableStream<S, T, R, K = fn(&T) -> Option<R>> {
+    stream: Option<S>,
+    resume_state: K,
+    resume: Option<R>,
+    retry: StreamRetry,
+    /// The items received after the last resume state.
+    buffered: VecDeque<T>,
+    /// The items to be yielded.
+    ready: VecDeque<T>,
+    /// false if the items are yielded without the resume state.
+    resumable: bool,
+    done: bool,
+}
+
+impl<S, T, R, K> ResumableStream<S, T, R, K>
+where
+    S: Stream<Item = Result<T, Status>> + Unpin,
+    K: FnMut(&T) -> Option<R>,
+    R: Clone,
+{
+    /// new creates the ResumableStream. `stream` is the stream already established by the caller,
+    /// and None establishes the stream at the first call of `next`.
+    pub fn new(retry: Option<RetrySetting>, stream: Option<S>, resume_state: K) -> Self {
+        Self {
+            stream,
+            resume_state,
+            resume: None,
+            retry: StreamRetry::new(retry.unwrap_or_default()),
+            buffered: VecDeque::new(),
+            ready: VecDeque::new(),
+            resumable: true,
+            done: false,
+        }
+    }
+
+    /// next returns the next item. None is returned after the stream is completed or the error is returned.
+    pub async fn next<C: Reconnect<S, R>>(&mut self, reconnect: &mut C) -> Option<Result<T, Status>> {
+        loop {
+            if let Some(item) = self.ready.pop_front() {
+                return Some(Ok(item));
+            }
+            if self.done {
+                return None;
+            }
+            let stream = match self.stream.as_mut() {
+                Some(stream) => stream,
+                None => match reconnect.reconnect(self.resume.clone()).await {
+                    Ok(stream) => self.stream.insert(stream),
+                    Err(e) => match self.retry.next(e).await {
+                        Ok(_) => continue,
+                        Err(e) => return Some(Err(self.fail(e))),
+                    },
+                },
+            };
+            match stream.next().await {
+                Some(Ok(item)) => {
+                    self.retry.reset();
+                    match (self.resume_state)(&item) {
+                        Some(resume) => {
+                            self.resume = Some(resume);
+                            self.resumable = true;
+                            self.flush();
+                            self.ready.push_back(item);
+                        }
+                        None => {
+                            self.buffered.push_back(item);
+                            if self.buffered.len() > MAX_BUFFERED_ITEMS {
+                                self.resumable = false;
+                                self.flush();
+                            }
+                        }
+                    }
+                }
+                Some(Err(e)) => {
+                    self.stream = None;
+                    // the server sends the buffered items again.
+                    self.buffered.clear();
+                    if !self.resumable {
+                        return Some(Err(self.fail(e)));
+                    }
+                    if let Err(e) = self.retry.next(e).await {
+                        return Some(Err(self.fail(e)));
+                    }
+                    tracing::debug!("resume the stream");
+                }
+                None => {
+                    self.flush();
+                    self.done = true;
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        self.ready.append(&mut self.buffered);
+    }
+
+    fn fail(&mut self, status: Status) -> Status {
+        self.stream = None;
+        self.buffered.clear();
+        self.done = true;
+        status
+    }
+}
+
+/// Repeats re-establishing the server-streaming call when the retryable error is detected.
+/// `f` creates the stream from the latest resume state extracted from the items by `resume_state`,
+/// `None` is passed for the first call and until the first resume state arrives.
+/// See `ResumableStream` for how the items are buffered until the resume state arrives.
+pub fn invoke_stream<S, T, R, A, F, K>(
+    retry: Option<RetrySetting>,
+    f: F,
+    resume_state: K,
+) -> impl Stream<Item = Result<T, Status>>
+where
+    S: Stream<Item = Result<T, Status>> + Unpin,
+    A: Future<Output = Result<S, Status>> + Send,
+    F: FnMut(Option<R>) -> A,
+    K: FnMut(&T) -> Option<R>,
+    R: Clone,
+{
+    let state = (ResumableStream::new(retry, None, resume_state), FnReconnect(f));
+    futures_util::stream::unfold(state, |(mut stream, mut reconnect)| async move {
+        let item = stream.next(&mut reconnect).await?;
+        Some((item, (stream, reconnect)))
+    })
+}
+
+struct FnReconnect<F>(F);
+
+impl<S, R, A, F> Reconnect<S, R> for FnReconnect<F>
+where
+    A: Future<Output = Result<S, Status>> + Send,
+    F: FnMut(Option<R>) -> A,
+{
+    fn reconnect(&mut self, resume: Option<R>) -> impl Future<Output = Result<S, Status>> + Send {
+        (self.0)(resume)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::{Arc, Mutex};
 
+    use futures_util::stream::Empty;
+    use futures_util::StreamExt;
     use tonic::{Code, Status};
 
-    use crate::retry::{invoke, RetrySetting};
+    use crate::retry::{invoke, invoke_stream, RetrySetting, MAX_BUFFERED_ITEMS};
+
+    fn setting(take: usize) -> RetrySetting {
+        RetrySetting {
+            from_millis: 1,
+            max_delay: None,
+            factor: 1,
+            take,
+            codes: vec![Code::Unavailable],
+        }
+    }
+
+    /// mock returns the stream of `start..end` failing with `code` at the position `fail_at`.
+    /// The item is the tonic Status as the streams of the generated clients.
+    #[allow(clippy::result_large_err)]
+    fn mock(
+        start: i32,
+        end: i32,
+        fail_at: Option<i32>,
+        code: Code,
+    ) -> impl futures_util::Stream<Item = Result<i32, Status>> {
+        futures_util::stream::iter((start..end).map(move |i| match fail_at {
+            Some(f) if f == i => Err(Status::new(code, "broken")),
+            _ => Ok(i),
+        }))
+        .take_while({
+            let mut failed = false;
+            move |v| {
+                let keep = !failed;
+                failed |= v.is_err();
+                futures_util::future::ready(keep)
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_invoke_stream_resume() {
+        // the stream fails at the arbitrary points once per point.
+        let failures = Arc::new(Mutex::new(vec![0, 3, 4, 7, 9]));
+        let calls = Arc::new(Mutex::new(vec![]));
+        let stream = invoke_stream(
+            Some(setting(2)),
+            |resume: Option<i32>| {
+                let failures = failures.clone();
+                calls.lock().unwrap().push(resume);
+                async move {
+                    let start = resume.map(|v| v + 1).unwrap_or(0);
+                    let mut failures = failures.lock().unwrap();
+                    let fail_at = failures.iter().position(|f| *f >= start).map(|i| failures.remove(i));
+                    Ok::<_, Status>(Box::pin(mock(start, 10, fail_at, Code::Unavailable)))
+                }
+            },
+            |item: &i32| Some(*item),
+        );
+        let items: Vec<i32> = stream.map(|v| v.unwrap()).collect().await;
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+        assert_eq!(*calls.lock().unwrap(), vec![None, None, Some(2), Some(3), Some(6), Some(8)]);
+    }
+
+    #[tokio::test]
+    async fn test_invoke_stream_buffer_until_resume_state() {
+        // only every third item has the resume state, and the stream fails at the arbitrary points once per point.
+        let failures = Arc::new(Mutex::new(vec![1, 4, 5, 9]));
+        let calls = Arc::new(Mutex::new(vec![]));
+        let stream = invoke_stream(
+            Some(setting(2)),
+            |resume: Option<i32>| {
+                let failures = failures.clone();
+                calls.lock().unwrap().push(resume);
+                async move {
+                    let start = resume.map(|v| v + 1).unwrap_or(0);
+                    let mut failures = failures.lock().unwrap();
+                    let fail_at = failures.iter().position(|f| *f >= start).map(|i| failures.remove(i));
+                    Ok::<_, Status>(Box::pin(mock(start, 11, fail_at, Code::Unavailable)))
+                }
+            },
+            |item: &i32| (item % 3 == 2).then_some(*item),
+        );
+        let items: Vec<i32> = stream.map(|v| v.unwrap()).collect().await;
+        assert_eq!(items, (0..11).collect::<Vec<_>>());
+        assert_eq!(*calls.lock().unwrap(), vec![None, None, Some(2), Some(2), Some(8)]);
+    }
+
+    #[tokio::test]
+    async fn test_invoke_stream_retry_reestablish_error() {
+        let calls = Arc::new(Mutex::new(0));
+        let stream = invoke_stream(
+            Some(setting(3)),
+            |resume: Option<i32>| {
+                let call = {
+                    let mut calls = calls.lock().unwrap();
+                    *calls += 1;
+                    *calls
+                };
+                async move {
+                    let start = resume.map(|v| v + 1).unwrap_or(0);
+                    match call {
+                        1 => Ok(Box::pin(mock(start, 10, Some(3), Code::Unavailable))),
+                        2 | 3 => Err(Status::new(Code::Unavailable, "reconnect")),
+                        _ => Ok(Box::pin(mock(start, 10, None, Code::Unavailable))),
+                    }
+                }
+            },
+            |item: &i32| Some(*item),
+        );
+        let items: Vec<i32> = stream.map(|v| v.unwrap()).collect().await;
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+        assert_eq!(*calls.lock().unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_invoke_stream_buffer_overflow() {
+        let calls = Arc::new(Mutex::new(0));
+        let end = MAX_BUFFERED_ITEMS as i32 + 10;
+        let stream = invoke_stream(
+            Some(setting(5)),
+            |_: Option<i32>| {
+                *calls.lock().unwrap() += 1;
+                async move { Ok::<_, Status>(Box::pin(mock(0, end, Some(end - 5), Code::Unavailable))) }
+            },
+            |_: &i32| None,
+        );
+        let items: Vec<Result<i32, Status>> = stream.collect().await;
+        // the items are yielded without the resume state, so the stream is not re-established.
+        assert_eq!(items.len(), MAX_BUFFERED_ITEMS + 2);
+        assert_eq!(items[MAX_BUFFERED_ITEMS].as_ref().unwrap(), &(MAX_BUFFERED_ITEMS as i32));
+        assert_eq!(items[MAX_BUFFERED_ITEMS + 1].as_ref().unwrap_err().code(), Code::Unavailable);
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_invoke_stream_not_retryable() {
+        let stream = invoke_stream(
+            Some(setting(5)),
+            |resume: Option<i32>| async move {
+                let start = resume.map(|v| v + 1).unwrap_or(0);
+                Ok::<_, Status>(Box::pin(mock(start, 10, Some(5), Code::InvalidArgument)))
+            },
+            |item: &i32| Some(*item),
+        );
+        let items: Vec<Result<i32, Status>> = stream.collect().await;
+        assert_eq!(items.len(), 6);
+        assert_eq!(items[5].as_ref().unwrap_err().code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_invoke_stream_exhausted() {
+        let counter = Arc::new(Mutex::new(0));
+        let stream = invoke_stream(
+            Some(setting(3)),
+            |_: Option<i32>| {
+                *counter.lock().unwrap() += 1;
+                let result: Result<Empty<Result<i32, Status>>, Status> = Err(Status::new(Code::Unavailable, "error"));
+                async { result }
+            },
+            |item: &i32| Some(*item),
+        );
+        let items: Vec<Result<i32, Status>> = stream.collect().await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].as_ref().unwrap_err().code(), Code::Unavailable);
+        assert_eq!(*counter.lock().unwrap(), 4);
+    }
 
     #[tokio::test]
     async fn test_retry() {
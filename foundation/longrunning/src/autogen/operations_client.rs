@@ -1,6 +1,8 @@
+use std::sync::Arc;
 use std::time::Duration;
 
-use tonic::Response;
+use tonic::metadata::{MetadataKey, MetadataValue};
+use tonic::{Request, Response};
 
 use google_cloud_gax::conn::{Channel, Error};
 use google_cloud_gax::create_request;
@@ -11,6 +13,10 @@ use google_cloud_googleapis::longrunning::{
     CancelOperationRequest, DeleteOperationRequest, GetOperationRequest, Operation, WaitOperationRequest,
 };
 
+/// WaitObserver is invoked with the name of the operation, the elapsed time and the error
+/// when `Operation::wait` returns. The error is None if the operation completed successfully.
+pub type WaitObserver = Arc<dyn Fn(&str, Duration, Option<&Status>) + Send + Sync>;
+
 pub fn default_retry_setting() -> RetrySetting {
     RetrySetting {
         from_millis: 50,
@@ -21,16 +27,124 @@ pub fn default_retry_setting() -> RetrySetting {
     }
 }
 
+/// OperationsClientBuilder creates the OperationsClient with the defaults applied to every request
+/// issued by the client and the `Operation` handles, unless the retry setting is passed per call.
+pub struct OperationsClientBuilder {
+    channel: Channel,
+    retry: RetrySetting,
+    timeout: Option<Duration>,
+    metadata: Vec<(String, String)>,
+    wait_observer: Option<WaitObserver>,
+}
+
+impl OperationsClientBuilder {
+    /// The retry setting used when no retry setting is passed per call.
+    /// The Operations API rate-limits aggressively, so the longer back off might be required.
+    pub fn with_retry(mut self, value: RetrySetting) -> Self {
+        self.retry = value;
+        self
+    }
+
+    /// The timeout of each attempt of the request.
+    pub fn with_timeout(mut self, value: Duration) -> Self {
+        self.timeout = Some(value);
+        self
+    }
+
+    /// The custom metadata sent with each attempt of the request.
+    /// The invalid key or value fails the request with INVALID_ARGUMENT.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.push((key.into(), value.into()));
+        self
+    }
+
+    /// The project billed for the quota of the requests. It is sent as `x-goog-user-project`.
+    pub fn with_quota_project(self, value: impl Into<String>) -> Self {
+        self.with_metadata("x-goog-user-project", value)
+    }
+
+    /// The observer invoked when `Operation::wait` or awaiting the operation returns,
+    /// for example to log the slow operations.
+    pub fn with_wait_observer(
+        mut self,
+        value: impl Fn(&str, Duration, Option<&Status>) + Send + Sync + 'static,
+    ) -> Self {
+        self.wait_observer = Some(Arc::new(value));
+        self
+    }
+
+    pub fn build(self) -> OperationsClient {
+        OperationsClient {
+            inner: InternalOperationsClient::new(self.channel).max_decoding_message_size(i32::MAX as usize),
+            retry: self.retry,
+            timeout: self.timeout,
+            metadata: self.metadata,
+            wait_observer: self.wait_observer,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct OperationsClient {
     inner: InternalOperationsClient<Channel>,
+    retry: RetrySetting,
+    timeout: Option<Duration>,
+    metadata: Vec<(String, String)>,
+    wait_observer: Option<WaitObserver>,
 }
 
 impl OperationsClient {
     pub async fn new(channel: Channel) -> Result<Self, Error> {
-        Ok(OperationsClient {
-            inner: InternalOperationsClient::new(channel).max_decoding_message_size(i32::MAX as usize),
-        })
+        Ok(Self::builder(channel).build())
+    }
+
+    /// builder returns the builder to set the defaults of the requests like the retry setting.
+    pub fn builder(channel: Channel) -> OperationsClientBuilder {
+        OperationsClientBuilder {
+            channel,
+            retry: default_retry_setting(),
+            timeout: None,
+            metadata: vec![],
+            wait_observer: None,
+        }
+    }
+
+    /// retry returns the retry setting used when no retry setting is passed per call.
+    pub fn retry(&self) -> &RetrySetting {
+        &self.retry
+    }
+
+    /// timeout returns the timeout of each attempt of the request.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// metadata returns the custom metadata sent with each attempt of the request.
+    pub fn metadata(&self) -> &[(String, String)] {
+        &self.metadata
+    }
+
+    /// wait_observer returns the observer invoked when `Operation::wait` returns.
+    pub fn wait_observer(&self) -> Option<&WaitObserver> {
+        self.wait_observer.as_ref()
+    }
+
+    /// create_request applies the defaults of the client to the request.
+    /// The error of the invalid metadata is boxed because the request is usually built successfully.
+    fn create_request<T>(&self, param_string: String, req: T) -> Result<Request<T>, Box<Status>> {
+        let mut request = create_request(param_string, req);
+        if let Some(timeout) = self.timeout {
+            request.set_timeout(timeout);
+        }
+        let metadata = request.metadata_mut();
+        for (key, value) in &self.metadata {
+            let k = MetadataKey::from_bytes(key.as_bytes())
+                .map_err(|_| Box::new(Status::invalid_argument(format!("invalid metadata key: {key}"))))?;
+            let v = MetadataValue::try_from(value.as_str())
+                .map_err(|_| Box::new(Status::invalid_argument(format!("invalid metadata value: key={key}"))))?;
+            metadata.append(k, v);
+        }
+        Ok(request)
     }
 
     /// GetOperation gets the latest state of a long-running operation.  Clients can use this
@@ -40,10 +154,12 @@ impl OperationsClient {
         req: GetOperationRequest,
         retry: Option<RetrySetting>,
     ) -> Result<Response<Operation>, Status> {
-        let setting = retry.unwrap_or_else(default_retry_setting);
+        let setting = retry.unwrap_or_else(|| self.retry.clone());
         let name = &req.name;
         let action = || async {
-            let request = create_request(format!("name={name}"), req.clone());
+            let request = self
+                .create_request(format!("name={name}"), req.clone())
+                .map_err(|e| *e)?;
             self.inner.clone().get_operation(request).await
         };
         invoke(Some(setting), action).await
@@ -58,10 +174,12 @@ impl OperationsClient {
         req: DeleteOperationRequest,
         retry: Option<RetrySetting>,
     ) -> Result<Response<()>, Status> {
-        let setting = retry.unwrap_or_else(default_retry_setting);
+        let setting = retry.unwrap_or_else(|| self.retry.clone());
         let name = &req.name;
         let action = || async {
-            let request = create_request(format!("name={name}"), req.clone());
+            let request = self
+                .create_request(format!("name={name}"), req.clone())
+                .map_err(|e| *e)?;
             self.inner.clone().delete_operation(request).await
         };
         invoke(Some(setting), action).await
@@ -82,10 +200,12 @@ impl OperationsClient {
         req: CancelOperationRequest,
         retry: Option<RetrySetting>,
     ) -> Result<Response<()>, Status> {
-        let setting = retry.unwrap_or_else(default_retry_setting);
+        let setting = retry.unwrap_or_else(|| self.retry.clone());
         let name = &req.name;
         let action = || async {
-            let request = create_request(format!("name={name}"), req.clone());
+            let request = self
+                .create_request(format!("name={name}"), req.clone())
+                .map_err(|e| *e)?;
             self.inner.clone().cancel_operation(request).await
         };
         invoke(Some(setting), action).await
@@ -105,9 +225,9 @@ impl OperationsClient {
         req: WaitOperationRequest,
         retry: Option<RetrySetting>,
     ) -> Result<Response<Operation>, Status> {
-        let setting = retry.unwrap_or_else(default_retry_setting);
+        let setting = retry.unwrap_or_else(|| self.retry.clone());
         let action = || async {
-            let request = create_request("".to_string(), req.clone());
+            let request = self.create_request("".to_string(), req.clone()).map_err(|e| *e)?;
             self.inner.clone().wait_operation(request).await
         };
         invoke(Some(setting), action).await
@@ -1,4 +1,7 @@
+use std::future::{Future, IntoFuture};
 use std::marker::PhantomData;
+use std::pin::Pin;
+use std::time::Instant;
 
 use google_cloud_gax::grpc::{Code, Status};
 use google_cloud_gax::retry::{invoke_fn, RetrySetting};
@@ -6,7 +9,7 @@ use google_cloud_googleapis::longrunning::{
     operation, CancelOperationRequest, DeleteOperationRequest, GetOperationRequest, Operation as InternalOperation,
 };
 
-use crate::autogen::operations_client::{default_retry_setting, OperationsClient};
+use crate::autogen::operations_client::OperationsClient;
 
 pub struct Operation<T: prost::Message + Default> {
     inner: InternalOperation,
@@ -69,11 +72,22 @@ impl<T: prost::Message + Default> Operation<T> {
     }
 
     /// wait implements Wait, taking exponentialBackoff and sleeper arguments for testing.
+    /// None polls with the back off of the retry setting configured in the operations client.
+    /// The wait observer of the operations client is invoked when it returns.
     pub async fn wait(&mut self, option: Option<RetrySetting>) -> Result<Option<T>, Status> {
+        let started_at = Instant::now();
+        let result = self.wait_inner(option).await;
+        if let Some(observer) = self.client.wait_observer() {
+            observer(self.name(), started_at.elapsed(), result.as_ref().err());
+        }
+        result
+    }
+
+    async fn wait_inner(&mut self, option: Option<RetrySetting>) -> Result<Option<T>, Status> {
         let settings = match option {
             Some(s) => s,
             None => {
-                let mut setting = default_retry_setting();
+                let mut setting = self.client.retry().clone();
                 setting.codes = vec![Code::DeadlineExceeded];
                 setting
             }
@@ -121,3 +135,278 @@ impl<T: prost::Message + Default> Operation<T> {
         self.client.delete_operation(req, None).await.map(|_x| ())
     }
 }
+
+/// Awaiting the operation waits for the completion and returns the response.
+/// This is equivalent to `wait(Default::default())`, so the retry setting of the operations client is used for polling.
+/// Use `wait` or `poll` to control the polling.
+///
+/// ```ignore
+/// let database = client.create_database(req, None).await?.await?;
+/// ```
+impl<T: prost::Message + Default + 'static> IntoFuture for Operation<T> {
+    type Output = Result<T, Status>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
+
+    fn into_future(mut self) -> Self::IntoFuture {
+        Box::pin(async move {
+            self.wait(Default::default())
+                .await?
+                .ok_or_else(|| Status::new(Code::Internal, "the operation completed without the response"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use prost_types::Any;
+    use tokio::net::TcpListener;
+    use tonic::body::BoxBody;
+    use tonic::codec::ProstCodec;
+    use tonic::codegen::{http, Body, BoxFuture, Context, Poll, Service, StdError};
+    use tonic::server::{Grpc, NamedService, UnaryService};
+    use tonic::transport::Server;
+
+    use google_cloud_gax::conn::{Channel, ConnectionManager, ConnectionOptions, Environment};
+    use google_cloud_gax::grpc::{Code, Status};
+    use google_cloud_gax::retry::RetrySetting;
+    use google_cloud_googleapis::longrunning::{operation, GetOperationRequest, Operation as InternalOperation};
+    use google_cloud_googleapis::rpc::Status as RpcStatus;
+
+    use crate::autogen::operations_client::OperationsClient;
+    use crate::longrunning::Operation;
+
+    /// MockOperations completes the operation at the `done_at`th GetOperation and records the metadata of the requests.
+    #[derive(Clone)]
+    struct MockOperations {
+        done_at: usize,
+        requests: Arc<Mutex<Vec<http::HeaderMap>>>,
+    }
+
+    impl MockOperations {
+        fn new(done_at: usize) -> Self {
+            Self {
+                done_at,
+                requests: Arc::new(Mutex::new(vec![])),
+            }
+        }
+
+        fn requests(&self) -> Vec<http::HeaderMap> {
+            self.requests.lock().unwrap().clone()
+        }
+
+        async fn start(&self) -> Channel {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let incoming = futures_util::stream::unfold(listener, |listener| async move {
+                let stream = listener.accept().await.map(|(stream, _)| stream);
+                Some((stream, listener))
+            });
+            tokio::spawn(
+                Server::builder()
+                    .add_service(self.clone())
+                    .serve_with_incoming(incoming),
+            );
+            let environment = Environment::Emulator(addr.to_string());
+            ConnectionManager::new(1, "", "", &environment, &ConnectionOptions::default())
+                .await
+                .unwrap()
+                .conn()
+        }
+    }
+
+    impl UnaryService<GetOperationRequest> for MockOperations {
+        type Response = InternalOperation;
+        type Future = BoxFuture<tonic::Response<InternalOperation>, Status>;
+
+        fn call(&mut self, request: tonic::Request<GetOperationRequest>) -> Self::Future {
+            let done = {
+                let mut requests = self.requests.lock().unwrap();
+                requests.push(request.metadata().clone().into_headers());
+                requests.len() >= self.done_at
+            };
+            let operation = new_operation(request.into_inner().name, done);
+            Box::pin(std::future::ready(Ok(tonic::Response::new(operation))))
+        }
+    }
+
+    impl<B> Service<http::Request<B>> for MockOperations
+    where
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<BoxBody>;
+        type Error = Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        // Only GetOperation is called to wait for the operation.
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            let mock = self.clone();
+            Box::pin(async move { Ok(Grpc::new(ProstCodec::default()).unary(mock, req).await) })
+        }
+    }
+
+    impl NamedService for MockOperations {
+        const NAME: &'static str = "google.longrunning.Operations";
+    }
+
+    fn new_operation(name: String, done: bool) -> InternalOperation {
+        InternalOperation {
+            name,
+            metadata: None,
+            done,
+            result: done.then(|| {
+                operation::Result::Response(Any {
+                    type_url: "type.googleapis.com/google.protobuf.Empty".to_string(),
+                    value: vec![],
+                })
+            }),
+        }
+    }
+
+    fn retry_setting(take: usize) -> RetrySetting {
+        RetrySetting {
+            from_millis: 1,
+            max_delay: None,
+            factor: 1,
+            take,
+            codes: vec![Code::Unavailable],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_builder() {
+        let mock = MockOperations::new(1);
+        let client = OperationsClient::builder(mock.start().await)
+            .with_retry(retry_setting(3))
+            .with_timeout(Duration::from_secs(5))
+            .with_quota_project("quota-project")
+            .with_metadata("x-goog-test", "value")
+            .build();
+        assert_eq!(client.retry().take, 3);
+        assert_eq!(client.timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(client.metadata().len(), 2);
+
+        let req = GetOperationRequest { name: "op".to_string() };
+        let operation = client.get_operation(req, None).await.unwrap().into_inner();
+        assert!(operation.done);
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0]["x-goog-user-project"], "quota-project");
+        assert_eq!(requests[0]["x-goog-test"], "value");
+        assert_eq!(requests[0]["x-goog-request-params"], "name=op");
+        assert!(requests[0].contains_key("grpc-timeout"));
+    }
+
+    #[tokio::test]
+    async fn test_builder_invalid_metadata() {
+        let mock = MockOperations::new(1);
+        let client = OperationsClient::builder(mock.start().await)
+            .with_metadata("invalid key", "value")
+            .build();
+        let req = GetOperationRequest { name: "op".to_string() };
+        let err = client.get_operation(req, None).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_wait_with_client_retry() {
+        let mock = MockOperations::new(usize::MAX);
+        let client = OperationsClient::builder(mock.start().await)
+            .with_retry(retry_setting(2))
+            .build();
+        let mut operation = Operation::<()>::new(client, new_operation("op".to_string(), false));
+
+        // the configured retry setting polls 3 times instead of the default 21 times.
+        let err = operation.wait(None).await.unwrap_err();
+        assert_eq!(err.code(), Code::DeadlineExceeded);
+        assert_eq!(mock.requests().len(), 3);
+        assert!(!operation.done());
+    }
+
+    #[tokio::test]
+    async fn test_wait() {
+        let mock = MockOperations::new(2);
+        let client = OperationsClient::builder(mock.start().await)
+            .with_retry(retry_setting(5))
+            .build();
+        let mut operation = Operation::<()>::new(client, new_operation("op".to_string(), false));
+        assert_eq!(operation.wait(None).await.unwrap(), Some(()));
+        assert_eq!(mock.requests().len(), 2);
+        assert!(operation.done());
+    }
+
+    #[tokio::test]
+    async fn test_wait_observer() {
+        let mock = MockOperations::new(usize::MAX);
+        let observed = Arc::new(Mutex::new(vec![]));
+        let observed_clone = observed.clone();
+        let client = OperationsClient::builder(mock.start().await)
+            .with_retry(retry_setting(5))
+            .with_wait_observer(move |name, elapsed, err| {
+                observed_clone
+                    .lock()
+                    .unwrap()
+                    .push((name.to_string(), elapsed, err.map(|e| e.code())));
+            })
+            .build();
+        Operation::<()>::new(client.clone(), new_operation("op".to_string(), true))
+            .await
+            .unwrap();
+
+        let mut operation = Operation::<()>::new(client, new_operation("op2".to_string(), false));
+        let err = operation.wait(Some(retry_setting(0))).await.unwrap_err();
+        assert_eq!(err.code(), Code::DeadlineExceeded);
+
+        let observed = observed.lock().unwrap();
+        assert_eq!(observed.len(), 2);
+        assert_eq!(observed[0].0, "op");
+        assert_eq!(observed[0].2, None);
+        assert_eq!(observed[1].0, "op2");
+        assert_eq!(observed[1].2, Some(Code::DeadlineExceeded));
+    }
+
+    #[tokio::test]
+    async fn test_into_future() {
+        let mock = MockOperations::new(2);
+        let client = OperationsClient::builder(mock.start().await)
+            .with_retry(retry_setting(5))
+            .build();
+        let operation = Operation::<()>::new(client, new_operation("op".to_string(), false));
+        operation.await.unwrap();
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_into_future_done() {
+        let mock = MockOperations::new(1);
+        let client = OperationsClient::builder(mock.start().await).build();
+        let operation = Operation::<()>::new(client, new_operation("op".to_string(), true));
+        operation.await.unwrap();
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_into_future_error() {
+        let mock = MockOperations::new(1);
+        let client = OperationsClient::builder(mock.start().await).build();
+        let mut inner = new_operation("op".to_string(), true);
+        inner.result = Some(operation::Result::Error(RpcStatus {
+            code: Code::PermissionDenied as i32,
+            message: "denied".to_string(),
+            details: vec![],
+        }));
+        let err = Operation::<()>::new(client, inner).await.unwrap_err();
+        assert_eq!(err.code(), Code::PermissionDenied);
+        assert_eq!(err.message(), "denied");
+    }
+}
@@ -1,7 +1,9 @@
+use std::collections::HashMap;
 use std::string;
 use std::time::Duration;
 
-use reqwest::header::{HeaderValue, USER_AGENT};
+use reqwest::header::{HeaderValue, ETAG, USER_AGENT};
+use reqwest::StatusCode;
 use tokio::net::lookup_host;
 use tokio::sync::OnceCell;
 
@@ -11,10 +13,18 @@ pub const METADATA_GOOGLE_HOST: &str = "metadata.google.internal:80";
 pub const METADATA_FLAVOR_KEY: &str = "Metadata-Flavor";
 pub const METADATA_GOOGLE: &str = "Google";
 
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
 static ON_GCE: OnceCell<bool> = OnceCell::const_new();
 
 static PROJECT_ID: OnceCell<String> = OnceCell::const_new();
 
+// The values never change while the instance is running.
+static NUMERIC_PROJECT_ID: OnceCell<String> = OnceCell::const_new();
+static INSTANCE_ID: OnceCell<String> = OnceCell::const_new();
+static INSTANCE_NAME: OnceCell<String> = OnceCell::const_new();
+static ZONE: OnceCell<String> = OnceCell::const_new();
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("invalid response code: {0}")]
@@ -23,6 +33,33 @@ pub enum Error {
     FromUTF8Error(#[from] string::FromUtf8Error),
     #[error(transparent)]
     HttpError(#[from] reqwest::Error),
+    #[error("not running on GCE")]
+    NotOnGCE,
+    /// The metadata server returned 404 for the path. The value is the path like `instance/attributes/key`.
+    /// It was returned as `InvalidResponse(404)` before.
+    #[error("metadata is not defined: {0}")]
+    NotDefined(String),
+    #[error("invalid attributes: {0}")]
+    InvalidAttributes(String),
+}
+
+impl Error {
+    /// is_transient returns true if the request may succeed when retried.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::InvalidResponse(code) => *code == 429 || *code >= 500,
+            Error::HttpError(e) => e.is_timeout() || e.is_connect(),
+            _ => false,
+        }
+    }
+}
+
+/// The value and the ETag returned by the metadata server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Watched {
+    pub value: String,
+    /// Pass it to `wait_for_change` as the last ETag to wait for the next change.
+    pub etag: String,
 }
 
 pub async fn on_gce() -> bool {
@@ -38,10 +75,7 @@ async fn test_on_gce() -> Result<bool, Error> {
         return Ok(true);
     }
 
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(3))
-        .build()
-        .unwrap();
+    let client = reqwest::Client::builder().timeout(DEFAULT_TIMEOUT).build().unwrap();
     let url = format!("http://{METADATA_IP}");
 
     let response = client.get(&url).send().await;
@@ -82,31 +116,170 @@ pub async fn project_id() -> String {
     }
 }
 
+/// The email of the service account like `default`.
+/// `Error::NotDefined` is returned if the service account doesn't exist on the instance.
 pub async fn email(service_account: &str) -> Result<String, Error> {
     get_etag_with_trim(&format!("instance/service-accounts/{service_account}/email")).await
 }
 
+/// The numeric id of the project.
+pub async fn numeric_project_id() -> Result<String, Error> {
+    cached(&NUMERIC_PROJECT_ID, "project/numeric-project-id").await
+}
+
+pub async fn instance_id() -> Result<String, Error> {
+    cached(&INSTANCE_ID, "instance/id").await
+}
+
+pub async fn instance_name() -> Result<String, Error> {
+    cached(&INSTANCE_NAME, "instance/name").await
+}
+
+/// The zone of the instance like `us-central1-a`.
+pub async fn zone() -> Result<String, Error> {
+    let zone = cached(&ZONE, "instance/zone").await?;
+    // The response is `projects/<numeric-project-id>/zones/<zone>`.
+    Ok(zone.rsplit('/').next().unwrap_or_default().to_string())
+}
+
+/// The scopes of the service account like `default`.
+pub async fn scopes(service_account: &str) -> Result<Vec<String>, Error> {
+    ensure_on_gce().await?;
+    let scopes = get_etag(&format!("instance/service-accounts/{service_account}/scopes")).await?;
+    Ok(lines(&scopes))
+}
+
+/// The ID token of the service account for the audience.
+/// The token includes the details of the instance when `format_full` is true.
+pub async fn identity_token(service_account: &str, audience: &str, format_full: bool) -> Result<String, Error> {
+    ensure_on_gce().await?;
+    let mut query = vec![("audience", audience)];
+    if format_full {
+        query.push(("format", "full"));
+    }
+    let suffix = format!("instance/service-accounts/{service_account}/identity");
+    let (value, _) = get(&suffix, &query, DEFAULT_TIMEOUT).await?;
+    Ok(value.trim().to_string())
+}
+
+/// The value of the custom metadata of the instance.
+/// The attributes are not cached because they can be changed while the instance is running.
+pub async fn instance_attribute(key: &str) -> Result<String, Error> {
+    ensure_on_gce().await?;
+    get_etag(&format!("instance/attributes/{key}")).await
+}
+
+/// All the custom metadata of the instance.
+pub async fn instance_attributes() -> Result<HashMap<String, String>, Error> {
+    attributes("instance/attributes/").await
+}
+
+/// The value of the custom metadata of the project.
+pub async fn project_attribute(key: &str) -> Result<String, Error> {
+    ensure_on_gce().await?;
+    get_etag(&format!("project/attributes/{key}")).await
+}
+
+/// All the custom metadata of the project.
+pub async fn project_attributes() -> Result<HashMap<String, String>, Error> {
+    attributes("project/attributes/").await
+}
+
+/// wait_for_change waits until the value differs from the one of the last ETag, or until the timeout.
+/// The current value is returned immediately if the last ETag is empty or outdated.
+/// ```no_run
+/// async fn watch() -> Result<(), google_cloud_metadata::Error> {
+///     let mut etag = "".to_string();
+///     loop {
+///         let watched = google_cloud_metadata::wait_for_change(
+///             "instance/attributes/config",
+///             &etag,
+///             std::time::Duration::from_secs(60),
+///         )
+///         .await?;
+///         if watched.etag != etag {
+///             println!("changed: {}", watched.value);
+///         }
+///         etag = watched.etag;
+///     }
+/// }
+/// ```
+pub async fn wait_for_change(suffix: &str, last_etag: &str, timeout: Duration) -> Result<Watched, Error> {
+    ensure_on_gce().await?;
+    let timeout_sec = timeout.as_secs().max(1).to_string();
+    let mut query = vec![("wait_for_change", "true"), ("timeout_sec", timeout_sec.as_str())];
+    if !last_etag.is_empty() {
+        query.push(("last_etag", last_etag));
+    }
+    // The server responds after the timeout at the latest.
+    let (value, etag) = get(suffix, &query, timeout + DEFAULT_TIMEOUT).await?;
+    Ok(Watched { value, etag })
+}
+
+async fn ensure_on_gce() -> Result<(), Error> {
+    if on_gce().await {
+        Ok(())
+    } else {
+        Err(Error::NotOnGCE)
+    }
+}
+
+async fn cached(cell: &OnceCell<String>, suffix: &str) -> Result<String, Error> {
+    ensure_on_gce().await?;
+    // The errors are not cached, so the transient failures are retried by the next call.
+    cell.get_or_try_init(|| get_etag_with_trim(suffix))
+        .await
+        .map(|s| s.to_string())
+}
+
+async fn attributes(suffix: &str) -> Result<HashMap<String, String>, Error> {
+    ensure_on_gce().await?;
+    let (value, _) = get(suffix, &[("recursive", "true")], DEFAULT_TIMEOUT).await?;
+    serde_json::from_str(&value).map_err(|e| Error::InvalidAttributes(e.to_string()))
+}
+
+fn lines(value: &str) -> Vec<String> {
+    value
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
 async fn get_etag_with_trim(suffix: &str) -> Result<String, Error> {
     let result = get_etag(suffix).await?;
-    return Ok(result.trim().to_string());
+    Ok(result.trim().to_string())
 }
 
 async fn get_etag(suffix: &str) -> Result<String, Error> {
+    let (value, _) = get(suffix, &[], DEFAULT_TIMEOUT).await?;
+    Ok(value)
+}
+
+async fn get(suffix: &str, query: &[(&str, &str)], timeout: Duration) -> Result<(String, String), Error> {
     let host = std::env::var(METADATA_HOST_ENV).unwrap_or_else(|_| METADATA_GOOGLE_HOST.to_string());
     let url = format!("http://{host}/computeMetadata/v1/{suffix}");
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(3))
-        .build()
-        .unwrap();
+    let client = reqwest::Client::builder().timeout(timeout).build().unwrap();
     let response = client
         .get(url)
+        .query(query)
         .header(METADATA_FLAVOR_KEY, HeaderValue::from_str(METADATA_GOOGLE).unwrap())
         .header(USER_AGENT, HeaderValue::from_str("gcloud-rust/0.1").unwrap())
         .send()
         .await?;
 
     if response.status().is_success() {
-        return Ok(response.text().await?);
+        let etag = response
+            .headers()
+            .get(ETAG)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        return Ok((response.text().await?, etag));
+    }
+    if response.status() == StatusCode::NOT_FOUND {
+        return Err(Error::NotDefined(suffix.to_string()));
     }
     Err(Error::InvalidResponse(response.status().as_u16()))
 }
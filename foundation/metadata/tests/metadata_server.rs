@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use google_cloud_metadata::{
+    email, instance_attribute, instance_attributes, project_attributes, scopes, wait_for_change, zone, Error, Watched,
+    METADATA_HOST_ENV,
+};
+
+/// serve starts the fake metadata server once and points the client to it.
+/// The tests of this file share the server because the host is read from the environment variable.
+fn serve() {
+    static SERVER: OnceLock<()> = OnceLock::new();
+    SERVER.get_or_init(|| {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        std::env::set_var(METADATA_HOST_ENV, listener.local_addr().unwrap().to_string());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                std::thread::spawn(move || handle(stream));
+            }
+        });
+    });
+}
+
+fn handle(mut stream: TcpStream) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let mut flavor = false;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        if header.trim().is_empty() {
+            break;
+        }
+        flavor |= header.to_ascii_lowercase().trim() == "metadata-flavor: google";
+    }
+    let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query: HashMap<&str, &str> = query.split('&').filter_map(|v| v.split_once('=')).collect();
+    let (status, etag, body) = if flavor {
+        respond(path.trim_start_matches("/computeMetadata/v1/"), &query)
+    } else {
+        (403, "", "".to_string())
+    };
+    let response = format!(
+        "HTTP/1.1 {status} X\r\nETag: {etag}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).unwrap();
+}
+
+fn respond(suffix: &str, query: &HashMap<&str, &str>) -> (u16, &'static str, String) {
+    match suffix {
+        "instance/zone" => (200, "", "projects/123456/zones/asia-northeast1-a".to_string()),
+        "instance/service-accounts/default/email" => (200, "", " sa@example.com\n".to_string()),
+        "instance/service-accounts/default/scopes" => (200, "", "scope1\n scope2 \n\n".to_string()),
+        "instance/attributes/" if query.get("recursive") == Some(&"true") => {
+            (200, "", r#"{"config":"v1","env":"test"}"#.to_string())
+        }
+        "project/attributes/" if query.get("recursive") == Some(&"true") => (200, "", "not json".to_string()),
+        "instance/attributes/config" => {
+            let waiting = query.get("wait_for_change") == Some(&"true");
+            match query.get("last_etag") {
+                // The current value is returned immediately if the last ETag is outdated.
+                Some(&"etag1") if waiting => {
+                    assert_eq!(Some(&"2"), query.get("timeout_sec"));
+                    std::thread::sleep(Duration::from_millis(100));
+                    (200, "etag2", "v2".to_string())
+                }
+                _ => (200, "etag1", "v1".to_string()),
+            }
+        }
+        "instance/attributes/unavailable" => (503, "", "".to_string()),
+        _ => (404, "", "not found".to_string()),
+    }
+}
+
+#[tokio::test]
+async fn test_zone() {
+    serve();
+    assert_eq!("asia-northeast1-a", zone().await.unwrap());
+}
+
+#[tokio::test]
+async fn test_email() {
+    serve();
+    assert_eq!("sa@example.com", email("default").await.unwrap());
+    match email("unknown").await {
+        Err(Error::NotDefined(suffix)) => assert_eq!("instance/service-accounts/unknown/email", suffix),
+        r => unreachable!("unexpected result {r:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_scopes() {
+    serve();
+    assert_eq!(vec!["scope1", "scope2"], scopes("default").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_attributes() {
+    serve();
+    let attributes = instance_attributes().await.unwrap();
+    assert_eq!(2, attributes.len());
+    assert_eq!("v1", attributes["config"]);
+    assert_eq!("test", attributes["env"]);
+    assert!(matches!(project_attributes().await, Err(Error::InvalidAttributes(_))));
+
+    assert_eq!("v1", instance_attribute("config").await.unwrap());
+    match instance_attribute("unknown").await {
+        Err(e) => assert!(matches!(e, Error::NotDefined(_)) && !e.is_transient()),
+        Ok(v) => unreachable!("unexpected value {v}"),
+    }
+    match instance_attribute("unavailable").await {
+        Err(e) => assert!(matches!(e, Error::InvalidResponse(503)) && e.is_transient()),
+        Ok(v) => unreachable!("unexpected value {v}"),
+    }
+}
+
+#[tokio::test]
+async fn test_wait_for_change() {
+    serve();
+    let suffix = "instance/attributes/config";
+    let timeout = Duration::from_secs(2);
+    let current = wait_for_change(suffix, "", timeout).await.unwrap();
+    assert_eq!(
+        Watched {
+            value: "v1".to_string(),
+            etag: "etag1".to_string(),
+        },
+        current
+    );
+    let outdated = wait_for_change(suffix, "outdated", timeout).await.unwrap();
+    assert_eq!(current, outdated);
+    let changed = wait_for_change(suffix, &current.etag, timeout).await.unwrap();
+    assert_eq!(
+        Watched {
+            value: "v2".to_string(),
+            etag: "etag2".to_string(),
+        },
+        changed
+    );
+}
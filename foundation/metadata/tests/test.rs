@@ -1,4 +1,4 @@
-use google_cloud_metadata::{email, on_gce, Error};
+use google_cloud_metadata::{email, instance_attribute, on_gce, zone, Error};
 
 #[tokio::test]
 async fn test_on_gce() {
@@ -22,3 +22,13 @@ async fn test_email() {
         unreachable!()
     }
 }
+
+#[tokio::test]
+async fn test_not_on_gce() {
+    assert!(matches!(zone().await, Err(Error::NotOnGCE)));
+    let result = instance_attribute("key").await;
+    match result {
+        Err(e) => assert!(matches!(e, Error::NotOnGCE) && !e.is_transient()),
+        Ok(_) => unreachable!(),
+    }
+}
@@ -2,11 +2,43 @@ use std::fmt::Debug;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use time::OffsetDateTime;
+
+/// Token is the value of the Authorization header with its expiry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    /// The value of the Authorization header like `Bearer ya29...`.
+    pub value: String,
+    /// The time the token expires at. None means the expiry is unknown.
+    pub expires_at: Option<OffsetDateTime>,
+}
+
+/// TokenCallback is invoked with the token when the token source mints the new token.
+pub type TokenCallback = Arc<dyn Fn(&Token) + Send + Sync>;
 
 #[async_trait]
 pub trait TokenSource: Send + Sync + Debug {
     /// token returns the valid token
     async fn token(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// token_with_expiry returns the valid token with its expiry.
+    /// The default implementation returns the token without the expiry.
+    async fn token_with_expiry(&self) -> Result<Token, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Token {
+            value: self.token().await?,
+            expires_at: None,
+        })
+    }
+
+    /// force_refresh discards the cached token and mints the new one even if the cached token is still valid.
+    /// It is used when the credentials are rotated. The default implementation is the same as `token_with_expiry`.
+    async fn force_refresh(&self) -> Result<Token, Box<dyn std::error::Error + Send + Sync>> {
+        self.token_with_expiry().await
+    }
+
+    /// on_refresh registers the callback invoked every time the new token is minted.
+    /// The default implementation never invokes the callback.
+    fn on_refresh(&self, _callback: TokenCallback) {}
 }
 
 pub trait TokenSourceProvider: Send + Sync + Debug {
@@ -734,6 +734,25 @@ pub struct QueryPlan {
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TransactionOptions {
+    /// When `exclude_txn_from_change_streams` is set to `true`:
+    ///   * Modifications from this transaction will not be recorded in change
+    ///   streams with DDL option `allow_txn_exclusion=true` that are tracking
+    ///   columns modified by these transactions.
+    ///   * Modifications from this transaction will be recorded in change streams
+    ///   with DDL option `allow_txn_exclusion=false or not set` that are
+    ///   tracking columns modified by these transactions.
+    ///
+    /// When `exclude_txn_from_change_streams` is set to `false` or not set,
+    /// Modifications from this transaction will be recorded in all change streams
+    /// that are tracking columns modified by these transactions.
+    /// `exclude_txn_from_change_streams` may only be specified for read-write or
+    /// partitioned-dml transactions, otherwise the API will return an
+    /// `INVALID_ARGUMENT` error.
+    #[prost(bool, tag = "5")]
+    pub exclude_txn_from_change_streams: bool,
+    /// Isolation level for the transaction.
+    #[prost(enumeration = "transaction_options::IsolationLevel", tag = "6")]
+    pub isolation_level: i32,
     /// Required. The type of transaction.
     #[prost(oneof = "transaction_options::Mode", tags = "1, 3, 2")]
     pub mode: ::core::option::Option<transaction_options::Mode>,
@@ -794,6 +813,56 @@ pub mod transaction_options {
             }
         }
     }
+    /// `IsolationLevel` is used when setting `isolation_level` for a transaction.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum IsolationLevel {
+        /// Default value.
+        ///
+        /// If the value is not specified, the `SERIALIZABLE` isolation level is
+        /// used.
+        Unspecified = 0,
+        /// All transactions appear as if they executed in a serial order, even if
+        /// some of the reads, writes, and other operations of distinct transactions
+        /// actually occurred in parallel. Spanner assigns commit timestamps that
+        /// reflect the order of committed transactions to implement this property.
+        Serializable = 1,
+        /// All reads performed during the transaction observe a consistent snapshot
+        /// of the database, and the transaction will only successfully commit in
+        /// the absence of conflicts between its updates and any concurrent updates
+        /// that have occurred since that snapshot. Consequently, in contrast to
+        /// `SERIALIZABLE` transactions, only write-write conflicts are detected in
+        /// snapshot transactions.
+        ///
+        /// This isolation level does not support Read-only and Partitioned DML
+        /// transactions.
+        ///
+        /// When `REPEATABLE_READ` is specified on a read-write transaction, the
+        /// locking semantics default to `OPTIMISTIC`.
+        RepeatableRead = 2,
+    }
+    impl IsolationLevel {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                IsolationLevel::Unspecified => "ISOLATION_LEVEL_UNSPECIFIED",
+                IsolationLevel::Serializable => "SERIALIZABLE",
+                IsolationLevel::RepeatableRead => "REPEATABLE_READ",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "ISOLATION_LEVEL_UNSPECIFIED" => Some(Self::Unspecified),
+                "SERIALIZABLE" => Some(Self::Serializable),
+                "REPEATABLE_READ" => Some(Self::RepeatableRead),
+                _ => None,
+            }
+        }
+    }
     /// Message type to initiate a Partitioned DML transaction.
     #[allow(clippy::derive_partial_eq_without_eq)]
     #[derive(Clone, PartialEq, ::prost::Message)]
@@ -1083,6 +1152,12 @@ pub enum TypeCode {
     ///    preserved.
     /// - JSON array elements will have their order preserved.
     Json = 11,
+    /// Encoded as `string`, in `ISO8601` duration format -
+    /// `P\[n\]Y\[n\]M\[n\]DT\[n\]H\[n\]M\[n[.fraction]\]S`
+    /// where `n` is an integer.
+    /// For example, `P1Y2M3DT4H5M6.5S` represents time duration of 1 year, 2
+    /// months, 3 days, 4 hours, 5 minutes, and 6.5 seconds.
+    Interval = 16,
 }
 impl TypeCode {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -1103,6 +1178,7 @@ impl TypeCode {
             TypeCode::Struct => "STRUCT",
             TypeCode::Numeric => "NUMERIC",
             TypeCode::Json => "JSON",
+            TypeCode::Interval => "INTERVAL",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -1120,6 +1196,7 @@ impl TypeCode {
             "STRUCT" => Some(Self::Struct),
             "NUMERIC" => Some(Self::Numeric),
             "JSON" => Some(Self::Json),
+            "INTERVAL" => Some(Self::Interval),
             _ => None,
         }
     }
@@ -1676,6 +1753,17 @@ pub struct ExecuteSqlRequest {
     /// `partition_token`, the API will return an `INVALID_ARGUMENT` error.
     #[prost(bool, tag = "16")]
     pub data_boost_enabled: bool,
+    /// Optional. If set to true, this statement marks the end of the transaction.
+    /// The transaction should be committed or aborted after this statement
+    /// executes, and attempts to execute any other requests against this
+    /// transaction (including reads and queries) will be rejected.
+    ///
+    /// For DML statements, setting this option may cause some error reporting to
+    /// be deferred until commit time (e.g. validation of unique constraints).
+    /// Given this, successful execution of a DML statement should not be assumed
+    /// until a subsequent Commit call completes successfully.
+    #[prost(bool, tag = "17")]
+    pub last_statement: bool,
 }
 /// Nested message and enum types in `ExecuteSqlRequest`.
 pub mod execute_sql_request {
@@ -1805,6 +1893,17 @@ pub struct ExecuteBatchDmlRequest {
     /// Common options for this request.
     #[prost(message, optional, tag = "5")]
     pub request_options: ::core::option::Option<RequestOptions>,
+    /// Optional. If set to true, this request marks the end of the transaction.
+    /// The transaction should be committed or aborted after these statements
+    /// execute, and attempts to execute any other requests against this
+    /// transaction (including reads and queries) will be rejected.
+    ///
+    /// Setting this option may cause some error reporting to be deferred until
+    /// commit time (e.g. validation of unique constraints). Given this, successful
+    /// execution of statements should not be assumed until a subsequent Commit
+    /// call completes successfully.
+    #[prost(bool, tag = "6")]
+    pub last_statements: bool,
 }
 /// Nested message and enum types in `ExecuteBatchDmlRequest`.
 pub mod execute_batch_dml_request {
@@ -0,0 +1,261 @@
+use std::sync::Arc;
+
+use backon::{ExponentialBuilder, Retryable};
+
+use google_cloud_token::{NopeTokenSourceProvider, TokenSource, TokenSourceProvider};
+
+use crate::http::error;
+use crate::http::iam_credentials_client::IamCredentialsClient;
+use crate::http::service_account_name;
+use crate::http::sign_blob::{SignBlobRequest, SignBlobResponse};
+use crate::http::sign_jwt::{SignJwtRequest, SignJwtResponse};
+
+#[derive(Debug)]
+pub struct ClientConfig {
+    pub endpoint: String,
+    pub token_source_provider: Box<dyn TokenSourceProvider>,
+    pub http: reqwest_middleware::ClientWithMiddleware,
+    /// The delegation chain used by all the requests. The emails or the resource names of the service accounts.
+    pub delegates: Vec<String>,
+    /// The retry setting of the transient failures.
+    pub retry: ExponentialBuilder,
+}
+
+#[cfg(feature = "auth")]
+pub use google_cloud_auth;
+
+#[cfg(feature = "auth")]
+impl ClientConfig {
+    pub async fn with_auth(self) -> Result<Self, google_cloud_auth::error::Error> {
+        let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new(Self::auth_config()).await?;
+        Ok(self.with_token_source(ts).await)
+    }
+
+    pub async fn with_credentials(
+        self,
+        credentials: google_cloud_auth::credentials::CredentialsFile,
+    ) -> Result<Self, google_cloud_auth::error::Error> {
+        let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new_with_credentials(
+            Self::auth_config(),
+            Box::new(credentials),
+        )
+        .await?;
+        Ok(self.with_token_source(ts).await)
+    }
+
+    async fn with_token_source(mut self, ts: google_cloud_auth::token::DefaultTokenSourceProvider) -> Self {
+        self.token_source_provider = Box::new(ts);
+        self
+    }
+
+    fn auth_config() -> google_cloud_auth::project::Config<'static> {
+        google_cloud_auth::project::Config {
+            audience: None,
+            scopes: Some(&crate::http::iam_credentials_client::SCOPES),
+            sub: None,
+        }
+    }
+}
+
+/// default_http_client uses rustls if the `rustls-tls` feature is enabled, even with `default-tls`.
+fn default_http_client() -> reqwest::Client {
+    let builder = reqwest::Client::builder();
+    #[cfg(feature = "rustls-tls")]
+    let builder = builder.use_rustls_tls();
+    builder.build().unwrap()
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://iamcredentials.googleapis.com".to_string(),
+            token_source_provider: Box::new(NopeTokenSourceProvider {}),
+            http: reqwest_middleware::ClientBuilder::new(default_http_client()).build(),
+            delegates: vec![],
+            retry: ExponentialBuilder::default(),
+        }
+    }
+}
+
+impl ClientConfig {
+    pub fn with_endpoint(mut self, value: impl Into<String>) -> Self {
+        self.endpoint = value.into();
+        self
+    }
+    pub fn with_http_client(mut self, value: reqwest_middleware::ClientWithMiddleware) -> Self {
+        self.http = value;
+        self
+    }
+    pub fn with_delegates(mut self, value: Vec<String>) -> Self {
+        self.delegates = value;
+        self
+    }
+    pub fn with_retry(mut self, value: ExponentialBuilder) -> Self {
+        self.retry = value;
+        self
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Http(#[from] error::Error),
+    /// The caller has no permission to sign as the service account.
+    /// The role `roles/iam.serviceAccountTokenCreator` on the service account grants the permission.
+    #[error("{permission} denied on {service_account}, roles/iam.serviceAccountTokenCreator is required: {source}")]
+    PermissionDenied {
+        permission: &'static str,
+        service_account: String,
+        source: error::ErrorResponse,
+    },
+    #[error("failed to serialize the claims: {0}")]
+    Claims(#[from] serde_json::Error),
+}
+
+impl Error {
+    /// is_retryable returns true for the transient failures.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Http(e) => e.is_retryable(),
+            _ => false,
+        }
+    }
+
+    fn from_http(e: error::Error, permission: &'static str, service_account: &str) -> Self {
+        match e {
+            error::Error::Response(r) if r.code == 403 => Error::PermissionDenied {
+                permission,
+                service_account: service_account.to_string(),
+                source: r,
+            },
+            e => Error::Http(e),
+        }
+    }
+}
+
+/// Client signs the blobs and the JWTs with the Google-managed keys of the service accounts.
+/// It is used to sign without the private key like on Google Compute Engine or with the workload identity.
+#[derive(Clone)]
+pub struct Client {
+    credentials_client: IamCredentialsClient,
+    delegates: Vec<String>,
+    retry: ExponentialBuilder,
+}
+
+impl Client {
+    pub fn new(config: ClientConfig) -> Self {
+        let ts = config.token_source_provider.token_source();
+        Self::new_with_token_source(ts, config)
+    }
+
+    /// new_with_token_source creates the client sharing the token source of the other client.
+    /// The `token_source_provider` of the config is not used.
+    pub fn new_with_token_source(ts: Arc<dyn TokenSource>, config: ClientConfig) -> Self {
+        Self {
+            credentials_client: IamCredentialsClient::new(ts, &config.endpoint, config.http),
+            delegates: config.delegates.iter().map(|v| service_account_name(v)).collect(),
+            retry: config.retry,
+        }
+    }
+
+    /// https://cloud.google.com/iam/docs/reference/credentials/rest
+    /// [IamCredentialsClient](crate::http::iam_credentials_client::IamCredentialsClient) calls the API without retries.
+    pub fn credentials(&self) -> &IamCredentialsClient {
+        &self.credentials_client
+    }
+
+    /// sign_blob signs the bytes with the system-managed private key of the service account.
+    /// The service account is the email or the resource name like `projects/-/serviceAccounts/{email}`.
+    pub async fn sign_blob(&self, service_account: &str, payload: &[u8]) -> Result<SignBlobResponse, Error> {
+        let name = service_account_name(service_account);
+        let request = SignBlobRequest {
+            delegates: self.delegates.clone(),
+            payload: payload.to_vec(),
+        };
+        let action = || async {
+            self.credentials_client
+                .sign_blob(&name, &request)
+                .await
+                .map_err(|e| Error::from_http(e, "iam.serviceAccounts.signBlob", &name))
+        };
+        action.retry(&self.retry).when(Error::is_retryable).await
+    }
+
+    /// sign_jwt signs the claims as the JWT with the system-managed private key of the service account.
+    /// The `exp` claim is required and must be within 12 hours. The `iat` claim is added by the server if missing.
+    pub async fn sign_jwt<T: serde::Serialize>(
+        &self,
+        service_account: &str,
+        claims: &T,
+    ) -> Result<SignJwtResponse, Error> {
+        let name = service_account_name(service_account);
+        let request = SignJwtRequest {
+            delegates: self.delegates.clone(),
+            payload: serde_json::to_string(claims)?,
+        };
+        let action = || async {
+            self.credentials_client
+                .sign_jwt(&name, &request)
+                .await
+                .map_err(|e| Error::from_http(e, "iam.serviceAccounts.signJwt", &name))
+        };
+        action.retry(&self.retry).when(Error::is_retryable).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use serial_test::serial;
+
+    use crate::client::{Client, ClientConfig};
+
+    async fn new_client() -> (Client, String) {
+        let cred = google_cloud_auth::credentials::CredentialsFile::new().await.unwrap();
+        let email = cred.client_email.clone().unwrap();
+        let config = ClientConfig::default().with_credentials(cred).await.unwrap();
+        (Client::new(config), email)
+    }
+
+    #[ctor::ctor]
+    fn init() {
+        let _ = tracing_subscriber::fmt().try_init();
+    }
+
+    /// IAM Service Account Credentials API is required
+    #[tokio::test]
+    #[serial]
+    async fn test_sign_blob() {
+        let (client, email) = new_client().await;
+        let signed = client.sign_blob(&email, b"payload").await.unwrap();
+        assert!(!signed.key_id.is_empty());
+        assert_eq!(signed.signed_blob.len(), 256);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_sign_jwt() {
+        let (client, email) = new_client().await;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let claims = serde_json::json!({
+            "iss": email,
+            "aud": "https://example.com",
+            "iat": now,
+            "exp": now + 300,
+        });
+        let signed = client.sign_jwt(&email, &claims).await.unwrap();
+        assert_eq!(signed.signed_jwt.split('.').count(), 3);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_permission_denied() {
+        let (client, _) = new_client().await;
+        let result = client
+            .sign_blob("unknown@example.iam.gserviceaccount.com", b"payload")
+            .await;
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("roles/iam.serviceAccountTokenCreator"), "{message}");
+    }
+}
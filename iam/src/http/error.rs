@@ -0,0 +1,76 @@
+use std::fmt;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    /// An error returned from the IAM Service Account Credentials API.
+    #[error(transparent)]
+    Response(#[from] ErrorResponse),
+
+    /// An error from the HTTP client.
+    #[error(transparent)]
+    HttpClient(#[from] reqwest::Error),
+
+    /// An error from the HTTP client.
+    #[error(transparent)]
+    HttpMiddleware(anyhow::Error),
+
+    /// An error from a token source.
+    #[error("token source failed: {0}")]
+    TokenSource(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl From<reqwest_middleware::Error> for Error {
+    fn from(error: reqwest_middleware::Error) -> Self {
+        match error {
+            reqwest_middleware::Error::Reqwest(err) => Error::HttpClient(err),
+            reqwest_middleware::Error::Middleware(err) => Error::HttpMiddleware(err),
+        }
+    }
+}
+
+impl Error {
+    /// is_retryable returns true for the transient failures.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Response(r) => r.is_retryable(),
+            Error::HttpClient(e) => e.is_timeout() || e.is_connect(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorResponse {
+    /// An HTTP status value, without the textual description.
+    ///
+    /// Example values include: `400` (Bad Request), `401` (Unauthorized), and `404` (Not Found).
+    pub code: u16,
+
+    /// The status code like `FAILED_PRECONDITION`.
+    pub status: Option<String>,
+
+    /// Description of the error.
+    pub message: String,
+}
+
+const RETRYABLE_CODES: [u16; 5] = [429, 500, 502, 503, 504];
+
+impl ErrorResponse {
+    pub fn is_retryable(&self) -> bool {
+        RETRYABLE_CODES.contains(&self.code)
+    }
+}
+
+impl fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.message.fmt(f)
+    }
+}
+
+impl std::error::Error for ErrorResponse {}
+
+#[derive(serde::Deserialize)]
+pub(crate) struct ErrorWrapper {
+    pub(crate) error: ErrorResponse,
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use reqwest::Response;
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+use google_cloud_token::TokenSource;
+
+use crate::http::error::{Error, ErrorWrapper};
+use crate::http::sign_blob::{SignBlobRequest, SignBlobResponse};
+use crate::http::sign_jwt::{SignJwtRequest, SignJwtResponse};
+use crate::http::{sign_blob, sign_jwt};
+
+pub const SCOPES: [&str; 1] = ["https://www.googleapis.com/auth/cloud-platform"];
+
+/// IamCredentialsClient calls the REST API of IAM Service Account Credentials.
+/// The requests are not retried, `crate::client::Client` retries them.
+#[derive(Debug, Clone)]
+pub struct IamCredentialsClient {
+    ts: Arc<dyn TokenSource>,
+    endpoint: String,
+    http: Client,
+}
+
+impl IamCredentialsClient {
+    pub fn new(ts: Arc<dyn TokenSource>, endpoint: &str, http: Client) -> Self {
+        Self {
+            ts,
+            endpoint: format!("{endpoint}/v1"),
+            http,
+        }
+    }
+
+    /// https://cloud.google.com/iam/docs/reference/credentials/rest/v1/projects.serviceAccounts/signBlob
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn sign_blob(&self, name: &str, req: &SignBlobRequest) -> Result<SignBlobResponse, Error> {
+        let builder = sign_blob::build(&self.endpoint, &self.http, name, req);
+        self.send(builder).await
+    }
+
+    /// https://cloud.google.com/iam/docs/reference/credentials/rest/v1/projects.serviceAccounts/signJwt
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn sign_jwt(&self, name: &str, req: &SignJwtRequest) -> Result<SignJwtResponse, Error> {
+        let builder = sign_jwt::build(&self.endpoint, &self.http, name, req);
+        self.send(builder).await
+    }
+
+    async fn with_headers(&self, builder: RequestBuilder) -> Result<RequestBuilder, Error> {
+        let token = self.ts.token().await.map_err(Error::TokenSource)?;
+        Ok(builder
+            .header("X-Goog-Api-Client", "rust")
+            .header(reqwest::header::USER_AGENT, "google-cloud-iam")
+            .header(reqwest::header::AUTHORIZATION, token))
+    }
+
+    async fn send<T>(&self, builder: RequestBuilder) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let request = self.with_headers(builder).await?;
+        let response = request.send().await?;
+        let response = Self::check_response_status(response).await?;
+        Ok(response.json().await?)
+    }
+
+    /// Checks whether an HTTP response is successful and returns it, or returns an error.
+    async fn check_response_status(response: Response) -> Result<Response, Error> {
+        // Check the status code, returning the response if it is not an error.
+        let error = match response.error_for_status_ref() {
+            Ok(_) => return Ok(response),
+            Err(error) => error,
+        };
+
+        // try to extract a response error, falling back to the status error if it can not be parsed.
+        Err(response
+            .json::<ErrorWrapper>()
+            .await
+            .map(|wrapper| Error::Response(wrapper.error))
+            .unwrap_or(Error::HttpClient(error)))
+    }
+}
@@ -0,0 +1,30 @@
+pub mod error;
+pub mod iam_credentials_client;
+pub mod sign_blob;
+pub mod sign_jwt;
+
+/// The bytes fields are encoded as base64 in JSON.
+pub(crate) mod bytes {
+    use base64::prelude::BASE64_STANDARD;
+    use base64::Engine;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64_STANDARD.encode(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        BASE64_STANDARD.decode(value).map_err(de::Error::custom)
+    }
+}
+
+/// service_account_name returns the resource name of the service account.
+/// The email is converted to `projects/-/serviceAccounts/{email}` and the resource name is used as it is.
+pub fn service_account_name(service_account: &str) -> String {
+    if service_account.starts_with("projects/") {
+        service_account.to_string()
+    } else {
+        format!("projects/-/serviceAccounts/{service_account}")
+    }
+}
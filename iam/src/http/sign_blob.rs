@@ -0,0 +1,28 @@
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SignBlobRequest {
+    /// The delegation chain like `projects/-/serviceAccounts/{email}`.
+    /// Each service account must be granted `roles/iam.serviceAccountTokenCreator` on the next one.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub delegates: Vec<String>,
+    /// Required. The bytes to sign.
+    #[serde(with = "crate::http::bytes")]
+    pub payload: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SignBlobResponse {
+    /// The ID of the key used to sign the blob.
+    pub key_id: String,
+    /// The signature for the blob.
+    #[serde(with = "crate::http::bytes")]
+    pub signed_blob: Vec<u8>,
+}
+
+pub fn build(base_url: &str, client: &Client, name: &str, req: &SignBlobRequest) -> RequestBuilder {
+    let url = format!("{}/{}:signBlob", base_url, name);
+    client.post(url).json(req)
+}
@@ -0,0 +1,26 @@
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SignJwtRequest {
+    /// The delegation chain like `projects/-/serviceAccounts/{email}`.
+    /// Each service account must be granted `roles/iam.serviceAccountTokenCreator` on the next one.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub delegates: Vec<String>,
+    /// Required. The JWT claims set in JSON. The `exp` claim must be within 12 hours.
+    pub payload: String,
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SignJwtResponse {
+    /// The ID of the key used to sign the JWT.
+    pub key_id: String,
+    /// The signed JWT.
+    pub signed_jwt: String,
+}
+
+pub fn build(base_url: &str, client: &Client, name: &str, req: &SignJwtRequest) -> RequestBuilder {
+    let url = format!("{}/{}:signJwt", base_url, name);
+    client.post(url).json(req)
+}
@@ -0,0 +1,69 @@
+//! # google-cloud-iam
+//!
+//! Google Cloud Platform IAM Service Account Credentials Client library.
+//!
+//! ## Quickstart
+//!
+//! ### Authentication
+//! There are two ways to create a client that is authenticated against the google cloud.
+//!
+//! #### Automatically
+//!
+//! The function `with_auth()` will try and read the credentials from a file specified in the environment variable `GOOGLE_APPLICATION_CREDENTIALS`, `GOOGLE_APPLICATION_CREDENTIALS_JSON` or
+//! from a metadata server.
+//!
+//! This is also described in [google-cloud-auth](https://github.com/yoshidan/google-cloud-rust/blob/main/foundation/auth/README.md)
+//!
+//! ```rust
+//! use google_cloud_iam::client::{Client, ClientConfig};
+//!
+//! async fn run() {
+//!     let config = ClientConfig::default().with_auth().await.unwrap();
+//!     let client = Client::new(config);
+//! }
+//! ```
+//!
+//! #### Manually
+//!
+//! When you can't use the `gcloud` authentication but you have a different way to get your credentials (e.g a different environment variable)
+//! you can parse your own version of the 'credentials-file' and use it like that:
+//!
+//! ```rust
+//! use google_cloud_auth::credentials::CredentialsFile;
+//! // or google_cloud_iam::client::google_cloud_auth::credentials::CredentialsFile
+//! use google_cloud_iam::client::{Client, ClientConfig};
+//!
+//! async fn run(cred: CredentialsFile) {
+//!     let config = ClientConfig::default().with_credentials(cred).await.unwrap();
+//!     let client = Client::new(config);
+//! }
+//! ```
+//!
+//! ### Usage
+//!
+//! The blobs and the JWTs are signed by the Google-managed key of the service account, so no private key is required.
+//! The caller requires `roles/iam.serviceAccountTokenCreator` on the service account.
+//!
+//! ```rust
+//! use google_cloud_iam::client::{Client, ClientConfig};
+//!
+//! async fn run(config: ClientConfig) {
+//!     // The delegation chain is optional.
+//!     let config = config.with_delegates(vec!["delegate@project.iam.gserviceaccount.com".to_string()]);
+//!     let client = Client::new(config);
+//!     let service_account = "signer@project.iam.gserviceaccount.com";
+//!
+//!     let signed = client.sign_blob(service_account, b"payload").await.unwrap();
+//!     println!("key={} signature={:?}", signed.key_id, signed.signed_blob);
+//!
+//!     let claims = serde_json::json!({
+//!         "iss": service_account,
+//!         "aud": "https://example.com",
+//!         "exp": 1700000000,
+//!     });
+//!     let signed = client.sign_jwt(service_account, &claims).await.unwrap();
+//!     println!("jwt={}", signed.signed_jwt);
+//! }
+//! ```
+pub mod client;
+pub mod http;
@@ -0,0 +1,479 @@
+use backon::{ExponentialBuilder, Retryable};
+use sha2::Digest as _;
+use zeroize::{Zeroize, Zeroizing};
+
+use google_cloud_token::{NopeTokenSourceProvider, TokenSourceProvider};
+
+use crate::envelope;
+use crate::envelope::Header;
+use crate::http::crypto_key::decrypt::DecryptRequest;
+use crate::http::crypto_key::encrypt::EncryptRequest;
+use crate::http::crypto_key_version::asymmetric_sign::{AsymmetricSignRequest, AsymmetricSignResponse, Digest};
+use crate::http::crypto_key_version::get_public_key::PublicKey;
+use crate::http::crypto_key_version::list::ListCryptoKeyVersionsRequest;
+use crate::http::crypto_key_version::mac_sign::{MacSignRequest, MacSignResponse};
+use crate::http::crypto_key_version::mac_verify::MacVerifyRequest;
+use crate::http::crypto_key_version::{CryptoKeyVersion, CryptoKeyVersionState, DigestAlgorithm};
+use crate::http::kms_client::{KmsClient, SCOPES};
+use crate::http::{crc32c, error};
+
+#[derive(Debug)]
+pub struct ClientConfig {
+    pub endpoint: String,
+    pub token_source_provider: Box<dyn TokenSourceProvider>,
+    pub http: reqwest_middleware::ClientWithMiddleware,
+    /// The retry setting of the transient failures and the integrity check failures.
+    pub retry: ExponentialBuilder,
+}
+
+#[cfg(feature = "auth")]
+pub use google_cloud_auth;
+
+#[cfg(feature = "auth")]
+impl ClientConfig {
+    pub async fn with_auth(self) -> Result<Self, google_cloud_auth::error::Error> {
+        let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new(Self::auth_config()).await?;
+        Ok(self.with_token_source(ts).await)
+    }
+
+    pub async fn with_credentials(
+        self,
+        credentials: google_cloud_auth::credentials::CredentialsFile,
+    ) -> Result<Self, google_cloud_auth::error::Error> {
+        let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new_with_credentials(
+            Self::auth_config(),
+            Box::new(credentials),
+        )
+        .await?;
+        Ok(self.with_token_source(ts).await)
+    }
+
+    async fn with_token_source(mut self, ts: google_cloud_auth::token::DefaultTokenSourceProvider) -> Self {
+        self.token_source_provider = Box::new(ts);
+        self
+    }
+
+    fn auth_config() -> google_cloud_auth::project::Config<'static> {
+        google_cloud_auth::project::Config {
+            audience: None,
+            scopes: Some(&SCOPES),
+            sub: None,
+        }
+    }
+}
+
+/// default_http_client prefers rustls when both `default-tls` and `rustls-tls` are enabled.
+fn default_http_client() -> reqwest::Client {
+    let builder = reqwest::Client::builder();
+    #[cfg(feature = "rustls-tls")]
+    let builder = builder.use_rustls_tls();
+    builder.build().unwrap()
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://cloudkms.googleapis.com".to_string(),
+            token_source_provider: Box::new(NopeTokenSourceProvider {}),
+            http: reqwest_middleware::ClientBuilder::new(default_http_client()).build(),
+            retry: ExponentialBuilder::default(),
+        }
+    }
+}
+
+impl ClientConfig {
+    pub fn with_endpoint(mut self, value: impl Into<String>) -> Self {
+        self.endpoint = value.into();
+        self
+    }
+    pub fn with_http_client(mut self, value: reqwest_middleware::ClientWithMiddleware) -> Self {
+        self.http = value;
+        self
+    }
+    pub fn with_retry(mut self, value: ExponentialBuilder) -> Self {
+        self.retry = value;
+        self
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Http(#[from] error::Error),
+    /// The request or the response is corrupted in transit. The operation is retried by the client.
+    #[error("integrity check failed: {0}")]
+    Integrity(&'static str),
+    #[error("algorithm {1} of {0} is not supported by the operation")]
+    UnsupportedAlgorithm(String, String),
+    #[error("no enabled version found: key={0}")]
+    NoEnabledVersion(String),
+    #[error(transparent)]
+    Envelope(#[from] envelope::Error),
+    /// The envelope is wrapped by the crypto key other than the expected one.
+    #[error("unexpected key wraps the envelope: expected={0}, actual={1}")]
+    UnexpectedKeyName(String, String),
+}
+
+impl Error {
+    /// is_retryable returns true for the transient failures and the integrity check failures.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Http(e) => e.is_retryable(),
+            Error::Integrity(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// KeyVersion specifies the crypto key version used for the operation.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum KeyVersion {
+    /// The resource name of the crypto key version like
+    /// `projects/*/locations/*/keyRings/*/cryptoKeys/*/cryptoKeyVersions/*`.
+    Pinned(String),
+    /// The resource name of the crypto key like `projects/*/locations/*/keyRings/*/cryptoKeys/*`.
+    /// The primary version is used for the keys with the primary, otherwise the latest enabled version is used.
+    Primary(String),
+}
+
+#[derive(Clone)]
+pub struct Client {
+    kms_client: KmsClient,
+    retry: ExponentialBuilder,
+}
+
+impl Client {
+    pub fn new(config: ClientConfig) -> Self {
+        let ts = config.token_source_provider.token_source();
+        Self {
+            kms_client: KmsClient::new(ts, &config.endpoint, config.http),
+            retry: config.retry,
+        }
+    }
+
+    /// https://cloud.google.com/kms/docs/reference/rest
+    /// [KmsClient](crate::http::kms_client::KmsClient) calls the API without the retries and the integrity checks.
+    pub fn kms(&self) -> &KmsClient {
+        &self.kms_client
+    }
+
+    /// resolve_version returns the crypto key version specified by the `KeyVersion`.
+    pub async fn resolve_version(&self, key: &KeyVersion) -> Result<CryptoKeyVersion, Error> {
+        match key {
+            KeyVersion::Pinned(name) => {
+                let action = || async { Ok::<_, Error>(self.kms_client.get_crypto_key_version(name).await?) };
+                action.retry(&self.retry).when(Error::is_retryable).await
+            }
+            KeyVersion::Primary(name) => {
+                let action = || async { Ok::<_, Error>(self.kms_client.get_crypto_key(name).await?) };
+                let crypto_key = action.retry(&self.retry).when(Error::is_retryable).await?;
+                match crypto_key.primary {
+                    Some(primary) => Ok(primary),
+                    // The asymmetric keys have no primary.
+                    None => self.latest_enabled_version(name).await,
+                }
+            }
+        }
+    }
+
+    async fn latest_enabled_version(&self, key_name: &str) -> Result<CryptoKeyVersion, Error> {
+        let mut request = ListCryptoKeyVersionsRequest {
+            filter: Some("state=ENABLED".to_string()),
+            ..Default::default()
+        };
+        let mut latest: Option<(u64, CryptoKeyVersion)> = None;
+        loop {
+            let action =
+                || async { Ok::<_, Error>(self.kms_client.list_crypto_key_versions(key_name, &request).await?) };
+            let response = action.retry(&self.retry).when(Error::is_retryable).await?;
+            for version in response.crypto_key_versions {
+                if version.state != CryptoKeyVersionState::Enabled {
+                    continue;
+                }
+                // The version ids are sequential numbers, so the largest one is the latest.
+                let id = version
+                    .name
+                    .rsplit('/')
+                    .next()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(0);
+                if latest.as_ref().is_none_or(|(latest_id, _)| id > *latest_id) {
+                    latest = Some((id, version));
+                }
+            }
+            match response.next_page_token {
+                Some(token) if !token.is_empty() => request.page_token = Some(token),
+                _ => break,
+            }
+        }
+        latest
+            .map(|v| v.1)
+            .ok_or_else(|| Error::NoEnabledVersion(key_name.to_string()))
+    }
+
+    /// asymmetric_sign signs the data with the asymmetric key.
+    /// The digest of the data is computed by the client for the algorithms with the digest like `EC_SIGN_P256_SHA256`,
+    /// otherwise the data is sent as it is.
+    pub async fn asymmetric_sign(&self, key: &KeyVersion, data: &[u8]) -> Result<AsymmetricSignResponse, Error> {
+        let version = self.resolve_version(key).await?;
+        let request = match version.digest_algorithm() {
+            Some(DigestAlgorithm::Sha256) => Self::digest_request(Digest::Sha256(sha2::Sha256::digest(data).to_vec())),
+            Some(DigestAlgorithm::Sha384) => Self::digest_request(Digest::Sha384(sha2::Sha384::digest(data).to_vec())),
+            Some(DigestAlgorithm::Sha512) => Self::digest_request(Digest::Sha512(sha2::Sha512::digest(data).to_vec())),
+            None if version.algorithm.starts_with("RSA_SIGN_RAW_") || version.algorithm == "EC_SIGN_ED25519" => {
+                AsymmetricSignRequest {
+                    data: data.to_vec(),
+                    data_crc32c: Some(crc32c(data)),
+                    ..Default::default()
+                }
+            }
+            _ => return Err(Error::UnsupportedAlgorithm(version.name, version.algorithm)),
+        };
+        self.sign(&version.name, &request).await
+    }
+
+    /// asymmetric_sign_digest signs the digest computed by the caller with the crypto key version.
+    pub async fn asymmetric_sign_digest(&self, name: &str, digest: Digest) -> Result<AsymmetricSignResponse, Error> {
+        self.sign(name, &Self::digest_request(digest)).await
+    }
+
+    fn digest_request(digest: Digest) -> AsymmetricSignRequest {
+        AsymmetricSignRequest {
+            digest_crc32c: Some(crc32c(digest.value())),
+            digest: Some(digest),
+            ..Default::default()
+        }
+    }
+
+    async fn sign(&self, name: &str, request: &AsymmetricSignRequest) -> Result<AsymmetricSignResponse, Error> {
+        let action = || async {
+            let response = self.kms_client.asymmetric_sign(name, request).await?;
+            if response.name != name {
+                return Err(Error::Integrity("unexpected version signed the request"));
+            }
+            if request.digest.is_some() && !response.verified_digest_crc32c {
+                return Err(Error::Integrity("digest_crc32c is not verified"));
+            }
+            if request.digest.is_none() && !response.verified_data_crc32c {
+                return Err(Error::Integrity("data_crc32c is not verified"));
+            }
+            if response.signature_crc32c != Some(crc32c(&response.signature)) {
+                return Err(Error::Integrity("signature_crc32c mismatch"));
+            }
+            Ok::<_, Error>(response)
+        };
+        action.retry(&self.retry).when(Error::is_retryable).await
+    }
+
+    /// get_public_key returns the public key in PEM format to verify the signature locally.
+    pub async fn get_public_key(&self, key: &KeyVersion) -> Result<PublicKey, Error> {
+        let name = self.version_name(key).await?;
+        let action = || async {
+            let response = self.kms_client.get_public_key(&name).await?;
+            if response.pem_crc32c != Some(crc32c(response.pem.as_bytes())) {
+                return Err(Error::Integrity("pem_crc32c mismatch"));
+            }
+            Ok::<_, Error>(response)
+        };
+        action.retry(&self.retry).when(Error::is_retryable).await
+    }
+
+    /// mac_sign creates the MAC of the data with the HMAC key.
+    pub async fn mac_sign(&self, key: &KeyVersion, data: &[u8]) -> Result<MacSignResponse, Error> {
+        let name = self.version_name(key).await?;
+        let request = MacSignRequest {
+            data: data.to_vec(),
+            data_crc32c: Some(crc32c(data)),
+        };
+        let action = || async {
+            let response = self.kms_client.mac_sign(&name, &request).await?;
+            if response.name != name {
+                return Err(Error::Integrity("unexpected version signed the request"));
+            }
+            if !response.verified_data_crc32c {
+                return Err(Error::Integrity("data_crc32c is not verified"));
+            }
+            if response.mac_crc32c != Some(crc32c(&response.mac)) {
+                return Err(Error::Integrity("mac_crc32c mismatch"));
+            }
+            Ok::<_, Error>(response)
+        };
+        action.retry(&self.retry).when(Error::is_retryable).await
+    }
+
+    /// mac_verify returns true if the MAC is valid for the data.
+    /// The verification has no side effects,
+    /// so it is retried on the transient failures and the integrity check failures.
+    pub async fn mac_verify(&self, key: &KeyVersion, data: &[u8], mac: &[u8]) -> Result<bool, Error> {
+        let name = self.version_name(key).await?;
+        let request = MacVerifyRequest {
+            data: data.to_vec(),
+            data_crc32c: Some(crc32c(data)),
+            mac: mac.to_vec(),
+            mac_crc32c: Some(crc32c(mac)),
+        };
+        let action = || async {
+            let response = self.kms_client.mac_verify(&name, &request).await?;
+            if response.name != name {
+                return Err(Error::Integrity("unexpected version verified the request"));
+            }
+            if !response.verified_data_crc32c {
+                return Err(Error::Integrity("data_crc32c is not verified"));
+            }
+            if !response.verified_mac_crc32c {
+                return Err(Error::Integrity("mac_crc32c is not verified"));
+            }
+            // verified_success_integrity mirrors success, so the invalid MAC is Ok(false).
+            if response.verified_success_integrity != response.success {
+                return Err(Error::Integrity("success is not verified"));
+            }
+            Ok::<_, Error>(response.success)
+        };
+        action.retry(&self.retry).when(Error::is_retryable).await
+    }
+
+    /// version_name resolves the primary version only, the pinned version is used without the request.
+    async fn version_name(&self, key: &KeyVersion) -> Result<String, Error> {
+        match key {
+            KeyVersion::Pinned(name) => Ok(name.clone()),
+            KeyVersion::Primary(_) => Ok(self.resolve_version(key).await?.name),
+        }
+    }
+
+    /// encrypt_envelope encrypts the data with the locally generated AES-256-GCM key
+    /// and wraps the key with the crypto key named `key_name`.
+    /// The returned envelope contains everything `decrypt_envelope` needs except the `aad`.
+    pub async fn encrypt_envelope(&self, key_name: &str, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+        let dek = envelope::generate_key();
+        let mut request = EncryptRequest {
+            plaintext_crc32c: Some(crc32c(&dek)),
+            plaintext: dek,
+            ..Default::default()
+        };
+        let action = || async {
+            let response = self.kms_client.encrypt(key_name, &request).await?;
+            if !response.verified_plaintext_crc32c {
+                return Err(Error::Integrity("plaintext_crc32c is not verified"));
+            }
+            if response.ciphertext_crc32c != Some(crc32c(&response.ciphertext)) {
+                return Err(Error::Integrity("ciphertext_crc32c mismatch"));
+            }
+            Ok::<_, Error>(response)
+        };
+        let response = action.retry(&self.retry).when(Error::is_retryable).await?;
+        let header = Header {
+            key_name: key_name.to_string(),
+            wrapped_key: response.ciphertext,
+        };
+        let sealed = envelope::seal(&header, &request.plaintext, plaintext, aad);
+        request.plaintext.zeroize();
+        Ok(sealed?)
+    }
+
+    /// decrypt_envelope unwraps the data encryption key in the envelope with Cloud KMS and decrypts the data.
+    /// The envelope must be wrapped by the crypto key named `key_name`. The key name in the envelope is
+    /// not trusted, because the envelope made with any key the caller can use would be authenticated.
+    pub async fn decrypt_envelope(&self, key_name: &str, envelope: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+        let header = Header::parse(envelope)?;
+        if header.key_name != key_name {
+            return Err(Error::UnexpectedKeyName(key_name.to_string(), header.key_name));
+        }
+        let request = DecryptRequest {
+            ciphertext_crc32c: Some(crc32c(&header.wrapped_key)),
+            ciphertext: header.wrapped_key,
+            ..Default::default()
+        };
+        let action = || async {
+            let response = self.kms_client.decrypt(&header.key_name, &request).await?;
+            if response.plaintext_crc32c != Some(crc32c(&response.plaintext)) {
+                return Err(Error::Integrity("plaintext_crc32c mismatch"));
+            }
+            Ok::<_, Error>(response)
+        };
+        let response = action.retry(&self.retry).when(Error::is_retryable).await?;
+        let dek = Zeroizing::new(response.plaintext);
+        Ok(envelope::open(envelope, &dek, aad)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use crate::client::{Client, ClientConfig, Error, KeyVersion};
+    use crate::envelope;
+    use crate::http::crypto_key_version::DigestAlgorithm;
+
+    async fn new_client() -> (Client, String) {
+        let cred = google_cloud_auth::credentials::CredentialsFile::new().await.unwrap();
+        let project = cred.project_id.clone().unwrap();
+        let config = ClientConfig::default().with_credentials(cred).await.unwrap();
+        (Client::new(config), project)
+    }
+
+    /// The key ring `gcr_test` must have the key `gcr_test_sign` with EC_SIGN_P256_SHA256,
+    /// the key `gcr_test_mac` with HMAC_SHA256 and the key `gcr_test_encrypt` with GOOGLE_SYMMETRIC_ENCRYPTION.
+    fn key_ring(project: &str) -> String {
+        format!("projects/{project}/locations/us-central1/keyRings/gcr_test")
+    }
+
+    #[ctor::ctor]
+    fn init() {
+        let _ = tracing_subscriber::fmt().try_init();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_asymmetric_sign() {
+        let (client, project) = new_client().await;
+        let key = KeyVersion::Primary(format!("{}/cryptoKeys/gcr_test_sign", key_ring(&project)));
+        let version = client.resolve_version(&key).await.unwrap();
+        assert_eq!(version.algorithm, "EC_SIGN_P256_SHA256");
+
+        let signed = client.asymmetric_sign(&key, b"data").await.unwrap();
+        assert_eq!(signed.name, version.name);
+        assert!(!signed.signature.is_empty());
+
+        let pinned = KeyVersion::Pinned(version.name.clone());
+        let public_key = client.get_public_key(&pinned).await.unwrap();
+        assert_eq!(public_key.name, version.name);
+        assert!(public_key.pem.starts_with("-----BEGIN PUBLIC KEY-----"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_mac() {
+        let (client, project) = new_client().await;
+        let key = KeyVersion::Primary(format!("{}/cryptoKeys/gcr_test_mac", key_ring(&project)));
+        let version = client.resolve_version(&key).await.unwrap();
+        assert_eq!(version.digest_algorithm(), Some(DigestAlgorithm::Sha256));
+
+        let signed = client.mac_sign(&key, b"payload").await.unwrap();
+        assert_eq!(signed.name, version.name);
+
+        let pinned = KeyVersion::Pinned(version.name);
+        assert!(client.mac_verify(&pinned, b"payload", &signed.mac).await.unwrap());
+        assert!(!client.mac_verify(&pinned, b"modified", &signed.mac).await.unwrap());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_envelope() {
+        let (client, project) = new_client().await;
+        let key_name = format!("{}/cryptoKeys/gcr_test_encrypt", key_ring(&project));
+        let encrypted = client.encrypt_envelope(&key_name, b"plaintext", b"aad").await.unwrap();
+        assert_eq!(envelope::Header::parse(&encrypted).unwrap().key_name, key_name);
+
+        let decrypted = client.decrypt_envelope(&key_name, &encrypted, b"aad").await.unwrap();
+        assert_eq!(decrypted, b"plaintext");
+
+        let result = client.decrypt_envelope(&key_name, &encrypted, b"other").await;
+        assert!(matches!(result, Err(Error::Envelope(envelope::Error::Authentication))));
+
+        let other_key_name = format!("{}/cryptoKeys/gcr_test_other", key_ring(&project));
+        let result = client.decrypt_envelope(&other_key_name, &encrypted, b"aad").await;
+        assert!(
+            matches!(result, Err(Error::UnexpectedKeyName(expected, actual)) if expected == other_key_name && actual == key_name)
+        );
+    }
+}
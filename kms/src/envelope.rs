@@ -0,0 +1,203 @@
+//! The self-describing format of the envelope encryption.
+//!
+//! ```text
+//! header     = magic(4) | version(1) | key_name_len(u16 BE) | key_name | wrapped_key_len(u32 BE) | wrapped_key
+//! ciphertext = header | nonce(12) | AES-256-GCM(plaintext, aad = header | user aad)
+//! ```
+//!
+//! The header is authenticated together with the data, so the key name and the wrapped key can not be replaced.
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+
+const MAGIC: &[u8; 4] = b"GKMS";
+const VERSION: u8 = 1;
+const NONCE_LEN: usize = 12;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("invalid envelope: {0}")]
+    InvalidFormat(&'static str),
+    #[error("unsupported envelope version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("invalid data encryption key length: {0}")]
+    InvalidKeyLength(usize),
+    /// The ciphertext, the header or the additional authenticated data is modified.
+    #[error("failed to authenticate the envelope")]
+    Authentication,
+    #[error("failed to encrypt the data")]
+    Encryption,
+}
+
+/// Header is the plaintext part of the envelope.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Header {
+    /// The crypto key used to wrap the data encryption key.
+    pub key_name: String,
+    /// The data encryption key encrypted by the crypto key.
+    pub wrapped_key: Vec<u8>,
+}
+
+impl Header {
+    /// parse reads the header of the envelope without decrypting it.
+    pub fn parse(envelope: &[u8]) -> Result<Self, Error> {
+        split(envelope).map(|v| v.0)
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, Error> {
+        let key_name_len =
+            u16::try_from(self.key_name.len()).map_err(|_| Error::InvalidFormat("key name is too long"))?;
+        let wrapped_key_len =
+            u32::try_from(self.wrapped_key.len()).map_err(|_| Error::InvalidFormat("wrapped key is too long"))?;
+        let mut buf = Vec::with_capacity(11 + self.key_name.len() + self.wrapped_key.len());
+        buf.extend_from_slice(MAGIC);
+        buf.push(VERSION);
+        buf.extend_from_slice(&key_name_len.to_be_bytes());
+        buf.extend_from_slice(self.key_name.as_bytes());
+        buf.extend_from_slice(&wrapped_key_len.to_be_bytes());
+        buf.extend_from_slice(&self.wrapped_key);
+        Ok(buf)
+    }
+}
+
+/// generate_key creates the random AES-256 data encryption key.
+pub(crate) fn generate_key() -> Vec<u8> {
+    Aes256Gcm::generate_key(OsRng).to_vec()
+}
+
+/// seal encrypts the plaintext with the data encryption key and prepends the header.
+pub(crate) fn seal(header: &Header, key: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+    let cipher = cipher(key)?;
+    let mut envelope = header.encode()?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &[envelope.as_slice(), aad].concat(),
+            },
+        )
+        .map_err(|_| Error::Encryption)?;
+    envelope.extend_from_slice(&nonce);
+    envelope.extend_from_slice(&ciphertext);
+    Ok(envelope)
+}
+
+/// open decrypts the envelope with the data encryption key unwrapped from the header.
+pub(crate) fn open(envelope: &[u8], key: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+    let cipher = cipher(key)?;
+    let (_, header, body) = split(envelope)?;
+    if body.len() < NONCE_LEN {
+        return Err(Error::InvalidFormat("nonce is missing"));
+    }
+    let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+    cipher
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: &[header, aad].concat(),
+            },
+        )
+        .map_err(|_| Error::Authentication)
+}
+
+fn cipher(key: &[u8]) -> Result<Aes256Gcm, Error> {
+    if key.len() != 32 {
+        return Err(Error::InvalidKeyLength(key.len()));
+    }
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)))
+}
+
+/// split returns the parsed header, the raw header and the rest of the envelope.
+fn split(envelope: &[u8]) -> Result<(Header, &[u8], &[u8]), Error> {
+    let mut reader = Reader { buf: envelope, pos: 0 };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(Error::InvalidFormat("magic mismatch"));
+    }
+    let version = reader.take(1)?[0];
+    if version != VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    let key_name_len = u16::from_be_bytes(reader.take(2)?.try_into().unwrap()) as usize;
+    let key_name = String::from_utf8(reader.take(key_name_len)?.to_vec())
+        .map_err(|_| Error::InvalidFormat("key name is not utf-8"))?;
+    let wrapped_key_len = u32::from_be_bytes(reader.take(4)?.try_into().unwrap()) as usize;
+    let wrapped_key = reader.take(wrapped_key_len)?.to_vec();
+    let (header, body) = envelope.split_at(reader.pos);
+    Ok((Header { key_name, wrapped_key }, header, body))
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.buf.len())
+            .ok_or(Error::InvalidFormat("header is truncated"))?;
+        let value = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::envelope::{generate_key, open, seal, Error, Header};
+
+    fn header() -> Header {
+        Header {
+            key_name: "projects/p/locations/global/keyRings/r/cryptoKeys/k".to_string(),
+            wrapped_key: vec![1, 2, 3, 4],
+        }
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let key = generate_key();
+        let envelope = seal(&header(), &key, b"plaintext", b"aad").unwrap();
+        assert_eq!(Header::parse(&envelope).unwrap(), header());
+        assert_eq!(open(&envelope, &key, b"aad").unwrap(), b"plaintext");
+
+        let empty = seal(&header(), &key, b"", b"").unwrap();
+        assert!(open(&empty, &key, b"").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tampered() {
+        let key = generate_key();
+        let envelope = seal(&header(), &key, b"plaintext", b"aad").unwrap();
+
+        // wrong aad
+        assert!(matches!(open(&envelope, &key, b"other"), Err(Error::Authentication)));
+
+        // modified ciphertext
+        let mut modified = envelope.clone();
+        let last = modified.len() - 1;
+        modified[last] ^= 1;
+        assert!(matches!(open(&modified, &key, b"aad"), Err(Error::Authentication)));
+
+        // modified wrapped key in the header
+        let mut modified = envelope.clone();
+        let pos = 4 + 1 + 2 + header().key_name.len() + 4;
+        modified[pos] ^= 1;
+        assert!(matches!(open(&modified, &key, b"aad"), Err(Error::Authentication)));
+
+        // truncated
+        assert!(matches!(open(&envelope[..10], &key, b"aad"), Err(Error::InvalidFormat(_))));
+        assert!(matches!(Header::parse(b"XXXX"), Err(Error::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let key = generate_key();
+        let mut envelope = seal(&header(), &key, b"plaintext", b"").unwrap();
+        envelope[4] = 99;
+        assert!(matches!(Header::parse(&envelope), Err(Error::UnsupportedVersion(99))));
+    }
+}
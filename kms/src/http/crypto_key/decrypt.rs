@@ -0,0 +1,37 @@
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DecryptRequest {
+    /// Required. The ciphertext returned by `encrypt`.
+    #[serde(with = "crate::http::bytes")]
+    pub ciphertext: Vec<u8>,
+    /// Optional. The data passed to `encrypt` as the additional authenticated data.
+    #[serde(with = "crate::http::bytes", skip_serializing_if = "Vec::is_empty", default)]
+    pub additional_authenticated_data: Vec<u8>,
+    /// Optional. The CRC32C checksum of the ciphertext verified by the server.
+    #[serde(with = "crate::http::int64_option", skip_serializing_if = "Option::is_none", default)]
+    pub ciphertext_crc32c: Option<i64>,
+    /// Optional. The CRC32C checksum of the additional authenticated data verified by the server.
+    #[serde(with = "crate::http::int64_option", skip_serializing_if = "Option::is_none", default)]
+    pub additional_authenticated_data_crc32c: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DecryptResponse {
+    /// The decrypted data.
+    #[serde(with = "crate::http::bytes", default)]
+    pub plaintext: Vec<u8>,
+    /// The CRC32C checksum of the plaintext.
+    #[serde(with = "crate::http::int64_option", default)]
+    pub plaintext_crc32c: Option<i64>,
+    /// Whether the version of the primary was used in decryption.
+    #[serde(default)]
+    pub used_primary: bool,
+}
+
+pub fn build(base_url: &str, client: &Client, name: &str, req: &DecryptRequest) -> RequestBuilder {
+    let url = format!("{}/{}:decrypt", base_url, name);
+    client.post(url).json(req)
+}
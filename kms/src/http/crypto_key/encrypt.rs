@@ -0,0 +1,42 @@
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptRequest {
+    /// Required. The data to encrypt. It must be no larger than 64KiB.
+    #[serde(with = "crate::http::bytes")]
+    pub plaintext: Vec<u8>,
+    /// Optional. The data authenticated together with the plaintext. It must be passed to `decrypt`.
+    #[serde(with = "crate::http::bytes", skip_serializing_if = "Vec::is_empty", default)]
+    pub additional_authenticated_data: Vec<u8>,
+    /// Optional. The CRC32C checksum of the plaintext verified by the server.
+    #[serde(with = "crate::http::int64_option", skip_serializing_if = "Option::is_none", default)]
+    pub plaintext_crc32c: Option<i64>,
+    /// Optional. The CRC32C checksum of the additional authenticated data verified by the server.
+    #[serde(with = "crate::http::int64_option", skip_serializing_if = "Option::is_none", default)]
+    pub additional_authenticated_data_crc32c: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptResponse {
+    /// The resource name of the version used in encryption.
+    pub name: String,
+    /// The encrypted data.
+    #[serde(with = "crate::http::bytes")]
+    pub ciphertext: Vec<u8>,
+    /// The CRC32C checksum of the ciphertext.
+    #[serde(with = "crate::http::int64_option", default)]
+    pub ciphertext_crc32c: Option<i64>,
+    /// Whether the server verified `plaintext_crc32c` of the request.
+    #[serde(default)]
+    pub verified_plaintext_crc32c: bool,
+    /// Whether the server verified `additional_authenticated_data_crc32c` of the request.
+    #[serde(default)]
+    pub verified_additional_authenticated_data_crc32c: bool,
+}
+
+pub fn build(base_url: &str, client: &Client, name: &str, req: &EncryptRequest) -> RequestBuilder {
+    let url = format!("{}/{}:encrypt", base_url, name);
+    client.post(url).json(req)
+}
@@ -0,0 +1,6 @@
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+pub fn build(base_url: &str, client: &Client, name: &str) -> RequestBuilder {
+    let url = format!("{}/{}", base_url, name);
+    client.get(url)
+}
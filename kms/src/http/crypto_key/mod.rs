@@ -0,0 +1,33 @@
+use crate::http::crypto_key_version::CryptoKeyVersion;
+
+pub mod decrypt;
+pub mod encrypt;
+pub mod get;
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CryptoKeyPurpose {
+    #[default]
+    CryptoKeyPurposeUnspecified,
+    /// The key is used by `encrypt` and `decrypt`.
+    EncryptDecrypt,
+    /// The key is used by `asymmetric_sign` and `get_public_key`.
+    AsymmetricSign,
+    AsymmetricDecrypt,
+    RawEncryptDecrypt,
+    Mac,
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CryptoKey {
+    /// Output only. The resource name in the format `projects/*/locations/*/keyRings/*/cryptoKeys/*`.
+    pub name: String,
+    /// Output only. The version used by `encrypt`. Only the keys with the purpose ENCRYPT_DECRYPT have the primary.
+    pub primary: Option<CryptoKeyVersion>,
+    /// Immutable. The purpose of the key.
+    #[serde(default)]
+    pub purpose: CryptoKeyPurpose,
+    /// Output only. The time at which the key was created in RFC3339.
+    pub create_time: Option<String>,
+}
@@ -0,0 +1,63 @@
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+/// Digest is the digest of the data to sign. The algorithm must match the one of the version.
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum Digest {
+    Sha256(#[serde(with = "crate::http::bytes")] Vec<u8>),
+    Sha384(#[serde(with = "crate::http::bytes")] Vec<u8>),
+    Sha512(#[serde(with = "crate::http::bytes")] Vec<u8>),
+}
+
+impl Digest {
+    pub fn value(&self) -> &[u8] {
+        match self {
+            Digest::Sha256(v) => v,
+            Digest::Sha384(v) => v,
+            Digest::Sha512(v) => v,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AsymmetricSignRequest {
+    /// Optional. The digest of the data to sign. Either the digest or the data must be set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<Digest>,
+    /// Optional. The CRC32C checksum of the digest verified by the server.
+    #[serde(with = "crate::http::int64_option", skip_serializing_if = "Option::is_none", default)]
+    pub digest_crc32c: Option<i64>,
+    /// Optional. The data to sign by the algorithms without the digest like `EC_SIGN_ED25519`.
+    #[serde(with = "crate::http::bytes", skip_serializing_if = "Vec::is_empty", default)]
+    pub data: Vec<u8>,
+    /// Optional. The CRC32C checksum of the data verified by the server.
+    #[serde(with = "crate::http::int64_option", skip_serializing_if = "Option::is_none", default)]
+    pub data_crc32c: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AsymmetricSignResponse {
+    /// The resource name of the version used for signing.
+    pub name: String,
+    /// The created signature.
+    #[serde(with = "crate::http::bytes")]
+    pub signature: Vec<u8>,
+    /// The CRC32C checksum of the signature.
+    #[serde(with = "crate::http::int64_option", default)]
+    pub signature_crc32c: Option<i64>,
+    /// Whether the server verified `digest_crc32c` of the request.
+    #[serde(default)]
+    pub verified_digest_crc32c: bool,
+    /// Whether the server verified `data_crc32c` of the request.
+    #[serde(default)]
+    pub verified_data_crc32c: bool,
+    /// The protection level of the version like `SOFTWARE` or `HSM`.
+    pub protection_level: Option<String>,
+}
+
+pub fn build(base_url: &str, client: &Client, name: &str, req: &AsymmetricSignRequest) -> RequestBuilder {
+    let url = format!("{}/{}:asymmetricSign", base_url, name);
+    client.post(url).json(req)
+}
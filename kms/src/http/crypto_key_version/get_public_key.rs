@@ -0,0 +1,23 @@
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKey {
+    /// The resource name of the version.
+    pub name: String,
+    /// The public key in PEM format.
+    pub pem: String,
+    /// The CRC32C checksum of the PEM.
+    #[serde(with = "crate::http::int64_option", default)]
+    pub pem_crc32c: Option<i64>,
+    /// The algorithm of the version like `EC_SIGN_P256_SHA256`.
+    #[serde(default)]
+    pub algorithm: String,
+    /// The protection level of the version like `SOFTWARE` or `HSM`.
+    pub protection_level: Option<String>,
+}
+
+pub fn build(base_url: &str, client: &Client, name: &str) -> RequestBuilder {
+    let url = format!("{}/{}/publicKey", base_url, name);
+    client.get(url)
+}
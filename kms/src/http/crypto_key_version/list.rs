@@ -0,0 +1,31 @@
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+use crate::http::crypto_key_version::CryptoKeyVersion;
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCryptoKeyVersionsRequest {
+    /// Optional. The maximum number of the versions in the response.
+    pub page_size: Option<i32>,
+    /// Optional. The page token returned by the previous response.
+    pub page_token: Option<String>,
+    /// Optional. The filter like `state=ENABLED`.
+    pub filter: Option<String>,
+    /// Optional. The order like `name desc`.
+    pub order_by: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ListCryptoKeyVersionsResponse {
+    #[serde(default)]
+    pub crypto_key_versions: Vec<CryptoKeyVersion>,
+    pub next_page_token: Option<String>,
+    #[serde(default)]
+    pub total_size: i32,
+}
+
+pub fn build(base_url: &str, client: &Client, parent: &str, req: &ListCryptoKeyVersionsRequest) -> RequestBuilder {
+    let url = format!("{}/{}/cryptoKeyVersions", base_url, parent);
+    client.get(url).query(req)
+}
@@ -0,0 +1,35 @@
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MacSignRequest {
+    /// Required. The data to sign. It must be no larger than 64KiB.
+    #[serde(with = "crate::http::bytes")]
+    pub data: Vec<u8>,
+    /// Optional. The CRC32C checksum of the data verified by the server.
+    #[serde(with = "crate::http::int64_option", skip_serializing_if = "Option::is_none", default)]
+    pub data_crc32c: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MacSignResponse {
+    /// The resource name of the version used for signing.
+    pub name: String,
+    /// The created MAC.
+    #[serde(with = "crate::http::bytes")]
+    pub mac: Vec<u8>,
+    /// The CRC32C checksum of the MAC.
+    #[serde(with = "crate::http::int64_option", default)]
+    pub mac_crc32c: Option<i64>,
+    /// Whether the server verified `data_crc32c` of the request.
+    #[serde(default)]
+    pub verified_data_crc32c: bool,
+    /// The protection level of the version like `SOFTWARE` or `HSM`.
+    pub protection_level: Option<String>,
+}
+
+pub fn build(base_url: &str, client: &Client, name: &str, req: &MacSignRequest) -> RequestBuilder {
+    let url = format!("{}/{}:macSign", base_url, name);
+    client.post(url).json(req)
+}
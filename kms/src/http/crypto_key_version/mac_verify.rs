@@ -0,0 +1,44 @@
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MacVerifyRequest {
+    /// Required. The data used to create the MAC.
+    #[serde(with = "crate::http::bytes")]
+    pub data: Vec<u8>,
+    /// Optional. The CRC32C checksum of the data verified by the server.
+    #[serde(with = "crate::http::int64_option", skip_serializing_if = "Option::is_none", default)]
+    pub data_crc32c: Option<i64>,
+    /// Required. The MAC to verify.
+    #[serde(with = "crate::http::bytes")]
+    pub mac: Vec<u8>,
+    /// Optional. The CRC32C checksum of the MAC verified by the server.
+    #[serde(with = "crate::http::int64_option", skip_serializing_if = "Option::is_none", default)]
+    pub mac_crc32c: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MacVerifyResponse {
+    /// The resource name of the version used for verification.
+    pub name: String,
+    /// Whether the MAC is valid for the data.
+    #[serde(default)]
+    pub success: bool,
+    /// Whether the server verified `data_crc32c` of the request.
+    #[serde(default)]
+    pub verified_data_crc32c: bool,
+    /// Whether the server verified `mac_crc32c` of the request.
+    #[serde(default)]
+    pub verified_mac_crc32c: bool,
+    /// Whether `success` is the same as the result of the verification on the server.
+    #[serde(default)]
+    pub verified_success_integrity: bool,
+    /// The protection level of the version like `SOFTWARE` or `HSM`.
+    pub protection_level: Option<String>,
+}
+
+pub fn build(base_url: &str, client: &Client, name: &str, req: &MacVerifyRequest) -> RequestBuilder {
+    let url = format!("{}/{}:macVerify", base_url, name);
+    client.post(url).json(req)
+}
@@ -0,0 +1,90 @@
+pub mod asymmetric_sign;
+pub mod get;
+pub mod get_public_key;
+pub mod list;
+pub mod mac_sign;
+pub mod mac_verify;
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CryptoKeyVersionState {
+    #[default]
+    CryptoKeyVersionStateUnspecified,
+    PendingGeneration,
+    /// The version can be used for the cryptographic operations.
+    Enabled,
+    Disabled,
+    Destroyed,
+    DestroyScheduled,
+    PendingImport,
+    ImportFailed,
+    GenerationFailed,
+    PendingExternalDestruction,
+    ExternalDestructionFailed,
+}
+
+/// DigestAlgorithm is the hash function used by the algorithm of the version.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DigestAlgorithm {
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CryptoKeyVersion {
+    /// Output only. The resource name in the format `projects/*/locations/*/keyRings/*/cryptoKeys/*/cryptoKeyVersions/*`.
+    pub name: String,
+    /// The current state of the version.
+    #[serde(default)]
+    pub state: CryptoKeyVersionState,
+    /// Output only. The algorithm like `EC_SIGN_P256_SHA256` or `GOOGLE_SYMMETRIC_ENCRYPTION`.
+    #[serde(default)]
+    pub algorithm: String,
+    /// Output only. The protection level like `SOFTWARE` or `HSM`.
+    pub protection_level: Option<String>,
+    /// Output only. The time at which the version was created in RFC3339.
+    pub create_time: Option<String>,
+}
+
+impl CryptoKeyVersion {
+    /// digest_algorithm returns the hash function of the algorithm
+    /// like SHA256 of `EC_SIGN_P256_SHA256` and `HMAC_SHA256`.
+    /// It returns None for the algorithms without the hash function like `EC_SIGN_ED25519`.
+    pub fn digest_algorithm(&self) -> Option<DigestAlgorithm> {
+        match self.algorithm.rsplit('_').next()? {
+            "SHA1" => Some(DigestAlgorithm::Sha1),
+            "SHA224" => Some(DigestAlgorithm::Sha224),
+            "SHA256" => Some(DigestAlgorithm::Sha256),
+            "SHA384" => Some(DigestAlgorithm::Sha384),
+            "SHA512" => Some(DigestAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::crypto_key_version::{CryptoKeyVersion, DigestAlgorithm};
+
+    #[test]
+    fn test_digest_algorithm() {
+        let version = |algorithm: &str| CryptoKeyVersion {
+            algorithm: algorithm.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(version("EC_SIGN_P256_SHA256").digest_algorithm(), Some(DigestAlgorithm::Sha256));
+        assert_eq!(
+            version("RSA_SIGN_PSS_4096_SHA512").digest_algorithm(),
+            Some(DigestAlgorithm::Sha512)
+        );
+        assert_eq!(version("HMAC_SHA1").digest_algorithm(), Some(DigestAlgorithm::Sha1));
+        assert_eq!(version("HMAC_SHA224").digest_algorithm(), Some(DigestAlgorithm::Sha224));
+        assert_eq!(version("EC_SIGN_ED25519").digest_algorithm(), None);
+        assert_eq!(version("RSA_SIGN_RAW_PKCS1_2048").digest_algorithm(), None);
+        assert_eq!(version("GOOGLE_SYMMETRIC_ENCRYPTION").digest_algorithm(), None);
+    }
+}
@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use reqwest::Response;
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+use google_cloud_token::TokenSource;
+
+use crate::http::crypto_key::decrypt::{DecryptRequest, DecryptResponse};
+use crate::http::crypto_key::encrypt::{EncryptRequest, EncryptResponse};
+use crate::http::crypto_key::CryptoKey;
+use crate::http::crypto_key_version::asymmetric_sign::{AsymmetricSignRequest, AsymmetricSignResponse};
+use crate::http::crypto_key_version::get_public_key::PublicKey;
+use crate::http::crypto_key_version::list::{ListCryptoKeyVersionsRequest, ListCryptoKeyVersionsResponse};
+use crate::http::crypto_key_version::mac_sign::{MacSignRequest, MacSignResponse};
+use crate::http::crypto_key_version::mac_verify::{MacVerifyRequest, MacVerifyResponse};
+use crate::http::crypto_key_version::CryptoKeyVersion;
+use crate::http::error::{Error, ErrorWrapper};
+use crate::http::{crypto_key, crypto_key_version};
+
+pub const SCOPES: [&str; 2] = [
+    "https://www.googleapis.com/auth/cloudkms",
+    "https://www.googleapis.com/auth/cloud-platform",
+];
+
+/// KmsClient calls the REST API of Cloud KMS.
+/// The integrity fields of the requests and the responses are not checked, `crate::client::Client` checks them.
+#[derive(Debug, Clone)]
+pub struct KmsClient {
+    ts: Arc<dyn TokenSource>,
+    endpoint: String,
+    http: Client,
+}
+
+impl KmsClient {
+    pub(crate) fn new(ts: Arc<dyn TokenSource>, endpoint: &str, http: Client) -> Self {
+        Self {
+            ts,
+            endpoint: format!("{endpoint}/v1"),
+            http,
+        }
+    }
+
+    /// https://cloud.google.com/kms/docs/reference/rest/v1/projects.locations.keyRings.cryptoKeys/get
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn get_crypto_key(&self, name: &str) -> Result<CryptoKey, Error> {
+        let builder = crypto_key::get::build(&self.endpoint, &self.http, name);
+        self.send(builder).await
+    }
+
+    /// https://cloud.google.com/kms/docs/reference/rest/v1/projects.locations.keyRings.cryptoKeys/encrypt
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn encrypt(&self, name: &str, req: &EncryptRequest) -> Result<EncryptResponse, Error> {
+        let builder = crypto_key::encrypt::build(&self.endpoint, &self.http, name, req);
+        self.send(builder).await
+    }
+
+    /// https://cloud.google.com/kms/docs/reference/rest/v1/projects.locations.keyRings.cryptoKeys/decrypt
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn decrypt(&self, name: &str, req: &DecryptRequest) -> Result<DecryptResponse, Error> {
+        let builder = crypto_key::decrypt::build(&self.endpoint, &self.http, name, req);
+        self.send(builder).await
+    }
+
+    /// https://cloud.google.com/kms/docs/reference/rest/v1/projects.locations.keyRings.cryptoKeys.cryptoKeyVersions/get
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn get_crypto_key_version(&self, name: &str) -> Result<CryptoKeyVersion, Error> {
+        let builder = crypto_key_version::get::build(&self.endpoint, &self.http, name);
+        self.send(builder).await
+    }
+
+    /// https://cloud.google.com/kms/docs/reference/rest/v1/projects.locations.keyRings.cryptoKeys.cryptoKeyVersions/list
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn list_crypto_key_versions(
+        &self,
+        parent: &str,
+        req: &ListCryptoKeyVersionsRequest,
+    ) -> Result<ListCryptoKeyVersionsResponse, Error> {
+        let builder = crypto_key_version::list::build(&self.endpoint, &self.http, parent, req);
+        self.send(builder).await
+    }
+
+    /// https://cloud.google.com/kms/docs/reference/rest/v1/projects.locations.keyRings.cryptoKeys.cryptoKeyVersions/asymmetricSign
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn asymmetric_sign(
+        &self,
+        name: &str,
+        req: &AsymmetricSignRequest,
+    ) -> Result<AsymmetricSignResponse, Error> {
+        let builder = crypto_key_version::asymmetric_sign::build(&self.endpoint, &self.http, name, req);
+        self.send(builder).await
+    }
+
+    /// https://cloud.google.com/kms/docs/reference/rest/v1/projects.locations.keyRings.cryptoKeys.cryptoKeyVersions/getPublicKey
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn get_public_key(&self, name: &str) -> Result<PublicKey, Error> {
+        let builder = crypto_key_version::get_public_key::build(&self.endpoint, &self.http, name);
+        self.send(builder).await
+    }
+
+    /// https://cloud.google.com/kms/docs/reference/rest/v1/projects.locations.keyRings.cryptoKeys.cryptoKeyVersions/macSign
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn mac_sign(&self, name: &str, req: &MacSignRequest) -> Result<MacSignResponse, Error> {
+        let builder = crypto_key_version::mac_sign::build(&self.endpoint, &self.http, name, req);
+        self.send(builder).await
+    }
+
+    /// https://cloud.google.com/kms/docs/reference/rest/v1/projects.locations.keyRings.cryptoKeys.cryptoKeyVersions/macVerify
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn mac_verify(&self, name: &str, req: &MacVerifyRequest) -> Result<MacVerifyResponse, Error> {
+        let builder = crypto_key_version::mac_verify::build(&self.endpoint, &self.http, name, req);
+        self.send(builder).await
+    }
+
+    async fn with_headers(&self, builder: RequestBuilder) -> Result<RequestBuilder, Error> {
+        let token = self.ts.token().await.map_err(Error::TokenSource)?;
+        Ok(builder
+            .header("X-Goog-Api-Client", "rust")
+            .header(reqwest::header::USER_AGENT, "google-cloud-kms")
+            .header(reqwest::header::AUTHORIZATION, token))
+    }
+
+    async fn send<T>(&self, builder: RequestBuilder) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let request = self.with_headers(builder).await?;
+        let response = request.send().await?;
+        let response = Self::check_response_status(response).await?;
+        Ok(response.json().await?)
+    }
+
+    /// Checks whether an HTTP response is successful and returns it, or returns an error.
+    async fn check_response_status(response: Response) -> Result<Response, Error> {
+        // Check the status code, returning the response if it is not an error.
+        let error = match response.error_for_status_ref() {
+            Ok(_) => return Ok(response),
+            Err(error) => error,
+        };
+
+        // try to extract a response error, falling back to the status error if it can not be parsed.
+        Err(response
+            .json::<ErrorWrapper>()
+            .await
+            .map(|wrapper| Error::Response(wrapper.error))
+            .unwrap_or(Error::HttpClient(error)))
+    }
+}
@@ -0,0 +1,44 @@
+pub mod crypto_key;
+pub mod crypto_key_version;
+pub mod error;
+pub mod kms_client;
+
+/// The bytes fields are encoded as base64 in JSON.
+pub(crate) mod bytes {
+    use base64::prelude::BASE64_STANDARD;
+    use base64::Engine;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64_STANDARD.encode(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        BASE64_STANDARD.decode(value).map_err(de::Error::custom)
+    }
+}
+
+/// The int64 fields are encoded as string in JSON.
+pub(crate) mod int64_option {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<i64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(v) => serializer.serialize_str(&v.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i64>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(v) => v.parse().map(Some).map_err(de::Error::custom),
+            None => Ok(None),
+        }
+    }
+}
+
+/// crc32c is the checksum used by the integrity fields of the requests and the responses.
+pub(crate) fn crc32c(value: &[u8]) -> i64 {
+    crc32c::crc32c(value) as i64
+}
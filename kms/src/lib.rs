@@ -0,0 +1,101 @@
+//! # google-cloud-kms
+//!
+//! Google Cloud Platform Key Management Service Client library.
+//!
+//! ## Quickstart
+//!
+//! ### Authentication
+//! There are two ways to create a client that is authenticated against the google cloud.
+//!
+//! #### Automatically
+//!
+//! The function `with_auth()` will try and read the credentials from a file specified in the environment variable `GOOGLE_APPLICATION_CREDENTIALS`, `GOOGLE_APPLICATION_CREDENTIALS_JSON` or
+//! from a metadata server.
+//!
+//! This is also described in [google-cloud-auth](https://github.com/yoshidan/google-cloud-rust/blob/main/foundation/auth/README.md)
+//!
+//! ```rust
+//! use google_cloud_kms::client::{Client, ClientConfig};
+//!
+//! async fn run() {
+//!     let config = ClientConfig::default().with_auth().await.unwrap();
+//!     let client = Client::new(config);
+//! }
+//! ```
+//!
+//! #### Manually
+//!
+//! When you can't use the `gcloud` authentication but you have a different way to get your credentials (e.g a different environment variable)
+//! you can parse your own version of the 'credentials-file' and use it like that:
+//!
+//! ```rust
+//! use google_cloud_auth::credentials::CredentialsFile;
+//! // or google_cloud_kms::client::google_cloud_auth::credentials::CredentialsFile
+//! use google_cloud_kms::client::{Client, ClientConfig};
+//!
+//! async fn run(cred: CredentialsFile) {
+//!     let config = ClientConfig::default().with_credentials(cred).await.unwrap();
+//!     let client = Client::new(config);
+//! }
+//! ```
+//!
+//! ### Usage
+//!
+//! #### Asymmetric signing
+//!
+//! The digest of the data is computed by the client according to the algorithm of the key version.
+//! `KeyVersion::Primary` uses the primary version or the latest enabled version,
+//! `KeyVersion::Pinned` uses the specified version.
+//!
+//! ```rust
+//! use google_cloud_kms::client::{Client, KeyVersion};
+//!
+//! async fn run(client: Client) {
+//!     let key = KeyVersion::Primary("projects/p/locations/global/keyRings/r/cryptoKeys/sign".to_string());
+//!     let signed = client.asymmetric_sign(&key, b"data").await.unwrap();
+//!
+//!     // Verify the signature locally with the public key in PEM format.
+//!     let public_key = client.get_public_key(&KeyVersion::Pinned(signed.name)).await.unwrap();
+//!     println!("{}", public_key.pem);
+//! }
+//! ```
+//!
+//! #### MAC signing
+//!
+//! ```rust
+//! use google_cloud_kms::client::{Client, KeyVersion};
+//!
+//! async fn run(client: Client, payload: &[u8]) {
+//!     let key = KeyVersion::Primary("projects/p/locations/global/keyRings/r/cryptoKeys/hmac".to_string());
+//!     let signed = client.mac_sign(&key, payload).await.unwrap();
+//!     let valid = client.mac_verify(&key, payload, &signed.mac).await.unwrap();
+//!     assert!(valid);
+//! }
+//! ```
+//!
+//! `resolve_version` returns the version with the algorithm,
+//! and `digest_algorithm()` tells the hash function to pre-hash the data locally.
+//!
+//! #### Envelope encryption
+//!
+//! The data is encrypted by the locally generated AES-256-GCM key, and the key is encrypted by the Cloud KMS key.
+//! The returned ciphertext contains the name of the Cloud KMS key and the encrypted key.
+//! `decrypt_envelope` takes the expected key name and rejects the ciphertext wrapped by the other key.
+//!
+//! ```rust
+//! use google_cloud_kms::client::Client;
+//!
+//! async fn run(client: Client) {
+//!     let key_name = "projects/p/locations/global/keyRings/r/cryptoKeys/encrypt";
+//!     let aad = b"additional authenticated data";
+//!     let ciphertext = client.encrypt_envelope(key_name, b"plaintext", aad).await.unwrap();
+//!     let plaintext = client.decrypt_envelope(key_name, &ciphertext, aad).await.unwrap();
+//!     assert_eq!(plaintext, b"plaintext");
+//! }
+//! ```
+//!
+//! The integrity fields (CRC32C) of the requests and the responses are checked,
+//! and the failed requests are retried according to `ClientConfig::retry`.
+pub mod client;
+pub mod envelope;
+pub mod http;
@@ -6,7 +6,7 @@ pub mod publisher_client;
 pub mod schema_client;
 pub mod subscriber_client;
 
-const PUBSUB_MESSAGE_LIMIT: usize = 10 * 1024 * 1024; // 10MB
+pub(crate) const PUBSUB_MESSAGE_LIMIT: usize = 10 * 1024 * 1024; // 10MB
 
 pub fn default_retry_setting() -> RetrySetting {
     let mut setting = RetrySetting::default();
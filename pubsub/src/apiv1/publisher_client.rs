@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use google_cloud_gax::conn::Channel;
 use google_cloud_gax::create_request;
+use google_cloud_gax::grpc::codegen::tokio_stream::Stream;
 use google_cloud_gax::grpc::Response;
 use google_cloud_gax::grpc::{Code, Status};
 use google_cloud_gax::retry::{invoke, RetrySetting};
@@ -138,7 +139,35 @@ impl PublisherClient {
         }
     }
 
-    /// list_topics lists matching topics.
+    /// list_topics_stream is the lazy version of `list_topics`.
+    /// The next page is fetched when the current page is consumed, and each page is retried according to the retry setting.
+    pub fn list_topics_stream(
+        &self,
+        mut req: ListTopicsRequest,
+        retry: Option<RetrySetting>,
+    ) -> impl Stream<Item = Result<Topic, Status>> + Send + 'static {
+        let this = self.clone();
+        async_stream::try_stream! {
+            let project = req.project.clone();
+            loop {
+                let action = || async {
+                    let mut client = this.client();
+                    let request = create_request(format!("project={project}"), req.clone());
+                    client.list_topics(request).await.map(|d| d.into_inner())
+                };
+                let response = invoke(retry.clone(), action).await?;
+                for v in response.topics {
+                    yield v;
+                }
+                if response.next_page_token.is_empty() {
+                    break;
+                }
+                req.page_token = response.next_page_token;
+            }
+        }
+    }
+
+    /// list_topic_subscriptions lists the names of the subscriptions attached to the topic.
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn list_topic_subscriptions(
         &self,
@@ -163,6 +192,34 @@ impl PublisherClient {
         }
     }
 
+    /// list_topic_subscriptions_stream is the lazy version of `list_topic_subscriptions`.
+    /// The next page is fetched when the current page is consumed, and each page is retried according to the retry setting.
+    pub fn list_topic_subscriptions_stream(
+        &self,
+        mut req: ListTopicSubscriptionsRequest,
+        retry: Option<RetrySetting>,
+    ) -> impl Stream<Item = Result<String, Status>> + Send + 'static {
+        let this = self.clone();
+        async_stream::try_stream! {
+            let topic = req.topic.clone();
+            loop {
+                let action = || async {
+                    let mut client = this.client();
+                    let request = create_request(format!("topic={topic}"), req.clone());
+                    client.list_topic_subscriptions(request).await.map(|d| d.into_inner())
+                };
+                let response = invoke(retry.clone(), action).await?;
+                for v in response.subscriptions {
+                    yield v;
+                }
+                if response.next_page_token.is_empty() {
+                    break;
+                }
+                req.page_token = response.next_page_token;
+            }
+        }
+    }
+
     /// list_topic_snapshots lists the names of the snapshots on this topic. Snapshots are used in
     /// Seek (at https://cloud.google.com/pubsub/docs/replay-overview) operations,
     /// which allow you to manage message acknowledgments in bulk. That is, you can
@@ -192,6 +249,34 @@ impl PublisherClient {
         }
     }
 
+    /// list_topic_snapshots_stream is the lazy version of `list_topic_snapshots`.
+    /// The next page is fetched when the current page is consumed, and each page is retried according to the retry setting.
+    pub fn list_topic_snapshots_stream(
+        &self,
+        mut req: ListTopicSnapshotsRequest,
+        retry: Option<RetrySetting>,
+    ) -> impl Stream<Item = Result<String, Status>> + Send + 'static {
+        let this = self.clone();
+        async_stream::try_stream! {
+            let topic = req.topic.clone();
+            loop {
+                let action = || async {
+                    let mut client = this.client();
+                    let request = create_request(format!("topic={topic}"), req.clone());
+                    client.list_topic_snapshots(request).await.map(|d| d.into_inner())
+                };
+                let response = invoke(retry.clone(), action).await?;
+                for v in response.snapshots {
+                    yield v;
+                }
+                if response.next_page_token.is_empty() {
+                    break;
+                }
+                req.page_token = response.next_page_token;
+            }
+        }
+    }
+
     /// delete_topic deletes the topic with the given name. Returns NOT_FOUND if the topic
     /// does not exist. After a topic is deleted, a new topic may be created with
     /// the same name; this is an entirely new topic with none of the old
@@ -7,20 +7,20 @@ use google_cloud_gax::grpc::Status;
 use google_cloud_gax::retry::{invoke, RetrySetting};
 use google_cloud_googleapis::pubsub::v1::schema_service_client::SchemaServiceClient;
 use google_cloud_googleapis::pubsub::v1::{
-    CreateSchemaRequest, DeleteSchemaRequest, GetSchemaRequest, ListSchemasRequest, Schema, ValidateMessageRequest,
+    CommitSchemaRequest, CreateSchemaRequest, DeleteSchemaRequest, DeleteSchemaRevisionRequest, GetSchemaRequest,
+    ListSchemaRevisionsRequest, ListSchemasRequest, RollbackSchemaRequest, Schema, ValidateMessageRequest,
     ValidateMessageResponse, ValidateSchemaRequest, ValidateSchemaResponse,
 };
 
 use crate::apiv1::conn_pool::ConnectionManager;
 
 #[derive(Clone, Debug)]
-pub(crate) struct SchemaClient {
+pub struct SchemaClient {
     cm: Arc<ConnectionManager>,
 }
 
-#[allow(dead_code)]
 impl SchemaClient {
-    /// create new publisher client
+    /// create new schema client
     pub fn new(cm: ConnectionManager) -> SchemaClient {
         SchemaClient { cm: Arc::new(cm) }
     }
@@ -59,19 +59,19 @@ impl SchemaClient {
         invoke(retry, action).await
     }
 
-    /// list_schemas lists matching topics.
+    /// list_schemas lists schemas in a project.
     pub async fn list_schemas(
         &self,
         mut req: ListSchemasRequest,
         retry: Option<RetrySetting>,
     ) -> Result<Vec<Schema>, Status> {
-        let project = &req.parent;
+        let parent = &req.parent;
         let mut all = vec![];
         //eager loading
         loop {
             let action = || async {
                 let mut client = self.client();
-                let request = create_request(format!("project={project}"), req.clone());
+                let request = create_request(format!("parent={parent}"), req.clone());
                 client.list_schemas(request).await.map(|d| d.into_inner())
             };
             let response = invoke(retry.clone(), action).await?;
@@ -83,6 +83,75 @@ impl SchemaClient {
         }
     }
 
+    /// list_schema_revisions lists all schema revisions for the named schema.
+    pub async fn list_schema_revisions(
+        &self,
+        mut req: ListSchemaRevisionsRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<Schema>, Status> {
+        let name = &req.name;
+        let mut all = vec![];
+        //eager loading
+        loop {
+            let action = || async {
+                let mut client = self.client();
+                let request = create_request(format!("name={name}"), req.clone());
+                client.list_schema_revisions(request).await.map(|d| d.into_inner())
+            };
+            let response = invoke(retry.clone(), action).await?;
+            all.extend(response.schemas);
+            if response.next_page_token.is_empty() {
+                return Ok(all);
+            }
+            req.page_token = response.next_page_token;
+        }
+    }
+
+    /// commit_schema commits a new schema revision to an existing schema.
+    pub async fn commit_schema(
+        &self,
+        req: CommitSchemaRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Schema>, Status> {
+        let name = &req.name;
+        let action = || async {
+            let mut client = self.client();
+            let request = create_request(format!("name={name}"), req.clone());
+            client.commit_schema(request).await
+        };
+        invoke(retry, action).await
+    }
+
+    /// rollback_schema creates a new schema revision that is a copy of the provided revision_id.
+    pub async fn rollback_schema(
+        &self,
+        req: RollbackSchemaRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Schema>, Status> {
+        let name = &req.name;
+        let action = || async {
+            let mut client = self.client();
+            let request = create_request(format!("name={name}"), req.clone());
+            client.rollback_schema(request).await
+        };
+        invoke(retry, action).await
+    }
+
+    /// delete_schema_revision deletes a specific schema revision.
+    pub async fn delete_schema_revision(
+        &self,
+        req: DeleteSchemaRevisionRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Schema>, Status> {
+        let name = &req.name;
+        let action = || async {
+            let mut client = self.client();
+            let request = create_request(format!("name={name}"), req.clone());
+            client.delete_schema_revision(request).await
+        };
+        invoke(retry, action).await
+    }
+
     /// delete_schema deletes a schema.
     pub async fn delete_schema(
         &self,
@@ -98,7 +167,7 @@ impl SchemaClient {
         invoke(retry, action).await
     }
 
-    /// validate_schema deletes a schema.
+    /// validate_schema validates a schema.
     pub async fn validate_schema(
         &self,
         req: ValidateSchemaRequest,
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use google_cloud_gax::conn::Channel;
 use google_cloud_gax::create_request;
+use google_cloud_gax::grpc::codegen::tokio_stream::Stream;
 use google_cloud_gax::grpc::Status;
 use google_cloud_gax::grpc::{IntoStreamingRequest, Response, Streaming};
 use google_cloud_gax::retry::{invoke, RetrySetting};
@@ -140,6 +141,34 @@ impl SubscriberClient {
         }
     }
 
+    /// list_subscriptions_stream is the lazy version of `list_subscriptions`.
+    /// The next page is fetched when the current page is consumed, and each page is retried according to the retry setting.
+    pub fn list_subscriptions_stream(
+        &self,
+        mut req: ListSubscriptionsRequest,
+        retry: Option<RetrySetting>,
+    ) -> impl Stream<Item = Result<Subscription, Status>> + Send + 'static {
+        let this = self.clone();
+        async_stream::try_stream! {
+            let project = req.project.clone();
+            loop {
+                let action = || async {
+                    let mut client = this.client();
+                    let request = create_request(format!("project={project}"), req.clone());
+                    client.list_subscriptions(request).await.map(|d| d.into_inner())
+                };
+                let response = invoke(retry.clone(), action).await?;
+                for v in response.subscriptions {
+                    yield v;
+                }
+                if response.next_page_token.is_empty() {
+                    break;
+                }
+                req.page_token = response.next_page_token;
+            }
+        }
+    }
+
     /// delete_subscription deletes an existing subscription. All messages retained in the subscription
     /// are immediately dropped. Calls to Pull after deletion will return
     /// NOT_FOUND. After a subscription is deleted, a new one may be created with
@@ -227,7 +256,7 @@ impl SubscriberClient {
     pub async fn streaming_pull(
         &self,
         req: StreamingPullRequest,
-        ping_receiver: async_channel::Receiver<bool>,
+        ping_receiver: async_channel::Receiver<StreamingPullRequest>,
         retry: Option<RetrySetting>,
     ) -> Result<Response<Streaming<StreamingPullResponse>>, Status> {
         let action = || async {
@@ -237,10 +266,9 @@ impl SubscriberClient {
             let request = Box::pin(async_stream::stream! {
                 yield base_req.clone();
 
-                // ping message.
-                // must be empty request
-                while let Ok(_r) = rx.recv().await {
-                   yield create_empty_streaming_pull_request();
+                // ping message (empty request) or acknowledgements.
+                while let Ok(r) = rx.recv().await {
+                   yield r;
                 }
             });
             let mut v = request.into_streaming_request();
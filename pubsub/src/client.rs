@@ -1,16 +1,20 @@
 use std::env::var;
 
 use google_cloud_gax::conn::{ConnectionOptions, Environment};
+use google_cloud_gax::grpc::codegen::tokio_stream::{Stream, StreamExt};
 use google_cloud_gax::grpc::Status;
 use google_cloud_gax::retry::RetrySetting;
 use google_cloud_googleapis::pubsub::v1::{
-    DetachSubscriptionRequest, ListSnapshotsRequest, ListSubscriptionsRequest, ListTopicsRequest, Snapshot,
+    DetachSubscriptionRequest, ListSchemasRequest, ListSnapshotsRequest, ListSubscriptionsRequest, ListTopicsRequest,
+    Snapshot, ValidateSchemaRequest,
 };
 use google_cloud_token::NopeTokenSourceProvider;
 
 use crate::apiv1::conn_pool::{ConnectionManager, PUBSUB};
 use crate::apiv1::publisher_client::PublisherClient;
+use crate::apiv1::schema_client::SchemaClient;
 use crate::apiv1::subscriber_client::SubscriberClient;
+use crate::schema::{Schema, SchemaConfig, SchemaView};
 use crate::subscription::{Subscription, SubscriptionConfig};
 use crate::topic::{Topic, TopicConfig};
 
@@ -26,13 +30,23 @@ pub struct ClientConfig {
     pub endpoint: String,
     /// gRPC connection option
     pub connection_option: ConnectionOptions,
+    /// The user impersonated by the service account with the domain-wide delegation, used by `with_auth` and
+    /// `with_credentials`. The token is always minted by the token endpoint instead of the self-signed JWT.
+    pub subject: Option<String>,
 }
 
-/// ClientConfigs created by default will prefer to use `PUBSUB_EMULATOR_HOST`
+/// The project id used with the emulator when `PUBSUB_PROJECT_ID` is not set.
+const DEFAULT_EMULATOR_PROJECT_ID: &str = "local-project";
+
+/// ClientConfigs created by default will prefer to use `PUBSUB_EMULATOR_HOST`.
+/// With the emulator, the connection is plaintext, the credentials are not used and
+/// the project id is taken from `PUBSUB_PROJECT_ID` because the metadata server is not available.
 impl Default for ClientConfig {
     fn default() -> Self {
         let emulator = var("PUBSUB_EMULATOR_HOST").ok();
-        let default_project_id = emulator.as_ref().map(|_| "local-project".to_string());
+        let default_project_id = emulator
+            .as_ref()
+            .map(|_| var("PUBSUB_PROJECT_ID").unwrap_or_else(|_| DEFAULT_EMULATOR_PROJECT_ID.to_string()));
         Self {
             pool_size: Some(4),
             environment: match emulator {
@@ -42,6 +56,19 @@ impl Default for ClientConfig {
             project_id: default_project_id,
             endpoint: PUBSUB.to_string(),
             connection_option: ConnectionOptions::default(),
+            subject: None,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// with_emulator creates the config connecting to the emulator at the host like `localhost:8681`
+    /// regardless of `PUBSUB_EMULATOR_HOST`. `with_auth` and `with_credentials` keep the emulator settings.
+    pub fn with_emulator(host: impl Into<String>, project_id: impl Into<String>) -> Self {
+        Self {
+            environment: Environment::Emulator(host.into()),
+            project_id: Some(project_id.into()),
+            ..Default::default()
         }
     }
 }
@@ -53,7 +80,7 @@ pub use google_cloud_auth;
 impl ClientConfig {
     pub async fn with_auth(mut self) -> Result<Self, google_cloud_auth::error::Error> {
         if let Environment::GoogleCloud(_) = self.environment {
-            let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new(Self::auth_config()).await?;
+            let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new(self.auth_config()).await?;
             self.project_id = self.project_id.or(ts.project_id.clone());
             self.environment = Environment::GoogleCloud(Box::new(ts))
         }
@@ -66,7 +93,7 @@ impl ClientConfig {
     ) -> Result<Self, google_cloud_auth::error::Error> {
         if let Environment::GoogleCloud(_) = self.environment {
             let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new_with_credentials(
-                Self::auth_config(),
+                self.auth_config(),
                 Box::new(credentials),
             )
             .await?;
@@ -76,11 +103,35 @@ impl ClientConfig {
         Ok(self)
     }
 
-    fn auth_config() -> google_cloud_auth::project::Config<'static> {
+    /// with_bootstrap uses the token source, the project id, the quota project and the `pubsub` endpoint override
+    /// shared by the bootstrap. It connects to the emulator instead if the bootstrap has its host.
+    pub fn with_bootstrap(mut self, bootstrap: &google_cloud_auth::bootstrap::Bootstrap) -> Self {
+        if let Some(endpoint) = bootstrap.endpoint("pubsub") {
+            self.endpoint = endpoint.to_string();
+        }
+        if let Some(host) = bootstrap.emulator_host("pubsub") {
+            self.environment = Environment::Emulator(host.to_string());
+        }
+        self.project_id = self.project_id.or(bootstrap.project_id().map(String::from));
+        match self.environment {
+            Environment::GoogleCloud(_) => {
+                self.environment = Environment::GoogleCloud(bootstrap.token_source_provider());
+                self.connection_option.quota_project = bootstrap.quota_project().map(String::from);
+            }
+            Environment::Emulator(_) => {
+                self.project_id = self.project_id.or_else(|| {
+                    Some(var("PUBSUB_PROJECT_ID").unwrap_or_else(|_| DEFAULT_EMULATOR_PROJECT_ID.to_string()))
+                });
+            }
+        }
+        self
+    }
+
+    fn auth_config(&self) -> google_cloud_auth::project::Config<'_> {
         google_cloud_auth::project::Config {
             audience: Some(crate::apiv1::conn_pool::AUDIENCE),
             scopes: Some(&crate::apiv1::conn_pool::SCOPES),
-            sub: None,
+            sub: self.subject.as_deref(),
         }
     }
 }
@@ -102,6 +153,7 @@ pub struct Client {
     project_id: String,
     pubc: PublisherClient,
     subc: SubscriberClient,
+    schc: SchemaClient,
 }
 
 impl Client {
@@ -127,11 +179,16 @@ impl Client {
             )
             .await?,
         );
+        // The schema service is used only for the administrative operations, so a single connection is enough.
+        let schc = SchemaClient::new(
+            ConnectionManager::new(1, config.endpoint.as_str(), &config.environment, &config.connection_option).await?,
+        );
 
         Ok(Self {
             project_id: config.project_id.ok_or(Error::ProjectIdNotFound)?,
             pubc,
             subc,
+            schc,
         })
     }
 
@@ -182,6 +239,24 @@ impl Client {
         })
     }
 
+    /// subscriptions_stream is the lazy version of `get_subscriptions`.
+    /// The subscriptions are listed page by page as the stream is consumed.
+    #[allow(clippy::result_large_err)]
+    pub fn subscriptions_stream(
+        &self,
+        retry: Option<RetrySetting>,
+    ) -> impl Stream<Item = Result<Subscription, Status>> + Send + 'static {
+        let req = ListSubscriptionsRequest {
+            project: self.fully_qualified_project_name(),
+            page_size: 0,
+            page_token: "".to_string(),
+        };
+        let subc = self.subc.clone();
+        self.subc
+            .list_subscriptions_stream(req, retry)
+            .map(move |v| v.map(|x| Subscription::new(x.name, subc.clone())))
+    }
+
     /// subscription creates a reference to a subscription.
     pub fn subscription(&self, id: &str) -> Subscription {
         Subscription::new(self.fully_qualified_subscription_name(id), self.subc.clone())
@@ -230,6 +305,25 @@ impl Client {
             .map(|v| v.into_iter().map(|x| x.name).collect())
     }
 
+    /// topics_stream is the lazy version of `get_topics`.
+    /// The topics are listed page by page as the stream is consumed.
+    #[allow(clippy::result_large_err)]
+    pub fn topics_stream(
+        &self,
+        retry: Option<RetrySetting>,
+    ) -> impl Stream<Item = Result<Topic, Status>> + Send + 'static {
+        let req = ListTopicsRequest {
+            project: self.fully_qualified_project_name(),
+            page_size: 0,
+            page_token: "".to_string(),
+        };
+        let pubc = self.pubc.clone();
+        let subc = self.subc.clone();
+        self.pubc
+            .list_topics_stream(req, retry)
+            .map(move |v| v.map(|x| Topic::new(x.name, pubc.clone(), subc.clone())))
+    }
+
     /// topic creates a reference to a topic in the client's project.
     ///
     /// If a Topic's Publish method is called, it has background tasks
@@ -253,6 +347,58 @@ impl Client {
         self.subc.list_snapshots(req, retry).await
     }
 
+    /// create_schema creates a new schema.
+    ///
+    /// The specified schema ID must start with a letter, and contain only letters
+    /// ([A-Za-z]), numbers ([0-9]), dashes (-), underscores (_), periods (.),
+    /// tildes (~), plus (+) or percent signs (%). It must be between 3 and 255
+    /// characters in length, and must not start with "goog".
+    ///
+    /// If the schema already exists an error will be returned.
+    pub async fn create_schema(
+        &self,
+        id: &str,
+        cfg: SchemaConfig,
+        retry: Option<RetrySetting>,
+    ) -> Result<Schema, Status> {
+        let schema = self.schema(id);
+        schema.create(cfg, retry).await.map(|_v| schema)
+    }
+
+    /// get_schemas lists the latest revisions of the schemas in the client's project.
+    /// The definitions are included only with `SchemaView::Full`.
+    pub async fn get_schemas(
+        &self,
+        view: SchemaView,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<SchemaConfig>, Status> {
+        let req = ListSchemasRequest {
+            parent: self.fully_qualified_project_name(),
+            view: view.into(),
+            page_size: 0,
+            page_token: "".to_string(),
+        };
+        self.schc
+            .list_schemas(req, retry)
+            .await
+            .map(|v| v.into_iter().map(SchemaConfig::from).collect())
+    }
+
+    /// schema creates a reference to a schema in the client's project.
+    pub fn schema(&self, id: &str) -> Schema {
+        Schema::new(self.fully_qualified_schema_name(id), self.schc.clone())
+    }
+
+    /// validate_schema validates the definition of the schema without creating it.
+    /// The error is `InvalidArgument` if the definition is invalid.
+    pub async fn validate_schema(&self, cfg: SchemaConfig, retry: Option<RetrySetting>) -> Result<(), Status> {
+        let req = ValidateSchemaRequest {
+            parent: self.fully_qualified_project_name(),
+            schema: Some(cfg.to_internal("")),
+        };
+        self.schc.validate_schema(req, retry).await.map(|_v| ())
+    }
+
     pub fn fully_qualified_topic_name(&self, id: &str) -> String {
         if id.contains('/') {
             id.to_string()
@@ -269,6 +415,14 @@ impl Client {
         }
     }
 
+    pub fn fully_qualified_schema_name(&self, id: &str) -> String {
+        if id.contains('/') {
+            id.to_string()
+        } else {
+            format!("projects/{}/schemas/{}", self.project_id, id)
+        }
+    }
+
     fn fully_qualified_project_name(&self) -> String {
         format!("projects/{}", self.project_id)
     }
@@ -284,9 +438,13 @@ mod tests {
     use tokio_util::sync::CancellationToken;
     use uuid::Uuid;
 
+    use google_cloud_gax::conn::Environment;
+    use google_cloud_gax::grpc::codegen::tokio_stream::StreamExt;
+    use google_cloud_gax::grpc::Code;
     use google_cloud_googleapis::pubsub::v1::PubsubMessage;
 
-    use crate::client::Client;
+    use crate::client::{Client, ClientConfig};
+    use crate::publisher::PublisherConfig;
     use crate::subscriber::SubscriberConfig;
     use crate::subscription::{ReceiveConfig, SubscriptionConfig};
 
@@ -310,9 +468,12 @@ mod tests {
         let topic_id = &format!("t{}", &uuid);
         let subscription_id = &format!("s{}", &uuid);
         let topic = client.create_topic(topic_id.as_str(), None, None).await.unwrap();
-        let publisher = topic.new_publisher(None);
+        let publisher = topic.new_publisher(Some(PublisherConfig {
+            enable_message_ordering: order,
+            ..Default::default()
+        }));
         let config = SubscriptionConfig {
-            enable_message_ordering: !ordering_key.is_empty(),
+            enable_message_ordering: order,
             ..Default::default()
         };
         let subscription = client
@@ -424,6 +585,154 @@ mod tests {
         do_publish_and_subscribe("", true).await;
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_publish_subscribe_ordered_concurrently() {
+        let client = create_client().await;
+
+        let uuid = Uuid::new_v4().hyphenated().to_string();
+        let topic_id = &format!("t{}", &uuid);
+        let subscription_id = &format!("s{}", &uuid);
+        let topic = client.create_topic(topic_id.as_str(), None, None).await.unwrap();
+        let publisher = topic.new_publisher(Some(PublisherConfig {
+            workers: 2,
+            enable_message_ordering: true,
+            ..Default::default()
+        }));
+        let config = SubscriptionConfig {
+            enable_message_ordering: true,
+            ..Default::default()
+        };
+        let subscription = client
+            .create_subscription(subscription_id.as_str(), topic_id.as_str(), config, None)
+            .await
+            .unwrap();
+
+        // Publish the messages for each key from its own task, so the publishes for the keys interleave.
+        let keys = ["key1", "key2", "key3", "key4", "key5"];
+        let tasks: Vec<_> = keys
+            .iter()
+            .map(|&key| {
+                let publisher = publisher.clone();
+                tokio::spawn(async move {
+                    let mut awaiters = Vec::with_capacity(30);
+                    for i in 0..30 {
+                        let message = PubsubMessage {
+                            data: format!("{key}_{i}").into(),
+                            ordering_key: key.to_string(),
+                            ..Default::default()
+                        };
+                        awaiters.push(publisher.publish(message).await);
+                        if i % 7 == 0 {
+                            tokio::task::yield_now().await;
+                        }
+                    }
+                    for awaiter in awaiters {
+                        awaiter.get().await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let cancellation_token = CancellationToken::new();
+        let cancel_receiver = cancellation_token.clone();
+        let (s, mut r) = tokio::sync::mpsc::channel(200);
+        let handle = tokio::spawn(async move {
+            let _ = subscription
+                .receive(
+                    move |v, _ctx| {
+                        let s2 = s.clone();
+                        async move {
+                            let _ = v.ack().await;
+                            let key = v.message.ordering_key.clone();
+                            let data = std::str::from_utf8(&v.message.data).unwrap().to_string();
+                            let _ = s2.send((key, data)).await;
+                        }
+                    },
+                    cancel_receiver,
+                    None,
+                )
+                .await;
+        });
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        cancellation_token.cancel();
+        let _ = handle.await;
+
+        let mut received = HashMap::<String, Vec<String>>::new();
+        while let Some((key, data)) = r.recv().await {
+            received.entry(key).or_default().push(data);
+        }
+        assert_eq!(keys.len(), received.len());
+        for key in keys {
+            let expected: Vec<String> = (0..30).map(|i| format!("{key}_{i}")).collect();
+            assert_eq!(expected, received[key], "key={key}");
+        }
+
+        let mut publisher = publisher;
+        publisher.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_publish_ordering_key_without_enable_message_ordering() {
+        let client = create_client().await;
+        let uuid = Uuid::new_v4().hyphenated().to_string();
+        let topic = client.create_topic(&format!("t{uuid}"), None, None).await.unwrap();
+        let mut publisher = topic.new_publisher(None);
+
+        let message = PubsubMessage {
+            data: "abc".into(),
+            ordering_key: "key".into(),
+            ..Default::default()
+        };
+        let err = publisher.publish(message.clone()).await.get().await.unwrap_err();
+        assert_eq!(Code::FailedPrecondition, err.code());
+        for awaiter in publisher.publish_bulk(vec![message.clone(), message]).await {
+            assert_eq!(Code::FailedPrecondition, awaiter.get().await.unwrap_err().code());
+        }
+        publisher.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_publish_resume_after_error() {
+        let client = create_client().await;
+        let uuid = Uuid::new_v4().hyphenated().to_string();
+        let topic = client.create_topic(&format!("t{uuid}"), None, None).await.unwrap();
+        let mut publisher = topic.new_publisher(Some(PublisherConfig {
+            enable_message_ordering: true,
+            ..Default::default()
+        }));
+        let message = |data: &str| PubsubMessage {
+            data: data.to_string().into(),
+            ordering_key: "key".into(),
+            ..Default::default()
+        };
+
+        // The publish fails while the topic doesn't exist.
+        topic.delete(None).await.unwrap();
+        let err = publisher.publish(message("abc")).await.get().await.unwrap_err();
+        assert_eq!(Code::NotFound, err.code());
+
+        // The key is paused until resume_publish is called even after the topic is back.
+        topic.create(None, None).await.unwrap();
+        let err = publisher.publish(message("abc")).await.get().await.unwrap_err();
+        assert_eq!(Code::FailedPrecondition, err.code());
+        let other = PubsubMessage {
+            data: "abc".into(),
+            ordering_key: "other".into(),
+            ..Default::default()
+        };
+        assert!(publisher.publish(other).await.get().await.is_ok());
+
+        publisher.resume_publish("key");
+        assert!(publisher.publish(message("abc")).await.get().await.is_ok());
+        publisher.shutdown().await;
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     #[serial]
     async fn test_lifecycle() {
@@ -453,6 +762,101 @@ mod tests {
         assert_eq!(1, topics_after.len() - topics.len());
         assert_eq!(1, subs_after.len() - subs.len());
         assert_eq!(1, snapshots_after.len() - snapshots.len());
+
+        let topics_streamed = client.topics_stream(None).collect::<Result<Vec<_>, _>>().await.unwrap();
+        let subs_streamed = client
+            .subscriptions_stream(None)
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .unwrap();
+        assert_eq!(topics_after.len(), topics_streamed.len());
+        assert_eq!(subs_after.len(), subs_streamed.len());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_with_emulator() {
+        let config = ClientConfig::with_emulator("localhost:8681", "local-project");
+        assert!(matches!(config.environment, Environment::Emulator(_)));
+        let client = Client::new(config).await.unwrap();
+        assert_eq!(client.fully_qualified_project_name(), "projects/local-project");
+
+        let uuid = Uuid::new_v4().hyphenated().to_string();
+        let topic_id = &format!("t{}", &uuid);
+        let subscription_id = &format!("s{}", &uuid);
+        let topic = client.create_topic(topic_id, None, None).await.unwrap();
+        let subscription = client
+            .create_subscription(subscription_id, topic_id, SubscriptionConfig::default(), None)
+            .await
+            .unwrap();
+        assert!(topic.exists(None).await.unwrap());
+        assert!(subscription.exists(None).await.unwrap());
+
+        // publish
+        let mut publisher = topic.new_publisher(None);
+        let msg = PubsubMessage {
+            data: "emulator".into(),
+            ..Default::default()
+        };
+        let message_id = publisher.publish(msg).await.get().await.unwrap();
+        publisher.shutdown().await;
+
+        // streaming pull
+        let mut stream = subscription.subscribe(None).await.unwrap();
+        let message = tokio::time::timeout(Duration::from_secs(30), stream.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(message.message.message_id, message_id);
+        assert_eq!(&*message.message.data, b"emulator");
+        message.ack().await.unwrap();
+        drop(stream);
+
+        subscription.delete(None).await.unwrap();
+        topic.delete(None).await.unwrap();
+        assert!(!subscription.exists(None).await.unwrap());
+        assert!(!topic.exists(None).await.unwrap());
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    #[serial]
+    async fn test_with_bootstrap() {
+        use google_cloud_auth::bootstrap::ClientBootstrap;
+        use google_cloud_token::NopeTokenSourceProvider;
+
+        let original = std::env::var("PUBSUB_EMULATOR_HOST").ok();
+        std::env::remove_var("PUBSUB_EMULATOR_HOST");
+        let bootstrap = ClientBootstrap::new()
+            .with_token_provider(Box::new(NopeTokenSourceProvider {}))
+            .with_project_id("project")
+            .with_quota_project("quota-project")
+            .with_endpoint_overrides([("pubsub", "pubsub.example.com")])
+            .build()
+            .await
+            .unwrap();
+        let config = ClientConfig::default().with_bootstrap(&bootstrap);
+        assert!(matches!(config.environment, Environment::GoogleCloud(_)));
+        assert_eq!(config.endpoint, "pubsub.example.com");
+        assert_eq!(config.project_id.as_deref(), Some("project"));
+        assert_eq!(config.connection_option.quota_project.as_deref(), Some("quota-project"));
+
+        std::env::set_var("PUBSUB_EMULATOR_HOST", "localhost:18681");
+        let bootstrap = ClientBootstrap::new()
+            .with_token_provider(Box::new(NopeTokenSourceProvider {}))
+            .with_quota_project("quota-project")
+            .build()
+            .await
+            .unwrap();
+        std::env::remove_var("PUBSUB_EMULATOR_HOST");
+        let config = ClientConfig::default().with_bootstrap(&bootstrap);
+        assert!(matches!(&config.environment, Environment::Emulator(host) if host == "localhost:18681"));
+        assert!(config.project_id.is_some());
+        assert_eq!(config.connection_option.quota_project, None);
+
+        if let Some(original) = original {
+            std::env::set_var("PUBSUB_EMULATOR_HOST", original);
+        }
     }
 }
 
@@ -460,6 +864,7 @@ mod tests {
 mod tests_in_gcp {
     use crate::client::{Client, ClientConfig};
     use crate::publisher::PublisherConfig;
+    use crate::schema::{Encoding, SchemaConfig, SchemaType, SchemaView};
     use google_cloud_gax::conn::Environment;
     use google_cloud_gax::grpc::codegen::tokio_stream::StreamExt;
     use google_cloud_googleapis::pubsub::v1::PubsubMessage;
@@ -501,6 +906,7 @@ mod tests_in_gcp {
         let publisher = topic.new_publisher(Some(PublisherConfig {
             flush_interval: Duration::from_secs(3),
             workers: 3,
+            enable_message_ordering: true,
             ..Default::default()
         }));
 
@@ -533,6 +939,7 @@ mod tests_in_gcp {
         let publisher = topic.new_publisher(Some(PublisherConfig {
             flush_interval: Duration::from_secs(30),
             workers: 1,
+            enable_message_ordering: true,
             bundle_size: 8,
             ..Default::default()
         }));
@@ -566,6 +973,7 @@ mod tests_in_gcp {
         let publisher = topic.new_publisher(Some(PublisherConfig {
             flush_interval: Duration::from_secs(30),
             workers: 2,
+            enable_message_ordering: true,
             bundle_size: 8,
             ..Default::default()
         }));
@@ -653,4 +1061,54 @@ mod tests_in_gcp {
             assert_eq!(count, 1, "msg_id = {msg_id}, count = {count}");
         }
     }
+
+    #[tokio::test]
+    #[serial]
+    #[ignore]
+    async fn test_schema_lifecycle_in_gcp() {
+        let client = Client::new(ClientConfig::default().with_auth().await.unwrap())
+            .await
+            .unwrap();
+        let definition = |fields: &str| {
+            format!(r#"{{"type":"record","name":"Avro","fields":[{{"name":"name","type":"string"}}{fields}]}}"#)
+        };
+        let config = SchemaConfig {
+            schema_type: SchemaType::Avro,
+            definition: definition(""),
+            ..Default::default()
+        };
+        client.validate_schema(config.clone(), None).await.unwrap();
+
+        let schema_id = format!("s{}", uuid::Uuid::new_v4());
+        let schema = client.create_schema(&schema_id, config.clone(), None).await.unwrap();
+        let first = schema.config(SchemaView::Full, None).await.unwrap();
+        assert_eq!(first.definition, config.definition);
+        let schemas = client.get_schemas(SchemaView::Basic, None).await.unwrap();
+        assert!(schemas.iter().any(|v| v.name == schema.fully_qualified_name()));
+
+        let second = schema
+            .commit(
+                SchemaConfig {
+                    definition: definition(r#",{"name":"age","type":"int","default":0}"#),
+                    ..config
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(schema.revisions(SchemaView::Basic, None).await.unwrap().len(), 2);
+        schema
+            .validate_message(br#"{"name":"a","age":1}"#, Encoding::Json, None)
+            .await
+            .unwrap();
+
+        let third = schema.rollback(&first.revision_id, None).await.unwrap();
+        assert_eq!(third.definition, first.definition);
+        schema.delete_revision(&second.revision_id, None).await.unwrap();
+        let revisions = schema.revisions(SchemaView::Basic, None).await.unwrap();
+        assert!(revisions.iter().all(|v| v.revision_id != second.revision_id));
+
+        schema.delete(None).await.unwrap();
+        assert!(!schema.exists(None).await.unwrap());
+    }
 }
@@ -0,0 +1,195 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Not;
+
+/// The max length of the filter in bytes.
+pub const MAX_FILTER_LENGTH: usize = 256;
+
+/// Filter is a subscription filter on the message attributes. Only the messages matching the filter
+/// are delivered to the subscription. See https://cloud.google.com/pubsub/docs/subscription-message-filter.
+///
+/// ```
+/// use google_cloud_pubsub::filter::Filter;
+///
+/// let filter = Filter::equals("type", "order").and(Filter::has_prefix("region", "eu-").or(!Filter::has("test")));
+/// assert_eq!(
+///     filter.to_string(),
+///     r#"attributes.type = "order" AND (hasPrefix(attributes.region, "eu-") OR NOT attributes:test)"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// The attribute equals the value like `attributes.type = "order"`.
+    Equals(String, String),
+    /// The attribute doesn't equal the value like `attributes.type != "order"`.
+    /// It matches the messages without the attribute too.
+    NotEquals(String, String),
+    /// The message has the attribute like `attributes:type`.
+    Has(String),
+    /// The attribute starts with the prefix like `hasPrefix(attributes.region, "eu-")`.
+    HasPrefix(String, String),
+    /// All of the filters match.
+    And(Vec<Filter>),
+    /// Any of the filters match.
+    Or(Vec<Filter>),
+    /// The filter doesn't match.
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn equals(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::Equals(key.into(), value.into())
+    }
+
+    pub fn not_equals(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::NotEquals(key.into(), value.into())
+    }
+
+    pub fn has(key: impl Into<String>) -> Self {
+        Self::Has(key.into())
+    }
+
+    pub fn has_prefix(key: impl Into<String>, prefix: impl Into<String>) -> Self {
+        Self::HasPrefix(key.into(), prefix.into())
+    }
+
+    /// and combines the filters with `AND`. The nested `AND`s are flattened.
+    pub fn and(self, other: Filter) -> Self {
+        match self {
+            Self::And(mut v) => {
+                v.push(other);
+                Self::And(v)
+            }
+            _ => Self::And(vec![self, other]),
+        }
+    }
+
+    /// or combines the filters with `OR`. The nested `OR`s are flattened.
+    pub fn or(self, other: Filter) -> Self {
+        match self {
+            Self::Or(mut v) => {
+                v.push(other);
+                Self::Or(v)
+            }
+            _ => Self::Or(vec![self, other]),
+        }
+    }
+
+    /// is_compound returns true if the filter needs the parentheses to be nested.
+    fn is_compound(&self) -> bool {
+        matches!(self, Self::And(v) | Self::Or(v) if v.len() > 1)
+    }
+
+    fn fmt_operand(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_compound() {
+            write!(f, "({self})")
+        } else {
+            write!(f, "{self}")
+        }
+    }
+
+    fn fmt_list(filters: &[Filter], op: &str, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, filter) in filters.iter().enumerate() {
+            if i > 0 {
+                write!(f, " {op} ")?;
+            }
+            // AND and OR can't be mixed without the parentheses.
+            filter.fmt_operand(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Self::Output {
+        match self {
+            Self::Not(v) => *v,
+            _ => Self::Not(Box::new(self)),
+        }
+    }
+}
+
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Equals(key, value) => write!(f, "attributes.{} = {}", key_literal(key), string_literal(value)),
+            Self::NotEquals(key, value) => write!(f, "attributes.{} != {}", key_literal(key), string_literal(value)),
+            Self::Has(key) => write!(f, "attributes:{}", key_literal(key)),
+            Self::HasPrefix(key, prefix) => {
+                write!(f, "hasPrefix(attributes.{}, {})", key_literal(key), string_literal(prefix))
+            }
+            Self::And(v) => Self::fmt_list(v, "AND", f),
+            Self::Or(v) => Self::fmt_list(v, "OR", f),
+            Self::Not(v) => {
+                write!(f, "NOT ")?;
+                v.fmt_operand(f)
+            }
+        }
+    }
+}
+
+impl From<Filter> for String {
+    fn from(filter: Filter) -> Self {
+        filter.to_string()
+    }
+}
+
+/// key_literal quotes the attribute key unless it is an identifier of only letters, digits and `_`.
+fn key_literal(key: &str) -> String {
+    let is_identifier = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier {
+        key.to_string()
+    } else {
+        string_literal(key)
+    }
+}
+
+fn string_literal(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::filter::Filter;
+
+    #[test]
+    fn test_filter_to_string() {
+        assert_eq!(Filter::equals("type", "order").to_string(), r#"attributes.type = "order""#);
+        assert_eq!(Filter::not_equals("type", "order").to_string(), r#"attributes.type != "order""#);
+        assert_eq!(Filter::has("type").to_string(), "attributes:type");
+        assert_eq!(
+            Filter::has_prefix("region", "eu-").to_string(),
+            r#"hasPrefix(attributes.region, "eu-")"#
+        );
+        assert_eq!(
+            Filter::equals("iana.org/language_tag", r#"say "hi" \o/"#).to_string(),
+            r#"attributes."iana.org/language_tag" = "say \"hi\" \\o/""#
+        );
+    }
+
+    #[test]
+    fn test_filter_operators() {
+        let filter = Filter::equals("a", "1")
+            .and(Filter::equals("b", "2"))
+            .and(Filter::has("c"));
+        assert_eq!(
+            filter.to_string(),
+            r#"attributes.a = "1" AND attributes.b = "2" AND attributes:c"#
+        );
+
+        let filter = Filter::equals("a", "1")
+            .or(Filter::equals("b", "2"))
+            .and(Filter::has("c"));
+        assert_eq!(
+            filter.to_string(),
+            r#"(attributes.a = "1" OR attributes.b = "2") AND attributes:c"#
+        );
+
+        let filter = !Filter::equals("a", "1").and(Filter::has("c"));
+        assert_eq!(filter.to_string(), r#"NOT (attributes.a = "1" AND attributes:c)"#);
+        assert_eq!(!filter.clone(), Filter::equals("a", "1").and(Filter::has("c")));
+        assert_eq!(String::from(!Filter::has("c")), "NOT attributes:c");
+    }
+}
@@ -48,6 +48,19 @@
 //!
 //! ```sh
 //! export PUBSUB_EMULATOR_HOST=localhost:8681
+//! # optional, the default is local-project
+//! export PUBSUB_PROJECT_ID=local-project
+//! ```
+//!
+//! Or create the config for the emulator explicitly.
+//!
+//! ```
+//! use google_cloud_pubsub::client::{ClientConfig, Client};
+//!
+//! async fn run() {
+//!     let config = ClientConfig::with_emulator("localhost:8681", "local-project");
+//!     let client = Client::new(config).await.unwrap();
+//! }
 //! ```
 //!
 //! ### Publish Message
@@ -55,6 +68,7 @@
 //! ```
 //! use google_cloud_pubsub::client::{Client, ClientConfig};
 //! use google_cloud_googleapis::pubsub::v1::PubsubMessage;
+//! use google_cloud_pubsub::publisher::PublisherConfig;
 //! use google_cloud_pubsub::topic::TopicConfig;
 //! use google_cloud_pubsub::subscription::SubscriptionConfig;
 //! use google_cloud_gax::grpc::Status;
@@ -73,7 +87,12 @@
 //!     }
 //!
 //!     // Start publisher.
-//!     let publisher = topic.new_publisher(None);
+//!     // Enable message ordering if the messages have ordering_key.
+//!     // When a publish fails, the ordering key is paused until `publisher.resume_publish(key)` is called.
+//!     let publisher = topic.new_publisher(Some(PublisherConfig {
+//!         enable_message_ordering: true,
+//!         ..Default::default()
+//!     }));
 //!
 //!     // Publish message.
 //!     let tasks : Vec<JoinHandle<Result<String,Status>>> = (0..10).into_iter().map(|_i| {
@@ -101,7 +120,7 @@
 //!
 //!     // Wait for publishers in topic finish.
 //!     let mut publisher = publisher;
-//!     publisher.shutdown();
+//!     publisher.shutdown().await;
 //!
 //!     Ok(())
 //! }
@@ -199,7 +218,10 @@
 //! ```
 pub mod apiv1;
 pub mod client;
+pub mod filter;
+pub mod message;
 pub mod publisher;
+pub mod schema;
 pub mod subscriber;
 pub mod subscription;
 pub mod topic;
@@ -0,0 +1,263 @@
+use std::time::SystemTime;
+
+use prost::Message;
+
+use google_cloud_gax::grpc::Status;
+use google_cloud_googleapis::pubsub::v1::PubsubMessage;
+
+use crate::apiv1::PUBSUB_MESSAGE_LIMIT;
+
+/// The max number of the attributes of a message.
+pub const MAX_ATTRIBUTES: usize = 100;
+/// The max length of the attribute key in bytes.
+pub const MAX_ATTRIBUTE_KEY_LENGTH: usize = 256;
+/// The max length of the attribute value in bytes.
+pub const MAX_ATTRIBUTE_VALUE_LENGTH: usize = 1024;
+/// The max length of the ordering key in bytes.
+pub const MAX_ORDERING_KEY_LENGTH: usize = 1024;
+/// The max encoded size of a message in bytes.
+pub const MAX_MESSAGE_SIZE: usize = PUBSUB_MESSAGE_LIMIT;
+
+/// The attribute keys starting with this prefix are reserved by Pub/Sub.
+const RESERVED_ATTRIBUTE_PREFIX: &str = "goog";
+
+#[cfg(not(feature = "bytes"))]
+type Data = Vec<u8>;
+#[cfg(feature = "bytes")]
+type Data = prost::bytes::Bytes;
+
+/// validate_attribute returns the error if the attribute can't be set to the message.
+// The error is the Status like the other public APIs of the crate, so it is not boxed.
+#[allow(clippy::result_large_err)]
+pub fn validate_attribute(key: &str, value: &str) -> Result<(), Status> {
+    if key.is_empty() {
+        return Err(Status::invalid_argument("attribute key must not be empty"));
+    }
+    if key.len() > MAX_ATTRIBUTE_KEY_LENGTH {
+        return Err(Status::invalid_argument(format!(
+            "attribute key must be at most {MAX_ATTRIBUTE_KEY_LENGTH} bytes: {} bytes",
+            key.len()
+        )));
+    }
+    if key.starts_with(RESERVED_ATTRIBUTE_PREFIX) {
+        return Err(Status::invalid_argument(format!(
+            "attribute key must not start with \"{RESERVED_ATTRIBUTE_PREFIX}\": {key}"
+        )));
+    }
+    if value.len() > MAX_ATTRIBUTE_VALUE_LENGTH {
+        return Err(Status::invalid_argument(format!(
+            "attribute value of {key} must be at most {MAX_ATTRIBUTE_VALUE_LENGTH} bytes: {} bytes",
+            value.len()
+        )));
+    }
+    Ok(())
+}
+
+/// validate_message returns the error if the message is rejected by the server.
+#[allow(clippy::result_large_err)]
+pub fn validate_message(message: &PubsubMessage) -> Result<(), Status> {
+    if message.data.is_empty() && message.attributes.is_empty() {
+        return Err(Status::invalid_argument("message must have data or at least one attribute"));
+    }
+    if message.attributes.len() > MAX_ATTRIBUTES {
+        return Err(Status::invalid_argument(format!(
+            "message must have at most {MAX_ATTRIBUTES} attributes: {} attributes",
+            message.attributes.len()
+        )));
+    }
+    for (key, value) in &message.attributes {
+        validate_attribute(key, value)?;
+    }
+    if message.ordering_key.len() > MAX_ORDERING_KEY_LENGTH {
+        return Err(Status::invalid_argument(format!(
+            "ordering key must be at most {MAX_ORDERING_KEY_LENGTH} bytes: {} bytes",
+            message.ordering_key.len()
+        )));
+    }
+    let size = message.encoded_len();
+    if size > MAX_MESSAGE_SIZE {
+        return Err(Status::invalid_argument(format!(
+            "message must be at most {MAX_MESSAGE_SIZE} bytes: {size} bytes"
+        )));
+    }
+    Ok(())
+}
+
+/// MessageExt is the typed accessors of the `PubsubMessage`.
+/// The received message is available as `ReceivedMessage::message`.
+pub trait MessageExt {
+    /// publish_time returns the time the server received the message. It is `None` for the outgoing messages.
+    fn publish_time(&self) -> Option<SystemTime>;
+
+    /// attribute returns the value of the attribute.
+    fn attribute(&self, key: &str) -> Option<&str>;
+
+    /// ordering_key returns the ordering key of the message. It is `None` if the message is not ordered.
+    fn ordering_key(&self) -> Option<&str>;
+}
+
+impl MessageExt for PubsubMessage {
+    fn publish_time(&self) -> Option<SystemTime> {
+        self.publish_time.clone().and_then(|v| v.try_into().ok())
+    }
+
+    fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.get(key).map(|v| v.as_str())
+    }
+
+    fn ordering_key(&self) -> Option<&str> {
+        (!self.ordering_key.is_empty()).then_some(self.ordering_key.as_str())
+    }
+}
+
+/// MessageBuilder builds the outgoing message validating it against the limits of the server,
+/// so the invalid message fails before it is published.
+///
+/// ```
+/// use google_cloud_pubsub::message::{MessageBuilder, MessageExt};
+///
+/// let message = MessageBuilder::new("hello")
+///     .with_attribute("type", "greeting")
+///     .with_ordering_key("user-1")
+///     .build()
+///     .unwrap();
+/// assert_eq!(message.attribute("type"), Some("greeting"));
+/// assert_eq!(MessageExt::ordering_key(&message), Some("user-1"));
+///
+/// assert!(MessageBuilder::new("hello").with_attribute("googclient", "x").build().is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MessageBuilder {
+    message: PubsubMessage,
+}
+
+impl MessageBuilder {
+    pub fn new(data: impl Into<Data>) -> Self {
+        Self {
+            message: PubsubMessage {
+                data: data.into(),
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn with_attribute(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.message.attributes.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_attributes<K, V>(mut self, attributes: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.message
+            .attributes
+            .extend(attributes.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// with_ordering_key sets the ordering key. The publisher must enable the message ordering to publish it.
+    pub fn with_ordering_key(mut self, ordering_key: impl Into<String>) -> Self {
+        self.message.ordering_key = ordering_key.into();
+        self
+    }
+
+    /// build returns the message. The error is `InvalidArgument` describing the first limit the message exceeds.
+    #[allow(clippy::result_large_err)]
+    pub fn build(self) -> Result<PubsubMessage, Status> {
+        validate_message(&self.message)?;
+        Ok(self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use google_cloud_gax::grpc::Code;
+    use google_cloud_googleapis::pubsub::v1::PubsubMessage;
+
+    use crate::message::{
+        validate_attribute, MessageBuilder, MessageExt, MAX_ATTRIBUTES, MAX_ATTRIBUTE_KEY_LENGTH,
+        MAX_ATTRIBUTE_VALUE_LENGTH, MAX_MESSAGE_SIZE, MAX_ORDERING_KEY_LENGTH,
+    };
+
+    #[test]
+    fn test_message_ext() {
+        let published_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let message = PubsubMessage {
+            data: "data".into(),
+            attributes: [("type".to_string(), "order".to_string())].into_iter().collect(),
+            publish_time: Some(published_at.into()),
+            ordering_key: "key".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(message.publish_time(), Some(published_at));
+        assert_eq!(message.attribute("type"), Some("order"));
+        assert_eq!(message.attribute("region"), None);
+        assert_eq!(MessageExt::ordering_key(&message), Some("key"));
+
+        let message = PubsubMessage::default();
+        assert_eq!(message.publish_time(), None);
+        assert_eq!(MessageExt::ordering_key(&message), None);
+    }
+
+    #[test]
+    fn test_message_builder() {
+        let message = MessageBuilder::new("data")
+            .with_attribute("a", "1")
+            .with_attributes([("b", "2"), ("c", "3")])
+            .with_ordering_key("key")
+            .build()
+            .unwrap();
+        assert_eq!(message.data, b"data".to_vec());
+        assert_eq!(message.attributes.len(), 3);
+        assert_eq!(message.attribute("b"), Some("2"));
+        assert_eq!(message.ordering_key, "key");
+
+        // The message with only attributes is valid.
+        assert!(MessageBuilder::new("").with_attribute("a", "1").build().is_ok());
+    }
+
+    #[test]
+    fn test_message_builder_invalid() {
+        let invalid = [
+            (MessageBuilder::new(""), "message must have data"),
+            (
+                MessageBuilder::new("data").with_attributes((0..=MAX_ATTRIBUTES).map(|i| (i.to_string(), ""))),
+                "at most 100 attributes: 101 attributes",
+            ),
+            (
+                MessageBuilder::new("data").with_attribute("goog-version", "1"),
+                "must not start with \"goog\": goog-version",
+            ),
+            (
+                MessageBuilder::new("data").with_attribute("", "1"),
+                "attribute key must not be empty",
+            ),
+            (
+                MessageBuilder::new("data").with_attribute("k".repeat(MAX_ATTRIBUTE_KEY_LENGTH + 1), "1"),
+                "attribute key must be at most 256 bytes: 257 bytes",
+            ),
+            (
+                MessageBuilder::new("data").with_attribute("a", "v".repeat(MAX_ATTRIBUTE_VALUE_LENGTH + 1)),
+                "attribute value of a must be at most 1024 bytes: 1025 bytes",
+            ),
+            (
+                MessageBuilder::new("data").with_ordering_key("k".repeat(MAX_ORDERING_KEY_LENGTH + 1)),
+                "ordering key must be at most 1024 bytes: 1025 bytes",
+            ),
+            (
+                MessageBuilder::new(vec![0u8; MAX_MESSAGE_SIZE]),
+                "message must be at most 10485760 bytes",
+            ),
+        ];
+        for (builder, message) in invalid {
+            let err = builder.build().unwrap_err();
+            assert_eq!(err.code(), Code::InvalidArgument);
+            assert!(err.message().contains(message), "{}", err.message());
+        }
+        assert!(validate_attribute("k".repeat(MAX_ATTRIBUTE_KEY_LENGTH).as_str(), "").is_ok());
+    }
+}
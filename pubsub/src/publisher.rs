@@ -1,25 +1,42 @@
-use std::collections::HashMap;
-use std::ops::{Deref, DerefMut};
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
 
 use std::sync::Arc;
 use std::time::Duration;
 
 use async_channel::Receiver;
-use tokio::sync::oneshot;
+use prost::Message;
+use tokio::select;
 use tokio::sync::Mutex;
+use tokio::sync::{oneshot, watch};
 use tokio::task::JoinHandle;
-use tokio::time::timeout;
+use tokio::time::{sleep_until, timeout, Instant};
 
 use google_cloud_gax::grpc::Status;
 use google_cloud_gax::retry::RetrySetting;
-use google_cloud_googleapis::pubsub::v1::{PublishRequest, PubsubMessage};
+use google_cloud_googleapis::pubsub::v1::{Encoding, PublishRequest, PubsubMessage};
 
 use crate::apiv1::publisher_client::PublisherClient;
+use crate::apiv1::PUBSUB_MESSAGE_LIMIT;
+use crate::message::validate_message;
 use crate::util::ToUsize;
 
 pub(crate) struct ReservedMessage {
     pub producer: oneshot::Sender<Result<String, Status>>,
     pub message: PubsubMessage,
+    /// The encoded size of the message in the publish request.
+    pub size: usize,
+}
+
+/// message_size returns the encoded size of the message as a field of the publish request.
+fn message_size(message: &PubsubMessage) -> usize {
+    let len = message.encoded_len();
+    1 + prost::encoding::encoded_len_varint(len as u64) + len
+}
+
+/// request_overhead returns the encoded size of the publish request without messages.
+fn request_overhead(topic: &str) -> usize {
+    1 + prost::encoding::encoded_len_varint(topic.len() as u64) + topic.len()
 }
 
 pub(crate) enum Reserved {
@@ -27,15 +44,54 @@ pub(crate) enum Reserved {
     Multi(Vec<ReservedMessage>),
 }
 
+impl Reserved {
+    /// reject notifies the status to all the messages without publishing them.
+    fn reject(self, status: Status) {
+        let messages = match self {
+            Reserved::Single(message) => vec![message],
+            Reserved::Multi(messages) => messages,
+        };
+        for message in messages {
+            let _ = message.producer.send(Err(status.clone()));
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PublisherConfig {
     /// worker count. each workers have gRPC channel
     pub workers: usize,
-    /// interval for flush bundle message
+    /// max delay before a partial bundle is sent. It is counted from the first message of the bundle.
     pub flush_interval: Duration,
-    /// max bundle size to flush
+    /// max number of the messages in a bundle. The bundle is sent as soon as it reaches this count.
     pub bundle_size: usize,
+    /// max bytes of a publish request. The bundle is sent before it exceeds this size.
+    /// It is capped at 10MB, the limit of the publish request of Pub/Sub.
+    /// A message exceeding this size by itself is rejected with `InvalidArgument` without being queued.
+    pub bundle_byte_size: usize,
     pub retry_setting: Option<RetrySetting>,
+    /// enable_message_ordering enables the delivery of the messages with the same ordering key in the order
+    /// they are published. It is false by default and the messages with an ordering key are rejected with
+    /// `FailedPrecondition` until it is enabled.
+    ///
+    /// The messages for the same key are published sequentially. When a publish for a key fails, the key is paused
+    /// and the subsequent messages for it are rejected until `Publisher::resume_publish` is called.
+    ///
+    /// The server accepts the ordering keys on any topic, but it delivers the messages in order only to
+    /// the subscriptions created with `enable_message_ordering`. The messages are ordered only when they are
+    /// published to the same region, so use a regional endpoint if the publishers run in multiple regions.
+    /// See https://cloud.google.com/pubsub/docs/ordering
+    pub enable_message_ordering: bool,
+    /// schema_encoding is the encoding of the schema settings of the topic. Set it from `Topic::schema_settings`
+    /// to check the messages before publishing them to the topic with a schema. No check is done by default.
+    ///
+    /// With `Encoding::Json`, the messages whose data is not a well-formed JSON value are rejected with
+    /// `InvalidArgument` without being queued. The data is validated against the schema only by the server.
+    /// Use `Schema::validate_message` to validate it against the schema in advance.
+    pub schema_encoding: Option<Encoding>,
+    /// shutdown_timeout is the max time `Publisher::shutdown` waits for the pending messages to be published
+    /// including their retries. The workers are aborted after it and the messages not published yet fail.
+    pub shutdown_timeout: Duration,
 }
 
 impl Default for PublisherConfig {
@@ -44,7 +100,11 @@ impl Default for PublisherConfig {
             workers: 3,
             flush_interval: Duration::from_millis(100),
             bundle_size: 3,
+            bundle_byte_size: PUBSUB_MESSAGE_LIMIT,
             retry_setting: None,
+            enable_message_ordering: false,
+            schema_encoding: None,
+            shutdown_timeout: Duration::from_secs(30),
         }
     }
 }
@@ -57,6 +117,14 @@ impl Awaiter {
     pub(crate) fn new(consumer: oneshot::Receiver<Result<String, Status>>) -> Self {
         Self { consumer }
     }
+
+    /// rejected creates the Awaiter which is already failed with the status.
+    fn rejected(status: Status) -> Self {
+        let (producer, consumer) = oneshot::channel();
+        let _ = producer.send(Err(status));
+        Self::new(consumer)
+    }
+
     pub async fn get(self) -> Result<String, Status> {
         match self.consumer.await {
             Ok(v) => v,
@@ -65,6 +133,88 @@ impl Awaiter {
     }
 }
 
+/// PausedKeys holds the ordering keys whose publish failed.
+#[derive(Clone, Debug, Default)]
+struct PausedKeys {
+    inner: Arc<std::sync::Mutex<HashSet<String>>>,
+}
+
+impl PausedKeys {
+    fn pause(&self, ordering_key: &str) {
+        self.inner.lock().unwrap().insert(ordering_key.to_string());
+    }
+
+    fn resume(&self, ordering_key: &str) {
+        self.inner.lock().unwrap().remove(ordering_key);
+    }
+
+    fn is_paused(&self, ordering_key: &str) -> bool {
+        self.inner.lock().unwrap().contains(ordering_key)
+    }
+}
+
+/// Outstanding counts the messages whose results are not notified yet.
+#[derive(Clone, Debug)]
+struct Outstanding {
+    inner: Arc<watch::Sender<usize>>,
+}
+
+impl Default for Outstanding {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(watch::channel(0).0),
+        }
+    }
+}
+
+impl Outstanding {
+    fn add(&self, count: usize) {
+        self.inner.send_modify(|v| *v += count);
+    }
+
+    fn done(&self, count: usize) {
+        self.inner.send_modify(|v| *v = v.saturating_sub(count));
+    }
+
+    fn count(&self) -> usize {
+        *self.inner.borrow()
+    }
+
+    /// clear forgets the messages of the aborted workers, so that their results are never notified.
+    fn clear(&self) {
+        self.inner.send_replace(0);
+    }
+
+    /// wait_empty waits until the results of all the messages are notified.
+    async fn wait_empty(&self) {
+        let mut receiver = self.inner.subscribe();
+        let _ = receiver.wait_for(|v| *v == 0).await;
+    }
+}
+
+fn closed_error() -> Status {
+    Status::cancelled("publisher closed")
+}
+
+fn paused_error(ordering_key: &str) -> Status {
+    Status::failed_precondition(format!(
+        "ordering key is paused by the previous publish error, call resume_publish to continue: ordering_key={ordering_key}"
+    ))
+}
+
+/// check_schema_encoding checks that the data of the message can be the message of the encoding.
+fn check_schema_encoding(encoding: Option<Encoding>, message: &PubsubMessage) -> Result<(), Box<Status>> {
+    match encoding {
+        Some(Encoding::Json) => match serde_json::from_slice::<serde::de::IgnoredAny>(&message.data) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(Box::new(Status::invalid_argument(format!(
+                "message data must be JSON for the topic with the JSON schema encoding: {e}"
+            )))),
+        },
+        _ => Ok(()),
+    }
+}
+
 /// Publisher is a scheduler which is designed for Pub/Sub's Publish flow.
 /// Each item is added with a given key.
 /// Items added to the empty string key are handled in random order.
@@ -76,6 +226,13 @@ pub struct Publisher {
     tasks: Arc<Mutex<Tasks>>,
     fqtn: String,
     pubc: PublisherClient,
+    enable_message_ordering: bool,
+    schema_encoding: Option<Encoding>,
+    paused_keys: PausedKeys,
+    bundle_byte_size: usize,
+    outstanding: Outstanding,
+    flush_requests: Arc<watch::Sender<u64>>,
+    shutdown_timeout: Duration,
 }
 
 impl Publisher {
@@ -99,13 +256,99 @@ impl Publisher {
             ordering_senders.push(sender);
         }
 
+        let enable_message_ordering = config.enable_message_ordering;
+        let schema_encoding = config.schema_encoding;
+        let bundle_byte_size = config.bundle_byte_size.min(PUBSUB_MESSAGE_LIMIT);
+        let paused_keys = PausedKeys::default();
+        let outstanding = Outstanding::default();
+        let shutdown_timeout = config.shutdown_timeout;
+        let (flush_requests, _) = watch::channel(0);
+        let tasks = Tasks::new(
+            fqtn.clone(),
+            pubc.clone(),
+            receivers,
+            config,
+            Shared {
+                paused_keys: paused_keys.clone(),
+                outstanding: outstanding.clone(),
+                flush_requests: flush_requests.subscribe(),
+            },
+        );
         Self {
             sender,
             ordering_senders: Arc::new(ordering_senders),
-            tasks: Arc::new(Mutex::new(Tasks::new(fqtn.clone(), pubc.clone(), receivers, config))),
+            tasks: Arc::new(Mutex::new(tasks)),
             fqtn,
             pubc,
+            enable_message_ordering,
+            schema_encoding,
+            paused_keys,
+            bundle_byte_size,
+            outstanding,
+            flush_requests: Arc::new(flush_requests),
+            shutdown_timeout,
+        }
+    }
+
+    /// reserve validates the message and creates the message to queue and its Awaiter.
+    /// The Awaiter already failed is returned as the error if the message can't be published.
+    fn reserve(&self, message: PubsubMessage) -> Result<(ReservedMessage, Awaiter), Awaiter> {
+        if self.sender.is_closed() {
+            return Err(Awaiter::rejected(closed_error()));
+        }
+        validate_message(&message).map_err(Awaiter::rejected)?;
+        let size = message_size(&message);
+        if request_overhead(&self.fqtn) + size > self.bundle_byte_size {
+            return Err(Awaiter::rejected(Status::invalid_argument(format!(
+                "message size {size} exceeds the max bundle byte size {}",
+                self.bundle_byte_size
+            ))));
+        }
+        self.check_ordering_key(&message).map_err(|e| Awaiter::rejected(*e))?;
+        check_schema_encoding(self.schema_encoding, &message).map_err(|e| Awaiter::rejected(*e))?;
+        let (producer, consumer) = oneshot::channel();
+        Ok((
+            ReservedMessage {
+                producer,
+                message,
+                size,
+            },
+            Awaiter::new(consumer),
+        ))
+    }
+
+    fn sender_for(&self, ordering_key: &str) -> &async_channel::Sender<Reserved> {
+        if ordering_key.is_empty() {
+            &self.sender
+        } else {
+            let key = ordering_key.to_usize();
+            &self.ordering_senders[key % self.ordering_senders.len()]
+        }
+    }
+
+    /// check_ordering_key returns the error if the message can't be published with its ordering key.
+    fn check_ordering_key(&self, message: &PubsubMessage) -> Result<(), Box<Status>> {
+        if message.ordering_key.is_empty() {
+            return Ok(());
+        }
+        if !self.enable_message_ordering {
+            return Err(Box::new(Status::failed_precondition(
+                "ordering_key is set but enable_message_ordering of the PublisherConfig is false",
+            )));
         }
+        if self.paused_keys.is_paused(&message.ordering_key) {
+            return Err(Box::new(paused_error(&message.ordering_key)));
+        }
+        Ok(())
+    }
+
+    /// resume_publish resumes accepting messages for the ordering key paused by a publish error.
+    /// It is a no-op if the ordering key is not paused.
+    ///
+    /// Wait for all the Awaiters of the messages with the key before resuming, otherwise the messages
+    /// queued before the error might be published after the newer ones.
+    pub fn resume_publish(&self, ordering_key: &str) {
+        self.paused_keys.resume(ordering_key);
     }
 
     /// publish publishes msg to the topic synchronously
@@ -131,38 +374,39 @@ impl Publisher {
     ///
     /// publish returns a non-nil Awaiter which will be ready when the
     /// message has been sent (or has failed to be sent) to the server.
+    ///
+    /// The messages with the same ordering key are published in the order of the calls.
+    /// The Awaiter fails immediately if the ordering key is paused or message ordering is not enabled.
+    /// It fails with `InvalidArgument` too if the message exceeds the limits checked by `message::validate_message`.
     pub async fn publish(&self, message: PubsubMessage) -> Awaiter {
-        let (producer, consumer) = oneshot::channel();
-        if message.ordering_key.is_empty() {
-            let _ = self
-                .sender
-                .send(Reserved::Single(ReservedMessage { producer, message }))
-                .await;
-        } else {
-            let key = message.ordering_key.as_str().to_usize();
-            let index = key % self.ordering_senders.len();
-            let _ = self.ordering_senders[index]
-                .send(Reserved::Single(ReservedMessage { producer, message }))
-                .await;
+        let (reserved, awaiter) = match self.reserve(message) {
+            Ok(v) => v,
+            Err(awaiter) => return awaiter,
+        };
+        self.outstanding.add(1);
+        let sender = self.sender_for(&reserved.message.ordering_key);
+        if let Err(e) = sender.send(Reserved::Single(reserved)).await {
+            self.outstanding.done(1);
+            e.into_inner().reject(closed_error());
         }
-        Awaiter::new(consumer)
+        awaiter
     }
 
     /// Publish a message to the topic asynchronously, from synchronous code.
     /// This method blocks until the message is sent to the publisher channel.
     /// The actual publishing to the server is done asynchronously.
     pub fn publish_blocking(&self, message: PubsubMessage) -> Awaiter {
-        let (producer, consumer) = oneshot::channel();
-        if message.ordering_key.is_empty() {
-            let _ = self
-                .sender
-                .send_blocking(Reserved::Single(ReservedMessage { producer, message }));
-        } else {
-            let key = message.ordering_key.as_str().to_usize();
-            let index = key % self.ordering_senders.len();
-            let _ = self.ordering_senders[index].send_blocking(Reserved::Single(ReservedMessage { producer, message }));
+        let (reserved, awaiter) = match self.reserve(message) {
+            Ok(v) => v,
+            Err(awaiter) => return awaiter,
+        };
+        self.outstanding.add(1);
+        let sender = self.sender_for(&reserved.message.ordering_key);
+        if let Err(e) = sender.send_blocking(Reserved::Single(reserved)) {
+            self.outstanding.done(1);
+            e.into_inner().reject(closed_error());
         }
-        Awaiter::new(consumer)
+        awaiter
     }
 
     /// publish_bulk publishes msg to the topic asynchronously. Messages are batched and
@@ -174,126 +418,249 @@ impl Publisher {
         let mut awaiters = Vec::with_capacity(messages.len());
         let mut split_by_key = HashMap::<String, Vec<ReservedMessage>>::with_capacity(messages.len());
         for message in messages {
-            let (producer, consumer) = oneshot::channel();
-            awaiters.push(Awaiter::new(consumer));
-            split_by_key
-                .entry(message.ordering_key.clone())
-                .or_default()
-                .push(ReservedMessage { producer, message });
+            match self.reserve(message) {
+                Ok((reserved, awaiter)) => {
+                    awaiters.push(awaiter);
+                    split_by_key
+                        .entry(reserved.message.ordering_key.clone())
+                        .or_default()
+                        .push(reserved);
+                }
+                Err(awaiter) => awaiters.push(awaiter),
+            }
         }
 
-        for e in split_by_key {
-            if e.0.is_empty() {
-                let _ = self.sender.send(Reserved::Multi(e.1)).await;
-            } else {
-                let key = e.0.as_str().to_usize();
-                let index = key % self.ordering_senders.len();
-                let _ = self.ordering_senders[index].send(Reserved::Multi(e.1)).await;
+        for (key, reserved) in split_by_key {
+            let count = reserved.len();
+            self.outstanding.add(count);
+            if let Err(e) = self.sender_for(&key).send(Reserved::Multi(reserved)).await {
+                self.outstanding.done(count);
+                e.into_inner().reject(closed_error());
             }
         }
         awaiters
     }
 
+    /// flush sends all the pending messages immediately and waits until the results of them are known.
+    /// The messages published while flushing are also waited for.
+    ///
+    /// Use this before dropping the publisher not to lose the messages, or use `shutdown` which also
+    /// stops the workers.
+    pub async fn flush(&self) {
+        self.flush_requests.send_modify(|v| *v += 1);
+        self.outstanding.wait_empty().await;
+    }
+
+    /// shutdown stops accepting new messages, publishes all the pending messages and stops the workers.
+    /// The messages published after it fail with `Cancelled` "publisher closed".
+    ///
+    /// It waits for the pending messages including their retries up to `PublisherConfig::shutdown_timeout`.
+    /// The workers are aborted after the timeout and the messages not published yet fail with `Cancelled`.
     pub async fn shutdown(&mut self) {
         self.sender.close();
         for s in self.ordering_senders.iter() {
             s.close();
         }
-        self.tasks.lock().await.done().await;
+        let mut tasks = self.tasks.lock().await;
+        if timeout(self.shutdown_timeout, tasks.done()).await.is_err() {
+            tracing::warn!(
+                "shutdown timed out, abort the publish of {} messages : {}",
+                self.outstanding.count(),
+                self.fqtn
+            );
+            tasks.abort();
+            self.outstanding.clear();
+        }
     }
 }
 
+/// Shared is the state shared by the publisher and its workers.
+struct Shared {
+    paused_keys: PausedKeys,
+    outstanding: Outstanding,
+    flush_requests: watch::Receiver<u64>,
+}
+
 #[derive(Debug)]
 struct Tasks {
     inner: Option<Vec<JoinHandle<()>>>,
+    topic: String,
+    outstanding: Outstanding,
 }
 
 impl Tasks {
-    pub fn new(
+    fn new(
         topic: String,
         pubc: PublisherClient,
         receivers: Vec<async_channel::Receiver<Reserved>>,
         config: PublisherConfig,
+        shared: Shared,
     ) -> Self {
+        let bundle_byte_size = config.bundle_byte_size.min(PUBSUB_MESSAGE_LIMIT);
         let tasks = receivers
             .into_iter()
             .map(|receiver| {
-                Self::run_task(
+                let worker = Worker {
                     receiver,
-                    pubc.clone(),
-                    topic.clone(),
-                    config.retry_setting.clone(),
-                    config.flush_interval,
-                    config.bundle_size,
-                )
+                    flush_requests: shared.flush_requests.clone(),
+                    client: pubc.clone(),
+                    request_overhead: request_overhead(&topic),
+                    topic: topic.clone(),
+                    retry: config.retry_setting.clone(),
+                    flush_interval: config.flush_interval,
+                    bundle_size: config.bundle_size,
+                    bundle_byte_size,
+                    paused_keys: shared.paused_keys.clone(),
+                    outstanding: shared.outstanding.clone(),
+                };
+                tokio::spawn(worker.run())
             })
             .collect();
 
-        Self { inner: Some(tasks) }
+        Self {
+            inner: Some(tasks),
+            topic,
+            outstanding: shared.outstanding,
+        }
     }
 
-    fn run_task(
-        receiver: Receiver<Reserved>,
-        mut client: PublisherClient,
-        topic: String,
-        retry: Option<RetrySetting>,
-        flush_interval: Duration,
-        bundle_size: usize,
-    ) -> JoinHandle<()> {
-        tokio::spawn(async move {
-            //TODO enable manage task by ordering_key
-            let mut bundle = MessageBundle::new();
-            while !receiver.is_closed() {
-                let result = match timeout(flush_interval, &mut receiver.recv()).await {
-                    Ok(result) => result,
-                    //timed out
-                    Err(_e) => {
-                        if !bundle.is_empty() {
-                            tracing::trace!("elapsed: flush buffer : {}", topic);
-                            for value in bundle.key_by() {
-                                Self::flush(&mut client, topic.as_str(), value, retry.clone()).await;
-                            }
-                            bundle = MessageBundle::new();
-                        }
-                        continue;
-                    }
-                };
-                match result {
-                    Ok(reserved) => {
-                        match reserved {
-                            Reserved::Single(message) => bundle.push(message),
-                            Reserved::Multi(messages) => bundle.extend(messages),
-                        }
-                        if bundle.len() >= bundle_size {
-                            tracing::trace!("bundle size max: {}", topic);
-                            for value in bundle.key_by() {
-                                Self::flush(&mut client, topic.as_str(), value, retry.clone()).await;
-                            }
-                            bundle = MessageBundle::new();
-                        }
-                    }
-                    //closed
-                    Err(_e) => break,
-                };
+    /// done waits for all the workers finish. It is cancel safe, so the workers can be aborted after it.
+    pub async fn done(&mut self) {
+        if let Some(tasks) = self.inner.as_mut() {
+            for task in tasks.iter_mut() {
+                let _ = task.await;
+            }
+        }
+        self.inner = None;
+    }
+
+    /// abort stops the workers without waiting for the pending messages.
+    fn abort(&mut self) {
+        if let Some(tasks) = self.inner.take() {
+            for task in tasks {
+                task.abort();
             }
+        }
+    }
+}
+
+impl Drop for Tasks {
+    fn drop(&mut self) {
+        // The workers keep publishing the pending messages after the drop, but they are lost if the runtime stops.
+        if self.inner.is_some() && self.outstanding.count() > 0 {
+            tracing::warn!(
+                "publisher dropped without shutdown, {} messages are not published yet : {}",
+                self.outstanding.count(),
+                self.topic
+            );
+        }
+    }
+}
 
-            tracing::trace!("stop publisher : {}", topic);
-            if !bundle.is_empty() {
-                tracing::trace!("flush rest buffer : {}", topic);
-                for value in bundle.key_by() {
-                    Self::flush(&mut client, topic.as_str(), value, retry.clone()).await;
+enum Event {
+    Received(Reserved),
+    Closed,
+    Elapsed,
+    FlushRequested,
+}
+
+/// Worker bundles the messages from a channel and publishes them.
+struct Worker {
+    receiver: Receiver<Reserved>,
+    flush_requests: watch::Receiver<u64>,
+    client: PublisherClient,
+    topic: String,
+    request_overhead: usize,
+    retry: Option<RetrySetting>,
+    flush_interval: Duration,
+    bundle_size: usize,
+    bundle_byte_size: usize,
+    paused_keys: PausedKeys,
+    outstanding: Outstanding,
+}
+
+impl Worker {
+    async fn run(mut self) {
+        let mut bundle = MessageBundle::new();
+        loop {
+            let deadline = bundle.created_at.map(|v| v + self.flush_interval);
+            let event = select! {
+                result = self.receiver.recv() => match result {
+                    Ok(reserved) => Event::Received(reserved),
+                    Err(_e) => Event::Closed,
+                },
+                _ = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => Event::Elapsed,
+                Ok(_) = self.flush_requests.changed() => Event::FlushRequested,
+            };
+            match event {
+                Event::Received(reserved) => self.add(&mut bundle, reserved).await,
+                Event::Closed => break,
+                Event::Elapsed => {
+                    tracing::trace!("elapsed: flush buffer : {}", self.topic);
+                    self.flush(&mut bundle).await;
+                }
+                Event::FlushRequested => {
+                    tracing::trace!("flush requested : {}", self.topic);
+                    while let Ok(reserved) = self.receiver.try_recv() {
+                        self.add(&mut bundle, reserved).await;
+                    }
+                    self.flush(&mut bundle).await;
                 }
             }
-        })
+        }
+
+        tracing::trace!("stop publisher : {}", self.topic);
+        if !bundle.is_empty() {
+            tracing::trace!("flush rest buffer : {}", self.topic);
+            self.flush(&mut bundle).await;
+        }
     }
 
-    /// flush publishes the messages in buffer.
-    async fn flush(
-        client: &mut PublisherClient,
-        topic: &str,
-        bundle: Vec<ReservedMessage>,
-        retry_setting: Option<RetrySetting>,
-    ) {
+    /// add adds the messages to the bundle and flushes it when it reaches the bundle size or byte size.
+    async fn add(&mut self, bundle: &mut MessageBundle, reserved: Reserved) {
+        let messages = match reserved {
+            Reserved::Single(message) => vec![message],
+            Reserved::Multi(messages) => messages,
+        };
+        for message in messages {
+            if !bundle.is_empty() && self.request_overhead + bundle.bytes + message.size > self.bundle_byte_size {
+                tracing::trace!("bundle byte size max: {}", self.topic);
+                self.flush(bundle).await;
+            }
+            bundle.push(message);
+            if bundle.len() >= self.bundle_size {
+                tracing::trace!("bundle size max: {}", self.topic);
+                self.flush(bundle).await;
+            }
+        }
+    }
+
+    async fn flush(&mut self, bundle: &mut MessageBundle) {
+        let bundle = std::mem::replace(bundle, MessageBundle::new());
+        for value in bundle.key_by() {
+            let count = value.len();
+            self.publish(value).await;
+            self.outstanding.done(count);
+        }
+    }
+
+    /// publish publishes the messages in buffer.
+    /// All the messages in the bundle have the same ordering key.
+    /// When the publish fails, the ordering key is paused and the queued messages for it are rejected.
+    async fn publish(&mut self, bundle: Vec<ReservedMessage>) {
+        let ordering_key = match bundle.first() {
+            Some(v) => v.message.ordering_key.clone(),
+            None => return,
+        };
+        if !ordering_key.is_empty() && self.paused_keys.is_paused(&ordering_key) {
+            for r in bundle.into_iter() {
+                if r.producer.send(Err(paused_error(&ordering_key))).is_err() {
+                    tracing::error!("failed to notify : ordering_key={ordering_key}");
+                }
+            }
+            return;
+        }
+
         let mut data = Vec::<PubsubMessage>::with_capacity(bundle.len());
         let mut callback = Vec::<oneshot::Sender<Result<String, Status>>>::with_capacity(bundle.len());
         bundle.into_iter().for_each(|r| {
@@ -301,11 +668,12 @@ impl Tasks {
             callback.push(r.producer);
         });
         let req = PublishRequest {
-            topic: topic.to_string(),
+            topic: self.topic.to_string(),
             messages: data,
         };
-        let result = client
-            .publish(req, retry_setting)
+        let result = self
+            .client
+            .publish(req, self.retry.clone())
             .await
             .map(|v| v.into_inner().message_ids);
 
@@ -320,6 +688,12 @@ impl Tasks {
                 }
             }
             Err(status) => {
+                if !ordering_key.is_empty() {
+                    tracing::warn!(
+                        "pause ordering key by the publish error : ordering_key={ordering_key}, status={status}"
+                    );
+                    self.paused_keys.pause(&ordering_key);
+                }
                 for p in callback.into_iter() {
                     let code = status.code();
                     let status = Status::new(code, (*status.message()).to_string());
@@ -330,24 +704,31 @@ impl Tasks {
             }
         };
     }
-
-    /// done waits for all the workers finish.
-    pub async fn done(&mut self) {
-        if let Some(tasks) = self.inner.take() {
-            for task in tasks {
-                let _ = task.await;
-            }
-        }
-    }
 }
 
 struct MessageBundle {
     inner: Vec<ReservedMessage>,
+    /// The total encoded size of the messages.
+    bytes: usize,
+    /// The time the first message was added.
+    created_at: Option<Instant>,
 }
 
 impl MessageBundle {
     fn new() -> Self {
-        Self { inner: vec![] }
+        Self {
+            inner: vec![],
+            bytes: 0,
+            created_at: None,
+        }
+    }
+
+    fn push(&mut self, message: ReservedMessage) {
+        if self.created_at.is_none() {
+            self.created_at = Some(Instant::now());
+        }
+        self.bytes += message.size;
+        self.inner.push(message);
     }
 
     fn key_by(self) -> Vec<Vec<ReservedMessage>> {
@@ -379,26 +760,26 @@ impl Deref for MessageBundle {
     }
 }
 
-impl DerefMut for MessageBundle {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.inner
-    }
-}
-
 #[cfg(test)]
 mod tests {
-    use crate::publisher::{MessageBundle, ReservedMessage};
-    use google_cloud_googleapis::pubsub::v1::PubsubMessage;
+    use crate::publisher::{
+        check_schema_encoding, message_size, request_overhead, Awaiter, MessageBundle, PausedKeys, ReservedMessage,
+    };
+    use google_cloud_gax::grpc::{Code, Status};
+    use google_cloud_googleapis::pubsub::v1::{Encoding, PublishRequest, PubsubMessage};
+    use prost::Message;
     use tokio::sync::oneshot;
 
     fn msg(key: &str) -> ReservedMessage {
         let (sender, _) = oneshot::channel();
+        let message = PubsubMessage {
+            ordering_key: key.to_string(),
+            ..Default::default()
+        };
         ReservedMessage {
             producer: sender,
-            message: PubsubMessage {
-                ordering_key: key.to_string(),
-                ..Default::default()
-            },
+            size: message_size(&message),
+            message,
         }
     }
 
@@ -419,4 +800,79 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_paused_keys() {
+        let keys = PausedKeys::default();
+        let cloned = keys.clone();
+        keys.pause("a");
+        assert!(cloned.is_paused("a"));
+        assert!(!cloned.is_paused("b"));
+        cloned.resume("a");
+        assert!(!keys.is_paused("a"));
+        // resume is a no-op for the key not paused.
+        keys.resume("b");
+        assert!(!keys.is_paused("b"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_awaiter() {
+        let err = Awaiter::rejected(Status::failed_precondition("paused"))
+            .get()
+            .await
+            .unwrap_err();
+        assert_eq!(Code::FailedPrecondition, err.code());
+        assert_eq!("paused", err.message());
+    }
+
+    #[test]
+    fn test_check_schema_encoding() {
+        let message = |data: &'static [u8]| PubsubMessage {
+            data: data.into(),
+            ..Default::default()
+        };
+        let json = message(br#"{"name":"a"}"#);
+        let binary = message(&[0xff, 0xfe, 0x00]);
+        let empty = message(&[]);
+        let malformed = message(br#"{"name":"a""#);
+        let text = message(b"not json");
+
+        assert!(check_schema_encoding(None, &binary).is_ok());
+        assert!(check_schema_encoding(Some(Encoding::Binary), &binary).is_ok());
+        assert!(check_schema_encoding(Some(Encoding::Binary), &empty).is_ok());
+        assert!(check_schema_encoding(Some(Encoding::Json), &json).is_ok());
+        for invalid in [&binary, &empty, &malformed, &text] {
+            let err = check_schema_encoding(Some(Encoding::Json), invalid).unwrap_err();
+            assert_eq!(Code::InvalidArgument, err.code());
+        }
+    }
+
+    #[test]
+    fn test_message_size() {
+        let topic = "projects/local-project/topics/test-topic";
+        let messages: Vec<PubsubMessage> = [0, 1, 127, 128, 20000]
+            .into_iter()
+            .map(|n| PubsubMessage {
+                data: "a".repeat(n).into(),
+                ordering_key: "key".to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let expected = request_overhead(topic) + messages.iter().map(message_size).sum::<usize>();
+        let request = PublishRequest {
+            topic: topic.to_string(),
+            messages,
+        };
+        assert_eq!(request.encoded_len(), expected);
+    }
+
+    #[test]
+    fn test_message_bundle_bytes() {
+        let mut bundle = MessageBundle::new();
+        assert!(bundle.created_at.is_none());
+        bundle.push(msg("a"));
+        bundle.push(msg("bb"));
+        assert!(bundle.created_at.is_some());
+        assert_eq!(message_size(&msg("a").message) + message_size(&msg("bb").message), bundle.bytes);
+    }
 }
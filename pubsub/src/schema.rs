@@ -0,0 +1,252 @@
+use std::time::SystemTime;
+
+use google_cloud_gax::grpc::{Code, Status};
+use google_cloud_gax::retry::RetrySetting;
+use google_cloud_googleapis::pubsub::v1::validate_message_request::SchemaSpec;
+use google_cloud_googleapis::pubsub::v1::{
+    CommitSchemaRequest, CreateSchemaRequest, DeleteSchemaRequest, DeleteSchemaRevisionRequest, GetSchemaRequest,
+    ListSchemaRevisionsRequest, RollbackSchemaRequest, Schema as InternalSchema, ValidateMessageRequest,
+};
+
+use crate::apiv1::schema_client::SchemaClient;
+
+pub use google_cloud_googleapis::pubsub::v1::schema::Type as SchemaType;
+pub use google_cloud_googleapis::pubsub::v1::{Encoding, SchemaView};
+
+/// SchemaConfig is the configuration of a schema or one of its revisions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaConfig {
+    /// The fully qualified name of the schema. It is set by the server.
+    pub name: String,
+    /// The type of the schema definition.
+    pub schema_type: SchemaType,
+    /// The definition of the schema. It must be a valid Protocol Buffer or Avro schema according to `schema_type`.
+    /// It is empty when the schema is fetched with `SchemaView::Basic`.
+    pub definition: String,
+    /// The revision id of the schema. It is set by the server.
+    pub revision_id: String,
+    /// The time the revision was created. It is set by the server.
+    pub revision_create_time: Option<SystemTime>,
+}
+
+impl Default for SchemaConfig {
+    fn default() -> Self {
+        Self {
+            name: "".to_string(),
+            schema_type: SchemaType::Unspecified,
+            definition: "".to_string(),
+            revision_id: "".to_string(),
+            revision_create_time: None,
+        }
+    }
+}
+
+impl From<InternalSchema> for SchemaConfig {
+    fn from(f: InternalSchema) -> Self {
+        Self {
+            schema_type: f.r#type(),
+            name: f.name,
+            definition: f.definition,
+            revision_id: f.revision_id,
+            revision_create_time: f.revision_create_time.and_then(|v| v.try_into().ok()),
+        }
+    }
+}
+
+impl SchemaConfig {
+    pub(crate) fn to_internal(&self, name: &str) -> InternalSchema {
+        InternalSchema {
+            name: name.to_string(),
+            r#type: self.schema_type.into(),
+            definition: self.definition.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Schema is a reference to a Pub/Sub schema.
+///
+/// The methods of Schema are safe for use by multiple tasks.
+#[derive(Clone, Debug)]
+pub struct Schema {
+    fqsn: String,
+    schc: SchemaClient,
+}
+
+impl Schema {
+    pub(crate) fn new(fqsn: String, schc: SchemaClient) -> Self {
+        Self { fqsn, schc }
+    }
+
+    /// id returns the unique identifier of the schema within its project.
+    pub fn id(&self) -> String {
+        self.fqsn
+            .rfind('/')
+            .map_or("".to_string(), |i| self.fqsn[(i + 1)..].to_string())
+    }
+
+    /// fully_qualified_name returns the globally unique printable name of the schema.
+    pub fn fully_qualified_name(&self) -> &str {
+        self.fqsn.as_str()
+    }
+
+    /// parent returns the fully qualified name of the project of the schema.
+    fn parent(&self) -> String {
+        self.fqsn
+            .rfind("/schemas/")
+            .map_or("".to_string(), |i| self.fqsn[..i].to_string())
+    }
+
+    /// create creates the schema. Only `schema_type` and `definition` of the config are used.
+    /// It returns the first revision of the schema.
+    pub async fn create(&self, cfg: SchemaConfig, retry: Option<RetrySetting>) -> Result<SchemaConfig, Status> {
+        let req = CreateSchemaRequest {
+            parent: self.parent(),
+            schema: Some(cfg.to_internal("")),
+            schema_id: self.id(),
+        };
+        self.schc.create_schema(req, retry).await.map(|v| v.into_inner().into())
+    }
+
+    /// config fetches the latest revision of the schema.
+    /// The definition is included only with `SchemaView::Full`.
+    pub async fn config(&self, view: SchemaView, retry: Option<RetrySetting>) -> Result<SchemaConfig, Status> {
+        let req = GetSchemaRequest {
+            name: self.fqsn.to_string(),
+            view: view.into(),
+        };
+        self.schc.get_schema(req, retry).await.map(|v| v.into_inner().into())
+    }
+
+    /// exists reports whether the schema exists on the server.
+    pub async fn exists(&self, retry: Option<RetrySetting>) -> Result<bool, Status> {
+        match self.config(SchemaView::Basic, retry).await {
+            Ok(_) => Ok(true),
+            Err(e) => {
+                if e.code() == Code::NotFound {
+                    Ok(false)
+                } else {
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    /// delete deletes the schema and all of its revisions.
+    /// The topics using the schema are not deleted, but they can't publish messages any longer.
+    pub async fn delete(&self, retry: Option<RetrySetting>) -> Result<(), Status> {
+        let req = DeleteSchemaRequest {
+            name: self.fqsn.to_string(),
+        };
+        self.schc.delete_schema(req, retry).await.map(|v| v.into_inner())
+    }
+
+    /// revisions lists all the revisions of the schema, the newest first.
+    pub async fn revisions(&self, view: SchemaView, retry: Option<RetrySetting>) -> Result<Vec<SchemaConfig>, Status> {
+        let req = ListSchemaRevisionsRequest {
+            name: self.fqsn.to_string(),
+            view: view.into(),
+            page_size: 0,
+            page_token: "".to_string(),
+        };
+        self.schc
+            .list_schema_revisions(req, retry)
+            .await
+            .map(|v| v.into_iter().map(SchemaConfig::from).collect())
+    }
+
+    /// commit commits a new revision of the schema. Only `schema_type` and `definition` of the config are used.
+    /// It returns the new revision.
+    pub async fn commit(&self, cfg: SchemaConfig, retry: Option<RetrySetting>) -> Result<SchemaConfig, Status> {
+        let req = CommitSchemaRequest {
+            name: self.fqsn.to_string(),
+            schema: Some(cfg.to_internal(&self.fqsn)),
+        };
+        self.schc.commit_schema(req, retry).await.map(|v| v.into_inner().into())
+    }
+
+    /// rollback creates a new revision of the schema which is a copy of the revision of revision_id.
+    /// It returns the new revision.
+    pub async fn rollback(&self, revision_id: &str, retry: Option<RetrySetting>) -> Result<SchemaConfig, Status> {
+        let req = RollbackSchemaRequest {
+            name: self.fqsn.to_string(),
+            revision_id: revision_id.to_string(),
+        };
+        self.schc
+            .rollback_schema(req, retry)
+            .await
+            .map(|v| v.into_inner().into())
+    }
+
+    /// delete_revision deletes the revision of revision_id. The latest revision can't be deleted if it is
+    /// the only revision of the schema. It returns the deleted revision.
+    pub async fn delete_revision(
+        &self,
+        revision_id: &str,
+        retry: Option<RetrySetting>,
+    ) -> Result<SchemaConfig, Status> {
+        // revision_id is deprecated in favor of the `name@revision_id` form but still required by the struct.
+        #[allow(deprecated)]
+        let req = DeleteSchemaRevisionRequest {
+            name: format!("{}@{revision_id}", self.fqsn),
+            revision_id: "".to_string(),
+        };
+        self.schc
+            .delete_schema_revision(req, retry)
+            .await
+            .map(|v| v.into_inner().into())
+    }
+
+    /// validate_message validates that the data is a valid message of the latest revision of the schema
+    /// in the encoding. The error is `InvalidArgument` if the data doesn't match the schema.
+    pub async fn validate_message(
+        &self,
+        data: &[u8],
+        encoding: Encoding,
+        retry: Option<RetrySetting>,
+    ) -> Result<(), Status> {
+        #[cfg(not(feature = "bytes"))]
+        let message = data.to_vec();
+        #[cfg(feature = "bytes")]
+        let message = prost::bytes::Bytes::copy_from_slice(data);
+        let req = ValidateMessageRequest {
+            parent: self.parent(),
+            message,
+            encoding: encoding.into(),
+            schema_spec: Some(SchemaSpec::Name(self.fqsn.to_string())),
+        };
+        self.schc.validate_message(req, retry).await.map(|_v| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use google_cloud_googleapis::pubsub::v1::Schema as InternalSchema;
+
+    use crate::schema::{SchemaConfig, SchemaType};
+
+    #[test]
+    fn test_schema_config_from_internal() {
+        let created_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let internal = InternalSchema {
+            name: "projects/p/schemas/s".to_string(),
+            r#type: SchemaType::Avro.into(),
+            definition: "{}".to_string(),
+            revision_id: "rev1".to_string(),
+            revision_create_time: Some(created_at.into()),
+        };
+        let config = SchemaConfig::from(internal);
+        assert_eq!(config.name, "projects/p/schemas/s");
+        assert_eq!(config.schema_type, SchemaType::Avro);
+        assert_eq!(config.definition, "{}");
+        assert_eq!(config.revision_id, "rev1");
+        assert_eq!(config.revision_create_time, Some(created_at));
+
+        let internal = config.to_internal("projects/p/schemas/s");
+        assert_eq!(internal.r#type(), SchemaType::Avro);
+        assert!(internal.revision_id.is_empty());
+        assert!(internal.revision_create_time.is_none());
+    }
+}
@@ -1,20 +1,308 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use tokio::select;
+use tokio::sync::oneshot;
 use tokio::task::JoinHandle;
-use tokio::time::sleep;
+use tokio::time::{sleep, timeout, Instant};
 use tokio_util::sync::CancellationToken;
 
 use google_cloud_gax::grpc::{Code, Status, Streaming};
-use google_cloud_gax::retry::RetrySetting;
+use google_cloud_gax::retry::{ExponentialBackoff, Retry, RetrySetting};
 use google_cloud_googleapis::pubsub::v1::{
     AcknowledgeRequest, ModifyAckDeadlineRequest, PubsubMessage, ReceivedMessage as InternalReceivedMessage,
-    StreamingPullResponse,
+    StreamingPullRequest, StreamingPullResponse,
 };
 
 use crate::apiv1::default_retry_setting;
 use crate::apiv1::subscriber_client::{create_empty_streaming_pull_request, SubscriberClient};
 
+/// The max number of the ack ids in an acknowledge or modify ack deadline request.
+pub(crate) const MAX_ACK_IDS_PER_REQUEST: usize = 2500;
+/// The max ack deadline of the modify ack deadline request.
+const MAX_ACK_DEADLINE: Duration = Duration::from_secs(600);
+/// The max delay of reconnecting the stream when the retry setting has no max delay.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// AckError is the failure of `ack`, `nack` and `modify_ack_deadline` of the `ReceivedMessage`.
+///
+/// The acknowledgements on the subscription with exactly-once delivery are confirmed by the server,
+/// so `InvalidAckId`, `UnorderedAckId` and `TransientFailure` are returned only for such subscriptions.
+#[derive(thiserror::Error, Debug)]
+pub enum AckError {
+    /// The ack id is malformed or its ack deadline has expired. The message will be redelivered.
+    /// This is a permanent failure and retrying it never succeeds.
+    #[error("invalid ack id or the ack deadline has expired: ack_id={0}")]
+    InvalidAckId(String),
+    /// The ack id is out of order on the subscription with message ordering. This is a permanent failure.
+    #[error("unordered ack id: ack_id={0}")]
+    UnorderedAckId(String),
+    /// The acknowledgement failed with temporary issues and all the retries are exhausted.
+    #[error("acknowledgement failed temporarily and the retries are exhausted: ack_id={0}")]
+    TransientFailure(String),
+    #[error(transparent)]
+    Status(#[from] Status),
+}
+
+impl AckError {
+    /// is_permanent returns true if the acknowledgement never succeeds even if it is retried.
+    pub fn is_permanent(&self) -> bool {
+        matches!(self, Self::InvalidAckId(_) | Self::UnorderedAckId(_))
+    }
+}
+
+impl From<AckError> for Status {
+    fn from(e: AckError) -> Self {
+        match e {
+            AckError::Status(status) => status,
+            AckError::InvalidAckId(_) | AckError::UnorderedAckId(_) => Status::failed_precondition(e.to_string()),
+            AckError::TransientFailure(_) => Status::unavailable(e.to_string()),
+        }
+    }
+}
+
+/// Confirmation is the result of an ack id confirmed by the streaming pull response.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Confirmation {
+    Success,
+    Invalid,
+    Unordered,
+    TemporaryFailed,
+}
+
+type PendingConfirmations = Arc<Mutex<HashMap<String, oneshot::Sender<Confirmation>>>>;
+
+/// StreamAcker sends the acknowledgements through the streaming pull and waits for their confirmations.
+/// The server confirms the acknowledgements only on the subscription with exactly-once delivery.
+#[derive(Clone, Debug)]
+pub(crate) struct StreamAcker {
+    requests: async_channel::Sender<StreamingPullRequest>,
+    pending_acks: PendingConfirmations,
+    pending_modacks: PendingConfirmations,
+    retry: RetrySetting,
+    confirmation_timeout: Duration,
+}
+
+impl StreamAcker {
+    fn new(
+        requests: async_channel::Sender<StreamingPullRequest>,
+        retry: RetrySetting,
+        confirmation_timeout: Duration,
+    ) -> Self {
+        Self {
+            requests,
+            pending_acks: Default::default(),
+            pending_modacks: Default::default(),
+            retry,
+            confirmation_timeout,
+        }
+    }
+
+    /// ack returns None if the stream is already closed.
+    async fn ack(&self, ack_id: &str) -> Option<Result<(), AckError>> {
+        let mut request = create_empty_streaming_pull_request();
+        request.ack_ids.push(ack_id.to_string());
+        self.send_with_retry(ack_id, request, &self.pending_acks).await
+    }
+
+    /// modify_ack_deadline returns None if the stream is already closed.
+    async fn modify_ack_deadline(&self, ack_id: &str, ack_deadline_seconds: i32) -> Option<Result<(), AckError>> {
+        let mut request = create_empty_streaming_pull_request();
+        request.modify_deadline_ack_ids.push(ack_id.to_string());
+        request.modify_deadline_seconds.push(ack_deadline_seconds);
+        self.send_with_retry(ack_id, request, &self.pending_modacks).await
+    }
+
+    /// send_with_retry sends the request until the ack id is confirmed.
+    /// The temporary failures and the missing confirmations are retried with backoff.
+    async fn send_with_retry(
+        &self,
+        ack_id: &str,
+        request: StreamingPullRequest,
+        pending: &PendingConfirmations,
+    ) -> Option<Result<(), AckError>> {
+        let mut strategy = self.retry.strategy();
+        loop {
+            let (sender, receiver) = oneshot::channel();
+            pending.lock().unwrap().insert(ack_id.to_string(), sender);
+            if self.requests.send(request.clone()).await.is_err() {
+                pending.lock().unwrap().remove(ack_id);
+                return None;
+            }
+            let confirmation = match timeout(self.confirmation_timeout, receiver).await {
+                Ok(Ok(confirmation)) => confirmation,
+                // The stream was reconnected or closed before the confirmation arrived.
+                _ => {
+                    pending.lock().unwrap().remove(ack_id);
+                    Confirmation::TemporaryFailed
+                }
+            };
+            let result = match confirmation {
+                Confirmation::Success => Ok(()),
+                Confirmation::Invalid => Err(AckError::InvalidAckId(ack_id.to_string())),
+                Confirmation::Unordered => Err(AckError::UnorderedAckId(ack_id.to_string())),
+                Confirmation::TemporaryFailed => match strategy.next() {
+                    Some(duration) => {
+                        tracing::debug!("retry acknowledgement after {duration:?}: ack_id={ack_id}");
+                        sleep(duration).await;
+                        continue;
+                    }
+                    None => Err(AckError::TransientFailure(ack_id.to_string())),
+                },
+            };
+            return Some(result);
+        }
+    }
+
+    /// confirm notifies the confirmations in the streaming pull response to the waiting acknowledgements.
+    fn confirm(&self, response: &StreamingPullResponse) {
+        if let Some(c) = &response.acknowledge_confirmation {
+            let mut pending = self.pending_acks.lock().unwrap();
+            notify(&mut pending, &c.ack_ids, Confirmation::Success);
+            notify(&mut pending, &c.invalid_ack_ids, Confirmation::Invalid);
+            notify(&mut pending, &c.unordered_ack_ids, Confirmation::Unordered);
+            notify(&mut pending, &c.temporary_failed_ack_ids, Confirmation::TemporaryFailed);
+        }
+        if let Some(c) = &response.modify_ack_deadline_confirmation {
+            let mut pending = self.pending_modacks.lock().unwrap();
+            notify(&mut pending, &c.ack_ids, Confirmation::Success);
+            notify(&mut pending, &c.invalid_ack_ids, Confirmation::Invalid);
+            notify(&mut pending, &c.temporary_failed_ack_ids, Confirmation::TemporaryFailed);
+        }
+    }
+}
+
+fn notify(pending: &mut HashMap<String, oneshot::Sender<Confirmation>>, ack_ids: &[String], c: Confirmation) {
+    for ack_id in ack_ids {
+        if let Some(sender) = pending.remove(ack_id) {
+            let _ = sender.send(c);
+        }
+    }
+}
+
+/// Leases holds the ack ids of the outstanding messages whose ack deadlines are extended automatically,
+/// with the time they were received.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Leases {
+    inner: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl Leases {
+    fn add(&self, ack_ids: impl Iterator<Item = String>) {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        for ack_id in ack_ids {
+            inner.insert(ack_id, now);
+        }
+    }
+
+    fn remove(&self, ack_id: &str) {
+        self.inner.lock().unwrap().remove(ack_id);
+    }
+
+    /// extendable drops the leases held longer than max_extension and returns the ack ids of the rest.
+    fn extendable(&self, max_extension: Duration) -> Vec<String> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.retain(|_, received_at| received_at.elapsed() < max_extension);
+        inner.keys().cloned().collect()
+    }
+}
+
+/// PendingAck is the acknowledgement waiting to be sent by the AckBatcher.
+#[derive(Debug)]
+enum PendingAck {
+    Ack(String),
+    ModifyAckDeadline(String, i32),
+}
+
+type BatchedAck = (PendingAck, oneshot::Sender<Result<(), Status>>);
+
+/// AckBatcher batches the acknowledgements sent by the unary RPCs.
+/// The acknowledgements requested while the previous batch is being sent are sent together in the next batch.
+#[derive(Clone, Debug)]
+pub(crate) struct AckBatcher {
+    sender: async_channel::Sender<BatchedAck>,
+}
+
+impl AckBatcher {
+    fn start(client: SubscriberClient, subscription: String) -> Self {
+        let (sender, receiver) = async_channel::unbounded::<BatchedAck>();
+        // The task stops when all the messages holding the sender are dropped.
+        tokio::spawn(async move {
+            while let Ok(first) = receiver.recv().await {
+                let mut batch = vec![first];
+                while batch.len() < MAX_ACK_IDS_PER_REQUEST {
+                    match receiver.try_recv() {
+                        Ok(v) => batch.push(v),
+                        Err(_) => break,
+                    }
+                }
+                send_batch(&client, &subscription, batch).await;
+            }
+            tracing::trace!("stop ack batcher : {}", subscription);
+        });
+        Self { sender }
+    }
+
+    /// send returns None if the batcher is already stopped.
+    async fn send(&self, ack: PendingAck) -> Option<Result<(), Status>> {
+        let (sender, receiver) = oneshot::channel();
+        self.sender.send((ack, sender)).await.ok()?;
+        receiver.await.ok()
+    }
+}
+
+/// send_batch sends the acks in a request and the modacks in a request per deadline.
+async fn send_batch(client: &SubscriberClient, subscription: &str, batch: Vec<BatchedAck>) {
+    let mut acks = vec![];
+    let mut modacks = HashMap::<i32, Vec<_>>::new();
+    for (pending, sender) in batch {
+        match pending {
+            PendingAck::Ack(ack_id) => acks.push((ack_id, sender)),
+            PendingAck::ModifyAckDeadline(ack_id, seconds) => {
+                modacks.entry(seconds).or_default().push((ack_id, sender))
+            }
+        }
+    }
+    if !acks.is_empty() {
+        let (ack_ids, senders): (Vec<_>, Vec<_>) = acks.into_iter().unzip();
+        reply(senders, ack(client, subscription.to_string(), ack_ids).await);
+    }
+    for (seconds, modacks) in modacks {
+        let (ack_ids, senders): (Vec<_>, Vec<_>) = modacks.into_iter().unzip();
+        reply(
+            senders,
+            modify_ack_deadline(client, subscription.to_string(), ack_ids, seconds).await,
+        );
+    }
+}
+
+fn reply(senders: Vec<oneshot::Sender<Result<(), Status>>>, result: Result<(), Status>) {
+    for sender in senders {
+        let _ = sender.send(result.clone());
+    }
+}
+
+/// Acknowledger holds the ways to acknowledge the messages received by a subscriber.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Acknowledger {
+    /// It is set only on the subscription with exactly-once delivery.
+    stream: Option<StreamAcker>,
+    batcher: Option<AckBatcher>,
+    leases: Option<Leases>,
+}
+
+impl Acknowledger {
+    /// batched creates the Acknowledger batching the acknowledgements of the messages received by the unary pull.
+    pub(crate) fn batched(client: SubscriberClient, subscription: String) -> Self {
+        Self {
+            batcher: Some(AckBatcher::start(client, subscription)),
+            ..Default::default()
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ReceivedMessage {
     pub message: PubsubMessage,
@@ -22,6 +310,7 @@ pub struct ReceivedMessage {
     subscription: String,
     subscriber_client: SubscriberClient,
     delivery_attempt: Option<usize>,
+    acknowledger: Acknowledger,
 }
 
 impl ReceivedMessage {
@@ -38,6 +327,19 @@ impl ReceivedMessage {
             subscription,
             subscriber_client: subc,
             delivery_attempt,
+            acknowledger: Acknowledger::default(),
+        }
+    }
+
+    pub(crate) fn with_acknowledger(mut self, acknowledger: Acknowledger) -> Self {
+        self.acknowledger = acknowledger;
+        self
+    }
+
+    /// release stops the automatic extension of the ack deadline.
+    fn release(&self) {
+        if let Some(leases) = &self.acknowledger.leases {
+            leases.remove(&self.ack_id);
         }
     }
 
@@ -45,38 +347,87 @@ impl ReceivedMessage {
         self.ack_id.as_str()
     }
 
-    pub async fn ack(&self) -> Result<(), Status> {
-        ack(
+    /// ack acknowledges the message.
+    ///
+    /// On the subscription with exactly-once delivery, the message received by the streaming pull is
+    /// acknowledged through the stream and this waits until the server confirms it.
+    /// The temporary failures are retried according to `SubscriberConfig::retry_setting` and
+    /// the final result is returned. Once it returns `Ok`, the message is never redelivered.
+    ///
+    /// Otherwise the acknowledgements of the messages received by the same subscriber are batched
+    /// into an Acknowledge request.
+    ///
+    /// The automatic extension of the ack deadline stops when `ack`, `nack` or `modify_ack_deadline` is called.
+    pub async fn ack(&self) -> Result<(), AckError> {
+        self.release();
+        if let Some(acker) = &self.acknowledger.stream {
+            if let Some(result) = acker.ack(&self.ack_id).await {
+                return result;
+            }
+        }
+        if let Some(batcher) = &self.acknowledger.batcher {
+            if let Some(result) = batcher.send(PendingAck::Ack(self.ack_id.to_string())).await {
+                return Ok(result?);
+            }
+        }
+        Ok(ack(
             &self.subscriber_client,
             self.subscription.to_string(),
             vec![self.ack_id.to_string()],
         )
-        .await
+        .await?)
     }
 
-    pub async fn nack(&self) -> Result<(), Status> {
-        nack(
-            &self.subscriber_client,
-            self.subscription.to_string(),
-            vec![self.ack_id.to_string()],
-        )
-        .await
+    /// nack rejects the message and makes it available for the redelivery immediately.
+    /// It is the same as `modify_ack_deadline` with zero.
+    pub async fn nack(&self) -> Result<(), AckError> {
+        self.modify_ack_deadline(Duration::ZERO).await
     }
 
-    pub async fn modify_ack_deadline(&self, ack_deadline_seconds: i32) -> Result<(), Status> {
-        modify_ack_deadline(
+    /// modify_ack_deadline sets the ack deadline of the message to the deadline from now.
+    /// The deadline is rounded down to seconds and must be at most 600 seconds,
+    /// otherwise `InvalidArgument` is returned without any request.
+    ///
+    /// The manual modification takes over the automatic extension of the ack deadline: the message is no longer
+    /// extended by the subscriber, so call it again before the deadline if the processing takes longer.
+    ///
+    /// The confirmation on the subscription with exactly-once delivery and the batching are the same as `ack`.
+    /// The modifications with the same deadline are batched into a ModifyAckDeadline request.
+    pub async fn modify_ack_deadline(&self, deadline: Duration) -> Result<(), AckError> {
+        if deadline > MAX_ACK_DEADLINE {
+            return Err(Status::invalid_argument(format!(
+                "ack deadline must be at most {MAX_ACK_DEADLINE:?}: deadline={deadline:?}"
+            ))
+            .into());
+        }
+        let ack_deadline_seconds = deadline.as_secs() as i32;
+        self.release();
+        if let Some(acker) = &self.acknowledger.stream {
+            if let Some(result) = acker.modify_ack_deadline(&self.ack_id, ack_deadline_seconds).await {
+                return result;
+            }
+        }
+        if let Some(batcher) = &self.acknowledger.batcher {
+            let pending = PendingAck::ModifyAckDeadline(self.ack_id.to_string(), ack_deadline_seconds);
+            if let Some(result) = batcher.send(pending).await {
+                return Ok(result?);
+            }
+        }
+        Ok(modify_ack_deadline(
             &self.subscriber_client,
             self.subscription.to_string(),
             vec![self.ack_id.to_string()],
             ack_deadline_seconds,
         )
-        .await
+        .await?)
     }
 
     /// The approximate number of times that Cloud Pub/Sub has attempted to deliver
     /// the associated message to a subscriber.
     ///
     /// The returned value, if present, will be greater than zero.
+    /// It is present only if the subscription has the dead letter policy, so the handler can behave differently
+    /// as it approaches `max_delivery_attempts`.
     ///
     /// For more information refer to the
     /// [protobuf definition](https://github.com/googleapis/googleapis/blob/3c7c76fb63d0f511cdb8c3c1cbc157315f6fbfd3/google/pubsub/v1/pubsub.proto#L1099-L1115).
@@ -107,6 +458,27 @@ pub struct SubscriberConfig {
     /// `INVALID_ARGUMENT`.
     pub max_outstanding_messages: i64,
     pub max_outstanding_bytes: i64,
+    /// max_extension is the maximum period for which the ack deadline of a received message is extended
+    /// automatically until it is acked or nacked. The message is redelivered once the deadline expires after that.
+    /// The extension includes the time the message waits in the queue of the subscriber.
+    /// Zero disables the automatic extension.
+    pub max_extension: Duration,
+    /// max_extension_period is the ack deadline set by each extension. It must be between 10 seconds and
+    /// 600 seconds (inclusive).
+    pub max_extension_period: Duration,
+    /// extension_interval is the interval of extending the ack deadlines of the outstanding messages.
+    /// It is capped at a half of `stream_ack_deadline_seconds` and `max_extension_period`,
+    /// so that the deadlines never expire between the extensions.
+    pub extension_interval: Duration,
+    /// concurrency is the max number of the handlers of `Subscription::receive` run at once per stream.
+    /// The messages are not taken from the queue while all the handlers are busy, so the server stops sending
+    /// the messages once `max_outstanding_messages` are outstanding. It is always 1 on the subscription with
+    /// message ordering, so that the messages are processed in order.
+    pub concurrency: usize,
+    /// shutdown_timeout is the max time `Subscription::receive` waits for the running handlers after it is
+    /// cancelled. The handlers still running after it are aborted. The messages whose handlers are aborted or
+    /// not started yet are nacked, so that they are redelivered immediately.
+    pub shutdown_timeout: Duration,
 }
 
 impl Default for SubscriberConfig {
@@ -117,13 +489,40 @@ impl Default for SubscriberConfig {
             stream_ack_deadline_seconds: 60,
             max_outstanding_messages: 50,
             max_outstanding_bytes: 1000 * 1000 * 1000,
+            max_extension: Duration::from_secs(60 * 60),
+            max_extension_period: Duration::from_secs(60),
+            extension_interval: Duration::from_secs(30),
+            concurrency: 1,
+            shutdown_timeout: Duration::from_secs(30),
         }
     }
 }
 
+impl SubscriberConfig {
+    /// extension_deadline_seconds returns the ack deadline set by each extension.
+    fn extension_deadline_seconds(&self) -> i32 {
+        self.max_extension_period.as_secs().clamp(10, 600) as i32
+    }
+
+    /// extension_interval returns the interval of the extensions. It is shorter than any ack deadline.
+    fn extension_interval(&self) -> Duration {
+        let deadline = self
+            .extension_deadline_seconds()
+            .min(self.stream_ack_deadline_seconds.max(10));
+        self.extension_interval.min(Duration::from_secs(deadline as u64) / 2)
+    }
+}
+
+/// reconnect_backoff returns the backoff of reconnecting the stream.
+/// Unlike the retries of the RPCs, the reconnection never gives up on the retryable errors.
+fn reconnect_backoff(retry: &RetrySetting) -> ExponentialBackoff {
+    ExponentialBackoff::from_millis(retry.from_millis.max(1)).max_delay(retry.max_delay.unwrap_or(MAX_RECONNECT_DELAY))
+}
+
 #[derive(Debug)]
 pub(crate) struct Subscriber {
     pinger: Option<JoinHandle<()>>,
+    leaser: Option<JoinHandle<()>>,
     inner: Option<JoinHandle<()>>,
 }
 
@@ -135,7 +534,31 @@ impl Subscriber {
         queue: async_channel::Sender<ReceivedMessage>,
         config: SubscriberConfig,
     ) -> Self {
+        // ping and acknowledgement requests
         let (ping_sender, ping_receiver) = async_channel::unbounded();
+        let retry = config.retry_setting.clone().unwrap_or_else(default_retry_setting);
+        let acker = StreamAcker::new(
+            ping_sender.clone(),
+            retry.clone(),
+            Duration::from_secs(config.stream_ack_deadline_seconds.max(10) as u64),
+        );
+        let leases = (!config.max_extension.is_zero()).then(Leases::default);
+        let acknowledger = Acknowledger {
+            stream: None,
+            batcher: Some(AckBatcher::start(client.clone(), subscription.to_string())),
+            leases: leases.clone(),
+        };
+
+        let leaser = leases.map(|leases| {
+            let ctx = ctx.clone();
+            let client = client.clone();
+            let subscription = subscription.to_string();
+            let config = config.clone();
+            tokio::spawn(async move {
+                Self::extend_leases(ctx, client, &subscription, leases, &config).await;
+                tracing::trace!("stop leaser : {}", subscription);
+            })
+        });
 
         // ping request
         let subscription_clone = subscription.to_string();
@@ -149,7 +572,7 @@ impl Subscriber {
                         break;
                     }
                     _ = sleep(config.ping_interval) => {
-                        let _ = ping_sender.send(true).await;
+                        let _ = ping_sender.send(create_empty_streaming_pull_request()).await;
                     }
                 }
             }
@@ -158,11 +581,25 @@ impl Subscriber {
 
         let inner = tokio::spawn(async move {
             tracing::trace!("start subscriber: {}", subscription);
-            let retryable_codes = match &config.retry_setting {
-                Some(v) => v.codes.clone(),
-                None => default_retry_setting().codes,
-            };
+            let retryable_codes = retry.codes.clone();
+            let mut backoff = reconnect_backoff(&retry);
+            let mut reconnecting = false;
             loop {
+                if reconnecting {
+                    // The iterator of the backoff never ends.
+                    let delay = backoff.next().unwrap_or(MAX_RECONNECT_DELAY);
+                    tracing::debug!("reconnect after {delay:?} : {}", subscription);
+                    select! {
+                        _ = cancel_receiver.cancelled() => {
+                            queue.close();
+                            break;
+                        }
+                        _ = sleep(delay) => {}
+                    }
+                }
+                reconnecting = true;
+
+                // The stream settings are sent again on every reconnection.
                 let mut request = create_empty_streaming_pull_request();
                 request.subscription = subscription.to_string();
                 request.stream_ack_deadline_seconds = config.stream_ack_deadline_seconds;
@@ -188,15 +625,20 @@ impl Subscriber {
                         }
                     }
                 };
-                match Self::recv(
+                let (result, received) = Self::recv(
                     client.clone(),
                     stream,
                     subscription.as_str(),
                     cancel_receiver.clone(),
                     queue.clone(),
+                    &acker,
+                    acknowledger.clone(),
                 )
-                .await
-                {
+                .await;
+                if received {
+                    backoff = reconnect_backoff(&retry);
+                }
+                match result {
                     Ok(_) => break,
                     Err(e) => {
                         if retryable_codes.contains(&e.code()) {
@@ -214,40 +656,117 @@ impl Subscriber {
         });
         Self {
             pinger: Some(pinger),
+            leaser,
             inner: Some(inner),
         }
     }
 
+    /// extend_leases extends the ack deadlines of the outstanding messages periodically until ctx is cancelled.
+    async fn extend_leases(
+        ctx: CancellationToken,
+        client: SubscriberClient,
+        subscription: &str,
+        leases: Leases,
+        config: &SubscriberConfig,
+    ) {
+        let deadline = config.extension_deadline_seconds();
+        let interval = config.extension_interval();
+        loop {
+            select! {
+                _ = ctx.cancelled() => break,
+                _ = sleep(interval) => {
+                    let ack_ids = leases.extendable(config.max_extension);
+                    for chunk in ack_ids.chunks(MAX_ACK_IDS_PER_REQUEST) {
+                        tracing::trace!("extend ack deadlines: count={} : {}", chunk.len(), subscription);
+                        let result = modify_ack_deadline(&client, subscription.to_string(), chunk.to_vec(), deadline).await;
+                        if let Err(err) = result {
+                            tracing::warn!("failed to extend ack deadlines {err} : {}", subscription);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// recv receives the messages until the stream is closed.
+    /// The second value of the result is true if any response was received.
     async fn recv(
         client: SubscriberClient,
         mut stream: Streaming<StreamingPullResponse>,
         subscription: &str,
         cancel: CancellationToken,
         queue: async_channel::Sender<ReceivedMessage>,
-    ) -> Result<(), Status> {
+        acker: &StreamAcker,
+        acknowledger: Acknowledger,
+    ) -> (Result<(), Status>, bool) {
         tracing::trace!("start streaming: {}", subscription);
-        loop {
+
+        // The received messages are forwarded to the queue by another task, so that the confirmations of
+        // the acknowledgements keep being read while the queue is full.
+        let (forward_sender, forward_receiver) =
+            async_channel::unbounded::<(Vec<InternalReceivedMessage>, Acknowledger)>();
+        let forwarder = {
+            let queue = queue.clone();
+            let subscription = subscription.to_string();
+            tokio::spawn(async move {
+                while let Ok((messages, acknowledger)) = forward_receiver.recv().await {
+                    let _ = handle_message(&queue, &client, &subscription, messages, acknowledger).await;
+                }
+            })
+        };
+
+        // The server confirms the acknowledgements only with exactly-once delivery.
+        let mut exactly_once = false;
+        let mut received = false;
+        let result = loop {
             select! {
                 _ = cancel.cancelled() => {
                     queue.close();
-                    return Ok(());
+                    break Ok(());
                 }
                 maybe = stream.message() => {
-                    let message = maybe?;
-                    let message = match message {
-                        Some(m) => m,
-                        None => return Ok(())
+                    let message = match maybe {
+                        Ok(Some(m)) => m,
+                        // The stream is closed by the server unless it is cancelled, so reconnect it.
+                        Ok(None) if cancel.is_cancelled() => {
+                            queue.close();
+                            break Ok(());
+                        }
+                        Ok(None) => break Err(Status::unavailable("the stream was closed by the server")),
+                        Err(e) => break Err(e),
                     };
-                    let _ = handle_message(&queue, &client, subscription, message.received_messages).await;
+                    received = true;
+                    acker.confirm(&message);
+                    if let Some(leases) = &acknowledger.leases {
+                        // The deadlines are extended while the messages wait in the queue.
+                        leases.add(message.received_messages.iter().map(|m| m.ack_id.clone()));
+                    }
+                    if let Some(properties) = &message.subscription_properties {
+                        exactly_once = properties.exactly_once_delivery_enabled;
+                    }
+                    let acknowledger = Acknowledger {
+                        stream: exactly_once.then(|| acker.clone()),
+                        ..acknowledger.clone()
+                    };
+                    let _ = forward_sender.send((message.received_messages, acknowledger)).await;
                 }
             }
+        };
+        forward_sender.close();
+        // The rest of the messages are forwarded in background while reconnecting.
+        if result.is_ok() {
+            let _ = forwarder.await;
         }
+        (result, received)
     }
 
     pub async fn done(&mut self) {
         if let Some(v) = self.pinger.take() {
             let _ = v.await;
         }
+        if let Some(v) = self.leaser.take() {
+            let _ = v.await;
+        }
         if let Some(v) = self.inner.take() {
             let _ = v.await;
         }
@@ -259,6 +778,7 @@ async fn handle_message(
     client: &SubscriberClient,
     subscription: &str,
     messages: Vec<InternalReceivedMessage>,
+    acknowledger: Acknowledger,
 ) -> usize {
     let mut nack_targets = vec![];
     for received_message in messages {
@@ -266,16 +786,22 @@ async fn handle_message(
             let id = message.message_id.clone();
             tracing::debug!("message received: msg_id={id}");
             if let Err(err) = queue
-                .send(ReceivedMessage::new(
-                    subscription.to_string(),
-                    client.clone(),
-                    message,
-                    received_message.ack_id.clone(),
-                    (received_message.delivery_attempt > 0).then_some(received_message.delivery_attempt as usize),
-                ))
+                .send(
+                    ReceivedMessage::new(
+                        subscription.to_string(),
+                        client.clone(),
+                        message,
+                        received_message.ack_id.clone(),
+                        (received_message.delivery_attempt > 0).then_some(received_message.delivery_attempt as usize),
+                    )
+                    .with_acknowledger(acknowledger.clone()),
+                )
                 .await
             {
                 tracing::error!(%err, "failed to send receiver queue -> so nack immediately : msg_id={id}");
+                if let Some(leases) = &acknowledger.leases {
+                    leases.remove(&received_message.ack_id);
+                }
                 nack_targets.push(received_message.ack_id);
             }
         }
@@ -312,7 +838,11 @@ async fn modify_ack_deadline(
         .map(|e| e.into_inner())
 }
 
-async fn nack(subscriber_client: &SubscriberClient, subscription: String, ack_ids: Vec<String>) -> Result<(), Status> {
+pub(crate) async fn nack(
+    subscriber_client: &SubscriberClient,
+    subscription: String,
+    ack_ids: Vec<String>,
+) -> Result<(), Status> {
     modify_ack_deadline(subscriber_client, subscription, ack_ids, 0).await
 }
 
@@ -330,15 +860,28 @@ pub(crate) async fn ack(
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use serial_test::serial;
 
     use google_cloud_gax::conn::{ConnectionOptions, Environment};
-    use google_cloud_googleapis::pubsub::v1::{PublishRequest, PubsubMessage, PullRequest};
+    use google_cloud_gax::grpc::Code;
+    use google_cloud_gax::retry::RetrySetting;
+    use google_cloud_googleapis::pubsub::v1::streaming_pull_response::{
+        AcknowledgeConfirmation, ModifyAckDeadlineConfirmation,
+    };
+    use google_cloud_googleapis::pubsub::v1::{
+        DeleteSubscriptionRequest, PublishRequest, PubsubMessage, PullRequest,
+        ReceivedMessage as InternalReceivedMessage, StreamingPullRequest, StreamingPullResponse,
+        Subscription as InternalSubscription,
+    };
 
     use crate::apiv1::conn_pool::ConnectionManager;
     use crate::apiv1::publisher_client::PublisherClient;
     use crate::apiv1::subscriber_client::SubscriberClient;
-    use crate::subscriber::handle_message;
+    use crate::subscriber::{
+        handle_message, reconnect_backoff, AckBatcher, AckError, Acknowledger, Leases, StreamAcker, SubscriberConfig,
+    };
 
     #[ctor::ctor]
     fn init() {
@@ -391,7 +934,259 @@ mod tests {
         let messages = response.received_messages;
         let (queue, _) = async_channel::unbounded();
         queue.close();
-        let nack_size = handle_message(&queue, &subc, subscription, messages).await;
+        let nack_size = handle_message(&queue, &subc, subscription, messages, Acknowledger::default()).await;
         assert_eq!(1, nack_size);
     }
+
+    async fn pull_all(subc: &SubscriberClient, subscription: &str, count: usize) -> Vec<InternalReceivedMessage> {
+        let mut messages = vec![];
+        while messages.len() < count {
+            let response = subc
+                .pull(
+                    PullRequest {
+                        subscription: subscription.to_string(),
+                        max_messages: (count - messages.len()) as i32,
+                        ..Default::default()
+                    },
+                    None,
+                )
+                .await
+                .unwrap()
+                .into_inner();
+            messages.extend(response.received_messages);
+        }
+        messages
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_ack_batcher() {
+        let cm = || async {
+            ConnectionManager::new(
+                4,
+                "",
+                &Environment::Emulator("localhost:8681".to_string()),
+                &ConnectionOptions::default(),
+            )
+            .await
+            .unwrap()
+        };
+        let subc = SubscriberClient::new(cm().await);
+        let pubc = PublisherClient::new(cm().await);
+
+        let subscription = format!("projects/local-project/subscriptions/s{}", uuid::Uuid::new_v4());
+        subc.create_subscription(
+            InternalSubscription {
+                name: subscription.to_string(),
+                topic: "projects/local-project/topics/test-topic1".to_string(),
+                ack_deadline_seconds: 60,
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        let messages = (0..3)
+            .map(|i| PubsubMessage {
+                data: format!("message{i}").into(),
+                ..Default::default()
+            })
+            .collect();
+        pubc.publish(
+            PublishRequest {
+                topic: "projects/local-project/topics/test-topic1".to_string(),
+                messages,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        let (queue, receiver) = async_channel::unbounded();
+        let acknowledger = Acknowledger {
+            batcher: Some(AckBatcher::start(subc.clone(), subscription.to_string())),
+            ..Default::default()
+        };
+        let messages = pull_all(&subc, &subscription, 3).await;
+        handle_message(&queue, &subc, &subscription, messages, acknowledger).await;
+        let messages: Vec<_> = (0..3).map(|_| receiver.try_recv().unwrap()).collect();
+
+        let err = messages[2]
+            .modify_ack_deadline(Duration::from_secs(601))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AckError::Status(status) if status.code() == Code::InvalidArgument));
+
+        // The acks are sent concurrently through the batcher.
+        let (ack1, ack2, nack) = tokio::join!(messages[0].ack(), messages[1].ack(), messages[2].nack());
+        ack1.unwrap();
+        ack2.unwrap();
+        nack.unwrap();
+
+        // Only the nacked message is redelivered.
+        let redelivered = pull_all(&subc, &subscription, 1).await;
+        assert_eq!(
+            messages[2].message.message_id,
+            redelivered[0].message.as_ref().unwrap().message_id
+        );
+
+        subc.delete_subscription(DeleteSubscriptionRequest { subscription }, None)
+            .await
+            .unwrap();
+    }
+
+    fn create_acker(take: usize) -> (StreamAcker, async_channel::Receiver<StreamingPullRequest>) {
+        let (sender, receiver) = async_channel::unbounded();
+        let retry = RetrySetting {
+            from_millis: 1,
+            take,
+            ..Default::default()
+        };
+        (StreamAcker::new(sender, retry, Duration::from_millis(200)), receiver)
+    }
+
+    fn ack_confirmation(f: impl FnOnce(&mut AcknowledgeConfirmation)) -> StreamingPullResponse {
+        let mut confirmation = AcknowledgeConfirmation::default();
+        f(&mut confirmation);
+        StreamingPullResponse {
+            acknowledge_confirmation: Some(confirmation),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_acker_retry_temporary_failure() {
+        let (acker, requests) = create_acker(3);
+        let server = acker.clone();
+        let server = tokio::spawn(async move {
+            let request = requests.recv().await.unwrap();
+            assert_eq!(vec!["ack1".to_string()], request.ack_ids);
+            server.confirm(&ack_confirmation(|c| c.temporary_failed_ack_ids = request.ack_ids));
+
+            // The confirmation never arrives for the second attempt.
+            let _ = requests.recv().await.unwrap();
+
+            let request = requests.recv().await.unwrap();
+            server.confirm(&ack_confirmation(|c| c.ack_ids = request.ack_ids));
+        });
+        acker.ack("ack1").await.unwrap().unwrap();
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stream_acker_permanent_failure() {
+        let (acker, requests) = create_acker(3);
+        let server = acker.clone();
+        let server = tokio::spawn(async move {
+            let request = requests.recv().await.unwrap();
+            server.confirm(&ack_confirmation(|c| c.invalid_ack_ids = request.ack_ids));
+            let request = requests.recv().await.unwrap();
+            server.confirm(&ack_confirmation(|c| c.unordered_ack_ids = request.ack_ids));
+            let request = requests.recv().await.unwrap();
+            assert_eq!(vec!["ack3".to_string()], request.modify_deadline_ack_ids);
+            assert_eq!(vec![0], request.modify_deadline_seconds);
+            server.confirm(&StreamingPullResponse {
+                modify_ack_deadline_confirmation: Some(ModifyAckDeadlineConfirmation {
+                    invalid_ack_ids: request.modify_deadline_ack_ids,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            });
+        });
+        let err = acker.ack("ack1").await.unwrap().unwrap_err();
+        assert!(matches!(&err, AckError::InvalidAckId(id) if id == "ack1"));
+        assert!(err.is_permanent());
+        let err = acker.ack("ack2").await.unwrap().unwrap_err();
+        assert!(matches!(&err, AckError::UnorderedAckId(id) if id == "ack2"));
+        assert!(err.is_permanent());
+        let err = acker.modify_ack_deadline("ack3", 0).await.unwrap().unwrap_err();
+        assert!(matches!(&err, AckError::InvalidAckId(id) if id == "ack3"));
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stream_acker_retry_exhausted() {
+        let (acker, requests) = create_acker(1);
+        let server = acker.clone();
+        let server = tokio::spawn(async move {
+            for _ in 0..2 {
+                let request = requests.recv().await.unwrap();
+                server.confirm(&ack_confirmation(|c| c.temporary_failed_ack_ids = request.ack_ids));
+            }
+        });
+        let err = acker.ack("ack1").await.unwrap().unwrap_err();
+        assert!(matches!(&err, AckError::TransientFailure(id) if id == "ack1"));
+        assert!(!err.is_permanent());
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stream_acker_closed() {
+        let (acker, requests) = create_acker(1);
+        requests.close();
+        assert!(acker.ack("ack1").await.is_none());
+        assert!(acker.pending_acks.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_leases() {
+        let leases = Leases::default();
+        leases.add(["ack1".to_string(), "ack2".to_string()].into_iter());
+        tokio::time::advance(Duration::from_secs(30)).await;
+        leases.add(["ack3".to_string()].into_iter());
+        leases.remove("ack2");
+
+        let mut ack_ids = leases.extendable(Duration::from_secs(60));
+        ack_ids.sort();
+        assert_eq!(vec!["ack1", "ack3"], ack_ids);
+
+        // The leases held longer than max_extension are dropped.
+        tokio::time::advance(Duration::from_secs(30)).await;
+        assert_eq!(vec!["ack3"], leases.extendable(Duration::from_secs(60)));
+        assert_eq!(vec!["ack3"], leases.extendable(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_extension_interval() {
+        let config = SubscriberConfig::default();
+        assert_eq!(60, config.extension_deadline_seconds());
+        assert_eq!(Duration::from_secs(30), config.extension_interval());
+
+        // The interval is capped so that the shortest deadline never expires between the extensions.
+        let config = SubscriberConfig {
+            stream_ack_deadline_seconds: 10,
+            ..Default::default()
+        };
+        assert_eq!(Duration::from_secs(5), config.extension_interval());
+        let config = SubscriberConfig {
+            max_extension_period: Duration::from_secs(1),
+            ..Default::default()
+        };
+        assert_eq!(10, config.extension_deadline_seconds());
+        assert_eq!(Duration::from_secs(5), config.extension_interval());
+        let config = SubscriberConfig {
+            max_extension_period: Duration::from_secs(3600),
+            stream_ack_deadline_seconds: 600,
+            extension_interval: Duration::from_secs(3600),
+            ..Default::default()
+        };
+        assert_eq!(600, config.extension_deadline_seconds());
+        assert_eq!(Duration::from_secs(300), config.extension_interval());
+    }
+
+    #[test]
+    fn test_reconnect_backoff() {
+        let retry = RetrySetting {
+            from_millis: 10,
+            max_delay: Some(Duration::from_secs(1)),
+            take: 1,
+            ..Default::default()
+        };
+        // The backoff doesn't end with the retry count of the RPCs.
+        let delays: Vec<Duration> = reconnect_backoff(&retry).take(5).collect();
+        assert_eq!(5, delays.len());
+        assert_eq!(Duration::from_millis(10), delays[0]);
+        assert!(delays.iter().all(|v| *v <= Duration::from_secs(1)));
+        assert_eq!(Duration::from_secs(1), delays[4]);
+    }
 }
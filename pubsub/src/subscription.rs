@@ -1,25 +1,323 @@
 use std::cmp::{max, min};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 use std::time::{Duration, SystemTime};
 
 use prost_types::{DurationError, FieldMask};
+use tokio::select;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
 use tokio_util::sync::CancellationToken;
 
 use google_cloud_gax::grpc::codegen::tokio_stream::Stream;
 use google_cloud_gax::grpc::{Code, Status};
 use google_cloud_gax::retry::RetrySetting;
+use google_cloud_googleapis::pubsub::v1::cloud_storage_config::{AvroConfig, OutputFormat, TextConfig};
+use google_cloud_googleapis::pubsub::v1::push_config::{AuthenticationMethod, NoWrapper, OidcToken, Wrapper};
 use google_cloud_googleapis::pubsub::v1::seek_request::Target;
 use google_cloud_googleapis::pubsub::v1::{
     BigQueryConfig, CloudStorageConfig, CreateSnapshotRequest, DeadLetterPolicy, DeleteSnapshotRequest,
-    DeleteSubscriptionRequest, ExpirationPolicy, GetSnapshotRequest, GetSubscriptionRequest, PullRequest, PushConfig,
-    RetryPolicy, SeekRequest, Snapshot, Subscription as InternalSubscription, UpdateSubscriptionRequest,
+    DeleteSubscriptionRequest, ExpirationPolicy, GetSnapshotRequest, GetSubscriptionRequest, ModifyPushConfigRequest,
+    PullRequest, PushConfig, RetryPolicy, SeekRequest, Snapshot, Subscription as InternalSubscription,
+    UpdateSubscriptionRequest,
 };
 
 use crate::apiv1::subscriber_client::SubscriberClient;
-use crate::subscriber::{ack, ReceivedMessage, Subscriber, SubscriberConfig};
+use crate::filter::MAX_FILTER_LENGTH;
+use crate::subscriber::{
+    ack, nack, Acknowledger, ReceivedMessage, Subscriber, SubscriberConfig, MAX_ACK_IDS_PER_REQUEST,
+};
+use crate::util::validate_message_retention_duration;
+
+pub use google_cloud_googleapis::pubsub::v1::big_query_config::State as BigQueryState;
+pub use google_cloud_googleapis::pubsub::v1::cloud_storage_config::State as CloudStorageState;
+pub use google_cloud_googleapis::pubsub::v1::subscription::State as SubscriptionState;
+
+/// The range of `max_delivery_attempts` of the dead letter policy.
+const MAX_DELIVERY_ATTEMPTS_RANGE: std::ops::RangeInclusive<i32> = 5..=100;
+/// The max backoff of the retry policy.
+const MAX_RETRY_BACKOFF_SECONDS: i64 = 600;
+/// The retention of the acknowledged messages when `retain_acked_messages` is set without
+/// `message_retention_duration`.
+const DEFAULT_MESSAGE_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// The min ttl of the expiration policy.
+const MIN_EXPIRATION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// The range of the max duration of a Cloud Storage file.
+const CLOUD_STORAGE_MAX_DURATION_RANGE: std::ops::RangeInclusive<Duration> =
+    Duration::from_secs(60)..=Duration::from_secs(10 * 60);
+/// The range of the max bytes of a Cloud Storage file.
+const CLOUD_STORAGE_MAX_BYTES_RANGE: std::ops::RangeInclusive<i64> = 1000..=10 * 1024 * 1024 * 1024;
+
+/// service_agent_email returns the email of the Pub/Sub service agent of the project like
+/// `service-123456789@gcp-sa-pubsub.iam.gserviceaccount.com`. Note that it is the project number, not the project id.
+///
+/// To forward the messages to the dead letter topic, the service agent must be granted
+/// `roles/pubsub.publisher` on the dead letter topic and `roles/pubsub.subscriber` on the subscription.
+/// See https://cloud.google.com/pubsub/docs/handling-failures#grant_forwarding_permissions
+pub fn service_agent_email(project_number: u64) -> String {
+    format!("service-{project_number}@gcp-sa-pubsub.iam.gserviceaccount.com")
+}
+
+/// dead_letter_policy creates the DeadLetterPolicy forwarding the messages to the dead letter topic after
+/// `max_delivery_attempts` (5 to 100) delivery attempts.
+/// The topic id is qualified with the project of the subscription if it doesn't contain a "/".
+fn dead_letter_policy(dead_letter_topic: impl Into<String>, max_delivery_attempts: i32) -> DeadLetterPolicy {
+    DeadLetterPolicy {
+        dead_letter_topic: dead_letter_topic.into(),
+        max_delivery_attempts,
+    }
+}
+
+/// retry_policy creates the RetryPolicy redelivering the nacked messages with the exponential backoff
+/// between `minimum_backoff` and `maximum_backoff` (0 to 600 seconds).
+fn retry_policy(minimum_backoff: Duration, maximum_backoff: Duration) -> RetryPolicy {
+    RetryPolicy {
+        minimum_backoff: Some(to_proto_duration(minimum_backoff)),
+        maximum_backoff: Some(to_proto_duration(maximum_backoff)),
+    }
+}
+
+fn to_proto_duration(v: Duration) -> prost_types::Duration {
+    prost_types::Duration {
+        seconds: v.as_secs() as i64,
+        nanos: v.subsec_nanos() as i32,
+    }
+}
+
+fn validate_dead_letter_policy(policy: &DeadLetterPolicy) -> Result<(), Box<Status>> {
+    if policy.dead_letter_topic.is_empty() {
+        return Err(Box::new(Status::invalid_argument(
+            "dead_letter_topic of the dead letter policy is required",
+        )));
+    }
+    // 0 means the default value 5.
+    if policy.max_delivery_attempts != 0 && !MAX_DELIVERY_ATTEMPTS_RANGE.contains(&policy.max_delivery_attempts) {
+        return Err(Box::new(Status::invalid_argument(format!(
+            "max_delivery_attempts of the dead letter policy must be between {} and {}: {}",
+            MAX_DELIVERY_ATTEMPTS_RANGE.start(),
+            MAX_DELIVERY_ATTEMPTS_RANGE.end(),
+            policy.max_delivery_attempts
+        ))));
+    }
+    Ok(())
+}
+
+fn validate_filter(filter: &str) -> Result<(), Box<Status>> {
+    if filter.len() > MAX_FILTER_LENGTH {
+        return Err(Box::new(Status::invalid_argument(format!(
+            "filter must be at most {MAX_FILTER_LENGTH} bytes: {} bytes",
+            filter.len()
+        ))));
+    }
+    Ok(())
+}
+
+fn validate_retry_policy(policy: &RetryPolicy) -> Result<(), Box<Status>> {
+    let seconds = |v: &Option<prost_types::Duration>| v.as_ref().map(|v| v.seconds as f64 + v.nanos as f64 / 1e9);
+    for (name, v) in [
+        ("minimum_backoff", seconds(&policy.minimum_backoff)),
+        ("maximum_backoff", seconds(&policy.maximum_backoff)),
+    ] {
+        if let Some(v) = v {
+            if !(0.0..=MAX_RETRY_BACKOFF_SECONDS as f64).contains(&v) {
+                return Err(Box::new(Status::invalid_argument(format!(
+                    "{name} of the retry policy must be between 0 and {MAX_RETRY_BACKOFF_SECONDS} seconds: {v}s"
+                ))));
+            }
+        }
+    }
+    if let (Some(min), Some(max)) = (seconds(&policy.minimum_backoff), seconds(&policy.maximum_backoff)) {
+        if min > max {
+            return Err(Box::new(Status::invalid_argument(
+                "minimum_backoff of the retry policy must not be greater than maximum_backoff",
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// push_config returns the push config to modify, creating the empty one if it is not set.
+fn push_config(config: &mut Option<PushConfig>) -> &mut PushConfig {
+    config.get_or_insert_with(PushConfig::default)
+}
+
+/// oidc_token creates the authentication method attaching the OIDC token of the service account to the requests.
+fn oidc_token(service_account_email: impl Into<String>, audience: impl Into<String>) -> AuthenticationMethod {
+    AuthenticationMethod::OidcToken(OidcToken {
+        service_account_email: service_account_email.into(),
+        audience: audience.into(),
+    })
+}
+
+/// no_wrapper creates the payload format delivering the message data as the raw HTTP body.
+fn no_wrapper(write_metadata: bool) -> Wrapper {
+    Wrapper::NoWrapper(NoWrapper { write_metadata })
+}
+
+/// validate_expiration_policy checks that the ttl is at least 1 day and the message retention duration.
+fn validate_expiration_policy(
+    policy: &ExpirationPolicy,
+    message_retention_duration: Option<Duration>,
+) -> Result<(), Box<Status>> {
+    let ttl = match &policy.ttl {
+        Some(v) if v.seconds >= 0 && v.nanos >= 0 => Duration::new(v.seconds as u64, v.nanos as u32),
+        Some(v) => {
+            return Err(Box::new(Status::invalid_argument(format!(
+                "ttl of the expiration policy must not be negative: {v:?}"
+            ))))
+        }
+        None => return Ok(()),
+    };
+    if ttl < MIN_EXPIRATION_TTL {
+        return Err(Box::new(Status::invalid_argument(format!(
+            "ttl of the expiration policy must be at least 1 day: {ttl:?}"
+        ))));
+    }
+    if let Some(retention) = message_retention_duration.filter(|v| ttl < *v) {
+        return Err(Box::new(Status::invalid_argument(format!(
+            "ttl={ttl:?} of the expiration policy must not be less than message_retention_duration={retention:?}"
+        ))));
+    }
+    Ok(())
+}
+
+/// Expiration is the expiration policy of the inactive subscription.
+/// The subscription is inactive if it has no subscriber nor any operation on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiration {
+    /// The subscription never expires.
+    Never,
+    /// The subscription is deleted after it is inactive for the ttl. It must be at least 1 day.
+    After(Duration),
+}
+
+impl From<Expiration> for ExpirationPolicy {
+    fn from(expiration: Expiration) -> Self {
+        match expiration {
+            // The policy without ttl means never, while no policy means the server default of 31 days.
+            Expiration::Never => ExpirationPolicy { ttl: None },
+            Expiration::After(ttl) => ExpirationPolicy {
+                ttl: Some(to_proto_duration(ttl)),
+            },
+        }
+    }
+}
+
+/// validate_push_config checks the push config. The empty endpoint means the pull delivery.
+fn validate_push_config(config: &PushConfig) -> Result<(), Box<Status>> {
+    let endpoint = config.push_endpoint.as_str();
+    if endpoint.is_empty() {
+        if config.authentication_method.is_some() || config.wrapper.is_some() {
+            return Err(Box::new(Status::invalid_argument(
+                "push_endpoint of the push config is required with the authentication method or the wrapper",
+            )));
+        }
+        return Ok(());
+    }
+    if !endpoint.starts_with("https://") {
+        return Err(Box::new(Status::invalid_argument(format!(
+            "push_endpoint of the push config must be an https URL: {endpoint}"
+        ))));
+    }
+    if let Some(AuthenticationMethod::OidcToken(token)) = &config.authentication_method {
+        if token.service_account_email.is_empty() {
+            return Err(Box::new(Status::invalid_argument(
+                "service_account_email of the OIDC token of the push config is required",
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// bigquery_config returns the BigQuery config to modify, creating the empty one if it is not set.
+fn bigquery_config(config: &mut Option<BigQueryConfig>) -> &mut BigQueryConfig {
+    config.get_or_insert_with(BigQueryConfig::default)
+}
+
+/// cloud_storage_config returns the Cloud Storage config to modify, creating the empty one if it is not set.
+fn cloud_storage_config(config: &mut Option<CloudStorageConfig>) -> &mut CloudStorageConfig {
+    config.get_or_insert_with(CloudStorageConfig::default)
+}
+
+/// validate_bigquery_config checks the BigQuery config. The empty table means no BigQuery delivery.
+fn validate_bigquery_config(config: &BigQueryConfig) -> Result<(), Box<Status>> {
+    if config.table.is_empty() && (config.use_topic_schema || config.write_metadata || config.drop_unknown_fields) {
+        return Err(Box::new(Status::invalid_argument("table of the BigQuery config is required")));
+    }
+    Ok(())
+}
+
+/// validate_cloud_storage_config checks the Cloud Storage config. The empty bucket means no Cloud Storage delivery.
+fn validate_cloud_storage_config(config: &CloudStorageConfig) -> Result<(), Box<Status>> {
+    if config.bucket.is_empty() {
+        if config != &CloudStorageConfig::default() {
+            return Err(Box::new(Status::invalid_argument(
+                "bucket of the Cloud Storage config is required",
+            )));
+        }
+        return Ok(());
+    }
+    if config.bucket.starts_with("gs://") {
+        return Err(Box::new(Status::invalid_argument(format!(
+            "bucket of the Cloud Storage config must be the name without the gs:// prefix: {}",
+            config.bucket
+        ))));
+    }
+    if config.filename_suffix.ends_with('/') {
+        return Err(Box::new(Status::invalid_argument(format!(
+            "filename_suffix of the Cloud Storage config must not end with /: {}",
+            config.filename_suffix
+        ))));
+    }
+    if let Some(v) = &config.max_duration {
+        let max_duration = Duration::new(v.seconds.max(0) as u64, v.nanos.max(0) as u32);
+        if !CLOUD_STORAGE_MAX_DURATION_RANGE.contains(&max_duration) {
+            return Err(Box::new(Status::invalid_argument(format!(
+                "max_duration of the Cloud Storage config must be between 1 and 10 minutes: {max_duration:?}"
+            ))));
+        }
+    }
+    // 0 means the default value.
+    if config.max_bytes != 0 && !CLOUD_STORAGE_MAX_BYTES_RANGE.contains(&config.max_bytes) {
+        return Err(Box::new(Status::invalid_argument(format!(
+            "max_bytes of the Cloud Storage config must be between 1KB and 10GiB: {}",
+            config.max_bytes
+        ))));
+    }
+    Ok(())
+}
+
+/// validate_delivery checks that at most one of the push, BigQuery and Cloud Storage deliveries is set.
+fn validate_delivery(
+    push: &Option<PushConfig>,
+    bigquery: &Option<BigQueryConfig>,
+    cloud_storage: &Option<CloudStorageConfig>,
+) -> Result<(), Box<Status>> {
+    let deliveries = [
+        ("push_config", push.as_ref().is_some_and(|v| !v.push_endpoint.is_empty())),
+        ("bigquery_config", bigquery.as_ref().is_some_and(|v| !v.table.is_empty())),
+        (
+            "cloud_storage_config",
+            cloud_storage.as_ref().is_some_and(|v| !v.bucket.is_empty()),
+        ),
+    ];
+    let set = deliveries
+        .iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+    if set.len() > 1 {
+        return Err(Box::new(Status::invalid_argument(format!(
+            "only one of push_config, bigquery_config and cloud_storage_config can be set: {}",
+            set.join(", ")
+        ))));
+    }
+    Ok(())
+}
 
 #[derive(Debug, Clone, Default)]
 pub struct SubscriptionConfig {
@@ -40,6 +338,177 @@ pub struct SubscriptionConfig {
     pub state: i32,
     pub cloud_storage_config: Option<CloudStorageConfig>,
 }
+
+impl SubscriptionConfig {
+    /// with_dead_letter_policy forwards the messages to the dead letter topic after `max_delivery_attempts`
+    /// (5 to 100) delivery attempts. The topic id is qualified with the project of the subscription if it
+    /// doesn't contain a "/". See [`service_agent_email`] for the permissions required to forward the messages.
+    pub fn with_dead_letter_policy(mut self, dead_letter_topic: impl Into<String>, max_delivery_attempts: i32) -> Self {
+        self.dead_letter_policy = Some(dead_letter_policy(dead_letter_topic, max_delivery_attempts));
+        self
+    }
+
+    /// with_retry_policy redelivers the nacked messages with the exponential backoff between `minimum_backoff`
+    /// and `maximum_backoff` (0 to 600 seconds) instead of immediately.
+    pub fn with_retry_policy(mut self, minimum_backoff: Duration, maximum_backoff: Duration) -> Self {
+        self.retry_policy = Some(retry_policy(minimum_backoff, maximum_backoff));
+        self
+    }
+
+    /// with_retain_acked_messages retains the acknowledged messages for `message_retention_duration`, so that
+    /// they can be replayed by seeking to a timestamp.
+    pub fn with_retain_acked_messages(mut self, retain_acked_messages: bool) -> Self {
+        self.retain_acked_messages = retain_acked_messages;
+        self
+    }
+
+    /// with_message_retention_duration retains the unacknowledged messages, and the acknowledged messages with
+    /// `retain_acked_messages`, for the duration (10 minutes to 31 days). The server default is 7 days.
+    pub fn with_message_retention_duration(mut self, duration: Duration) -> Self {
+        self.message_retention_duration = Some(duration);
+        self
+    }
+
+    /// with_expiration sets when the inactive subscription is deleted. The server default is 31 days.
+    /// The ttl must not be less than `message_retention_duration`.
+    pub fn with_expiration(mut self, expiration: Expiration) -> Self {
+        self.expiration_policy = Some(expiration.into());
+        self
+    }
+
+    /// with_filter delivers only the messages whose attributes match the filter like `attributes.type = "order"`.
+    /// Use [`Filter`](crate::filter::Filter) to build the expression. The filter can't be changed after the creation.
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = filter.into();
+        self
+    }
+
+    /// with_push_endpoint delivers the messages to the https endpoint like `https://example.com/push`
+    /// instead of the pull.
+    pub fn with_push_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        push_config(&mut self.push_config).push_endpoint = endpoint.into();
+        self
+    }
+
+    /// with_push_oidc_token attaches the OIDC token of the service account to the push requests, so that
+    /// the endpoint like Cloud Run can authenticate them. The audience defaults to the push endpoint if empty.
+    pub fn with_push_oidc_token(
+        mut self,
+        service_account_email: impl Into<String>,
+        audience: impl Into<String>,
+    ) -> Self {
+        push_config(&mut self.push_config).authentication_method = Some(oidc_token(service_account_email, audience));
+        self
+    }
+
+    /// with_push_no_wrapper delivers the message data as the raw HTTP body instead of the JSON wrapping the
+    /// message. The attributes are sent as the HTTP headers if write_metadata is true.
+    pub fn with_push_no_wrapper(mut self, write_metadata: bool) -> Self {
+        push_config(&mut self.push_config).wrapper = Some(no_wrapper(write_metadata));
+        self
+    }
+
+    /// with_bigquery writes the messages to the BigQuery table like `project.dataset.table` instead of the pull.
+    /// With use_topic_schema, the fields of the message are written to the columns according to the schema of
+    /// the topic, otherwise the data is written to the `data` column.
+    pub fn with_bigquery(mut self, table: impl Into<String>, use_topic_schema: bool) -> Self {
+        let config = bigquery_config(&mut self.bigquery_config);
+        config.table = table.into();
+        config.use_topic_schema = use_topic_schema;
+        self
+    }
+
+    /// with_bigquery_write_metadata writes the metadata of the messages like `message_id` and `publish_time`
+    /// to the additional columns of the BigQuery table.
+    pub fn with_bigquery_write_metadata(mut self, write_metadata: bool) -> Self {
+        bigquery_config(&mut self.bigquery_config).write_metadata = write_metadata;
+        self
+    }
+
+    /// with_bigquery_drop_unknown_fields drops the fields of the messages missing in the BigQuery table
+    /// instead of failing to write them. It is used with use_topic_schema.
+    pub fn with_bigquery_drop_unknown_fields(mut self, drop_unknown_fields: bool) -> Self {
+        bigquery_config(&mut self.bigquery_config).drop_unknown_fields = drop_unknown_fields;
+        self
+    }
+
+    /// with_cloud_storage writes the messages to the files in the Cloud Storage bucket instead of the pull.
+    /// The bucket is the name without the `gs://` prefix. The files are text files by default.
+    pub fn with_cloud_storage(mut self, bucket: impl Into<String>) -> Self {
+        cloud_storage_config(&mut self.cloud_storage_config).bucket = bucket.into();
+        self
+    }
+
+    /// with_cloud_storage_filename sets the prefix and the suffix of the names of the Cloud Storage files.
+    /// The suffix must not end with "/".
+    pub fn with_cloud_storage_filename(mut self, prefix: impl Into<String>, suffix: impl Into<String>) -> Self {
+        let config = cloud_storage_config(&mut self.cloud_storage_config);
+        config.filename_prefix = prefix.into();
+        config.filename_suffix = suffix.into();
+        self
+    }
+
+    /// with_cloud_storage_max_file starts a new Cloud Storage file after max_duration (1 to 10 minutes) or
+    /// when the file reaches max_bytes (1KB to 10GiB).
+    pub fn with_cloud_storage_max_file(mut self, max_duration: Duration, max_bytes: i64) -> Self {
+        let config = cloud_storage_config(&mut self.cloud_storage_config);
+        config.max_duration = Some(to_proto_duration(max_duration));
+        config.max_bytes = max_bytes;
+        self
+    }
+
+    /// with_cloud_storage_text writes the message data as the lines of the text files.
+    pub fn with_cloud_storage_text(mut self) -> Self {
+        cloud_storage_config(&mut self.cloud_storage_config).output_format =
+            Some(OutputFormat::TextConfig(TextConfig {}));
+        self
+    }
+
+    /// with_cloud_storage_avro writes the messages as the Avro records. The metadata of the messages like
+    /// `message_id` and `attributes` are written to the additional fields if write_metadata is true.
+    pub fn with_cloud_storage_avro(mut self, write_metadata: bool) -> Self {
+        cloud_storage_config(&mut self.cloud_storage_config).output_format =
+            Some(OutputFormat::AvroConfig(AvroConfig { write_metadata }));
+        self
+    }
+
+    /// subscription_state returns the state of the subscription. It is `ResourceError` if the subscription
+    /// can't write to the BigQuery table or the Cloud Storage bucket, see `bigquery_state` and
+    /// `cloud_storage_state` for the reason.
+    pub fn subscription_state(&self) -> SubscriptionState {
+        SubscriptionState::try_from(self.state).unwrap_or(SubscriptionState::Unspecified)
+    }
+
+    /// bigquery_state returns the state of the BigQuery delivery like `PermissionDenied`.
+    /// It is None if the subscription doesn't write to BigQuery.
+    pub fn bigquery_state(&self) -> Option<BigQueryState> {
+        self.bigquery_config
+            .as_ref()
+            .filter(|v| !v.table.is_empty())
+            .map(|v| v.state())
+    }
+
+    /// cloud_storage_state returns the state of the Cloud Storage delivery like `PermissionDenied`.
+    /// It is None if the subscription doesn't write to Cloud Storage.
+    pub fn cloud_storage_state(&self) -> Option<CloudStorageState> {
+        self.cloud_storage_config
+            .as_ref()
+            .filter(|v| !v.bucket.is_empty())
+            .map(|v| v.state())
+    }
+
+    /// replay_window returns how far back the acknowledged messages can be replayed by seeking to a timestamp.
+    /// It is None if neither the subscription (`retain_acked_messages`) nor the topic
+    /// (`topic_message_retention_duration`) retains the acknowledged messages.
+    pub fn replay_window(&self) -> Option<Duration> {
+        let subscription = self
+            .retain_acked_messages
+            .then(|| self.message_retention_duration.unwrap_or(DEFAULT_MESSAGE_RETENTION));
+        let topic = self.topic_message_retention_duration.filter(|v| !v.is_zero());
+        max(subscription, topic)
+    }
+}
+
 impl From<InternalSubscription> for SubscriptionConfig {
     fn from(f: InternalSubscription) -> Self {
         Self {
@@ -71,6 +540,7 @@ impl From<InternalSubscription> for SubscriptionConfig {
 pub struct SubscriptionConfigToUpdate {
     pub push_config: Option<PushConfig>,
     pub bigquery_config: Option<BigQueryConfig>,
+    pub cloud_storage_config: Option<CloudStorageConfig>,
     pub ack_deadline_seconds: Option<i32>,
     pub retain_acked_messages: Option<bool>,
     pub message_retention_duration: Option<Duration>,
@@ -80,6 +550,192 @@ pub struct SubscriptionConfigToUpdate {
     pub retry_policy: Option<RetryPolicy>,
 }
 
+impl SubscriptionConfigToUpdate {
+    /// with_dead_letter_policy updates the dead letter policy. See [`SubscriptionConfig::with_dead_letter_policy`].
+    pub fn with_dead_letter_policy(mut self, dead_letter_topic: impl Into<String>, max_delivery_attempts: i32) -> Self {
+        self.dead_letter_policy = Some(dead_letter_policy(dead_letter_topic, max_delivery_attempts));
+        self
+    }
+
+    /// with_retry_policy updates the retry policy. See [`SubscriptionConfig::with_retry_policy`].
+    pub fn with_retry_policy(mut self, minimum_backoff: Duration, maximum_backoff: Duration) -> Self {
+        self.retry_policy = Some(retry_policy(minimum_backoff, maximum_backoff));
+        self
+    }
+
+    /// with_retain_acked_messages updates whether the acknowledged messages are retained.
+    /// See [`SubscriptionConfig::with_retain_acked_messages`].
+    pub fn with_retain_acked_messages(mut self, retain_acked_messages: bool) -> Self {
+        self.retain_acked_messages = Some(retain_acked_messages);
+        self
+    }
+
+    /// with_message_retention_duration updates the message retention duration (10 minutes to 31 days).
+    pub fn with_message_retention_duration(mut self, duration: Duration) -> Self {
+        self.message_retention_duration = Some(duration);
+        self
+    }
+
+    /// with_expiration updates the expiration policy. Use `Expiration::Never` to stop the expiration.
+    /// See [`SubscriptionConfig::with_expiration`].
+    pub fn with_expiration(mut self, expiration: Expiration) -> Self {
+        self.expiration_policy = Some(expiration.into());
+        self
+    }
+
+    /// with_push_endpoint updates the push endpoint. See [`SubscriptionConfig::with_push_endpoint`].
+    /// The whole push config is replaced, so set the authentication method and the wrapper together if any.
+    /// Use the empty endpoint to switch the subscription to the pull.
+    pub fn with_push_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        push_config(&mut self.push_config).push_endpoint = endpoint.into();
+        self
+    }
+
+    /// with_push_oidc_token updates the OIDC token of the push config.
+    /// See [`SubscriptionConfig::with_push_oidc_token`].
+    pub fn with_push_oidc_token(
+        mut self,
+        service_account_email: impl Into<String>,
+        audience: impl Into<String>,
+    ) -> Self {
+        push_config(&mut self.push_config).authentication_method = Some(oidc_token(service_account_email, audience));
+        self
+    }
+
+    /// with_push_no_wrapper updates the payload format of the push config.
+    /// See [`SubscriptionConfig::with_push_no_wrapper`].
+    pub fn with_push_no_wrapper(mut self, write_metadata: bool) -> Self {
+        push_config(&mut self.push_config).wrapper = Some(no_wrapper(write_metadata));
+        self
+    }
+
+    /// with_bigquery updates the BigQuery table. See [`SubscriptionConfig::with_bigquery`].
+    /// The whole BigQuery config is replaced, so set the other options together if any.
+    /// Use the empty table to stop the BigQuery delivery.
+    pub fn with_bigquery(mut self, table: impl Into<String>, use_topic_schema: bool) -> Self {
+        let config = bigquery_config(&mut self.bigquery_config);
+        config.table = table.into();
+        config.use_topic_schema = use_topic_schema;
+        self
+    }
+
+    /// with_bigquery_write_metadata updates the BigQuery config.
+    /// See [`SubscriptionConfig::with_bigquery_write_metadata`].
+    pub fn with_bigquery_write_metadata(mut self, write_metadata: bool) -> Self {
+        bigquery_config(&mut self.bigquery_config).write_metadata = write_metadata;
+        self
+    }
+
+    /// with_bigquery_drop_unknown_fields updates the BigQuery config.
+    /// See [`SubscriptionConfig::with_bigquery_drop_unknown_fields`].
+    pub fn with_bigquery_drop_unknown_fields(mut self, drop_unknown_fields: bool) -> Self {
+        bigquery_config(&mut self.bigquery_config).drop_unknown_fields = drop_unknown_fields;
+        self
+    }
+
+    /// with_cloud_storage updates the Cloud Storage bucket. See [`SubscriptionConfig::with_cloud_storage`].
+    /// The whole Cloud Storage config is replaced, so set the other options together if any.
+    /// Use the empty bucket to stop the Cloud Storage delivery.
+    pub fn with_cloud_storage(mut self, bucket: impl Into<String>) -> Self {
+        cloud_storage_config(&mut self.cloud_storage_config).bucket = bucket.into();
+        self
+    }
+
+    /// with_cloud_storage_filename updates the Cloud Storage config.
+    /// See [`SubscriptionConfig::with_cloud_storage_filename`].
+    pub fn with_cloud_storage_filename(mut self, prefix: impl Into<String>, suffix: impl Into<String>) -> Self {
+        let config = cloud_storage_config(&mut self.cloud_storage_config);
+        config.filename_prefix = prefix.into();
+        config.filename_suffix = suffix.into();
+        self
+    }
+
+    /// with_cloud_storage_max_file updates the Cloud Storage config.
+    /// See [`SubscriptionConfig::with_cloud_storage_max_file`].
+    pub fn with_cloud_storage_max_file(mut self, max_duration: Duration, max_bytes: i64) -> Self {
+        let config = cloud_storage_config(&mut self.cloud_storage_config);
+        config.max_duration = Some(to_proto_duration(max_duration));
+        config.max_bytes = max_bytes;
+        self
+    }
+
+    /// with_cloud_storage_text updates the Cloud Storage config. See [`SubscriptionConfig::with_cloud_storage_text`].
+    pub fn with_cloud_storage_text(mut self) -> Self {
+        cloud_storage_config(&mut self.cloud_storage_config).output_format =
+            Some(OutputFormat::TextConfig(TextConfig {}));
+        self
+    }
+
+    /// with_cloud_storage_avro updates the Cloud Storage config. See [`SubscriptionConfig::with_cloud_storage_avro`].
+    pub fn with_cloud_storage_avro(mut self, write_metadata: bool) -> Self {
+        cloud_storage_config(&mut self.cloud_storage_config).output_format =
+            Some(OutputFormat::AvroConfig(AvroConfig { write_metadata }));
+        self
+    }
+
+    /// apply sets the fields to update to the subscription and returns the field mask of them.
+    fn apply(self, config: &mut InternalSubscription) -> Result<FieldMask, Box<Status>> {
+        let mut paths = vec![];
+        if let Some(v) = self.push_config {
+            validate_push_config(&v)?;
+            config.push_config = Some(v);
+            paths.push("push_config".to_string());
+        }
+        if let Some(v) = self.bigquery_config {
+            validate_bigquery_config(&v)?;
+            config.bigquery_config = Some(v);
+            paths.push("bigquery_config".to_string());
+        }
+        if let Some(v) = self.cloud_storage_config {
+            validate_cloud_storage_config(&v)?;
+            config.cloud_storage_config = Some(v);
+            paths.push("cloud_storage_config".to_string());
+        }
+        validate_delivery(&config.push_config, &config.bigquery_config, &config.cloud_storage_config)?;
+        if let Some(v) = self.ack_deadline_seconds {
+            config.ack_deadline_seconds = v;
+            paths.push("ack_deadline_seconds".to_string());
+        }
+        if let Some(v) = self.retain_acked_messages {
+            config.retain_acked_messages = v;
+            paths.push("retain_acked_messages".to_string());
+        }
+        if let Some(v) = self.message_retention_duration {
+            validate_message_retention_duration(v)?;
+            config.message_retention_duration = self
+                .message_retention_duration
+                .map(prost_types::Duration::try_from)
+                .transpose()
+                .map_err(|err| Status::internal(err.to_string()))?;
+            paths.push("message_retention_duration".to_string());
+        }
+        if let Some(v) = self.expiration_policy {
+            let retention = config
+                .message_retention_duration
+                .as_ref()
+                .map(|v| Duration::new(v.seconds as u64, v.nanos as u32));
+            validate_expiration_policy(&v, retention)?;
+            config.expiration_policy = Some(v);
+            paths.push("expiration_policy".to_string());
+        }
+        if let Some(v) = self.labels {
+            config.labels = v;
+            paths.push("labels".to_string());
+        }
+        if let Some(v) = self.dead_letter_policy {
+            validate_dead_letter_policy(&v)?;
+            config.dead_letter_policy = Some(v);
+            paths.push("dead_letter_policy".to_string());
+        }
+        if let Some(v) = self.retry_policy {
+            validate_retry_policy(&v)?;
+            config.retry_policy = Some(v);
+            paths.push("retry_policy".to_string());
+        }
+        Ok(FieldMask { paths })
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct SubscribeConfig {
     enable_multiple_subscriber: bool,
@@ -191,6 +847,15 @@ impl Subscription {
         }
     }
 
+    /// fully_qualified_topic_name returns the globally unique printable name of the topic in the same project.
+    fn fully_qualified_topic_name(&self, id: &str) -> String {
+        if id.contains('/') {
+            id.to_string()
+        } else {
+            format!("{}/topics/{}", self.fully_qualified_project_name(), id)
+        }
+    }
+
     fn fully_qualified_project_name(&self) -> String {
         let parts: Vec<_> = self
             .fqsn
@@ -208,6 +873,32 @@ impl Subscription {
 
     /// create creates the subscription.
     pub async fn create(&self, fqtn: &str, cfg: SubscriptionConfig, retry: Option<RetrySetting>) -> Result<(), Status> {
+        let mut cfg = cfg;
+        if let Some(policy) = cfg.dead_letter_policy.as_mut() {
+            validate_dead_letter_policy(policy).map_err(|e| *e)?;
+            policy.dead_letter_topic = self.fully_qualified_topic_name(&policy.dead_letter_topic);
+        }
+        if let Some(policy) = &cfg.retry_policy {
+            validate_retry_policy(policy).map_err(|e| *e)?;
+        }
+        if let Some(config) = &cfg.push_config {
+            validate_push_config(config).map_err(|e| *e)?;
+        }
+        if let Some(config) = &cfg.bigquery_config {
+            validate_bigquery_config(config).map_err(|e| *e)?;
+        }
+        if let Some(config) = &cfg.cloud_storage_config {
+            validate_cloud_storage_config(config).map_err(|e| *e)?;
+        }
+        validate_delivery(&cfg.push_config, &cfg.bigquery_config, &cfg.cloud_storage_config).map_err(|e| *e)?;
+        if let Some(v) = cfg.message_retention_duration {
+            validate_message_retention_duration(v).map_err(|e| *e)?;
+        }
+        if let Some(policy) = &cfg.expiration_policy {
+            validate_expiration_policy(policy, cfg.message_retention_duration).map_err(|e| *e)?;
+        }
+        validate_filter(&cfg.filter).map_err(|e| *e)?;
+        let filter = cfg.filter.clone();
         self.subc
             .create_subscription(
                 InternalSubscription {
@@ -242,6 +933,13 @@ impl Subscription {
             )
             .await
             .map(|_v| ())
+            .map_err(|e| {
+                if e.code() == Code::InvalidArgument && !filter.is_empty() {
+                    Status::invalid_argument(format!("{} (filter: `{filter}`)", e.message()))
+                } else {
+                    e
+                }
+            })
     }
 
     /// delete deletes the subscription.
@@ -291,48 +989,19 @@ impl Subscription {
             subscription: self.fqsn.to_string(),
         };
         let mut config = self.subc.get_subscription(req, retry.clone()).await?.into_inner();
-
-        let mut paths = vec![];
-        if updating.push_config.is_some() {
-            config.push_config = updating.push_config;
-            paths.push("push_config".to_string());
-        }
-        if updating.bigquery_config.is_some() {
-            config.bigquery_config = updating.bigquery_config;
-            paths.push("bigquery_config".to_string());
-        }
-        if let Some(v) = updating.ack_deadline_seconds {
-            config.ack_deadline_seconds = v;
-            paths.push("ack_deadline_seconds".to_string());
-        }
-        if let Some(v) = updating.retain_acked_messages {
-            config.retain_acked_messages = v;
-            paths.push("retain_acked_messages".to_string());
-        }
-        if updating.message_retention_duration.is_some() {
-            config.message_retention_duration = updating
-                .message_retention_duration
-                .map(prost_types::Duration::try_from)
-                .transpose()
-                .map_err(|err| Status::internal(err.to_string()))?;
-            paths.push("message_retention_duration".to_string());
-        }
-        if updating.expiration_policy.is_some() {
-            config.expiration_policy = updating.expiration_policy;
-            paths.push("expiration_policy".to_string());
-        }
-        if let Some(v) = updating.labels {
-            config.labels = v;
-            paths.push("labels".to_string());
-        }
-        if updating.retry_policy.is_some() {
-            config.retry_policy = updating.retry_policy;
-            paths.push("retry_policy".to_string());
+        let mut updating = updating;
+        if let Some(policy) = updating
+            .dead_letter_policy
+            .as_mut()
+            .filter(|v| !v.dead_letter_topic.is_empty())
+        {
+            policy.dead_letter_topic = self.fully_qualified_topic_name(&policy.dead_letter_topic);
         }
+        let update_mask = updating.apply(&mut config).map_err(|e| *e)?;
 
         let update_req = UpdateSubscriptionRequest {
             subscription: Some(config),
-            update_mask: Some(FieldMask { paths }),
+            update_mask: Some(update_mask),
         };
         self.subc.update_subscription(update_req, retry).await.map(|v| {
             let inner = v.into_inner();
@@ -340,8 +1009,34 @@ impl Subscription {
         })
     }
 
-    /// pull get message synchronously.
-    /// It blocks until at least one message is available.
+    /// modify_push_config switches the subscription between the push and the pull at runtime, or changes
+    /// the endpoint and the other attributes of the push. None or the empty endpoint means the pull.
+    /// The messages keep accumulating for the delivery through the call.
+    pub async fn modify_push_config(
+        &self,
+        push_config: Option<PushConfig>,
+        retry: Option<RetrySetting>,
+    ) -> Result<(), Status> {
+        if let Some(config) = &push_config {
+            validate_push_config(config).map_err(|e| *e)?;
+        }
+        let req = ModifyPushConfigRequest {
+            subscription: self.fqsn.to_string(),
+            push_config,
+        };
+        self.subc.modify_push_config(req, retry).await.map(|v| v.into_inner())
+    }
+
+    /// pull receives up to max_messages messages by the unary Pull RPC.
+    /// It waits until at least one message is available or the server returns an empty response after a while.
+    ///
+    /// The acknowledgements of the returned messages by `ReceivedMessage::ack`, `nack` and `modify_ack_deadline` are
+    /// batched, and `ack_all` acknowledges them in bulk. Their ack deadlines are not extended automatically, so
+    /// process them within the ack deadline of the subscription.
+    ///
+    /// It suits the batch consumers like "pull, process, ack and exit". Prefer `receive` or `subscribe` for
+    /// the long-running consumers: the streaming pull delivers the messages with lower latency and higher
+    /// throughput, and it extends the ack deadlines and handles the flow control.
     pub async fn pull(&self, max_messages: i32, retry: Option<RetrySetting>) -> Result<Vec<ReceivedMessage>, Status> {
         #[allow(deprecated)]
         let req = PullRequest {
@@ -350,6 +1045,10 @@ impl Subscription {
             max_messages,
         };
         let messages = self.subc.pull(req, retry).await?.into_inner().received_messages;
+        if messages.is_empty() {
+            return Ok(vec![]);
+        }
+        let acknowledger = Acknowledger::batched(self.subc.clone(), self.fqsn.clone());
         Ok(messages
             .into_iter()
             .filter(|m| m.message.is_some())
@@ -361,10 +1060,21 @@ impl Subscription {
                     m.ack_id,
                     (m.delivery_attempt > 0).then_some(m.delivery_attempt as usize),
                 )
+                .with_acknowledger(acknowledger.clone())
             })
             .collect())
     }
 
+    /// ack_all acknowledges the messages received by `pull` in bulk.
+    /// The ack ids are sent in Acknowledge requests of up to 2500 ids.
+    pub async fn ack_all(&self, messages: &[ReceivedMessage]) -> Result<(), Status> {
+        for chunk in messages.chunks(MAX_ACK_IDS_PER_REQUEST) {
+            let ack_ids = chunk.iter().map(|m| m.ack_id().to_string()).collect();
+            ack(&self.subc, self.fqsn.to_string(), ack_ids).await?;
+        }
+        Ok(())
+    }
+
     /// subscribe creates a `Stream` of `ReceivedMessage`
     /// Terminates the underlying `Subscriber` when dropped.
     /// ```no_test
@@ -410,6 +1120,10 @@ impl Subscription {
     /// receive calls f with the outstanding messages from the subscription.
     /// It blocks until cancellation token is cancelled, or the service returns a non-retryable error.
     /// The standard way to terminate a receive is to use CancellationToken.
+    ///
+    /// Up to `SubscriberConfig::concurrency` handlers run at once per stream.
+    /// Once cancelled, it stops pulling and waits for the running handlers up to `SubscriberConfig::shutdown_timeout`.
+    /// The messages not processed by then are nacked.
     pub async fn receive<F>(
         &self,
         f: impl Fn(ReceivedMessage, CancellationToken) -> F + Send + 'static + Sync + Clone,
@@ -424,12 +1138,12 @@ impl Subscription {
         let mut senders = Vec::with_capacity(receivers.len());
         let sub_opt = self.unwrap_subscribe_config(op.subscriber_config).await?;
 
-        if self
+        let ordering = self
             .config(sub_opt.retry_setting.clone())
             .await?
             .1
-            .enable_message_ordering
-        {
+            .enable_message_ordering;
+        if ordering {
             (0..op.worker_count).for_each(|_v| {
                 let (sender, receiver) = create_channel(op.channel_capacity);
                 receivers.push(receiver);
@@ -445,22 +1159,28 @@ impl Subscription {
 
         //same ordering key is in same stream.
         let subscribers: Vec<Subscriber> = senders
-            .into_iter()
+            .iter()
             .map(|queue| {
-                Subscriber::start(cancel.clone(), self.fqsn.clone(), self.subc.clone(), queue, sub_opt.clone())
+                Subscriber::start(
+                    cancel.clone(),
+                    self.fqsn.clone(),
+                    self.subc.clone(),
+                    queue.clone(),
+                    sub_opt.clone(),
+                )
             })
             .collect();
 
+        let concurrency = if ordering { 1 } else { sub_opt.concurrency.max(1) };
         let mut message_receivers = Vec::with_capacity(receivers.len());
         for receiver in receivers {
             let f_clone = f.clone();
             let cancel_clone = cancel.clone();
             let name = self.fqsn.clone();
+            let subc = self.subc.clone();
+            let shutdown_timeout = sub_opt.shutdown_timeout;
             message_receivers.push(tokio::spawn(async move {
-                while let Ok(message) = receiver.recv().await {
-                    f_clone(message, cancel_clone.clone()).await;
-                }
-                // queue is closed by subscriber when the cancellation token is cancelled
+                process_messages(receiver, f_clone, cancel_clone, concurrency, shutdown_timeout, &subc, &name).await;
                 tracing::trace!("stop message receiver : {}", name);
             }));
         }
@@ -470,6 +1190,10 @@ impl Subscription {
         for mut subscriber in subscribers {
             subscriber.done().await;
         }
+        // No more messages are queued after the subscribers stopped.
+        for sender in senders {
+            sender.close();
+        }
 
         // wait for all the receivers process received messages
         for mr in message_receivers {
@@ -545,10 +1269,21 @@ impl Subscription {
     }
 
     /// seek seeks the subscription a past timestamp or a saved snapshot.
+    ///
+    /// Seeking to a timestamp marks the messages published before it as acknowledged and the messages published
+    /// after it as unacknowledged. The acknowledged messages are replayed only if they are retained, see
+    /// [`SubscriptionConfig::replay_window`]. The error is `FailedPrecondition` if the server rejects the seek,
+    /// for example the snapshot has expired or was created from a subscription of another topic.
     pub async fn seek(&self, to: SeekTo, retry: Option<RetrySetting>) -> Result<(), Status> {
-        let to = match to {
-            SeekTo::Timestamp(t) => SeekTo::Timestamp(t),
-            SeekTo::Snapshot(name) => SeekTo::Snapshot(self.fully_qualified_snapshot_name(name.as_str())),
+        let (to, hint) = match to {
+            SeekTo::Timestamp(t) => (
+                SeekTo::Timestamp(t),
+                "seeking to a timestamp requires the retention of the acknowledged messages",
+            ),
+            SeekTo::Snapshot(name) => (
+                SeekTo::Snapshot(self.fully_qualified_snapshot_name(name.as_str())),
+                "the snapshot must not be expired and must belong to the topic of the subscription",
+            ),
         };
 
         let req = SeekRequest {
@@ -556,8 +1291,40 @@ impl Subscription {
             target: Some(to.into()),
         };
 
-        let _ = self.subc.seek(req, retry).await?;
-        Ok(())
+        match self.subc.seek(req, retry).await {
+            Ok(_) => Ok(()),
+            Err(e) if e.code() == Code::FailedPrecondition => Err(Status::failed_precondition(format!(
+                "failed to seek {}: {hint}: {}",
+                self.fqsn,
+                e.message()
+            ))),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// replay_from seeks the subscription to the timestamp after checking that the acknowledged messages
+    /// published since then are still retained, so that all of them are delivered again.
+    /// The error is `FailedPrecondition` if the subscription retains no acknowledged messages or the timestamp is
+    /// older than [`SubscriptionConfig::replay_window`].
+    pub async fn replay_from(&self, timestamp: SystemTime, retry: Option<RetrySetting>) -> Result<(), Status> {
+        let (_, cfg) = self.config(retry.clone()).await?;
+        let window = cfg.replay_window().ok_or_else(|| {
+            Status::failed_precondition(format!(
+                "{} retains no acknowledged messages: set retain_acked_messages or the topic message retention",
+                self.fqsn
+            ))
+        })?;
+        if let Ok(elapsed) = SystemTime::now().duration_since(timestamp) {
+            if elapsed > window {
+                return Err(Status::failed_precondition(format!(
+                    "{} retains the acknowledged messages only for {}s, but the timestamp is {}s ago",
+                    self.fqsn,
+                    window.as_secs(),
+                    elapsed.as_secs()
+                )));
+            }
+        }
+        self.seek(SeekTo::Timestamp(timestamp), retry).await
     }
 
     /// get_snapshot fetches an existing pubsub snapshot.
@@ -616,6 +1383,74 @@ impl Subscription {
     }
 }
 
+/// process_messages runs up to concurrency handlers at once until cancelled.
+/// Then it waits for the running handlers up to shutdown_timeout and nacks the messages not processed.
+async fn process_messages<F>(
+    receiver: async_channel::Receiver<ReceivedMessage>,
+    f: impl Fn(ReceivedMessage, CancellationToken) -> F + Send + 'static + Sync + Clone,
+    cancel: CancellationToken,
+    concurrency: usize,
+    shutdown_timeout: Duration,
+    subc: &SubscriberClient,
+    subscription: &str,
+) where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let limit = Arc::new(Semaphore::new(concurrency));
+    // The ack ids of the messages whose handlers are running.
+    let running = Arc::new(Mutex::new(HashSet::<String>::new()));
+    let mut handlers: Vec<JoinHandle<()>> = Vec::with_capacity(concurrency);
+    loop {
+        // The message is taken from the queue only when a handler is available.
+        let permit = select! {
+            biased;
+            _ = cancel.cancelled() => break,
+            permit = limit.clone().acquire_owned() => permit.unwrap(),
+        };
+        let message = select! {
+            biased;
+            _ = cancel.cancelled() => break,
+            message = receiver.recv() => match message {
+                Ok(message) => message,
+                Err(_) => break,
+            },
+        };
+        let ack_id = message.ack_id().to_string();
+        running.lock().unwrap().insert(ack_id.clone());
+        let f = f.clone();
+        let ctx = cancel.clone();
+        let running = running.clone();
+        handlers.retain(|h| !h.is_finished());
+        handlers.push(tokio::spawn(async move {
+            f(message, ctx).await;
+            running.lock().unwrap().remove(&ack_id);
+            drop(permit);
+        }));
+    }
+
+    // All the permits are available once all the running handlers finish.
+    if timeout(shutdown_timeout, limit.acquire_many(concurrency as u32))
+        .await
+        .is_err()
+    {
+        tracing::warn!("abort the running handlers after {shutdown_timeout:?} : {}", subscription);
+        for handler in &handlers {
+            handler.abort();
+        }
+    }
+    let mut ack_ids: Vec<String> = running.lock().unwrap().drain().collect();
+    // The queue is closed after the subscribers stopped.
+    while let Ok(message) = receiver.recv().await {
+        ack_ids.push(message.ack_id().to_string());
+    }
+    if !ack_ids.is_empty() {
+        tracing::debug!("nack the unprocessed messages: count={} : {}", ack_ids.len(), subscription);
+        if let Err(err) = nack(subc, subscription.to_string(), ack_ids).await {
+            tracing::error!("failed to nack the unprocessed messages {err} : {}", subscription);
+        }
+    }
+}
+
 fn create_channel(
     channel_capacity: Option<usize>,
 ) -> (async_channel::Sender<ReceivedMessage>, async_channel::Receiver<ReceivedMessage>) {
@@ -631,7 +1466,7 @@ mod tests {
     use std::sync::atomic::AtomicU32;
     use std::sync::atomic::Ordering::SeqCst;
     use std::sync::{Arc, Mutex};
-    use std::time::Duration;
+    use std::time::{Duration, SystemTime};
 
     use futures_util::StreamExt;
     use serial_test::serial;
@@ -644,9 +1479,17 @@ mod tests {
     use crate::apiv1::conn_pool::ConnectionManager;
     use crate::apiv1::publisher_client::PublisherClient;
     use crate::apiv1::subscriber_client::SubscriberClient;
-    use crate::subscriber::ReceivedMessage;
+    use crate::filter::{Filter, MAX_FILTER_LENGTH};
+    use crate::subscriber::{ReceivedMessage, SubscriberConfig};
     use crate::subscription::{
-        ReceiveConfig, SeekTo, SubscribeConfig, Subscription, SubscriptionConfig, SubscriptionConfigToUpdate,
+        service_agent_email, BigQueryState, Expiration, ReceiveConfig, SeekTo, SubscribeConfig, Subscription,
+        SubscriptionConfig, SubscriptionConfigToUpdate, SubscriptionState,
+    };
+    use google_cloud_gax::grpc::Code;
+    use google_cloud_googleapis::pubsub::v1::cloud_storage_config::{AvroConfig, OutputFormat};
+    use google_cloud_googleapis::pubsub::v1::push_config::{AuthenticationMethod, NoWrapper, OidcToken, Wrapper};
+    use google_cloud_googleapis::pubsub::v1::{
+        BigQueryConfig, ExpirationPolicy, PushConfig, Subscription as InternalSubscription,
     };
 
     const PROJECT_NAME: &str = "local-project";
@@ -757,6 +1600,40 @@ mod tests {
         subscription.delete(None).await.unwrap();
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_pull_ack_all() {
+        let subscription = create_subscription(false).await;
+        let base = PubsubMessage {
+            data: "test_message".into(),
+            ..Default::default()
+        };
+        publish(Some(vec![base.clone(), base.clone(), base])).await;
+        let messages = subscription.pull(100, None).await.unwrap();
+        assert_eq!(messages.len(), 3);
+
+        // The concurrent nacks are batched.
+        let results = futures_util::future::join_all(messages.iter().map(|m| m.nack())).await;
+        assert!(results.into_iter().all(|r| r.is_ok()));
+
+        let mut messages = vec![];
+        while messages.len() < 3 {
+            messages.extend(subscription.pull(100, None).await.unwrap());
+        }
+        assert_eq!(messages.len(), 3);
+        subscription.ack_all(&messages).await.unwrap();
+        subscription.ack_all(&[]).await.unwrap();
+
+        // Nothing is redelivered after the ack deadline.
+        tokio::time::sleep(Duration::from_secs(11)).await;
+        let subscriber = subscription.clone();
+        let pulled =
+            tokio::time::timeout(Duration::from_secs(3), async move { subscriber.pull(100, None).await }).await;
+        assert!(pulled.map_or(true, |v| v.unwrap().is_empty()));
+
+        subscription.delete(None).await.unwrap();
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_subscription_exactly_once() {
@@ -1050,6 +1927,78 @@ mod tests {
         subscription.delete(None).await.unwrap();
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_replay_from() {
+        let subscription = create_subscription(false).await;
+
+        // acknowledged messages are not retained
+        let err = subscription
+            .replay_from(SystemTime::now() - Duration::from_secs(60), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::FailedPrecondition);
+
+        subscription
+            .update(
+                SubscriptionConfigToUpdate {
+                    retain_acked_messages: Some(true),
+                    message_retention_duration: Some(Duration::new(60 * 60, 0)),
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        // older than the retention
+        let err = subscription
+            .replay_from(SystemTime::now() - Duration::from_secs(60 * 60 * 2), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::FailedPrecondition);
+
+        let started_at = SystemTime::now() - Duration::from_secs(1);
+        publish(None).await;
+        let messages = subscription.pull(100, None).await.unwrap();
+        assert_eq!(messages.len(), 1);
+        ack_all(&messages).await;
+
+        subscription.replay_from(started_at, None).await.unwrap();
+        let replayed = subscription.pull(100, None).await.unwrap();
+        assert_eq!(replayed.len(), 1);
+        assert_eq!(replayed[0].message.message_id, messages[0].message.message_id);
+        ack_all(&replayed).await;
+
+        subscription.delete(None).await.unwrap();
+    }
+
+    #[test]
+    fn test_replay_window() {
+        assert_eq!(SubscriptionConfig::default().replay_window(), None);
+
+        let cfg = SubscriptionConfig {
+            retain_acked_messages: true,
+            ..Default::default()
+        };
+        assert_eq!(cfg.replay_window(), Some(Duration::from_secs(7 * 24 * 60 * 60)));
+
+        let cfg = SubscriptionConfig {
+            retain_acked_messages: true,
+            message_retention_duration: Some(Duration::from_secs(600)),
+            topic_message_retention_duration: Some(Duration::from_secs(3600)),
+            ..Default::default()
+        };
+        assert_eq!(cfg.replay_window(), Some(Duration::from_secs(3600)));
+
+        let cfg = SubscriptionConfig {
+            message_retention_duration: Some(Duration::from_secs(600)),
+            topic_message_retention_duration: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        assert_eq!(cfg.replay_window(), None);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     #[serial]
     async fn test_subscribe_single_subscriber() {
@@ -1096,4 +2045,519 @@ mod tests {
         tokio::time::sleep(Duration::from_secs(8)).await;
         assert_eq!(*checking.lock().unwrap(), msg_count);
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_receive_concurrency() {
+        let subscription = create_subscription(false).await;
+        let msg_count = 20;
+        let messages = (0..msg_count)
+            .map(|i| PubsubMessage {
+                data: format!("message{i}").into(),
+                ..Default::default()
+            })
+            .collect();
+        publish(Some(messages)).await;
+
+        let running = Arc::new(AtomicU32::new(0));
+        let max_running = Arc::new(AtomicU32::new(0));
+        let processed = Arc::new(AtomicU32::new(0));
+        let (running_for_handler, max_for_handler, processed_for_handler) =
+            (running.clone(), max_running.clone(), processed.clone());
+        let cancel = CancellationToken::new();
+        let cancel_receiver = cancel.clone();
+        let handle = tokio::spawn(async move {
+            let config = ReceiveConfig {
+                worker_count: 1,
+                subscriber_config: Some(SubscriberConfig {
+                    concurrency: 3,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            subscription
+                .receive(
+                    move |message, _ctx| {
+                        let running = running_for_handler.clone();
+                        let max_running = max_for_handler.clone();
+                        let processed = processed_for_handler.clone();
+                        async move {
+                            let current = running.fetch_add(1, SeqCst) + 1;
+                            max_running.fetch_max(current, SeqCst);
+                            tokio::time::sleep(Duration::from_millis(200)).await;
+                            running.fetch_sub(1, SeqCst);
+                            message.ack().await.unwrap();
+                            processed.fetch_add(1, SeqCst);
+                        }
+                    },
+                    cancel_receiver,
+                    Some(config),
+                )
+                .await
+        });
+        tokio::time::sleep(Duration::from_secs(8)).await;
+        cancel.cancel();
+        handle.await.unwrap().unwrap();
+        assert_eq!(processed.load(SeqCst), msg_count);
+        assert_eq!(max_running.load(SeqCst), 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_receive_shutdown_nack() {
+        let subscription = create_subscription(false).await;
+        let msg_count = 5;
+        let messages = (0..msg_count)
+            .map(|i| PubsubMessage {
+                data: format!("message{i}").into(),
+                ..Default::default()
+            })
+            .collect();
+        publish(Some(messages)).await;
+
+        let started = Arc::new(AtomicU32::new(0));
+        let started_for_handler = started.clone();
+        let cancel = CancellationToken::new();
+        let cancel_receiver = cancel.clone();
+        let subscription_for_receive = subscription.clone();
+        let handle = tokio::spawn(async move {
+            let config = ReceiveConfig {
+                worker_count: 1,
+                subscriber_config: Some(SubscriberConfig {
+                    // The messages are not redelivered by the expiration during the test.
+                    stream_ack_deadline_seconds: 600,
+                    concurrency: 1,
+                    shutdown_timeout: Duration::from_secs(1),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            };
+            subscription_for_receive
+                .receive(
+                    move |message, _ctx| {
+                        let started = started_for_handler.clone();
+                        async move {
+                            started.fetch_add(1, SeqCst);
+                            // The handler never finishes before the shutdown timeout.
+                            tokio::time::sleep(Duration::from_secs(60)).await;
+                            let _ = message.ack().await;
+                        }
+                    },
+                    cancel_receiver,
+                    Some(config),
+                )
+                .await
+        });
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        cancel.cancel();
+        handle.await.unwrap().unwrap();
+        assert_eq!(started.load(SeqCst), 1);
+
+        // Both the aborted message and the queued messages are redelivered immediately.
+        let mut redelivered = 0;
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        while redelivered < msg_count && tokio::time::Instant::now() < deadline {
+            let messages = subscription.pull(msg_count as i32, None).await.unwrap();
+            redelivered += messages.len();
+            ack_all(&messages).await;
+        }
+        assert_eq!(redelivered, msg_count);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_subscribe_lease_extension() {
+        test_subscribe_slow_ack(Duration::from_secs(60), false).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_subscribe_without_lease_extension() {
+        test_subscribe_slow_ack(Duration::ZERO, true).await;
+    }
+
+    async fn test_subscribe_slow_ack(max_extension: Duration, expect_redelivery: bool) {
+        let subscription = create_subscription(false).await;
+        let config = SubscribeConfig::default().with_subscriber_config(SubscriberConfig {
+            stream_ack_deadline_seconds: 10,
+            max_extension,
+            max_extension_period: Duration::from_secs(10),
+            extension_interval: Duration::from_secs(2),
+            ..Default::default()
+        });
+        let mut iter = subscription.subscribe(Some(config)).await.unwrap();
+        publish(None).await;
+
+        let first = iter.next().await.unwrap();
+        // The ack is delayed beyond the ack deadline of the stream.
+        let redelivered = tokio::time::timeout(Duration::from_secs(25), iter.next()).await;
+        assert_eq!(redelivered.is_ok(), expect_redelivery);
+        if let Ok(Some(message)) = redelivered {
+            assert_eq!(message.message.message_id, first.message.message_id);
+            message.ack().await.unwrap();
+        }
+        // The ack id of the first delivery is already expired if the message was redelivered.
+        let _ = first.ack().await;
+        drop(iter);
+    }
+
+    #[test]
+    fn test_service_agent_email() {
+        assert_eq!(
+            "service-123456789@gcp-sa-pubsub.iam.gserviceaccount.com",
+            service_agent_email(123456789)
+        );
+    }
+
+    #[test]
+    fn test_subscription_config_to_update_field_mask() {
+        let mut config = InternalSubscription::default();
+        let updating = SubscriptionConfigToUpdate {
+            ack_deadline_seconds: Some(30),
+            ..Default::default()
+        }
+        .with_dead_letter_policy("projects/p/topics/dlq", 10)
+        .with_retry_policy(Duration::from_secs(10), Duration::from_millis(60500));
+        let mask = updating.apply(&mut config).unwrap();
+        assert_eq!(vec!["ack_deadline_seconds", "dead_letter_policy", "retry_policy"], mask.paths);
+        assert_eq!(30, config.ack_deadline_seconds);
+        let policy = config.dead_letter_policy.unwrap();
+        assert_eq!("projects/p/topics/dlq", policy.dead_letter_topic);
+        assert_eq!(10, policy.max_delivery_attempts);
+        let policy = config.retry_policy.unwrap();
+        assert_eq!(10, policy.minimum_backoff.unwrap().seconds);
+        let max = policy.maximum_backoff.unwrap();
+        assert_eq!((60, 500_000_000), (max.seconds, max.nanos));
+
+        let mask = SubscriptionConfigToUpdate::default()
+            .apply(&mut InternalSubscription::default())
+            .unwrap();
+        assert!(mask.paths.is_empty());
+    }
+
+    #[test]
+    fn test_subscription_config_to_update_retention() {
+        let mut config = InternalSubscription::default();
+        let updating = SubscriptionConfigToUpdate::default()
+            .with_retain_acked_messages(true)
+            .with_message_retention_duration(Duration::from_secs(2 * 24 * 60 * 60))
+            .with_expiration(Expiration::After(Duration::from_secs(3 * 24 * 60 * 60)));
+        let mask = updating.apply(&mut config).unwrap();
+        assert_eq!(
+            vec![
+                "retain_acked_messages",
+                "message_retention_duration",
+                "expiration_policy"
+            ],
+            mask.paths
+        );
+        assert!(config.retain_acked_messages);
+        assert_eq!(2 * 24 * 60 * 60, config.message_retention_duration.as_ref().unwrap().seconds);
+        assert_eq!(
+            3 * 24 * 60 * 60,
+            config.expiration_policy.as_ref().unwrap().ttl.as_ref().unwrap().seconds
+        );
+
+        // Never is the policy without ttl, distinct from no policy.
+        let mask = SubscriptionConfigToUpdate::default()
+            .with_expiration(Expiration::Never)
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(vec!["expiration_policy"], mask.paths);
+        assert_eq!(Some(ExpirationPolicy { ttl: None }), config.expiration_policy);
+        assert!(SubscriptionConfig::default().expiration_policy.is_none());
+    }
+
+    #[test]
+    fn test_subscription_config_to_update_invalid_policy() {
+        for updating in [
+            SubscriptionConfigToUpdate::default().with_dead_letter_policy("", 10),
+            SubscriptionConfigToUpdate::default().with_dead_letter_policy("dlq", 4),
+            SubscriptionConfigToUpdate::default().with_dead_letter_policy("dlq", 101),
+            SubscriptionConfigToUpdate::default().with_retry_policy(Duration::from_secs(10), Duration::from_secs(601)),
+            SubscriptionConfigToUpdate::default().with_retry_policy(Duration::from_secs(20), Duration::from_secs(10)),
+            SubscriptionConfigToUpdate::default().with_push_endpoint("htps://example.com/push"),
+            SubscriptionConfigToUpdate::default().with_push_endpoint("example.com/push"),
+            SubscriptionConfigToUpdate::default().with_push_endpoint("http://example.com/push"),
+            SubscriptionConfigToUpdate::default().with_push_no_wrapper(true),
+            SubscriptionConfigToUpdate::default()
+                .with_push_endpoint("https://example.com/push")
+                .with_push_oidc_token("", "aud"),
+            SubscriptionConfigToUpdate::default().with_message_retention_duration(Duration::from_secs(9 * 60)),
+            SubscriptionConfigToUpdate::default().with_message_retention_duration(Duration::from_secs(32 * 86400)),
+            SubscriptionConfigToUpdate::default().with_expiration(Expiration::After(Duration::from_secs(3600))),
+            SubscriptionConfigToUpdate::default()
+                .with_message_retention_duration(Duration::from_secs(10 * 86400))
+                .with_expiration(Expiration::After(Duration::from_secs(2 * 86400))),
+            SubscriptionConfigToUpdate::default().with_bigquery_write_metadata(true),
+            SubscriptionConfigToUpdate::default().with_cloud_storage("gs://bucket"),
+            SubscriptionConfigToUpdate::default().with_cloud_storage_text(),
+            SubscriptionConfigToUpdate::default()
+                .with_cloud_storage("bucket")
+                .with_cloud_storage_filename("log-", "/"),
+            SubscriptionConfigToUpdate::default()
+                .with_cloud_storage("bucket")
+                .with_cloud_storage_max_file(Duration::from_secs(30), 0),
+            SubscriptionConfigToUpdate::default()
+                .with_cloud_storage("bucket")
+                .with_cloud_storage_max_file(Duration::from_secs(60), 999),
+            SubscriptionConfigToUpdate::default()
+                .with_push_endpoint("https://example.com/push")
+                .with_bigquery("p.d.t", false),
+            SubscriptionConfigToUpdate::default()
+                .with_bigquery("p.d.t", false)
+                .with_cloud_storage("bucket"),
+        ] {
+            let err = updating.apply(&mut InternalSubscription::default()).unwrap_err();
+            assert_eq!(Code::InvalidArgument, err.code(), "{err:?}");
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_filter() {
+        let cm = ConnectionManager::new(
+            4,
+            "",
+            &Environment::Emulator(EMULATOR.to_string()),
+            &ConnectionOptions::default(),
+        )
+        .await
+        .unwrap();
+        let uuid = Uuid::new_v4().hyphenated().to_string();
+        let subscription = Subscription::new(
+            format!("projects/{PROJECT_NAME}/subscriptions/s{uuid}"),
+            SubscriberClient::new(cm),
+        );
+        let topic_name = format!("projects/{PROJECT_NAME}/topics/test-topic1");
+
+        // too long
+        let config = SubscriptionConfig::default().with_filter(Filter::equals("type", "a".repeat(MAX_FILTER_LENGTH)));
+        let err = subscription.create(&topic_name, config, None).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert!(!subscription.exists(None).await.unwrap());
+
+        let config = SubscriptionConfig::default().with_filter(Filter::equals("type", "order"));
+        subscription.create(&topic_name, config, None).await.unwrap();
+        let (_, config) = subscription.config(None).await.unwrap();
+        assert_eq!(config.filter, r#"attributes.type = "order""#);
+
+        let message = |t: &str| PubsubMessage {
+            data: t.to_string().into(),
+            attributes: HashMap::from([("type".to_string(), t.to_string())]),
+            ..Default::default()
+        };
+        publish(Some(vec![message("user"), message("order")])).await;
+        let messages = subscription.pull(100, None).await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].message.attributes["type"], "order");
+        ack_all(&messages).await;
+
+        subscription.delete(None).await.unwrap();
+    }
+
+    #[test]
+    fn test_bigquery_and_cloud_storage_config() {
+        let config = SubscriptionConfig::default()
+            .with_bigquery("p.d.t", true)
+            .with_bigquery_write_metadata(true)
+            .with_bigquery_drop_unknown_fields(true)
+            .bigquery_config
+            .unwrap();
+        assert_eq!("p.d.t", config.table);
+        assert!(config.use_topic_schema && config.write_metadata && config.drop_unknown_fields);
+
+        let config = SubscriptionConfig::default()
+            .with_cloud_storage("bucket")
+            .with_cloud_storage_filename("log-", ".avro")
+            .with_cloud_storage_max_file(Duration::from_secs(120), 1_000_000)
+            .with_cloud_storage_avro(true)
+            .cloud_storage_config
+            .unwrap();
+        assert_eq!("bucket", config.bucket);
+        assert_eq!(
+            ("log-", ".avro"),
+            (config.filename_prefix.as_str(), config.filename_suffix.as_str())
+        );
+        assert_eq!(120, config.max_duration.unwrap().seconds);
+        assert_eq!(1_000_000, config.max_bytes);
+        assert_eq!(
+            Some(OutputFormat::AvroConfig(AvroConfig { write_metadata: true })),
+            config.output_format
+        );
+
+        // Switching from the push to BigQuery requires clearing the push endpoint.
+        let mut config = InternalSubscription {
+            push_config: Some(PushConfig {
+                push_endpoint: "https://example.com/push".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let err = SubscriptionConfigToUpdate::default()
+            .with_bigquery("p.d.t", false)
+            .apply(&mut config.clone())
+            .unwrap_err();
+        assert_eq!(Code::InvalidArgument, err.code());
+        let mask = SubscriptionConfigToUpdate::default()
+            .with_push_endpoint("")
+            .with_bigquery("p.d.t", false)
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(vec!["push_config", "bigquery_config"], mask.paths);
+
+        // Update only the Cloud Storage config.
+        let mask = SubscriptionConfigToUpdate::default()
+            .with_bigquery("", false)
+            .with_cloud_storage("bucket")
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(vec!["bigquery_config", "cloud_storage_config"], mask.paths);
+        let mask = SubscriptionConfigToUpdate::default()
+            .with_cloud_storage("bucket")
+            .with_cloud_storage_text()
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(vec!["cloud_storage_config"], mask.paths);
+    }
+
+    #[test]
+    fn test_delivery_state() {
+        let config = SubscriptionConfig::from(InternalSubscription {
+            state: SubscriptionState::ResourceError.into(),
+            bigquery_config: Some(BigQueryConfig {
+                table: "p.d.t".to_string(),
+                state: BigQueryState::PermissionDenied.into(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        assert_eq!(SubscriptionState::ResourceError, config.subscription_state());
+        assert_eq!(Some(BigQueryState::PermissionDenied), config.bigquery_state());
+        assert_eq!(None, config.cloud_storage_state());
+        assert_eq!(
+            SubscriptionState::Unspecified,
+            SubscriptionConfig::default().subscription_state()
+        );
+    }
+
+    #[test]
+    fn test_push_config() {
+        let config = SubscriptionConfig::default()
+            .with_push_endpoint("https://example.com/push")
+            .with_push_oidc_token("sa@p.iam.gserviceaccount.com", "https://example.com")
+            .with_push_no_wrapper(true)
+            .push_config
+            .unwrap();
+        assert_eq!("https://example.com/push", config.push_endpoint);
+        assert_eq!(
+            Some(AuthenticationMethod::OidcToken(OidcToken {
+                service_account_email: "sa@p.iam.gserviceaccount.com".to_string(),
+                audience: "https://example.com".to_string(),
+            })),
+            config.authentication_method
+        );
+        assert_eq!(Some(Wrapper::NoWrapper(NoWrapper { write_metadata: true })), config.wrapper);
+
+        let mut config = InternalSubscription::default();
+        let mask = SubscriptionConfigToUpdate::default()
+            .with_push_endpoint("https://example.com/push")
+            .apply(&mut config)
+            .unwrap();
+        assert_eq!(vec!["push_config"], mask.paths);
+        assert_eq!("https://example.com/push", config.push_config.unwrap().push_endpoint);
+
+        // The empty endpoint switches the subscription to the pull.
+        let mask = SubscriptionConfigToUpdate::default()
+            .with_push_endpoint("")
+            .apply(&mut InternalSubscription::default())
+            .unwrap();
+        assert_eq!(vec!["push_config"], mask.paths);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_modify_push_config() {
+        let subscription = create_subscription(false).await;
+        let endpoint = "https://example.com/push";
+
+        subscription
+            .modify_push_config(
+                Some(PushConfig {
+                    push_endpoint: endpoint.to_string(),
+                    ..Default::default()
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        let (_, config) = subscription.config(None).await.unwrap();
+        assert_eq!(endpoint, config.push_config.unwrap().push_endpoint);
+
+        subscription.modify_push_config(None, None).await.unwrap();
+        let (_, config) = subscription.config(None).await.unwrap();
+        assert!(config.push_config.is_none_or(|v| v.push_endpoint.is_empty()));
+
+        let updating = SubscriptionConfigToUpdate::default().with_push_endpoint(endpoint);
+        let (_, config) = subscription.update(updating, None).await.unwrap();
+        assert_eq!(endpoint, config.push_config.unwrap().push_endpoint);
+
+        // The invalid endpoint is rejected without calling the server.
+        let err = subscription
+            .modify_push_config(
+                Some(PushConfig {
+                    push_endpoint: "example.com/push".to_string(),
+                    ..Default::default()
+                }),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(Code::InvalidArgument, err.code());
+
+        subscription.delete(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_dead_letter_and_retry_policy() {
+        let cm = ConnectionManager::new(
+            4,
+            "",
+            &Environment::Emulator(EMULATOR.to_string()),
+            &ConnectionOptions::default(),
+        )
+        .await
+        .unwrap();
+        let uuid = Uuid::new_v4().hyphenated().to_string();
+        let subscription_name = format!("projects/{PROJECT_NAME}/subscriptions/s{uuid}");
+        let topic_name = format!("projects/{PROJECT_NAME}/topics/test-topic1");
+        let subscription = Subscription::new(subscription_name, SubscriberClient::new(cm));
+
+        // The topic id of the dead letter topic is qualified with the project of the subscription.
+        let config = SubscriptionConfig::default()
+            .with_dead_letter_policy("test-topic1", 5)
+            .with_retry_policy(Duration::from_secs(10), Duration::from_secs(60));
+        subscription.create(topic_name.as_str(), config, None).await.unwrap();
+        let (_, config) = subscription.config(None).await.unwrap();
+        let policy = config.dead_letter_policy.unwrap();
+        assert_eq!(format!("projects/{PROJECT_NAME}/topics/test-topic1"), policy.dead_letter_topic);
+        assert_eq!(5, policy.max_delivery_attempts);
+        assert_eq!(60, config.retry_policy.unwrap().maximum_backoff.unwrap().seconds);
+
+        let updating = SubscriptionConfigToUpdate::default().with_dead_letter_policy("test-topic1", 20);
+        let (_, config) = subscription.update(updating, None).await.unwrap();
+        assert_eq!(20, config.dead_letter_policy.unwrap().max_delivery_attempts);
+        assert_eq!(60, config.retry_policy.unwrap().maximum_backoff.unwrap().seconds);
+
+        // Invalid policies are rejected without calling the server.
+        let config = SubscriptionConfig::default().with_dead_letter_policy("test-topic1", 1);
+        let err = subscription
+            .create(topic_name.as_str(), config, None)
+            .await
+            .unwrap_err();
+        assert_eq!(Code::InvalidArgument, err.code());
+
+        subscription.delete(None).await.unwrap();
+    }
 }
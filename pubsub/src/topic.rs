@@ -1,19 +1,23 @@
 use std::collections::HashMap;
 use std::time::Duration;
 
-use prost_types::DurationError;
+use prost_types::{DurationError, FieldMask};
 
+use tokio::task::JoinSet;
+
+use google_cloud_gax::grpc::codegen::tokio_stream::{Stream, StreamExt};
 use google_cloud_gax::grpc::{Code, Status};
 use google_cloud_gax::retry::RetrySetting;
 use google_cloud_googleapis::pubsub::v1::{
-    DeleteTopicRequest, GetTopicRequest, ListTopicSubscriptionsRequest, MessageStoragePolicy, SchemaSettings,
-    Topic as InternalTopic,
+    DeleteTopicRequest, GetTopicRequest, ListTopicSnapshotsRequest, ListTopicSubscriptionsRequest,
+    MessageStoragePolicy, SchemaSettings, Topic as InternalTopic, UpdateTopicRequest,
 };
 
 use crate::apiv1::publisher_client::PublisherClient;
 use crate::apiv1::subscriber_client::SubscriberClient;
 use crate::publisher::{Publisher, PublisherConfig};
-use crate::subscription::Subscription;
+use crate::subscription::{Subscription, SubscriptionConfig};
+use crate::util::validate_message_retention_duration;
 
 #[derive(Debug, Clone)]
 pub struct TopicConfig {
@@ -38,6 +42,64 @@ impl Default for TopicConfig {
     }
 }
 
+impl TopicConfig {
+    /// with_message_retention_duration retains the published messages for the duration (10 minutes to 31 days)
+    /// even after they are acknowledged, so that the subscriptions can seek to a timestamp in the window.
+    /// The messages are not retained by default.
+    pub fn with_message_retention_duration(mut self, duration: Duration) -> Self {
+        self.message_retention_duration = Some(duration);
+        self
+    }
+}
+
+impl From<InternalTopic> for TopicConfig {
+    fn from(f: InternalTopic) -> Self {
+        Self {
+            labels: f.labels,
+            message_storage_policy: f.message_storage_policy,
+            kms_key_name: f.kms_key_name,
+            schema_settings: f.schema_settings,
+            satisfies_pzs: f.satisfies_pzs,
+            message_retention_duration: f
+                .message_retention_duration
+                .map(|v| Duration::new(v.seconds as u64, v.nanos as u32)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TopicConfigToUpdate {
+    pub labels: Option<HashMap<String, String>>,
+    pub message_retention_duration: Option<Duration>,
+}
+
+impl TopicConfigToUpdate {
+    /// with_message_retention_duration updates the message retention duration (10 minutes to 31 days).
+    /// See [`TopicConfig::with_message_retention_duration`].
+    pub fn with_message_retention_duration(mut self, duration: Duration) -> Self {
+        self.message_retention_duration = Some(duration);
+        self
+    }
+
+    /// apply sets the fields to update to the topic and returns the field mask of them.
+    fn apply(self, topic: &mut InternalTopic) -> Result<FieldMask, Box<Status>> {
+        let mut paths = vec![];
+        if let Some(v) = self.labels {
+            topic.labels = v;
+            paths.push("labels".to_string());
+        }
+        if let Some(v) = self.message_retention_duration {
+            validate_message_retention_duration(v)?;
+            topic.message_retention_duration = Some(
+                v.try_into()
+                    .map_err(|err: DurationError| Status::internal(err.to_string()))?,
+            );
+            paths.push("message_retention_duration".to_string());
+        }
+        Ok(FieldMask { paths })
+    }
+}
+
 /// Topic is a reference to a PubSub topic.
 ///
 /// The methods of Topic are safe for use by multiple tasks.
@@ -72,6 +134,9 @@ impl Topic {
     /// create creates the topic.
     pub async fn create(&self, cfg: Option<TopicConfig>, retry: Option<RetrySetting>) -> Result<(), Status> {
         let topic_config = cfg.unwrap_or_default();
+        if let Some(v) = topic_config.message_retention_duration {
+            validate_message_retention_duration(v).map_err(|e| *e)?;
+        }
         let req = InternalTopic {
             name: self.fully_qualified_name().to_string(),
             labels: topic_config.labels,
@@ -116,6 +181,44 @@ impl Topic {
         }
     }
 
+    /// config fetches the current configuration for the topic.
+    pub async fn config(&self, retry: Option<RetrySetting>) -> Result<TopicConfig, Status> {
+        let req = GetTopicRequest {
+            topic: self.fqtn.to_string(),
+        };
+        self.pubc.get_topic(req, retry).await.map(|v| v.into_inner().into())
+    }
+
+    /// update changes an existing topic according to the fields set in updating.
+    /// It returns the new TopicConfig.
+    pub async fn update(
+        &self,
+        updating: TopicConfigToUpdate,
+        retry: Option<RetrySetting>,
+    ) -> Result<TopicConfig, Status> {
+        let mut topic = InternalTopic {
+            name: self.fqtn.to_string(),
+            ..Default::default()
+        };
+        let update_mask = updating.apply(&mut topic).map_err(|e| *e)?;
+        let req = UpdateTopicRequest {
+            topic: Some(topic),
+            update_mask: Some(update_mask),
+        };
+        self.pubc.update_topic(req, retry).await.map(|v| v.into_inner().into())
+    }
+
+    /// schema_settings fetches the schema settings of the topic. It is None if the topic has no schema.
+    pub async fn schema_settings(&self, retry: Option<RetrySetting>) -> Result<Option<SchemaSettings>, Status> {
+        let req = GetTopicRequest {
+            topic: self.fqtn.to_string(),
+        };
+        self.pubc
+            .get_topic(req, retry)
+            .await
+            .map(|v| v.into_inner().schema_settings)
+    }
+
     /// Subscriptions returns an iterator which returns the subscriptions for this topic.
     pub async fn subscriptions(&self, retry: Option<RetrySetting>) -> Result<Vec<Subscription>, Status> {
         let req = ListTopicSubscriptionsRequest {
@@ -129,10 +232,89 @@ impl Topic {
                 .collect()
         })
     }
+
+    /// subscriptions_stream is the lazy version of `subscriptions`.
+    /// The subscriptions are listed page by page as the stream is consumed.
+    #[allow(clippy::result_large_err)]
+    pub fn subscriptions_stream(
+        &self,
+        retry: Option<RetrySetting>,
+    ) -> impl Stream<Item = Result<Subscription, Status>> + Send + 'static {
+        let req = ListTopicSubscriptionsRequest {
+            topic: self.fqtn.to_string(),
+            page_size: 0,
+            page_token: "".to_string(),
+        };
+        let subc = self.subc.clone();
+        self.pubc
+            .list_topic_subscriptions_stream(req, retry)
+            .map(move |v| v.map(|sub_name| Subscription::new(sub_name, subc.clone())))
+    }
+
+    /// subscription_configs lists the subscriptions of the topic with their configurations.
+    /// The topic listing has only the names of the subscriptions, so the configurations are fetched with
+    /// up to `concurrency` GetSubscription calls at once. The subscriptions deleted while listing are skipped.
+    /// The order is the same as `subscriptions`.
+    pub async fn subscription_configs(
+        &self,
+        concurrency: usize,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<(Subscription, SubscriptionConfig)>, Status> {
+        let mut pending = self.subscriptions(retry.clone()).await?.into_iter().enumerate();
+        let mut fetched = Vec::with_capacity(pending.len());
+        let mut tasks = JoinSet::new();
+        loop {
+            while tasks.len() < concurrency.max(1) {
+                let Some((index, subscription)) = pending.next() else {
+                    break;
+                };
+                let retry = retry.clone();
+                tasks.spawn(async move {
+                    let result = subscription.config(retry).await;
+                    (index, subscription, result)
+                });
+            }
+            let Some(joined) = tasks.join_next().await else {
+                break;
+            };
+            let (index, subscription, result) = joined.map_err(|e| Status::internal(e.to_string()))?;
+            match result {
+                Ok((_, config)) => fetched.push((index, subscription, config)),
+                Err(e) if e.code() == Code::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+        fetched.sort_by_key(|(index, _, _)| *index);
+        Ok(fetched.into_iter().map(|(_, s, c)| (s, c)).collect())
+    }
+
+    /// snapshots lists the names of the snapshots of the topic.
+    pub async fn snapshots(&self, retry: Option<RetrySetting>) -> Result<Vec<String>, Status> {
+        let req = ListTopicSnapshotsRequest {
+            topic: self.fqtn.to_string(),
+            page_size: 0,
+            page_token: "".to_string(),
+        };
+        self.pubc.list_topic_snapshots(req, retry).await
+    }
+
+    /// snapshots_stream is the lazy version of `snapshots`.
+    pub fn snapshots_stream(
+        &self,
+        retry: Option<RetrySetting>,
+    ) -> impl Stream<Item = Result<String, Status>> + Send + 'static {
+        let req = ListTopicSnapshotsRequest {
+            topic: self.fqtn.to_string(),
+            page_size: 0,
+            page_token: "".to_string(),
+        };
+        self.pubc.list_topic_snapshots_stream(req, retry)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::time::Duration;
 
     use serial_test::serial;
@@ -141,6 +323,7 @@ mod tests {
     use uuid::Uuid;
 
     use google_cloud_gax::conn::{ConnectionOptions, Environment};
+    use google_cloud_gax::grpc::codegen::tokio_stream::StreamExt;
     use google_cloud_gax::grpc::{Code, Status};
     use google_cloud_googleapis::pubsub::v1::PubsubMessage;
 
@@ -148,7 +331,8 @@ mod tests {
     use crate::apiv1::publisher_client::PublisherClient;
     use crate::apiv1::subscriber_client::SubscriberClient;
     use crate::publisher::{Publisher, PublisherConfig};
-    use crate::topic::Topic;
+    use crate::subscription::{Subscription, SubscriptionConfig};
+    use crate::topic::{Topic, TopicConfig, TopicConfigToUpdate};
 
     #[ctor::ctor]
     fn init() {
@@ -198,6 +382,7 @@ mod tests {
         let config = PublisherConfig {
             flush_interval: Duration::from_secs(10),
             bundle_size: 11,
+            enable_message_ordering: true,
             ..Default::default()
         };
         let publisher = topic.new_publisher(Some(config));
@@ -231,7 +416,7 @@ mod tests {
         for result in results {
             let err = result.get().await.unwrap_err();
             assert_eq!(Code::Cancelled, err.code());
-            assert_eq!("closed", err.message());
+            assert_eq!("publisher closed", err.message());
         }
 
         topic.delete(None).await.unwrap();
@@ -277,6 +462,117 @@ mod tests {
         publish_after_shutdown(true).await;
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_shutdown_timeout() {
+        let topic = create_topic().await;
+        let publisher = topic.new_publisher(Some(PublisherConfig {
+            flush_interval: Duration::from_secs(60),
+            bundle_size: 100,
+            shutdown_timeout: Duration::ZERO,
+            ..Default::default()
+        }));
+
+        let tasks = publish(publisher.clone()).await;
+        sleep(Duration::from_millis(500)).await;
+
+        // The workers are aborted before the pending messages are published.
+        let mut publisher = publisher;
+        publisher.shutdown().await;
+        for task in tasks {
+            let err = task.await.unwrap().unwrap_err();
+            assert_eq!(Code::Cancelled, err.code());
+        }
+
+        // The aborted messages are not waited for.
+        tokio::time::timeout(Duration::from_secs(1), publisher.flush())
+            .await
+            .unwrap();
+        topic.delete(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_message_retention() {
+        let topic = create_topic().await;
+        let err = topic
+            .update(
+                TopicConfigToUpdate::default().with_message_retention_duration(Duration::from_secs(60)),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(Code::InvalidArgument, err.code());
+
+        let config = topic
+            .update(
+                TopicConfigToUpdate::default().with_message_retention_duration(Duration::from_secs(3600)),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(Some(Duration::from_secs(3600)), config.message_retention_duration);
+        let config = topic.config(None).await.unwrap();
+        assert_eq!(Some(Duration::from_secs(3600)), config.message_retention_duration);
+        topic.delete(None).await.unwrap();
+
+        let config = TopicConfig::default().with_message_retention_duration(Duration::from_secs(32 * 24 * 60 * 60));
+        let err = topic.create(Some(config), None).await.unwrap_err();
+        assert_eq!(Code::InvalidArgument, err.code());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_list_subscriptions() {
+        let topic = create_topic().await;
+        let mut deadlines = HashMap::new();
+        for i in 0..3 {
+            let uuid = Uuid::new_v4().hyphenated().to_string();
+            let subscription =
+                Subscription::new(format!("projects/local-project/subscriptions/s{uuid}"), topic.subc.clone());
+            let config = SubscriptionConfig {
+                ack_deadline_seconds: 10 + i,
+                ..Default::default()
+            };
+            subscription
+                .create(topic.fully_qualified_name(), config, None)
+                .await
+                .unwrap();
+            deadlines.insert(subscription.fully_qualified_name().to_string(), 10 + i);
+        }
+        let mut names: Vec<String> = deadlines.keys().cloned().collect();
+        names.sort();
+
+        let listed = topic
+            .subscriptions_stream(None)
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .unwrap();
+        let mut listed: Vec<String> = listed.iter().map(|v| v.fully_qualified_name().to_string()).collect();
+        listed.sort();
+        assert_eq!(names, listed);
+
+        // The deleted subscription is skipped.
+        Subscription::new(names[0].clone(), topic.subc.clone())
+            .delete(None)
+            .await
+            .unwrap();
+        let configs = topic.subscription_configs(2, None).await.unwrap();
+        let subscriptions = topic.subscriptions(None).await.unwrap();
+        assert_eq!(subscriptions.len(), configs.len());
+        for ((subscription, config), expected) in configs.iter().zip(subscriptions.iter()) {
+            assert_eq!(subscription.fully_qualified_name(), expected.fully_qualified_name());
+            assert_eq!(config.ack_deadline_seconds, deadlines[subscription.fully_qualified_name()]);
+        }
+
+        assert!(topic.snapshots(None).await.unwrap().is_empty());
+        assert_eq!(topic.snapshots_stream(None).collect::<Vec<_>>().await.len(), 0);
+        for subscription in subscriptions {
+            subscription.delete(None).await.unwrap();
+        }
+        topic.delete(None).await.unwrap();
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_publish_immediately() {
@@ -298,4 +594,73 @@ mod tests {
         publisher.shutdown().await;
         topic.delete(None).await.unwrap();
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_flush() {
+        let topic = create_topic().await;
+        let publisher = topic.new_publisher(Some(PublisherConfig {
+            flush_interval: Duration::from_secs(60),
+            bundle_size: 100,
+            ..Default::default()
+        }));
+
+        // The messages are not sent until the flush_interval elapses or the bundle is full.
+        let tasks = publish(publisher.clone()).await;
+        sleep(Duration::from_millis(500)).await;
+        assert!(tasks.iter().all(|task| !task.is_finished()));
+
+        // flush resolves when the results of all the messages are known.
+        tokio::time::timeout(Duration::from_secs(5), publisher.flush())
+            .await
+            .unwrap();
+        for task in tasks {
+            assert!(task.is_finished());
+            assert!(!task.await.unwrap().unwrap().is_empty());
+        }
+
+        // flush without the pending messages returns immediately.
+        publisher.flush().await;
+
+        let mut publisher = publisher;
+        publisher.shutdown().await;
+        topic.delete(None).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_publish_bundle_byte_size() {
+        let topic = create_topic().await;
+        let publisher = topic.new_publisher(Some(PublisherConfig {
+            flush_interval: Duration::from_secs(60),
+            bundle_size: 100,
+            bundle_byte_size: 1000,
+            ..Default::default()
+        }));
+
+        // The bundle is sent before it exceeds the bundle_byte_size.
+        let message = |size: usize| PubsubMessage {
+            data: "a".repeat(size).into(),
+            ..Default::default()
+        };
+        let awaiters = publisher.publish_bulk((0..3).map(|_| message(400)).collect()).await;
+        let mut awaiters = awaiters.into_iter();
+        for awaiter in awaiters.by_ref().take(2) {
+            let result = tokio::time::timeout(Duration::from_secs(5), awaiter.get()).await;
+            assert!(result.unwrap().is_ok());
+        }
+
+        // The oversized message fails immediately without being queued.
+        let err = publisher.publish(message(1000)).await.get().await.unwrap_err();
+        assert_eq!(Code::InvalidArgument, err.code());
+        let err = publisher.publish_blocking(message(1000)).get().await.unwrap_err();
+        assert_eq!(Code::InvalidArgument, err.code());
+
+        let mut publisher = publisher;
+        publisher.shutdown().await;
+        for awaiter in awaiters {
+            assert!(awaiter.get().await.is_ok());
+        }
+        topic.delete(None).await.unwrap();
+    }
 }
@@ -1,3 +1,8 @@
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use google_cloud_gax::grpc::Status;
+
 pub(crate) trait ToUsize {
     fn to_usize(&self) -> usize;
 }
@@ -7,3 +12,17 @@ impl ToUsize for &str {
         self.as_bytes().iter().map(|v| *v as usize).sum()
     }
 }
+
+/// The range of the message retention duration of the topics and the subscriptions.
+const MESSAGE_RETENTION_RANGE: RangeInclusive<Duration> =
+    Duration::from_secs(10 * 60)..=Duration::from_secs(31 * 24 * 60 * 60);
+
+/// validate_message_retention_duration checks that the message retention duration is between 10 minutes and 31 days.
+pub(crate) fn validate_message_retention_duration(duration: Duration) -> Result<(), Box<Status>> {
+    if !MESSAGE_RETENTION_RANGE.contains(&duration) {
+        return Err(Box::new(Status::invalid_argument(format!(
+            "message_retention_duration must be between 10 minutes and 31 days: {duration:?}"
+        ))));
+    }
+    Ok(())
+}
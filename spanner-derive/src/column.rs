@@ -4,64 +4,206 @@ use syn::spanned::Spanned;
 use syn::Lit::Str;
 use syn::Meta::{List, NameValue, Path};
 use syn::NestedMeta::Meta;
-use syn::{Error, Field};
+use syn::{Attribute, Error, Field};
 
-use crate::symbol::{COLUMN, COLUMN_NAME, COMMIT_TIMESTAMP};
+use crate::symbol::{
+    ALLOW_MISSING_COLUMNS, COLUMN, COLUMN_NAME, COMMIT_TIMESTAMP, COMMIT_TIMESTAMP_CAMEL, DEFAULT, RENAME_ALL, SKIP,
+    SKIP_DESERIALIZING, SKIP_SERIALIZING, TABLE,
+};
+
+/// Container is the `#[spanner(...)]` attributes of the struct.
+pub(crate) struct Container {
+    rename_all: Case,
+    allow_missing_columns: bool,
+    /// The table name of `TableMetadata`. `TableMetadata` is generated only with it.
+    pub table: Option<String>,
+}
+
+impl Container {
+    /// Extract out the `#[spanner(...)]` attributes from a struct.
+    pub(crate) fn parse(attrs: &[Attribute]) -> Result<Self, Error> {
+        // The column name is the pascal case of the field name by default.
+        let mut rename_all = None;
+        let mut allow_missing_columns = false;
+        let mut table = None;
+        for meta_item in get_all_meta_items(attrs)? {
+            match &meta_item {
+                // Parse `#[spanner(allow_missing_columns)]`
+                Meta(Path(word)) if word == ALLOW_MISSING_COLUMNS => {
+                    allow_missing_columns = true;
+                }
+                // Parse `#[spanner(table = "Users")]`
+                Meta(NameValue(m)) if m.path == TABLE => {
+                    if table.is_some() {
+                        return Err(Error::new(m.path.span(), "duplicate spanner attribute `table`"));
+                    }
+                    match &m.lit {
+                        Str(s) if !s.value().is_empty() => table = Some(s.value()),
+                        Str(s) => return Err(Error::new(s.span(), "table name must not be empty")),
+                        lit => return Err(Error::new(lit.span(), "expected string literal")),
+                    }
+                }
+                // Parse `#[spanner(rename_all = "camelCase")]`
+                Meta(NameValue(m)) if m.path == RENAME_ALL => {
+                    if rename_all.is_some() {
+                        return Err(Error::new(m.path.span(), "duplicate spanner attribute `rename_all`"));
+                    }
+                    rename_all = Some(match &m.lit {
+                        Str(s) => match s.value().as_str() {
+                            "camelCase" => Case::Camel,
+                            "PascalCase" => Case::Pascal,
+                            "snake_case" => Case::Snake,
+                            _ => return Err(Error::new(
+                                s.span(),
+                                "unknown rename rule, expected one of \"camelCase\", \"PascalCase\", \"snake_case\"",
+                            )),
+                        },
+                        lit => return Err(Error::new(lit.span(), "expected string literal")),
+                    });
+                }
+                other => return Err(unknown_attribute(other, "unknown spanner container attribute")),
+            }
+        }
+        Ok(Self {
+            rename_all: rename_all.unwrap_or(Case::Pascal),
+            allow_missing_columns,
+            table,
+        })
+    }
+}
 
 pub(crate) struct Column<'a> {
     field: &'a Field,
+    rename_all: Case,
     pub column_name: Option<String>,
     pub commit_timestamp: bool,
+    /// The field is not written to the mutations.
+    pub skip_serializing: bool,
+    /// The field is not read from the rows and is populated by `Default::default()`.
+    pub skip_deserializing: bool,
+    /// The field is populated by `Default::default()` when the column is not in the row.
+    pub default_if_missing: bool,
 }
 
 impl<'a> Column<'a> {
     pub(crate) fn name(&self) -> String {
         match &self.column_name {
             Some(v) => v.to_string(),
-            None => {
-                let field_var = self.field.ident.as_ref().unwrap();
-                field_var.unraw().to_string().to_case(Case::Pascal)
-            }
+            None => self.var().unraw().to_string().to_case(self.rename_all),
         }
     }
-}
 
-impl<'a> From<&'a Field> for Column<'a> {
-    /// Extract out the `#[column(...)]` attributes from a struct field.
-    fn from(field: &'a Field) -> Self {
+    pub(crate) fn ty(&self) -> &'a syn::Type {
+        &self.field.ty
+    }
+
+    pub(crate) fn var(&self) -> &'a syn::Ident {
+        // Unnamed fields are rejected by parse_columns.
+        self.field.ident.as_ref().unwrap()
+    }
+
+    /// Extract out the `#[spanner(...)]` attributes from a struct field.
+    pub(crate) fn parse(field: &'a Field, container: &Container) -> Result<Self, Error> {
         let mut commit_timestamp = false;
         let mut column_name = None;
-        for meta_item in field.attrs.iter().flat_map(|attr| get_meta_items(attr).unwrap()) {
+        let mut skip_serializing = false;
+        let mut skip_deserializing = false;
+        let mut default_if_missing = container.allow_missing_columns;
+        for meta_item in get_all_meta_items(&field.attrs)? {
             match &meta_item {
-                // Parse `#[column(name = "foo")]`
+                // Parse `#[spanner(name = "foo")]`
                 Meta(NameValue(m)) if m.path == COLUMN_NAME => {
-                    if let Str(s) = &m.lit {
-                        column_name = Some(s.value());
+                    if column_name.is_some() {
+                        return Err(Error::new(m.path.span(), "duplicate spanner attribute `name`"));
+                    }
+                    match &m.lit {
+                        Str(s) if !s.value().is_empty() => column_name = Some(s.value()),
+                        Str(s) => return Err(Error::new(s.span(), "column name must not be empty")),
+                        lit => return Err(Error::new(lit.span(), "expected string literal")),
                     }
                 }
-                // Parse `#[column(commitTimestamp)]`
-                Meta(Path(word)) if word == COMMIT_TIMESTAMP => {
+                // Parse `#[spanner(commit_timestamp)]` or `#[spanner(commitTimestamp)]`
+                Meta(Path(word)) if word == COMMIT_TIMESTAMP || word == COMMIT_TIMESTAMP_CAMEL => {
                     commit_timestamp = true;
                 }
-                _ => {}
+                // Parse `#[spanner(skip)]`
+                Meta(Path(word)) if word == SKIP => {
+                    skip_serializing = true;
+                    skip_deserializing = true;
+                }
+                // Parse `#[spanner(skip_serializing)]`
+                Meta(Path(word)) if word == SKIP_SERIALIZING => {
+                    skip_serializing = true;
+                }
+                // Parse `#[spanner(default)]`
+                Meta(Path(word)) if word == DEFAULT => {
+                    default_if_missing = true;
+                }
+                // Parse `#[spanner(skip_deserializing)]`
+                Meta(Path(word)) if word == SKIP_DESERIALIZING => {
+                    skip_deserializing = true;
+                }
+                other => return Err(unknown_attribute(other, "unknown spanner field attribute")),
             }
         }
 
-        Self {
+        if commit_timestamp && skip_serializing {
+            return Err(Error::new(
+                field.ident.span(),
+                "`commit_timestamp` can't be used with `skip` or `skip_serializing`",
+            ));
+        }
+
+        Ok(Self {
             field,
+            rename_all: container.rename_all,
             commit_timestamp,
             column_name,
-        }
+            skip_serializing,
+            skip_deserializing,
+            default_if_missing,
+        })
     }
 }
 
-fn get_meta_items(attr: &syn::Attribute) -> Result<Vec<syn::NestedMeta>, Error> {
+/// parse_columns extracts out the container and field attributes of the struct.
+pub(crate) fn parse_columns(item: &syn::ItemStruct) -> Result<Vec<Column<'_>>, Error> {
+    if let syn::Fields::Unnamed(_) = item.fields {
+        return Err(Error::new(
+            item.ident.span(),
+            "spanner derive supports only structs with named fields",
+        ));
+    }
+    let container = Container::parse(&item.attrs)?;
+    item.fields
+        .iter()
+        .map(|field| Column::parse(field, &container))
+        .collect()
+}
+
+pub(crate) fn get_all_meta_items(attrs: &[Attribute]) -> Result<Vec<syn::NestedMeta>, Error> {
+    let mut items = Vec::new();
+    for attr in attrs {
+        items.extend(get_meta_items(attr)?);
+    }
+    Ok(items)
+}
+
+fn get_meta_items(attr: &Attribute) -> Result<Vec<syn::NestedMeta>, Error> {
     if attr.path != COLUMN {
         return Ok(Vec::new());
     }
 
     match attr.parse_meta()? {
         List(meta) => Ok(meta.nested.into_iter().collect()),
-        _ => Err(Error::new(attr.span(), "expected [column(...)]")),
+        _ => Err(Error::new(attr.path.span(), "expected #[spanner(...)]")),
+    }
+}
+
+/// unknown_attribute creates the error pointing the name of the invalid attribute.
+pub(crate) fn unknown_attribute(meta: &syn::NestedMeta, message: &str) -> Error {
+    match meta {
+        Meta(m) => Error::new(m.path().span(), message),
+        syn::NestedMeta::Lit(lit) => Error::new(lit.span(), message),
     }
 }
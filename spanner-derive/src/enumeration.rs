@@ -0,0 +1,202 @@
+use quote::{quote, ToTokens};
+use syn::spanned::Spanned;
+use syn::Lit::Str;
+use syn::Meta::{NameValue, Path};
+use syn::NestedMeta::Meta;
+use syn::{Error, Fields, ItemEnum, Variant};
+
+use crate::column::{get_all_meta_items, unknown_attribute};
+use crate::symbol::{COLUMN_NAME, OTHER, REPR};
+
+/// Repr is the column type storing the enum.
+#[derive(Clone, Copy, PartialEq)]
+enum Repr {
+    /// STRING column storing the variant name.
+    String,
+    /// INT64 column storing the explicit discriminant.
+    Int64,
+}
+
+struct EnumVariant<'a> {
+    variant: &'a Variant,
+    name: Option<String>,
+    other: bool,
+}
+
+impl<'a> EnumVariant<'a> {
+    /// holds_value returns true if the variant is the `other` variant holding the stored value like `Other(String)`.
+    fn holds_value(&self) -> bool {
+        matches!(self.variant.fields, Fields::Unnamed(_))
+    }
+}
+
+/// Extract out the `#[spanner(...)]` attributes from an enum.
+fn parse_repr(item: &ItemEnum) -> Result<Repr, Error> {
+    let mut repr = None;
+    for meta_item in get_all_meta_items(&item.attrs)? {
+        match &meta_item {
+            // Parse `#[spanner(repr = "string")]`
+            Meta(NameValue(m)) if m.path == REPR => {
+                if repr.is_some() {
+                    return Err(Error::new(m.path.span(), "duplicate spanner attribute `repr`"));
+                }
+                repr = Some(match &m.lit {
+                    Str(s) => match s.value().as_str() {
+                        "string" => Repr::String,
+                        "int64" => Repr::Int64,
+                        _ => return Err(Error::new(s.span(), "unknown repr, expected one of \"string\", \"int64\"")),
+                    },
+                    lit => return Err(Error::new(lit.span(), "expected string literal")),
+                });
+            }
+            other => return Err(unknown_attribute(other, "unknown spanner enum attribute")),
+        }
+    }
+    Ok(repr.unwrap_or(Repr::String))
+}
+
+/// Extract out the `#[spanner(...)]` attributes from an enum variant.
+fn parse_variant(variant: &Variant, repr: Repr) -> Result<EnumVariant<'_>, Error> {
+    let mut name = None;
+    let mut other = false;
+    for meta_item in get_all_meta_items(&variant.attrs)? {
+        match &meta_item {
+            // Parse `#[spanner(name = "foo")]`
+            Meta(NameValue(m)) if m.path == COLUMN_NAME => {
+                if repr != Repr::String {
+                    return Err(Error::new(m.path.span(), "`name` requires repr = \"string\""));
+                }
+                match &m.lit {
+                    Str(s) => name = Some(s.value()),
+                    lit => return Err(Error::new(lit.span(), "expected string literal")),
+                }
+            }
+            // Parse `#[spanner(other)]`
+            Meta(Path(word)) if word == OTHER => {
+                other = true;
+            }
+            other => return Err(unknown_attribute(other, "unknown spanner variant attribute")),
+        }
+    }
+
+    match &variant.fields {
+        Fields::Unit => {}
+        Fields::Unnamed(fields) if other && fields.unnamed.len() == 1 => {}
+        _ => {
+            return Err(Error::new(
+                variant.ident.span(),
+                "only unit variants are supported, except the `other` variant holding the stored value like `Other(String)`",
+            ))
+        }
+    }
+    let variant = EnumVariant { variant, name, other };
+    if repr == Repr::Int64 && !variant.holds_value() && variant.variant.discriminant.is_none() {
+        return Err(Error::new(
+            variant.variant.ident.span(),
+            "repr = \"int64\" requires the explicit discriminant like `Variant = 1`",
+        ));
+    }
+    Ok(variant)
+}
+
+pub(crate) fn generate_enum_methods(item: &ItemEnum) -> Result<impl ToTokens, Error> {
+    if !item.generics.params.is_empty() {
+        return Err(Error::new(item.generics.span(), "SpannerEnum doesn't support generic enums"));
+    }
+    if item.variants.is_empty() {
+        return Err(Error::new(item.ident.span(), "SpannerEnum requires at least one variant"));
+    }
+    let repr = parse_repr(item)?;
+    let variants = item
+        .variants
+        .iter()
+        .map(|v| parse_variant(v, repr))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(second) = variants.iter().filter(|v| v.other).nth(1) {
+        return Err(Error::new(second.variant.ident.span(), "only one variant can be `other`"));
+    }
+
+    let enum_name = &item.ident;
+    let enum_name_str = enum_name.to_string();
+    let raw_type = match repr {
+        Repr::String => quote! { String },
+        Repr::Int64 => quote! { i64 },
+    };
+
+    let mut to_kind_arms = Vec::with_capacity(variants.len());
+    let mut from_raw = Vec::with_capacity(variants.len());
+    for v in &variants {
+        let ident = &v.variant.ident;
+        if v.holds_value() {
+            to_kind_arms.push(quote! {
+                #enum_name::#ident(v) => <#raw_type as ToKind>::to_kind(v)
+            });
+            continue;
+        }
+        let value = match repr {
+            Repr::String => {
+                let name = v.name.clone().unwrap_or_else(|| ident.to_string());
+                quote! { #name }
+            }
+            Repr::Int64 => {
+                // Unit variants without the discriminant are rejected by parse_variant.
+                let (_, discriminant) = v.variant.discriminant.as_ref().unwrap();
+                quote! { #discriminant }
+            }
+        };
+        let to_kind = match repr {
+            Repr::String => quote! { <&str as ToKind>::to_kind(&#value) },
+            Repr::Int64 => quote! { <i64 as ToKind>::to_kind(&(#value)) },
+        };
+        to_kind_arms.push(quote! {
+            #enum_name::#ident => #to_kind
+        });
+        from_raw.push(quote! {
+            if v == #value {
+                return Ok(#enum_name::#ident);
+            }
+        });
+    }
+
+    let fallback = match variants.iter().find(|v| v.other) {
+        Some(v) if v.holds_value() => {
+            let ident = &v.variant.ident;
+            quote! { Ok(#enum_name::#ident(v)) }
+        }
+        Some(v) => {
+            let ident = &v.variant.ident;
+            quote! { Ok(#enum_name::#ident) }
+        }
+        None => quote! {
+            Err(RowError::UnknownEnumValue(field.name.to_string(), v.to_string(), #enum_name_str))
+        },
+    };
+
+    Ok(quote! {
+        impl ToKind for #enum_name {
+            fn to_kind(&self) -> google_cloud_spanner::__private::Kind {
+                match self {
+                    #(
+                        #to_kind_arms,
+                    )*
+                }
+            }
+            fn get_type() -> google_cloud_spanner::__private::Type {
+                <#raw_type as ToKind>::get_type()
+            }
+        }
+
+        impl TryFromValue for #enum_name {
+            fn try_from(
+                value: &google_cloud_spanner::__private::Value,
+                field: &google_cloud_spanner::__private::Field,
+            ) -> Result<Self, RowError> {
+                let v = <#raw_type as TryFromValue>::try_from(value, field)?;
+                #(
+                    #from_raw
+                )*
+                #fallback
+            }
+        }
+    })
+}
@@ -25,7 +25,7 @@
 //!     // #[spanner(name=...) is used when the column name does not appear in camel case of the field name
 //!     #[spanner(name="LevelX")]
 //!     pub level: i64,
-//!     #[spanner(commitTimestamp)]
+//!     #[spanner(commit_timestamp)]
 //!     pub updated_at: OffsetDateTime,
 //! }
 //!
@@ -59,12 +59,9 @@
 //!```
 //!
 //! Here is the generated implementation.
+//! The implementation is wrapped in an anonymous const so that the imports don't leak into the module.
 //!```
 //! use time::OffsetDateTime;
-//! use google_cloud_spanner::statement::{ToStruct, ToKind, Kinds, Types};
-//! use google_cloud_spanner::row::{Struct, TryFromValue, TryFromStruct, Row, Error as RowError};
-//! use google_cloud_spanner::value::CommitTimestamp;
-//! use std::convert::TryFrom;
 //!
 //! pub struct UserCharacter {
 //!     pub user_id: String,
@@ -73,49 +70,216 @@
 //!     pub updated_at: OffsetDateTime,
 //! }
 //!
-//! impl ToStruct for UserCharacter {
-//!     fn to_kinds(&self) -> Kinds {
-//!         vec![
-//!             ("UserId", self.user_id.to_kind()),
-//!             ("CharacterId", self.character_id.to_kind()),
-//!             ("LevelX", self.level.to_kind()),
-//!             ("UpdatedAt", self.updated_at.to_kind()),
-//!         ]
+//! #[allow(unused_imports)]
+//! const _: () = {
+//!     use google_cloud_spanner::statement::{ToStruct, ToKind, Kinds, TableMetadata, Types};
+//!     use google_cloud_spanner::row::{Struct, TryFromValue, TryFromStruct, Row, Error as RowError};
+//!     use google_cloud_spanner::value::{CommitTimestamp, TimestampColumn};
+//!     use std::convert::TryFrom;
+//!
+//!     impl ToStruct for UserCharacter {
+//!         fn to_kinds(&self) -> Kinds {
+//!             vec![
+//!                 ("UserId", <String as ToKind>::to_kind(&self.user_id)),
+//!                 ("CharacterId", <i64 as ToKind>::to_kind(&self.character_id)),
+//!                 ("LevelX", <i64 as ToKind>::to_kind(&self.level)),
+//!                 ("UpdatedAt", {
+//!                     fn timestamp_column<T: TimestampColumn>() {}
+//!                     timestamp_column::<OffsetDateTime>();
+//!                     CommitTimestamp::new().to_kind()
+//!                 }),
+//!             ]
+//!         }
+//!
+//!         fn get_types() -> Types {
+//!             vec![
+//!                 ("UserId", <String as ToKind>::get_type()),
+//!                 ("CharacterId", <i64 as ToKind>::get_type()),
+//!                 ("LevelX", <i64 as ToKind>::get_type()),
+//!                 ("UpdatedAt", CommitTimestamp::get_type()),
+//!             ]
+//!         }
 //!     }
 //!
-//!    fn get_types() -> Types {
-//!        vec![
-//!           ("UserId", String::get_type()),
-//!            ("CharacterId", i64::get_type()),
-//!            ("LevelX", i64::get_type()),
-//!            ("UpdatedAt", CommitTimestamp::get_type()),
-//!        ]
-//!    }
+//!     impl TryFromStruct for UserCharacter {
+//!         fn try_from_struct(s: Struct<'_>) -> Result<Self, RowError> {
+//!             Ok(UserCharacter {
+//!                 user_id: match s.try_column_by_name::<String>("UserId")? {
+//!                     Some(v) => v,
+//!                     None => return Err(RowError::MissingColumn("UserId".to_string(), "UserCharacter", "user_id")),
+//!                 },
+//!                 character_id: match s.try_column_by_name::<i64>("CharacterId")? {
+//!                     Some(v) => v,
+//!                     None => return Err(RowError::MissingColumn("CharacterId".to_string(), "UserCharacter", "character_id")),
+//!                 },
+//!                 level: match s.try_column_by_name::<i64>("LevelX")? {
+//!                     Some(v) => v,
+//!                     None => return Err(RowError::MissingColumn("LevelX".to_string(), "UserCharacter", "level")),
+//!                 },
+//!                 updated_at: match s.try_column_by_name::<OffsetDateTime>("UpdatedAt")? {
+//!                     Some(v) => v,
+//!                     None => return Err(RowError::MissingColumn("UpdatedAt".to_string(), "UserCharacter", "updated_at")),
+//!                 },
+//!             })
+//!         }
+//!     }
+//!
+//!     // TryFrom<Row> has the same body as try_from_struct.
+//!     impl TryFrom<Row> for UserCharacter {
+//!         type Error = RowError;
+//!         fn try_from(s: Row) -> Result<Self, RowError> {
+//!             Ok(UserCharacter {
+//!                 user_id: match s.try_column_by_name::<String>("UserId")? {
+//!                     Some(v) => v,
+//!                     None => return Err(RowError::MissingColumn("UserId".to_string(), "UserCharacter", "user_id")),
+//!                 },
+//!                 character_id: match s.try_column_by_name::<i64>("CharacterId")? {
+//!                     Some(v) => v,
+//!                     None => return Err(RowError::MissingColumn("CharacterId".to_string(), "UserCharacter", "character_id")),
+//!                 },
+//!                 level: match s.try_column_by_name::<i64>("LevelX")? {
+//!                     Some(v) => v,
+//!                     None => return Err(RowError::MissingColumn("LevelX".to_string(), "UserCharacter", "level")),
+//!                 },
+//!                 updated_at: match s.try_column_by_name::<OffsetDateTime>("UpdatedAt")? {
+//!                     Some(v) => v,
+//!                     None => return Err(RowError::MissingColumn("UpdatedAt".to_string(), "UserCharacter", "updated_at")),
+//!                 },
+//!             })
+//!         }
+//!     }
+//! };
+//!```
+//!
+//! ### Column names
+//!
+//! The column name is the pascal case of the field name by default.
+//! `#[spanner(rename_all = "...")]` on the struct changes the case of all the columns,
+//! and `#[spanner(name = "...")]` on the field overrides the name of the column.
+//! The supported rules are `"camelCase"`, `"PascalCase"` and `"snake_case"`.
+//!
+//! ```
+//! use google_cloud_spanner_derive::Table;
+//!
+//! #[derive(Table)]
+//! #[spanner(rename_all = "snake_case")]
+//! pub struct LegacyUser {
+//!     // user_id
+//!     pub user_id: String,
+//!     #[spanner(name = "USER_ID2")]
+//!     pub user_id2: String,
+//!     // type
+//!     pub r#type: i64,
 //! }
+//! ```
 //!
-//! impl TryFromStruct for UserCharacter {
-//!    fn try_from_struct(s: Struct<'_>) -> Result<Self, RowError> {
-//!        Ok(UserCharacter {
-//!            user_id: s.column_by_name("UserId")?,
-//!            character_id: s.column_by_name("CharacterId")?,
-//!            level: s.column_by_name("LevelX")?,
-//!            updated_at: s.column_by_name("UpdatedAt")?,
-//!        })
-//!    }
+//! ### Commit timestamp
+//!
+//! `#[spanner(commit_timestamp)]` on the TIMESTAMP column with `allow_commit_timestamp=true` writes the commit timestamp placeholder
+//! in the mutations regardless of the value of the field, and the stored timestamp is read from the row.
+//! The type of the field must be `OffsetDateTime`, `CommitTimestamp` or `Option` of them, and the field can't be skipped.
+//!
+//! ### Skipping fields
+//!
+//! `#[spanner(skip)]` excludes the field from both the mutations and the rows.
+//! `#[spanner(skip_serializing)]` excludes the field only from the mutations,
+//! and `#[spanner(skip_deserializing)]` only from the rows.
+//! The field not read from the rows is populated by `Default::default()`.
+//!
+//! ```
+//! use google_cloud_spanner_derive::Table;
+//!
+//! #[derive(Table)]
+//! pub struct UserCharacter {
+//!     pub user_id: String,
+//!     pub character_id: i64,
+//!     #[spanner(skip)]
+//!     pub display_name: String,
 //! }
+//! ```
 //!
-//! impl TryFrom<Row> for UserCharacter {
-//!    type Error = RowError;
-//!    fn try_from(s: Row) -> Result<Self, RowError> {
-//!        Ok(UserCharacter {
-//!            user_id: s.column_by_name("UserId")?,
-//!            character_id: s.column_by_name("CharacterId")?,
-//!            level: s.column_by_name("LevelX")?,
-//!            updated_at: s.column_by_name("UpdatedAt")?,
-//!        })
-//!    }
+//! ### Nested structs
+//!
+//! A field of the type deriving `Table` or `Query` is decoded from a STRUCT column,
+//! `Vec<T>` from an ARRAY<STRUCT> column like `ARRAY(SELECT AS STRUCT ...)` and `Option<T>` from a nullable STRUCT column.
+//! The nested structs can be nested again.
+//!
+//! ### Missing columns
+//!
+//! Decoding fails with `row::Error::MissingColumn` when the column of the field is not in the row.
+//! `#[spanner(default)]` on the field populates the field by `Default::default()` instead,
+//! and `#[spanner(allow_missing_columns)]` on the struct does it for all the fields.
+//! The column name is the one renamed by `name` and `rename_all`.
+//!
+//! ### Table metadata
+//!
+//! `#[spanner(table = "...")]` on the struct implements `TableMetadata` with the table name,
+//! the columns read into the struct honoring `name`, `rename_all` and `skip`, and `select_sql()`.
+//!
+//! ```
+//! use google_cloud_spanner::client::{Client, Error};
+//! use google_cloud_spanner::key::Key;
+//! use google_cloud_spanner::statement::TableMetadata;
+//! use google_cloud_spanner_derive::Table;
+//!
+//! #[derive(Table)]
+//! #[spanner(table = "UserItem")]
+//! pub struct UserItem {
+//!     pub user_id: String,
+//!     pub item_id: i64,
+//!     #[spanner(skip)]
+//!     pub cached: Option<String>,
 //! }
-//!```
+//!
+//! async fn run(client: &Client) -> Result<Option<UserItem>, Error> {
+//!     let key = Key::composite(&[&"user", &1_i64]);
+//!     // SELECT UserId, ItemId FROM UserItem
+//!     let _sql = UserItem::select_sql();
+//!     match client.read_row(UserItem::TABLE_NAME, UserItem::COLUMNS, key).await? {
+//!         Some(row) => Ok(Some(row.try_into()?)),
+//!         None => Ok(None),
+//!     }
+//! }
+//! ```
+//!
+//! ### Enum derive
+//!
+//! `#[derive(SpannerEnum)]` generates the implementation of `ToKind` and `TryFromValue` for the enum,
+//! so that the enum can be used as a field of `Table` and `Query`, a query parameter and an element of the arrays.
+//! `#[spanner(repr = "string")]` (default) stores the variant name or `#[spanner(name = "...")]` of the variant to the STRING column,
+//! and `#[spanner(repr = "int64")]` stores the explicit discriminant to the INT64 column.
+//! Decoding an unknown value fails with `row::Error::UnknownEnumValue` unless the `#[spanner(other)]` variant exists.
+//! The `other` variant is either a unit variant or holds the stored value like `Other(String)`.
+//!
+//! ```
+//! use google_cloud_spanner_derive::{SpannerEnum, Table};
+//!
+//! #[derive(SpannerEnum)]
+//! #[spanner(repr = "int64")]
+//! pub enum OrderStatus {
+//!     Pending = 1,
+//!     Paid = 2,
+//!     #[spanner(other)]
+//!     Unknown = 0,
+//! }
+//!
+//! #[derive(SpannerEnum)]
+//! pub enum Currency {
+//!     #[spanner(name = "JPY")]
+//!     Yen,
+//!     #[spanner(name = "USD")]
+//!     Dollar,
+//!     #[spanner(other)]
+//!     Other(String),
+//! }
+//!
+//! #[derive(Table)]
+//! pub struct Order {
+//!     pub order_id: String,
+//!     pub status: OrderStatus,
+//!     pub currency: Currency,
+//! }
+//! ```
 //!
 //! ### Query derive
 //!
@@ -168,9 +332,10 @@
 use proc_macro::TokenStream;
 
 use quote::{quote, ToTokens};
-use syn::{parse_macro_input, ItemStruct};
+use syn::{parse_macro_input, ItemEnum, ItemStruct};
 
 mod column;
+mod enumeration;
 mod query;
 mod symbol;
 mod table;
@@ -178,19 +343,35 @@ mod table;
 #[proc_macro_derive(Table, attributes(spanner))]
 pub fn table(input: TokenStream) -> TokenStream {
     let item = parse_macro_input!(input as ItemStruct);
-    let table = table::generate_table_methods(item.clone());
-    let query = query::generate_query_methods(item);
-    wrap_in_dummy_mod(quote! {
-        #table
-        #query
-    })
+    let methods = table::generate_table_methods(&item).and_then(|table| {
+        let query = query::generate_query_methods(&item)?;
+        Ok(quote! {
+            #table
+            #query
+        })
+    });
+    match methods {
+        Ok(methods) => wrap_in_dummy_mod(methods),
+        Err(e) => e.to_compile_error().into(),
+    }
 }
 
 #[proc_macro_derive(Query, attributes(spanner))]
 pub fn query(input: TokenStream) -> TokenStream {
     let item = parse_macro_input!(input as ItemStruct);
-    let query = query::generate_query_methods(item);
-    wrap_in_dummy_mod(query)
+    match query::generate_query_methods(&item) {
+        Ok(query) => wrap_in_dummy_mod(query),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[proc_macro_derive(SpannerEnum, attributes(spanner))]
+pub fn spanner_enum(input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as ItemEnum);
+    match enumeration::generate_enum_methods(&item) {
+        Ok(methods) => wrap_in_dummy_mod(methods),
+        Err(e) => e.to_compile_error().into(),
+    }
 }
 
 fn wrap_in_dummy_mod(item: impl ToTokens) -> TokenStream {
@@ -198,9 +379,9 @@ fn wrap_in_dummy_mod(item: impl ToTokens) -> TokenStream {
     let wrapped = quote! {
         #[allow(unused_imports)]
         const _: () = {
-            use google_cloud_spanner::statement::{ToStruct, ToKind, Kinds, Types};
+            use google_cloud_spanner::statement::{ToStruct, ToKind, Kinds, TableMetadata, Types};
             use google_cloud_spanner::row::{Struct, TryFromValue, TryFromStruct, Row, Error as RowError};
-            use google_cloud_spanner::value::CommitTimestamp;
+            use google_cloud_spanner::value::{CommitTimestamp, TimestampColumn};
             use std::convert::TryFrom;
 
             #item
@@ -1,22 +1,58 @@
-use quote::{quote, ToTokens};
-use syn::ItemStruct;
+use quote::{quote, quote_spanned, ToTokens};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{Error, ItemStruct};
 
-use crate::column::Column;
+use crate::column::{parse_columns, Container};
 
-pub(crate) fn generate_query_methods(item: ItemStruct) -> impl ToTokens {
-    let struct_name = item.ident;
+pub(crate) fn generate_query_methods(item: &ItemStruct) -> Result<impl ToTokens, Error> {
+    let struct_name = &item.ident;
+    let struct_name_str = struct_name.to_string();
 
     let mut try_from_struct_fields = Vec::with_capacity(item.fields.len());
-    for field in &item.fields {
-        let field_var = field.ident.as_ref().unwrap();
-        let column = Column::from(field);
+    let mut column_names = Vec::with_capacity(item.fields.len());
+    for column in parse_columns(item)? {
+        let field_var = column.var();
+        let ty = column.ty();
+        if column.skip_deserializing {
+            // The span of the type points the field without the Default impl.
+            try_from_struct_fields.push(quote_spanned! { ty.span() =>
+                #field_var: <#ty as ::std::default::Default>::default()
+            });
+            continue;
+        }
         let column_name = column.name();
-        try_from_struct_fields.push(quote! {
-            #field_var: s.column_by_name(#column_name)?
-        });
+        column_names.push(column_name.clone());
+        let field_name = field_var.unraw().to_string();
+        // The span of the type points the field whose type doesn't implement TryFromValue,
+        // e.g. the nested struct without TryFromStruct.
+        if column.default_if_missing {
+            try_from_struct_fields.push(quote_spanned! { ty.span() =>
+                #field_var: s.try_column_by_name::<#ty>(#column_name)?.unwrap_or_else(<#ty as ::std::default::Default>::default)
+            });
+        } else {
+            try_from_struct_fields.push(quote_spanned! { ty.span() =>
+                #field_var: match s.try_column_by_name::<#ty>(#column_name)? {
+                    Some(v) => v,
+                    None => return Err(RowError::MissingColumn(#column_name.to_string(), #struct_name_str, #field_name)),
+                }
+            });
+        }
     }
 
-    quote! {
+    // TableMetadata is generated only for the struct with `#[spanner(table = "...")]`.
+    let table_metadata = Container::parse(&item.attrs)?.table.map(|table| {
+        quote! {
+            impl TableMetadata for #struct_name {
+                const TABLE_NAME: &'static str = #table;
+                const COLUMNS: &'static [&'static str] = &[#(#column_names),*];
+            }
+        }
+    });
+
+    Ok(quote! {
+        #table_metadata
+
         impl TryFromStruct for #struct_name {
             fn try_from_struct(s: Struct<'_>) -> Result<Self, RowError> {
                 Ok(#struct_name {
@@ -37,5 +73,5 @@ pub(crate) fn generate_query_methods(item: ItemStruct) -> impl ToTokens {
                 })
             }
         }
-    }
+    })
 }
@@ -6,9 +6,20 @@ use syn::{Ident, Path};
 #[derive(Copy, Clone)]
 pub(crate) struct Symbol(&'static str);
 
-pub(crate) const COMMIT_TIMESTAMP: Symbol = Symbol("commitTimestamp");
+pub(crate) const COMMIT_TIMESTAMP: Symbol = Symbol("commit_timestamp");
+// The camel case is kept for the compatibility.
+pub(crate) const COMMIT_TIMESTAMP_CAMEL: Symbol = Symbol("commitTimestamp");
 pub(crate) const COLUMN_NAME: Symbol = Symbol("name");
 pub(crate) const COLUMN: Symbol = Symbol("spanner");
+pub(crate) const RENAME_ALL: Symbol = Symbol("rename_all");
+pub(crate) const SKIP: Symbol = Symbol("skip");
+pub(crate) const SKIP_SERIALIZING: Symbol = Symbol("skip_serializing");
+pub(crate) const SKIP_DESERIALIZING: Symbol = Symbol("skip_deserializing");
+pub(crate) const DEFAULT: Symbol = Symbol("default");
+pub(crate) const ALLOW_MISSING_COLUMNS: Symbol = Symbol("allow_missing_columns");
+pub(crate) const REPR: Symbol = Symbol("repr");
+pub(crate) const OTHER: Symbol = Symbol("other");
+pub(crate) const TABLE: Symbol = Symbol("table");
 
 impl PartialEq<Symbol> for Ident {
     fn eq(&self, word: &Symbol) -> bool {
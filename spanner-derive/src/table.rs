@@ -1,33 +1,42 @@
-use quote::{quote, ToTokens};
-use syn::ItemStruct;
+use quote::{quote, quote_spanned, ToTokens};
+use syn::spanned::Spanned;
+use syn::{Error, ItemStruct};
 
-use crate::column::Column;
+use crate::column::parse_columns;
 
-pub(crate) fn generate_table_methods(item: ItemStruct) -> impl ToTokens {
-    let struct_name = item.ident;
+pub(crate) fn generate_table_methods(item: &ItemStruct) -> Result<impl ToTokens, Error> {
+    let struct_name = &item.ident;
 
     let mut to_kinds_fields = Vec::with_capacity(item.fields.len());
     let mut get_types_fields = Vec::with_capacity(item.fields.len());
-    for field in &item.fields {
-        let field_var = field.ident.as_ref().unwrap();
-        let column = Column::from(field);
+    for column in parse_columns(item)?.into_iter().filter(|c| !c.skip_serializing) {
+        let field_var = column.var();
         let column_name = column.name();
-        let ty = &field.ty;
-        let mut get_field_type = quote! { <#ty> };
-        let mut to_kind_field_type = quote! { self.#field_var };
+        let ty = column.ty();
         if column.commit_timestamp {
-            get_field_type = quote! { CommitTimestamp };
-            to_kind_field_type = quote! { CommitTimestamp::new() };
+            // The placeholder is written regardless of the value, and the stored timestamp is read from the row.
+            to_kinds_fields.push(quote_spanned! { ty.span() =>
+                (#column_name, {
+                    fn timestamp_column<T: TimestampColumn>() {}
+                    timestamp_column::<#ty>();
+                    CommitTimestamp::new().to_kind()
+                })
+            });
+            get_types_fields.push(quote! {
+                (#column_name, CommitTimestamp::get_type())
+            });
+            continue;
         }
-        to_kinds_fields.push(quote! {
-            (#column_name, #to_kind_field_type.to_kind())
+        // The span of the type points the field whose type doesn't implement ToKind, e.g. the nested struct without ToStruct.
+        to_kinds_fields.push(quote_spanned! { ty.span() =>
+            (#column_name, <#ty as ToKind>::to_kind(&self.#field_var))
         });
-        get_types_fields.push(quote! {
-            (#column_name, #get_field_type::get_type())
+        get_types_fields.push(quote_spanned! { ty.span() =>
+            (#column_name, <#ty as ToKind>::get_type())
         });
     }
 
-    quote! {
+    Ok(quote! {
 
         impl ToStruct for #struct_name  {
 
@@ -46,5 +55,5 @@ pub(crate) fn generate_table_methods(item: ItemStruct) -> impl ToTokens {
                 ]
             }
         }
-    }
+    })
 }
@@ -1,3 +1,6 @@
+// The tests return client::Error, which holds the tonic Status, to use `?`.
+#![allow(clippy::result_large_err)]
+
 use google_cloud_spanner::bigdecimal::{BigDecimal, Zero};
 use serde::{Deserialize, Serialize};
 use serial_test::serial;
@@ -5,9 +8,11 @@ use std::str::FromStr;
 use time::{Date, OffsetDateTime};
 
 use google_cloud_spanner::client::{Client, ClientConfig, Error};
+use google_cloud_spanner::key::Key;
 use google_cloud_spanner::mutation::insert_struct;
 use google_cloud_spanner::statement::Statement;
-use google_cloud_spanner_derive::{Query, Table};
+use google_cloud_spanner::value::CommitTimestamp;
+use google_cloud_spanner_derive::{Query, SpannerEnum, Table};
 
 #[derive(Table, Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct UserCharacter {
@@ -107,6 +112,107 @@ pub struct UserBundle {
     pub user_items: Vec<UserItem>,
 }
 
+#[derive(Table, Clone, Debug)]
+pub struct UserItemCommitTimestamp {
+    pub user_id: String,
+    pub item_id: i64,
+    pub quantity: i64,
+    #[spanner(name = "UpdatedAt")]
+    pub commit_ts: CommitTimestamp,
+}
+
+#[derive(Table, Clone, Debug)]
+pub struct UserItemUpdated {
+    pub user_id: String,
+    pub item_id: i64,
+    pub quantity: i64,
+    #[spanner(commit_timestamp)]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Query, Debug, PartialEq)]
+#[spanner(allow_missing_columns)]
+pub struct PartialUserItem {
+    pub user_id: String,
+    pub item_id: i64,
+    pub quantity: i64,
+}
+
+#[derive(Query, Debug, PartialEq)]
+pub struct UserItemWithDefault {
+    pub user_id: String,
+    #[spanner(name = "ItemId")]
+    pub id: i64,
+    #[spanner(default)]
+    pub quantity: i64,
+}
+
+#[derive(SpannerEnum, Clone, Copy, Debug, PartialEq)]
+#[spanner(repr = "int64")]
+pub enum OrderStatus {
+    Pending = 1,
+    Paid = 2,
+    Shipped = 3,
+}
+
+#[derive(SpannerEnum, Clone, Debug, PartialEq)]
+#[spanner(repr = "string")]
+pub enum Currency {
+    #[spanner(name = "JPY")]
+    Yen,
+    #[spanner(name = "USD")]
+    Dollar,
+    #[spanner(other)]
+    Other(String),
+}
+
+#[derive(Table, Debug, PartialEq)]
+pub struct Order {
+    pub order_id: String,
+    pub status: OrderStatus,
+    pub currency: Currency,
+    pub history: Vec<OrderStatus>,
+    pub previous_status: Option<OrderStatus>,
+}
+
+#[derive(Table, Debug, PartialEq)]
+#[spanner(table = "UserItem")]
+pub struct UserItemRecord {
+    pub user_id: String,
+    #[spanner(name = "ItemId")]
+    pub id: i64,
+    pub quantity: i64,
+    #[spanner(skip)]
+    pub cached: Option<String>,
+}
+
+#[derive(Query)]
+pub struct CharacterWithItems {
+    pub character_id: i64,
+    pub user_items: Vec<UserItem>,
+}
+
+#[derive(Query)]
+pub struct UserNestedBundle {
+    pub user_id: String,
+    pub characters: Vec<CharacterWithItems>,
+}
+
+#[derive(Query, Debug, PartialEq)]
+pub struct CharacterDetail {
+    pub character_id: i64,
+    pub level: i64,
+    pub items: Vec<UserItem>,
+    pub first_item: UserItem,
+    pub missing_item: Option<UserItem>,
+}
+
+#[derive(Query, Debug, PartialEq)]
+pub struct UserDetail {
+    pub user_id: String,
+    pub characters: Vec<CharacterDetail>,
+}
+
 #[tokio::test]
 #[serial]
 async fn test_table_derive() -> Result<(), Error> {
@@ -200,3 +306,498 @@ async fn test_query_derive() -> Result<(), Error> {
     }
     Ok(())
 }
+
+#[tokio::test]
+#[serial]
+async fn test_nested_query_derive() -> Result<(), Error> {
+    std::env::set_var("SPANNER_EMULATOR_HOST", "localhost:9010");
+    let config = ClientConfig::default();
+    let client = Client::new(
+        "projects/local-project/instances/test-instance/databases/local-database",
+        config,
+    )
+    .await?;
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let user_id = format!("user-n-{now}");
+    let user = User {
+        user_id: user_id.clone(),
+        ..Default::default()
+    };
+    let user_character = UserCharacter {
+        user_id: user_id.clone(),
+        ..Default::default()
+    };
+    let user_item = UserItem {
+        user_id: user_id.clone(),
+        ..Default::default()
+    };
+    client
+        .apply(vec![
+            insert_struct("User", user),
+            insert_struct("UserCharacter", user_character),
+            insert_struct("UserItem", user_item),
+        ])
+        .await?;
+
+    let mut tx = client.read_only_transaction().await?;
+    let mut stmt = Statement::new(
+        "
+    SELECT
+        UserId,
+        ARRAY(
+            SELECT AS STRUCT
+                c.CharacterId,
+                ARRAY(SELECT AS STRUCT * FROM UserItem WHERE UserId = c.UserId) AS UserItems
+            FROM UserCharacter c WHERE c.UserId = @UserId
+        ) AS Characters,
+    From User
+    WHERE UserID = @UserID",
+    );
+    stmt.add_param("UserID", &user_id);
+    let mut reader = tx.query(stmt).await?;
+    if let Some(row) = reader.next().await? {
+        let v: UserNestedBundle = row.try_into()?;
+        assert_eq!(v.user_id, user_id);
+        assert_eq!(v.characters.len(), 1);
+        assert_eq!(v.characters[0].user_items.len(), 1);
+        assert_eq!(v.characters[0].user_items[0].user_id, user_id);
+    } else {
+        panic!("no data found");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_commit_timestamp_field() -> Result<(), Error> {
+    std::env::set_var("SPANNER_EMULATOR_HOST", "localhost:9010");
+    let config = ClientConfig::default();
+    let client = Client::new(
+        "projects/local-project/instances/test-instance/databases/local-database",
+        config,
+    )
+    .await?;
+
+    let now = OffsetDateTime::now_utc();
+    let user_id = format!("user-ct-{}", now.unix_timestamp());
+    let user = User {
+        user_id: user_id.clone(),
+        ..Default::default()
+    };
+    let user_item = UserItemCommitTimestamp {
+        user_id: user_id.clone(),
+        item_id: 1,
+        quantity: 1,
+        commit_ts: CommitTimestamp::new(),
+    };
+    client
+        .apply(vec![insert_struct("User", user), insert_struct("UserItem", user_item)])
+        .await?;
+
+    let row = client
+        .read_row(
+            "UserItem",
+            &["UserId", "ItemId", "Quantity", "UpdatedAt"],
+            Key::composite(&[&user_id, &1_i64]),
+        )
+        .await?
+        .unwrap();
+    let v: UserItemCommitTimestamp = row.try_into()?;
+    assert!(*v.commit_ts >= now - time::Duration::seconds(1));
+    Ok(())
+}
+
+#[derive(Table)]
+#[spanner(rename_all = "snake_case")]
+pub struct LegacyUser {
+    pub user_id: String,
+    #[spanner(name = "USER_ID2")]
+    pub user_id2: String,
+    pub r#type: i64,
+}
+
+#[test]
+fn test_rename_all() {
+    use google_cloud_spanner::statement::ToStruct;
+
+    let names: Vec<&str> = LegacyUser::get_types().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["user_id", "USER_ID2", "type"]);
+    let user = LegacyUser {
+        user_id: "a".to_string(),
+        user_id2: "b".to_string(),
+        r#type: 1,
+    };
+    let names: Vec<&str> = user.to_kinds().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["user_id", "USER_ID2", "type"]);
+}
+
+#[derive(Table)]
+pub struct UserWithCache {
+    pub user_id: String,
+    #[spanner(skip)]
+    pub display_name: String,
+    #[spanner(skip_serializing)]
+    pub loaded: bool,
+    #[spanner(skip_deserializing)]
+    pub quantity: i64,
+}
+
+#[test]
+fn test_skip() {
+    use google_cloud_spanner::statement::ToStruct;
+
+    let names: Vec<&str> = UserWithCache::get_types().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["UserId", "Quantity"]);
+    let user = UserWithCache {
+        user_id: "a".to_string(),
+        display_name: "cached".to_string(),
+        loaded: true,
+        quantity: 1,
+    };
+    let names: Vec<&str> = user.to_kinds().into_iter().map(|(name, _)| name).collect();
+    assert_eq!(names, vec!["UserId", "Quantity"]);
+    assert_eq!(user.display_name, "cached");
+    assert!(user.loaded);
+}
+
+#[test]
+fn test_invalid_attributes() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_nested_struct_round_trip() -> Result<(), Error> {
+    std::env::set_var("SPANNER_EMULATOR_HOST", "localhost:9010");
+    let config = ClientConfig::default();
+    let client = Client::new(
+        "projects/local-project/instances/test-instance/databases/local-database",
+        config,
+    )
+    .await?;
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let user_id = format!("user-rt-{now}");
+    let characters: Vec<UserCharacter> = (1..=2)
+        .map(|character_id| UserCharacter {
+            user_id: user_id.clone(),
+            character_id,
+            level: character_id * 10,
+            ..Default::default()
+        })
+        .collect();
+    let items: Vec<UserItem> = (1..=2)
+        .map(|item_id| UserItem {
+            user_id: user_id.clone(),
+            item_id,
+            quantity: item_id * 100,
+            ..Default::default()
+        })
+        .collect();
+    let mut mutations = vec![insert_struct(
+        "User",
+        User {
+            user_id: user_id.clone(),
+            ..Default::default()
+        },
+    )];
+    mutations.extend(characters.iter().map(|c| insert_struct("UserCharacter", c)));
+    mutations.extend(items.iter().map(|i| insert_struct("UserItem", i)));
+    client.apply(mutations).await?;
+
+    let mut tx = client.read_only_transaction().await?;
+    let mut stmt = Statement::new(
+        "
+    SELECT
+        u.UserId,
+        ARRAY(
+            SELECT AS STRUCT
+                c.CharacterId,
+                c.Level,
+                ARRAY(SELECT AS STRUCT * FROM UserItem WHERE UserId = c.UserId ORDER BY ItemId) AS Items,
+                (SELECT AS STRUCT * FROM UserItem WHERE UserId = c.UserId ORDER BY ItemId LIMIT 1) AS FirstItem,
+                (SELECT AS STRUCT * FROM UserItem WHERE UserId = c.UserId AND ItemId < 0 LIMIT 1) AS MissingItem
+            FROM UserCharacter c WHERE c.UserId = u.UserId ORDER BY c.CharacterId
+        ) AS Characters
+    FROM User u
+    WHERE u.UserId = @UserId",
+    );
+    stmt.add_param("UserId", &user_id);
+    let mut reader = tx.query(stmt).await?;
+    let row = reader.next().await?.expect("no data found");
+    let detail: UserDetail = row.try_into()?;
+
+    let expected = UserDetail {
+        user_id: user_id.clone(),
+        characters: characters
+            .iter()
+            .map(|c| CharacterDetail {
+                character_id: c.character_id,
+                level: c.level,
+                items: items.clone(),
+                first_item: items[0].clone(),
+                missing_item: None,
+            })
+            .collect(),
+    };
+    assert_eq!(detail, expected);
+    Ok(())
+}
+
+#[test]
+fn test_commit_timestamp_attribute() {
+    use google_cloud_spanner::statement::{ToKind, ToStruct};
+
+    let item = UserItemUpdated {
+        user_id: "user".to_string(),
+        item_id: 1,
+        quantity: 1,
+        updated_at: OffsetDateTime::now_utc(),
+    };
+    let kinds = item.to_kinds();
+    assert_eq!(kinds[3].0, "UpdatedAt");
+    assert_eq!(kinds[3].1, CommitTimestamp::new().to_kind());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_commit_timestamp_attribute_round_trip() -> Result<(), Error> {
+    std::env::set_var("SPANNER_EMULATOR_HOST", "localhost:9010");
+    let config = ClientConfig::default();
+    let client = Client::new(
+        "projects/local-project/instances/test-instance/databases/local-database",
+        config,
+    )
+    .await?;
+
+    let now = OffsetDateTime::now_utc();
+    let user_id = format!("user-cta-{}", now.unix_timestamp());
+    let user = User {
+        user_id: user_id.clone(),
+        ..Default::default()
+    };
+    let user_item = UserItemUpdated {
+        user_id: user_id.clone(),
+        item_id: 1,
+        quantity: 1,
+        updated_at: OffsetDateTime::UNIX_EPOCH,
+    };
+    client
+        .apply(vec![insert_struct("User", user), insert_struct("UserItem", user_item)])
+        .await?;
+
+    let row = client
+        .read_row(
+            "UserItem",
+            &["UserId", "ItemId", "Quantity", "UpdatedAt"],
+            Key::composite(&[&user_id, &1_i64]),
+        )
+        .await?
+        .unwrap();
+    let v: UserItemUpdated = row.try_into()?;
+    assert_eq!(v.user_id, user_id);
+    assert!(v.updated_at >= now - time::Duration::seconds(1));
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_missing_columns() -> Result<(), Error> {
+    std::env::set_var("SPANNER_EMULATOR_HOST", "localhost:9010");
+    let config = ClientConfig::default();
+    let client = Client::new(
+        "projects/local-project/instances/test-instance/databases/local-database",
+        config,
+    )
+    .await?;
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let user_id = format!("user-mc-{now}");
+    let user = User {
+        user_id: user_id.clone(),
+        ..Default::default()
+    };
+    let user_item = UserItem {
+        user_id: user_id.clone(),
+        item_id: 1,
+        quantity: 10,
+        ..Default::default()
+    };
+    client
+        .apply(vec![insert_struct("User", user), insert_struct("UserItem", user_item)])
+        .await?;
+
+    let mut tx = client.read_only_transaction().await?;
+    let mut stmt = Statement::new("SELECT UserId, ItemId FROM UserItem WHERE UserId = @UserId");
+    stmt.add_param("UserId", &user_id);
+    let mut reader = tx.query(stmt).await?;
+    let row = reader.next().await?.expect("no data found");
+
+    let v: PartialUserItem = row.clone().try_into()?;
+    assert_eq!(
+        v,
+        PartialUserItem {
+            user_id: user_id.clone(),
+            item_id: 1,
+            quantity: 0,
+        }
+    );
+    let v: UserItemWithDefault = row.clone().try_into()?;
+    assert_eq!(
+        v,
+        UserItemWithDefault {
+            user_id: user_id.clone(),
+            id: 1,
+            quantity: 0,
+        }
+    );
+
+    // The strict mode names the missing column.
+    let err = UserItem::try_from(row).err().unwrap();
+    match &err {
+        google_cloud_spanner::row::Error::MissingColumn(column, struct_name, field) => {
+            assert_eq!(column, "Quantity");
+            assert_eq!(*struct_name, "UserItem");
+            assert_eq!(*field, "quantity");
+        }
+        e => panic!("unexpected error {e:?}"),
+    }
+    assert!(err.to_string().contains("#[spanner(default)]"));
+    Ok(())
+}
+
+#[test]
+fn test_spanner_enum_to_kind() {
+    use google_cloud_spanner::statement::{ToKind, ToStruct};
+
+    assert_eq!(OrderStatus::Paid.to_kind(), 2_i64.to_kind());
+    assert_eq!(OrderStatus::get_type(), i64::get_type());
+    assert_eq!(Currency::Yen.to_kind(), "JPY".to_kind());
+    assert_eq!(Currency::Other("EUR".to_string()).to_kind(), "EUR".to_kind());
+    assert_eq!(Currency::get_type(), String::get_type());
+
+    let order = Order {
+        order_id: "order".to_string(),
+        status: OrderStatus::Shipped,
+        currency: Currency::Dollar,
+        history: vec![OrderStatus::Pending, OrderStatus::Paid],
+        previous_status: None,
+    };
+    let kinds = order.to_kinds();
+    assert_eq!(kinds[1].1, 3_i64.to_kind());
+    assert_eq!(kinds[2].1, "USD".to_kind());
+    assert_eq!(kinds[3].1, vec![1_i64, 2_i64].to_kind());
+    assert_eq!(kinds[4].1, Option::<i64>::None.to_kind());
+    assert_eq!(Order::get_types()[3].1, Vec::<i64>::get_type());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_spanner_enum() -> Result<(), Error> {
+    std::env::set_var("SPANNER_EMULATOR_HOST", "localhost:9010");
+    let config = ClientConfig::default();
+    let client = Client::new(
+        "projects/local-project/instances/test-instance/databases/local-database",
+        config,
+    )
+    .await?;
+
+    let mut tx = client.single().await?;
+    let mut stmt = Statement::new(
+        "SELECT
+            'order' AS OrderId,
+            @Status AS Status,
+            'EUR' AS Currency,
+            @History AS History,
+            CAST(NULL AS INT64) AS PreviousStatus,
+            99 AS UnknownStatus",
+    );
+    stmt.add_param("Status", &OrderStatus::Paid);
+    stmt.add_param("History", &vec![OrderStatus::Pending, OrderStatus::Paid]);
+    let mut reader = tx.query(stmt).await?;
+    let row = reader.next().await?.expect("no data found");
+
+    let err = row.column_by_name::<OrderStatus>("UnknownStatus").err().unwrap();
+    assert!(err.to_string().contains("Unknown enum value"), "{err}");
+    let order: Order = row.try_into()?;
+    assert_eq!(
+        order,
+        Order {
+            order_id: "order".to_string(),
+            status: OrderStatus::Paid,
+            currency: Currency::Other("EUR".to_string()),
+            history: vec![OrderStatus::Pending, OrderStatus::Paid],
+            previous_status: None,
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_table_metadata() {
+    use google_cloud_spanner::statement::TableMetadata;
+
+    assert_eq!(UserItemRecord::TABLE_NAME, "UserItem");
+    assert_eq!(UserItemRecord::COLUMNS, &["UserId", "ItemId", "Quantity"]);
+    assert_eq!(UserItemRecord::select_sql(), "SELECT UserId, ItemId, Quantity FROM UserItem");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_table_metadata_read() -> Result<(), Error> {
+    use google_cloud_spanner::statement::TableMetadata;
+
+    std::env::set_var("SPANNER_EMULATOR_HOST", "localhost:9010");
+    let config = ClientConfig::default();
+    let client = Client::new(
+        "projects/local-project/instances/test-instance/databases/local-database",
+        config,
+    )
+    .await?;
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let user_id = format!("user-tm-{now}");
+    let user = User {
+        user_id: user_id.clone(),
+        ..Default::default()
+    };
+    let user_item = UserItem {
+        user_id: user_id.clone(),
+        item_id: 1,
+        quantity: 10,
+        ..Default::default()
+    };
+    client
+        .apply(vec![insert_struct("User", user), insert_struct("UserItem", user_item)])
+        .await?;
+
+    let row = client
+        .read_row(
+            UserItemRecord::TABLE_NAME,
+            UserItemRecord::COLUMNS,
+            Key::composite(&[&user_id, &1_i64]),
+        )
+        .await?
+        .unwrap();
+    let v: UserItemRecord = row.try_into()?;
+    assert_eq!(
+        v,
+        UserItemRecord {
+            user_id: user_id.clone(),
+            id: 1,
+            quantity: 10,
+            cached: None,
+        }
+    );
+
+    let mut tx = client.single().await?;
+    let mut stmt = Statement::new(format!("{} WHERE UserId = @UserId", UserItemRecord::select_sql()));
+    stmt.add_param("UserId", &user_id);
+    let mut reader = tx.query(stmt).await?;
+    let v: UserItemRecord = reader.next().await?.expect("no data found").try_into()?;
+    assert_eq!(v.quantity, 10);
+    assert!(v.cached.is_none());
+    Ok(())
+}
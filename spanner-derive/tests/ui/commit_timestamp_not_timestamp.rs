@@ -0,0 +1,10 @@
+use google_cloud_spanner_derive::Table;
+
+#[derive(Table)]
+pub struct User {
+    pub user_id: String,
+    #[spanner(commit_timestamp)]
+    pub updated_at: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use google_cloud_spanner_derive::SpannerEnum;
+
+#[derive(SpannerEnum)]
+#[spanner(repr = "int64")]
+pub enum OrderStatus {
+    Pending = 1,
+    Paid,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use google_cloud_spanner_derive::SpannerEnum;
+
+#[derive(SpannerEnum)]
+pub enum Currency {
+    Yen,
+    Dollar(String),
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use google_cloud_spanner_derive::Query;
+
+#[derive(Query)]
+pub struct User {
+    #[spanner(name = 1)]
+    pub user_id: String,
+}
+
+fn main() {}
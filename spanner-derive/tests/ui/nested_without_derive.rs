@@ -0,0 +1,13 @@
+use google_cloud_spanner_derive::Query;
+
+pub struct Child {
+    pub child_id: i64,
+}
+
+#[derive(Query)]
+pub struct Parent {
+    pub parent_id: i64,
+    pub child: Child,
+}
+
+fn main() {}
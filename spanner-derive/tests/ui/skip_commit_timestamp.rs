@@ -0,0 +1,11 @@
+use google_cloud_spanner_derive::Table;
+use time::OffsetDateTime;
+
+#[derive(Table)]
+pub struct User {
+    pub user_id: String,
+    #[spanner(commit_timestamp, skip)]
+    pub updated_at: OffsetDateTime,
+}
+
+fn main() {}
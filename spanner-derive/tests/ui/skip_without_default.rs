@@ -0,0 +1,12 @@
+use google_cloud_spanner_derive::Query;
+
+pub struct NoDefault;
+
+#[derive(Query)]
+pub struct User {
+    pub user_id: String,
+    #[spanner(skip)]
+    pub cache: NoDefault,
+}
+
+fn main() {}
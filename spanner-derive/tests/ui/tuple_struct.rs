@@ -0,0 +1,6 @@
+use google_cloud_spanner_derive::Table;
+
+#[derive(Table)]
+pub struct User(pub String);
+
+fn main() {}
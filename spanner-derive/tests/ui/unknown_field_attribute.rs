@@ -0,0 +1,9 @@
+use google_cloud_spanner_derive::Table;
+
+#[derive(Table)]
+pub struct User {
+    #[spanner(rename = "Id")]
+    pub user_id: String,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use google_cloud_spanner_derive::Table;
+
+#[derive(Table)]
+#[spanner(rename_all = "kebab-case")]
+pub struct User {
+    pub user_id: String,
+}
+
+fn main() {}
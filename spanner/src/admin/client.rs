@@ -7,6 +7,7 @@ use crate::admin::database::database_admin_client::DatabaseAdminClient;
 use crate::admin::instance::instance_admin_client::InstanceAdminClient;
 use crate::admin::AdminClientConfig;
 use crate::apiv1::conn_pool::{AUDIENCE, SPANNER};
+use crate::slow_operation::{OperationDetails, SlowOperationReporter};
 
 #[derive(Clone)]
 pub struct Client {
@@ -16,10 +17,11 @@ pub struct Client {
 
 impl Client {
     pub async fn new(config: AdminClientConfig) -> Result<Self, Error> {
-        let (conn, lro_client) = internal_client(&config).await?;
+        let slow_operation = config.slow_operation.clone().map(SlowOperationReporter::new);
+        let (conn, lro_client) = internal_client(&config, slow_operation.clone()).await?;
         let database = DatabaseAdminClient::new(conn, lro_client);
 
-        let (conn, lro_client) = internal_client(&config).await?;
+        let (conn, lro_client) = internal_client(&config, slow_operation).await?;
         let instance = InstanceAdminClient::new(conn, lro_client);
         Ok(Self { database, instance })
     }
@@ -33,13 +35,119 @@ impl Client {
     }
 }
 
-async fn internal_client(config: &AdminClientConfig) -> Result<(Channel, OperationsClient), Error> {
+async fn internal_client(
+    config: &AdminClientConfig,
+    slow_operation: Option<SlowOperationReporter>,
+) -> Result<(Channel, OperationsClient), Error> {
     let conn_options = ConnectionOptions {
         timeout: Some(Duration::from_secs(30)),
         connect_timeout: Some(Duration::from_secs(30)),
+        ..Default::default()
     };
     let conn_pool = ConnectionManager::new(1, SPANNER, AUDIENCE, &config.environment, &conn_options).await?;
     let conn = conn_pool.conn();
-    let lro_client = OperationsClient::new(conn).await?;
+    let mut builder = OperationsClient::builder(conn);
+    if let Some(retry) = &config.lro_retry {
+        builder = builder.with_retry(retry.clone());
+    }
+    if let Some(timeout) = config.lro_timeout {
+        builder = builder.with_timeout(timeout);
+    }
+    if let Some(project) = &config.quota_project {
+        builder = builder.with_quota_project(project);
+    }
+    if let Some(reporter) = slow_operation {
+        builder = builder.with_wait_observer(move |name, elapsed, err| {
+            let result = match err {
+                Some(e) => Err(e.clone()),
+                None => Ok(()),
+            };
+            reporter.report("WaitOperation", elapsed, &result, || OperationDetails {
+                session_id: String::new(),
+                transaction_id: None,
+                statement: None,
+                operation_name: Some(name.to_string()),
+            });
+        });
+    }
+    let lro_client = builder.build();
     Ok((conn_pool.conn(), lro_client))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    use google_cloud_gax::conn::Environment;
+    use google_cloud_gax::grpc::{Code, Status};
+    use google_cloud_gax::retry::RetrySetting;
+
+    use crate::admin::client::internal_client;
+    use crate::admin::AdminClientConfig;
+    use crate::slow_operation::SlowOperationReporter;
+
+    #[tokio::test]
+    async fn test_internal_client_lro_defaults() {
+        // the listener accepts the connection without the server because no request is sent.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let environment = Environment::Emulator(listener.local_addr().unwrap().to_string());
+
+        let config = AdminClientConfig {
+            environment,
+            lro_retry: Some(RetrySetting {
+                from_millis: 100,
+                max_delay: None,
+                factor: 1,
+                take: 3,
+                codes: vec![Code::Unavailable],
+            }),
+            lro_timeout: Some(Duration::from_secs(5)),
+            quota_project: Some("quota-project".to_string()),
+            slow_operation: None,
+        };
+        let (_, lro_client) = internal_client(&config, None).await.unwrap();
+        assert_eq!(lro_client.retry().from_millis, 100);
+        assert_eq!(lro_client.retry().take, 3);
+        assert_eq!(lro_client.timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(
+            lro_client.metadata(),
+            &[("x-goog-user-project".to_string(), "quota-project".to_string())]
+        );
+
+        let config = AdminClientConfig {
+            environment: Environment::Emulator(listener.local_addr().unwrap().to_string()),
+            ..Default::default()
+        };
+        let (_, lro_client) = internal_client(&config, None).await.unwrap();
+        assert_eq!(lro_client.retry().take, 20);
+        assert_eq!(lro_client.timeout(), None);
+        assert!(lro_client.metadata().is_empty());
+        assert!(lro_client.wait_observer().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_internal_client_slow_operation() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let config = AdminClientConfig {
+            environment: Environment::Emulator(listener.local_addr().unwrap().to_string()),
+            ..Default::default()
+        }
+        .on_slow_operation(Duration::from_secs(1), move |operation| {
+            let _ = sender.send(operation);
+        });
+        let reporter = config.slow_operation.clone().map(SlowOperationReporter::new);
+        let (_, lro_client) = internal_client(&config, reporter).await.unwrap();
+        let observer = lro_client.wait_observer().unwrap();
+
+        observer("op1", Duration::from_millis(999), None);
+        observer("op2", Duration::from_secs(2), Some(&Status::deadline_exceeded("wait timeout")));
+        let operation = receiver.recv().await.unwrap();
+        assert_eq!(operation.method, "WaitOperation");
+        assert_eq!(operation.operation_name.as_deref(), Some("op2"));
+        assert_eq!(operation.elapsed, Duration::from_secs(2));
+        assert_eq!(operation.status.code(), Code::DeadlineExceeded);
+        assert!(receiver.try_recv().is_err());
+    }
+}
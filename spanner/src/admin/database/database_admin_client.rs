@@ -1,3 +1,5 @@
+use async_trait::async_trait;
+
 use google_cloud_gax::conn::Channel;
 use google_cloud_gax::create_request;
 use google_cloud_gax::grpc::{Response, Status};
@@ -33,7 +35,19 @@ impl DatabaseAdminClient {
     }
 
     /// list_databases lists Cloud Spanner databases.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "ListDatabases",
+                db.name = req.parent.as_str(),
+            )
+        )
+    )]
     pub async fn list_databases(
         &self,
         mut req: ListDatabasesRequest,
@@ -62,7 +76,19 @@ impl DatabaseAdminClient {
     /// have a name of the format <database_name>/operations/<operation_id> and
     /// can be used to track preparation of the database. The metadata field type is CreateDatabaseMetadata.
     /// The response field type is Database, if successful.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "CreateDatabase",
+                db.name = req.parent.as_str(),
+            )
+        )
+    )]
     pub async fn create_database(
         &self,
         req: CreateDatabaseRequest,
@@ -80,7 +106,19 @@ impl DatabaseAdminClient {
     }
 
     /// get_database gets the state of a Cloud Spanner database.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "GetDatabase",
+                db.name = req.name.as_str(),
+            )
+        )
+    )]
     pub async fn get_database(
         &self,
         req: GetDatabaseRequest,
@@ -103,7 +141,19 @@ impl DatabaseAdminClient {
     /// metadata field type is
     /// UpdateDatabaseDdlMetadata.
     /// The operation has no response.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "UpdateDatabaseDdl",
+                db.name = req.database.as_str(),
+            )
+        )
+    )]
     pub async fn update_database_ddl(
         &self,
         req: UpdateDatabaseDdlRequest,
@@ -123,7 +173,19 @@ impl DatabaseAdminClient {
     /// drop_database drops (aka deletes) a Cloud Spanner database.
     /// Completed backups for the database will be retained according to their
     /// expire_time.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "DropDatabase",
+                db.name = req.database.as_str(),
+            )
+        )
+    )]
     pub async fn drop_database(
         &self,
         req: DropDatabaseRequest,
@@ -141,7 +203,19 @@ impl DatabaseAdminClient {
     /// get_database_ddl returns the schema of a Cloud Spanner database as a list of formatted
     /// DDL statements. This method does not show pending schema updates, those may
     /// be queried using the Operations API.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "GetDatabaseDdl",
+                db.name = req.database.as_str(),
+            )
+        )
+    )]
     pub async fn get_database_ddl(
         &self,
         req: GetDatabaseDdlRequest,
@@ -163,7 +237,19 @@ impl DatabaseAdminClient {
     /// permission on resource.
     /// For backups, authorization requires spanner.backups.setIamPolicy
     /// permission on resource.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "SetIamPolicy",
+                db.name = req.resource.as_str(),
+            )
+        )
+    )]
     pub async fn set_iam_policy(
         &self,
         req: SetIamPolicyRequest,
@@ -186,7 +272,19 @@ impl DatabaseAdminClient {
     /// resource.
     /// For backups, authorization requires spanner.backups.getIamPolicy
     /// permission on resource.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "GetIamPolicy",
+                db.name = req.resource.as_str(),
+            )
+        )
+    )]
     pub async fn get_iam_policy(
         &self,
         req: GetIamPolicyRequest,
@@ -211,7 +309,19 @@ impl DatabaseAdminClient {
     /// Calling this method on a backup that does not exist will
     /// result in a NOT_FOUND error if the user has
     /// spanner.backups.list permission on the containing instance
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "TestIamPermissions",
+                db.name = req.resource.as_str(),
+            )
+        )
+    )]
     pub async fn test_iam_permissions(
         &self,
         req: TestIamPermissionsRequest,
@@ -238,7 +348,19 @@ impl DatabaseAdminClient {
     /// Cancelling the returned operation will stop the creation and delete the
     /// backup. There can be only one pending backup creation per database. Backup
     /// creation of different databases can run concurrently.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "CreateBackup",
+                db.name = req.parent.as_str(),
+            )
+        )
+    )]
     pub async fn create_backup(
         &self,
         req: CreateBackupRequest,
@@ -256,7 +378,19 @@ impl DatabaseAdminClient {
     }
 
     /// get_backup gets metadata on a pending or completed Backup.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "GetBackup",
+                db.name = req.name.as_str(),
+            )
+        )
+    )]
     pub async fn get_backup(
         &self,
         req: GetBackupRequest,
@@ -272,7 +406,19 @@ impl DatabaseAdminClient {
     }
 
     /// update_backup updates a pending or completed Backup.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "UpdateBackup",
+                db.name = req.backup.as_ref().map(|b| b.name.as_str()),
+            )
+        )
+    )]
     pub async fn update_backup(
         &self,
         req: UpdateBackupRequest,
@@ -288,7 +434,19 @@ impl DatabaseAdminClient {
     }
 
     /// delete_backup deletes a pending or completed Backup.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "DeleteBackup",
+                db.name = req.name.as_str(),
+            )
+        )
+    )]
     pub async fn delete_backup(
         &self,
         req: DeleteBackupRequest,
@@ -306,7 +464,19 @@ impl DatabaseAdminClient {
     /// list_backups lists completed and pending backups.
     /// Backups returned are ordered by create_time in descending order,
     /// starting from the most recent create_time.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "ListBackups",
+                db.name = req.parent.as_str(),
+            )
+        )
+    )]
     pub async fn list_backups(
         &self,
         mut req: ListBackupsRequest,
@@ -347,7 +517,19 @@ impl DatabaseAdminClient {
     /// Once the restore operation completes, a new restore operation can be
     /// initiated, without waiting for the optimize operation associated with the
     /// first restore to complete.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "RestoreDatabase",
+                db.name = req.parent.as_str(),
+            )
+        )
+    )]
     pub async fn restore_database(
         &self,
         req: RestoreDatabaseRequest,
@@ -374,7 +556,19 @@ impl DatabaseAdminClient {
     /// and pending operations. Operations returned are ordered by
     /// operation.metadata.value.progress.start_time in descending order starting
     /// from the most recently started operation.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "ListBackupOperations",
+                db.name = req.parent.as_str(),
+            )
+        )
+    )]
     pub async fn list_backup_operations(
         &self,
         mut req: ListBackupOperationsRequest,
@@ -410,7 +604,19 @@ impl DatabaseAdminClient {
     /// metadata.type_url describes the type of the metadata. Operations returned
     /// include those that have completed/failed/canceled within the last 7 days,
     /// and pending operations.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "ListDatabaseOperations",
+                db.name = req.parent.as_str(),
+            )
+        )
+    )]
     pub async fn list_database_operations(
         &self,
         mut req: ListDatabaseOperationsRequest,
@@ -438,3 +644,219 @@ impl DatabaseAdminClient {
         }
     }
 }
+
+/// DatabaseAdmin is the method surface of [DatabaseAdminClient].
+/// Depend on `Arc<dyn DatabaseAdmin>` to replace the client with `MockDatabaseAdmin` enabled by the `mock` feature in the tests.
+#[cfg_attr(feature = "mock", mockall::automock)]
+#[allow(clippy::result_large_err)]
+#[async_trait]
+pub trait DatabaseAdmin: Send + Sync {
+    async fn list_databases(
+        &self,
+        req: ListDatabasesRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<Database>, Status>;
+    async fn create_database(
+        &self,
+        req: CreateDatabaseRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Operation<Database>, Status>;
+    async fn get_database(
+        &self,
+        req: GetDatabaseRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Database>, Status>;
+    async fn update_database_ddl(
+        &self,
+        req: UpdateDatabaseDdlRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Operation<()>, Status>;
+    async fn drop_database(
+        &self,
+        req: DropDatabaseRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<()>, Status>;
+    async fn get_database_ddl(
+        &self,
+        req: GetDatabaseDdlRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<GetDatabaseDdlResponse>, Status>;
+    async fn set_iam_policy(
+        &self,
+        req: SetIamPolicyRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Policy>, Status>;
+    async fn get_iam_policy(
+        &self,
+        req: GetIamPolicyRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Policy>, Status>;
+    async fn test_iam_permissions(
+        &self,
+        req: TestIamPermissionsRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<TestIamPermissionsResponse>, Status>;
+    async fn create_backup(
+        &self,
+        req: CreateBackupRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Operation<Backup>, Status>;
+    async fn get_backup(&self, req: GetBackupRequest, retry: Option<RetrySetting>) -> Result<Response<Backup>, Status>;
+    async fn update_backup(
+        &self,
+        req: UpdateBackupRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Backup>, Status>;
+    async fn delete_backup(
+        &self,
+        req: DeleteBackupRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<()>, Status>;
+    async fn list_backups(&self, req: ListBackupsRequest, retry: Option<RetrySetting>) -> Result<Vec<Backup>, Status>;
+    async fn restore_database(
+        &self,
+        req: RestoreDatabaseRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Operation<Database>, Status>;
+    async fn list_backup_operations(
+        &self,
+        req: ListBackupOperationsRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<InternalOperation>, Status>;
+    async fn list_database_operations(
+        &self,
+        req: ListDatabaseOperationsRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<InternalOperation>, Status>;
+}
+
+#[async_trait]
+impl DatabaseAdmin for DatabaseAdminClient {
+    async fn list_databases(
+        &self,
+        req: ListDatabasesRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<Database>, Status> {
+        DatabaseAdminClient::list_databases(self, req, retry).await
+    }
+
+    async fn create_database(
+        &self,
+        req: CreateDatabaseRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Operation<Database>, Status> {
+        DatabaseAdminClient::create_database(self, req, retry).await
+    }
+
+    async fn get_database(
+        &self,
+        req: GetDatabaseRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Database>, Status> {
+        DatabaseAdminClient::get_database(self, req, retry).await
+    }
+
+    async fn update_database_ddl(
+        &self,
+        req: UpdateDatabaseDdlRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Operation<()>, Status> {
+        DatabaseAdminClient::update_database_ddl(self, req, retry).await
+    }
+
+    async fn drop_database(
+        &self,
+        req: DropDatabaseRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<()>, Status> {
+        DatabaseAdminClient::drop_database(self, req, retry).await
+    }
+
+    async fn get_database_ddl(
+        &self,
+        req: GetDatabaseDdlRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<GetDatabaseDdlResponse>, Status> {
+        DatabaseAdminClient::get_database_ddl(self, req, retry).await
+    }
+
+    async fn set_iam_policy(
+        &self,
+        req: SetIamPolicyRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Policy>, Status> {
+        DatabaseAdminClient::set_iam_policy(self, req, retry).await
+    }
+
+    async fn get_iam_policy(
+        &self,
+        req: GetIamPolicyRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Policy>, Status> {
+        DatabaseAdminClient::get_iam_policy(self, req, retry).await
+    }
+
+    async fn test_iam_permissions(
+        &self,
+        req: TestIamPermissionsRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<TestIamPermissionsResponse>, Status> {
+        DatabaseAdminClient::test_iam_permissions(self, req, retry).await
+    }
+
+    async fn create_backup(
+        &self,
+        req: CreateBackupRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Operation<Backup>, Status> {
+        DatabaseAdminClient::create_backup(self, req, retry).await
+    }
+
+    async fn get_backup(&self, req: GetBackupRequest, retry: Option<RetrySetting>) -> Result<Response<Backup>, Status> {
+        DatabaseAdminClient::get_backup(self, req, retry).await
+    }
+
+    async fn update_backup(
+        &self,
+        req: UpdateBackupRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Backup>, Status> {
+        DatabaseAdminClient::update_backup(self, req, retry).await
+    }
+
+    async fn delete_backup(
+        &self,
+        req: DeleteBackupRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<()>, Status> {
+        DatabaseAdminClient::delete_backup(self, req, retry).await
+    }
+
+    async fn list_backups(&self, req: ListBackupsRequest, retry: Option<RetrySetting>) -> Result<Vec<Backup>, Status> {
+        DatabaseAdminClient::list_backups(self, req, retry).await
+    }
+
+    async fn restore_database(
+        &self,
+        req: RestoreDatabaseRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Operation<Database>, Status> {
+        DatabaseAdminClient::restore_database(self, req, retry).await
+    }
+
+    async fn list_backup_operations(
+        &self,
+        req: ListBackupOperationsRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<InternalOperation>, Status> {
+        DatabaseAdminClient::list_backup_operations(self, req, retry).await
+    }
+
+    async fn list_database_operations(
+        &self,
+        req: ListDatabaseOperationsRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<InternalOperation>, Status> {
+        DatabaseAdminClient::list_database_operations(self, req, retry).await
+    }
+}
@@ -139,4 +139,41 @@ mod tests {
         };
         let _ = update_result.unwrap();
     }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    #[allow(clippy::result_large_err)]
+    async fn test_mock_database_admin() {
+        use std::sync::Arc;
+
+        use google_cloud_gax::grpc::{Code, Response, Status};
+
+        use crate::admin::database::database_admin_client::{DatabaseAdmin, MockDatabaseAdmin};
+
+        let name = "projects/local-project/instances/test-instance/databases/local-database";
+        let mut mock = MockDatabaseAdmin::new();
+        mock.expect_get_database()
+            .withf(move |req, _| req.name == name)
+            .returning(|req, _| {
+                Ok(Response::new(Database {
+                    name: req.name,
+                    ..Default::default()
+                }))
+            });
+        mock.expect_list_databases()
+            .returning(|_, _| Err(Status::permission_denied("denied")));
+
+        let client: Arc<dyn DatabaseAdmin> = Arc::new(mock);
+        let request = GetDatabaseRequest { name: name.to_string() };
+        let db = client.get_database(request, None).await.unwrap().into_inner();
+        assert_eq!(db.name, name);
+
+        let request = ListDatabasesRequest {
+            parent: "projects/local-project/instances/test-instance".to_string(),
+            page_size: 1,
+            page_token: "".to_string(),
+        };
+        let err = client.list_databases(request, None).await.unwrap_err();
+        assert_eq!(err.code(), Code::PermissionDenied);
+    }
 }
@@ -1,3 +1,5 @@
+use async_trait::async_trait;
+
 use google_cloud_gax::conn::Channel;
 use google_cloud_gax::create_request;
 use google_cloud_gax::grpc::{Response, Status};
@@ -30,7 +32,19 @@ impl InstanceAdminClient {
     }
 
     /// list_instance_configs lists the supported instance configurations for a given project.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "ListInstanceConfigs",
+                db.name = req.parent.as_str(),
+            )
+        )
+    )]
     pub async fn list_instance_configs(
         &self,
         mut req: ListInstanceConfigsRequest,
@@ -59,7 +73,19 @@ impl InstanceAdminClient {
     }
 
     /// get_instance_config gets information about a particular instance configuration.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "GetInstanceConfig",
+                db.name = req.name.as_str(),
+            )
+        )
+    )]
     pub async fn get_instance_config(
         &self,
         req: GetInstanceConfigRequest,
@@ -79,7 +105,19 @@ impl InstanceAdminClient {
     }
 
     /// list_instances lists all instances in the given project.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "ListInstances",
+                db.name = req.parent.as_str(),
+            )
+        )
+    )]
     pub async fn list_instances(
         &self,
         mut req: ListInstancesRequest,
@@ -104,7 +142,19 @@ impl InstanceAdminClient {
     }
 
     /// gets information about a particular instance.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "GetInstance",
+                db.name = req.name.as_str(),
+            )
+        )
+    )]
     pub async fn get_instance(
         &self,
         req: GetInstanceRequest,
@@ -158,7 +208,19 @@ impl InstanceAdminClient {
     /// CreateInstanceMetadata.
     /// The response field type is
     /// Instance, if successful.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "CreateInstance",
+                db.name = req.parent.as_str(),
+            )
+        )
+    )]
     pub async fn create_instance(
         &self,
         req: CreateInstanceRequest,
@@ -219,7 +281,19 @@ impl InstanceAdminClient {
     ///
     /// Authorization requires spanner.instances.update permission on
     /// resource [name][google.spanner.admin.instance.v1.Instance.name (at http://google.spanner.admin.instance.v1.Instance.name)].
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "UpdateInstance",
+                db.name = req.instance.as_ref().map(|i| i.name.as_str()),
+            )
+        )
+    )]
     pub async fn update_instance(
         &self,
         req: UpdateInstanceRequest,
@@ -247,7 +321,19 @@ impl InstanceAdminClient {
     ///   The instance and all of its databases immediately and
     ///   irrevocably disappear from the API. All data in the databases
     ///   is permanently deleted.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "DeleteInstance",
+                db.name = req.name.as_str(),
+            )
+        )
+    )]
     pub async fn delete_instance(
         &self,
         req: DeleteInstanceRequest,
@@ -266,7 +352,19 @@ impl InstanceAdminClient {
     /// existing policy.
     ///
     /// Authorization requires spanner.instances.setIamPolicy on resource.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "SetIamPolicy",
+                db.name = req.resource.as_str(),
+            )
+        )
+    )]
     pub async fn set_iam_policy(
         &self,
         req: SetIamPolicyRequest,
@@ -285,7 +383,19 @@ impl InstanceAdminClient {
     /// existing policy.
     ///
     /// Authorization requires spanner.instances.setIamPolicy on resource.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "GetIamPolicy",
+                db.name = req.resource.as_str(),
+            )
+        )
+    )]
     pub async fn get_iam_policy(
         &self,
         req: GetIamPolicyRequest,
@@ -306,7 +416,19 @@ impl InstanceAdminClient {
     /// result in a NOT_FOUND error if the user has spanner.instances.list
     /// permission on the containing Google Cloud Project. Otherwise returns an
     /// empty set of permissions.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "TestIamPermissions",
+                db.name = req.resource.as_str(),
+            )
+        )
+    )]
     pub async fn test_iam_permissions(
         &self,
         req: TestIamPermissionsRequest,
@@ -321,3 +443,144 @@ impl InstanceAdminClient {
         invoke(retry, action).await
     }
 }
+
+/// InstanceAdmin is the method surface of [InstanceAdminClient].
+/// Depend on `Arc<dyn InstanceAdmin>` to replace the client with `MockInstanceAdmin` enabled by the `mock` feature in the tests.
+#[cfg_attr(feature = "mock", mockall::automock)]
+#[allow(clippy::result_large_err)]
+#[async_trait]
+pub trait InstanceAdmin: Send + Sync {
+    async fn list_instance_configs(
+        &self,
+        req: ListInstanceConfigsRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<InstanceConfig>, Status>;
+    async fn get_instance_config(
+        &self,
+        req: GetInstanceConfigRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<InstanceConfig, Status>;
+    async fn list_instances(
+        &self,
+        req: ListInstancesRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<Instance>, Status>;
+    async fn get_instance(
+        &self,
+        req: GetInstanceRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Instance>, Status>;
+    async fn create_instance(
+        &self,
+        req: CreateInstanceRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Operation<Instance>, Status>;
+    async fn update_instance(
+        &self,
+        req: UpdateInstanceRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Operation<Instance>, Status>;
+    async fn delete_instance(
+        &self,
+        req: DeleteInstanceRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<()>, Status>;
+    async fn set_iam_policy(
+        &self,
+        req: SetIamPolicyRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Policy>, Status>;
+    async fn get_iam_policy(
+        &self,
+        req: GetIamPolicyRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Policy>, Status>;
+    async fn test_iam_permissions(
+        &self,
+        req: TestIamPermissionsRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<TestIamPermissionsResponse>, Status>;
+}
+
+#[async_trait]
+impl InstanceAdmin for InstanceAdminClient {
+    async fn list_instance_configs(
+        &self,
+        req: ListInstanceConfigsRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<InstanceConfig>, Status> {
+        InstanceAdminClient::list_instance_configs(self, req, retry).await
+    }
+
+    async fn get_instance_config(
+        &self,
+        req: GetInstanceConfigRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<InstanceConfig, Status> {
+        InstanceAdminClient::get_instance_config(self, req, retry).await
+    }
+
+    async fn list_instances(
+        &self,
+        req: ListInstancesRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Vec<Instance>, Status> {
+        InstanceAdminClient::list_instances(self, req, retry).await
+    }
+
+    async fn get_instance(
+        &self,
+        req: GetInstanceRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Instance>, Status> {
+        InstanceAdminClient::get_instance(self, req, retry).await
+    }
+
+    async fn create_instance(
+        &self,
+        req: CreateInstanceRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Operation<Instance>, Status> {
+        InstanceAdminClient::create_instance(self, req, retry).await
+    }
+
+    async fn update_instance(
+        &self,
+        req: UpdateInstanceRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Operation<Instance>, Status> {
+        InstanceAdminClient::update_instance(self, req, retry).await
+    }
+
+    async fn delete_instance(
+        &self,
+        req: DeleteInstanceRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<()>, Status> {
+        InstanceAdminClient::delete_instance(self, req, retry).await
+    }
+
+    async fn set_iam_policy(
+        &self,
+        req: SetIamPolicyRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Policy>, Status> {
+        InstanceAdminClient::set_iam_policy(self, req, retry).await
+    }
+
+    async fn get_iam_policy(
+        &self,
+        req: GetIamPolicyRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<Policy>, Status> {
+        InstanceAdminClient::get_iam_policy(self, req, retry).await
+    }
+
+    async fn test_iam_permissions(
+        &self,
+        req: TestIamPermissionsRequest,
+        retry: Option<RetrySetting>,
+    ) -> Result<Response<TestIamPermissionsResponse>, Status> {
+        InstanceAdminClient::test_iam_permissions(self, req, retry).await
+    }
+}
@@ -1,4 +1,5 @@
 use std::env::var;
+use std::sync::Arc;
 use std::time::Duration;
 
 use google_cloud_gax::conn::Environment;
@@ -6,6 +7,8 @@ use google_cloud_gax::grpc::Code;
 use google_cloud_gax::retry::RetrySetting;
 use google_cloud_token::NopeTokenSourceProvider;
 
+use crate::slow_operation::{SlowOperation, SlowOperationConfig};
+
 pub mod client;
 pub mod database;
 pub mod instance;
@@ -18,6 +21,32 @@ pub const SCOPES: [&str; 2] = [
 pub struct AdminClientConfig {
     /// Runtime project
     pub environment: Environment,
+    /// The retry setting of the long-running operation requests, also used as the back off of `Operation::wait(None)`.
+    /// None uses the default of the operations client.
+    pub lro_retry: Option<RetrySetting>,
+    /// The timeout of each long-running operation request.
+    pub lro_timeout: Option<Duration>,
+    /// The project billed for the quota of the long-running operation requests.
+    pub quota_project: Option<String>,
+    /// The hook invoked when waiting for the long-running operation takes longer than the threshold.
+    /// Set it by `on_slow_operation`.
+    pub slow_operation: Option<SlowOperationConfig>,
+}
+
+impl AdminClientConfig {
+    /// on_slow_operation invokes the callback when waiting for the long-running operation
+    /// with `Operation::wait` or `.await` takes longer than the threshold. See `slow_operation` for the details.
+    pub fn on_slow_operation(
+        mut self,
+        threshold: Duration,
+        callback: impl Fn(SlowOperation) + Send + Sync + 'static,
+    ) -> Self {
+        self.slow_operation = Some(SlowOperationConfig {
+            threshold,
+            callback: Arc::new(callback),
+        });
+        self
+    }
 }
 
 impl Default for AdminClientConfig {
@@ -27,6 +56,10 @@ impl Default for AdminClientConfig {
                 Some(v) => Environment::Emulator(v),
                 None => Environment::GoogleCloud(Box::new(NopeTokenSourceProvider {})),
             },
+            lro_retry: None,
+            lro_timeout: None,
+            quota_project: None,
+            slow_operation: None,
         }
     }
 }
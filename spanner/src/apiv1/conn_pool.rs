@@ -1,7 +1,10 @@
-use google_cloud_gax::conn::{ConnectionManager as GRPCConnectionManager, ConnectionOptions, Environment, Error};
+use google_cloud_gax::conn::{
+    Channel, ConnectionManager as GRPCConnectionManager, ConnectionOptions, Environment, Error,
+};
 use google_cloud_googleapis::spanner::v1::spanner_client::SpannerClient;
 
-use crate::apiv1::spanner_client::Client;
+use crate::apiv1::spanner_client::{Client, TraceConfig};
+use crate::metrics::ClientMetrics;
 
 pub const AUDIENCE: &str = "https://spanner.googleapis.com/";
 pub const SPANNER: &str = "spanner.googleapis.com";
@@ -12,6 +15,10 @@ pub const SCOPES: [&str; 2] = [
 
 pub struct ConnectionManager {
     inner: GRPCConnectionManager,
+    trace_config: TraceConfig,
+    metrics: ClientMetrics,
+    route_to_leader: bool,
+    emulator: bool,
 }
 
 impl ConnectionManager {
@@ -23,15 +30,58 @@ impl ConnectionManager {
     ) -> Result<Self, Error> {
         Ok(ConnectionManager {
             inner: GRPCConnectionManager::new(pool_size, domain, AUDIENCE, environment, conn_options).await?,
+            trace_config: TraceConfig::default(),
+            metrics: ClientMetrics::default(),
+            route_to_leader: false,
+            emulator: matches!(environment, Environment::Emulator(_)),
         })
     }
 
+    /// with_trace_config sets the attributes recorded on the spans of the clients created by `conn`.
+    pub fn with_trace_config(mut self, trace_config: TraceConfig) -> Self {
+        self.trace_config = trace_config;
+        self
+    }
+
+    pub(crate) fn with_metrics(mut self, metrics: ClientMetrics) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// with_route_to_leader enables the leader aware routing of the clients created by `conn`.
+    pub fn with_route_to_leader(mut self, route_to_leader: bool) -> Self {
+        self.route_to_leader = route_to_leader;
+        self
+    }
+
     pub fn num(&self) -> usize {
         self.inner.num()
     }
 
     pub fn conn(&self) -> Client {
-        let conn = self.inner.conn();
+        self.client(self.inner.conn())
+    }
+
+    /// conn_at returns the client using the channel at the index.
+    pub fn conn_at(&self, index: usize) -> Client {
+        self.client(self.inner.conn_at(index))
+    }
+
+    /// replace reconnects the channel at the index after the transport failure.
+    pub async fn replace(&self, index: usize) -> Result<(), Error> {
+        self.inner.replace(index).await
+    }
+
+    /// generation returns the number of the times the channel at the index has been replaced.
+    pub fn generation(&self, index: usize) -> usize {
+        self.inner.generation(index)
+    }
+
+    fn client(&self, conn: Channel) -> Client {
         Client::new(SpannerClient::new(conn))
+            .with_trace_config(self.trace_config.clone())
+            .with_metrics(self.metrics.clone())
+            .with_route_to_leader(self.route_to_leader)
+            .with_emulator(self.emulator)
     }
 }
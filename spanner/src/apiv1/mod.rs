@@ -18,7 +18,11 @@ mod tests {
     use google_cloud_googleapis::spanner::v1::{execute_batch_dml_request, KeySet, Mutation};
 
     use crate::apiv1::conn_pool::ConnectionManager;
-    use crate::apiv1::spanner_client::Client;
+    use crate::apiv1::spanner_client::{
+        create_routed_request, leader_aware_options, leader_aware_selector, sanitize_sql, Client, ExtraMetadata,
+        RedactionPolicy, TraceConfig, ROUTE_TO_LEADER_HEADER,
+    };
+    use crate::statement::Statement;
 
     const DATABASE: &str = "projects/local-project/instances/test-instance/databases/local-database";
 
@@ -47,6 +51,8 @@ mod tests {
         let request = BeginTransactionRequest {
             session: session.name.to_string(),
             options: Option::from(TransactionOptions {
+                exclude_txn_from_change_streams: false,
+                isolation_level: 0,
                 mode: Option::from(transaction_options::Mode::ReadOnly(transaction_options::ReadOnly {
                     return_read_timestamp: false,
                     timestamp_bound: None,
@@ -61,6 +67,8 @@ mod tests {
         let request = BeginTransactionRequest {
             session: session.name.to_string(),
             options: Some(TransactionOptions {
+                exclude_txn_from_change_streams: false,
+                isolation_level: 0,
                 mode: Some(transaction_options::Mode::ReadWrite(transaction_options::ReadWrite::default())),
             }),
             request_options: None,
@@ -190,6 +198,7 @@ mod tests {
             query_options: None,
             request_options: None,
             data_boost_enabled: false,
+            last_statement: false,
         };
         match client.execute_sql(request, None).await {
             Ok(res) => {
@@ -217,6 +226,7 @@ mod tests {
             query_options: None,
             request_options: None,
             data_boost_enabled: false,
+            last_statement: false,
         };
 
         let resume_token = match client.execute_streaming_sql(request.clone(), None).await {
@@ -251,6 +261,8 @@ mod tests {
         let request = BeginTransactionRequest {
             session: session.name.to_string(),
             options: Option::from(TransactionOptions {
+                exclude_txn_from_change_streams: false,
+                isolation_level: 0,
                 mode: Option::from(transaction_options::Mode::ReadOnly(transaction_options::ReadOnly {
                     return_read_timestamp: false,
                     timestamp_bound: None,
@@ -296,6 +308,7 @@ mod tests {
             ],
             seqno: 0,
             request_options: None,
+            last_statements: false,
         };
 
         let result = client.execute_batch_dml(request, None).await;
@@ -337,6 +350,7 @@ mod tests {
             }],
             seqno: 0,
             request_options: None,
+            last_statements: false,
         };
 
         let result = client.execute_batch_dml(request, None).await;
@@ -548,4 +562,178 @@ mod tests {
             }
         };
     }
+
+    #[test]
+    fn test_trace_config_statement() {
+        let sql = "SELECT * FROM User WHERE UserId = 'user1' AND Age > 20 AND Id = @id";
+        let config = TraceConfig::default();
+        assert_eq!(config.statement(sql), None);
+
+        let config = TraceConfig {
+            redaction: RedactionPolicy::SanitizedStatement,
+            max_statement_length: None,
+        };
+        assert_eq!(
+            config.statement(sql).as_deref(),
+            Some("SELECT * FROM User WHERE UserId = ? AND Age > ? AND Id = @id")
+        );
+
+        let config = TraceConfig {
+            redaction: RedactionPolicy::FullStatement,
+            max_statement_length: None,
+        };
+        assert_eq!(config.statement(sql).as_deref(), Some(sql));
+
+        let config = TraceConfig {
+            redaction: RedactionPolicy::FullStatement,
+            max_statement_length: Some(9),
+        };
+        assert_eq!(config.statement("SELECT 'あいう'").as_deref(), Some("SELECT '"));
+        let statements = vec![
+            execute_batch_dml_request::Statement {
+                sql: "DELETE A".to_string(),
+                params: None,
+                param_types: Default::default(),
+            },
+            execute_batch_dml_request::Statement {
+                sql: "DELETE B".to_string(),
+                params: None,
+                param_types: Default::default(),
+            },
+        ];
+        assert_eq!(config.batch_statement(&statements).as_deref(), Some("DELETE A;"));
+    }
+
+    #[test]
+    fn test_trace_config_params() {
+        let mut stmt = Statement::new("SELECT @id, @name");
+        stmt.add_param("name", &"user1");
+        stmt.add_param("id", &vec![1_i64, 2]);
+        let params = prost_types::Struct {
+            fields: stmt.params.into_iter().collect(),
+        };
+        for redaction in [RedactionPolicy::OperationOnly, RedactionPolicy::SanitizedStatement] {
+            let config = TraceConfig {
+                redaction,
+                max_statement_length: None,
+            };
+            assert_eq!(config.params(Some(&params)), None);
+        }
+        let config = TraceConfig {
+            redaction: RedactionPolicy::FullStatement,
+            max_statement_length: None,
+        };
+        assert_eq!(
+            config.params(Some(&params)).as_deref(),
+            Some(r#"@id=["1", "2"], @name="user1""#)
+        );
+        assert_eq!(config.params(None), None);
+    }
+
+    #[test]
+    fn test_sanitize_sql() {
+        let cases = [
+            ("SELECT 1", "SELECT ?"),
+            ("SELECT * FROM T1 WHERE c2 = -1.5e+10", "SELECT * FROM T1 WHERE c2 = -?"),
+            (
+                r#"SELECT "a", 'it\'s', b'\x01', RB"x", """multi"line""" FROM `T 1`"#,
+                "SELECT ?, ?, ?, ?, ? FROM `T 1`",
+            ),
+            ("SELECT @p1, $1 -- 'secret'\nFROM T # comment", "SELECT @p1, $1 \nFROM T "),
+            ("SELECT /* 'secret' */ col_1 FROM T", "SELECT  col_1 FROM T"),
+            (
+                "SELECT 0x1F, c FROM T WHERE s = 'unterminated",
+                "SELECT ?, c FROM T WHERE s = ?",
+            ),
+        ];
+        for (sql, expected) in cases {
+            assert_eq!(sanitize_sql(sql), expected, "{sql}");
+        }
+    }
+
+    fn mode_options(mode: transaction_options::Mode) -> Option<TransactionOptions> {
+        Some(TransactionOptions {
+            exclude_txn_from_change_streams: false,
+            isolation_level: 0,
+            mode: Some(mode),
+        })
+    }
+
+    #[test]
+    fn test_leader_aware_routing() {
+        let read_write = mode_options(transaction_options::Mode::ReadWrite(transaction_options::ReadWrite::default()));
+        let pdml = mode_options(transaction_options::Mode::PartitionedDml(
+            transaction_options::PartitionedDml::default(),
+        ));
+        let read_only = mode_options(transaction_options::Mode::ReadOnly(transaction_options::ReadOnly::default()));
+
+        // BeginTransaction
+        assert!(leader_aware_options(&read_write));
+        assert!(leader_aware_options(&pdml));
+        assert!(!leader_aware_options(&read_only));
+        assert!(!leader_aware_options(&None));
+
+        // ExecuteSql / Read
+        let selector = |s: transaction_selector::Selector| Some(TransactionSelector { selector: Some(s) });
+        assert!(leader_aware_selector(&selector(transaction_selector::Selector::Begin(
+            read_write.clone().unwrap()
+        ))));
+        assert!(leader_aware_selector(&selector(transaction_selector::Selector::SingleUse(
+            pdml.unwrap()
+        ))));
+        assert!(!leader_aware_selector(&selector(transaction_selector::Selector::SingleUse(
+            read_only.unwrap()
+        ))));
+        assert!(!leader_aware_selector(&selector(transaction_selector::Selector::Id(vec![1]))));
+        assert!(!leader_aware_selector(&None));
+
+        // header
+        let request = create_routed_request("session=s".to_string(), CommitRequest::default(), true);
+        assert_eq!(request.metadata().get(ROUTE_TO_LEADER_HEADER).unwrap(), "true");
+        let request = create_routed_request("session=s".to_string(), ExecuteSqlRequest::default(), false);
+        assert!(request.metadata().get(ROUTE_TO_LEADER_HEADER).is_none());
+    }
+
+    #[test]
+    fn test_extra_metadata() {
+        let entries = vec![
+            ("x-custom-header".to_string(), "value".to_string()),
+            ("x-custom-data-bin".to_string(), "\u{1}binary".to_string()),
+        ];
+        let metadata = ExtraMetadata::parse(&entries).unwrap();
+        let mut request = create_routed_request("session=s".to_string(), CommitRequest::default(), true);
+        metadata.apply(&mut request);
+        assert_eq!(request.metadata().get("x-custom-header").unwrap(), "value");
+        assert_eq!(
+            request
+                .metadata()
+                .get_bin("x-custom-data-bin")
+                .unwrap()
+                .to_bytes()
+                .unwrap()
+                .as_ref(),
+            "\u{1}binary".as_bytes()
+        );
+        assert_eq!(request.metadata().get(ROUTE_TO_LEADER_HEADER).unwrap(), "true");
+        assert_eq!(request.metadata().get("x-goog-request-params").unwrap(), "session=s");
+
+        for key in [
+            "X-Upper",
+            "",
+            "x-space key",
+            "grpc-timeout",
+            "authorization",
+            "x-goog-request-params",
+            ROUTE_TO_LEADER_HEADER,
+        ] {
+            let status = ExtraMetadata::parse(&[(key.to_string(), "v".to_string())])
+                .err()
+                .unwrap();
+            assert_eq!(status.code(), Code::InvalidArgument, "key={key}");
+        }
+        let status = ExtraMetadata::parse(&[("x-custom".to_string(), "new\nline".to_string())])
+            .err()
+            .unwrap();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
 }
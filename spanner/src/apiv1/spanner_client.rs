@@ -1,17 +1,25 @@
+use std::fmt::{Debug, Formatter};
 use std::time::Duration;
 
 use google_cloud_gax::conn::Channel;
 use google_cloud_gax::create_request;
-use google_cloud_gax::grpc::{Code, Response, Status, Streaming};
+use google_cloud_gax::grpc::metadata::{Ascii, Binary, MetadataKey, MetadataValue};
+use google_cloud_gax::grpc::{Code, Request, Response, Status, Streaming};
 use google_cloud_gax::retry::{invoke_fn, RetrySetting};
 use google_cloud_googleapis::spanner::v1::spanner_client::SpannerClient;
 use google_cloud_googleapis::spanner::v1::{
-    BatchCreateSessionsRequest, BatchCreateSessionsResponse, BeginTransactionRequest, CommitRequest, CommitResponse,
-    CreateSessionRequest, DeleteSessionRequest, ExecuteBatchDmlRequest, ExecuteBatchDmlResponse, ExecuteSqlRequest,
-    GetSessionRequest, ListSessionsRequest, ListSessionsResponse, PartialResultSet, PartitionQueryRequest,
-    PartitionReadRequest, PartitionResponse, ReadRequest, ResultSet, RollbackRequest, Session, Transaction,
+    commit_request, execute_batch_dml_request, transaction_options, transaction_selector, BatchCreateSessionsRequest,
+    BatchCreateSessionsResponse, BeginTransactionRequest, CommitRequest, CommitResponse, CreateSessionRequest,
+    DeleteSessionRequest, ExecuteBatchDmlRequest, ExecuteBatchDmlResponse, ExecuteSqlRequest, GetSessionRequest,
+    ListSessionsRequest, ListSessionsResponse, PartialResultSet, PartitionQueryRequest, PartitionReadRequest,
+    PartitionResponse, ReadRequest, ResultSet, RollbackRequest, Session, Transaction, TransactionOptions,
+    TransactionSelector,
 };
 
+use crate::metrics::ClientMetrics;
+use crate::slow_operation::{OperationDetails, StreamOperation};
+use crate::statement::{RedactedStatement, Statement};
+
 pub(crate) fn ping_query_request(session_name: impl Into<String>) -> ExecuteSqlRequest {
     ExecuteSqlRequest {
         session: session_name.into(),
@@ -26,6 +34,7 @@ pub(crate) fn ping_query_request(session_name: impl Into<String>) -> ExecuteSqlR
         query_options: None,
         request_options: None,
         data_boost_enabled: false,
+        last_statement: false,
     }
 }
 
@@ -39,9 +48,375 @@ fn default_setting() -> RetrySetting {
     }
 }
 
+/// RedactionPolicy controls how much of the statements is recorded on the spans.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RedactionPolicy {
+    /// Record only the RPC name and the other operation-level attributes. No SQL is recorded.
+    #[default]
+    OperationOnly,
+    /// Record the SQL as `db.statement` with the literals replaced by `?` and the comments removed.
+    /// The values of the parameters are not recorded.
+    SanitizedStatement,
+    /// Record the SQL as `db.statement` and the values of the parameters as `db.statement.params` as they are.
+    /// The values may contain personal data, so use this only in the development.
+    FullStatement,
+}
+
+/// TraceConfig controls how the statements are recorded on the spans of the `trace` feature and the slow operations,
+/// and how they are printed by `debug_statement` and `redact_statement`.
+/// The Debug of `Statement` and `Statement::redacted` use `TraceConfig::default()`,
+/// so only `Statement::debug_with_values` prints the SQL and the values regardless of `redaction`.
+#[derive(Clone, Debug, Default)]
+pub struct TraceConfig {
+    /// The redaction policy of the SQL. Nothing but the operation is recorded by default
+    /// because the SQL may contain personal data.
+    pub redaction: RedactionPolicy,
+    /// The recorded SQL is truncated to this length in bytes. `None` records the whole SQL.
+    pub max_statement_length: Option<usize>,
+}
+
+impl TraceConfig {
+    /// statement returns the SQL recorded as `db.statement`.
+    pub fn statement(&self, sql: &str) -> Option<String> {
+        let sql = match self.redaction {
+            RedactionPolicy::OperationOnly => return None,
+            RedactionPolicy::SanitizedStatement => sanitize_sql(sql),
+            RedactionPolicy::FullStatement => sql.to_string(),
+        };
+        Some(self.truncate(sql))
+    }
+
+    /// batch_statement returns the SQLs of the batch DML recorded as `db.statement`.
+    pub fn batch_statement(&self, statements: &[execute_batch_dml_request::Statement]) -> Option<String> {
+        let sql = statements
+            .iter()
+            .map(|s| s.sql.as_str())
+            .collect::<Vec<_>>()
+            .join(";\n");
+        self.statement(&sql)
+    }
+
+    /// params returns the parameters recorded as `db.statement.params` like `@id=1, @name="a"`.
+    /// Only `RedactionPolicy::FullStatement` records the parameters.
+    pub fn params(&self, params: Option<&prost_types::Struct>) -> Option<String> {
+        if self.redaction != RedactionPolicy::FullStatement {
+            return None;
+        }
+        let params = params.filter(|p| !p.fields.is_empty())?;
+        let mut names: Vec<&String> = params.fields.keys().collect();
+        names.sort();
+        let params = names
+            .into_iter()
+            .map(|name| format!("@{name}={}", format_kind(params.fields[name].kind.as_ref())))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Some(self.truncate(params))
+    }
+
+    /// debug_statement returns the Debug printing the statement according to the policy.
+    /// The SQL is printed as `statement` returns and the values are printed only with `RedactionPolicy::FullStatement`.
+    pub fn debug_statement<'a>(&self, statement: &'a Statement) -> impl Debug + 'a {
+        DebugStatement {
+            config: self.clone(),
+            statement,
+        }
+    }
+
+    /// redact_statement returns the SQL formatted by `statement` and the names and the types of the parameters.
+    pub fn redact_statement(&self, statement: &Statement) -> RedactedStatement {
+        RedactedStatement {
+            sql: self.statement(&statement.sql),
+            params: statement.redacted_params(),
+        }
+    }
+
+    fn truncate(&self, mut value: String) -> String {
+        if let Some(max) = self.max_statement_length {
+            if value.len() > max {
+                let mut end = max;
+                while !value.is_char_boundary(end) {
+                    end -= 1;
+                }
+                value.truncate(end);
+            }
+        }
+        value
+    }
+}
+
+struct DebugStatement<'a> {
+    config: TraceConfig,
+    statement: &'a Statement,
+}
+
+impl Debug for DebugStatement<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let sql = self.config.statement(&self.statement.sql);
+        let with_values = self.config.redaction == RedactionPolicy::FullStatement;
+        self.statement.fmt_debug(f, sql.as_deref(), with_values)
+    }
+}
+
+fn format_kind(kind: Option<&prost_types::value::Kind>) -> String {
+    use prost_types::value::Kind;
+    match kind {
+        None | Some(Kind::NullValue(_)) => "NULL".to_string(),
+        Some(Kind::BoolValue(v)) => v.to_string(),
+        Some(Kind::NumberValue(v)) => v.to_string(),
+        Some(Kind::StringValue(v)) => format!("{v:?}"),
+        Some(Kind::ListValue(v)) => format!(
+            "[{}]",
+            v.values
+                .iter()
+                .map(|v| format_kind(v.kind.as_ref()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Some(Kind::StructValue(v)) => {
+            let mut names: Vec<&String> = v.fields.keys().collect();
+            names.sort();
+            let fields = names
+                .into_iter()
+                .map(|name| format!("{name}: {}", format_kind(v.fields[name].kind.as_ref())))
+                .collect::<Vec<_>>();
+            format!("{{{}}}", fields.join(", "))
+        }
+    }
+}
+
+/// sanitize_sql replaces the string, bytes and numeric literals with `?` and removes the comments.
+/// The identifiers quoted with the backticks and the parameter names are kept,
+/// but the double-quoted identifiers of the PostgreSQL dialect are replaced as the literals.
+pub(crate) fn sanitize_sql(sql: &str) -> String {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut sanitized = String::with_capacity(sql.len());
+    let mut i = 0;
+    // true while in the identifier, the keyword or the parameter name
+    let mut in_word = false;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '-' if next == Some('-') => i = skip_line(&chars, i),
+            '#' => i = skip_line(&chars, i),
+            '/' if next == Some('*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+                in_word = false;
+            }
+            '`' => {
+                // the quoted identifier
+                let end = skip_quoted(&chars, i, '`');
+                sanitized.extend(&chars[i..end]);
+                i = end;
+                in_word = false;
+            }
+            '\'' | '"' => {
+                // the prefixes of the raw and the bytes literals like rb'...' are the part of the literal
+                let prefix = sanitized
+                    .chars()
+                    .rev()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .count();
+                let is_prefix = sanitized
+                    .chars()
+                    .rev()
+                    .take(prefix)
+                    .all(|c| matches!(c, 'r' | 'R' | 'b' | 'B'));
+                if in_word && prefix <= 2 && is_prefix {
+                    sanitized.truncate(sanitized.len() - prefix);
+                }
+                sanitized.push('?');
+                i = skip_quoted(&chars, i, c);
+                in_word = false;
+            }
+            c if c.is_ascii_digit() && !in_word => {
+                i += 1;
+                while i < chars.len() {
+                    let c = chars[i];
+                    let exponent_sign = (c == '+' || c == '-') && matches!(chars[i - 1], 'e' | 'E');
+                    if c.is_alphanumeric() || c == '.' || c == '_' || exponent_sign {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                sanitized.push('?');
+            }
+            c => {
+                in_word = c.is_alphanumeric() || c == '_' || c == '@' || c == '$';
+                sanitized.push(c);
+                i += 1;
+            }
+        }
+    }
+    sanitized
+}
+
+fn skip_line(chars: &[char], mut i: usize) -> usize {
+    while i < chars.len() && chars[i] != '\n' {
+        i += 1;
+    }
+    i
+}
+
+/// skip_quoted returns the index next to the closing quote. The triple-quoted literals and the escapes are supported.
+fn skip_quoted(chars: &[char], start: usize, quote: char) -> usize {
+    let triple = quote != '`' && chars.get(start + 1) == Some(&quote) && chars.get(start + 2) == Some(&quote);
+    let mut i = if triple { start + 3 } else { start + 1 };
+    while i < chars.len() {
+        if chars[i] == '\\' {
+            i += 2;
+            continue;
+        }
+        if chars[i] == quote {
+            if !triple {
+                return i + 1;
+            }
+            if chars.get(i + 1) == Some(&quote) && chars.get(i + 2) == Some(&quote) {
+                return i + 3;
+            }
+        }
+        i += 1;
+    }
+    chars.len()
+}
+
+/// database_name returns the database part of the session name.
+#[cfg(feature = "trace")]
+fn database_name(name: &str) -> &str {
+    match name.find("/sessions/") {
+        Some(index) => &name[..index],
+        None => name,
+    }
+}
+
+fn session_id(session: &str) -> &str {
+    session.rsplit('/').next().unwrap_or(session)
+}
+
+#[cfg(feature = "trace")]
+fn transaction_tag(options: &Option<google_cloud_googleapis::spanner::v1::RequestOptions>) -> Option<&str> {
+    options
+        .as_ref()
+        .map(|o| o.transaction_tag.as_str())
+        .filter(|tag| !tag.is_empty())
+}
+
+/// transaction_id returns the ID of the transaction already begun.
+fn transaction_id(selector: &Option<TransactionSelector>) -> Option<Vec<u8>> {
+    match selector.as_ref()?.selector.as_ref()? {
+        transaction_selector::Selector::Id(id) => Some(id.clone()),
+        _ => None,
+    }
+}
+
+pub(crate) const ROUTE_TO_LEADER_HEADER: &str = "x-goog-spanner-route-to-leader";
+
+/// create_routed_request creates the request with the `x-goog-spanner-route-to-leader` header
+/// if route_to_leader is true.
+pub(crate) fn create_routed_request<T>(param_string: String, req: T, route_to_leader: bool) -> Request<T> {
+    let mut request = create_request(param_string, req);
+    if route_to_leader {
+        request
+            .metadata_mut()
+            .insert(ROUTE_TO_LEADER_HEADER, MetadataValue::from_static("true"));
+    }
+    request
+}
+
+/// The headers set by the client. The extra metadata can't overwrite them.
+const RESERVED_HEADERS: [&str; 5] = [
+    "authorization",
+    "content-type",
+    "user-agent",
+    "x-goog-request-params",
+    ROUTE_TO_LEADER_HEADER,
+];
+
+/// ExtraMetadata is the custom metadata sent with each attempt of the request.
+#[derive(Default)]
+pub(crate) struct ExtraMetadata {
+    ascii: Vec<(MetadataKey<Ascii>, MetadataValue<Ascii>)>,
+    binary: Vec<(MetadataKey<Binary>, MetadataValue<Binary>)>,
+}
+
+impl ExtraMetadata {
+    /// parse validates the keys by the gRPC rules. The key must consist of lowercase ASCII letters, digits, `-`, `_` and `.`
+    /// and must not start with `grpc-`. The value of the key ending with `-bin` is sent as binary,
+    /// so that it is base64 encoded on the wire.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn parse(entries: &[(String, String)]) -> Result<Self, Status> {
+        let mut metadata = Self::default();
+        for (key, value) in entries {
+            let valid_key = !key.is_empty()
+                && !key.starts_with("grpc-")
+                && !RESERVED_HEADERS.contains(&key.as_str())
+                && key
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || matches!(b, b'-' | b'_' | b'.'));
+            if !valid_key {
+                return Err(Status::invalid_argument(format!("invalid metadata key: {key}")));
+            }
+            if key.ends_with("-bin") {
+                let k = MetadataKey::from_bytes(key.as_bytes())
+                    .map_err(|_| Status::invalid_argument(format!("invalid metadata key: {key}")))?;
+                metadata.binary.push((k, MetadataValue::from_bytes(value.as_bytes())));
+            } else {
+                let k = MetadataKey::from_bytes(key.as_bytes())
+                    .map_err(|_| Status::invalid_argument(format!("invalid metadata key: {key}")))?;
+                let v = MetadataValue::try_from(value.as_str())
+                    .map_err(|_| Status::invalid_argument(format!("invalid metadata value: key={key}")))?;
+                metadata.ascii.push((k, v));
+            }
+        }
+        Ok(metadata)
+    }
+
+    /// apply appends the metadata after the standard headers.
+    pub(crate) fn apply<T>(&self, request: &mut Request<T>) {
+        let metadata = request.metadata_mut();
+        for (k, v) in &self.ascii {
+            metadata.append(k.clone(), v.clone());
+        }
+        for (k, v) in &self.binary {
+            metadata.append_bin(k.clone(), v.clone());
+        }
+    }
+}
+
+/// leader_aware_options returns true if the transaction is read-write or partitioned DML.
+pub(crate) fn leader_aware_options(options: &Option<TransactionOptions>) -> bool {
+    leader_aware_mode(options.as_ref())
+}
+
+/// leader_aware_selector returns true if the selector begins a read-write or partitioned DML transaction.
+/// The type of the transaction can't be determined by the transaction id.
+pub(crate) fn leader_aware_selector(selector: &Option<TransactionSelector>) -> bool {
+    match selector.as_ref().and_then(|s| s.selector.as_ref()) {
+        Some(transaction_selector::Selector::Begin(options)) => leader_aware_mode(Some(options)),
+        Some(transaction_selector::Selector::SingleUse(options)) => leader_aware_mode(Some(options)),
+        _ => false,
+    }
+}
+
+fn leader_aware_mode(options: Option<&TransactionOptions>) -> bool {
+    matches!(
+        options.and_then(|o| o.mode.as_ref()),
+        Some(transaction_options::Mode::ReadWrite(_)) | Some(transaction_options::Mode::PartitionedDml(_))
+    )
+}
+
 #[derive(Clone)]
 pub struct Client {
     inner: SpannerClient<Channel>,
+    trace_config: TraceConfig,
+    metrics: ClientMetrics,
+    route_to_leader: bool,
+    emulator: bool,
 }
 
 impl Client {
@@ -50,9 +425,59 @@ impl Client {
         // https://github.com/googleapis/google-cloud-go/blob/65a9ba55ed3777f520bd881d891e8917323549a5/spanner/apiv1/spanner_client.go#L73
         Client {
             inner: inner.max_decoding_message_size(i32::MAX as usize),
+            trace_config: TraceConfig::default(),
+            metrics: ClientMetrics::default(),
+            route_to_leader: false,
+            emulator: false,
         }
     }
 
+    /// with_trace_config sets the attributes recorded on the spans.
+    pub fn with_trace_config(mut self, trace_config: TraceConfig) -> Client {
+        self.trace_config = trace_config;
+        self
+    }
+
+    pub(crate) fn with_metrics(mut self, metrics: ClientMetrics) -> Client {
+        self.metrics = metrics;
+        self
+    }
+
+    /// with_route_to_leader enables the `x-goog-spanner-route-to-leader` header on the requests
+    /// of the read-write and partitioned DML transactions.
+    pub fn with_route_to_leader(mut self, route_to_leader: bool) -> Client {
+        self.route_to_leader = route_to_leader;
+        self
+    }
+
+    /// with_emulator disables the features the emulator doesn't support like the data boost.
+    pub fn with_emulator(mut self, emulator: bool) -> Client {
+        self.emulator = emulator;
+        self
+    }
+
+    /// start_streaming_sql starts measuring the ExecuteStreamingSql until the stream is completed.
+    /// None if the slow operation hook is not configured.
+    pub(crate) fn start_streaming_sql(&self, req: &ExecuteSqlRequest) -> Option<StreamOperation> {
+        self.metrics.start_stream("ExecuteStreamingSql", || OperationDetails {
+            session_id: session_id(&req.session).to_string(),
+            transaction_id: transaction_id(&req.transaction),
+            statement: self.trace_config.statement(&req.sql),
+            operation_name: None,
+        })
+    }
+
+    /// start_streaming_read starts measuring the StreamingRead until the stream is completed.
+    /// None if the slow operation hook is not configured.
+    pub(crate) fn start_streaming_read(&self, req: &ReadRequest) -> Option<StreamOperation> {
+        self.metrics.start_stream("StreamingRead", || OperationDetails {
+            session_id: session_id(&req.session).to_string(),
+            transaction_id: transaction_id(&req.transaction),
+            statement: None,
+            operation_name: None,
+        })
+    }
+
     /// create_session creates a new session. A session can be used to perform
     /// transactions that read and/or modify data in a Cloud Spanner database.
     /// Sessions are meant to be reused for many consecutive
@@ -72,7 +497,19 @@ impl Client {
     ///
     /// Idle sessions can be kept alive by sending a trivial SQL query
     /// periodically, e.g., "SELECT 1".
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "CreateSession",
+                db.name = database_name(&req.database),
+            )
+        )
+    )]
     pub async fn create_session(
         &mut self,
         req: CreateSessionRequest,
@@ -80,25 +517,41 @@ impl Client {
     ) -> Result<Response<Session>, Status> {
         let setting = retry.unwrap_or_else(default_setting);
         let database = &req.database;
-        invoke_fn(
+        let route_to_leader = self.route_to_leader;
+        let operation = self.metrics.start_operation("CreateSession");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
-                let request = create_request(format!("database={database}"), req.clone());
-                spanner_client
-                    .create_session(request)
-                    .await
-                    .map_err(|e| (e, spanner_client))
+                let request = create_routed_request(format!("database={database}"), req.clone(), route_to_leader);
+                let attempt = operation.start_attempt();
+                let result = spanner_client.create_session(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish(&result);
+        result
     }
 
     /// batch_create_sessions creates multiple new sessions.
     ///
     /// This API can be used to initialize a session cache on the clients.
     /// See https:///goo.gl/TgSFN2 (at https:///goo.gl/TgSFN2) for best practices on session cache management.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "BatchCreateSessions",
+                db.name = database_name(&req.database),
+            )
+        )
+    )]
     pub async fn batch_create_sessions(
         &mut self,
         req: BatchCreateSessionsRequest,
@@ -106,23 +559,40 @@ impl Client {
     ) -> Result<Response<BatchCreateSessionsResponse>, Status> {
         let setting = retry.unwrap_or_else(default_setting);
         let database = &req.database;
-        invoke_fn(
+        let route_to_leader = self.route_to_leader;
+        let operation = self.metrics.start_operation("BatchCreateSessions");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
-                let request = create_request(format!("database={database}"), req.clone());
-                spanner_client
-                    .batch_create_sessions(request)
-                    .await
-                    .map_err(|e| (e, spanner_client))
+                let request = create_routed_request(format!("database={database}"), req.clone(), route_to_leader);
+                let attempt = operation.start_attempt();
+                let result = spanner_client.batch_create_sessions(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish(&result);
+        result
     }
 
     /// get_session gets a session. Returns NOT_FOUND if the session does not exist.
     /// This is mainly useful for determining whether a session is still alive.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "GetSession",
+                db.name = database_name(&req.name),
+                spanner.session_id = session_id(&req.name),
+            )
+        )
+    )]
     pub async fn get_session(
         &mut self,
         req: GetSessionRequest,
@@ -130,22 +600,37 @@ impl Client {
     ) -> Result<Response<Session>, Status> {
         let setting = retry.unwrap_or_else(default_setting);
         let name = &req.name;
-        invoke_fn(
+        let operation = self.metrics.start_operation("GetSession");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
                 let request = create_request(format!("name={name}"), req.clone());
-                spanner_client
-                    .get_session(request)
-                    .await
-                    .map_err(|e| (e, spanner_client))
+                let attempt = operation.start_attempt();
+                let result = spanner_client.get_session(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish(&result);
+        result
     }
 
     /// list_sessions lists all sessions in a given database.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "ListSessions",
+                db.name = database_name(&req.database),
+            )
+        )
+    )]
     pub async fn list_sessions(
         &mut self,
         req: ListSessionsRequest,
@@ -153,24 +638,40 @@ impl Client {
     ) -> Result<Response<ListSessionsResponse>, Status> {
         let setting = retry.unwrap_or_else(default_setting);
         let database = &req.database;
-        invoke_fn(
+        let operation = self.metrics.start_operation("ListSessions");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
                 let request = create_request(format!("database={database}"), req.clone());
-                spanner_client
-                    .list_sessions(request)
-                    .await
-                    .map_err(|e| (e, spanner_client))
+                let attempt = operation.start_attempt();
+                let result = spanner_client.list_sessions(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish(&result);
+        result
     }
 
     /// delete_session ends a session, releasing server resources associated with it. This will
     /// asynchronously trigger cancellation of any operations that are running with
     /// this session.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "DeleteSession",
+                db.name = database_name(&req.name),
+                spanner.session_id = session_id(&req.name),
+            )
+        )
+    )]
     pub async fn delete_session(
         &mut self,
         req: DeleteSessionRequest,
@@ -178,18 +679,21 @@ impl Client {
     ) -> Result<Response<()>, Status> {
         let setting = retry.unwrap_or_else(default_setting);
         let name = &req.name;
-        invoke_fn(
+        let operation = self.metrics.start_operation("DeleteSession");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
                 let request = create_request(format!("name={name}"), req.clone());
-                spanner_client
-                    .delete_session(request)
-                    .await
-                    .map_err(|e| (e, spanner_client))
+                let attempt = operation.start_attempt();
+                let result = spanner_client.delete_session(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish(&result);
+        result
     }
 
     /// execute_sql executes an SQL statement, returning all results in a single reply. This
@@ -203,26 +707,68 @@ impl Client {
     ///
     /// Larger result sets can be fetched in streaming fashion by calling
     /// ExecuteStreamingSql instead.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn execute_sql(
         &mut self,
         req: ExecuteSqlRequest,
         retry: Option<RetrySetting>,
     ) -> Result<Response<ResultSet>, Status> {
+        let route_to_leader = leader_aware_selector(&req.transaction);
+        self.execute_sql_with_routing(req, retry, route_to_leader, &[]).await
+    }
+
+    /// execute_sql_with_routing sends the `x-goog-spanner-route-to-leader` header if route_to_leader is true
+    /// and the leader aware routing is enabled.
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            name = "execute_sql",
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "ExecuteSql",
+                db.name = database_name(&req.session),
+                db.statement = self.trace_config.statement(&req.sql).as_deref(),
+                db.statement.params = self.trace_config.params(req.params.as_ref()).as_deref(),
+                spanner.session_id = session_id(&req.session),
+                spanner.transaction_tag = transaction_tag(&req.request_options),
+            )
+        )
+    )]
+    pub(crate) async fn execute_sql_with_routing(
+        &mut self,
+        mut req: ExecuteSqlRequest,
+        retry: Option<RetrySetting>,
+        route_to_leader: bool,
+        extra_metadata: &[(String, String)],
+    ) -> Result<Response<ResultSet>, Status> {
+        req.data_boost_enabled &= !self.emulator;
+        let extra_metadata = ExtraMetadata::parse(extra_metadata)?;
         let setting = retry.unwrap_or_else(default_setting);
         let session = &req.session;
-        invoke_fn(
+        let route_to_leader = self.route_to_leader && route_to_leader;
+        let operation = self.metrics.start_operation("ExecuteSql");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
-                let request = create_request(format!("session={session}"), req.clone());
-                spanner_client
-                    .execute_sql(request)
-                    .await
-                    .map_err(|e| (e, spanner_client))
+                let mut request = create_routed_request(format!("session={session}"), req.clone(), route_to_leader);
+                extra_metadata.apply(&mut request);
+                let attempt = operation.start_attempt();
+                let result = spanner_client.execute_sql(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish_with_details(&result, || OperationDetails {
+            session_id: session_id(&req.session).to_string(),
+            transaction_id: transaction_id(&req.transaction),
+            statement: self.trace_config.statement(&req.sql),
+            operation_name: None,
+        });
+        result
     }
 
     /// execute_streaming_sql like ExecuteSql, except returns the result
@@ -230,26 +776,64 @@ impl Client {
     /// is no limit on the size of the returned result set. However, no
     /// individual row in the result set can exceed 100 MiB, and no
     /// column value can exceed 10 MiB.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn execute_streaming_sql(
         &mut self,
         req: ExecuteSqlRequest,
         retry: Option<RetrySetting>,
     ) -> Result<Response<Streaming<PartialResultSet>>, Status> {
+        let route_to_leader = leader_aware_selector(&req.transaction);
+        self.execute_streaming_sql_with_routing(req, retry, route_to_leader, &[])
+            .await
+    }
+
+    /// execute_streaming_sql_with_routing sends the `x-goog-spanner-route-to-leader` header if route_to_leader is true
+    /// and the leader aware routing is enabled.
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            name = "execute_streaming_sql",
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "ExecuteStreamingSql",
+                db.name = database_name(&req.session),
+                db.statement = self.trace_config.statement(&req.sql).as_deref(),
+                db.statement.params = self.trace_config.params(req.params.as_ref()).as_deref(),
+                spanner.session_id = session_id(&req.session),
+                spanner.transaction_tag = transaction_tag(&req.request_options),
+            )
+        )
+    )]
+    pub(crate) async fn execute_streaming_sql_with_routing(
+        &mut self,
+        mut req: ExecuteSqlRequest,
+        retry: Option<RetrySetting>,
+        route_to_leader: bool,
+        extra_metadata: &[(String, String)],
+    ) -> Result<Response<Streaming<PartialResultSet>>, Status> {
+        req.data_boost_enabled &= !self.emulator;
+        let extra_metadata = ExtraMetadata::parse(extra_metadata)?;
         let setting = retry.unwrap_or_else(default_setting);
         let session = &req.session;
-        invoke_fn(
+        let route_to_leader = self.route_to_leader && route_to_leader;
+        let operation = self.metrics.start_operation("ExecuteStreamingSql");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
-                let request = create_request(format!("session={session}"), req.clone());
-                spanner_client
-                    .execute_streaming_sql(request)
-                    .await
-                    .map_err(|e| (e, spanner_client))
+                let mut request = create_routed_request(format!("session={session}"), req.clone(), route_to_leader);
+                extra_metadata.apply(&mut request);
+                let attempt = operation.start_attempt();
+                let result = spanner_client.execute_streaming_sql(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish(&result);
+        result
     }
 
     /// execute_batch_dml executes a batch of SQL DML statements. This method allows many statements
@@ -263,19 +847,51 @@ impl Client {
     ///
     /// Execution stops after the first failed statement; the remaining statements
     /// are not executed.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn execute_batch_dml(
         &mut self,
         req: ExecuteBatchDmlRequest,
         retry: Option<RetrySetting>,
     ) -> Result<Response<ExecuteBatchDmlResponse>, Status> {
+        self.execute_batch_dml_with_metadata(req, retry, &[]).await
+    }
+
+    /// execute_batch_dml_with_metadata sends the extra metadata with the request.
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            name = "execute_batch_dml",
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "ExecuteBatchDml",
+                db.name = database_name(&req.session),
+                db.statement = self.trace_config.batch_statement(&req.statements).as_deref(),
+                spanner.session_id = session_id(&req.session),
+                spanner.transaction_tag = transaction_tag(&req.request_options),
+            )
+        )
+    )]
+    pub(crate) async fn execute_batch_dml_with_metadata(
+        &mut self,
+        req: ExecuteBatchDmlRequest,
+        retry: Option<RetrySetting>,
+        extra_metadata: &[(String, String)],
+    ) -> Result<Response<ExecuteBatchDmlResponse>, Status> {
+        let extra_metadata = ExtraMetadata::parse(extra_metadata)?;
         let setting = retry.unwrap_or_else(default_setting);
         let session = &req.session;
-        invoke_fn(
+        let route_to_leader = self.route_to_leader;
+        let operation = self.metrics.start_operation("ExecuteBatchDml");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
-                let request = create_request(format!("session={session}"), req.clone());
+                let mut request = create_routed_request(format!("session={session}"), req.clone(), route_to_leader);
+                extra_metadata.apply(&mut request);
+                let attempt = operation.start_attempt();
                 let result = spanner_client.execute_batch_dml(request).await;
+                attempt.finish(&result);
                 match result {
                     Ok(response) => match response.get_ref().status.as_ref() {
                         Some(s) => {
@@ -293,7 +909,14 @@ impl Client {
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish_with_details(&result, || OperationDetails {
+            session_id: session_id(&req.session).to_string(),
+            transaction_id: transaction_id(&req.transaction),
+            statement: self.trace_config.batch_statement(&req.statements),
+            operation_name: None,
+        });
+        result
     }
 
     /// read reads rows from the database using key lookups and scans, as a
@@ -309,19 +932,62 @@ impl Client {
     ///
     /// Larger result sets can be yielded in streaming fashion by calling
     /// StreamingRead instead.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn read(&mut self, req: ReadRequest, retry: Option<RetrySetting>) -> Result<Response<ResultSet>, Status> {
+        let route_to_leader = leader_aware_selector(&req.transaction);
+        self.read_with_routing(req, retry, route_to_leader, &[]).await
+    }
+
+    /// read_with_routing sends the `x-goog-spanner-route-to-leader` header if route_to_leader is true
+    /// and the leader aware routing is enabled.
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            name = "read",
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "Read",
+                db.name = database_name(&req.session),
+                spanner.session_id = session_id(&req.session),
+                spanner.transaction_tag = transaction_tag(&req.request_options),
+            )
+        )
+    )]
+    pub(crate) async fn read_with_routing(
+        &mut self,
+        mut req: ReadRequest,
+        retry: Option<RetrySetting>,
+        route_to_leader: bool,
+        extra_metadata: &[(String, String)],
+    ) -> Result<Response<ResultSet>, Status> {
+        req.data_boost_enabled &= !self.emulator;
+        let extra_metadata = ExtraMetadata::parse(extra_metadata)?;
         let setting = retry.unwrap_or_else(default_setting);
         let session = &req.session;
-        invoke_fn(
+        let route_to_leader = self.route_to_leader && route_to_leader;
+        let operation = self.metrics.start_operation("Read");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
-                let request = create_request(format!("session={session}"), req.clone());
-                spanner_client.read(request).await.map_err(|e| (e, spanner_client))
+                let mut request = create_routed_request(format!("session={session}"), req.clone(), route_to_leader);
+                extra_metadata.apply(&mut request);
+                let attempt = operation.start_attempt();
+                let result = spanner_client.read(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish_with_details(&result, || OperationDetails {
+            session_id: session_id(&req.session).to_string(),
+            transaction_id: transaction_id(&req.transaction),
+            statement: None,
+            operation_name: None,
+        });
+        result
     }
 
     /// streaming_read like read, except returns the result set as a
@@ -329,33 +995,82 @@ impl Client {
     /// size of the returned result set. However, no individual row in
     /// the result set can exceed 100 MiB, and no column value can exceed
     /// 10 MiB.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn streaming_read(
         &mut self,
         req: ReadRequest,
         retry: Option<RetrySetting>,
     ) -> Result<Response<Streaming<PartialResultSet>>, Status> {
+        let route_to_leader = leader_aware_selector(&req.transaction);
+        self.streaming_read_with_routing(req, retry, route_to_leader, &[]).await
+    }
+
+    /// streaming_read_with_routing sends the `x-goog-spanner-route-to-leader` header if route_to_leader is true
+    /// and the leader aware routing is enabled.
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            name = "streaming_read",
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "StreamingRead",
+                db.name = database_name(&req.session),
+                spanner.session_id = session_id(&req.session),
+                spanner.transaction_tag = transaction_tag(&req.request_options),
+            )
+        )
+    )]
+    pub(crate) async fn streaming_read_with_routing(
+        &mut self,
+        mut req: ReadRequest,
+        retry: Option<RetrySetting>,
+        route_to_leader: bool,
+        extra_metadata: &[(String, String)],
+    ) -> Result<Response<Streaming<PartialResultSet>>, Status> {
+        req.data_boost_enabled &= !self.emulator;
+        let extra_metadata = ExtraMetadata::parse(extra_metadata)?;
         let setting = retry.unwrap_or_else(default_setting);
         let session = &req.session;
-        invoke_fn(
+        let route_to_leader = self.route_to_leader && route_to_leader;
+        let operation = self.metrics.start_operation("StreamingRead");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
-                let request = create_request(format!("session={session}"), req.clone());
-                spanner_client
-                    .streaming_read(request)
-                    .await
-                    .map_err(|e| (e, spanner_client))
+                let mut request = create_routed_request(format!("session={session}"), req.clone(), route_to_leader);
+                extra_metadata.apply(&mut request);
+                let attempt = operation.start_attempt();
+                let result = spanner_client.streaming_read(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish(&result);
+        result
     }
 
     /// BeginTransaction begins a new transaction. This step can often be skipped:
     /// Read, ExecuteSql and
     /// Commit can begin a new transaction as a
     /// side-effect.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "BeginTransaction",
+                db.name = database_name(&req.session),
+                spanner.session_id = session_id(&req.session),
+                spanner.transaction_tag = transaction_tag(&req.request_options),
+            )
+        )
+    )]
     pub async fn begin_transaction(
         &mut self,
         req: BeginTransactionRequest,
@@ -363,18 +1078,22 @@ impl Client {
     ) -> Result<Response<Transaction>, Status> {
         let setting = retry.unwrap_or_else(default_setting);
         let session = &req.session;
-        invoke_fn(
+        let route_to_leader = self.route_to_leader && leader_aware_options(&req.options);
+        let operation = self.metrics.start_operation("BeginTransaction");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
-                let request = create_request(format!("session={session}"), req.clone());
-                spanner_client
-                    .begin_transaction(request)
-                    .await
-                    .map_err(|e| (e, spanner_client))
+                let request = create_routed_request(format!("session={session}"), req.clone(), route_to_leader);
+                let attempt = operation.start_attempt();
+                let result = spanner_client.begin_transaction(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish(&result);
+        result
     }
 
     /// Commit commits a transaction. The request includes the mutations to be
@@ -391,23 +1110,65 @@ impl Client {
     /// At that point, Cloud Spanner has lost track of the transaction outcome and
     /// we recommend that you perform another read from the database to see the
     /// state of things as they are now.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn commit(
         &mut self,
         req: CommitRequest,
         retry: Option<RetrySetting>,
     ) -> Result<Response<CommitResponse>, Status> {
+        self.commit_with_metadata(req, retry, &[]).await
+    }
+
+    /// commit_with_metadata sends the extra metadata with the request.
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            name = "commit",
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "Commit",
+                db.name = database_name(&req.session),
+                spanner.session_id = session_id(&req.session),
+                spanner.transaction_tag = transaction_tag(&req.request_options),
+            )
+        )
+    )]
+    pub(crate) async fn commit_with_metadata(
+        &mut self,
+        req: CommitRequest,
+        retry: Option<RetrySetting>,
+        extra_metadata: &[(String, String)],
+    ) -> Result<Response<CommitResponse>, Status> {
+        let extra_metadata = ExtraMetadata::parse(extra_metadata)?;
         let setting = retry.unwrap_or_else(default_setting);
         let session = &req.session;
-        invoke_fn(
+        let route_to_leader = self.route_to_leader;
+        let operation = self.metrics.start_operation("Commit");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
-                let request = create_request(format!("session={session}"), req.clone());
-                spanner_client.commit(request).await.map_err(|e| (e, spanner_client))
+                let mut request = create_routed_request(format!("session={session}"), req.clone(), route_to_leader);
+                extra_metadata.apply(&mut request);
+                let attempt = operation.start_attempt();
+                let result = spanner_client.commit(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish_with_details(&result, || OperationDetails {
+            session_id: session_id(&req.session).to_string(),
+            transaction_id: match &req.transaction {
+                Some(commit_request::Transaction::TransactionId(id)) => Some(id.clone()),
+                _ => None,
+            },
+            statement: None,
+            operation_name: None,
+        });
+        result
     }
 
     /// Rollback rolls back a transaction, releasing any locks it holds. It is a good
@@ -418,7 +1179,20 @@ impl Client {
     /// Rollback returns OK if it successfully aborts the transaction, the
     /// transaction was already aborted, or the transaction is not
     /// found. Rollback never returns ABORTED.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "Rollback",
+                db.name = database_name(&req.session),
+                spanner.session_id = session_id(&req.session),
+            )
+        )
+    )]
     pub async fn rollback(
         &mut self,
         req: RollbackRequest,
@@ -426,15 +1200,22 @@ impl Client {
     ) -> Result<Response<()>, Status> {
         let setting = retry.unwrap_or_else(default_setting);
         let session = &req.session;
-        invoke_fn(
+        let route_to_leader = self.route_to_leader;
+        let operation = self.metrics.start_operation("Rollback");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
-                let request = create_request(format!("session={session}"), req.clone());
-                spanner_client.rollback(request).await.map_err(|e| (e, spanner_client))
+                let request = create_routed_request(format!("session={session}"), req.clone(), route_to_leader);
+                let attempt = operation.start_attempt();
+                let result = spanner_client.rollback(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish(&result);
+        result
     }
 
     /// PartitionQuery creates a set of partition tokens that can be used to execute a query
@@ -448,7 +1229,22 @@ impl Client {
     /// is deleted, is idle for too long, begins a new transaction, or becomes too
     /// old.  When any of these happen, it is not possible to resume the query, and
     /// the whole operation must be restarted from the beginning.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "PartitionQuery",
+                db.name = database_name(&req.session),
+                db.statement = self.trace_config.statement(&req.sql).as_deref(),
+                db.statement.params = self.trace_config.params(req.params.as_ref()).as_deref(),
+                spanner.session_id = session_id(&req.session),
+            )
+        )
+    )]
     pub async fn partition_query(
         &mut self,
         req: PartitionQueryRequest,
@@ -456,18 +1252,21 @@ impl Client {
     ) -> Result<Response<PartitionResponse>, Status> {
         let setting = retry.unwrap_or_else(default_setting);
         let session = &req.session;
-        invoke_fn(
+        let operation = self.metrics.start_operation("PartitionQuery");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
                 let request = create_request(format!("session={session}"), req.clone());
-                spanner_client
-                    .partition_query(request)
-                    .await
-                    .map_err(|e| (e, spanner_client))
+                let attempt = operation.start_attempt();
+                let result = spanner_client.partition_query(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish(&result);
+        result
     }
 
     /// PartitionRead creates a set of partition tokens that can be used to execute a read
@@ -483,7 +1282,20 @@ impl Client {
     /// is deleted, is idle for too long, begins a new transaction, or becomes too
     /// old.  When any of these happen, it is not possible to resume the read, and
     /// the whole operation must be restarted from the beginning.
-    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    #[cfg_attr(
+        feature = "trace",
+        tracing::instrument(
+            skip_all,
+            err,
+            fields(
+                otel.kind = "client",
+                db.system = "spanner",
+                db.operation = "PartitionRead",
+                db.name = database_name(&req.session),
+                spanner.session_id = session_id(&req.session),
+            )
+        )
+    )]
     pub async fn partition_read(
         &mut self,
         req: PartitionReadRequest,
@@ -491,17 +1303,20 @@ impl Client {
     ) -> Result<Response<PartitionResponse>, Status> {
         let setting = retry.unwrap_or_else(default_setting);
         let session = &req.session;
-        invoke_fn(
+        let operation = self.metrics.start_operation("PartitionRead");
+        let result = invoke_fn(
             Some(setting),
             |spanner_client| async {
                 let request = create_request(format!("session={session}"), req.clone());
-                spanner_client
-                    .partition_read(request)
-                    .await
-                    .map_err(|e| (e, spanner_client))
+                let attempt = operation.start_attempt();
+                let result = spanner_client.partition_read(request).await;
+                attempt.finish(&result);
+                result.map_err(|e| (e, spanner_client))
             },
             &mut self.inner,
         )
-        .await
+        .await;
+        operation.finish(&result);
+        result
     }
 }
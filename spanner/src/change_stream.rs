@@ -0,0 +1,854 @@
+//! Consumer for the change streams.
+//!
+//! `ChangeStreamReader` runs the `READ_<change_stream>` table valued function for every partition,
+//! starts the child partitions after all their parents finish and resumes the partitions from their watermarks
+//! after transient failures.
+//! Only the GoogleSQL dialect is supported.
+//!
+//! ```
+//! use futures_util::StreamExt;
+//! use time::OffsetDateTime;
+//! use google_cloud_spanner::change_stream::{ChangeStreamReader, ChangeStreamRecord};
+//! use google_cloud_spanner::client::{Client, Error};
+//!
+//! async fn run(client: Client) -> Result<(), Error> {
+//!     let mut reader = ChangeStreamReader::new(client, "UserItemChangeStream", OffsetDateTime::now_utc());
+//!     while let Some(record) = reader.next().await {
+//!         match record? {
+//!             ChangeStreamRecord::DataChange { record, .. } => {
+//!                 for m in record.mods {
+//!                     tracing::info!("{:?} {} keys={} new={}", record.mod_type, record.table_name, m.keys, m.new_values);
+//!                 }
+//!             }
+//!             ChangeStreamRecord::Heartbeat { .. } | ChangeStreamRecord::PartitionEnd { .. } => {}
+//!         }
+//!         // persist the progress to resume after restart.
+//!         let checkpoint = reader.checkpoint();
+//!     }
+//!     Ok(())
+//! }
+//! ```
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_util::Stream;
+use parking_lot::Mutex;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+use google_cloud_gax::grpc::{Code, Status};
+use google_cloud_gax::retry::{CodeCondition, Retry, RetrySetting};
+
+use crate::client::{Client, Error};
+use crate::row::{Error as RowError, Row, Struct, TryFromStruct};
+use crate::statement::Statement;
+use crate::transaction::QueryOptions;
+
+#[derive(Clone, Debug)]
+pub struct ChangeStreamReaderConfig {
+    /// The partitions finish at this time. `None` reads the change stream indefinitely.
+    pub end_time: Option<OffsetDateTime>,
+    /// The interval of the heartbeat records when there are no data changes in the partition.
+    pub heartbeat_interval: Duration,
+    /// The number of records buffered before the consumer polls them.
+    pub buffer_size: usize,
+    /// The partition is resumed from its watermark when the query fails with the codes.
+    pub retry: RetrySetting,
+}
+
+impl Default for ChangeStreamReaderConfig {
+    fn default() -> Self {
+        Self {
+            end_time: None,
+            heartbeat_interval: Duration::from_secs(10),
+            buffer_size: 1024,
+            retry: RetrySetting {
+                from_millis: 100,
+                max_delay: Some(Duration::from_secs(32)),
+                factor: 1u64,
+                take: 20,
+                codes: vec![
+                    Code::Unavailable,
+                    Code::Unknown,
+                    Code::Aborted,
+                    Code::Internal,
+                    Code::DeadlineExceeded,
+                    Code::ResourceExhausted,
+                ],
+            },
+        }
+    }
+}
+
+/// ModType is the type of the change.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModType {
+    Insert,
+    Update,
+    Delete,
+}
+
+impl std::str::FromStr for ModType {
+    type Err = RowError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "INSERT" => Ok(ModType::Insert),
+            "UPDATE" => Ok(ModType::Update),
+            "DELETE" => Ok(ModType::Delete),
+            _ => Err(RowError::CustomParseError(format!("unknown mod_type {s}"))),
+        }
+    }
+}
+
+/// ColumnType is the metadata of the column tracked by the change stream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnType {
+    pub name: String,
+    /// JSON representation of the column type like `{"code":"STRING"}`.
+    pub column_type: String,
+    pub is_primary_key: bool,
+    pub ordinal_position: i64,
+}
+
+impl TryFromStruct for ColumnType {
+    fn try_from_struct(s: Struct<'_>) -> Result<Self, RowError> {
+        Ok(Self {
+            name: s.column_by_name("name")?,
+            column_type: s.column_by_name("type")?,
+            is_primary_key: s.column_by_name("is_primary_key")?,
+            ordinal_position: s.column_by_name("ordinal_position")?,
+        })
+    }
+}
+
+/// Mod is the change of a row. The values are the JSON text keyed by the column name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mod {
+    pub keys: String,
+    pub new_values: String,
+    pub old_values: String,
+}
+
+impl TryFromStruct for Mod {
+    fn try_from_struct(s: Struct<'_>) -> Result<Self, RowError> {
+        Ok(Self {
+            keys: s.column_by_name("keys")?,
+            new_values: s.column_by_name("new_values")?,
+            old_values: s.column_by_name("old_values")?,
+        })
+    }
+}
+
+/// DataChangeRecord contains the changes of a table in a transaction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataChangeRecord {
+    pub commit_timestamp: OffsetDateTime,
+    pub record_sequence: String,
+    pub server_transaction_id: String,
+    pub is_last_record_in_transaction_in_partition: bool,
+    pub table_name: String,
+    pub column_types: Vec<ColumnType>,
+    pub mods: Vec<Mod>,
+    pub mod_type: ModType,
+    pub value_capture_type: String,
+    pub number_of_records_in_transaction: i64,
+    pub number_of_partitions_in_transaction: i64,
+    pub transaction_tag: String,
+    pub is_system_transaction: bool,
+}
+
+impl TryFromStruct for DataChangeRecord {
+    fn try_from_struct(s: Struct<'_>) -> Result<Self, RowError> {
+        Ok(Self {
+            commit_timestamp: s.column_by_name("commit_timestamp")?,
+            record_sequence: s.column_by_name("record_sequence")?,
+            server_transaction_id: s.column_by_name("server_transaction_id")?,
+            is_last_record_in_transaction_in_partition: s
+                .column_by_name("is_last_record_in_transaction_in_partition")?,
+            table_name: s.column_by_name("table_name")?,
+            column_types: s.column_by_name("column_types")?,
+            mods: s.column_by_name("mods")?,
+            mod_type: s.column_by_name::<String>("mod_type")?.parse()?,
+            value_capture_type: s.column_by_name("value_capture_type")?,
+            number_of_records_in_transaction: s.column_by_name("number_of_records_in_transaction")?,
+            number_of_partitions_in_transaction: s.column_by_name("number_of_partitions_in_transaction")?,
+            transaction_tag: s.column_by_name("transaction_tag")?,
+            is_system_transaction: s.column_by_name("is_system_transaction")?,
+        })
+    }
+}
+
+struct HeartbeatRecord {
+    timestamp: OffsetDateTime,
+}
+
+impl TryFromStruct for HeartbeatRecord {
+    fn try_from_struct(s: Struct<'_>) -> Result<Self, RowError> {
+        Ok(Self {
+            timestamp: s.column_by_name("timestamp")?,
+        })
+    }
+}
+
+struct ChildPartition {
+    token: String,
+    parent_partition_tokens: Vec<String>,
+}
+
+impl TryFromStruct for ChildPartition {
+    fn try_from_struct(s: Struct<'_>) -> Result<Self, RowError> {
+        Ok(Self {
+            token: s.column_by_name("token")?,
+            parent_partition_tokens: s.column_by_name("parent_partition_tokens")?,
+        })
+    }
+}
+
+struct ChildPartitionsRecord {
+    start_timestamp: OffsetDateTime,
+    child_partitions: Vec<ChildPartition>,
+}
+
+impl TryFromStruct for ChildPartitionsRecord {
+    fn try_from_struct(s: Struct<'_>) -> Result<Self, RowError> {
+        Ok(Self {
+            start_timestamp: s.column_by_name("start_timestamp")?,
+            child_partitions: s.column_by_name("child_partitions")?,
+        })
+    }
+}
+
+struct ChangeRecord {
+    data_change_record: Vec<DataChangeRecord>,
+    heartbeat_record: Vec<HeartbeatRecord>,
+    child_partitions_record: Vec<ChildPartitionsRecord>,
+}
+
+impl TryFromStruct for ChangeRecord {
+    fn try_from_struct(s: Struct<'_>) -> Result<Self, RowError> {
+        Ok(Self {
+            data_change_record: s.column_by_name("data_change_record")?,
+            heartbeat_record: s.column_by_name("heartbeat_record")?,
+            child_partitions_record: s.column_by_name("child_partitions_record")?,
+        })
+    }
+}
+
+/// ChangeStreamRecord is the record received from a partition.
+/// The initial partition has no partition token.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ChangeStreamRecord {
+    DataChange {
+        partition_token: Option<String>,
+        record: DataChangeRecord,
+    },
+    /// No changes were committed in the partition until the timestamp.
+    Heartbeat {
+        partition_token: Option<String>,
+        timestamp: OffsetDateTime,
+    },
+    /// All the records of the partition were received.
+    PartitionEnd { partition_token: Option<String> },
+}
+
+impl ChangeStreamRecord {
+    pub fn partition_token(&self) -> Option<&str> {
+        match self {
+            ChangeStreamRecord::DataChange { partition_token, .. } => partition_token.as_deref(),
+            ChangeStreamRecord::Heartbeat { partition_token, .. } => partition_token.as_deref(),
+            ChangeStreamRecord::PartitionEnd { partition_token } => partition_token.as_deref(),
+        }
+    }
+}
+
+/// PartitionCheckpoint is the progress of a partition.
+/// The watermark is the timestamp of the last record returned by the reader, so the records
+/// committed at the watermark may be received again after resuming.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartitionCheckpoint {
+    pub partition_token: Option<String>,
+    pub watermark: OffsetDateTime,
+    pub finished: bool,
+    /// The partitions which must finish before this partition starts.
+    /// The split partition has a parent and the merged partition has the multiple parents.
+    pub parent_partition_tokens: Vec<String>,
+}
+
+#[derive(Default)]
+struct PartitionStates {
+    partitions: HashMap<Option<String>, PartitionCheckpoint>,
+}
+
+impl PartitionStates {
+    fn apply(&mut self, record: &ChangeStreamRecord) {
+        let token = record.partition_token().map(|v| v.to_string());
+        let Some(state) = self.partitions.get_mut(&token) else {
+            return;
+        };
+        match record {
+            ChangeStreamRecord::DataChange { record, .. } => {
+                state.watermark = state.watermark.max(record.commit_timestamp);
+            }
+            ChangeStreamRecord::Heartbeat { timestamp, .. } => {
+                state.watermark = state.watermark.max(*timestamp);
+            }
+            ChangeStreamRecord::PartitionEnd { .. } => state.finished = true,
+        }
+    }
+
+    /// register adds the child partition.
+    /// The partition merged from the multiple parents is reported by every parent, so the parents are merged.
+    fn register(&mut self, partition: &Partition) {
+        let state = self
+            .partitions
+            .entry(partition.token.clone())
+            .or_insert_with(|| PartitionCheckpoint {
+                partition_token: partition.token.clone(),
+                watermark: partition.start_timestamp,
+                finished: false,
+                parent_partition_tokens: vec![],
+            });
+        for parent in &partition.parent_tokens {
+            if !state.parent_partition_tokens.contains(parent) {
+                state.parent_partition_tokens.push(parent.clone());
+            }
+        }
+    }
+
+    fn checkpoint(&self) -> Vec<PartitionCheckpoint> {
+        let mut result: Vec<PartitionCheckpoint> = self.partitions.values().cloned().collect();
+        result.sort_by(|a, b| a.partition_token.cmp(&b.partition_token));
+        result
+    }
+}
+
+struct Partition {
+    token: Option<String>,
+    start_timestamp: OffsetDateTime,
+    parent_tokens: Vec<String>,
+}
+
+/// PartitionScheduler starts the partition only after all its parents finish,
+/// so the records of a key are received in the commit timestamp order across the split and the merge.
+#[derive(Default)]
+struct PartitionScheduler {
+    started: HashSet<Option<String>>,
+    finished: HashSet<String>,
+    waiting: HashMap<Option<String>, Partition>,
+}
+
+impl PartitionScheduler {
+    /// add returns the partitions ready to start.
+    /// The partition reported again by the other parent is started only once.
+    fn add(&mut self, partition: Partition) -> Vec<Partition> {
+        if self.started.contains(&partition.token) {
+            return vec![];
+        }
+        match self.waiting.get_mut(&partition.token) {
+            Some(waiting) => {
+                for parent in partition.parent_tokens {
+                    if !waiting.parent_tokens.contains(&parent) {
+                        waiting.parent_tokens.push(parent);
+                    }
+                }
+            }
+            None => {
+                self.waiting.insert(partition.token.clone(), partition);
+            }
+        }
+        self.ready()
+    }
+
+    /// finish marks the partition finished and returns the children ready to start.
+    fn finish(&mut self, token: Option<String>) -> Vec<Partition> {
+        if let Some(token) = token {
+            self.finished.insert(token);
+        }
+        self.ready()
+    }
+
+    fn ready(&mut self) -> Vec<Partition> {
+        let ready: Vec<Option<String>> = self
+            .waiting
+            .values()
+            .filter(|p| p.parent_tokens.iter().all(|parent| self.finished.contains(parent)))
+            .map(|p| p.token.clone())
+            .collect();
+        let mut result = Vec::with_capacity(ready.len());
+        for token in ready {
+            if let Some(partition) = self.waiting.remove(&token) {
+                self.started.insert(token);
+                result.push(partition);
+            }
+        }
+        result.sort_by(|a, b| a.token.cmp(&b.token));
+        result
+    }
+}
+
+struct ReaderContext {
+    client: Client,
+    stream_name: String,
+    config: ChangeStreamReaderConfig,
+    states: Arc<Mutex<PartitionStates>>,
+    records: mpsc::Sender<Result<ChangeStreamRecord, Error>>,
+    partitions: mpsc::UnboundedSender<Partition>,
+}
+
+/// ChangeStreamReader is a `Stream` of the records of all the partitions of the change stream.
+/// The records of the same partition are ordered by the commit timestamp, but the records of the different partitions are interleaved.
+/// The child partition is started after all its parents finish, so the records of the same key are ordered by the commit timestamp.
+/// The stream ends when all the partitions reach the end time.
+///
+/// An error which cannot be retried stops only the failed partition, the other partitions continue.
+/// The background tasks are cancelled when the reader is dropped.
+pub struct ChangeStreamReader {
+    records: mpsc::Receiver<Result<ChangeStreamRecord, Error>>,
+    states: Arc<Mutex<PartitionStates>>,
+    cancel: CancellationToken,
+}
+
+impl ChangeStreamReader {
+    /// new starts reading the change stream from the start time.
+    /// This must be called within the tokio runtime.
+    pub fn new(client: Client, stream_name: &str, start_time: OffsetDateTime) -> Self {
+        Self::new_with_config(client, stream_name, start_time, ChangeStreamReaderConfig::default())
+    }
+
+    /// new starts reading the change stream from the start time.
+    /// This must be called within the tokio runtime.
+    pub fn new_with_config(
+        client: Client,
+        stream_name: &str,
+        start_time: OffsetDateTime,
+        config: ChangeStreamReaderConfig,
+    ) -> Self {
+        let checkpoint = PartitionCheckpoint {
+            partition_token: None,
+            watermark: start_time,
+            finished: false,
+            parent_partition_tokens: vec![],
+        };
+        Self::resume(client, stream_name, vec![checkpoint], config)
+    }
+
+    /// resume restarts reading the change stream from the checkpoint returned by `checkpoint`.
+    /// The unfinished partitions are resumed from their watermarks after their unfinished parents finish.
+    pub fn resume(
+        client: Client,
+        stream_name: &str,
+        checkpoint: Vec<PartitionCheckpoint>,
+        config: ChangeStreamReaderConfig,
+    ) -> Self {
+        let (records_tx, records_rx) = mpsc::channel(config.buffer_size.max(1));
+        let (partitions_tx, partitions_rx) = mpsc::unbounded_channel();
+        let states = Arc::new(Mutex::new(PartitionStates::default()));
+        // the parents not in the checkpoint are regarded as finished.
+        let unfinished: HashSet<Option<String>> = checkpoint
+            .iter()
+            .filter(|c| !c.finished)
+            .map(|c| c.partition_token.clone())
+            .collect();
+        let mut initial = vec![];
+        {
+            let mut lock = states.lock();
+            for c in checkpoint {
+                if !c.finished {
+                    initial.push(Partition {
+                        token: c.partition_token.clone(),
+                        start_timestamp: c.watermark,
+                        parent_tokens: c
+                            .parent_partition_tokens
+                            .iter()
+                            .filter(|v| unfinished.contains(&Some(v.to_string())))
+                            .cloned()
+                            .collect(),
+                    });
+                }
+                lock.partitions.insert(c.partition_token.clone(), c);
+            }
+        }
+        let ctx = Arc::new(ReaderContext {
+            client,
+            stream_name: stream_name.to_string(),
+            config,
+            states: states.clone(),
+            records: records_tx,
+            partitions: partitions_tx,
+        });
+        let cancel = CancellationToken::new();
+        let read = move |partition| read_partition(ctx.clone(), partition);
+        tokio::spawn(supervise(read, initial, partitions_rx, cancel.clone()));
+        Self {
+            records: records_rx,
+            states,
+            cancel,
+        }
+    }
+
+    /// checkpoint returns the progress of the all the known partitions.
+    /// The watermarks are advanced only for the records already returned by this reader.
+    pub fn checkpoint(&self) -> Vec<PartitionCheckpoint> {
+        self.states.lock().checkpoint()
+    }
+}
+
+impl Stream for ChangeStreamReader {
+    type Item = Result<ChangeStreamRecord, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let polled = self.records.poll_recv(cx);
+        if let Poll::Ready(Some(Ok(record))) = &polled {
+            self.states.lock().apply(record);
+        }
+        polled
+    }
+}
+
+impl Drop for ChangeStreamReader {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+/// supervise runs `read` for the partitions and their children.
+/// `read` returns the partition token and whether the partition is finished.
+async fn supervise<F, Fut>(
+    read: F,
+    initial: Vec<Partition>,
+    mut partitions: mpsc::UnboundedReceiver<Partition>,
+    cancel: CancellationToken,
+) where
+    F: Fn(Partition) -> Fut,
+    Fut: Future<Output = (Option<String>, bool)> + Send + 'static,
+{
+    let mut scheduler = PartitionScheduler::default();
+    let mut tasks = JoinSet::new();
+    for partition in initial {
+        for ready in scheduler.add(partition) {
+            tasks.spawn(read(ready));
+        }
+    }
+    while !tasks.is_empty() {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            Some(partition) = partitions.recv() => {
+                for ready in scheduler.add(partition) {
+                    tasks.spawn(read(ready));
+                }
+            },
+            Some(result) = tasks.join_next() => {
+                // the child partitions are always sent before the parent finishes.
+                while let Ok(partition) = partitions.try_recv() {
+                    for ready in scheduler.add(partition) {
+                        tasks.spawn(read(ready));
+                    }
+                }
+                if let Ok((token, true)) = result {
+                    for ready in scheduler.finish(token) {
+                        tasks.spawn(read(ready));
+                    }
+                }
+            }
+        }
+    }
+    tracing::trace!("change stream finished");
+}
+
+/// read_partition returns the partition token and whether all the records of the partition are sent.
+async fn read_partition(ctx: Arc<ReaderContext>, partition: Partition) -> (Option<String>, bool) {
+    let mut watermark = partition.start_timestamp;
+    let mut strategy = <RetrySetting as Retry<Status, CodeCondition>>::strategy(&ctx.config.retry);
+    loop {
+        let result = query_partition(&ctx, &partition.token, &mut watermark).await;
+        let err = match result {
+            Ok(true) => {
+                let sent = ctx
+                    .records
+                    .send(Ok(ChangeStreamRecord::PartitionEnd {
+                        partition_token: partition.token.clone(),
+                    }))
+                    .await;
+                return (partition.token, sent.is_ok());
+            }
+            // the consumer is dropped
+            Ok(false) => return (partition.token, false),
+            Err((err, progressed)) => {
+                if progressed {
+                    strategy = <RetrySetting as Retry<Status, CodeCondition>>::strategy(&ctx.config.retry);
+                }
+                err
+            }
+        };
+        let retryable = matches!(&err, Error::GRPC(status) if ctx.config.retry.codes.contains(&status.code()));
+        match strategy.next() {
+            Some(duration) if retryable => {
+                tracing::debug!(
+                    "resume partition {:?} from {} after error: {:?}",
+                    partition.token,
+                    watermark,
+                    err
+                );
+                tokio::time::sleep(duration).await;
+            }
+            _ => {
+                let _ = ctx.records.send(Err(err)).await;
+                return (partition.token, false);
+            }
+        }
+    }
+}
+
+/// query_partition returns Ok(true) when the partition is finished and Ok(false) when the consumer is dropped.
+/// The bool of the error indicates whether some records were received before the error.
+async fn query_partition(
+    ctx: &ReaderContext,
+    token: &Option<String>,
+    watermark: &mut OffsetDateTime,
+) -> Result<bool, (Error, bool)> {
+    let mut stmt = Statement::new(format!(
+        "SELECT ChangeRecord FROM READ_{}(start_timestamp => @start_timestamp, end_timestamp => @end_timestamp, partition_token => @partition_token, heartbeat_milliseconds => @heartbeat_milliseconds)",
+        ctx.stream_name
+    ));
+    stmt.add_param("start_timestamp", &*watermark);
+    stmt.add_param("end_timestamp", &ctx.config.end_time);
+    stmt.add_param("partition_token", token);
+    stmt.add_param("heartbeat_milliseconds", &(ctx.config.heartbeat_interval.as_millis() as i64));
+
+    let mut tx = ctx.client.single().await.map_err(|e| (e, false))?;
+    let options = QueryOptions {
+        enable_resume: false,
+        ..Default::default()
+    };
+    let mut rows = tx
+        .query_with_option(stmt, options)
+        .await
+        .map_err(|e| (e.into(), false))?;
+    let mut progressed = false;
+    loop {
+        let row = match rows.next().await {
+            Ok(Some(row)) => row,
+            Ok(None) => return Ok(true),
+            Err(e) => return Err((e.into(), progressed)),
+        };
+        let records = to_records(ctx, token, &row).map_err(|e| (e.into(), progressed))?;
+        for (timestamp, record) in records {
+            if let Some(record) = record {
+                if ctx.records.send(Ok(record)).await.is_err() {
+                    return Ok(false);
+                }
+            }
+            *watermark = (*watermark).max(timestamp);
+            progressed = true;
+        }
+    }
+}
+
+/// to_records converts the row into the records with their timestamps.
+/// The child partitions are registered and scheduled instead of being returned.
+fn to_records(
+    ctx: &ReaderContext,
+    token: &Option<String>,
+    row: &Row,
+) -> Result<Vec<(OffsetDateTime, Option<ChangeStreamRecord>)>, RowError> {
+    let change_records: Vec<ChangeRecord> = row.column(0)?;
+    let mut result = vec![];
+    for change_record in change_records {
+        for record in change_record.data_change_record {
+            let timestamp = record.commit_timestamp;
+            let record = ChangeStreamRecord::DataChange {
+                partition_token: token.clone(),
+                record,
+            };
+            result.push((timestamp, Some(record)));
+        }
+        for record in change_record.heartbeat_record {
+            let timestamp = record.timestamp;
+            let record = ChangeStreamRecord::Heartbeat {
+                partition_token: token.clone(),
+                timestamp,
+            };
+            result.push((timestamp, Some(record)));
+        }
+        for record in change_record.child_partitions_record {
+            for child in record.child_partitions {
+                let mut parent_tokens = child.parent_partition_tokens;
+                if let Some(token) = token {
+                    if !parent_tokens.contains(token) {
+                        parent_tokens.push(token.clone());
+                    }
+                }
+                let partition = Partition {
+                    token: Some(child.token),
+                    start_timestamp: record.start_timestamp,
+                    parent_tokens,
+                };
+                ctx.states.lock().register(&partition);
+                let _ = ctx.partitions.send(partition);
+            }
+            result.push((record.start_timestamp, None));
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use parking_lot::Mutex;
+    use time::macros::datetime;
+    use tokio::sync::mpsc;
+    use tokio_util::sync::CancellationToken;
+
+    use crate::change_stream::{
+        supervise, ChangeStreamRecord, ModType, Partition, PartitionScheduler, PartitionStates,
+    };
+
+    fn partition(token: &str, parents: &[&str]) -> Partition {
+        Partition {
+            token: Some(token.to_string()),
+            start_timestamp: datetime!(2024-01-01 00:00:00 UTC),
+            parent_tokens: parents.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    fn tokens(partitions: Vec<Partition>) -> Vec<String> {
+        partitions.into_iter().map(|p| p.token.unwrap()).collect()
+    }
+
+    /// run_reader runs the partitions with the fake reader and returns the events in order.
+    /// The reader of the partition sends the children, waits for the delay and finishes.
+    async fn run_reader(children: Vec<(&'static str, Vec<Partition>, u64)>) -> Vec<String> {
+        let events = Arc::new(Mutex::new(vec![]));
+        let children = Arc::new(Mutex::new(children));
+        let (tx, rx) = mpsc::unbounded_channel();
+        let read = {
+            let events = events.clone();
+            move |p: Partition| {
+                let events = events.clone();
+                let children = children.clone();
+                let tx = tx.clone();
+                async move {
+                    let token = p.token.clone().unwrap();
+                    events.lock().push(format!("start:{token}"));
+                    let (delay, sent) = {
+                        let mut children = children.lock();
+                        match children.iter_mut().find(|(t, _, _)| *t == token) {
+                            Some((_, sent, delay)) => (*delay, std::mem::take(sent)),
+                            None => (0, vec![]),
+                        }
+                    };
+                    for child in sent {
+                        tx.send(child).unwrap();
+                    }
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    events.lock().push(format!("end:{token}"));
+                    (p.token, true)
+                }
+            }
+        };
+        supervise(read, vec![partition("A", &[])], rx, CancellationToken::new()).await;
+        let events = events.lock().clone();
+        events
+    }
+
+    fn position(events: &[String], event: &str) -> usize {
+        events.iter().position(|v| v == event).unwrap()
+    }
+
+    #[test]
+    fn test_mod_type() {
+        assert_eq!("INSERT".parse::<ModType>().unwrap(), ModType::Insert);
+        assert_eq!("UPDATE".parse::<ModType>().unwrap(), ModType::Update);
+        assert_eq!("DELETE".parse::<ModType>().unwrap(), ModType::Delete);
+        assert!("UPSERT".parse::<ModType>().is_err());
+    }
+
+    #[test]
+    fn test_partition_states() {
+        let mut states = PartitionStates::default();
+        let start = datetime!(2024-01-01 00:00:00 UTC);
+        states.register(&Partition {
+            token: None,
+            start_timestamp: start,
+            parent_tokens: vec![],
+        });
+        states.register(&partition("child", &["parent1"]));
+        states.register(&Partition {
+            start_timestamp: datetime!(2024-01-01 00:00:01 UTC),
+            ..partition("child", &["parent1", "parent2"])
+        });
+
+        let heartbeat = datetime!(2024-01-01 00:00:10 UTC);
+        states.apply(&ChangeStreamRecord::Heartbeat {
+            partition_token: Some("child".to_string()),
+            timestamp: heartbeat,
+        });
+        states.apply(&ChangeStreamRecord::PartitionEnd { partition_token: None });
+
+        let checkpoint = states.checkpoint();
+        assert_eq!(checkpoint.len(), 2);
+        assert_eq!(checkpoint[0].partition_token, None);
+        assert_eq!(checkpoint[0].watermark, start);
+        assert!(checkpoint[0].finished);
+        assert_eq!(checkpoint[1].partition_token.as_deref(), Some("child"));
+        assert_eq!(checkpoint[1].watermark, heartbeat);
+        assert!(!checkpoint[1].finished);
+        assert_eq!(checkpoint[1].parent_partition_tokens, vec!["parent1", "parent2"]);
+    }
+
+    #[test]
+    fn test_scheduler_split() {
+        let mut scheduler = PartitionScheduler::default();
+        assert_eq!(tokens(scheduler.add(partition("A", &[]))), vec!["A"]);
+        assert!(scheduler.add(partition("B", &["A"])).is_empty());
+        assert!(scheduler.add(partition("C", &["A"])).is_empty());
+        assert_eq!(tokens(scheduler.finish(Some("A".to_string()))), vec!["B", "C"]);
+        assert!(scheduler.add(partition("B", &["A"])).is_empty());
+    }
+
+    #[test]
+    fn test_scheduler_merge() {
+        let mut scheduler = PartitionScheduler::default();
+        assert_eq!(tokens(scheduler.add(partition("B", &[]))), vec!["B"]);
+        assert_eq!(tokens(scheduler.add(partition("C", &[]))), vec!["C"]);
+        // every parent reports the merged partition
+        assert!(scheduler.add(partition("D", &["B", "C"])).is_empty());
+        assert!(scheduler.finish(Some("B".to_string())).is_empty());
+        assert!(scheduler.add(partition("D", &["C"])).is_empty());
+        assert_eq!(tokens(scheduler.finish(Some("C".to_string()))), vec!["D"]);
+        assert!(scheduler.add(partition("D", &["B", "C"])).is_empty());
+        assert!(scheduler.finish(Some("D".to_string())).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reader_split() {
+        let events = run_reader(vec![("A", vec![partition("B", &["A"]), partition("C", &["A"])], 50)]).await;
+        assert_eq!(events.len(), 6, "{events:?}");
+        assert!(position(&events, "end:A") < position(&events, "start:B"), "{events:?}");
+        assert!(position(&events, "end:A") < position(&events, "start:C"), "{events:?}");
+    }
+
+    #[tokio::test]
+    async fn test_reader_merge() {
+        let events = run_reader(vec![
+            ("A", vec![partition("B", &["A"]), partition("C", &["A"])], 0),
+            ("B", vec![partition("D", &["B", "C"])], 0),
+            ("C", vec![partition("D", &["B", "C"])], 50),
+        ])
+        .await;
+        assert_eq!(events.len(), 8, "{events:?}");
+        assert_eq!(events.iter().filter(|v| *v == "start:D").count(), 1);
+        assert!(position(&events, "end:B") < position(&events, "start:D"), "{events:?}");
+        assert!(position(&events, "end:C") < position(&events, "start:D"), "{events:?}");
+    }
+}
@@ -1,23 +1,35 @@
+use std::convert::Infallible;
 use std::env::var;
 use std::fmt::Debug;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
+use futures_util::StreamExt;
+use time::OffsetDateTime;
+
 use google_cloud_gax::conn::{ConnectionOptions, Environment};
+use google_cloud_gax::grpc::transport::{Certificate, Identity};
 use google_cloud_gax::grpc::{Code, Status};
 use google_cloud_gax::retry::{invoke_fn, TryAs};
 use google_cloud_googleapis::spanner::v1::{commit_request, transaction_options, Mutation, TransactionOptions};
 use google_cloud_token::NopeTokenSourceProvider;
 
 use crate::apiv1::conn_pool::{ConnectionManager, SPANNER};
-use crate::retry::TransactionRetrySetting;
-use crate::session::{ManagedSession, SessionConfig, SessionError, SessionManager};
+use crate::apiv1::spanner_client::TraceConfig;
+use crate::key::{Key, KeySet};
+use crate::metrics::{ClientMetrics, MetricsConfig};
+use crate::mutation::chunk_mutations;
+use crate::retry::{TransactionRetry, TransactionRetrySetting};
+use crate::row::Row;
+use crate::session::{ManagedSession, SessionConfig, SessionError, SessionManager, SessionPoolStats};
+use crate::slow_operation::{SlowOperation, SlowOperationConfig};
 use crate::statement::Statement;
-use crate::transaction::{CallOptions, QueryOptions};
-use crate::transaction_ro::{BatchReadOnlyTransaction, ReadOnlyTransaction};
-use crate::transaction_rw::{commit, CommitOptions, ReadWriteTransaction};
+use crate::transaction::{CallOptions, QueryOptions, ReadOptions, RowCount};
+use crate::transaction_ro::{BatchReadOnlyTransaction, ReadOnlyTransaction, SharedReadOnlyTransaction};
+use crate::transaction_rw::{commit, CommitOptions, IsolationLevel, ReadWriteTransaction, TxHandle};
 use crate::value::{Timestamp, TimestampBound};
 
 #[derive(Clone, Default)]
@@ -41,18 +53,105 @@ impl Default for ReadOnlyTransactionOption {
     }
 }
 
+#[derive(Clone)]
+pub struct ReadRowOption {
+    /// Strong read by default. Use `TimestampBound::max_staleness` or similar for bounded-staleness reads.
+    pub timestamp_bound: TimestampBound,
+    /// The index, limit and call options used for the read.
+    pub read_options: ReadOptions,
+}
+
+impl Default for ReadRowOption {
+    fn default() -> Self {
+        ReadRowOption {
+            timestamp_bound: TimestampBound::strong_read(),
+            read_options: ReadOptions::default(),
+        }
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct ReadWriteTransactionOption {
     pub begin_options: CallOptions,
     pub commit_options: CommitOptions,
+    /// Overrides `ClientConfig::transaction_retry` for this transaction.
+    pub retry: Option<TransactionRetrySetting>,
+    /// Overrides `ClientConfig::isolation_level` for this transaction.
+    pub isolation_level: Option<IsolationLevel>,
+}
+
+#[derive(Clone)]
+pub struct ApplyAtLeastOnceOption {
+    pub commit_options: CommitOptions,
+    /// When true, the mutations are not recorded in the change streams with the DDL option `allow_txn_exclusion=true`.
+    pub exclude_txn_from_change_streams: bool,
+    /// The mutations may be applied more than once on retry, so no retry is performed by default.
+    /// Set the retry setting like `TransactionRetrySetting::default()` to retry on ABORTED.
+    pub retry: TransactionRetrySetting,
+}
+
+impl Default for ApplyAtLeastOnceOption {
+    fn default() -> Self {
+        ApplyAtLeastOnceOption {
+            commit_options: CommitOptions::default(),
+            exclude_txn_from_change_streams: false,
+            retry: TransactionRetrySetting::new(vec![]),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ApplyInChunksOption {
+    /// The maximum number of the mutations per commit. Spanner rejects the commit with more than 80,000 mutations.
+    pub max_mutations_per_chunk: usize,
+    /// The maximum size of the mutations per commit in bytes. Spanner rejects the commit larger than 100MB.
+    pub max_bytes_per_chunk: usize,
+    /// The number of the chunks committed concurrently.
+    pub max_concurrency: usize,
+    /// When true, the chunks not started yet are not applied after a chunk fails.
+    pub stop_on_error: bool,
+    pub transaction_option: ReadWriteTransactionOption,
+}
+
+impl Default for ApplyInChunksOption {
+    fn default() -> Self {
+        ApplyInChunksOption {
+            max_mutations_per_chunk: 40_000,
+            max_bytes_per_chunk: 50 * 1024 * 1024,
+            max_concurrency: 4,
+            stop_on_error: false,
+            transaction_option: ReadWriteTransactionOption::default(),
+        }
+    }
+}
+
+/// ChunkResult is the result of a chunk applied by `Client::apply_in_chunks`.
+pub struct ChunkResult {
+    /// The mutations of the chunk. Apply them again to retry the failed chunk.
+    pub mutations: Vec<Mutation>,
+    /// The commit timestamp or the error. None if the chunk was not applied because of `stop_on_error`.
+    pub result: Option<Result<Option<Timestamp>, Error>>,
+}
+
+impl ChunkResult {
+    /// is_committed returns true if the chunk was committed.
+    pub fn is_committed(&self) -> bool {
+        matches!(self.result, Some(Ok(_)))
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct ChannelConfig {
     /// num_channels is the number of gRPC channels.
+    /// The sessions are distributed evenly across the channels, so increase it for the high QPS workloads.
+    /// The emulator always uses a single channel.
     pub num_channels: usize,
     pub connect_timeout: Duration,
     pub timeout: Duration,
+    /// The PEM encoded root certificates trusted in addition to the roots selected by the features.
+    pub ca_certificate: Option<Certificate>,
+    /// The client certificate and key for the mutual TLS.
+    pub identity: Option<Identity>,
 }
 
 impl Default for ChannelConfig {
@@ -61,6 +160,8 @@ impl Default for ChannelConfig {
             num_channels: 4,
             connect_timeout: Duration::from_secs(30),
             timeout: Duration::from_secs(30),
+            ca_certificate: None,
+            identity: None,
         }
     }
 }
@@ -76,6 +177,28 @@ pub struct ClientConfig {
     pub endpoint: String,
     /// Runtime project
     pub environment: Environment,
+    /// TraceConfig is the configuration for the span attributes recorded with the `trace` feature.
+    pub trace_config: TraceConfig,
+    /// MetricsConfig is the configuration for the client-side metrics recorded with the `metrics` feature.
+    pub metrics: MetricsConfig,
+    /// Send the `x-goog-spanner-route-to-leader` header on the requests of the read-write and partitioned DML
+    /// transactions, which reduces the latency of the multi-region instances.
+    pub route_to_leader: bool,
+    /// The retry setting of the read-write transactions on ABORTED, used by `read_write_transaction` and `apply`.
+    /// It can be overridden by `ReadWriteTransactionOption::retry`.
+    pub transaction_retry: TransactionRetrySetting,
+    /// The query options like the priority used by `execute`, `query_one`, `query_opt` and `query_all`.
+    pub query_options: QueryOptions,
+    /// The isolation level of the read-write transactions.
+    /// It can be overridden by `ReadWriteTransactionOption::isolation_level`.
+    pub isolation_level: IsolationLevel,
+    /// The user impersonated by the service account with the domain-wide delegation, used by `with_auth` and
+    /// `with_credentials`. The token is always minted by the token endpoint instead of the self-signed JWT.
+    pub subject: Option<String>,
+    /// The hook invoked when an operation takes longer than the threshold. Set it by `on_slow_operation`.
+    pub slow_operation: Option<SlowOperationConfig>,
+    /// The project billed for the quota of the requests. It is sent as `x-goog-user-project`.
+    pub quota_project: Option<String>,
 }
 
 impl Default for ClientConfig {
@@ -84,14 +207,62 @@ impl Default for ClientConfig {
             channel_config: Default::default(),
             session_config: Default::default(),
             endpoint: SPANNER.to_string(),
-            environment: match var("SPANNER_EMULATOR_HOST").ok() {
-                Some(v) => Environment::Emulator(v),
-                None => Environment::GoogleCloud(Box::new(NopeTokenSourceProvider {})),
-            },
+            environment: Environment::GoogleCloud(Box::new(NopeTokenSourceProvider {})),
+            trace_config: TraceConfig::default(),
+            metrics: MetricsConfig::default(),
+            route_to_leader: true,
+            transaction_retry: TransactionRetrySetting::default(),
+            query_options: QueryOptions::default(),
+            isolation_level: IsolationLevel::default(),
+            subject: None,
+            slow_operation: None,
+            quota_project: None,
         };
         config.session_config.min_opened = config.channel_config.num_channels * 4;
         config.session_config.max_opened = config.channel_config.num_channels * 100;
-        config
+        match var("SPANNER_EMULATOR_HOST").ok() {
+            Some(host) => config.with_emulator(host),
+            None => config,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// with_emulator configures the client to connect to the emulator running on the host like `localhost:9010`.
+    /// The connection is plaintext, the credentials are never resolved and the number of the gRPC connections is 1.
+    /// `session_config.min_opened` and `session_config.max_opened` are clamped to 100 for the single connection,
+    /// so set them after calling this to use the larger values.
+    ///
+    /// The data boost the emulator doesn't support is disabled on each request instead of failing it.
+    /// The multiplexed sessions and the directed reads are never requested by this client,
+    /// so nothing is downgraded for them.
+    ///
+    /// ClientConfig::default() calls this automatically if the `SPANNER_EMULATOR_HOST` environment variable is set.
+    pub fn with_emulator(mut self, host: impl Into<String>) -> Self {
+        self.environment = Environment::Emulator(host.into());
+        self.channel_config.num_channels = 1;
+        self.session_config.min_opened = self.session_config.min_opened.min(100);
+        self.session_config.max_opened = self.session_config.max_opened.min(100);
+        self
+    }
+
+    /// on_slow_operation invokes the callback when an ExecuteSql, Read, Commit or their variants
+    /// take longer than the threshold including all the retries. See `slow_operation` for the details.
+    pub fn on_slow_operation(
+        mut self,
+        threshold: Duration,
+        callback: impl Fn(SlowOperation) + Send + Sync + 'static,
+    ) -> Self {
+        self.slow_operation = Some(SlowOperationConfig {
+            threshold,
+            callback: Arc::new(callback),
+        });
+        self
+    }
+
+    /// is_emulator returns true if the client connects to the emulator.
+    pub fn is_emulator(&self) -> bool {
+        matches!(self.environment, Environment::Emulator(_))
     }
 }
 
@@ -102,7 +273,7 @@ pub use google_cloud_auth;
 impl ClientConfig {
     pub async fn with_auth(mut self) -> Result<Self, google_cloud_auth::error::Error> {
         if let Environment::GoogleCloud(_) = self.environment {
-            let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new(Self::auth_config()).await?;
+            let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new(self.auth_config()).await?;
             self.environment = Environment::GoogleCloud(Box::new(ts))
         }
         Ok(self)
@@ -114,7 +285,7 @@ impl ClientConfig {
     ) -> Result<Self, google_cloud_auth::error::Error> {
         if let Environment::GoogleCloud(_) = self.environment {
             let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new_with_credentials(
-                Self::auth_config(),
+                self.auth_config(),
                 Box::new(credentials),
             )
             .await?;
@@ -123,11 +294,27 @@ impl ClientConfig {
         Ok(self)
     }
 
-    fn auth_config() -> google_cloud_auth::project::Config<'static> {
+    /// with_bootstrap uses the token source, the quota project and the `spanner` endpoint override
+    /// shared by the bootstrap. It connects to the emulator instead if the bootstrap has its host.
+    pub fn with_bootstrap(mut self, bootstrap: &google_cloud_auth::bootstrap::Bootstrap) -> Self {
+        if let Some(endpoint) = bootstrap.endpoint("spanner") {
+            self.endpoint = endpoint.to_string();
+        }
+        if let Some(host) = bootstrap.emulator_host("spanner") {
+            return self.with_emulator(host);
+        }
+        if let Environment::GoogleCloud(_) = self.environment {
+            self.environment = Environment::GoogleCloud(bootstrap.token_source_provider());
+            self.quota_project = bootstrap.quota_project().map(String::from);
+        }
+        self
+    }
+
+    fn auth_config(&self) -> google_cloud_auth::project::Config<'_> {
         google_cloud_auth::project::Config {
             audience: Some(crate::apiv1::conn_pool::AUDIENCE),
             scopes: Some(&crate::apiv1::conn_pool::SCOPES),
-            sub: None,
+            sub: self.subject.as_deref(),
         }
     }
 }
@@ -148,6 +335,18 @@ pub enum Error {
 
     #[error("invalid config: {0}")]
     InvalidConfig(String),
+
+    #[error("no rows found")]
+    NoRows,
+
+    #[error("more than one row found")]
+    TooManyRows,
+}
+
+impl From<Infallible> for Error {
+    fn from(e: Infallible) -> Self {
+        match e {}
+    }
 }
 
 impl TryAs<Status> for Error {
@@ -164,12 +363,19 @@ impl TryAs<Status> for Error {
 #[derive(Clone)]
 pub struct Client {
     sessions: Arc<SessionManager>,
+    transaction_retry: TransactionRetrySetting,
+    query_options: QueryOptions,
+    isolation_level: IsolationLevel,
 }
 
 impl Client {
     /// new creates a client to a database. A valid database name has
     /// the form projects/PROJECT_ID/instances/INSTANCE_ID/databases/DATABASE_ID.
     pub async fn new(database: impl Into<String>, config: ClientConfig) -> Result<Self, Error> {
+        let database = database.into();
+        if config.channel_config.num_channels == 0 {
+            return Err(Error::InvalidConfig("num_channels must be greater than 0".to_string()));
+        }
         if config.session_config.max_opened > config.channel_config.num_channels * 100 {
             return Err(Error::InvalidConfig(format!(
                 "max session size is {} because max session size is 100 per gRPC connection",
@@ -181,13 +387,22 @@ impl Client {
         let options = ConnectionOptions {
             timeout: Some(config.channel_config.timeout),
             connect_timeout: Some(config.channel_config.connect_timeout),
+            ca_certificate: config.channel_config.ca_certificate,
+            identity: config.channel_config.identity,
+            quota_project: config.quota_project,
         };
-        let conn_pool =
-            ConnectionManager::new(pool_size, &config.environment, config.endpoint.as_str(), &options).await?;
+        let conn_pool = ConnectionManager::new(pool_size, &config.environment, config.endpoint.as_str(), &options)
+            .await?
+            .with_trace_config(config.trace_config)
+            .with_metrics(ClientMetrics::new(&config.metrics, &database).with_slow_operation(config.slow_operation))
+            .with_route_to_leader(config.route_to_leader);
         let session_manager = SessionManager::new(database, conn_pool, config.session_config).await?;
 
         Ok(Client {
             sessions: session_manager,
+            transaction_retry: config.transaction_retry,
+            query_options: config.query_options,
+            isolation_level: config.isolation_level,
         })
     }
 
@@ -227,6 +442,134 @@ impl Client {
         Ok(result)
     }
 
+    /// query_single_use executes the query in a single-use read-only transaction with the timestamp bound.
+    /// The timestamp bound is sent with the query, so no BeginTransaction is required.
+    /// It returns the rows and the read timestamp chosen by Cloud Spanner.
+    /// ```
+    /// use std::time::Duration;
+    /// use google_cloud_spanner::client::{Client, Error};
+    /// use google_cloud_spanner::statement::Statement;
+    /// use google_cloud_spanner::value::TimestampBound;
+    ///
+    /// async fn run(client: Client) -> Result<(), Error> {
+    ///     let stmt = Statement::new("SELECT GuildId FROM Guild");
+    ///     let bound = TimestampBound::max_staleness(Duration::from_secs(10));
+    ///     let (rows, read_timestamp) = client.query_single_use(stmt, bound).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn query_single_use(
+        &self,
+        stmt: Statement,
+        tb: TimestampBound,
+    ) -> Result<(Vec<Row>, Option<OffsetDateTime>), Error> {
+        let mut tx = self.single_with_timestamp_bound(tb).await?;
+        let mut iter = tx.query(stmt).await?;
+        let mut rows = vec![];
+        while let Some(row) = iter.next().await? {
+            rows.push(row);
+        }
+        Ok((rows, iter.read_timestamp()))
+    }
+
+    /// read_single_use reads the rows in a single-use read-only transaction with the timestamp bound.
+    /// The timestamp bound is sent with the read, so no BeginTransaction is required.
+    /// It returns the rows and the read timestamp chosen by Cloud Spanner.
+    pub async fn read_single_use(
+        &self,
+        table: &str,
+        columns: &[&str],
+        key_set: impl Into<KeySet>,
+        tb: TimestampBound,
+    ) -> Result<(Vec<Row>, Option<OffsetDateTime>), Error> {
+        let mut tx = self.single_with_timestamp_bound(tb).await?;
+        let mut iter = tx.read(table, columns, key_set).await?;
+        let mut rows = vec![];
+        while let Some(row) = iter.next().await? {
+            rows.push(row);
+        }
+        Ok((rows, iter.read_timestamp()))
+    }
+
+    /// read_row reads a single row by primary key in a single-use read-only transaction.
+    /// It returns `Ok(None)` if the row does not exist.
+    /// ```
+    /// use google_cloud_spanner::key::Key;
+    /// use google_cloud_spanner::client::{Client, Error};
+    ///
+    /// async fn run(client: Client) -> Result<(), Error> {
+    ///     let row = client.read_row("Guild", &["GuildID", "OwnerUserID"], Key::new(&"guild1")).await?;
+    ///     if let Some(row) = row {
+    ///         let owner_user_id = row.column_by_name::<String>("OwnerUserID")?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn read_row(&self, table: &str, columns: &[&str], key: Key) -> Result<Option<Row>, Error> {
+        self.read_row_with_option(table, columns, key, ReadRowOption::default())
+            .await
+    }
+
+    /// read_row reads a single row by primary key in a single-use read-only transaction.
+    /// It returns `Ok(None)` if the row does not exist.
+    pub async fn read_row_with_option(
+        &self,
+        table: &str,
+        columns: &[&str],
+        key: Key,
+        options: ReadRowOption,
+    ) -> Result<Option<Row>, Error> {
+        let mut tx = self.single_with_timestamp_bound(options.timestamp_bound).await?;
+        let row = tx
+            .read_row_with_option(table, columns, key, options.read_options)
+            .await?;
+        Ok(row)
+    }
+
+    /// read_rows reads the rows with the given primary keys in a single-use read-only transaction.
+    /// Keys that do not exist are skipped, so the result may be shorter than `keys`.
+    /// ```
+    /// use google_cloud_spanner::key::Key;
+    /// use google_cloud_spanner::client::{Client, Error, ReadRowOption};
+    /// use google_cloud_spanner::value::TimestampBound;
+    /// use std::time::Duration;
+    ///
+    /// async fn run(client: Client) -> Result<(), Error> {
+    ///     let option = ReadRowOption {
+    ///         timestamp_bound: TimestampBound::max_staleness(Duration::from_secs(10)),
+    ///         ..Default::default()
+    ///     };
+    ///     let rows = client.read_rows_with_option("Guild", &["GuildID", "OwnerUserID"], vec![
+    ///         Key::new(&"guild1"),
+    ///         Key::new(&"guild2")
+    ///     ], option).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn read_rows(&self, table: &str, columns: &[&str], keys: Vec<Key>) -> Result<Vec<Row>, Error> {
+        self.read_rows_with_option(table, columns, keys, ReadRowOption::default())
+            .await
+    }
+
+    /// read_rows reads the rows with the given primary keys in a single-use read-only transaction.
+    /// Keys that do not exist are skipped, so the result may be shorter than `keys`.
+    pub async fn read_rows_with_option(
+        &self,
+        table: &str,
+        columns: &[&str],
+        keys: Vec<Key>,
+        options: ReadRowOption,
+    ) -> Result<Vec<Row>, Error> {
+        if keys.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut tx = self.single_with_timestamp_bound(options.timestamp_bound).await?;
+        let rows = tx
+            .read_rows_with_option(table, columns, keys, options.read_options)
+            .await?;
+        Ok(rows)
+    }
+
     /// read_only_transaction returns a ReadOnlyTransaction that can be used for
     /// multiple reads from the database.
     ///
@@ -277,6 +620,23 @@ impl Client {
         Ok(result)
     }
 
+    /// shared_read_only_transaction returns a SharedReadOnlyTransaction that can be cloned
+    /// and used by the concurrent tasks to read from the same snapshot of the database.
+    pub async fn shared_read_only_transaction(&self) -> Result<SharedReadOnlyTransaction, Error> {
+        self.shared_read_only_transaction_with_option(ReadOnlyTransactionOption::default())
+            .await
+    }
+
+    /// shared_read_only_transaction returns a SharedReadOnlyTransaction that can be cloned
+    /// and used by the concurrent tasks to read from the same snapshot of the database.
+    pub async fn shared_read_only_transaction_with_option(
+        &self,
+        options: ReadOnlyTransactionOption,
+    ) -> Result<SharedReadOnlyTransaction, Error> {
+        let tx = self.read_only_transaction_with_option(options).await?;
+        Ok(tx.into_shared())
+    }
+
     /// batch_read_only_transaction returns a BatchReadOnlyTransaction that can be used
     /// for partitioned reads or queries from a snapshot of the database. This is
     /// useful in batch processing pipelines where one wants to divide the work of
@@ -305,9 +665,9 @@ impl Client {
     /// of many statements each of which accesses only a single row of the table. The
     /// statement should also be idempotent, because it may be applied more than once.
     ///
-    /// PartitionedUpdate returns an estimated count of the number of rows affected.
-    /// The actual number of affected rows may be greater than the estimate.
-    pub async fn partitioned_update(&self, stmt: Statement) -> Result<i64, Error> {
+    /// PartitionedUpdate returns an estimated count of the number of rows affected
+    /// as `RowCount::LowerBound`. The actual number of affected rows may be greater than the estimate.
+    pub async fn partitioned_update(&self, stmt: Statement) -> Result<RowCount, Error> {
         self.partitioned_update_with_option(stmt, PartitionedUpdateOption::default())
             .await
     }
@@ -318,13 +678,13 @@ impl Client {
     /// of many statements each of which accesses only a single row of the table. The
     /// statement should also be idempotent, because it may be applied more than once.
     ///
-    /// PartitionedUpdate returns an estimated count of the number of rows affected.
-    /// The actual number of affected rows may be greater than the estimate.
+    /// PartitionedUpdate returns an estimated count of the number of rows affected
+    /// as `RowCount::LowerBound`. The actual number of affected rows may be greater than the estimate.
     pub async fn partitioned_update_with_option(
         &self,
         stmt: Statement,
         options: PartitionedUpdateOption,
-    ) -> Result<i64, Error> {
+    ) -> Result<RowCount, Error> {
         let ro = TransactionRetrySetting::new(vec![Code::Aborted, Code::Internal]);
         let session = Some(self.get_session().await?);
 
@@ -362,7 +722,8 @@ impl Client {
     /// method may be appropriate for latency sensitive and/or high throughput blind
     /// writing.
     pub async fn apply_at_least_once(&self, ms: Vec<Mutation>) -> Result<Option<Timestamp>, Error> {
-        self.apply_at_least_once_with_option(ms, CommitOptions::default()).await
+        self.apply_at_least_once_with_option(ms, ApplyAtLeastOnceOption::default())
+            .await
     }
 
     /// apply_at_least_once may attempt to apply mutations more than once; if
@@ -374,21 +735,41 @@ impl Client {
     /// apply's default replay protection may require an additional RPC.  So this
     /// method may be appropriate for latency sensitive and/or high throughput blind
     /// writing.
+    ///
+    /// The commit is not retried by default because a retry after ABORTED or UNKNOWN
+    /// may apply the mutations twice. Set `ApplyAtLeastOnceOption::retry` to opt in.
+    /// ```
+    /// use google_cloud_spanner::client::{ApplyAtLeastOnceOption, Client, Error};
+    /// use google_cloud_spanner::mutation::insert_or_update;
+    /// use google_cloud_spanner::retry::TransactionRetrySetting;
+    ///
+    /// async fn run(client: Client) -> Result<(), Error>{
+    ///     let m = insert_or_update("Counter", &["Name", "Value"], &[&"page_view", &1_i64]);
+    ///     let option = ApplyAtLeastOnceOption {
+    ///         exclude_txn_from_change_streams: true,
+    ///         retry: TransactionRetrySetting::default(),
+    ///         ..Default::default()
+    ///     };
+    ///     let commit_timestamp = client.apply_at_least_once_with_option(vec![m], option).await?;
+    ///     Ok(())
+    /// }
+    /// ```
     pub async fn apply_at_least_once_with_option(
         &self,
         ms: Vec<Mutation>,
-        options: CommitOptions,
+        options: ApplyAtLeastOnceOption,
     ) -> Result<Option<Timestamp>, Error> {
-        let ro = TransactionRetrySetting::default();
         let mut session = self.get_session().await?;
 
         invoke_fn(
-            Some(ro),
+            Some(options.retry.clone()),
             |session| async {
                 let tx = commit_request::Transaction::SingleUseTransaction(TransactionOptions {
+                    exclude_txn_from_change_streams: options.exclude_txn_from_change_streams,
+                    isolation_level: transaction_options::IsolationLevel::Unspecified.into(),
                     mode: Some(transaction_options::Mode::ReadWrite(transaction_options::ReadWrite::default())),
                 });
-                match commit(session, ms.clone(), tx, options.clone()).await {
+                match commit(session, ms.clone(), tx, options.commit_options.clone()).await {
                     Ok(s) => Ok(s.commit_timestamp.map(|s| s.into())),
                     Err(e) => Err((Error::GRPC(e), session)),
                 }
@@ -436,6 +817,51 @@ impl Client {
         Ok(result?.0)
     }
 
+    /// apply_in_chunks applies the mutations split into the chunks under the commit limits.
+    /// Each chunk is committed in its own transaction, so the mutations are not applied atomically.
+    /// The results are returned in the order of the chunks, so that only the failed chunks can be retried.
+    /// ```
+    /// use google_cloud_spanner::client::{ApplyInChunksOption, Client};
+    /// use google_cloud_googleapis::spanner::v1::Mutation;
+    ///
+    /// async fn run(client: Client, mutations: Vec<Mutation>) {
+    ///     let results = client.apply_in_chunks(mutations, ApplyInChunksOption::default()).await;
+    ///     let failed: Vec<Mutation> = results
+    ///         .into_iter()
+    ///         .filter(|r| !r.is_committed())
+    ///         .flat_map(|r| r.mutations)
+    ///         .collect();
+    /// }
+    /// ```
+    pub async fn apply_in_chunks(&self, ms: Vec<Mutation>, options: ApplyInChunksOption) -> Vec<ChunkResult> {
+        let chunks = chunk_mutations(ms, options.max_mutations_per_chunk, options.max_bytes_per_chunk);
+        let failed = AtomicBool::new(false);
+        let failed = &failed;
+        let options = &options;
+        futures_util::stream::iter(chunks)
+            .map(|mutations| async move {
+                if options.stop_on_error && failed.load(Ordering::Acquire) {
+                    return ChunkResult {
+                        mutations,
+                        result: None,
+                    };
+                }
+                let result = self
+                    .apply_with_option(mutations.clone(), options.transaction_option.clone())
+                    .await;
+                if result.is_err() {
+                    failed.store(true, Ordering::Release);
+                }
+                ChunkResult {
+                    mutations,
+                    result: Some(result),
+                }
+            })
+            .buffered(options.max_concurrency.max(1))
+            .collect()
+            .await
+    }
+
     /// ReadWriteTransaction executes a read-write transaction, with retries as
     /// necessary.
     ///
@@ -521,21 +947,31 @@ impl Client {
         E: TryAs<Status> + From<SessionError> + From<Status>,
         F: for<'tx> Fn(&'tx mut ReadWriteTransaction) -> Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'tx>>,
     {
+        let retry = &mut TransactionRetry::new_with_setting(self.transaction_retry_setting(&options));
+        let isolation_level = self.isolation_level(&options);
         let (bo, co) = Client::split_read_write_transaction_option(options);
 
-        let ro = TransactionRetrySetting::default();
-        let session = Some(self.get_session().await?);
         // must reuse session
-        invoke_fn(
-            Some(ro),
-            |session| async {
-                let mut tx = self.create_read_write_transaction::<E>(session, bo.clone()).await?;
-                let result = f(&mut tx).await;
-                tx.finish(result, Some(co.clone())).await
-            },
-            session,
-        )
-        .await
+        let mut session = Some(self.get_session().await?);
+        loop {
+            let result = match self
+                .create_read_write_transaction::<E>(session.take(), bo.clone(), isolation_level)
+                .await
+            {
+                Ok(mut tx) => {
+                    let result = f(&mut tx).await;
+                    tx.finish(result, Some(co.clone())).await
+                }
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(v) => return Ok(v),
+                Err((err, s)) => {
+                    retry.next(err).await?;
+                    session = s;
+                }
+            }
+        }
     }
 
     /// begin_read_write_transaction creates new ReadWriteTransaction.
@@ -581,16 +1017,155 @@ impl Client {
     /// ```
     pub async fn begin_read_write_transaction(&self) -> Result<ReadWriteTransaction, Error> {
         let session = self.get_session().await?;
-        ReadWriteTransaction::begin(session, ReadWriteTransactionOption::default().begin_options)
+        let options = ReadWriteTransactionOption::default().begin_options;
+        ReadWriteTransaction::begin_with_isolation_level(session, options, self.isolation_level)
             .await
             .map_err(|e| e.status.into())
     }
 
+    /// begin_read_write begins the read-write transaction committed or rolled back explicitly.
+    /// It is for the frameworks that begin and end the transaction in the different stages of the request.
+    /// ABORTED is not replayed, so `retry` of the options is not used.
+    /// ```
+    /// use google_cloud_spanner::client::{Client, Error, ReadWriteTransactionOption};
+    /// use google_cloud_spanner::statement::Statement;
+    /// use google_cloud_spanner::transaction_rw::{TxError, TxHandle};
+    ///
+    /// async fn begin(client: &Client) -> Result<TxHandle, Error> {
+    ///     client.begin_read_write(ReadWriteTransactionOption::default()).await
+    /// }
+    ///
+    /// async fn handle(tx: &mut TxHandle) -> Result<(), TxError> {
+    ///     let stmt = Statement::new("UPDATE User SET Premium = true WHERE UserId = 'user1'");
+    ///     tx.update(stmt).await?;
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn end(tx: TxHandle, result: Result<(), TxError>) -> Result<(), TxError> {
+    ///     match result {
+    ///         Ok(()) => tx.commit().await.map(|_| ()),
+    ///         Err(e) => {
+    ///             // The transaction is rolled back when it is dropped, roll back explicitly to wait for it.
+    ///             let _ = tx.rollback().await;
+    ///             Err(e)
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub async fn begin_read_write(&self, options: ReadWriteTransactionOption) -> Result<TxHandle, Error> {
+        let isolation_level = self.isolation_level(&options);
+        let (bo, co) = Client::split_read_write_transaction_option(options);
+        let session = self.get_session().await?;
+        let tx = ReadWriteTransaction::begin_with_isolation_level(session, bo, isolation_level)
+            .await
+            .map_err(|e| e.status)?;
+        Ok(TxHandle::new(tx, co))
+    }
+
+    /// execute executes the DML statement in a read-write transaction managed by the client.
+    /// The transaction is retried when it is aborted, so the statement might be executed more than once.
+    /// ```
+    /// use google_cloud_spanner::client::{Client, Error};
+    /// use google_cloud_spanner::statement::Statement;
+    ///
+    /// async fn run(client: Client) -> Result<(), Error> {
+    ///     let mut stmt = Statement::new("UPDATE User SET Premium = true WHERE UserId = @UserId");
+    ///     stmt.add_param("UserId", &"user1");
+    ///     let row_count = client.execute(stmt).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn execute(&self, stmt: Statement) -> Result<RowCount, Error> {
+        let options = self.query_options.clone();
+        let (_, row_count) = self
+            .read_write_transaction(|tx| {
+                let stmt = stmt.clone();
+                let options = options.clone();
+                Box::pin(async move { tx.update_with_option(stmt, options).await.map_err(Error::from) })
+            })
+            .await?;
+        Ok(row_count)
+    }
+
+    /// query_one executes the query in a single-use read-only transaction and returns exactly one row
+    /// converted by `TryFrom<Row>` like the struct with `#[derive(Query)]`.
+    /// It returns `Error::NoRows` if no rows are found and `Error::TooManyRows` if more than one row is found.
+    /// ```
+    /// use google_cloud_spanner::client::{Client, Error};
+    /// use google_cloud_spanner::row::Row;
+    /// use google_cloud_spanner::statement::Statement;
+    ///
+    /// async fn run(client: Client) -> Result<(), Error> {
+    ///     let stmt = Statement::new("SELECT COUNT(*) AS Count FROM User");
+    ///     let row: Row = client.query_one(stmt).await?;
+    ///     let count = row.column_by_name::<i64>("Count")?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn query_one<T>(&self, stmt: Statement) -> Result<T, Error>
+    where
+        T: TryFrom<Row>,
+        Error: From<T::Error>,
+    {
+        self.query_opt(stmt).await?.ok_or(Error::NoRows)
+    }
+
+    /// query_opt executes the query in a single-use read-only transaction and returns zero or one row.
+    /// It returns `Error::TooManyRows` if more than one row is found.
+    pub async fn query_opt<T>(&self, stmt: Statement) -> Result<Option<T>, Error>
+    where
+        T: TryFrom<Row>,
+        Error: From<T::Error>,
+    {
+        let mut tx = self.single().await?;
+        let mut iter = tx.query_with_option(stmt, self.query_options.clone()).await?;
+        let row = match iter.next().await? {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        if iter.next().await?.is_some() {
+            return Err(Error::TooManyRows);
+        }
+        Ok(Some(T::try_from(row)?))
+    }
+
+    /// query_all executes the query in a single-use read-only transaction and returns all the rows.
+    /// ```
+    /// use google_cloud_spanner::client::{Client, Error};
+    /// use google_cloud_spanner::row::Row;
+    /// use google_cloud_spanner::statement::Statement;
+    ///
+    /// async fn run(client: Client) -> Result<(), Error> {
+    ///     let stmt = Statement::new("SELECT UserId FROM User");
+    ///     let rows: Vec<Row> = client.query_all(stmt).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn query_all<T>(&self, stmt: Statement) -> Result<Vec<T>, Error>
+    where
+        T: TryFrom<Row>,
+        Error: From<T::Error>,
+    {
+        let mut tx = self.single().await?;
+        let mut iter = tx.query_with_option(stmt, self.query_options.clone()).await?;
+        let mut rows = vec![];
+        while let Some(row) = iter.next().await? {
+            rows.push(T::try_from(row)?);
+        }
+        Ok(rows)
+    }
+
     /// Get open session count.
     pub fn session_count(&self) -> usize {
         self.sessions.num_opened()
     }
 
+    /// session_pool_stats returns the snapshot of the session pool
+    /// including the number of the sessions of each gRPC channel.
+    pub fn session_pool_stats(&self) -> SessionPoolStats {
+        self.sessions.stats()
+    }
+
     async fn read_write_transaction_sync_with_option<T, E>(
         &self,
         f: impl Fn(&mut ReadWriteTransaction) -> Result<T, E>,
@@ -599,33 +1174,51 @@ impl Client {
     where
         E: TryAs<Status> + From<SessionError> + From<Status>,
     {
+        let retry = &mut TransactionRetry::new_with_setting(self.transaction_retry_setting(&options));
+        let isolation_level = self.isolation_level(&options);
         let (bo, co) = Client::split_read_write_transaction_option(options);
 
-        let ro = TransactionRetrySetting::default();
-        let session = Some(self.get_session().await?);
-
         // reuse session
-        invoke_fn(
-            Some(ro),
-            |session| async {
-                let mut tx = self.create_read_write_transaction::<E>(session, bo.clone()).await?;
-                let result = f(&mut tx);
-                tx.finish(result, Some(co.clone())).await
-            },
-            session,
-        )
-        .await
+        let mut session = Some(self.get_session().await?);
+        loop {
+            let result = match self
+                .create_read_write_transaction::<E>(session.take(), bo.clone(), isolation_level)
+                .await
+            {
+                Ok(mut tx) => {
+                    let result = f(&mut tx);
+                    tx.finish(result, Some(co.clone())).await
+                }
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(v) => return Ok(v),
+                Err((err, s)) => {
+                    retry.next(err).await?;
+                    session = s;
+                }
+            }
+        }
+    }
+
+    fn transaction_retry_setting(&self, options: &ReadWriteTransactionOption) -> TransactionRetrySetting {
+        options.retry.clone().unwrap_or_else(|| self.transaction_retry.clone())
+    }
+
+    fn isolation_level(&self, options: &ReadWriteTransactionOption) -> IsolationLevel {
+        options.isolation_level.unwrap_or(self.isolation_level)
     }
 
     async fn create_read_write_transaction<E>(
         &self,
         session: Option<ManagedSession>,
         bo: CallOptions,
+        isolation_level: IsolationLevel,
     ) -> Result<ReadWriteTransaction, (E, Option<ManagedSession>)>
     where
         E: TryAs<Status> + From<SessionError> + From<Status>,
     {
-        ReadWriteTransaction::begin(session.unwrap(), bo)
+        ReadWriteTransaction::begin_with_isolation_level(session.unwrap(), bo, isolation_level)
             .await
             .map_err(|e| (E::from(e.status), Some(e.session)))
     }
@@ -638,3 +1231,88 @@ impl Client {
         (options.begin_options, options.commit_options)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use google_cloud_gax::conn::Environment;
+
+    use crate::client::ClientConfig;
+
+    #[test]
+    fn test_with_emulator() {
+        let config = ClientConfig::default().with_emulator("localhost:9010");
+        assert!(config.is_emulator());
+        assert!(matches!(&config.environment, Environment::Emulator(host) if host == "localhost:9010"));
+        assert_eq!(config.channel_config.num_channels, 1);
+        // the default min_opened is less than 100.
+        assert_eq!(config.session_config.min_opened, 16);
+        assert_eq!(config.session_config.max_opened, 100);
+
+        let mut config = ClientConfig::default();
+        config.session_config.min_opened = 200;
+        let config = config.with_emulator("localhost:9010");
+        assert_eq!(config.session_config.min_opened, 100);
+    }
+
+    #[test]
+    #[serial]
+    fn test_emulator_env() {
+        let original = std::env::var("SPANNER_EMULATOR_HOST").ok();
+
+        std::env::set_var("SPANNER_EMULATOR_HOST", "localhost:19010");
+        let config = ClientConfig::default();
+        assert!(matches!(&config.environment, Environment::Emulator(host) if host == "localhost:19010"));
+        assert_eq!(config.channel_config.num_channels, 1);
+        assert_eq!(config.session_config.max_opened, 100);
+
+        std::env::remove_var("SPANNER_EMULATOR_HOST");
+        let config = ClientConfig::default();
+        assert!(!config.is_emulator());
+        assert_eq!(config.channel_config.num_channels, 4);
+        assert_eq!(config.session_config.max_opened, 400);
+
+        if let Some(original) = original {
+            std::env::set_var("SPANNER_EMULATOR_HOST", original);
+        }
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    #[serial]
+    async fn test_with_bootstrap() {
+        use google_cloud_auth::bootstrap::ClientBootstrap;
+        use google_cloud_token::NopeTokenSourceProvider;
+
+        let original = std::env::var("SPANNER_EMULATOR_HOST").ok();
+        std::env::remove_var("SPANNER_EMULATOR_HOST");
+        let bootstrap = ClientBootstrap::new()
+            .with_token_provider(Box::new(NopeTokenSourceProvider {}))
+            .with_quota_project("quota-project")
+            .with_endpoint_overrides([("spanner", "spanner.example.com")])
+            .build()
+            .await
+            .unwrap();
+        let config = ClientConfig::default().with_bootstrap(&bootstrap);
+        assert!(!config.is_emulator());
+        assert_eq!(config.endpoint, "spanner.example.com");
+        assert_eq!(config.quota_project.as_deref(), Some("quota-project"));
+
+        std::env::set_var("SPANNER_EMULATOR_HOST", "localhost:19010");
+        let bootstrap = ClientBootstrap::new()
+            .with_token_provider(Box::new(NopeTokenSourceProvider {}))
+            .with_quota_project("quota-project")
+            .build()
+            .await
+            .unwrap();
+        std::env::remove_var("SPANNER_EMULATOR_HOST");
+        let config = ClientConfig::default().with_bootstrap(&bootstrap);
+        assert!(matches!(&config.environment, Environment::Emulator(host) if host == "localhost:19010"));
+        assert_eq!(config.quota_project, None);
+
+        if let Some(original) = original {
+            std::env::set_var("SPANNER_EMULATOR_HOST", original);
+        }
+    }
+}
@@ -0,0 +1,689 @@
+//! Deserialize a [`Row`](crate::row::Row) into any type implementing `serde::Deserialize`.
+//!
+//! Spanner types are mapped to the serde data model as follows.
+//!
+//! | Spanner type | serde data model |
+//! |--------------|------------------|
+//! | INT64        | i64 |
+//! | FLOAT64      | f64 (`NaN`, `Infinity` and `-Infinity` included) |
+//! | BOOL         | bool |
+//! | STRING       | str |
+//! | BYTES        | byte array (or seq of u8). The raw base64 text can be read as str. |
+//! | TIMESTAMP    | str (RFC3339) |
+//! | DATE         | str (`YYYY-MM-DD`) |
+//! | NUMERIC      | str |
+//! | JSON         | any JSON value. The raw JSON text can be read as str. |
+//! | ARRAY        | seq |
+//! | STRUCT       | map keyed by field name, or seq when read as a tuple |
+//! | NULL         | none / unit |
+//!
+//! Unknown fields are ignored unless the target type uses `#[serde(deny_unknown_fields)]`,
+//! and missing columns deserialize into `None` for `Option` fields.
+//!
+//! ```
+//! use google_cloud_spanner::client::{Client, Error};
+//! use google_cloud_spanner::statement::Statement;
+//!
+//! #[derive(serde::Deserialize)]
+//! #[serde(rename_all = "PascalCase")]
+//! struct Guild {
+//!     guild_id: String,
+//!     owner_user_id: Option<String>,
+//! }
+//!
+//! async fn run(client: Client) -> Result<Vec<Guild>, Error> {
+//!     let mut tx = client.single().await?;
+//!     let mut iter = tx.query(Statement::new("SELECT GuildId, OwnerUserId FROM Guild")).await?;
+//!     let mut guilds = vec![];
+//!     while let Some(row) = iter.next().await? {
+//!         guilds.push(row.deserialize::<Guild>()?);
+//!     }
+//!     Ok(guilds)
+//! }
+//! ```
+use std::collections::btree_map;
+use std::fmt::Display;
+
+use base64::prelude::*;
+use prost_types::value::Kind;
+use prost_types::Value;
+use serde::de::value::{BorrowedStrDeserializer, SeqDeserializer};
+use serde::de::{DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::forward_to_deserialize_any;
+
+use google_cloud_googleapis::spanner::v1::struct_type::Field;
+use google_cloud_googleapis::spanner::v1::{Type, TypeCode};
+
+use crate::row::{type_name, Error};
+
+impl serde::de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::DeserializeError(msg.to_string())
+    }
+}
+
+/// Deserializes the columns of a row. Used by `Row::deserialize`.
+pub(crate) fn from_columns<'a, T>(fields: &'a [Field], values: &'a [Value]) -> Result<T, Error>
+where
+    T: serde::Deserialize<'a>,
+{
+    T::deserialize(RowDeserializer { fields, values })
+}
+
+/// Adds the column name and the column type to the error.
+/// Nested struct fields are joined with '.'.
+fn with_field(err: Error, name: &str, tp: Option<&Type>) -> Error {
+    match err {
+        Error::DeserializeError(msg) => Error::DeserializeFieldError(name.to_string(), type_name(tp), msg),
+        Error::DeserializeFieldError(inner, inner_type, msg) => {
+            Error::DeserializeFieldError(format!("{name}.{inner}"), inner_type, msg)
+        }
+        e => e,
+    }
+}
+
+struct RowDeserializer<'a> {
+    fields: &'a [Field],
+    values: &'a [Value],
+}
+
+impl<'de> Deserializer<'de> for RowDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(FieldsAccess::new(self.fields, self.values))
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(ListAccess::new(self.values, None, Some(self.fields)))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct map struct enum identifier ignored_any
+    }
+}
+
+struct ValueDeserializer<'a> {
+    value: &'a Value,
+    tp: Option<&'a Type>,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    fn code(&self) -> TypeCode {
+        self.tp.map(|tp| tp.code()).unwrap_or(TypeCode::Unspecified)
+    }
+
+    fn is_null(&self) -> bool {
+        matches!(self.value.kind, None | Some(Kind::NullValue(_)))
+    }
+
+    fn struct_fields(&self) -> Option<&'a [Field]> {
+        self.tp
+            .and_then(|tp| tp.struct_type.as_ref())
+            .map(|st| st.fields.as_slice())
+    }
+}
+
+fn parse_i64(s: &str) -> Result<i64, Error> {
+    s.parse()
+        .map_err(|e| Error::DeserializeError(format!("invalid INT64 value '{s}': {e}")))
+}
+
+fn parse_f64(s: &str) -> Result<f64, Error> {
+    match s {
+        "NaN" => Ok(f64::NAN),
+        "Infinity" => Ok(f64::INFINITY),
+        "-Infinity" => Ok(f64::NEG_INFINITY),
+        _ => s
+            .parse()
+            .map_err(|e| Error::DeserializeError(format!("invalid FLOAT64 value '{s}': {e}"))),
+    }
+}
+
+fn decode_bytes(s: &str) -> Result<Vec<u8>, Error> {
+    BASE64_STANDARD
+        .decode(s)
+        .map_err(|e| Error::DeserializeError(format!("invalid BYTES value: {e}")))
+}
+
+fn parse_json(s: &str) -> Result<serde_json::Value, Error> {
+    serde_json::from_str(s).map_err(|e| Error::DeserializeError(format!("invalid JSON value: {e}")))
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let kind = match self.value.kind.as_ref() {
+            Some(kind) => kind,
+            None => return visitor.visit_unit(),
+        };
+        match kind {
+            Kind::NullValue(_) => visitor.visit_unit(),
+            Kind::StringValue(s) => match self.code() {
+                TypeCode::Int64 => visitor.visit_i64(parse_i64(s)?),
+                TypeCode::Float64 => visitor.visit_f64(parse_f64(s)?),
+                TypeCode::Bytes => visitor.visit_byte_buf(decode_bytes(s)?),
+                TypeCode::Json => parse_json(s)?
+                    .deserialize_any(visitor)
+                    .map_err(|e| Error::DeserializeError(e.to_string())),
+                _ => visitor.visit_borrowed_str(s),
+            },
+            Kind::NumberValue(n) => visitor.visit_f64(*n),
+            Kind::BoolValue(b) => visitor.visit_bool(*b),
+            Kind::ListValue(list) => match self.struct_fields() {
+                Some(fields) => visitor.visit_map(FieldsAccess::new(fields, &list.values)),
+                None => visitor.visit_seq(ListAccess::new(
+                    &list.values,
+                    self.tp.and_then(|tp| tp.array_element_type.as_deref()),
+                    None,
+                )),
+            },
+            Kind::StructValue(s) => visitor.visit_map(StructValueAccess {
+                iter: s.fields.iter(),
+                fields: self.struct_fields(),
+                current: None,
+            }),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_null() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value.kind.as_ref() {
+            Some(Kind::StringValue(s)) => visitor.visit_borrowed_str(s),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value.kind.as_ref() {
+            Some(Kind::StringValue(s)) if self.code() == TypeCode::Bytes => visitor.visit_byte_buf(decode_bytes(s)?),
+            Some(Kind::StringValue(s)) => visitor.visit_borrowed_bytes(s.as_bytes()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value.kind.as_ref() {
+            // Vec<u8> is deserialized as a sequence.
+            Some(Kind::StringValue(s)) if self.code() == TypeCode::Bytes => {
+                let mut seq = SeqDeserializer::new(decode_bytes(s)?.into_iter());
+                let value = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(value)
+            }
+            Some(Kind::ListValue(list)) => visitor.visit_seq(ListAccess::new(
+                &list.values,
+                self.tp.and_then(|tp| tp.array_element_type.as_deref()),
+                self.struct_fields(),
+            )),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_null() {
+            visitor.visit_unit()
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.value.kind.as_ref() {
+            Some(Kind::StringValue(s)) if self.code() != TypeCode::Json => {
+                visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(s.as_str()))
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char map struct identifier
+    }
+}
+
+/// Iterates the values of an ARRAY, or a STRUCT read as a tuple.
+struct ListAccess<'a> {
+    values: std::slice::Iter<'a, Value>,
+    element_type: Option<&'a Type>,
+    fields: Option<&'a [Field]>,
+    index: usize,
+}
+
+impl<'a> ListAccess<'a> {
+    fn new(values: &'a [Value], element_type: Option<&'a Type>, fields: Option<&'a [Field]>) -> Self {
+        Self {
+            values: values.iter(),
+            element_type,
+            fields,
+            index: 0,
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for ListAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Error> {
+        let value = match self.values.next() {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let field = self.fields.and_then(|fields| fields.get(self.index));
+        self.index += 1;
+        match field {
+            Some(field) => {
+                let tp = field.r#type.as_ref();
+                seed.deserialize(ValueDeserializer { value, tp })
+                    .map(Some)
+                    .map_err(|e| with_field(e, &field.name, tp))
+            }
+            None => seed
+                .deserialize(ValueDeserializer {
+                    value,
+                    tp: self.element_type,
+                })
+                .map(Some),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+/// Iterates the columns of a row, or a STRUCT encoded as a list of values.
+struct FieldsAccess<'a> {
+    fields: &'a [Field],
+    values: &'a [Value],
+    index: usize,
+}
+
+impl<'a> FieldsAccess<'a> {
+    fn new(fields: &'a [Field], values: &'a [Value]) -> Self {
+        Self {
+            fields,
+            values,
+            index: 0,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for FieldsAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        if self.index >= self.fields.len().min(self.values.len()) {
+            return Ok(None);
+        }
+        let name = self.fields[self.index].name.as_str();
+        seed.deserialize(BorrowedStrDeserializer::new(name)).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let field = &self.fields[self.index];
+        let value = &self.values[self.index];
+        self.index += 1;
+        let tp = field.r#type.as_ref();
+        seed.deserialize(ValueDeserializer { value, tp })
+            .map_err(|e| with_field(e, &field.name, tp))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len().min(self.values.len()) - self.index)
+    }
+}
+
+/// Iterates a STRUCT encoded as a protobuf struct.
+struct StructValueAccess<'a> {
+    iter: btree_map::Iter<'a, String, Value>,
+    fields: Option<&'a [Field]>,
+    current: Option<(&'a str, &'a Value)>,
+}
+
+impl<'de> MapAccess<'de> for StructValueAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Error> {
+        match self.iter.next() {
+            Some((name, value)) => {
+                self.current = Some((name, value));
+                seed.deserialize(BorrowedStrDeserializer::new(name)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let (name, value) = self
+            .current
+            .take()
+            .ok_or_else(|| Error::DeserializeError("value is missing".to_string()))?;
+        let tp = self
+            .fields
+            .and_then(|fields| fields.iter().find(|f| f.name == name))
+            .and_then(|f| f.r#type.as_ref());
+        seed.deserialize(ValueDeserializer { value, tp })
+            .map_err(|e| with_field(e, name, tp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::str::FromStr;
+    use std::sync::Arc;
+
+    use prost_types::value::Kind;
+    use prost_types::{ListValue, Value};
+    use serde::Deserialize;
+    use time::OffsetDateTime;
+
+    use google_cloud_googleapis::spanner::v1::struct_type::Field;
+    use google_cloud_googleapis::spanner::v1::{Type, TypeCode};
+
+    use crate::bigdecimal::BigDecimal;
+    use crate::row::{Error, Row};
+    use crate::statement::{single_type, Kinds, ToKind, ToStruct, Types};
+
+    struct Item {
+        item_id: i64,
+        name: String,
+    }
+
+    impl ToStruct for Item {
+        fn to_kinds(&self) -> Kinds {
+            vec![("ItemId", self.item_id.to_kind()), ("Name", self.name.to_kind())]
+        }
+
+        fn get_types() -> Types {
+            vec![("ItemId", i64::get_type()), ("Name", String::get_type())]
+        }
+    }
+
+    fn create_row(columns: Vec<(&str, Type, Value)>) -> Row {
+        let mut index = HashMap::new();
+        let mut fields = vec![];
+        let mut values = vec![];
+        for (i, (name, tp, value)) in columns.into_iter().enumerate() {
+            index.insert(name.to_string(), i);
+            fields.push(Field {
+                name: name.to_string(),
+                r#type: Some(tp),
+            });
+            values.push(value);
+        }
+        Row::new(Arc::new(index), Arc::new(fields), values)
+    }
+
+    fn value<T: ToKind>(v: T) -> Value {
+        Value {
+            kind: Some(v.to_kind()),
+        }
+    }
+
+    fn list(values: Vec<Value>) -> Value {
+        Value {
+            kind: Some(Kind::ListValue(ListValue { values })),
+        }
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "PascalCase")]
+    struct DeItem {
+        item_id: i64,
+        name: String,
+    }
+
+    #[derive(Deserialize, Debug)]
+    #[serde(rename_all = "PascalCase")]
+    struct DeUser {
+        user_id: String,
+        level: i32,
+        rate: f64,
+        premium: bool,
+        #[serde(with = "serde_bytes_vec")]
+        icon: Vec<u8>,
+        raw_icon: Vec<u8>,
+        updated_at: String,
+        birthday: String,
+        price: BigDecimal,
+        attributes: serde_json::Value,
+        raw_attributes: String,
+        tags: Vec<String>,
+        items: Vec<DeItem>,
+        nullable_string: Option<String>,
+        nullable_array: Option<Vec<i64>>,
+        missing_column: Option<i64>,
+    }
+
+    mod serde_bytes_vec {
+        use serde::de::{Deserializer, Visitor};
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+            struct BytesVisitor;
+            impl<'de> Visitor<'de> for BytesVisitor {
+                type Value = Vec<u8>;
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("bytes")
+                }
+                fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                    Ok(v)
+                }
+            }
+            d.deserialize_byte_buf(BytesVisitor)
+        }
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let now = OffsetDateTime::now_utc();
+        let row = create_row(vec![
+            ("UserId", String::get_type(), value("user1")),
+            ("Level", i64::get_type(), value(10_i64)),
+            ("Rate", f64::get_type(), value(0.5)),
+            ("Premium", bool::get_type(), value(true)),
+            ("Icon", Vec::<u8>::get_type(), value(vec![1_u8, 2, 3])),
+            ("RawIcon", Vec::<u8>::get_type(), value(vec![4_u8, 5])),
+            ("UpdatedAt", OffsetDateTime::get_type(), value(now)),
+            ("Birthday", single_type(TypeCode::Date), value("2000-01-01")),
+            ("Price", BigDecimal::get_type(), value(BigDecimal::from_str("1.25").unwrap())),
+            ("Attributes", single_type(TypeCode::Json), value(r#"{"a":1}"#)),
+            ("RawAttributes", single_type(TypeCode::Json), value(r#"{"b":2}"#)),
+            ("Tags", Vec::<String>::get_type(), value(vec!["a", "b"])),
+            (
+                "Items",
+                Vec::<Item>::get_type(),
+                value(vec![Item {
+                    item_id: 1,
+                    name: "item1".to_string(),
+                }]),
+            ),
+            ("NullableString", String::get_type(), value(None::<String>)),
+            ("NullableArray", Vec::<i64>::get_type(), value(None::<Vec<i64>>)),
+            ("UnknownColumn", String::get_type(), value("ignored")),
+        ]);
+        let user = row.deserialize::<DeUser>().unwrap();
+        assert_eq!(user.user_id, "user1");
+        assert_eq!(user.level, 10);
+        assert_eq!(user.rate, 0.5);
+        assert!(user.premium);
+        assert_eq!(user.icon, vec![1, 2, 3]);
+        assert_eq!(user.raw_icon, vec![4, 5]);
+        assert_eq!(
+            OffsetDateTime::parse(&user.updated_at, &time::format_description::well_known::Rfc3339).unwrap(),
+            now
+        );
+        assert_eq!(user.birthday, "2000-01-01");
+        assert_eq!(user.price, BigDecimal::from_str("1.25").unwrap());
+        assert_eq!(user.attributes["a"], 1);
+        assert_eq!(user.raw_attributes, r#"{"b":2}"#);
+        assert_eq!(user.tags, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(
+            user.items,
+            vec![DeItem {
+                item_id: 1,
+                name: "item1".to_string()
+            }]
+        );
+        assert!(user.nullable_string.is_none());
+        assert!(user.nullable_array.is_none());
+        assert!(user.missing_column.is_none());
+    }
+
+    #[test]
+    fn test_deserialize_struct_list_value() {
+        // STRUCT values returned from the server are encoded as list values.
+        let tp = Vec::<Item>::get_type();
+        let row = create_row(vec![(
+            "Items",
+            tp,
+            list(vec![
+                list(vec![value(1_i64), value("item1")]),
+                list(vec![value(2_i64), value("item2")]),
+            ]),
+        )]);
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Items {
+            items: Vec<DeItem>,
+        }
+        let items = row.deserialize::<Items>().unwrap().items;
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].item_id, 2);
+        assert_eq!(items[1].name, "item2");
+
+        let (tuples,) = row.deserialize::<(Vec<(i64, String)>,)>().unwrap();
+        assert_eq!(tuples[0], (1, "item1".to_string()));
+    }
+
+    #[test]
+    fn test_deserialize_float_special_values() {
+        let row = create_row(vec![
+            ("Nan", f64::get_type(), value("NaN")),
+            ("Inf", f64::get_type(), value("Infinity")),
+            ("NegInf", f64::get_type(), value("-Infinity")),
+        ]);
+        let (nan, inf, neg_inf) = row.deserialize::<(f64, f64, f64)>().unwrap();
+        assert!(nan.is_nan());
+        assert_eq!(inf, f64::INFINITY);
+        assert_eq!(neg_inf, f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_deserialize_error() {
+        #[derive(Deserialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        #[allow(dead_code)]
+        struct Wrong {
+            user_id: i64,
+        }
+        let row = create_row(vec![("UserId", String::get_type(), value("user1"))]);
+        match row.deserialize::<Wrong>().unwrap_err() {
+            Error::DeserializeFieldError(field, tp, msg) => {
+                assert_eq!(field, "UserId");
+                assert_eq!(tp, "STRING");
+                assert!(msg.contains("expected i64"), "{msg}");
+            }
+            e => unreachable!("unexpected error {e:?}"),
+        }
+
+        #[derive(Deserialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        #[allow(dead_code)]
+        struct WrongItems {
+            items: Vec<WrongItem>,
+        }
+        #[derive(Deserialize, Debug)]
+        #[serde(rename_all = "PascalCase")]
+        #[allow(dead_code)]
+        struct WrongItem {
+            name: i64,
+        }
+        let row = create_row(vec![(
+            "Items",
+            Vec::<Item>::get_type(),
+            list(vec![list(vec![value(1_i64), value("item1")])]),
+        )]);
+        match row.deserialize::<WrongItems>().unwrap_err() {
+            Error::DeserializeFieldError(field, tp, _) => {
+                assert_eq!(field, "Items.Name");
+                assert_eq!(tp, "STRING");
+            }
+            e => unreachable!("unexpected error {e:?}"),
+        }
+
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct Required {
+            required: String,
+        }
+        let err = row.deserialize::<Required>().unwrap_err();
+        assert!(err.to_string().contains("missing field `required`"), "{err}");
+    }
+}
@@ -111,6 +111,23 @@
 //! }
 //! ```
 //!
+//! The emulator can also be specified explicitly without the environment variable.
+//! The client uses a single plaintext connection and skips the credentials,
+//! and the features the emulator doesn't support like the data boost are disabled.
+//!
+//! ```
+//! use google_cloud_spanner::client::Client;
+//! use google_cloud_spanner::client::ClientConfig;
+//! use google_cloud_spanner::client::Error;
+//!
+//! async fn run() -> Result<(), Error>{
+//!     const DATABASE: &str = "projects/local-project/instances/test-instance/databases/local-database";
+//!     let config = ClientConfig::default().with_emulator("localhost:9010");
+//!     let client = Client::new(DATABASE, config).await?;
+//!     Ok(())
+//! }
+//! ```
+//!
 //! ### <a name="Authentication"></a>Authentication
 //!
 //! There are two ways to create a client that is authenticated against the google cloud.
@@ -323,6 +340,20 @@
 //! let struct_data     = row.column_by_name::<Vec<User>>("struct_data")?;
 //! ```
 //!
+//! NULL is decoded as `None` for every supported type: use `Option<T>` for a nullable column,
+//! `Option<Vec<T>>` for a nullable ARRAY and `Vec<Option<T>>` for an ARRAY containing NULL elements.
+//! Decoding NULL into a non-`Option` type is an error.
+//!
+//! Use `try_column` or `try_column_by_name` when the column may not be in the projection,
+//! and `RowIterator::set_case_insensitive` to look up column names ignoring ASCII case.
+//!
+//! ```ignore
+//! let maybe_value     = row.try_column_by_name::<String>("optional_column")?;
+//! ```
+//!
+//! The error of decoding the column is `row::Error::ColumnDecodeError` with the column name, the index and the types.
+//! Use `row::Error::root_cause` to match on the underlying error like `KindMismatch`.
+//!
 //! Or you can define a Rust struct that corresponds to your columns, and extract into that:
 //! * `TryFromStruct` trait is required
 //!
@@ -348,6 +379,29 @@
 //! }
 //! ```
 //!
+//! With the `serde` feature (enabled by default), any type implementing `serde::Deserialize` can be extracted.
+//! See [`de`] for the type mapping.
+//!
+//! ```ignore
+//! #[derive(serde::Deserialize)]
+//! #[serde(rename_all = "PascalCase")]
+//! pub struct User {
+//!     pub user_id: String,
+//!     pub premium: Option<bool>,
+//! }
+//!
+//! let user = row.deserialize::<User>()?;
+//! ```
+//!
+//! When the schema is not known at compile time, use `try_to_map` or `values` to get
+//! the columns as [`value::Value`].
+//!
+//! ```ignore
+//! for (name, value) in row.try_to_map()? {
+//!     println!("{name} = {value}");
+//! }
+//! ```
+//!
 //! ### <a name="MultipleReads"></a>Multiple Reads
 //!
 //! To perform more than one read in a transaction, use ReadOnlyTransaction:
@@ -395,6 +449,23 @@
 //!
 //! * The used session is returned to the drop timing session pool, so unlike Go, there is no need to call txn Close.
 //!
+//! The queries of ReadOnlyTransaction take `&mut self`. To read the same snapshot from the concurrent tasks,
+//! use SharedReadOnlyTransaction. It is cloneable and its queries and reads take `&self`.
+//!
+//! ```ignore
+//! use futures_util::future::join_all;
+//! use google_cloud_spanner::client::{Client, Error};
+//! use google_cloud_spanner::key::Key;
+//!
+//! async fn run(client: Client, user_ids: Vec<String>) -> Result<(), Error> {
+//!     let tx = client.shared_read_only_transaction().await?;
+//!     let rows = join_all(user_ids.iter().map(|user_id| tx.read_row("User", &["UserId"], Key::new(user_id)))).await;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! * The session is returned to the session pool when all the clones and the RowIterators are dropped.
+//!
 //! ### <a name="TimestampsAndTimestampBounds"></a>Timestamps and Timestamp Bounds
 //!
 //! Cloud Spanner read-only transactions conceptually perform all their reads at a single moment in time, called the transaction's read timestamp. Once a read has started, you can call ReadOnlyTransaction's Timestamp method to obtain the read timestamp.
@@ -633,16 +704,33 @@
 //! ```
 pub mod admin;
 pub mod apiv1;
+pub mod change_stream;
 pub mod client;
+#[cfg(feature = "serde")]
+pub mod de;
 pub mod key;
+pub mod metrics;
 pub mod mutation;
 pub mod reader;
 pub mod retry;
 pub mod row;
 pub mod session;
+pub mod slow_operation;
+#[cfg(feature = "spanner-test")]
+pub mod spanner_test;
 pub mod statement;
 pub mod transaction;
 pub mod transaction_ro;
 pub mod transaction_rw;
 pub mod value;
 pub use bigdecimal;
+pub use indexmap;
+
+/// The types used by the code generated by google-cloud-spanner-derive. Not public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use google_cloud_googleapis::spanner::v1::struct_type::Field;
+    pub use google_cloud_googleapis::spanner::v1::Type;
+    pub use prost_types::value::Kind;
+    pub use prost_types::Value;
+}
@@ -0,0 +1,323 @@
+//! Client-side metrics of the data client.
+//!
+//! The metrics are recorded only with the `metrics` feature and a `Meter` set to `MetricsConfig`.
+//! The metric names and the labels are the same as the built-in metrics of the official Java and Go clients,
+//! so that the existing dashboards can be reused.
+//!
+//! | name | kind | unit |
+//! |------|------|------|
+//! | `operation_latencies` | histogram | ms |
+//! | `attempt_latencies` | histogram | ms |
+//! | `operation_count` | counter | |
+//! | `attempt_count` | counter | |
+//! | `gfe_latencies` | histogram | ms |
+//! | `gfe_connectivity_error_count` | counter | |
+//!
+//! The number of the retries of an operation is `attempt_count - operation_count`.
+//! `gfe_latencies` is read from the `server-timing: gfet4t7; dur=<ms>` response header.
+//! `gfe_connectivity_error_count` is incremented when the response doesn't have the header.
+//!
+//! ```ignore
+//! use google_cloud_spanner::client::{Client, ClientConfig};
+//! use google_cloud_spanner::metrics::MetricsConfig;
+//!
+//! async fn run(meter: opentelemetry::metrics::Meter) {
+//!     let config = ClientConfig {
+//!         metrics: MetricsConfig { meter: Some(meter) },
+//!         ..Default::default()
+//!     };
+//!     let client = Client::new("projects/local-project/instances/test-instance/databases/local-database", config).await.unwrap();
+//! }
+//! ```
+use std::fmt::{Debug, Formatter};
+#[cfg(feature = "metrics")]
+use std::sync::Arc;
+use std::time::Instant;
+
+use google_cloud_gax::grpc::{Response, Status};
+
+use crate::slow_operation::{OperationDetails, SlowOperationConfig, SlowOperationReporter, StreamOperation};
+
+pub const METRIC_PREFIX: &str = "spanner.googleapis.com/internal/client/";
+pub const OPERATION_LATENCIES: &str = "operation_latencies";
+pub const ATTEMPT_LATENCIES: &str = "attempt_latencies";
+pub const OPERATION_COUNT: &str = "operation_count";
+pub const ATTEMPT_COUNT: &str = "attempt_count";
+pub const GFE_LATENCIES: &str = "gfe_latencies";
+pub const GFE_CONNECTIVITY_ERROR_COUNT: &str = "gfe_connectivity_error_count";
+
+pub const LABEL_METHOD: &str = "method";
+pub const LABEL_STATUS: &str = "status";
+pub const LABEL_DATABASE: &str = "database";
+pub const LABEL_INSTANCE_ID: &str = "instance_id";
+pub const LABEL_CLIENT_NAME: &str = "client_name";
+pub const LABEL_DIRECT_PATH_ENABLED: &str = "directpath_enabled";
+pub const LABEL_DIRECT_PATH_USED: &str = "directpath_used";
+
+#[cfg(feature = "metrics")]
+const SERVER_TIMING_HEADER: &str = "server-timing";
+const GFE_TIMING_NAME: &str = "gfet4t7";
+
+#[derive(Clone, Default)]
+pub struct MetricsConfig {
+    /// The metrics are recorded with this meter. No metrics are recorded by default.
+    #[cfg(feature = "metrics")]
+    pub meter: Option<opentelemetry::metrics::Meter>,
+}
+
+impl Debug for MetricsConfig {
+    #[allow(unused_mut)]
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut s = f.debug_struct("MetricsConfig");
+        #[cfg(feature = "metrics")]
+        s.field("meter", &self.meter.is_some());
+        s.finish()
+    }
+}
+
+/// parse_gfe_latency returns the duration of `gfet4t7` in milliseconds from the `server-timing` header value
+/// like `gfet4t7; dur=123`.
+pub fn parse_gfe_latency(server_timing: &str) -> Option<f64> {
+    server_timing.split(',').find_map(|metric| {
+        let mut params = metric.split(';').map(str::trim);
+        if params.next()? != GFE_TIMING_NAME {
+            return None;
+        }
+        params.find_map(|p| p.strip_prefix("dur=").and_then(|v| v.trim().parse::<f64>().ok()))
+    })
+}
+
+/// status_label returns the status code name used by the official clients like `DEADLINE_EXCEEDED`.
+pub fn status_label<T>(result: &Result<T, Status>) -> &'static str {
+    use google_cloud_gax::grpc::Code;
+    match result {
+        Ok(_) => "OK",
+        Err(status) => match status.code() {
+            Code::Ok => "OK",
+            Code::Cancelled => "CANCELLED",
+            Code::Unknown => "UNKNOWN",
+            Code::InvalidArgument => "INVALID_ARGUMENT",
+            Code::DeadlineExceeded => "DEADLINE_EXCEEDED",
+            Code::NotFound => "NOT_FOUND",
+            Code::AlreadyExists => "ALREADY_EXISTS",
+            Code::PermissionDenied => "PERMISSION_DENIED",
+            Code::ResourceExhausted => "RESOURCE_EXHAUSTED",
+            Code::FailedPrecondition => "FAILED_PRECONDITION",
+            Code::Aborted => "ABORTED",
+            Code::OutOfRange => "OUT_OF_RANGE",
+            Code::Unimplemented => "UNIMPLEMENTED",
+            Code::Internal => "INTERNAL",
+            Code::Unavailable => "UNAVAILABLE",
+            Code::DataLoss => "DATA_LOSS",
+            Code::Unauthenticated => "UNAUTHENTICATED",
+        },
+    }
+}
+
+#[cfg(feature = "metrics")]
+struct Instruments {
+    operation_latencies: opentelemetry::metrics::Histogram<f64>,
+    attempt_latencies: opentelemetry::metrics::Histogram<f64>,
+    operation_count: opentelemetry::metrics::Counter<u64>,
+    attempt_count: opentelemetry::metrics::Counter<u64>,
+    gfe_latencies: opentelemetry::metrics::Histogram<f64>,
+    gfe_connectivity_error_count: opentelemetry::metrics::Counter<u64>,
+    common_labels: Vec<opentelemetry::KeyValue>,
+}
+
+#[cfg(feature = "metrics")]
+impl Instruments {
+    fn new(meter: &opentelemetry::metrics::Meter, database: &str) -> Self {
+        use opentelemetry::metrics::Unit;
+        use opentelemetry::KeyValue;
+
+        let name = |n: &str| format!("{METRIC_PREFIX}{n}");
+        let instance_id = database.split('/').nth(3).unwrap_or_default().to_string();
+        let database_id = database.rsplit('/').next().unwrap_or_default().to_string();
+        Self {
+            operation_latencies: meter
+                .f64_histogram(name(OPERATION_LATENCIES))
+                .with_unit(Unit::new("ms"))
+                .init(),
+            attempt_latencies: meter
+                .f64_histogram(name(ATTEMPT_LATENCIES))
+                .with_unit(Unit::new("ms"))
+                .init(),
+            operation_count: meter.u64_counter(name(OPERATION_COUNT)).init(),
+            attempt_count: meter.u64_counter(name(ATTEMPT_COUNT)).init(),
+            gfe_latencies: meter
+                .f64_histogram(name(GFE_LATENCIES))
+                .with_unit(Unit::new("ms"))
+                .init(),
+            gfe_connectivity_error_count: meter.u64_counter(name(GFE_CONNECTIVITY_ERROR_COUNT)).init(),
+            common_labels: vec![
+                KeyValue::new(LABEL_DATABASE, database_id),
+                KeyValue::new(LABEL_INSTANCE_ID, instance_id),
+                KeyValue::new(LABEL_CLIENT_NAME, concat!("spanner-rust/", env!("CARGO_PKG_VERSION"))),
+                KeyValue::new(LABEL_DIRECT_PATH_ENABLED, "false"),
+                KeyValue::new(LABEL_DIRECT_PATH_USED, "false"),
+            ],
+        }
+    }
+
+    fn labels(&self, method: &'static str, status: &'static str) -> Vec<opentelemetry::KeyValue> {
+        use opentelemetry::KeyValue;
+
+        let mut labels = self.common_labels.clone();
+        labels.push(KeyValue::new(LABEL_METHOD, format!("Spanner.{method}")));
+        labels.push(KeyValue::new(LABEL_STATUS, status));
+        labels
+    }
+}
+
+/// ClientMetrics records the metrics of the RPCs. It does nothing without a meter.
+/// The slow operations are also reported through it because they are measured at the same points.
+#[derive(Clone, Default)]
+pub(crate) struct ClientMetrics {
+    #[cfg(feature = "metrics")]
+    inner: Option<Arc<Instruments>>,
+    slow_operation: Option<SlowOperationReporter>,
+}
+
+impl ClientMetrics {
+    #[allow(unused_variables)]
+    pub(crate) fn new(config: &MetricsConfig, database: &str) -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            inner: config.meter.as_ref().map(|m| Arc::new(Instruments::new(m, database))),
+            slow_operation: None,
+        }
+    }
+
+    /// with_slow_operation reports the operations exceeding the threshold to the callback.
+    pub(crate) fn with_slow_operation(mut self, config: Option<SlowOperationConfig>) -> Self {
+        self.slow_operation = config.map(SlowOperationReporter::new);
+        self
+    }
+
+    /// start_stream starts measuring the streaming method for the slow operation hook.
+    /// The metrics of the streaming methods are recorded by `start_operation` when the stream is opened.
+    pub(crate) fn start_stream(
+        &self,
+        method: &'static str,
+        details: impl FnOnce() -> OperationDetails,
+    ) -> Option<StreamOperation> {
+        let reporter = self.slow_operation.clone()?;
+        Some(StreamOperation::new(reporter, method, details()))
+    }
+
+    /// start_operation starts the operation including all the retries of the method.
+    #[allow(unused_variables)]
+    pub(crate) fn start_operation(&self, method: &'static str) -> OperationMetrics {
+        OperationMetrics {
+            #[cfg(feature = "metrics")]
+            inner: self.inner.clone(),
+            slow_operation: self.slow_operation.clone(),
+            method,
+            started_at: Instant::now(),
+        }
+    }
+}
+
+pub(crate) struct OperationMetrics {
+    #[cfg(feature = "metrics")]
+    inner: Option<Arc<Instruments>>,
+    slow_operation: Option<SlowOperationReporter>,
+    method: &'static str,
+    started_at: Instant,
+}
+
+impl OperationMetrics {
+    pub(crate) fn start_attempt(&self) -> AttemptMetrics {
+        AttemptMetrics {
+            #[cfg(feature = "metrics")]
+            inner: self.inner.clone(),
+            #[cfg(feature = "metrics")]
+            method: self.method,
+            #[cfg(feature = "metrics")]
+            started_at: Instant::now(),
+        }
+    }
+
+    #[allow(unused_variables)]
+    pub(crate) fn finish<T>(self, result: &Result<T, Status>) {
+        #[cfg(feature = "metrics")]
+        if let Some(inner) = &self.inner {
+            let labels = inner.labels(self.method, status_label(result));
+            let elapsed = self.started_at.elapsed().as_secs_f64() * 1000.0;
+            inner.operation_latencies.record(elapsed, &labels);
+            inner.operation_count.add(1, &labels);
+        }
+    }
+
+    /// finish_with_details is the same as `finish` but also reports the operation if it is slow.
+    /// The details are built only when the operation is reported.
+    pub(crate) fn finish_with_details<T>(self, result: &Result<T, Status>, details: impl FnOnce() -> OperationDetails) {
+        if let Some(slow_operation) = &self.slow_operation {
+            slow_operation.report(self.method, self.started_at.elapsed(), result, details);
+        }
+        self.finish(result);
+    }
+}
+
+pub(crate) struct AttemptMetrics {
+    #[cfg(feature = "metrics")]
+    inner: Option<Arc<Instruments>>,
+    #[cfg(feature = "metrics")]
+    method: &'static str,
+    #[cfg(feature = "metrics")]
+    started_at: Instant,
+}
+
+impl AttemptMetrics {
+    #[allow(unused_variables)]
+    pub(crate) fn finish<T>(self, result: &Result<Response<T>, Status>) {
+        #[cfg(feature = "metrics")]
+        if let Some(inner) = &self.inner {
+            let labels = inner.labels(self.method, status_label(result));
+            let elapsed = self.started_at.elapsed().as_secs_f64() * 1000.0;
+            inner.attempt_latencies.record(elapsed, &labels);
+            inner.attempt_count.add(1, &labels);
+
+            let metadata = match result {
+                Ok(response) => response.metadata(),
+                Err(status) => status.metadata(),
+            };
+            let gfe_latency = metadata
+                .get(SERVER_TIMING_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_gfe_latency);
+            match gfe_latency {
+                Some(latency) => inner.gfe_latencies.record(latency, &labels),
+                None => inner.gfe_connectivity_error_count.add(1, &labels),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use google_cloud_gax::grpc::{Code, Status};
+
+    use crate::metrics::{parse_gfe_latency, status_label};
+
+    #[test]
+    fn test_parse_gfe_latency() {
+        assert_eq!(parse_gfe_latency("gfet4t7; dur=123"), Some(123.0));
+        assert_eq!(parse_gfe_latency("gfet4t7;dur=1.5"), Some(1.5));
+        assert_eq!(parse_gfe_latency("afe; dur=10, gfet4t7; dur=20"), Some(20.0));
+        assert_eq!(parse_gfe_latency("afe; dur=10"), None);
+        assert_eq!(parse_gfe_latency("gfet4t7"), None);
+        assert_eq!(parse_gfe_latency("gfet4t7; dur=abc"), None);
+    }
+
+    #[test]
+    fn test_status_label() {
+        assert_eq!(status_label::<()>(&Ok(())), "OK");
+        assert_eq!(status_label::<()>(&Err(Status::new(Code::Aborted, ""))), "ABORTED");
+        assert_eq!(
+            status_label::<()>(&Err(Status::new(Code::DeadlineExceeded, ""))),
+            "DEADLINE_EXCEEDED"
+        );
+    }
+}
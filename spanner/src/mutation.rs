@@ -1,10 +1,25 @@
+use indexmap::IndexMap;
+use prost::Message;
+use prost_types::value::Kind;
 use prost_types::{ListValue, Value};
 
+use google_cloud_googleapis::spanner::v1::key_range::{EndKeyType, StartKeyType};
 use google_cloud_googleapis::spanner::v1::mutation::{Delete, Operation, Write};
-use google_cloud_googleapis::spanner::v1::Mutation;
+use google_cloud_googleapis::spanner::v1::{KeyRange as InternalKeyRange, KeySet as InternalKeySet, Mutation};
 
-use crate::key::KeySet;
+use crate::key::{all_keys, KeyRange, KeySet};
 use crate::statement::{ToKind, ToStruct};
+use crate::value::Value as SpannerValue;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum Error {
+    #[error("column count {0} does not match value count {1}")]
+    ColumnCountMismatch(usize, usize),
+    #[error("duplicate column: {0}")]
+    DuplicateColumn(String),
+    #[error("mutation has no operation")]
+    NoOperation,
+}
 
 fn write(table: &str, columns: &[&str], values: &[&dyn ToKind]) -> Write {
     let values = values
@@ -52,6 +67,114 @@ fn write_struct(table: &str, to_struct: impl ToStruct) -> Write {
     }
 }
 
+fn write_values(table: &str, columns_and_values: IndexMap<&str, SpannerValue>) -> Write {
+    let mut columns = Vec::with_capacity(columns_and_values.len());
+    let mut values = Vec::with_capacity(columns_and_values.len());
+    columns_and_values.into_iter().for_each(|(column, value)| {
+        columns.push(column.to_string());
+        values.push(Value {
+            kind: Some(value.to_kind()),
+        })
+    });
+    Write {
+        table: table.to_string(),
+        columns,
+        values: vec![ListValue { values }],
+    }
+}
+
+/// ColumnValues pairs each column with its value, so the columns and the values can't get out of sync.
+/// ```
+/// use google_cloud_spanner::mutation::{ColumnValues, Error};
+///
+/// fn run() -> Result<(), Error> {
+///     let mutation = ColumnValues::new()
+///         .set("UserId", &"user1")?
+///         .set("Premium", &true)?
+///         .insert_or_update("User");
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ColumnValues {
+    columns: Vec<String>,
+    values: Vec<Value>,
+}
+
+impl ColumnValues {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// try_new pairs the columns with the values.
+    /// It fails if the lengths are different or the same column appears twice.
+    pub fn try_new(columns: &[&str], values: &[&dyn ToKind]) -> Result<Self, Error> {
+        if columns.len() != values.len() {
+            return Err(Error::ColumnCountMismatch(columns.len(), values.len()));
+        }
+        columns
+            .iter()
+            .zip(values.iter())
+            .try_fold(Self::new(), |acc, (column, value)| acc.set(column, *value))
+    }
+
+    /// set adds the column and its value. It fails if the column is already set.
+    pub fn set(mut self, column: &str, value: &dyn ToKind) -> Result<Self, Error> {
+        if self.columns.iter().any(|c| c == column) {
+            return Err(Error::DuplicateColumn(column.to_string()));
+        }
+        self.columns.push(column.to_string());
+        self.values.push(Value {
+            kind: Some(value.to_kind()),
+        });
+        Ok(self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    fn write(self, table: &str) -> Write {
+        Write {
+            table: table.to_string(),
+            columns: self.columns,
+            values: vec![ListValue { values: self.values }],
+        }
+    }
+
+    /// insert returns a Mutation to insert a row into a table. See `insert`.
+    pub fn insert(self, table: &str) -> Mutation {
+        Mutation {
+            operation: Some(Operation::Insert(self.write(table))),
+        }
+    }
+
+    /// update returns a Mutation to update a row in a table. See `update`.
+    pub fn update(self, table: &str) -> Mutation {
+        Mutation {
+            operation: Some(Operation::Update(self.write(table))),
+        }
+    }
+
+    /// replace returns a Mutation to insert a row into a table, deleting any existing row. See `replace`.
+    pub fn replace(self, table: &str) -> Mutation {
+        Mutation {
+            operation: Some(Operation::Replace(self.write(table))),
+        }
+    }
+
+    /// insert_or_update returns a Mutation to insert or update a row in a table. See `insert_or_update`.
+    pub fn insert_or_update(self, table: &str) -> Mutation {
+        Mutation {
+            operation: Some(Operation::InsertOrUpdate(self.write(table))),
+        }
+    }
+}
+
 /// Insert returns a Mutation to insert a row into a table. If the row already
 /// exists, the write or transaction fails with codes.AlreadyExists.
 pub fn insert(table: &str, columns: &[&str], values: &[&dyn ToKind]) -> Mutation {
@@ -156,6 +279,48 @@ pub fn insert_or_update_map(table: &str, columns_ans_values: &[(&str, &dyn ToKin
     }
 }
 
+/// insert_values returns a Mutation to insert a row into a table, specified by
+/// a map of column name to dynamically typed value.
+pub fn insert_values(table: &str, columns_and_values: IndexMap<&str, SpannerValue>) -> Mutation {
+    Mutation {
+        operation: Some(Operation::Insert(write_values(table, columns_and_values))),
+    }
+}
+
+/// update_values returns a Mutation to update a row in a table, specified by
+/// a map of column name to dynamically typed value.
+pub fn update_values(table: &str, columns_and_values: IndexMap<&str, SpannerValue>) -> Mutation {
+    Mutation {
+        operation: Some(Operation::Update(write_values(table, columns_and_values))),
+    }
+}
+
+/// replace_values returns a Mutation to insert a row into a table, deleting any existing row,
+/// specified by a map of column name to dynamically typed value.
+pub fn replace_values(table: &str, columns_and_values: IndexMap<&str, SpannerValue>) -> Mutation {
+    Mutation {
+        operation: Some(Operation::Replace(write_values(table, columns_and_values))),
+    }
+}
+
+/// insert_or_update_values returns a Mutation to insert a row into a table, specified by
+/// a map of column name to dynamically typed value. If the row already exists, it updates it instead.
+/// ```
+/// use google_cloud_spanner::mutation::insert_or_update_values;
+/// use google_cloud_spanner::value::Value;
+/// use google_cloud_spanner::indexmap::IndexMap;
+///
+/// let mut row = IndexMap::new();
+/// row.insert("UserId", Value::String("user1".to_string()));
+/// row.insert("Premium", Value::Bool(true));
+/// let mutation = insert_or_update_values("User", row);
+/// ```
+pub fn insert_or_update_values(table: &str, columns_and_values: IndexMap<&str, SpannerValue>) -> Mutation {
+    Mutation {
+        operation: Some(Operation::InsertOrUpdate(write_values(table, columns_and_values))),
+    }
+}
+
 /// insert_or_update_struct returns a Mutation to insert a row into a table,
 /// specified by a Go struct. If the row already exists, it updates it instead.
 /// Any column values not explicitly written are preserved.
@@ -177,8 +342,320 @@ pub fn delete(table: &str, key_set: impl Into<KeySet>) -> Mutation {
     }
 }
 
+/// delete_key_range removes the rows in the KeyRange from the table.
+pub fn delete_key_range(table: &str, key_range: KeyRange) -> Mutation {
+    delete(table, key_range)
+}
+
+/// delete_all removes all the rows from the table.
+pub fn delete_all(table: &str) -> Mutation {
+    delete(table, all_keys())
+}
+
+/// The maximum number of the mutations per commit.
+pub const MAX_MUTATIONS_PER_COMMIT: usize = 80_000;
+/// The maximum size of a commit request in bytes.
+pub const MAX_COMMIT_BYTES: usize = 100 * 1024 * 1024;
+
+/// MutationExt estimates the size of a mutation counted for the commit limits.
+/// See <https://cloud.google.com/spanner/quotas#limits-for> for the counting rules.
+pub trait MutationExt {
+    /// mutation_count returns the number of the mutations counted for the commit limit.
+    /// * insert, update, insert_or_update and replace count the number of the columns per row.
+    /// * delete counts 1 per key and per key range regardless of the columns.
+    /// * delete_all counts 1.
+    ///
+    /// The mutations of the secondary indexes are not included.
+    fn mutation_count(&self) -> usize;
+
+    /// encoded_len_estimate returns the encoded size of the mutation in bytes.
+    fn encoded_len_estimate(&self) -> usize;
+}
+
+impl MutationExt for Mutation {
+    fn mutation_count(&self) -> usize {
+        match &self.operation {
+            Some(Operation::Insert(w))
+            | Some(Operation::Update(w))
+            | Some(Operation::InsertOrUpdate(w))
+            | Some(Operation::Replace(w)) => w.columns.len() * w.values.len(),
+            Some(Operation::Delete(d)) => match &d.key_set {
+                Some(k) if k.all => 1,
+                Some(k) => k.keys.len() + k.ranges.len(),
+                None => 0,
+            },
+            None => 0,
+        }
+    }
+
+    fn encoded_len_estimate(&self) -> usize {
+        self.encoded_len()
+    }
+}
+
+/// CommitSizeEstimate is the total size of the mutations in a commit.
+/// ```
+/// use google_cloud_spanner::mutation::{insert, delete_all, CommitSizeEstimate};
+///
+/// let ms = vec![insert("Guild", &["GuildId", "OwnerUserId"], &[&"1", &"2"]), delete_all("User")];
+/// let estimate = CommitSizeEstimate::new(&ms);
+/// assert_eq!(estimate.mutation_count, 3);
+/// assert!(estimate.is_within_limits());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CommitSizeEstimate {
+    pub mutation_count: usize,
+    pub encoded_len: usize,
+}
+
+impl CommitSizeEstimate {
+    pub fn new(mutations: &[Mutation]) -> Self {
+        let mut estimate = Self::default();
+        for mutation in mutations {
+            estimate.add(mutation);
+        }
+        estimate
+    }
+
+    pub fn add(&mut self, mutation: &Mutation) {
+        self.mutation_count += mutation.mutation_count();
+        self.encoded_len += mutation.encoded_len_estimate();
+    }
+
+    /// is_within returns true if the mutations fit in max_count mutations and max_bytes bytes.
+    pub fn is_within(&self, max_count: usize, max_bytes: usize) -> bool {
+        self.mutation_count <= max_count && self.encoded_len <= max_bytes
+    }
+
+    /// is_within_limits returns true if the mutations can be committed in a transaction.
+    pub fn is_within_limits(&self) -> bool {
+        self.is_within(MAX_MUTATIONS_PER_COMMIT, MAX_COMMIT_BYTES)
+    }
+}
+
+/// chunk_mutations splits the mutations into the chunks having at most max_count mutations and max_bytes bytes.
+/// The order of the mutations is preserved. A mutation exceeding the limits by itself is put in its own chunk.
+pub fn chunk_mutations(mutations: Vec<Mutation>, max_count: usize, max_bytes: usize) -> Vec<Vec<Mutation>> {
+    let mut chunks = vec![];
+    let mut chunk = vec![];
+    let mut estimate = CommitSizeEstimate::default();
+    for mutation in mutations {
+        let mut next = estimate;
+        next.add(&mutation);
+        if !chunk.is_empty() && !next.is_within(max_count, max_bytes) {
+            chunks.push(std::mem::take(&mut chunk));
+            next = CommitSizeEstimate::default();
+            next.add(&mutation);
+        }
+        estimate = next;
+        chunk.push(mutation);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// MutationOperation is the kind of the mutation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "snake_case"))]
+pub enum MutationOperation {
+    Insert,
+    Update,
+    InsertOrUpdate,
+    Replace,
+    Delete,
+}
+
+/// MutationRecord is the readable form of the Mutation to assert the mutations in the tests
+/// and to write the audit logs. It is converted from and into the Mutation without loss.
+///
+/// The values are decoded without the column types, so INT64, NUMERIC, TIMESTAMP, DATE and BYTES
+/// are `Value::String` in the wire format, like `Value::String("1")` for `1_i64`.
+/// ```
+/// use google_cloud_spanner::mutation::{insert, MutationOperation, MutationRecord};
+/// use google_cloud_spanner::value::Value;
+///
+/// let mutation = insert("User", &["UserId", "Premium"], &[&"user1", &true]);
+/// let record = MutationRecord::try_from(&mutation).unwrap();
+/// assert_eq!(record.operation, MutationOperation::Insert);
+/// assert_eq!(record.values, vec![vec![Value::String("user1".to_string()), Value::Bool(true)]]);
+/// assert_eq!(mutation, record.into());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MutationRecord {
+    pub table: String,
+    pub operation: MutationOperation,
+    /// The written columns. It is empty for delete.
+    pub columns: Vec<String>,
+    /// The written rows in the order of the columns. It is empty for delete.
+    pub values: Vec<Vec<SpannerValue>>,
+    /// The deleted keys. It is None except for delete.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub key_set: Option<KeySetRecord>,
+}
+
+/// KeySetRecord is the readable form of the KeySet of the delete mutation.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KeySetRecord {
+    pub keys: Vec<Vec<SpannerValue>>,
+    pub ranges: Vec<KeyRangeRecord>,
+    pub all: bool,
+}
+
+/// KeyRangeRecord is the readable form of the KeyRange. An empty key is the unbounded start or end.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KeyRangeRecord {
+    pub start: Vec<SpannerValue>,
+    pub start_closed: bool,
+    pub end: Vec<SpannerValue>,
+    pub end_closed: bool,
+}
+
+fn to_record_value(value: &Value) -> SpannerValue {
+    match &value.kind {
+        None | Some(Kind::NullValue(_)) => SpannerValue::Null,
+        Some(Kind::BoolValue(v)) => SpannerValue::Bool(*v),
+        Some(Kind::NumberValue(v)) => SpannerValue::Float64(*v),
+        Some(Kind::StringValue(v)) => SpannerValue::String(v.to_string()),
+        Some(Kind::ListValue(v)) => SpannerValue::Array(v.values.iter().map(to_record_value).collect()),
+        Some(Kind::StructValue(v)) => SpannerValue::Struct(
+            v.fields
+                .iter()
+                .map(|(name, v)| (name.to_string(), to_record_value(v)))
+                .collect(),
+        ),
+    }
+}
+
+fn to_record_values(values: &ListValue) -> Vec<SpannerValue> {
+    values.values.iter().map(to_record_value).collect()
+}
+
+fn to_list_value(values: Vec<SpannerValue>) -> ListValue {
+    ListValue {
+        values: values
+            .into_iter()
+            .map(|v| Value {
+                kind: Some(v.to_kind()),
+            })
+            .collect(),
+    }
+}
+
+impl From<&InternalKeySet> for KeySetRecord {
+    fn from(key_set: &InternalKeySet) -> Self {
+        Self {
+            keys: key_set.keys.iter().map(to_record_values).collect(),
+            ranges: key_set
+                .ranges
+                .iter()
+                .map(|range| {
+                    let (start, start_closed) = match &range.start_key_type {
+                        Some(StartKeyType::StartClosed(v)) => (to_record_values(v), true),
+                        Some(StartKeyType::StartOpen(v)) => (to_record_values(v), false),
+                        None => (vec![], false),
+                    };
+                    let (end, end_closed) = match &range.end_key_type {
+                        Some(EndKeyType::EndClosed(v)) => (to_record_values(v), true),
+                        Some(EndKeyType::EndOpen(v)) => (to_record_values(v), false),
+                        None => (vec![], false),
+                    };
+                    KeyRangeRecord {
+                        start,
+                        start_closed,
+                        end,
+                        end_closed,
+                    }
+                })
+                .collect(),
+            all: key_set.all,
+        }
+    }
+}
+
+impl From<KeySetRecord> for InternalKeySet {
+    fn from(record: KeySetRecord) -> Self {
+        Self {
+            keys: record.keys.into_iter().map(to_list_value).collect(),
+            ranges: record
+                .ranges
+                .into_iter()
+                .map(|range| InternalKeyRange {
+                    start_key_type: Some(match range.start_closed {
+                        true => StartKeyType::StartClosed(to_list_value(range.start)),
+                        false => StartKeyType::StartOpen(to_list_value(range.start)),
+                    }),
+                    end_key_type: Some(match range.end_closed {
+                        true => EndKeyType::EndClosed(to_list_value(range.end)),
+                        false => EndKeyType::EndOpen(to_list_value(range.end)),
+                    }),
+                })
+                .collect(),
+            all: record.all,
+        }
+    }
+}
+
+impl TryFrom<&Mutation> for MutationRecord {
+    type Error = Error;
+
+    fn try_from(mutation: &Mutation) -> Result<Self, Self::Error> {
+        let (operation, write) = match &mutation.operation {
+            Some(Operation::Insert(w)) => (MutationOperation::Insert, w),
+            Some(Operation::Update(w)) => (MutationOperation::Update, w),
+            Some(Operation::InsertOrUpdate(w)) => (MutationOperation::InsertOrUpdate, w),
+            Some(Operation::Replace(w)) => (MutationOperation::Replace, w),
+            Some(Operation::Delete(d)) => {
+                return Ok(Self {
+                    table: d.table.to_string(),
+                    operation: MutationOperation::Delete,
+                    columns: vec![],
+                    values: vec![],
+                    key_set: Some(d.key_set.as_ref().map(KeySetRecord::from).unwrap_or_default()),
+                })
+            }
+            None => return Err(Error::NoOperation),
+        };
+        Ok(Self {
+            table: write.table.to_string(),
+            operation,
+            columns: write.columns.clone(),
+            values: write.values.iter().map(to_record_values).collect(),
+            key_set: None,
+        })
+    }
+}
+
+impl From<MutationRecord> for Mutation {
+    fn from(record: MutationRecord) -> Self {
+        let write = Write {
+            table: record.table,
+            columns: record.columns,
+            values: record.values.into_iter().map(to_list_value).collect(),
+        };
+        let operation = match record.operation {
+            MutationOperation::Insert => Operation::Insert(write),
+            MutationOperation::Update => Operation::Update(write),
+            MutationOperation::InsertOrUpdate => Operation::InsertOrUpdate(write),
+            MutationOperation::Replace => Operation::Replace(write),
+            MutationOperation::Delete => Operation::Delete(Delete {
+                table: write.table,
+                key_set: Some(record.key_set.unwrap_or_default().into()),
+            }),
+        };
+        Mutation {
+            operation: Some(operation),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use indexmap::IndexMap;
     use prost_types::value::Kind;
 
     use google_cloud_googleapis::spanner::*;
@@ -186,7 +663,7 @@ mod tests {
     use crate::key::*;
     use crate::mutation::*;
     use crate::statement::{Kinds, ToKind, Types};
-    use crate::value::CommitTimestamp;
+    use crate::value::{CommitTimestamp, Value as SpannerValue};
 
     struct TestStruct {
         pub struct_field: String,
@@ -387,6 +864,81 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_column_values() {
+        let now = CommitTimestamp::new();
+        let expected = insert_or_update("Guild", &["GuildId", "UserId", "UpdatedAt"], &[&"1", &2_i64, &now]);
+        let mutation = ColumnValues::new()
+            .set("GuildId", &"1")
+            .unwrap()
+            .set("UserId", &2_i64)
+            .unwrap()
+            .set("UpdatedAt", &now)
+            .unwrap()
+            .insert_or_update("Guild");
+        assert_eq!(expected, mutation);
+
+        let values = ColumnValues::try_new(&["GuildId", "UserId", "UpdatedAt"], &[&"1", &2_i64, &now]).unwrap();
+        assert_eq!(values.len(), 3);
+        assert_eq!(expected, values.insert_or_update("Guild"));
+    }
+
+    #[test]
+    fn test_column_values_error() {
+        let err = ColumnValues::try_new(&["GuildId", "UserId"], &[&"1"]).unwrap_err();
+        assert_eq!(err, Error::ColumnCountMismatch(2, 1));
+
+        let err = ColumnValues::try_new(&["GuildId", "GuildId"], &[&"1", &"2"]).unwrap_err();
+        assert_eq!(err, Error::DuplicateColumn("GuildId".to_string()));
+
+        let err = ColumnValues::new()
+            .set("A", &1_i64)
+            .unwrap()
+            .set("A", &2_i64)
+            .unwrap_err();
+        assert_eq!(err, Error::DuplicateColumn("A".to_string()));
+    }
+
+    #[test]
+    fn test_insert_or_update_values() {
+        let mut values = IndexMap::new();
+        values.insert("GuildId", SpannerValue::String("1".to_string()));
+        values.insert("UserId", SpannerValue::Int64(2));
+        values.insert("Tags", SpannerValue::Array(vec![SpannerValue::String("a".to_string())]));
+        values.insert("Deleted", SpannerValue::Null);
+        let expected = insert_or_update(
+            "Guild",
+            &["GuildId", "UserId", "Tags", "Deleted"],
+            &[&"1", &2_i64, &vec!["a"], &None::<bool>],
+        );
+        assert_eq!(expected, insert_or_update_values("Guild", values.clone()));
+        assert_eq!(
+            insert(
+                "Guild",
+                &["GuildId", "UserId", "Tags", "Deleted"],
+                &[&"1", &2_i64, &vec!["a"], &None::<bool>]
+            ),
+            insert_values("Guild", values)
+        );
+    }
+
+    #[test]
+    fn test_delete_helpers() {
+        assert_eq!(delete("Guild", all_keys()), delete_all("Guild"));
+
+        let range = || KeyRange::new(Key::new(&"1"), Key::new(&"9"), RangeKind::ClosedOpen);
+        let mutation = delete_key_range("Guild", range());
+        assert_eq!(delete("Guild", range()), mutation);
+        match mutation.operation.unwrap() {
+            v1::mutation::Operation::Delete(w) => {
+                let key_set = w.key_set.unwrap();
+                assert!(!key_set.all);
+                assert_eq!(key_set.ranges.len(), 1);
+            }
+            _ => panic!("invalid operation"),
+        }
+    }
+
     fn assert_struct(mut w: Write) {
         assert_eq!("Guild", w.table);
         assert_eq!("StructField", w.columns.pop().unwrap());
@@ -398,4 +950,136 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_mutation_count() {
+        // columns x rows
+        let m = insert("Guild", &["GuildId", "UserId", "UpdatedAt"], &[&1_i64, &2_i64, &3_i64]);
+        assert_eq!(m.mutation_count(), 3);
+        let m = update_map("Guild", &[("GuildId", &1_i64), ("UserId", &2_i64)]);
+        assert_eq!(m.mutation_count(), 2);
+        let mut m = insert_or_update("Guild", &["GuildId", "UserId"], &[&1_i64, &2_i64]);
+        if let Some(v1::mutation::Operation::InsertOrUpdate(w)) = m.operation.as_mut() {
+            let row = w.values[0].clone();
+            w.values.push(row);
+        }
+        assert_eq!(m.mutation_count(), 4);
+
+        // deletes count 1 per key or key range regardless of the columns
+        assert_eq!(delete("Guild", Key::new(&"1")).mutation_count(), 1);
+        assert_eq!(delete("Guild", vec![Key::new(&"1"), Key::new(&"2")]).mutation_count(), 2);
+        let range = KeyRange::new(Key::new(&"1"), Key::new(&"9"), RangeKind::ClosedOpen);
+        assert_eq!(delete_key_range("Guild", range).mutation_count(), 1);
+        assert_eq!(delete_all("Guild").mutation_count(), 1);
+    }
+
+    #[test]
+    fn test_commit_size_estimate() {
+        let ms = vec![
+            insert("Guild", &["GuildId", "UserId"], &[&1_i64, &2_i64]),
+            delete("Guild", vec![Key::new(&"1"), Key::new(&"2")]),
+        ];
+        let estimate = CommitSizeEstimate::new(&ms);
+        assert_eq!(estimate.mutation_count, 4);
+        assert_eq!(
+            estimate.encoded_len,
+            ms[0].encoded_len_estimate() + ms[1].encoded_len_estimate()
+        );
+        assert!(estimate.is_within_limits());
+        assert!(estimate.is_within(4, estimate.encoded_len));
+        assert!(!estimate.is_within(3, estimate.encoded_len));
+        assert!(!estimate.is_within(4, estimate.encoded_len - 1));
+    }
+
+    #[test]
+    fn test_chunk_mutations() {
+        let mutations: Vec<Mutation> = (0..5_i64)
+            .map(|i| insert("Guild", &["GuildId", "UserId"], &[&i, &i]))
+            .collect();
+
+        let chunks = chunk_mutations(mutations.clone(), 4, usize::MAX);
+        assert_eq!(chunks.iter().map(|c| c.len()).collect::<Vec<_>>(), vec![2, 2, 1]);
+        assert_eq!(chunks.concat(), mutations);
+
+        // a mutation exceeding the limit is put in its own chunk
+        let chunks = chunk_mutations(mutations.clone(), 1, usize::MAX);
+        assert_eq!(chunks.len(), 5);
+
+        let size = mutations[0].encoded_len_estimate();
+        let chunks = chunk_mutations(mutations, usize::MAX, size * 3);
+        assert_eq!(chunks.iter().map(|c| c.len()).collect::<Vec<_>>(), vec![3, 2]);
+
+        assert!(chunk_mutations(vec![], 1, 1).is_empty());
+    }
+
+    #[test]
+    fn test_mutation_record() {
+        let ms = vec![
+            insert("Guild", &["GuildId", "UserId", "Score"], &[&"1", &2_i64, &1.5_f64]),
+            insert_or_update("Guild", &["GuildId", "Tags"], &[&"1", &vec!["a".to_string()]]),
+            delete("Guild", vec![Key::new(&"1"), Key::composite(&[&"2", &3_i64])]),
+            delete("Guild", KeyRange::new(Key::new(&"1"), Key::new(&"5"), RangeKind::ClosedOpen)),
+            delete_all("Guild"),
+        ];
+        let records: Vec<MutationRecord> = ms.iter().map(|m| MutationRecord::try_from(m).unwrap()).collect();
+
+        assert_eq!(records[0].table, "Guild");
+        assert_eq!(records[0].operation, MutationOperation::Insert);
+        assert_eq!(records[0].columns, vec!["GuildId", "UserId", "Score"]);
+        assert_eq!(
+            records[0].values,
+            vec![vec![
+                SpannerValue::String("1".to_string()),
+                SpannerValue::String("2".to_string()),
+                SpannerValue::Float64(1.5)
+            ]]
+        );
+        assert_eq!(records[1].operation, MutationOperation::InsertOrUpdate);
+        assert_eq!(
+            records[1].values[0][1],
+            SpannerValue::Array(vec![SpannerValue::String("a".to_string())])
+        );
+
+        let key_set = records[2].key_set.as_ref().unwrap();
+        assert_eq!(records[2].operation, MutationOperation::Delete);
+        assert!(records[2].columns.is_empty());
+        assert_eq!(key_set.keys.len(), 2);
+        assert_eq!(key_set.keys[1].len(), 2);
+        let range = &records[3].key_set.as_ref().unwrap().ranges[0];
+        assert!(range.start_closed);
+        assert!(!range.end_closed);
+        assert!(records[4].key_set.as_ref().unwrap().all);
+
+        // round trip
+        let converted: Vec<Mutation> = records.into_iter().map(Mutation::from).collect();
+        assert_eq!(converted, ms);
+
+        assert_eq!(MutationRecord::try_from(&Mutation { operation: None }), Err(Error::NoOperation));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_mutation_record_serialize() {
+        let record = MutationRecord::try_from(&insert("Guild", &["GuildId", "Active"], &[&"1", &true])).unwrap();
+        assert_eq!(
+            serde_json::to_value(&record).unwrap(),
+            serde_json::json!({
+                "table": "Guild",
+                "operation": "insert",
+                "columns": ["GuildId", "Active"],
+                "values": [["1", true]],
+            })
+        );
+        let record = MutationRecord::try_from(&delete_all("Guild")).unwrap();
+        assert_eq!(
+            serde_json::to_value(&record).unwrap(),
+            serde_json::json!({
+                "table": "Guild",
+                "operation": "delete",
+                "columns": [],
+                "values": [],
+                "key_set": {"keys": [], "ranges": [], "all": true},
+            })
+        );
+    }
 }
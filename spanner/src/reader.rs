@@ -1,15 +1,26 @@
 use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::SystemTime;
 
+use futures_util::{Stream, StreamExt};
 use prost_types::{value::Kind, Value};
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 
 use google_cloud_gax::grpc::{Code, Response, Status, Streaming};
+use google_cloud_gax::retry::{Reconnect, ResumableStream, RetrySetting};
 use google_cloud_googleapis::spanner::v1::struct_type::Field;
-use google_cloud_googleapis::spanner::v1::{ExecuteSqlRequest, PartialResultSet, ReadRequest, ResultSetMetadata};
+use google_cloud_googleapis::spanner::v1::{
+    ExecuteSqlRequest, PartialResultSet, ReadRequest, ResultSetMetadata, ResultSetStats,
+};
 
 use crate::row::Row;
-use crate::session::SessionHandle;
-use crate::transaction::CallOptions;
+use crate::session::{DetachedSession, SessionHandle};
+use crate::slow_operation::StreamOperation;
+use crate::transaction::{CallOptions, RowCount};
 
 pub trait Reader: Send + Sync {
     fn read(
@@ -20,12 +31,21 @@ pub trait Reader: Send + Sync {
 
     fn update_token(&mut self, resume_token: Vec<u8>);
 
+    /// can_resume returns true if the broken stream can be re-established by the resume token.
     fn can_resume(&self) -> bool;
+
+    /// start_operation starts measuring the stream for the slow operation hook until it is completed.
+    /// None doesn't measure the stream.
+    fn start_operation(&self, _session: &SessionHandle) -> Option<StreamOperation> {
+        None
+    }
 }
 
 pub struct StatementReader {
     pub enable_resume: bool,
     pub request: ExecuteSqlRequest,
+    /// Send the `x-goog-spanner-route-to-leader` header. Set for the queries of the read-write transactions.
+    pub route_to_leader: bool,
 }
 
 impl Reader for StatementReader {
@@ -36,7 +56,14 @@ impl Reader for StatementReader {
     ) -> Result<Response<Streaming<PartialResultSet>>, Status> {
         let option = option.unwrap_or_default();
         let client = &mut session.spanner_client;
-        let result = client.execute_streaming_sql(self.request.clone(), option.retry).await;
+        let result = client
+            .execute_streaming_sql_with_routing(
+                self.request.clone(),
+                option.retry,
+                self.route_to_leader,
+                &option.extra_metadata,
+            )
+            .await;
         session.invalidate_if_needed(result).await
     }
 
@@ -45,12 +72,18 @@ impl Reader for StatementReader {
     }
 
     fn can_resume(&self) -> bool {
-        self.enable_resume && !self.request.resume_token.is_empty()
+        self.enable_resume
+    }
+
+    fn start_operation(&self, session: &SessionHandle) -> Option<StreamOperation> {
+        session.spanner_client.start_streaming_sql(&self.request)
     }
 }
 
 pub struct TableReader {
     pub request: ReadRequest,
+    /// Send the `x-goog-spanner-route-to-leader` header. Set for the reads of the read-write transactions.
+    pub route_to_leader: bool,
 }
 
 impl Reader for TableReader {
@@ -61,7 +94,14 @@ impl Reader for TableReader {
     ) -> Result<Response<Streaming<PartialResultSet>>, Status> {
         let option = option.unwrap_or_default();
         let client = &mut session.spanner_client;
-        let result = client.streaming_read(self.request.clone(), option.retry).await;
+        let result = client
+            .streaming_read_with_routing(
+                self.request.clone(),
+                option.retry,
+                self.route_to_leader,
+                &option.extra_metadata,
+            )
+            .await;
         session.invalidate_if_needed(result).await
     }
 
@@ -70,7 +110,11 @@ impl Reader for TableReader {
     }
 
     fn can_resume(&self) -> bool {
-        !self.request.resume_token.is_empty()
+        true
+    }
+
+    fn start_operation(&self, session: &SessionHandle) -> Option<StreamOperation> {
+        session.spanner_client.start_streaming_read(&self.request)
     }
 }
 
@@ -79,6 +123,7 @@ pub struct ResultSet {
     index: Arc<HashMap<String, usize>>,
     rows: VecDeque<Value>,
     chunked_value: bool,
+    case_insensitive: bool,
 }
 
 impl ResultSet {
@@ -94,59 +139,82 @@ impl ResultSet {
                 for _ in 0..column_length {
                     values.push(self.rows.pop_front().unwrap());
                 }
-                return Some(Row::new(Arc::clone(&self.index), Arc::clone(&self.fields), values));
+                let mut row = Row::new(Arc::clone(&self.index), Arc::clone(&self.fields), values);
+                row.set_case_insensitive(self.case_insensitive);
+                return Some(row);
             }
         }
         None
     }
 
-    /// Merge tries to combine two protobuf Values if possible.
+    /// merge combines the chunked value with the first value of the next PartialResultSet
+    /// following the rules described in `PartialResultSet.chunked_value`:
+    /// * strings are concatenated.
+    /// * lists are concatenated, but the last element of the previous list and the first element
+    ///   of the current list are merged recursively when both of them are strings or lists.
+    /// * other kinds (bool, number and null) can't be chunked.
     fn merge(previous_last: Value, current_first: Value) -> Result<Value, Status> {
-        match previous_last.kind.unwrap() {
-            Kind::StringValue(last) => match current_first.kind.unwrap() {
-                Kind::StringValue(first) => {
-                    tracing::trace!("previous_last={}, current_first={}", &last, first);
-                    Ok(Value {
-                        kind: Some(Kind::StringValue(last + &first)),
-                    })
-                }
-                _ => Err(Status::new(
-                    Code::Internal,
-                    "chunks kind mismatch: current_first must be StringKind",
-                )),
-            },
-            Kind::ListValue(mut last) => match current_first.kind.unwrap() {
-                Kind::ListValue(mut first) => {
-                    let first_value_of_current = first.values.remove(0);
-                    let merged = match last.values.pop() {
+        let kind = match (previous_last.kind, current_first.kind) {
+            (Some(Kind::StringValue(mut last)), Some(Kind::StringValue(first))) => {
+                tracing::trace!("previous_last={}, current_first={}", &last, first);
+                last.push_str(&first);
+                Kind::StringValue(last)
+            }
+            (Some(Kind::ListValue(mut last)), Some(Kind::ListValue(first))) => {
+                let mut first = first.values.into_iter();
+                if let Some(first_value_of_current) = first.next() {
+                    match last.values.pop() {
+                        Some(last_value_of_previous)
+                            if ResultSet::can_merge(&last_value_of_previous, &first_value_of_current) =>
+                        {
+                            let merged = ResultSet::merge(last_value_of_previous, first_value_of_current)?;
+                            last.values.push(merged);
+                        }
                         Some(last_value_of_previous) => {
-                            ResultSet::merge(last_value_of_previous, first_value_of_current)?
+                            last.values.push(last_value_of_previous);
+                            last.values.push(first_value_of_current);
                         }
                         // last record can be empty
-                        None => first_value_of_current,
-                    };
-                    last.values.push(merged);
-                    last.values.extend(first.values);
-                    Ok(Value {
-                        kind: Some(Kind::ListValue(last)),
-                    })
+                        None => last.values.push(first_value_of_current),
+                    }
                 }
-                _ => Err(Status::new(
+                last.values.extend(first);
+                Kind::ListValue(last)
+            }
+            (Some(Kind::StringValue(_)), _) => {
+                return Err(Status::new(
+                    Code::Internal,
+                    "chunks kind mismatch: current_first must be StringValue",
+                ))
+            }
+            (Some(Kind::ListValue(_)), _) => {
+                return Err(Status::new(
                     Code::Internal,
                     "chunks kind mismatch: current_first must be ListValue",
-                )),
-            },
-            _ => Err(Status::new(
-                Code::Internal,
-                "previous_last kind mismatch: only StringValue and ListValue can be chunked",
-            )),
-        }
+                ))
+            }
+            _ => {
+                return Err(Status::new(
+                    Code::Internal,
+                    "previous_last kind mismatch: only StringValue and ListValue can be chunked",
+                ))
+            }
+        };
+        Ok(Value { kind: Some(kind) })
+    }
+
+    fn can_merge(previous_last: &Value, current_first: &Value) -> bool {
+        matches!(
+            (&previous_last.kind, &current_first.kind),
+            (Some(Kind::StringValue(_)), Some(Kind::StringValue(_)))
+                | (Some(Kind::ListValue(_)), Some(Kind::ListValue(_)))
+        )
     }
 
     fn add(
         &mut self,
         metadata: Option<ResultSetMetadata>,
-        mut values: Vec<Value>,
+        values: Vec<Value>,
         chunked_value: bool,
     ) -> Result<bool, Status> {
         // get metadata only once.
@@ -165,10 +233,19 @@ impl ResultSet {
             }
         }
 
+        if values.is_empty() {
+            // nothing to merge: the chunked value continues in the next PartialResultSet.
+            return Ok(true);
+        }
+        let mut values = values.into_iter();
         if self.chunked_value {
             tracing::trace!("now chunked value found previous={}, current={}", self.rows.len(), values.len());
             //merge when the chunked value is found.
-            let merged = ResultSet::merge(self.rows.pop_back().unwrap(), values.remove(0))?;
+            let previous_last = self
+                .rows
+                .pop_back()
+                .ok_or_else(|| Status::new(Code::Internal, "no chunked value found"))?;
+            let merged = ResultSet::merge(previous_last, values.next().unwrap())?;
             self.rows.push_back(merged);
         }
         self.rows.extend(values);
@@ -177,15 +254,144 @@ impl ResultSet {
     }
 }
 
+/// Prefetch reads the PartialResultSets ahead of the consumer in the background task.
+/// The task reads the next PartialResultSet only when the channel has room for it,
+/// so the HTTP/2 flow control stops the server while the consumer is slow.
+struct Prefetch {
+    receiver: mpsc::Receiver<Result<PartialResultSet, Status>>,
+    task: JoinHandle<()>,
+}
+
+impl Prefetch {
+    fn spawn<S>(mut stream: S, size: usize) -> Self
+    where
+        S: Stream<Item = Result<PartialResultSet, Status>> + Send + Unpin + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(size.max(1));
+        let task = tokio::spawn(async move {
+            // reserve the room before reading the next message.
+            while let Ok(permit) = sender.reserve().await {
+                match stream.next().await {
+                    Some(Ok(result_set)) => permit.send(Ok(result_set)),
+                    Some(Err(e)) => {
+                        permit.send(Err(e));
+                        break;
+                    }
+                    None => break,
+                }
+            }
+        });
+        Self { receiver, task }
+    }
+}
+
+impl Stream for Prefetch {
+    type Item = Result<PartialResultSet, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl Drop for Prefetch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+enum ResultSetStream {
+    Direct(Box<Streaming<PartialResultSet>>),
+    Prefetch(Prefetch),
+}
+
+impl ResultSetStream {
+    fn new(streaming: Streaming<PartialResultSet>, prefetch: Option<usize>) -> Self {
+        match prefetch {
+            Some(size) => ResultSetStream::Prefetch(Prefetch::spawn(streaming, size)),
+            None => ResultSetStream::Direct(Box::new(streaming)),
+        }
+    }
+}
+
+impl Stream for ResultSetStream {
+    type Item = Result<PartialResultSet, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match &mut *self {
+            ResultSetStream::Direct(streaming) => streaming.poll_next_unpin(cx),
+            ResultSetStream::Prefetch(prefetch) => prefetch.poll_next_unpin(cx),
+        }
+    }
+}
+
+/// RowStream is the stream of the PartialResultSets read by the RowIterator.
+enum RowStream {
+    /// The stream re-established by the resume token when it is broken.
+    Resumable(Box<ResumableStream<ResultSetStream, PartialResultSet, Vec<u8>>>),
+    /// The stream returning the error as it is when it is broken.
+    Direct(ResultSetStream),
+}
+
+/// ReadState is the session and the reader used to re-establish the broken stream.
+struct ReadState<'a, T> {
+    session: IteratorSession<'a>,
+    reader: T,
+    /// The call options set by `RowIterator::set_call_options`.
+    option: Option<CallOptions>,
+    prefetch: Option<usize>,
+}
+
+impl<T: Reader> Reconnect<ResultSetStream, Vec<u8>> for ReadState<'_, T> {
+    async fn reconnect(&mut self, resume_token: Option<Vec<u8>>) -> Result<ResultSetStream, Status> {
+        self.reader.update_token(resume_token.unwrap_or_default());
+        let response = self.reader.read(self.session.handle(), self.option.clone()).await?;
+        Ok(ResultSetStream::new(response.into_inner(), self.prefetch))
+    }
+}
+
+/// resume_token returns the resume token of the PartialResultSet if it is set.
+fn resume_token(result_set: &PartialResultSet) -> Option<Vec<u8>> {
+    (!result_set.resume_token.is_empty()).then(|| result_set.resume_token.clone())
+}
+
 pub struct RowIterator<'a, T>
 where
     T: Reader,
 {
-    streaming: Streaming<PartialResultSet>,
-    session: &'a mut SessionHandle,
-    reader: T,
+    streaming: RowStream,
+    state: ReadState<'a, T>,
     rs: ResultSet,
-    reader_option: Option<CallOptions>,
+    metadata: Option<ResultSetMetadata>,
+    stats: Option<ResultSetStats>,
+    read_timestamp: Option<OffsetDateTime>,
+    done: bool,
+    /// The stream measured for the slow operation hook until it is completed or fails.
+    operation: Option<StreamOperation>,
+}
+
+/// resume_setting is the retry setting of resuming the broken stream by the resume token.
+fn resume_setting() -> RetrySetting {
+    RetrySetting {
+        codes: vec![Code::Unavailable, Code::Internal, Code::Unknown],
+        ..Default::default()
+    }
+}
+
+/// IteratorSession is the session the RowIterator reads with.
+enum IteratorSession<'a> {
+    /// The session of the transaction borrowed exclusively.
+    Borrowed(&'a mut SessionHandle),
+    /// The copy of the session of the shared read-only transaction.
+    Detached(Box<DetachedSession>),
+}
+
+impl IteratorSession<'_> {
+    fn handle(&mut self) -> &mut SessionHandle {
+        match self {
+            IteratorSession::Borrowed(session) => session,
+            IteratorSession::Detached(session) => session,
+        }
+    }
 }
 
 impl<'a, T> RowIterator<'a, T>
@@ -197,54 +403,120 @@ where
         reader: T,
         option: Option<CallOptions>,
     ) -> Result<RowIterator<'a, T>, Status> {
-        let streaming = reader.read(session, option).await?.into_inner();
+        Self::new_with_prefetch(session, reader, option, None).await
+    }
+
+    pub(crate) async fn new_with_prefetch(
+        session: &'a mut SessionHandle,
+        reader: T,
+        option: Option<CallOptions>,
+        prefetch: Option<usize>,
+    ) -> Result<RowIterator<'a, T>, Status> {
+        Self::new_with_session(IteratorSession::Borrowed(session), reader, option, prefetch).await
+    }
+
+    async fn new_with_session(
+        mut session: IteratorSession<'a>,
+        reader: T,
+        option: Option<CallOptions>,
+        prefetch: Option<usize>,
+    ) -> Result<RowIterator<'a, T>, Status> {
+        let operation = reader.start_operation(session.handle());
+        let first = match reader.read(session.handle(), option).await {
+            Ok(response) => ResultSetStream::new(response.into_inner(), prefetch),
+            Err(e) => {
+                if let Some(operation) = operation {
+                    operation.finish::<()>(&Err(e.clone()));
+                }
+                return Err(e);
+            }
+        };
+        let streaming = if reader.can_resume() {
+            RowStream::Resumable(Box::new(ResumableStream::new(
+                Some(resume_setting()),
+                Some(first),
+                resume_token,
+            )))
+        } else {
+            RowStream::Direct(first)
+        };
         let rs = ResultSet {
             fields: Arc::new(vec![]),
             index: Arc::new(HashMap::new()),
             rows: VecDeque::new(),
             chunked_value: false,
+            case_insensitive: false,
         };
         Ok(Self {
             streaming,
-            session,
-            reader,
+            state: ReadState {
+                session,
+                reader,
+                option: None,
+                prefetch,
+            },
             rs,
-            reader_option: None,
+            metadata: None,
+            stats: None,
+            read_timestamp: None,
+            done: false,
+            operation,
         })
     }
 
+    pub(crate) async fn new_detached(
+        session: DetachedSession,
+        reader: T,
+        option: Option<CallOptions>,
+        prefetch: Option<usize>,
+    ) -> Result<RowIterator<'static, T>, Status> {
+        RowIterator::new_with_session(IteratorSession::Detached(Box::new(session)), reader, option, prefetch).await
+    }
+
     pub fn set_call_options(&mut self, option: CallOptions) {
-        self.reader_option = Some(option);
+        self.state.option = Some(option);
     }
 
-    async fn try_recv(&mut self, option: Option<CallOptions>) -> Result<bool, Status> {
-        // try getting records from server
-        let maybe_result_set = match self.streaming.message().await {
-            Ok(s) => s,
-            Err(e) => {
-                if !self.reader.can_resume() {
-                    return Err(e);
-                }
-                tracing::debug!("streaming error: {}. resume reading by resume_token", e);
-                let result = self.reader.read(self.session, option).await?;
-                self.streaming = result.into_inner();
-                self.streaming.message().await?
-            }
-        };
+    /// set_case_insensitive enables the case-insensitive column name lookup
+    /// for the rows returned by this iterator. See `Row::set_case_insensitive`.
+    pub fn set_case_insensitive(&mut self, case_insensitive: bool) {
+        self.rs.case_insensitive = case_insensitive;
+    }
+
+    async fn try_recv(&mut self) -> Result<bool, Status> {
+        // try getting records from server. the broken stream is resumed by the resume token.
+        let maybe_result_set = match &mut self.streaming {
+            RowStream::Resumable(streaming) => streaming.next(&mut self.state).await,
+            RowStream::Direct(streaming) => streaming.next().await,
+        }
+        .transpose()?;
 
         match maybe_result_set {
             Some(result_set) => {
-                if result_set.values.is_empty() {
-                    return Ok(false);
+                // stats are sent with the last PartialResultSet.
+                if result_set.stats.is_some() {
+                    self.stats = result_set.stats;
                 }
-                //if resume_token changes set new resume_token
-                if !result_set.resume_token.is_empty() {
-                    self.reader.update_token(result_set.resume_token);
+                // the read timestamp of the single-use transaction is sent with the metadata.
+                if let Some(rts) = result_set
+                    .metadata
+                    .as_ref()
+                    .and_then(|m| m.transaction.as_ref())
+                    .and_then(|tx| tx.read_timestamp.clone())
+                {
+                    self.read_timestamp = SystemTime::try_from(rts).ok().map(OffsetDateTime::from);
+                }
+                // metadata is sent only with the first PartialResultSet.
+                if self.metadata.is_none() {
+                    self.metadata = result_set.metadata.clone();
                 }
                 self.rs
                     .add(result_set.metadata, result_set.values, result_set.chunked_value)
             }
-            None => Ok(false),
+            None => {
+                self.done = true;
+                Ok(false)
+            }
         }
     }
 
@@ -257,6 +529,45 @@ where
         None
     }
 
+    /// row_count returns the number of rows modified by the DML statement like `UPDATE ... THEN RETURN`.
+    /// It is available only after `next` returns None, and None is returned for queries.
+    pub fn row_count(&self) -> Option<RowCount> {
+        RowCount::from_stats(self.stats.as_ref())
+    }
+
+    /// metadata returns the metadata of the result set like the column types.
+    /// It is available after the first call of `next`.
+    pub fn metadata(&self) -> Option<&ResultSetMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// stats returns the statistics of the query like the query plan in the `Profile` mode
+    /// and the row count of the DML statement.
+    /// It is available only after `next` returns None, otherwise None is returned.
+    pub fn stats(&self) -> Option<&ResultSetStats> {
+        if self.done {
+            self.stats.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// transaction_id returns the id of the transaction begun by the request.
+    /// It is available after the first call of `next` only if the request began the transaction.
+    pub fn transaction_id(&self) -> Option<&[u8]> {
+        self.metadata
+            .as_ref()
+            .and_then(|m| m.transaction.as_ref())
+            .map(|tx| tx.id.as_slice())
+            .filter(|id| !id.is_empty())
+    }
+
+    /// read_timestamp returns the timestamp chosen by the single-use read-only transaction.
+    /// It is available after the first call of `next`.
+    pub fn read_timestamp(&self) -> Option<OffsetDateTime> {
+        self.read_timestamp
+    }
+
     /// next returns the next result.
     /// Its second return value is None if there are no more results.
     pub async fn next(&mut self) -> Result<Option<Row>, Status> {
@@ -266,7 +577,13 @@ where
                 return Ok(row);
             }
             // no data found or record chunked.
-            if !self.try_recv(self.reader_option.clone()).await? {
+            let received = self.try_recv().await;
+            if !matches!(received, Ok(true)) {
+                if let Some(operation) = self.operation.take() {
+                    operation.finish(&received);
+                }
+            }
+            if !received? {
                 return Ok(None);
             }
         }
@@ -276,15 +593,18 @@ where
 #[cfg(test)]
 mod tests {
     use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
 
+    use futures_util::StreamExt;
     use prost_types::value::Kind;
-    use prost_types::Value;
+    use prost_types::{ListValue, Value};
 
+    use google_cloud_gax::grpc::{Code, Status};
     use google_cloud_googleapis::spanner::v1::struct_type::Field;
-    use google_cloud_googleapis::spanner::v1::{ResultSetMetadata, StructType};
+    use google_cloud_googleapis::spanner::v1::{PartialResultSet, ResultSetMetadata, StructType};
 
-    use crate::reader::ResultSet;
+    use crate::reader::{Prefetch, ResultSet};
     use crate::row::{Row, TryFromValue};
     use crate::statement::ToKind;
 
@@ -294,6 +614,7 @@ mod tests {
             index: Arc::new(Default::default()),
             rows: Default::default(),
             chunked_value: false,
+            case_insensitive: false,
         }
     }
 
@@ -350,6 +671,7 @@ mod tests {
             index: Arc::new(Default::default()),
             rows: Default::default(),
             chunked_value: false,
+            case_insensitive: false,
         };
         assert!(rs.next().is_none());
     }
@@ -361,6 +683,7 @@ mod tests {
             index: Arc::new(Default::default()),
             rows: VecDeque::from(values),
             chunked_value: false,
+            case_insensitive: false,
         };
         let mut rs1 = rs(vec![value("value1")]);
         assert!(rs1.next().is_none());
@@ -375,6 +698,7 @@ mod tests {
             index: Arc::new(Default::default()),
             rows: VecDeque::from(vec![value("value1"), value("value2")]),
             chunked_value,
+            case_insensitive: false,
         };
         assert!(rs(true).next().is_none());
         assert_eq!(rs(false).next().unwrap().column::<String>(0).unwrap(), "value1".to_string());
@@ -387,6 +711,7 @@ mod tests {
             index: Arc::new(Default::default()),
             rows: VecDeque::from(vec![value("value1"), value("value2"), value("value3")]),
             chunked_value,
+            case_insensitive: false,
         };
         let mut incomplete = rs(true);
         assert!(incomplete.next().is_some());
@@ -406,6 +731,7 @@ mod tests {
             index: Arc::new(Default::default()),
             rows: VecDeque::from(vec![value("value1"), value("value2"), value("value3")]),
             chunked_value,
+            case_insensitive: false,
         };
         let mut incomplete = rs(true);
         assert_eq!(incomplete.next().unwrap().column::<String>(1).unwrap(), "value2".to_string());
@@ -701,4 +1027,223 @@ mod tests {
         );
         assert!(rs.next().is_none());
     }
+
+    fn list(values: Vec<Value>) -> Value {
+        Value {
+            kind: Some(Kind::ListValue(ListValue { values })),
+        }
+    }
+
+    #[test]
+    fn test_rs_merge_list_value_not_mergeable_elements() {
+        // [2, 3] + [4] => [2, 3, 4]
+        let result = ResultSet::merge(value(vec![2.0, 3.0]), value(vec![4.0])).unwrap();
+        assert_eq!(result, value(vec![2.0, 3.0, 4.0]));
+        // ["a", null] + ["b"] => ["a", null, "b"]
+        let result = ResultSet::merge(list(vec![value("a"), value(None::<String>)]), list(vec![value("b")])).unwrap();
+        assert_eq!(result, list(vec![value("a"), value(None::<String>), value("b")]));
+        // ["a"] + [] => ["a"]
+        let result = ResultSet::merge(value(vec!["a"]), list(vec![])).unwrap();
+        assert_eq!(result, value(vec!["a"]));
+    }
+
+    #[test]
+    fn test_rs_merge_nested_list_value() {
+        // ["a", ["b", "c"]] + [["d"], "e"] => ["a", ["b", "cd"], "e"]
+        let previous_last = list(vec![value("a"), value(vec!["b", "c"])]);
+        let current_first = list(vec![value(vec!["d"]), value("e")]);
+        let result = ResultSet::merge(previous_last, current_first).unwrap();
+        assert_eq!(result, list(vec![value("a"), value(vec!["b", "cd"]), value("e")]));
+
+        // [[1.0, "x"]] + [["y", 2.0]] => [[1.0, "xy", 2.0]]
+        let previous_last = list(vec![list(vec![value(1.0), value("x")])]);
+        let current_first = list(vec![list(vec![value("y"), value(2.0)])]);
+        let result = ResultSet::merge(previous_last, current_first).unwrap();
+        assert_eq!(result, list(vec![list(vec![value(1.0), value("xy"), value(2.0)])]));
+    }
+
+    #[test]
+    fn test_rs_merge_kind_mismatch() {
+        assert!(ResultSet::merge(value("a"), value(vec!["b"])).is_err());
+        assert!(ResultSet::merge(value(vec!["a"]), value("b")).is_err());
+        assert!(ResultSet::merge(value(1.0), value(2.0)).is_err());
+        assert!(ResultSet::merge(value(None::<String>), value("a")).is_err());
+    }
+
+    /// xorshift is a tiny deterministic random generator for the chunking test.
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// returns a value in 0..=max
+        fn up_to(&mut self, max: usize) -> usize {
+            (self.next() % (max as u64 + 1)) as usize
+        }
+
+        fn bool(&mut self) -> bool {
+            self.next().is_multiple_of(2)
+        }
+    }
+
+    fn random_value(rng: &mut XorShift, depth: usize) -> Value {
+        let max_kind = if depth < 3 { 5 } else { 4 };
+        match rng.up_to(max_kind) {
+            0 => value(None::<String>),
+            1 => value(rng.bool()),
+            2 => value(rng.next() as f64 / 1000.0),
+            3 | 4 => {
+                let chars = ['a', 'b', 'z', '0', ' ', '\u{e9}', '\u{3042}', '\u{1f600}'];
+                let s: String = (0..rng.up_to(20)).map(|_| chars[rng.up_to(chars.len() - 1)]).collect();
+                value(s)
+            }
+            _ => list((0..rng.up_to(5)).map(|_| random_value(rng, depth + 1)).collect()),
+        }
+    }
+
+    fn can_chunk(v: &Value) -> bool {
+        matches!(v.kind, Some(Kind::StringValue(_)) | Some(Kind::ListValue(_)))
+    }
+
+    /// split_value splits the value into two chunks which are merged into the original value.
+    fn split_value(v: &Value, rng: &mut XorShift) -> Option<(Value, Value)> {
+        match v.kind.as_ref()? {
+            Kind::StringValue(s) => {
+                let boundaries: Vec<usize> = s.char_indices().map(|(i, _)| i).chain([s.len()]).collect();
+                let at = boundaries[rng.up_to(boundaries.len() - 1)];
+                Some((value(&s[..at]), value(&s[at..])))
+            }
+            Kind::ListValue(l) => {
+                let values = &l.values;
+                let at = rng.up_to(values.len());
+                // The boundary between two elements is kept only if the elements are not merged.
+                let boundary_ok =
+                    at == 0 || at == values.len() || !can_chunk(&values[at - 1]) || !can_chunk(&values[at]);
+                if boundary_ok && (at == values.len() || !can_chunk(&values[at]) || rng.bool()) {
+                    return Some((list(values[..at].to_vec()), list(values[at..].to_vec())));
+                }
+                let (head, tail) = split_value(&values[at], rng)?;
+                let mut head_values = values[..at].to_vec();
+                head_values.push(head);
+                let mut tail_values = vec![tail];
+                tail_values.extend_from_slice(&values[at + 1..]);
+                Some((list(head_values), list(tail_values)))
+            }
+            _ => None,
+        }
+    }
+
+    /// split_stream splits the values into PartialResultSet like chunks: (values, chunked_value).
+    fn split_stream(values: Vec<Value>, rng: &mut XorShift) -> Vec<(Vec<Value>, bool)> {
+        let mut chunks = vec![];
+        let mut current = vec![];
+        let mut rest = VecDeque::from(values);
+        while let Some(v) = rest.pop_front() {
+            if rng.up_to(3) == 0 {
+                if let Some((head, tail)) = split_value(&v, rng) {
+                    current.push(head);
+                    chunks.push((std::mem::take(&mut current), true));
+                    rest.push_front(tail);
+                    continue;
+                }
+            }
+            current.push(v);
+            if rng.up_to(3) == 0 {
+                chunks.push((std::mem::take(&mut current), false));
+            }
+        }
+        if !current.is_empty() {
+            chunks.push((current, false));
+        }
+        chunks
+    }
+
+    #[test]
+    fn test_rs_add_random_chunks() {
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+        for _ in 0..500 {
+            let column_count = rng.up_to(3) + 1;
+            let row_count = rng.up_to(5);
+            let fields: Vec<Field> = (0..column_count).map(|i| field(&format!("column{i}"))).collect();
+            let original: Vec<Value> = (0..column_count * row_count)
+                .map(|_| random_value(&mut rng, 0))
+                .collect();
+            let metadata = Some(ResultSetMetadata {
+                row_type: Some(StructType { fields: fields.clone() }),
+                transaction: None,
+                undeclared_parameters: None,
+            });
+
+            let mut rs = empty_rs();
+            let mut rows = vec![];
+            for (i, (values, chunked_value)) in split_stream(original.clone(), &mut rng).into_iter().enumerate() {
+                let metadata = if i == 0 { metadata.clone() } else { None };
+                assert!(rs.add(metadata, values, chunked_value).unwrap());
+                while let Some(row) = rs.next() {
+                    rows.push(row.values().unwrap());
+                }
+            }
+            assert!(!rs.chunked_value);
+            assert!(rs.rows.is_empty());
+
+            let expected: Vec<_> = original
+                .chunks(column_count)
+                .map(|values| {
+                    Row::new(Arc::new(Default::default()), Arc::new(fields.clone()), values.to_vec())
+                        .values()
+                        .unwrap()
+                })
+                .collect();
+            assert_eq!(rows, expected);
+        }
+    }
+
+    #[tokio::test]
+    #[allow(clippy::result_large_err)]
+    async fn test_prefetch_bounded() {
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&pulled);
+        let total = 10_000;
+        let size = 8;
+        let stream = futures_util::stream::iter(0..total).map(move |i| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(PartialResultSet {
+                values: vec![value(format!("{i}:{}", "x".repeat(1024)))],
+                ..Default::default()
+            })
+        });
+
+        let mut prefetch = Prefetch::spawn(stream, size);
+        let mut consumed = 0;
+        while let Some(result_set) = prefetch.next().await.transpose().unwrap() {
+            assert!(
+                matches!(&result_set.values[0].kind, Some(Kind::StringValue(v)) if v.starts_with(&format!("{consumed}:")))
+            );
+            consumed += 1;
+            // slow consumer
+            for _ in 0..10 {
+                tokio::task::yield_now().await;
+            }
+            assert!(pulled.load(Ordering::SeqCst) - consumed <= size);
+        }
+        assert_eq!(consumed, total);
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_error() {
+        let stream = futures_util::stream::iter(vec![
+            Ok(PartialResultSet::default()),
+            Err(Status::new(Code::Unavailable, "unavailable")),
+            Ok(PartialResultSet::default()),
+        ]);
+        let mut prefetch = Prefetch::spawn(stream, 4);
+        assert!(prefetch.next().await.transpose().unwrap().is_some());
+        assert_eq!(prefetch.next().await.transpose().unwrap_err().code(), Code::Unavailable);
+        assert!(prefetch.next().await.transpose().unwrap().is_none());
+    }
 }
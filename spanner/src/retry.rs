@@ -1,9 +1,34 @@
 use std::iter::Take;
 use std::marker::PhantomData;
+use std::time::Duration;
+
+use prost::Message;
 
 use google_cloud_gax::grpc::{Code, Status};
 use google_cloud_gax::retry::{CodeCondition, Condition, ExponentialBackoff, Retry, RetrySetting, TryAs};
 
+const RETRY_INFO_TYPE: &str = "type.googleapis.com/google.rpc.RetryInfo";
+
+/// google.rpc.RetryInfo
+#[derive(Clone, PartialEq, Message)]
+struct RetryInfo {
+    #[prost(message, optional, tag = "1")]
+    retry_delay: Option<prost_types::Duration>,
+}
+
+/// server_retry_delay returns the delay of the RetryInfo in the status details.
+pub(crate) fn server_retry_delay(status: &Status) -> Option<Duration> {
+    let details = google_cloud_googleapis::rpc::Status::decode(status.details()).ok()?;
+    let retry_info = details
+        .details
+        .iter()
+        .find(|d| d.type_url == RETRY_INFO_TYPE)
+        .and_then(|d| RetryInfo::decode(d.value.as_slice()).ok())?;
+    retry_info
+        .retry_delay
+        .map(|d| Duration::new(d.seconds.max(0) as u64, d.nanos.max(0) as u32))
+}
+
 pub struct TransactionCondition<E>
 where
     E: TryAs<Status>,
@@ -39,7 +64,8 @@ pub struct TransactionRetry<E>
 where
     E: TryAs<Status>,
 {
-    strategy: Take<ExponentialBackoff>,
+    setting: TransactionRetrySetting,
+    retries: usize,
     condition: TransactionCondition<E>,
 }
 
@@ -47,27 +73,32 @@ impl<E> TransactionRetry<E>
 where
     E: TryAs<Status>,
 {
+    /// next waits for the backoff if the error is retryable, otherwise returns the error.
     pub async fn next(&mut self, status: E) -> Result<(), E> {
-        let duration = if self.condition.should_retry(&status) {
-            self.strategy.next()
+        if self.retries >= self.setting.inner.take || !self.condition.should_retry(&status) {
+            return Err(status);
+        }
+        let server_delay = status.try_as().and_then(server_retry_delay);
+        let duration = if self.setting.server_retry_delay_only {
+            server_delay.unwrap_or_default()
         } else {
-            None
+            server_delay.unwrap_or_else(|| self.setting.backoff(self.retries))
         };
-        match duration {
-            Some(duration) => {
-                tokio::time::sleep(duration).await;
-                Ok(())
-            }
-            None => Err(status),
-        }
+        self.retries += 1;
+        tokio::time::sleep(duration).await;
+        Ok(())
     }
 
     pub fn new() -> Self {
-        let setting = TransactionRetrySetting::default();
-        let strategy = <TransactionRetrySetting as Retry<E, TransactionCondition<E>>>::strategy(&setting);
+        Self::new_with_setting(TransactionRetrySetting::default())
+    }
+
+    pub fn new_with_setting(setting: TransactionRetrySetting) -> Self {
+        let condition = setting.condition();
         Self {
-            strategy,
-            condition: setting.condition(),
+            setting,
+            retries: 0,
+            condition,
         }
     }
 }
@@ -81,9 +112,17 @@ where
     }
 }
 
+/// TransactionRetrySetting is the setting to replay the read-write transaction on ABORTED.
+/// It is distinct from the `RetrySetting` of the RPC because the user function is called again.
 #[derive(Clone, Debug)]
 pub struct TransactionRetrySetting {
+    /// `from_millis` is the initial backoff, `max_delay` is the max backoff and `take` is the max number of the retries.
     pub inner: RetrySetting,
+    /// The backoff is multiplied by this value for each retry.
+    pub multiplier: f64,
+    /// When true, the transaction is retried only after the delay of the RetryInfo returned by the server,
+    /// or immediately without it. Otherwise the backoff is used when the server doesn't return the RetryInfo.
+    pub server_retry_delay_only: bool,
 }
 
 impl<E> Retry<E, TransactionCondition<E>> for TransactionRetrySetting
@@ -109,6 +148,41 @@ impl TransactionRetrySetting {
                 codes,
                 ..Default::default()
             },
+            multiplier: 10.0,
+            server_retry_delay_only: false,
+        }
+    }
+
+    /// with_max_attempts sets the max number of the calls of the transaction function including the first call.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.inner.take = max_attempts.saturating_sub(1);
+        self
+    }
+
+    /// with_backoff sets the initial and the max backoff.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.inner.from_millis = initial.as_millis() as u64;
+        self.inner.max_delay = Some(max);
+        self
+    }
+
+    pub fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    pub fn with_server_retry_delay_only(mut self, server_retry_delay_only: bool) -> Self {
+        self.server_retry_delay_only = server_retry_delay_only;
+        self
+    }
+
+    /// backoff returns the delay before the retry. `retries` is the number of the retries performed.
+    pub fn backoff(&self, retries: usize) -> Duration {
+        let millis = self.inner.from_millis as f64 * self.multiplier.powi(retries as i32);
+        let delay = Duration::from_secs_f64(millis.max(0.0).min(u64::MAX as f64) / 1000.0);
+        match self.inner.max_delay {
+            Some(max) => delay.min(max),
+            None => delay,
         }
     }
 }
@@ -121,11 +195,15 @@ impl Default for TransactionRetrySetting {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
+    use prost::Message;
+
     use google_cloud_gax::grpc::{Code, Status};
     use google_cloud_gax::retry::{Condition, Retry};
 
     use crate::client::Error;
-    use crate::retry::TransactionRetrySetting;
+    use crate::retry::{server_retry_delay, RetryInfo, TransactionRetry, TransactionRetrySetting, RETRY_INFO_TYPE};
 
     #[test]
     fn test_transaction_condition() {
@@ -135,5 +213,54 @@ mod tests {
 
         let err = &Error::GRPC(Status::new(Code::Aborted, ""));
         assert!(default.condition().should_retry(err));
+
+        let no_retry = TransactionRetrySetting::new(vec![]);
+        assert!(!no_retry.condition().should_retry(err));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let default = TransactionRetrySetting::default();
+        assert_eq!(default.backoff(0), Duration::from_millis(10));
+        assert_eq!(default.backoff(1), Duration::from_millis(100));
+        assert_eq!(default.backoff(2), Duration::from_secs(1));
+        assert_eq!(default.backoff(3), Duration::from_secs(1));
+
+        let setting = TransactionRetrySetting::default()
+            .with_max_attempts(3)
+            .with_backoff(Duration::from_millis(50), Duration::from_millis(300))
+            .with_multiplier(2.0);
+        assert_eq!(setting.inner.take, 2);
+        assert_eq!(setting.backoff(0), Duration::from_millis(50));
+        assert_eq!(setting.backoff(1), Duration::from_millis(100));
+        assert_eq!(setting.backoff(3), Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn test_transaction_retry_max_attempts() {
+        let setting = TransactionRetrySetting::default()
+            .with_max_attempts(2)
+            .with_backoff(Duration::ZERO, Duration::ZERO);
+        let mut retry = TransactionRetry::<Status>::new_with_setting(setting);
+        assert!(retry.next(Status::new(Code::Aborted, "")).await.is_ok());
+        assert!(retry.next(Status::new(Code::Aborted, "")).await.is_err());
+    }
+
+    #[test]
+    fn test_server_retry_delay() {
+        let retry_info = RetryInfo {
+            retry_delay: Some(prost_types::Duration { seconds: 1, nanos: 5 }),
+        };
+        let details = google_cloud_googleapis::rpc::Status {
+            code: Code::Aborted as i32,
+            message: "".to_string(),
+            details: vec![prost_types::Any {
+                type_url: RETRY_INFO_TYPE.to_string(),
+                value: retry_info.encode_to_vec(),
+            }],
+        };
+        let status = Status::with_details(Code::Aborted, "", details.encode_to_vec().into());
+        assert_eq!(server_retry_delay(&status), Some(Duration::new(1, 5)));
+        assert_eq!(server_retry_delay(&Status::new(Code::Aborted, "")), None);
     }
 }
@@ -1,10 +1,12 @@
 use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Formatter};
 use std::num::ParseIntError;
 use std::str::FromStr;
 use std::sync::Arc;
 
 use base64::prelude::*;
 use base64::DecodeError;
+use indexmap::IndexMap;
 use prost_types::value::Kind;
 use prost_types::{value, Value};
 use time::format_description::well_known::Rfc3339;
@@ -12,18 +14,24 @@ use time::macros::format_description;
 use time::{Date, OffsetDateTime};
 
 use google_cloud_googleapis::spanner::v1::struct_type::Field;
-use google_cloud_googleapis::spanner::v1::StructType;
+use google_cloud_googleapis::spanner::v1::{StructType, Type, TypeCode};
 
 use crate::bigdecimal::{BigDecimal, ParseBigDecimalError};
-use crate::value::CommitTimestamp;
+use crate::value::{CommitTimestamp, Interval, IntervalError, Value as SpannerValue};
 
 #[derive(Clone)]
 pub struct Row {
     index: Arc<HashMap<String, usize>>,
     fields: Arc<Vec<Field>>,
     values: Vec<Value>,
+    case_insensitive: bool,
 }
 
+/// Error is the error of reading the column values of the row.
+///
+/// Breaking change: `NoColumnFound` holds the available column names as the second value,
+/// and the error decoding the value of a column is wrapped in `ColumnDecodeError` with the column context.
+/// Match on `Error::root_cause` instead of the error itself to handle the decoding error like `KindMismatch`.
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("Illegal Kind: field={0}, kind={1}")]
@@ -40,8 +48,13 @@ pub enum Error {
     StructParseError(String, &'static str),
     #[error("Failed to parse as Custom Type {0}")]
     CustomParseError(String),
+    /// The second value is the list of the available column names.
     #[error("No column found: name={0}")]
-    NoColumnFound(String),
+    NoColumnFound(String, Vec<String>),
+    /// The column name matches more than one column case-insensitively and none of them exactly.
+    /// The second value is the list of the matching column names.
+    #[error("Ambiguous column: name={0}, candidates={1:?}")]
+    AmbiguousColumn(String, Vec<String>),
     #[error("invalid column index: index={0}, length={1}")]
     InvalidColumnIndex(usize, usize),
     #[error("invalid struct column index: index={0}")]
@@ -50,11 +63,49 @@ pub enum Error {
     NoColumnFoundInStruct(String),
     #[error("Failed to parse as BigDecimal field={0}")]
     BigDecimalParseError(String, #[source] ParseBigDecimalError),
+    #[error("Failed to parse as Interval field={0}")]
+    IntervalParseError(String, #[source] IntervalError),
+    #[error("Failed to deserialize: {0}")]
+    DeserializeError(String),
+    #[error("Failed to deserialize field={0}, type={1}: {2}")]
+    DeserializeFieldError(String, String, String),
+    #[error("Failed to decode column: name={0}, index={1}, spanner_type={2}, rust_type={3}: {4}")]
+    ColumnDecodeError(String, usize, String, &'static str, Box<Error>),
+    /// The column of the field of the struct deriving `Table` or `Query` is not in the row.
+    /// The values are the column name, the struct name and the field name.
+    #[error("No column found: name={0}, field={1}.{2}. Select the column, or use #[spanner(default)] on the field or #[spanner(allow_missing_columns)] on the struct to set the default value")]
+    MissingColumn(String, &'static str, &'static str),
+    /// The stored value doesn't match any variant of the enum deriving `SpannerEnum`.
+    #[error("Unknown enum value: field={0}, value={1}, enum={2}")]
+    UnknownEnumValue(String, String, &'static str),
+}
+
+impl Error {
+    /// root_cause returns the error wrapped by `ColumnDecodeError`, or the error itself for the other variants.
+    pub fn root_cause(&self) -> &Error {
+        match self {
+            Error::ColumnDecodeError(_, _, _, _, source) => source.root_cause(),
+            e => e,
+        }
+    }
 }
 
 impl Row {
     pub fn new(index: Arc<HashMap<String, usize>>, fields: Arc<Vec<Field>>, values: Vec<Value>) -> Row {
-        Row { index, fields, values }
+        Row {
+            index,
+            fields,
+            values,
+            case_insensitive: false,
+        }
+    }
+
+    /// set_case_insensitive enables the case-insensitive column name lookup used by
+    /// `column_by_name` and `try_column_by_name`.
+    /// The exact match is preferred when the both `UserId` and `userid` exist. Without the exact match,
+    /// `Error::AmbiguousColumn` is returned if more than one column matches.
+    pub fn set_case_insensitive(&mut self, case_insensitive: bool) {
+        self.case_insensitive = case_insensitive;
     }
 
     pub fn column<T>(&self, column_index: usize) -> Result<T, Error>
@@ -68,16 +119,180 @@ impl Row {
     where
         T: TryFromValue,
     {
-        self.column(index(&self.index, column_name)?)
+        self.column(index(&self.index, &self.fields, column_name, self.case_insensitive)?)
+    }
+
+    /// try_column returns None instead of an error when the column index is out of range.
+    /// Errors while decoding the value are still returned.
+    pub fn try_column<T>(&self, column_index: usize) -> Result<Option<T>, Error>
+    where
+        T: TryFromValue,
+    {
+        if column_index >= self.values.len() {
+            return Ok(None);
+        }
+        self.column(column_index).map(Some)
+    }
+
+    /// try_column_by_name returns None instead of an error when the column is not in the projection.
+    /// Errors while decoding the value are still returned.
+    pub fn try_column_by_name<T>(&self, column_name: &str) -> Result<Option<T>, Error>
+    where
+        T: TryFromValue,
+    {
+        match index(&self.index, &self.fields, column_name, self.case_insensitive) {
+            Ok(column_index) => self.try_column(column_index),
+            Err(Error::NoColumnFound(_, _)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// values returns the all the columns as dynamically typed values with the column metadata.
+    pub fn values(&self) -> Result<Vec<(ColumnMeta, SpannerValue)>, Error> {
+        self.fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let value = self.column::<SpannerValue>(i)?;
+                Ok((ColumnMeta::new(i, field), value))
+            })
+            .collect()
+    }
+
+    /// try_to_map returns the all the columns as dynamically typed values keyed by the column name.
+    /// The order of the columns is preserved.
+    pub fn try_to_map(&self) -> Result<IndexMap<String, SpannerValue>, Error> {
+        let mut map = IndexMap::with_capacity(self.fields.len());
+        for (i, field) in self.fields.iter().enumerate() {
+            map.insert(field.name.to_string(), self.column::<SpannerValue>(i)?);
+        }
+        Ok(map)
+    }
+
+    /// deserialize converts the row into any type implementing `serde::Deserialize`.
+    /// See [`crate::de`] for the mapping of Spanner types.
+    #[cfg(feature = "serde")]
+    pub fn deserialize<'a, T>(&'a self) -> Result<T, Error>
+    where
+        T: serde::Deserialize<'a>,
+    {
+        crate::de::from_columns(&self.fields, &self.values)
+    }
+}
+
+/// ColumnMeta is the metadata of the column.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnMeta {
+    pub index: usize,
+    pub name: String,
+    pub column_type: Option<Type>,
+}
+
+impl ColumnMeta {
+    fn new(index: usize, field: &Field) -> Self {
+        Self {
+            index,
+            name: field.name.to_string(),
+            column_type: field.r#type.clone(),
+        }
+    }
+
+    /// type_name returns the SQL representation of the column type like `ARRAY<INT64>`.
+    pub fn type_name(&self) -> String {
+        type_name(self.column_type.as_ref())
+    }
+}
+
+/// Debug prints the column names, the types and the values like `Row { UserId: STRING "user1" }`.
+/// The values are printed, so use [`Row::redacted`] to log the rows containing personal data.
+impl Debug for Row {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut s = f.debug_struct("Row");
+        for (field, value) in self.fields.iter().zip(self.values.iter()) {
+            s.field(&field.name, &TypedValue::new(field, Some(value)));
+        }
+        s.finish()
+    }
+}
+
+impl Row {
+    /// redacted returns the column names and the types without the values.
+    /// It is safe to be written to the logs in the same way as the spans of the `trace` feature never record the values.
+    pub fn redacted(&self) -> RedactedRow {
+        RedactedRow {
+            columns: self
+                .fields
+                .iter()
+                .map(|field| RedactedField::new(&field.name, field.r#type.as_ref()))
+                .collect(),
+        }
+    }
+}
+
+/// RedactedRow is the row without the values.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RedactedRow {
+    pub columns: Vec<RedactedField>,
+}
+
+/// RedactedField is the name and the type of the column or the parameter without the value.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RedactedField {
+    pub name: String,
+    /// The SQL representation of the type like `ARRAY<INT64>`.
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub type_name: String,
+}
+
+impl RedactedField {
+    pub(crate) fn new(name: &str, tp: Option<&Type>) -> Self {
+        Self {
+            name: name.to_string(),
+            type_name: type_name(tp),
+        }
+    }
+}
+
+/// TypedValue prints the type and the value like `STRING "user1"`.
+/// The value is replaced with `<redacted>` when it is None.
+pub(crate) struct TypedValue<'a> {
+    field: &'a Field,
+    value: Option<&'a Value>,
+}
+
+impl<'a> TypedValue<'a> {
+    pub(crate) fn new(field: &'a Field, value: Option<&'a Value>) -> Self {
+        Self { field, value }
+    }
+}
+
+impl Debug for TypedValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let tp = self.field.r#type.as_ref();
+        write!(f, "{} ", type_name(tp))?;
+        match self.value {
+            None => write!(f, "<redacted>"),
+            Some(value) => match to_spanner_value(value, tp, self.field) {
+                Ok(v) => v.fmt_nested(f),
+                Err(_) => write!(f, "{:?}", value.kind),
+            },
+        }
     }
 }
 
 //don't use TryFrom trait to avoid the conflict
 //https://github.com/rust-lang/rust/issues/50133
+#[diagnostic::on_unimplemented(message = "`{Self}` can't be decoded from a Spanner column")]
 pub trait TryFromValue: Sized {
     fn try_from(value: &Value, field: &Field) -> Result<Self, Error>;
 }
 
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be decoded from a Spanner STRUCT",
+    note = "derive `Table` or `Query` for `{Self}`, or implement `TryFromStruct`"
+)]
 pub trait TryFromStruct: Sized {
     fn try_from_struct(s: Struct<'_>) -> Result<Self, Error>;
 }
@@ -113,23 +328,37 @@ impl<'a> Struct<'a> {
         }
     }
 
+    /// fields returns the name and the type of the fields in declared order.
+    pub fn fields(&self) -> &[Field] {
+        &self.metadata.fields
+    }
+
+    pub fn len(&self) -> usize {
+        self.metadata.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.metadata.fields.is_empty()
+    }
+
     pub fn column<T>(&self, column_index: usize) -> Result<T, Error>
     where
         T: TryFromValue,
     {
-        match self.list_values {
-            Some(values) => column(values, &self.metadata.fields, column_index),
-            None => match self.struct_values {
-                Some(values) => {
-                    let field = &self.metadata.fields[column_index];
-                    let name = &field.name;
-                    match values.get(name) {
-                        Some(value) => T::try_from(value, field),
-                        None => Err(Error::NoColumnFoundInStruct(name.to_string())),
-                    }
+        match (self.list_values, self.struct_values) {
+            (Some(values), _) => column(values, &self.metadata.fields, column_index),
+            (None, Some(values)) => {
+                let field = self
+                    .metadata
+                    .fields
+                    .get(column_index)
+                    .ok_or(Error::InvalidStructColumnIndex(column_index))?;
+                match values.get(&field.name) {
+                    Some(value) => decode(value, field, column_index),
+                    None => Err(Error::NoColumnFoundInStruct(field.name.to_string())),
                 }
-                None => Err(Error::InvalidStructColumnIndex(column_index)),
-            },
+            }
+            (None, None) => Err(Error::InvalidStructColumnIndex(column_index)),
         }
     }
 
@@ -137,7 +366,36 @@ impl<'a> Struct<'a> {
     where
         T: TryFromValue,
     {
-        self.column(index(&self.index, column_name)?)
+        self.column(index(&self.index, &self.metadata.fields, column_name, false)?)
+    }
+
+    /// try_column_by_name returns None instead of an error when the field is not in the struct.
+    /// Errors while decoding the value are still returned.
+    pub fn try_column_by_name<T>(&self, column_name: &str) -> Result<Option<T>, Error>
+    where
+        T: TryFromValue,
+    {
+        match index(&self.index, &self.metadata.fields, column_name, false) {
+            Ok(column_index) => match self.column(column_index) {
+                Err(Error::NoColumnFoundInStruct(_)) => Ok(None),
+                result => result.map(Some),
+            },
+            Err(Error::NoColumnFound(_, _)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// values returns the all the fields as dynamically typed values with the field metadata.
+    pub fn values(&self) -> Result<Vec<(ColumnMeta, SpannerValue)>, Error> {
+        self.metadata
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let value = self.column::<SpannerValue>(i)?;
+                Ok((ColumnMeta::new(i, field), value))
+            })
+            .collect()
     }
 }
 
@@ -154,6 +412,13 @@ impl TryFromValue for f64 {
     fn try_from(item: &Value, field: &Field) -> Result<Self, Error> {
         match as_ref(item, field)? {
             Kind::NumberValue(s) => Ok(*s),
+            // NaN and infinity are encoded as string.
+            Kind::StringValue(s) => match s.as_str() {
+                "NaN" => Ok(f64::NAN),
+                "Infinity" => Ok(f64::INFINITY),
+                "-Infinity" => Ok(f64::NEG_INFINITY),
+                _ => Err(Error::KindMismatch(field.name.to_string(), "StringValue".to_string())),
+            },
             v => kind_to_error(v, field),
         }
     }
@@ -197,6 +462,17 @@ impl TryFromValue for Date {
     }
 }
 
+impl TryFromValue for Interval {
+    fn try_from(item: &Value, field: &Field) -> Result<Self, Error> {
+        match as_ref(item, field)? {
+            Kind::StringValue(s) => s
+                .parse()
+                .map_err(|e| Error::IntervalParseError(field.name.to_string(), e)),
+            v => kind_to_error(v, field),
+        }
+    }
+}
+
 impl TryFromValue for Vec<u8> {
     fn try_from(item: &Value, field: &Field) -> Result<Self, Error> {
         match as_ref(item, field)? {
@@ -228,18 +504,113 @@ impl TryFromValue for String {
     }
 }
 
+impl TryFromValue for SpannerValue {
+    fn try_from(item: &Value, field: &Field) -> Result<Self, Error> {
+        to_spanner_value(item, field.r#type.as_ref(), field)
+    }
+}
+
+fn to_spanner_value(item: &Value, tp: Option<&Type>, field: &Field) -> Result<SpannerValue, Error> {
+    let kind = match item.kind.as_ref() {
+        None | Some(Kind::NullValue(_)) => return Ok(SpannerValue::Null),
+        Some(kind) => kind,
+    };
+    let tp = match tp {
+        Some(tp) if tp.code() != TypeCode::Unspecified => tp,
+        // Decide the type by the kind when the type is unknown.
+        _ => {
+            return Ok(match kind {
+                Kind::BoolValue(v) => SpannerValue::Bool(*v),
+                Kind::NumberValue(v) => SpannerValue::Float64(*v),
+                Kind::StringValue(v) => SpannerValue::String(v.to_string()),
+                Kind::ListValue(v) => SpannerValue::Array(
+                    v.values
+                        .iter()
+                        .map(|v| to_spanner_value(v, None, field))
+                        .collect::<Result<_, _>>()?,
+                ),
+                Kind::StructValue(v) => SpannerValue::Struct(
+                    v.fields
+                        .iter()
+                        .map(|(name, v)| Ok((name.to_string(), to_spanner_value(v, None, field)?)))
+                        .collect::<Result<_, Error>>()?,
+                ),
+                Kind::NullValue(_) => SpannerValue::Null,
+            })
+        }
+    };
+    Ok(match tp.code() {
+        TypeCode::Bool => SpannerValue::Bool(TryFromValue::try_from(item, field)?),
+        TypeCode::Int64 => SpannerValue::Int64(TryFromValue::try_from(item, field)?),
+        TypeCode::Float64 => SpannerValue::Float64(TryFromValue::try_from(item, field)?),
+        TypeCode::Timestamp => SpannerValue::Timestamp(TryFromValue::try_from(item, field)?),
+        TypeCode::Date => SpannerValue::Date(TryFromValue::try_from(item, field)?),
+        TypeCode::String => SpannerValue::String(TryFromValue::try_from(item, field)?),
+        TypeCode::Bytes => SpannerValue::Bytes(TryFromValue::try_from(item, field)?),
+        TypeCode::Numeric => SpannerValue::Numeric(TryFromValue::try_from(item, field)?),
+        TypeCode::Json => SpannerValue::Json(TryFromValue::try_from(item, field)?),
+        TypeCode::Interval => SpannerValue::Interval(TryFromValue::try_from(item, field)?),
+        TypeCode::Array => match kind {
+            Kind::ListValue(v) => SpannerValue::Array(
+                v.values
+                    .iter()
+                    .map(|v| to_spanner_value(v, tp.array_element_type.as_deref(), field))
+                    .collect::<Result<_, _>>()?,
+            ),
+            v => return kind_to_error(v, field),
+        },
+        TypeCode::Struct => {
+            let struct_fields = tp.struct_type.as_ref().map(|t| t.fields.as_slice()).unwrap_or_default();
+            match kind {
+                Kind::ListValue(v) => SpannerValue::Struct(
+                    struct_fields
+                        .iter()
+                        .zip(v.values.iter())
+                        .map(|(f, v)| Ok((f.name.to_string(), to_spanner_value(v, f.r#type.as_ref(), f)?)))
+                        .collect::<Result<_, Error>>()?,
+                ),
+                // Keep the order of the struct type if available.
+                Kind::StructValue(v) if !struct_fields.is_empty() => SpannerValue::Struct(
+                    struct_fields
+                        .iter()
+                        .filter_map(|f| v.fields.get(&f.name).map(|v| (f, v)))
+                        .map(|(f, v)| Ok((f.name.to_string(), to_spanner_value(v, f.r#type.as_ref(), f)?)))
+                        .collect::<Result<_, Error>>()?,
+                ),
+                Kind::StructValue(v) => SpannerValue::Struct(
+                    v.fields
+                        .iter()
+                        .map(|(name, v)| Ok((name.to_string(), to_spanner_value(v, None, field)?)))
+                        .collect::<Result<_, Error>>()?,
+                ),
+                v => return kind_to_error(v, field),
+            }
+        }
+        TypeCode::Unspecified => unreachable!("unspecified type is handled above"),
+    })
+}
+
 impl<T> TryFromValue for T
 where
     T: TryFromStruct,
 {
     fn try_from(item: &Value, field: &Field) -> Result<Self, Error> {
-        let maybe_array = match field.r#type.as_ref() {
+        let tp = match field.r#type.as_ref() {
             None => return Err(Error::StructParseError(field.name.to_string(), "field type must not be none")),
-            Some(tp) => tp.array_element_type.as_ref(),
+            Some(tp) => tp,
         };
-        let maybe_struct_type = match maybe_array {
-            None => return Err(Error::StructParseError(field.name.to_string(), "array must not be none")),
-            Some(tp) => tp.struct_type.as_ref(),
+        // STRUCT column or element of ARRAY<STRUCT> column
+        let maybe_struct_type = match tp.struct_type.as_ref() {
+            Some(struct_type) => Some(struct_type),
+            None => match tp.array_element_type.as_ref() {
+                None => {
+                    return Err(Error::StructParseError(
+                        field.name.to_string(),
+                        "column type must be STRUCT or ARRAY<STRUCT>",
+                    ))
+                }
+                Some(tp) => tp.struct_type.as_ref(),
+            },
         };
         let struct_type = match maybe_struct_type {
             None => {
@@ -255,13 +626,21 @@ where
     }
 }
 
+/// NULL is decoded as `None` for every supported type.
+///
+/// * NULL scalar: `Option<T>`
+/// * NULL ARRAY: `Option<Vec<T>>`
+/// * ARRAY containing NULL elements: `Vec<Option<T>>` (or `Option<Vec<Option<T>>>`)
+/// * NULL STRUCT: `Option<T>` where `T: TryFromStruct`
+///
+/// Decoding NULL into a type other than `Option<T>` fails with `Error::KindMismatch`.
 impl<T> TryFromValue for Option<T>
 where
     T: TryFromValue,
 {
     fn try_from(item: &Value, field: &Field) -> Result<Self, Error> {
-        match as_ref(item, field)? {
-            Kind::NullValue(_i) => Ok(None),
+        match item.kind.as_ref() {
+            None | Some(Kind::NullValue(_)) => Ok(None),
             _ => Ok(Some(T::try_from(item, field)?)),
         }
     }
@@ -279,22 +658,72 @@ where
     }
 }
 
-fn index(index: &HashMap<String, usize>, column_name: &str) -> Result<usize, Error> {
-    match index.get(column_name) {
-        Some(column_index) => Ok(*column_index),
-        None => Err(Error::NoColumnFound(column_name.to_string())),
+fn index(
+    index: &HashMap<String, usize>,
+    fields: &[Field],
+    column_name: &str,
+    case_insensitive: bool,
+) -> Result<usize, Error> {
+    if let Some(column_index) = index.get(column_name) {
+        return Ok(*column_index);
     }
+    if case_insensitive {
+        let mut matches = fields
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.name.eq_ignore_ascii_case(column_name));
+        match (matches.next(), matches.next()) {
+            (Some((column_index, _)), None) => return Ok(column_index),
+            (Some(first), Some(second)) => {
+                let candidates = [first, second].into_iter().chain(matches);
+                return Err(Error::AmbiguousColumn(
+                    column_name.to_string(),
+                    candidates.map(|(_, f)| f.name.to_string()).collect(),
+                ));
+            }
+            _ => {}
+        }
+    }
+    Err(Error::NoColumnFound(
+        column_name.to_string(),
+        fields.iter().map(|f| f.name.to_string()).collect(),
+    ))
 }
 
 fn column<T>(values: &[Value], fields: &[Field], column_index: usize) -> Result<T, Error>
 where
     T: TryFromValue,
 {
-    if values.len() <= column_index {
-        return Err(Error::InvalidColumnIndex(column_index, values.len()));
+    if values.len() <= column_index || fields.len() <= column_index {
+        return Err(Error::InvalidColumnIndex(column_index, values.len().min(fields.len())));
+    }
+    decode(&values[column_index], &fields[column_index], column_index)
+}
+
+fn decode<T>(value: &Value, field: &Field, column_index: usize) -> Result<T, Error>
+where
+    T: TryFromValue,
+{
+    T::try_from(value, field).map_err(|e| {
+        Error::ColumnDecodeError(
+            field.name.to_string(),
+            column_index,
+            type_name(field.r#type.as_ref()),
+            std::any::type_name::<T>(),
+            Box::new(e),
+        )
+    })
+}
+
+/// type_name returns the SQL representation of the type like `ARRAY<INT64>`.
+pub(crate) fn type_name(tp: Option<&Type>) -> String {
+    match tp {
+        None => "UNKNOWN".to_string(),
+        Some(tp) => match tp.code() {
+            TypeCode::Array => format!("ARRAY<{}>", type_name(tp.array_element_type.as_deref())),
+            code => code.as_str_name().to_string(),
+        },
     }
-    let value = &values[column_index];
-    T::try_from(value, &fields[column_index])
 }
 
 pub fn as_ref<'a>(item: &'a Value, field: &'a Field) -> Result<&'a Kind, Error> {
@@ -311,7 +740,7 @@ pub fn kind_to_error<'a, T>(v: &'a value::Kind, field: &'a Field) -> Result<T, E
         Kind::NumberValue(_s) => "NumberValue".to_string(),
         Kind::ListValue(_s) => "ListValue".to_string(),
         Kind::StructValue(_s) => "StructValue".to_string(),
-        _ => "unknown".to_string(),
+        Kind::NullValue(_s) => "NullValue".to_string(),
     };
     Err(Error::KindMismatch(field.name.to_string(), actual))
 }
@@ -319,19 +748,22 @@ pub fn kind_to_error<'a, T>(v: &'a value::Kind, field: &'a Field) -> Result<T, E
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::fmt::Debug;
     use std::ops::Add;
     use std::str::FromStr;
     use std::sync::Arc;
 
-    use prost_types::Value;
-    use time::OffsetDateTime;
+    use prost_types::value::Kind;
+    use prost_types::{ListValue, Value};
+    use time::{Date, OffsetDateTime};
 
     use google_cloud_googleapis::spanner::v1::struct_type::Field;
+    use google_cloud_googleapis::spanner::v1::{Type, TypeAnnotationCode, TypeCode};
 
     use crate::bigdecimal::{BigDecimal, FromPrimitive, ToPrimitive, Zero};
-    use crate::row::{Error, Row, Struct as RowStruct, TryFromStruct};
+    use crate::row::{Error, Row, Struct as RowStruct, TryFromStruct, TryFromValue};
     use crate::statement::{Kinds, ToKind, ToStruct, Types};
-    use crate::value::CommitTimestamp;
+    use crate::value::{CommitTimestamp, Interval, Value as SpannerValue};
 
     struct TestStruct {
         pub struct_field: String,
@@ -433,6 +865,7 @@ mod tests {
                     kind: Some(BigDecimal::from_f64(100.999999999999).unwrap().to_kind()),
                 },
             ],
+            case_insensitive: false,
         };
 
         let value = row.column_by_name::<String>("value").unwrap();
@@ -461,4 +894,534 @@ mod tests {
             BigDecimal::zero()
         );
     }
+
+    fn create_row() -> Row {
+        let mut index = HashMap::new();
+        index.insert("UserId".to_string(), 0);
+        index.insert("Level".to_string(), 1);
+        Row::new(
+            Arc::new(index),
+            Arc::new(vec![
+                Field {
+                    name: "UserId".to_string(),
+                    r#type: Some(String::get_type()),
+                },
+                Field {
+                    name: "Level".to_string(),
+                    r#type: Some(i64::get_type()),
+                },
+            ]),
+            vec![
+                Value {
+                    kind: Some("user1".to_kind()),
+                },
+                Value {
+                    kind: Some(10_i64.to_kind()),
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn test_column_error() {
+        let row = create_row();
+        match row.column_by_name::<bool>("Level").unwrap_err() {
+            Error::ColumnDecodeError(name, index, spanner_type, rust_type, source) => {
+                assert_eq!(name, "Level");
+                assert_eq!(index, 1);
+                assert_eq!(spanner_type, "INT64");
+                assert_eq!(rust_type, "bool");
+                assert!(matches!(*source, Error::KindMismatch(_, _)));
+            }
+            e => unreachable!("unexpected error {e:?}"),
+        }
+        let err = row.column_by_name::<bool>("Level").unwrap_err();
+        assert!(matches!(err.root_cause(), Error::KindMismatch(_, _)));
+        match row.column_by_name::<String>("userid").unwrap_err() {
+            Error::NoColumnFound(name, available) => {
+                assert_eq!(name, "userid");
+                assert_eq!(available, vec!["UserId".to_string(), "Level".to_string()]);
+            }
+            e => unreachable!("unexpected error {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let mut row = create_row();
+        assert!(row.column_by_name::<String>("userid").is_err());
+        row.set_case_insensitive(true);
+        assert_eq!(row.column_by_name::<String>("userid").unwrap(), "user1");
+        assert_eq!(row.column_by_name::<i64>("LEVEL").unwrap(), 10);
+        assert!(row.column_by_name::<String>("unknown").is_err());
+
+        let mut index = HashMap::new();
+        index.insert("UserId".to_string(), 0);
+        index.insert("userid".to_string(), 1);
+        let field = |name: &str| Field {
+            name: name.to_string(),
+            r#type: Some(String::get_type()),
+        };
+        let value = |v: &str| Value {
+            kind: Some(v.to_kind()),
+        };
+        let mut row = Row::new(
+            Arc::new(index),
+            Arc::new(vec![field("UserId"), field("userid")]),
+            vec![value("a"), value("b")],
+        );
+        row.set_case_insensitive(true);
+        assert_eq!(row.column_by_name::<String>("userid").unwrap(), "b");
+        match row.column_by_name::<String>("USERID").unwrap_err() {
+            Error::AmbiguousColumn(name, candidates) => {
+                assert_eq!(name, "USERID");
+                assert_eq!(candidates, vec!["UserId".to_string(), "userid".to_string()]);
+            }
+            e => unreachable!("unexpected error {e:?}"),
+        }
+        assert!(row.try_column_by_name::<String>("USERID").is_err());
+    }
+
+    #[test]
+    fn test_try_column() {
+        let row = create_row();
+        assert_eq!(row.try_column::<String>(0).unwrap(), Some("user1".to_string()));
+        assert_eq!(row.try_column::<String>(2).unwrap(), None);
+        assert_eq!(row.try_column_by_name::<i64>("Level").unwrap(), Some(10));
+        assert_eq!(row.try_column_by_name::<i64>("Unknown").unwrap(), None);
+        assert!(row.try_column_by_name::<bool>("Level").is_err());
+    }
+
+    #[test]
+    fn test_debug() {
+        let row = create_row();
+        assert_eq!(format!("{row:?}"), r#"Row { UserId: STRING "user1", Level: INT64 10 }"#);
+        assert_eq!(
+            format!("{:?}", row.redacted()),
+            r#"RedactedRow { columns: [RedactedField { name: "UserId", type_name: "STRING" }, RedactedField { name: "Level", type_name: "INT64" }] }"#
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_redacted_serialize() {
+        let row = create_row();
+        assert_eq!(
+            serde_json::to_string(&row.redacted()).unwrap(),
+            r#"{"columns":[{"name":"UserId","type":"STRING"},{"name":"Level","type":"INT64"}]}"#
+        );
+    }
+
+    fn null_value() -> Value {
+        Value {
+            kind: Some(Kind::NullValue(prost_types::NullValue::NullValue.into())),
+        }
+    }
+
+    fn assert_nullable<T>(non_null: Value, tp: Type)
+    where
+        T: TryFromValue + PartialEq + Debug,
+    {
+        let array_type = Type {
+            code: TypeCode::Array.into(),
+            array_element_type: Some(Box::new(tp.clone())),
+            struct_type: None,
+            type_annotation: TypeAnnotationCode::Unspecified.into(),
+        };
+        let field = |name: &str, tp: &Type| Field {
+            name: name.to_string(),
+            r#type: Some(tp.clone()),
+        };
+        let row = Row::new(
+            Arc::new(HashMap::new()),
+            Arc::new(vec![
+                field("scalar", &tp),
+                field("null_scalar", &tp),
+                field("null_array", &array_type),
+                field("array_with_null", &array_type),
+            ]),
+            vec![
+                non_null.clone(),
+                null_value(),
+                null_value(),
+                Value {
+                    kind: Some(Kind::ListValue(ListValue {
+                        values: vec![non_null, null_value()],
+                    })),
+                },
+            ],
+        );
+        let expected = row.column::<T>(0).unwrap();
+        assert_eq!(row.column::<Option<T>>(0).unwrap(), Some(expected));
+        let expected = row.column::<T>(0).unwrap();
+
+        // NULL scalar
+        assert_eq!(row.column::<Option<T>>(1).unwrap(), None);
+        assert!(row.column::<T>(1).is_err());
+
+        // NULL array
+        assert_eq!(row.column::<Option<Vec<T>>>(2).unwrap(), None);
+        assert_eq!(row.column::<Option<Vec<Option<T>>>>(2).unwrap(), None);
+        assert!(row.column::<Vec<T>>(2).is_err());
+
+        // array with NULL element
+        assert_eq!(row.column::<Vec<Option<T>>>(3).unwrap(), vec![Some(expected), None]);
+        assert!(row.column::<Vec<T>>(3).is_err());
+
+        // missing kind is treated as NULL
+        let row = Row::new(
+            Arc::new(HashMap::new()),
+            Arc::new(vec![field("no_kind", &tp)]),
+            vec![Value { kind: None }],
+        );
+        assert_eq!(row.column::<Option<T>>(0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_nullable() {
+        let now = OffsetDateTime::now_utc();
+        assert_nullable::<i64>(
+            Value {
+                kind: Some(1_i64.to_kind()),
+            },
+            i64::get_type(),
+        );
+        assert_nullable::<f64>(
+            Value {
+                kind: Some(1.5.to_kind()),
+            },
+            f64::get_type(),
+        );
+        assert_nullable::<bool>(
+            Value {
+                kind: Some(true.to_kind()),
+            },
+            bool::get_type(),
+        );
+        assert_nullable::<String>(
+            Value {
+                kind: Some("a".to_kind()),
+            },
+            String::get_type(),
+        );
+        assert_nullable::<Vec<u8>>(
+            Value {
+                kind: Some(vec![1_u8, 2].to_kind()),
+            },
+            Vec::<u8>::get_type(),
+        );
+        assert_nullable::<OffsetDateTime>(
+            Value {
+                kind: Some(now.to_kind()),
+            },
+            OffsetDateTime::get_type(),
+        );
+        assert_nullable::<CommitTimestamp>(
+            Value {
+                kind: Some(now.to_kind()),
+            },
+            CommitTimestamp::get_type(),
+        );
+        assert_nullable::<Date>(
+            Value {
+                kind: Some(now.date().to_kind()),
+            },
+            Date::get_type(),
+        );
+        assert_nullable::<BigDecimal>(
+            Value {
+                kind: Some(BigDecimal::from_str("1.1").unwrap().to_kind()),
+            },
+            BigDecimal::get_type(),
+        );
+        assert_nullable::<Interval>(
+            Value {
+                kind: Some(Interval::new(14, 3, 1_500_000_000).to_kind()),
+            },
+            Interval::get_type(),
+        );
+    }
+
+    #[test]
+    fn test_nullable_struct() {
+        let now = OffsetDateTime::now_utc();
+        let data = TestStruct {
+            struct_field: "aaa".to_string(),
+            struct_field_time: now,
+            commit_timestamp: CommitTimestamp { timestamp: now },
+            big_decimal: BigDecimal::zero(),
+        };
+        let row = Row::new(
+            Arc::new(HashMap::new()),
+            Arc::new(vec![
+                Field {
+                    name: "struct".to_string(),
+                    r#type: Some(TestStruct::get_type()),
+                },
+                Field {
+                    name: "null_struct".to_string(),
+                    r#type: Some(TestStruct::get_type()),
+                },
+                Field {
+                    name: "array_with_null".to_string(),
+                    r#type: Some(Vec::<TestStruct>::get_type()),
+                },
+            ]),
+            vec![
+                Value {
+                    kind: Some(data.to_kind()),
+                },
+                null_value(),
+                Value {
+                    kind: Some(Kind::ListValue(ListValue {
+                        values: vec![
+                            Value {
+                                kind: Some(data.to_kind()),
+                            },
+                            null_value(),
+                        ],
+                    })),
+                },
+            ],
+        );
+        assert_eq!(row.column::<TestStruct>(0).unwrap().struct_field, "aaa");
+        assert!(row.column::<Option<TestStruct>>(1).unwrap().is_none());
+        assert!(row.column::<TestStruct>(1).is_err());
+        let array = row.column::<Vec<Option<TestStruct>>>(2).unwrap();
+        assert_eq!(array[0].as_ref().unwrap().struct_field, "aaa");
+        assert!(array[1].is_none());
+    }
+
+    struct Outer {
+        name: String,
+        inner: TestStruct,
+        inners: Vec<TestStruct>,
+        nullable: Option<TestStruct>,
+    }
+
+    impl TryFromStruct for Outer {
+        fn try_from_struct(s: RowStruct<'_>) -> Result<Self, Error> {
+            Ok(Outer {
+                name: s.column_by_name("name")?,
+                inner: s.column_by_name("inner")?,
+                inners: s.column_by_name("inners")?,
+                nullable: s.column_by_name("nullable")?,
+            })
+        }
+    }
+
+    impl ToStruct for Outer {
+        fn to_kinds(&self) -> Kinds {
+            vec![
+                ("name", self.name.to_kind()),
+                ("inner", self.inner.to_kind()),
+                ("inners", self.inners.to_kind()),
+                ("nullable", self.nullable.to_kind()),
+            ]
+        }
+
+        fn get_types() -> Types {
+            vec![
+                ("name", String::get_type()),
+                ("inner", TestStruct::get_type()),
+                ("inners", Vec::<TestStruct>::get_type()),
+                ("nullable", Option::<TestStruct>::get_type()),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_nested_struct() {
+        let now = OffsetDateTime::now_utc();
+        let inner = |name: &str| TestStruct {
+            struct_field: name.to_string(),
+            struct_field_time: now,
+            commit_timestamp: CommitTimestamp { timestamp: now },
+            big_decimal: BigDecimal::zero(),
+        };
+        let outer = Outer {
+            name: "outer".to_string(),
+            inner: inner("inner"),
+            inners: vec![inner("a"), inner("b")],
+            nullable: None,
+        };
+        let row = Row::new(
+            Arc::new(HashMap::new()),
+            Arc::new(vec![Field {
+                name: "outers".to_string(),
+                r#type: Some(Vec::<Outer>::get_type()),
+            }]),
+            vec![Value {
+                kind: Some(vec![outer].to_kind()),
+            }],
+        );
+
+        // typed
+        let outers = row.column::<Vec<Outer>>(0).unwrap();
+        assert_eq!(outers[0].name, "outer");
+        assert_eq!(outers[0].inner.struct_field, "inner");
+        assert_eq!(outers[0].inners[1].struct_field, "b");
+        assert!(outers[0].nullable.is_none());
+
+        // dynamic
+        let value = row.column::<SpannerValue>(0).unwrap();
+        let outer = match &value {
+            SpannerValue::Array(values) => &values[0],
+            v => panic!("unexpected value {v:?}"),
+        };
+        assert_eq!(outer.field("name"), Some(&SpannerValue::String("outer".to_string())));
+        assert_eq!(
+            outer.field("inner").and_then(|v| v.field("struct_field")),
+            Some(&SpannerValue::String("inner".to_string()))
+        );
+        assert!(outer.field("nullable").unwrap().is_null());
+        assert!(outer.field("unknown").is_none());
+
+        // field metadata and mismatch
+        struct Check;
+        impl TryFromStruct for Check {
+            fn try_from_struct(s: RowStruct<'_>) -> Result<Self, Error> {
+                assert_eq!(s.len(), 4);
+                assert_eq!(s.fields()[1].name, "inner");
+                let values = s.values()?;
+                assert_eq!(values[2].0.type_name(), "ARRAY<STRUCT>");
+                assert!(values[3].1.is_null());
+                assert!(s.try_column_by_name::<String>("unknown")?.is_none());
+                match s.column_by_name::<i64>("name").unwrap_err() {
+                    Error::ColumnDecodeError(name, index, spanner_type, _, _) => {
+                        assert_eq!(name, "name");
+                        assert_eq!(index, 0);
+                        assert_eq!(spanner_type, "STRING");
+                    }
+                    e => unreachable!("unexpected error {e:?}"),
+                }
+                assert!(matches!(
+                    s.column::<String>(10).unwrap_err(),
+                    Error::InvalidStructColumnIndex(10)
+                ));
+                Ok(Check)
+            }
+        }
+        assert_eq!(row.column::<Vec<Check>>(0).unwrap().len(), 1);
+        assert!(row.column::<Vec<String>>(0).is_err());
+    }
+
+    #[test]
+    fn test_float_special_values() {
+        let row = Row::new(
+            Arc::new(HashMap::new()),
+            Arc::new(vec![Field {
+                name: "nan".to_string(),
+                r#type: Some(f64::get_type()),
+            }]),
+            vec![Value {
+                kind: Some("NaN".to_kind()),
+            }],
+        );
+        assert!(row.column::<f64>(0).unwrap().is_nan());
+    }
+
+    #[test]
+    fn test_dynamic_values() {
+        let date = Date::from_calendar_date(2024, time::Month::January, 2).unwrap();
+        let data = TestStruct {
+            struct_field: "aaa".to_string(),
+            struct_field_time: OffsetDateTime::UNIX_EPOCH,
+            commit_timestamp: CommitTimestamp {
+                timestamp: OffsetDateTime::UNIX_EPOCH,
+            },
+            big_decimal: BigDecimal::from_i64(10).unwrap(),
+        };
+        let field = |name: &str, tp: Type| Field {
+            name: name.to_string(),
+            r#type: Some(tp),
+        };
+        let row = Row::new(
+            Arc::new(HashMap::new()),
+            Arc::new(vec![
+                field("id", i64::get_type()),
+                field("name", String::get_type()),
+                field("tags", Vec::<String>::get_type()),
+                field("born", Date::get_type()),
+                field("data", TestStruct::get_type()),
+                field("empty", Option::<f64>::get_type()),
+                field("bin", Vec::<u8>::get_type()),
+            ]),
+            vec![
+                Value {
+                    kind: Some(1_i64.to_kind()),
+                },
+                Value {
+                    kind: Some("name".to_kind()),
+                },
+                Value {
+                    kind: Some(vec!["a".to_string(), "b".to_string()].to_kind()),
+                },
+                Value {
+                    kind: Some(date.to_kind()),
+                },
+                Value {
+                    kind: Some(data.to_kind()),
+                },
+                null_value(),
+                Value {
+                    kind: Some(vec![1_u8, 2].to_kind()),
+                },
+            ],
+        );
+
+        let map = row.try_to_map().unwrap();
+        assert_eq!(
+            map.keys().collect::<Vec<_>>(),
+            vec!["id", "name", "tags", "born", "data", "empty", "bin"]
+        );
+        assert_eq!(map["id"], SpannerValue::Int64(1));
+        assert_eq!(map["name"], SpannerValue::String("name".to_string()));
+        assert_eq!(
+            map["tags"],
+            SpannerValue::Array(vec![
+                SpannerValue::String("a".to_string()),
+                SpannerValue::String("b".to_string())
+            ])
+        );
+        assert_eq!(map["born"], SpannerValue::Date(date));
+        assert!(map["empty"].is_null());
+        assert_eq!(map["bin"], SpannerValue::Bytes(vec![1, 2]));
+        match &map["data"] {
+            SpannerValue::Struct(fields) => {
+                assert_eq!(fields[0], ("struct_field".to_string(), SpannerValue::String("aaa".to_string())));
+                assert_eq!(fields[3].1, SpannerValue::Numeric(BigDecimal::from_i64(10).unwrap()));
+            }
+            v => panic!("unexpected value {v:?}"),
+        }
+
+        assert_eq!(map["tags"].to_string(), r#"["a", "b"]"#);
+        assert_eq!(map["born"].to_string(), "2024-01-02");
+        assert_eq!(map["empty"].to_string(), "NULL");
+        assert_eq!(map["bin"].to_string(), "AQI=");
+
+        let values = row.values().unwrap();
+        assert_eq!(values.len(), 7);
+        assert_eq!(values[2].0.name, "tags");
+        assert_eq!(values[2].0.index, 2);
+        assert_eq!(values[2].0.type_name(), "ARRAY<STRING>");
+        assert_eq!(values[4].0.type_name(), "STRUCT");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dynamic_values_serialize() {
+        let value = SpannerValue::Struct(vec![
+            ("id".to_string(), SpannerValue::Int64(1)),
+            ("json".to_string(), SpannerValue::Json(r#"{"a":1}"#.to_string())),
+            (
+                "list".to_string(),
+                SpannerValue::Array(vec![SpannerValue::Bool(true), SpannerValue::Null]),
+            ),
+        ]);
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"id":1,"json":{"a":1},"list":[true,null]}"#
+        );
+    }
 }
@@ -13,6 +13,7 @@ use tokio::task::JoinHandle;
 use tokio::time::{sleep, timeout};
 use tokio_util::sync::CancellationToken;
 
+use google_cloud_gax::conn::Error as ConnError;
 use google_cloud_gax::grpc::{Code, Status};
 use google_cloud_gax::retry::TryAs;
 use google_cloud_googleapis::spanner::v1::{BatchCreateSessionsRequest, DeleteSessionRequest, Session};
@@ -24,6 +25,10 @@ use crate::apiv1::spanner_client::{ping_query_request, Client};
 pub struct SessionHandle {
     pub session: Session,
     pub spanner_client: Client,
+    /// The index of the gRPC channel the session was created on.
+    channel_index: usize,
+    /// The generation of the channel the spanner_client uses. See `ConnectionManager::generation`.
+    channel_generation: usize,
     valid: bool,
     deleted: bool,
     last_used_at: Instant,
@@ -37,6 +42,8 @@ impl SessionHandle {
         SessionHandle {
             session,
             spanner_client,
+            channel_index: 0,
+            channel_generation: 0,
             valid: true,
             deleted: false,
             last_used_at: now,
@@ -46,6 +53,29 @@ impl SessionHandle {
         }
     }
 
+    /// detach copies the session for one stream of the shared read-only transaction.
+    /// The state changed by the stream is reflected to the original session with `merge`.
+    fn detach(&self) -> SessionHandle {
+        SessionHandle {
+            session: self.session.clone(),
+            spanner_client: self.spanner_client.clone(),
+            channel_index: self.channel_index,
+            channel_generation: self.channel_generation,
+            valid: self.valid,
+            deleted: self.deleted,
+            last_used_at: self.last_used_at,
+            last_checked_at: self.last_checked_at,
+            last_pong_at: self.last_pong_at,
+            created_at: self.created_at,
+        }
+    }
+
+    /// merge invalidates the session if the detached session is invalidated.
+    fn merge(&mut self, detached: &SessionHandle) {
+        self.valid &= detached.valid;
+        self.deleted |= detached.deleted;
+    }
+
     pub async fn invalidate_if_needed<T>(&mut self, arg: Result<T, Status>) -> Result<T, Status> {
         match arg {
             Ok(s) => Ok(s),
@@ -108,6 +138,65 @@ impl DerefMut for ManagedSession {
     }
 }
 
+/// SharedSession is the session used by the concurrent streams of the shared read-only transaction.
+/// The session is returned to the pool when all the clones and the detached sessions are dropped.
+#[derive(Clone)]
+pub(crate) struct SharedSession {
+    inner: Arc<Mutex<ManagedSession>>,
+    name: Arc<str>,
+}
+
+impl SharedSession {
+    pub(crate) fn new(session: ManagedSession) -> Self {
+        let name = session.deref().session.name.as_str().into();
+        SharedSession {
+            inner: Arc::new(Mutex::new(session)),
+            name,
+        }
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// detach returns the copy of the session owned by one stream.
+    /// The lock is held only while copying, so the streams don't block each other.
+    pub(crate) fn detach(&self) -> DetachedSession {
+        let handle = self.inner.lock().detach();
+        DetachedSession {
+            handle,
+            parent: self.clone(),
+        }
+    }
+}
+
+/// DetachedSession is the copy of the shared session owned by one stream.
+/// The invalidation of the session by the stream is reflected to the shared session on drop.
+pub(crate) struct DetachedSession {
+    handle: SessionHandle,
+    parent: SharedSession,
+}
+
+impl Deref for DetachedSession {
+    type Target = SessionHandle;
+
+    fn deref(&self) -> &Self::Target {
+        &self.handle
+    }
+}
+
+impl DerefMut for DetachedSession {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.handle
+    }
+}
+
+impl Drop for DetachedSession {
+    fn drop(&mut self) {
+        self.parent.inner.lock().merge(&self.handle);
+    }
+}
+
 /// Sessions have all sessions and waiters.
 /// This is for atomically locking the waiting list and free sessions.
 struct Sessions {
@@ -123,6 +212,9 @@ struct Sessions {
 
     /// number of sessions scheduled to be replenished.
     num_creating: usize,
+
+    /// number of opened sessions of each gRPC channel.
+    channel_sessions: Vec<usize>,
 }
 
 impl Sessions {
@@ -154,12 +246,24 @@ impl Sessions {
         self.num_inuse -= 1;
         if session.valid {
             self.available_sessions.push_back(session);
-        } else if !session.deleted {
+            return;
+        }
+        if let Some(count) = self.channel_sessions.get_mut(session.channel_index) {
+            *count = count.saturating_sub(1);
+        }
+        if !session.deleted {
             tracing::trace!("save as orphan name={}", session.session.name);
             self.orphans.push(session);
         }
     }
 
+    fn add(&mut self, session: SessionHandle) {
+        if let Some(count) = self.channel_sessions.get_mut(session.channel_index) {
+            *count += 1;
+        }
+        self.available_sessions.push_back(session);
+    }
+
     /// reserve calculates next session count to create.
     /// Must call replenish after calling this method.
     fn reserve(&mut self, max_opened: usize, inc_step: usize) -> usize {
@@ -187,7 +291,7 @@ impl Sessions {
         match result {
             Ok(mut new_sessions) => {
                 while let Some(session) = new_sessions.pop() {
-                    self.available_sessions.push_back(session);
+                    self.add(session);
                     if let Some(waiter) = self.take_waiter() {
                         let _ = waiter.send(());
                     }
@@ -203,52 +307,55 @@ struct SessionPool {
     inner: Arc<RwLock<Sessions>>,
     session_creation_sender: UnboundedSender<usize>,
     config: Arc<SessionConfig>,
+    conn_pool: Arc<ConnectionManager>,
 }
 
 impl SessionPool {
     async fn new(
         database: String,
-        conn_pool: &ConnectionManager,
+        conn_pool: Arc<ConnectionManager>,
         session_creation_sender: UnboundedSender<usize>,
         config: Arc<SessionConfig>,
     ) -> Result<Self, Status> {
-        let available_sessions = Self::init_pool(database, conn_pool, config.min_opened).await?;
+        let channel_sessions = vec![0; conn_pool.num()];
+        let available_sessions =
+            create_sessions(&conn_pool, database.as_str(), config.min_opened, &channel_sessions).await?;
+        tracing::debug!("initial session created count = {}", available_sessions.len());
+        let mut sessions = Sessions {
+            available_sessions: VecDeque::with_capacity(available_sessions.len()),
+            waiters: VecDeque::new(),
+            orphans: Vec::new(),
+            num_inuse: 0,
+            num_creating: 0,
+            channel_sessions,
+        };
+        for session in available_sessions {
+            sessions.add(session);
+        }
         Ok(SessionPool {
-            inner: Arc::new(RwLock::new(Sessions {
-                available_sessions,
-                waiters: VecDeque::new(),
-                orphans: Vec::new(),
-                num_inuse: 0,
-                num_creating: 0,
-            })),
+            inner: Arc::new(RwLock::new(sessions)),
             session_creation_sender,
             config,
+            conn_pool,
         })
     }
 
-    async fn init_pool(
-        database: String,
-        conn_pool: &ConnectionManager,
-        min_opened: usize,
-    ) -> Result<VecDeque<SessionHandle>, Status> {
-        let channel_num = conn_pool.num();
-        let creation_count_per_channel = min_opened / channel_num;
-
-        let mut sessions = Vec::<SessionHandle>::new();
-        for _ in 0..channel_num {
-            let next_client = conn_pool.conn();
-            let new_sessions =
-                batch_create_sessions(next_client, database.as_str(), creation_count_per_channel).await?;
-            sessions.extend(new_sessions);
-        }
-        tracing::debug!("initial session created count = {}", sessions.len());
-        Ok(sessions.into())
-    }
-
     fn num_opened(&self) -> usize {
         self.inner.read().num_opened()
     }
 
+    fn stats(&self) -> SessionPoolStats {
+        let sessions = self.inner.read();
+        SessionPoolStats {
+            num_opened: sessions.num_opened(),
+            num_inuse: sessions.num_inuse,
+            num_available: sessions.available_sessions.len(),
+            num_creating: sessions.num_creating,
+            num_waiters: sessions.waiters.len(),
+            sessions_per_channel: sessions.channel_sessions.clone(),
+        }
+    }
+
     /// The client first checks the waiting list.
     /// If the waiting list is empty, it retrieves the first available session.
     /// If there are no available sessions, it enters the waiting list.
@@ -264,6 +371,7 @@ impl SessionPool {
                 if sessions.waiters.is_empty() {
                     if let Some(mut s) = sessions.take() {
                         s.last_used_at = Instant::now();
+                        self.rebind(&mut s);
                         return Ok(ManagedSession::new(self.clone(), s));
                     }
                 }
@@ -284,6 +392,7 @@ impl SessionPool {
                     let mut sessions = self.inner.write();
                     if let Some(mut s) = sessions.take() {
                         s.last_used_at = Instant::now();
+                        self.rebind(&mut s);
                         return Ok(ManagedSession::new(self.clone(), s));
                     } else {
                         continue; // another waiter raced for session
@@ -344,6 +453,29 @@ impl SessionPool {
         }
     }
 
+    /// rebind moves the session created on the replaced channel to the new channel.
+    /// Spanner doesn't bind the session to the channel, so the session is reused without recreating it,
+    /// and the number of the sessions of the channel is kept.
+    fn rebind(&self, session: &mut SessionHandle) {
+        let generation = self.conn_pool.generation(session.channel_index);
+        if session.channel_generation != generation {
+            tracing::debug!("rebind session {} to the replaced channel", session.session.name);
+            session.spanner_client = self.conn_pool.conn_at(session.channel_index);
+            session.channel_generation = generation;
+        }
+    }
+
+    /// replace_channel replaces the channel of the session after the transport failure.
+    /// The other sessions of the channel are moved to the new channel when they are acquired.
+    async fn replace_channel(&self, session: &mut SessionHandle) -> Result<(), ConnError> {
+        // the channel might be already replaced by the failure of the other session.
+        if session.channel_generation == self.conn_pool.generation(session.channel_index) {
+            self.conn_pool.replace(session.channel_index).await?;
+        }
+        self.rebind(session);
+        Ok(())
+    }
+
     async fn close(&self) {
         let empty = VecDeque::new();
         let deleting_sessions = { mem::replace(&mut self.inner.write().available_sessions, empty) };
@@ -426,6 +558,21 @@ pub enum SessionError {
     GRPC(#[from] Status),
 }
 
+/// SessionPoolStats is the snapshot of the session pool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionPoolStats {
+    /// The number of the sessions in use and available.
+    pub num_opened: usize,
+    pub num_inuse: usize,
+    pub num_available: usize,
+    /// The number of the sessions being created.
+    pub num_creating: usize,
+    /// The number of the callers waiting for a session.
+    pub num_waiters: usize,
+    /// The number of the opened sessions of each gRPC channel.
+    pub sessions_per_channel: Vec<usize>,
+}
+
 impl TryAs<Status> for SessionError {
     fn try_as(&self) -> Option<&Status> {
         match self {
@@ -449,7 +596,9 @@ impl SessionManager {
     ) -> Result<Arc<SessionManager>, Status> {
         let database = database.into();
         let (sender, receiver) = mpsc::unbounded_channel();
-        let session_pool = SessionPool::new(database.clone(), &conn_pool, sender, Arc::new(config.clone())).await?;
+        let conn_pool = Arc::new(conn_pool);
+        let session_pool =
+            SessionPool::new(database.clone(), conn_pool.clone(), sender, Arc::new(config.clone())).await?;
 
         let cancel = CancellationToken::new();
         let task_session_cleaner = Self::spawn_health_check_task(config, session_pool.clone(), cancel.clone());
@@ -468,6 +617,10 @@ impl SessionManager {
         self.session_pool.num_opened()
     }
 
+    pub fn stats(&self) -> SessionPoolStats {
+        self.session_pool.stats()
+    }
+
     pub async fn get(&self) -> Result<ManagedSession, SessionError> {
         self.session_pool.acquire().await
     }
@@ -487,7 +640,7 @@ impl SessionManager {
     fn spawn_session_creation_task(
         session_pool: SessionPool,
         database: String,
-        conn_pool: ConnectionManager,
+        conn_pool: Arc<ConnectionManager>,
         mut rx: UnboundedReceiver<usize>,
         cancel: CancellationToken,
     ) -> JoinHandle<()> {
@@ -500,7 +653,8 @@ impl SessionManager {
                     },
                     _ = cancel.cancelled() => break
                 };
-                let result = batch_create_sessions(conn_pool.conn(), database.as_str(), session_count).await;
+                let channel_sessions = session_pool.inner.read().channel_sessions.clone();
+                let result = create_sessions(&conn_pool, database.as_str(), session_count, &channel_sessions).await;
                 session_pool.inner.write().replenish(session_count, result);
             }
             tracing::trace!("shutdown session creation task.");
@@ -574,6 +728,7 @@ async fn health_check(
                 None => break,
             }
         };
+        sessions.rebind(&mut s);
 
         let request = ping_query_request(s.session.name.clone());
         match s.spanner_client.execute_sql(request, None).await {
@@ -582,6 +737,15 @@ async fn health_check(
                 s.last_pong_at = now;
                 sessions.recycle(s);
             }
+            // the transport failure breaks the channel, not the session.
+            // The session is pinged again on the new channel, or kept on the old one until the next health check.
+            Err(e) if e.code() == Code::Unavailable => {
+                if let Err(e) = sessions.replace_channel(&mut s).await {
+                    tracing::error!("failed to replace the channel {}: {:?}", s.channel_index, e);
+                    s.last_checked_at = now;
+                }
+                sessions.recycle(s);
+            }
             Err(_) => {
                 s.delete().await;
                 sessions.recycle(s);
@@ -591,14 +755,89 @@ async fn health_check(
     tracing::trace!("end health check elapsed={}msec", start.elapsed().as_millis());
 }
 
+/// sessions_per_channel distributes the sessions to the gRPC channels with fewer opened sessions first
+/// so that the number of the sessions of each channel becomes even.
+fn sessions_per_channel(session_count: usize, channel_sessions: &[usize]) -> Vec<usize> {
+    if channel_sessions.is_empty() {
+        return vec![session_count];
+    }
+    let mut allocated = vec![0; channel_sessions.len()];
+    for _ in 0..session_count {
+        let (index, _) = channel_sessions
+            .iter()
+            .zip(&allocated)
+            .map(|(opened, allocated)| opened + allocated)
+            .enumerate()
+            .min_by_key(|(_, count)| *count)
+            .unwrap();
+        allocated[index] += 1;
+    }
+    allocated
+}
+
+/// create_sessions creates the sessions distributed across the gRPC channels so that the streams spread evenly.
+/// The sessions a channel failed to create, for example because of the transport failure, are created on the next channel,
+/// and the shortfall of the last channel wraps around to the first channels, so each channel is tried at most twice.
+/// If the sessions still can't be created, the created sessions are deleted and the last error is returned.
+async fn create_sessions(
+    conn_pool: &ConnectionManager,
+    database: &str,
+    session_count: usize,
+    channel_sessions: &[usize],
+) -> Result<Vec<SessionHandle>, Status> {
+    let allocated = sessions_per_channel(session_count, channel_sessions);
+    let channel_num = allocated.len();
+    let mut sessions = Vec::with_capacity(session_count);
+    let mut last_error = None;
+    let mut failed_count = 0;
+    for (i, channel_index) in (0..channel_num).chain(0..channel_num).enumerate() {
+        let count = failed_count + if i < channel_num { allocated[channel_index] } else { 0 };
+        if count == 0 {
+            continue;
+        }
+        let generation = conn_pool.generation(channel_index);
+        match batch_create_sessions(conn_pool.conn_at(channel_index), database, channel_index, count).await {
+            Ok(new_sessions) => {
+                failed_count = 0;
+                sessions.extend(new_sessions.into_iter().map(|mut s| {
+                    s.channel_generation = generation;
+                    s
+                }));
+            }
+            Err(e) => {
+                tracing::warn!("failed to create {} sessions on the channel: {:?}", count, e);
+                // replace the broken channel so that the shortfall wrapping around is created on the new channel.
+                if e.code() == Code::Unavailable && generation == conn_pool.generation(channel_index) {
+                    if let Err(e) = conn_pool.replace(channel_index).await {
+                        tracing::error!("failed to replace the channel {}: {:?}", channel_index, e);
+                    }
+                }
+                failed_count = count;
+                last_error = Some(e);
+            }
+        }
+    }
+    match last_error {
+        Some(e) if failed_count > 0 => {
+            for mut session in sessions {
+                session.delete().await;
+            }
+            Err(e)
+        }
+        _ => Ok(sessions),
+    }
+}
+
 async fn batch_create_sessions(
     spanner_client: Client,
     database: &str,
+    channel_index: usize,
     mut remaining_create_count: usize,
 ) -> Result<Vec<SessionHandle>, Status> {
     let mut created = Vec::with_capacity(remaining_create_count);
     while remaining_create_count > 0 {
-        let sessions = batch_create_session(spanner_client.clone(), database, remaining_create_count).await?;
+        let sessions =
+            batch_create_session(spanner_client.clone(), database, channel_index, remaining_create_count).await?;
         // Spanner could return less sessions than requested.
         // In that case, we should do another call using the same gRPC channel.
         let actually_created = sessions.len();
@@ -611,6 +850,7 @@ async fn batch_create_sessions(
 async fn batch_create_session(
     mut spanner_client: Client,
     database: &str,
+    channel_index: usize,
     session_count: usize,
 ) -> Result<Vec<SessionHandle>, Status> {
     let request = BatchCreateSessionsRequest {
@@ -626,7 +866,11 @@ async fn batch_create_session(
     Ok(response
         .session
         .into_iter()
-        .map(|s| SessionHandle::new(s, spanner_client.clone(), now))
+        .map(|s| {
+            let mut session = SessionHandle::new(s, spanner_client.clone(), now);
+            session.channel_index = channel_index;
+            session
+        })
         .collect::<Vec<SessionHandle>>())
 }
 
@@ -645,7 +889,10 @@ mod tests {
     use google_cloud_googleapis::spanner::v1::ExecuteSqlRequest;
 
     use crate::apiv1::conn_pool::ConnectionManager;
-    use crate::session::{batch_create_sessions, health_check, SessionConfig, SessionError, SessionManager};
+    use crate::session::{
+        batch_create_sessions, health_check, sessions_per_channel, SessionConfig, SessionError, SessionManager,
+        SharedSession,
+    };
 
     pub const DATABASE: &str = "projects/local-project/instances/test-instance/databases/local-database";
 
@@ -884,6 +1131,8 @@ mod tests {
                 "now is {available_sessions}"
             );
         }
+        let stats = sm.stats();
+        assert_eq!(stats.sessions_per_channel.iter().sum::<usize>(), stats.num_opened);
         sm.close().await;
     }
 
@@ -1079,7 +1328,7 @@ mod tests {
         .unwrap();
         let client = cm.conn();
         let session_count = 125;
-        let result = batch_create_sessions(client.clone(), DATABASE, session_count).await;
+        let result = batch_create_sessions(client.clone(), DATABASE, 0, session_count).await;
         match result {
             Ok(created) => {
                 assert_eq!(session_count, created.len());
@@ -1100,6 +1349,7 @@ mod tests {
                                 query_options: None,
                                 request_options: None,
                                 data_boost_enabled: false,
+                                last_statement: false,
                             },
                             None,
                         )
@@ -1110,4 +1360,184 @@ mod tests {
             Err(err) => panic!("{err:?}"),
         }
     }
+
+    #[test]
+    fn test_sessions_per_channel() {
+        assert_eq!(sessions_per_channel(16, &[0; 4]), vec![4, 4, 4, 4]);
+        assert_eq!(sessions_per_channel(10, &[0; 4]), vec![3, 3, 2, 2]);
+        assert_eq!(sessions_per_channel(1, &[0; 4]), vec![1, 0, 0, 0]);
+        assert_eq!(sessions_per_channel(5, &[0]), vec![5]);
+        assert_eq!(sessions_per_channel(5, &[]), vec![5]);
+        // the channel lost its sessions gets the new sessions first.
+        assert_eq!(sessions_per_channel(4, &[3, 0, 1, 3]), vec![0, 3, 1, 0]);
+        assert_eq!(sessions_per_channel(2, &[1, 5]), vec![2, 0]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[serial]
+    async fn test_shared_session_stress() {
+        let cm = ConnectionManager::new(
+            4,
+            &Environment::Emulator("localhost:9010".to_string()),
+            "",
+            &ConnectionOptions::default(),
+        )
+        .await
+        .unwrap();
+        let config = SessionConfig {
+            min_opened: 1,
+            max_opened: 1,
+            ..Default::default()
+        };
+        let sm = SessionManager::new(DATABASE, cm, config).await.unwrap();
+
+        for invalidate in [false, true] {
+            let shared = SharedSession::new(sm.get().await.unwrap());
+            let mut spawns = Vec::with_capacity(100);
+            for i in 0..100 {
+                let shared = shared.clone();
+                spawns.push(tokio::spawn(async move {
+                    let mut detached = shared.detach();
+                    assert_eq!(detached.session.name, shared.name());
+                    tokio::task::yield_now().await;
+                    if invalidate && i == 50 {
+                        detached.valid = false;
+                    }
+                }));
+            }
+            // the session is used until all the clones are dropped.
+            assert_eq!(sm.session_pool.inner.read().num_inuse, 1);
+            for handler in spawns {
+                handler.await.unwrap();
+            }
+            assert_eq!(shared.inner.lock().valid, !invalidate);
+            assert_eq!(sm.session_pool.inner.read().num_inuse, 1);
+            let name = shared.name().to_string();
+            drop(shared);
+
+            let sessions = sm.session_pool.inner.read();
+            assert_eq!(sessions.num_inuse, 0);
+            let reused = sessions.available_sessions.iter().any(|s| s.session.name == name);
+            assert_eq!(reused, !invalidate, "invalid session must not be reused");
+        }
+    }
+
+    /// The tests with the in-process mock, which can fail the requests unlike the emulator.
+    #[cfg(feature = "spanner-test")]
+    mod mock {
+        use google_cloud_gax::conn::{ConnectionOptions, Environment};
+        use google_cloud_gax::grpc::{Code, Status};
+
+        use crate::apiv1::conn_pool::ConnectionManager;
+        use crate::apiv1::spanner_client::ping_query_request;
+        use crate::session::{create_sessions, SessionConfig, SessionHandle, SessionManager};
+        use crate::spanner_test::{Method, MockServer, MockSpanner, DATABASE};
+
+        async fn conn_pool(server: &MockServer) -> ConnectionManager {
+            let environment = Environment::Emulator(server.addr().to_string());
+            ConnectionManager::new(1, &environment, "", &ConnectionOptions::default())
+                .await
+                .unwrap()
+        }
+
+        #[tokio::test]
+        async fn test_create_sessions_retry_shortfall() {
+            let mock = MockSpanner::new();
+            let server = mock.start().await.unwrap();
+            let cm = conn_pool(&server).await;
+
+            // the shortfall of the last channel is created on the first channel.
+            mock.inject_error(Method::BatchCreateSessions, Status::permission_denied("injected"));
+            mock.inject_error(Method::BatchCreateSessions, Status::permission_denied("injected"));
+            let sessions = create_sessions(&cm, DATABASE, 4, &[0, 0]).await.unwrap();
+            assert_eq!(sessions.len(), 4);
+            assert!(sessions.iter().all(|s| s.channel_index == 0));
+            assert_eq!(mock.call_count(Method::BatchCreateSessions), 3);
+        }
+
+        #[tokio::test]
+        async fn test_create_sessions_error() {
+            let mock = MockSpanner::new();
+            let server = mock.start().await.unwrap();
+            let cm = conn_pool(&server).await;
+
+            // each channel is tried twice.
+            for _ in 0..4 {
+                mock.inject_error(Method::BatchCreateSessions, Status::permission_denied("injected"));
+            }
+            match create_sessions(&cm, DATABASE, 4, &[0, 0]).await {
+                Ok(sessions) => unreachable!("must fail: created {}", sessions.len()),
+                Err(status) => assert_eq!(status.code(), Code::PermissionDenied),
+            }
+            assert_eq!(mock.call_count(Method::BatchCreateSessions), 4);
+        }
+
+        #[tokio::test]
+        async fn test_replace_channel() {
+            let mock = MockSpanner::new();
+            let server = mock.start().await.unwrap();
+            let config = SessionConfig {
+                min_opened: 2,
+                max_opened: 2,
+                ..Default::default()
+            };
+            let sm = SessionManager::new(DATABASE, conn_pool(&server).await, config)
+                .await
+                .unwrap();
+            let conn_pool = sm.session_pool.conn_pool.clone();
+
+            let mut session = sm.get().await.unwrap();
+            assert_eq!(session.channel_generation, 0);
+            sm.session_pool.replace_channel(&mut session).await.unwrap();
+            assert_eq!(conn_pool.generation(0), 1);
+            assert_eq!(session.channel_generation, 1);
+
+            // the replaced channel is not replaced again by the other sessions created on the old channel.
+            let mut other = sm.session_pool.inner.write().take().unwrap();
+            assert_eq!(other.channel_generation, 0);
+            sm.session_pool.replace_channel(&mut other).await.unwrap();
+            assert_eq!(conn_pool.generation(0), 1);
+            assert_eq!(other.channel_generation, 1);
+            sm.session_pool.recycle(other);
+            drop(session);
+
+            // the sessions are moved to the new channel and the pool doesn't shrink.
+            let mut sessions = vec![sm.get().await.unwrap(), sm.get().await.unwrap()];
+            for session in sessions.iter_mut() {
+                let session: &mut SessionHandle = session;
+                assert_eq!(session.channel_generation, 1);
+                let request = ping_query_request(session.session.name.clone());
+                session.spanner_client.execute_sql(request, None).await.unwrap();
+            }
+            let stats = sm.stats();
+            assert_eq!(stats.num_opened, 2);
+            assert_eq!(stats.sessions_per_channel, vec![2]);
+            assert_eq!(mock.call_count(Method::BatchCreateSessions), 1);
+            drop(sessions);
+            sm.close().await;
+        }
+
+        #[tokio::test]
+        async fn test_rebind_on_acquire() {
+            let mock = MockSpanner::new();
+            let server = mock.start().await.unwrap();
+            let config = SessionConfig {
+                min_opened: 3,
+                max_opened: 3,
+                ..Default::default()
+            };
+            let sm = SessionManager::new(DATABASE, conn_pool(&server).await, config)
+                .await
+                .unwrap();
+            sm.session_pool.conn_pool.replace(0).await.unwrap();
+
+            for _ in 0..3 {
+                let session = sm.get().await.unwrap();
+                assert_eq!(session.channel_generation, 1);
+                assert_eq!(session.channel_index, 0);
+            }
+            assert_eq!(sm.stats().sessions_per_channel, vec![3]);
+            sm.close().await;
+        }
+    }
 }
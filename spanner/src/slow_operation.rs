@@ -0,0 +1,205 @@
+//! Slow operation hook of the data client.
+//!
+//! The callback set by `ClientConfig::on_slow_operation` is invoked when an `ExecuteSql`, `ExecuteStreamingSql`,
+//! `ExecuteBatchDml`, `Read`, `StreamingRead` or `Commit` takes longer than the threshold.
+//! The elapsed time includes all the retries of the operation.
+//! The streaming methods are measured by the `RowIterator` until the stream is completed or fails,
+//! so the time to read the rows and to resume the stream is included. The iterator dropped before the end is not reported.
+//!
+//! The callback set by `AdminClientConfig::on_slow_operation` is invoked when waiting for the long-running operation
+//! of the admin clients like `create_database` with `Operation::wait` or `.await` takes longer than the threshold.
+//!
+//! The callback is invoked on a background task through a channel, so the slow callback never delays the operations.
+//! The statement is recorded according to `TraceConfig::redaction`, so no SQL is recorded by default.
+//!
+//! ```
+//! use std::time::Duration;
+//! use google_cloud_spanner::client::ClientConfig;
+//!
+//! let config = ClientConfig::default().on_slow_operation(Duration::from_millis(500), |operation| {
+//!     println!("slow {}: {:?} {:?}", operation.method, operation.elapsed, operation.statement);
+//! });
+//! ```
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc;
+
+use google_cloud_gax::grpc::{Code, Status};
+
+/// SlowOperation is the operation taking longer than the threshold.
+#[derive(Clone, Debug)]
+pub struct SlowOperation {
+    /// The RPC method like `ExecuteSql` and `Commit`. `WaitOperation` for the long-running operations.
+    pub method: &'static str,
+    /// The elapsed time including all the retries.
+    pub elapsed: Duration,
+    /// The last part of the session name. Empty for the long-running operations.
+    pub session_id: String,
+    /// None for the single-use transactions and the transactions begun by the operation.
+    pub transaction_id: Option<Vec<u8>>,
+    /// The statement recorded according to `TraceConfig::redaction`. Always None for the reads and the commits.
+    pub statement: Option<String>,
+    /// The name of the long-running operation. None for the operations of the data client.
+    pub operation_name: Option<String>,
+    /// The final status. The code is `Code::Ok` if the operation succeeded.
+    pub status: Status,
+}
+
+/// SlowOperationConfig is the threshold and the callback of the slow operation hook.
+#[derive(Clone)]
+pub struct SlowOperationConfig {
+    pub threshold: Duration,
+    pub callback: Arc<dyn Fn(SlowOperation) + Send + Sync>,
+}
+
+impl Debug for SlowOperationConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SlowOperationConfig")
+            .field("threshold", &self.threshold)
+            .finish()
+    }
+}
+
+/// OperationDetails is the part of the `SlowOperation` taken from the request.
+pub(crate) struct OperationDetails {
+    pub session_id: String,
+    pub transaction_id: Option<Vec<u8>>,
+    pub statement: Option<String>,
+    pub operation_name: Option<String>,
+}
+
+/// StreamOperation measures the streaming method from the start until the stream is completed.
+/// It is created by `Reader::start_operation` only when the slow operation hook is configured.
+pub struct StreamOperation {
+    reporter: SlowOperationReporter,
+    method: &'static str,
+    details: OperationDetails,
+    started_at: Instant,
+}
+
+impl StreamOperation {
+    pub(crate) fn new(reporter: SlowOperationReporter, method: &'static str, details: OperationDetails) -> Self {
+        Self {
+            reporter,
+            method,
+            details,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// finish reports the stream if the elapsed time exceeds the threshold.
+    pub(crate) fn finish<T>(self, result: &Result<T, Status>) {
+        let details = self.details;
+        self.reporter
+            .report(self.method, self.started_at.elapsed(), result, || details);
+    }
+}
+
+/// SlowOperationReporter sends the slow operations to the task invoking the callback.
+#[derive(Clone)]
+pub(crate) struct SlowOperationReporter {
+    threshold: Duration,
+    sender: mpsc::UnboundedSender<SlowOperation>,
+}
+
+impl SlowOperationReporter {
+    /// new spawns the task invoking the callback. The task ends when all the reporters are dropped.
+    pub(crate) fn new(config: SlowOperationConfig) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<SlowOperation>();
+        let callback = config.callback;
+        tokio::spawn(async move {
+            while let Some(operation) = receiver.recv().await {
+                callback(operation);
+            }
+        });
+        Self {
+            threshold: config.threshold,
+            sender,
+        }
+    }
+
+    /// report sends the operation if the elapsed time exceeds the threshold.
+    /// The details are built only for the slow operations.
+    pub(crate) fn report<T>(
+        &self,
+        method: &'static str,
+        elapsed: Duration,
+        result: &Result<T, Status>,
+        details: impl FnOnce() -> OperationDetails,
+    ) {
+        if elapsed < self.threshold {
+            return;
+        }
+        let details = details();
+        let status = match result {
+            Ok(_) => Status::new(Code::Ok, ""),
+            Err(status) => status.clone(),
+        };
+        let operation = SlowOperation {
+            method,
+            elapsed,
+            session_id: details.session_id,
+            transaction_id: details.transaction_id,
+            statement: details.statement,
+            operation_name: details.operation_name,
+            status,
+        };
+        if self.sender.send(operation).is_err() {
+            tracing::debug!("the slow operation task is already stopped");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use google_cloud_gax::grpc::{Code, Status};
+
+    use crate::slow_operation::{OperationDetails, SlowOperationConfig, SlowOperationReporter};
+
+    fn details() -> OperationDetails {
+        OperationDetails {
+            session_id: "session1".to_string(),
+            transaction_id: Some(vec![1, 2]),
+            statement: Some("SELECT ?".to_string()),
+            operation_name: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_report() {
+        let reported = Arc::new(Mutex::new(vec![]));
+        let (done, mut wait) = tokio::sync::mpsc::unbounded_channel();
+        let reported_clone = reported.clone();
+        let reporter = SlowOperationReporter::new(SlowOperationConfig {
+            threshold: Duration::from_millis(100),
+            callback: Arc::new(move |operation| {
+                reported_clone.lock().unwrap().push(operation);
+                let _ = done.send(());
+            }),
+        });
+
+        reporter.report("ExecuteSql", Duration::from_millis(99), &Ok::<_, Status>(()), || {
+            unreachable!("details must not be built for the fast operations")
+        });
+        reporter.report("ExecuteSql", Duration::from_millis(100), &Ok::<_, Status>(()), details);
+        reporter.report::<()>("Commit", Duration::from_secs(1), &Err(Status::aborted("aborted")), details);
+        wait.recv().await.unwrap();
+        wait.recv().await.unwrap();
+
+        let reported = reported.lock().unwrap();
+        assert_eq!(reported.len(), 2);
+        assert_eq!(reported[0].method, "ExecuteSql");
+        assert_eq!(reported[0].elapsed, Duration::from_millis(100));
+        assert_eq!(reported[0].session_id, "session1");
+        assert_eq!(reported[0].transaction_id, Some(vec![1, 2]));
+        assert_eq!(reported[0].statement.as_deref(), Some("SELECT ?"));
+        assert_eq!(reported[0].status.code(), Code::Ok);
+        assert_eq!(reported[1].method, "Commit");
+        assert_eq!(reported[1].status.code(), Code::Aborted);
+    }
+}
@@ -0,0 +1,651 @@
+//! In-process mock of the Spanner service for the tests without the emulator.
+//!
+//! The mock implements the subset of the Spanner API used by the client: the sessions, the transactions,
+//! the queries, the reads and the commits. The results are registered per SQL or per table, the committed
+//! mutations are captured and the errors can be injected to test the retry and the resume deterministically.
+//!
+//! ```
+//! use google_cloud_googleapis::spanner::v1::TypeCode;
+//! use google_cloud_gax::grpc::Status;
+//! use google_cloud_spanner::mutation::insert;
+//! use google_cloud_spanner::spanner_test::{result_set, Method, MockSpanner};
+//! use google_cloud_spanner::statement::Statement;
+//! use google_cloud_spanner::value::Value;
+//!
+//! async fn run() {
+//!     let mock = MockSpanner::new();
+//!     mock.put_query_result(
+//!         "SELECT UserId FROM User",
+//!         result_set(&[("UserId", TypeCode::String)], vec![vec![Value::String("user1".to_string())]]),
+//!     );
+//!     mock.inject_error(Method::Commit, Status::aborted("injected"));
+//!
+//!     let server = mock.start().await.unwrap();
+//!     let client = server.client().await.unwrap();
+//!
+//!     let mut tx = client.single().await.unwrap();
+//!     let mut iter = tx.query(Statement::new("SELECT UserId FROM User")).await.unwrap();
+//!     while let Some(row) = iter.next().await.unwrap() {
+//!         let user_id = row.column_by_name::<String>("UserId").unwrap();
+//!     }
+//!
+//!     // the aborted commit is retried
+//!     client.apply(vec![insert("User", &["UserId"], &[&"user2"])]).await.unwrap();
+//!     assert_eq!(mock.call_count(Method::Commit), 2);
+//!     assert_eq!(mock.mutations().len(), 1);
+//! }
+//! ```
+// The handlers return the tonic Status as the generated servers do.
+#![allow(clippy::result_large_err)]
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use futures_util::stream::BoxStream;
+use futures_util::StreamExt;
+use parking_lot::Mutex;
+use prost_types::{ListValue, Value as ProtoValue};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::{empty_body, http, Body, BoxFuture, Context, Poll, Service, StdError};
+use tonic::server::{Grpc, NamedService, ServerStreamingService, UnaryService};
+use tonic::transport::Server;
+
+use google_cloud_gax::grpc::Status;
+use google_cloud_googleapis::spanner::v1::result_set_stats::RowCount;
+use google_cloud_googleapis::spanner::v1::struct_type::Field;
+use google_cloud_googleapis::spanner::v1::{
+    BatchCreateSessionsRequest, BatchCreateSessionsResponse, BeginTransactionRequest, CommitRequest, CommitResponse,
+    CreateSessionRequest, DeleteSessionRequest, ExecuteBatchDmlRequest, ExecuteBatchDmlResponse, ExecuteSqlRequest,
+    GetSessionRequest, Mutation, PartialResultSet, ReadRequest, ResultSet, ResultSetMetadata, ResultSetStats,
+    RollbackRequest, Session, StructType, Transaction, Type, TypeCode,
+};
+
+use crate::client::{Client, ClientConfig, Error};
+use crate::value::Value;
+
+/// The database name used by the client created with `MockServer::client`.
+pub const DATABASE: &str = "projects/local-project/instances/test-instance/databases/local-database";
+
+const SERVICE_NAME: &str = "google.spanner.v1.Spanner";
+
+/// Method is the Spanner RPC implemented by the mock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Method {
+    CreateSession,
+    BatchCreateSessions,
+    GetSession,
+    DeleteSession,
+    ExecuteSql,
+    ExecuteStreamingSql,
+    ExecuteBatchDml,
+    Read,
+    StreamingRead,
+    BeginTransaction,
+    Commit,
+    Rollback,
+}
+
+impl Method {
+    fn from_path(path: &str) -> Option<Self> {
+        let name = path.strip_prefix("/google.spanner.v1.Spanner/")?;
+        Some(match name {
+            "CreateSession" => Method::CreateSession,
+            "BatchCreateSessions" => Method::BatchCreateSessions,
+            "GetSession" => Method::GetSession,
+            "DeleteSession" => Method::DeleteSession,
+            "ExecuteSql" => Method::ExecuteSql,
+            "ExecuteStreamingSql" => Method::ExecuteStreamingSql,
+            "ExecuteBatchDml" => Method::ExecuteBatchDml,
+            "Read" => Method::Read,
+            "StreamingRead" => Method::StreamingRead,
+            "BeginTransaction" => Method::BeginTransaction,
+            "Commit" => Method::Commit,
+            "Rollback" => Method::Rollback,
+            _ => return None,
+        })
+    }
+}
+
+/// result_set creates the result set of the columns and the rows.
+/// Use `ResultSet` directly for the ARRAY and STRUCT columns requiring the element types.
+pub fn result_set(columns: &[(&str, TypeCode)], rows: Vec<Vec<Value>>) -> ResultSet {
+    let fields = columns
+        .iter()
+        .map(|(name, code)| Field {
+            name: name.to_string(),
+            r#type: Some(Type {
+                code: (*code).into(),
+                ..Default::default()
+            }),
+        })
+        .collect();
+    ResultSet {
+        metadata: Some(ResultSetMetadata {
+            row_type: Some(StructType { fields }),
+            ..Default::default()
+        }),
+        rows: rows
+            .into_iter()
+            .map(|row| ListValue {
+                values: row
+                    .iter()
+                    .map(|v| ProtoValue {
+                        kind: Some(v.to_kind()),
+                    })
+                    .collect(),
+            })
+            .collect(),
+        stats: None,
+    }
+}
+
+struct StreamError {
+    after: usize,
+    status: Status,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: u64,
+    sessions: HashSet<String>,
+    query_results: HashMap<String, ResultSet>,
+    read_results: HashMap<String, ResultSet>,
+    update_counts: HashMap<String, i64>,
+    errors: HashMap<Method, VecDeque<Status>>,
+    stream_errors: HashMap<Method, VecDeque<StreamError>>,
+    call_counts: HashMap<Method, usize>,
+    commits: Vec<CommitRequest>,
+}
+
+impl State {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn create_session(&mut self, database: &str) -> Session {
+        let name = format!("{database}/sessions/{}", self.next_id());
+        self.sessions.insert(name.clone());
+        Session {
+            name,
+            create_time: Some(SystemTime::now().into()),
+            ..Default::default()
+        }
+    }
+
+    fn check_session(&self, name: &str) -> Result<(), Status> {
+        match self.sessions.contains(name) {
+            true => Ok(()),
+            false => Err(Status::not_found(format!("Session not found: {name}"))),
+        }
+    }
+}
+
+/// MockSpanner is the in-process mock of the Spanner service.
+/// It is cheap to clone and all the clones share the registered results and the captured requests.
+#[derive(Clone)]
+pub struct MockSpanner {
+    state: Arc<Mutex<State>>,
+}
+
+impl Default for MockSpanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockSpanner {
+    pub fn new() -> Self {
+        let mock = Self {
+            state: Default::default(),
+        };
+        // used by the health check of the session pool
+        mock.put_query_result("SELECT 1", result_set(&[("", TypeCode::Int64)], vec![vec![Value::Int64(1)]]));
+        mock
+    }
+
+    /// put_query_result registers the result of ExecuteSql and ExecuteStreamingSql for the SQL.
+    pub fn put_query_result(&self, sql: impl Into<String>, result: ResultSet) {
+        self.state.lock().query_results.insert(sql.into(), result);
+    }
+
+    /// put_read_result registers the result of Read and StreamingRead for the table regardless of the keys.
+    pub fn put_read_result(&self, table: impl Into<String>, result: ResultSet) {
+        self.state.lock().read_results.insert(table.into(), result);
+    }
+
+    /// put_update_count registers the number of the rows modified by the DML.
+    pub fn put_update_count(&self, sql: impl Into<String>, row_count: i64) {
+        self.state.lock().update_counts.insert(sql.into(), row_count);
+    }
+
+    /// inject_error makes the next call of the method fail with the status.
+    /// The errors injected to the same method are returned in order.
+    pub fn inject_error(&self, method: Method, status: Status) {
+        self.state.lock().errors.entry(method).or_default().push_back(status);
+    }
+
+    /// inject_stream_error makes the next stream of ExecuteStreamingSql or StreamingRead fail with the status
+    /// after sending `after` PartialResultSets. Each PartialResultSet has a row and the resume token.
+    pub fn inject_stream_error(&self, method: Method, after: usize, status: Status) {
+        self.state
+            .lock()
+            .stream_errors
+            .entry(method)
+            .or_default()
+            .push_back(StreamError { after, status });
+    }
+
+    /// call_count returns the number of the calls of the method including the failed calls.
+    pub fn call_count(&self, method: Method) -> usize {
+        self.state.lock().call_counts.get(&method).copied().unwrap_or_default()
+    }
+
+    /// commits returns the succeeded commit requests in order.
+    pub fn commits(&self) -> Vec<CommitRequest> {
+        self.state.lock().commits.clone()
+    }
+
+    /// mutations returns the mutations of the succeeded commits in order.
+    pub fn mutations(&self) -> Vec<Mutation> {
+        self.state
+            .lock()
+            .commits
+            .iter()
+            .flat_map(|c| c.mutations.clone())
+            .collect()
+    }
+
+    /// session_count returns the number of the sessions not deleted.
+    pub fn session_count(&self) -> usize {
+        self.state.lock().sessions.len()
+    }
+
+    /// start serves the mock on a random local port until the returned server is dropped.
+    pub async fn start(&self) -> std::io::Result<MockServer> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let incoming = futures_util::stream::unfold(listener, |listener| async move {
+            let stream = listener.accept().await.map(|(stream, _)| stream);
+            Some((stream, listener))
+        });
+        let service = self.clone();
+        let handle = tokio::spawn(async move {
+            if let Err(e) = Server::builder()
+                .add_service(service)
+                .serve_with_incoming(incoming)
+                .await
+            {
+                tracing::error!("mock spanner server stopped: {e:?}");
+            }
+        });
+        Ok(MockServer {
+            addr,
+            mock: self.clone(),
+            handle,
+        })
+    }
+
+    /// begin_call counts the call and returns the injected error if any.
+    fn begin_call(&self, method: Method) -> Result<(), Status> {
+        let mut state = self.state.lock();
+        *state.call_counts.entry(method).or_default() += 1;
+        match state.errors.get_mut(&method).and_then(|v| v.pop_front()) {
+            Some(status) => Err(status),
+            None => Ok(()),
+        }
+    }
+
+    fn create_session(&self, req: CreateSessionRequest) -> Result<Session, Status> {
+        self.begin_call(Method::CreateSession)?;
+        Ok(self.state.lock().create_session(&req.database))
+    }
+
+    fn batch_create_sessions(&self, req: BatchCreateSessionsRequest) -> Result<BatchCreateSessionsResponse, Status> {
+        self.begin_call(Method::BatchCreateSessions)?;
+        let mut state = self.state.lock();
+        let session = (0..req.session_count.max(1))
+            .map(|_| state.create_session(&req.database))
+            .collect();
+        Ok(BatchCreateSessionsResponse { session })
+    }
+
+    fn get_session(&self, req: GetSessionRequest) -> Result<Session, Status> {
+        self.begin_call(Method::GetSession)?;
+        self.state.lock().check_session(&req.name)?;
+        Ok(Session {
+            name: req.name,
+            ..Default::default()
+        })
+    }
+
+    fn delete_session(&self, req: DeleteSessionRequest) -> Result<(), Status> {
+        self.begin_call(Method::DeleteSession)?;
+        let mut state = self.state.lock();
+        state.check_session(&req.name)?;
+        state.sessions.remove(&req.name);
+        Ok(())
+    }
+
+    fn execute_sql(&self, req: ExecuteSqlRequest) -> Result<ResultSet, Status> {
+        self.begin_call(Method::ExecuteSql)?;
+        let state = self.state.lock();
+        state.check_session(&req.session)?;
+        if let Some(count) = state.update_counts.get(&req.sql) {
+            return Ok(update_result(*count));
+        }
+        state
+            .query_results
+            .get(&req.sql)
+            .cloned()
+            .ok_or_else(|| Status::invalid_argument(format!("no result registered for the sql: {}", req.sql)))
+    }
+
+    fn execute_streaming_sql(&self, req: ExecuteSqlRequest) -> Result<Vec<Result<PartialResultSet, Status>>, Status> {
+        self.begin_call(Method::ExecuteStreamingSql)?;
+        let mut state = self.state.lock();
+        state.check_session(&req.session)?;
+        let result =
+            match state.update_counts.get(&req.sql) {
+                Some(count) => update_result(*count),
+                None => state.query_results.get(&req.sql).cloned().ok_or_else(|| {
+                    Status::invalid_argument(format!("no result registered for the sql: {}", req.sql))
+                })?,
+            };
+        let error = state
+            .stream_errors
+            .get_mut(&Method::ExecuteStreamingSql)
+            .and_then(|v| v.pop_front());
+        Ok(partial_result_sets(result, &req.resume_token, error))
+    }
+
+    fn execute_batch_dml(&self, req: ExecuteBatchDmlRequest) -> Result<ExecuteBatchDmlResponse, Status> {
+        self.begin_call(Method::ExecuteBatchDml)?;
+        let state = self.state.lock();
+        state.check_session(&req.session)?;
+        let result_sets = req
+            .statements
+            .iter()
+            .map(|stmt| match state.update_counts.get(&stmt.sql) {
+                Some(count) => Ok(update_result(*count)),
+                None => Err(Status::invalid_argument(format!(
+                    "no update count registered for the sql: {}",
+                    stmt.sql
+                ))),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(ExecuteBatchDmlResponse {
+            result_sets,
+            status: Some(Default::default()),
+        })
+    }
+
+    fn read(&self, req: ReadRequest) -> Result<ResultSet, Status> {
+        self.begin_call(Method::Read)?;
+        let state = self.state.lock();
+        state.check_session(&req.session)?;
+        state
+            .read_results
+            .get(&req.table)
+            .cloned()
+            .ok_or_else(|| Status::invalid_argument(format!("no result registered for the table: {}", req.table)))
+    }
+
+    fn streaming_read(&self, req: ReadRequest) -> Result<Vec<Result<PartialResultSet, Status>>, Status> {
+        self.begin_call(Method::StreamingRead)?;
+        let mut state = self.state.lock();
+        state.check_session(&req.session)?;
+        let result =
+            state.read_results.get(&req.table).cloned().ok_or_else(|| {
+                Status::invalid_argument(format!("no result registered for the table: {}", req.table))
+            })?;
+        let error = state
+            .stream_errors
+            .get_mut(&Method::StreamingRead)
+            .and_then(|v| v.pop_front());
+        Ok(partial_result_sets(result, &req.resume_token, error))
+    }
+
+    fn begin_transaction(&self, req: BeginTransactionRequest) -> Result<Transaction, Status> {
+        self.begin_call(Method::BeginTransaction)?;
+        let mut state = self.state.lock();
+        state.check_session(&req.session)?;
+        Ok(Transaction {
+            id: format!("tx{}", state.next_id()).into_bytes(),
+            read_timestamp: Some(SystemTime::now().into()),
+        })
+    }
+
+    fn commit(&self, req: CommitRequest) -> Result<CommitResponse, Status> {
+        self.begin_call(Method::Commit)?;
+        let mut state = self.state.lock();
+        state.check_session(&req.session)?;
+        state.commits.push(req);
+        Ok(CommitResponse {
+            commit_timestamp: Some(SystemTime::now().into()),
+            ..Default::default()
+        })
+    }
+
+    fn rollback(&self, req: RollbackRequest) -> Result<(), Status> {
+        self.begin_call(Method::Rollback)?;
+        self.state.lock().check_session(&req.session)
+    }
+}
+
+fn update_result(count: i64) -> ResultSet {
+    ResultSet {
+        metadata: Some(ResultSetMetadata {
+            row_type: Some(StructType::default()),
+            ..Default::default()
+        }),
+        rows: vec![],
+        stats: Some(ResultSetStats {
+            row_count: Some(RowCount::RowCountExact(count)),
+            ..Default::default()
+        }),
+    }
+}
+
+/// partial_result_sets splits the result into the PartialResultSets of a row.
+/// The resume token is the number of the rows already sent, so the resumed stream starts from the next row.
+fn partial_result_sets(
+    result: ResultSet,
+    resume_token: &[u8],
+    error: Option<StreamError>,
+) -> Vec<Result<PartialResultSet, Status>> {
+    let start = match <[u8; 8]>::try_from(resume_token) {
+        Ok(token) => u64::from_be_bytes(token) as usize,
+        Err(_) => 0,
+    };
+    let mut metadata = match start {
+        0 => result.metadata,
+        _ => None,
+    };
+    let row_count = result.rows.len();
+    let mut messages: Vec<Result<PartialResultSet, Status>> = result
+        .rows
+        .into_iter()
+        .enumerate()
+        .skip(start)
+        .map(|(i, row)| {
+            Ok(PartialResultSet {
+                metadata: metadata.take(),
+                values: row.values,
+                resume_token: ((i + 1) as u64).to_be_bytes().to_vec(),
+                ..Default::default()
+            })
+        })
+        .collect();
+    if messages.is_empty() || start >= row_count {
+        messages.push(Ok(PartialResultSet {
+            metadata: metadata.take(),
+            ..Default::default()
+        }));
+    }
+    if let Some(Ok(last)) = messages.last_mut() {
+        last.stats = result.stats;
+    }
+    if let Some(error) = error {
+        messages.truncate(error.after);
+        messages.push(Err(error.status));
+    }
+    messages
+}
+
+/// MockServer is the running mock. The server is stopped when it is dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    mock: MockSpanner,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn mock(&self) -> &MockSpanner {
+        &self.mock
+    }
+
+    /// config returns the client config connecting to the mock with the plaintext connection.
+    pub fn config(&self) -> ClientConfig {
+        ClientConfig::default().with_emulator(self.addr.to_string())
+    }
+
+    /// client creates the client of `DATABASE` connecting to the mock.
+    pub async fn client(&self) -> Result<Client, Error> {
+        Client::new(DATABASE, self.config()).await
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+struct Unary<Req, Resp> {
+    mock: MockSpanner,
+    handler: fn(&MockSpanner, Req) -> Result<Resp, Status>,
+}
+
+impl<Req, Resp: Send + 'static> UnaryService<Req> for Unary<Req, Resp> {
+    type Response = Resp;
+    type Future = BoxFuture<tonic::Response<Resp>, Status>;
+
+    fn call(&mut self, request: tonic::Request<Req>) -> Self::Future {
+        let result = (self.handler)(&self.mock, request.into_inner()).map(tonic::Response::new);
+        Box::pin(std::future::ready(result))
+    }
+}
+
+type Messages = Vec<Result<PartialResultSet, Status>>;
+
+struct Streaming<Req> {
+    mock: MockSpanner,
+    handler: fn(&MockSpanner, Req) -> Result<Messages, Status>,
+}
+
+impl<Req> ServerStreamingService<Req> for Streaming<Req> {
+    type Response = PartialResultSet;
+    type ResponseStream = BoxStream<'static, Result<PartialResultSet, Status>>;
+    type Future = BoxFuture<tonic::Response<Self::ResponseStream>, Status>;
+
+    fn call(&mut self, request: tonic::Request<Req>) -> Self::Future {
+        // yield before each message so that the messages already encoded are flushed before the error.
+        // tonic discards the buffered messages when the error is polled in the same batch.
+        let result = (self.handler)(&self.mock, request.into_inner()).map(|messages| {
+            let stream = futures_util::stream::iter(messages).then(|message| async move {
+                tokio::task::yield_now().await;
+                message
+            });
+            tonic::Response::new(stream.boxed())
+        });
+        Box::pin(std::future::ready(result))
+    }
+}
+
+async fn unary<Req, Resp, B>(
+    mock: MockSpanner,
+    handler: fn(&MockSpanner, Req) -> Result<Resp, Status>,
+    req: http::Request<B>,
+) -> http::Response<BoxBody>
+where
+    Req: prost::Message + Default + Send + 'static,
+    Resp: prost::Message + Send + 'static,
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    Grpc::new(ProstCodec::default())
+        .unary(Unary { mock, handler }, req)
+        .await
+}
+
+async fn streaming<Req, B>(
+    mock: MockSpanner,
+    handler: fn(&MockSpanner, Req) -> Result<Messages, Status>,
+    req: http::Request<B>,
+) -> http::Response<BoxBody>
+where
+    Req: prost::Message + Default + Send + 'static,
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    Grpc::new(ProstCodec::default())
+        .server_streaming(Streaming { mock, handler }, req)
+        .await
+}
+
+impl<B> Service<http::Request<B>> for MockSpanner
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let mock = self.clone();
+        Box::pin(async move {
+            let response = match Method::from_path(req.uri().path()) {
+                Some(Method::CreateSession) => unary(mock, MockSpanner::create_session, req).await,
+                Some(Method::BatchCreateSessions) => unary(mock, MockSpanner::batch_create_sessions, req).await,
+                Some(Method::GetSession) => unary(mock, MockSpanner::get_session, req).await,
+                Some(Method::DeleteSession) => unary(mock, MockSpanner::delete_session, req).await,
+                Some(Method::ExecuteSql) => unary(mock, MockSpanner::execute_sql, req).await,
+                Some(Method::ExecuteStreamingSql) => streaming(mock, MockSpanner::execute_streaming_sql, req).await,
+                Some(Method::ExecuteBatchDml) => unary(mock, MockSpanner::execute_batch_dml, req).await,
+                Some(Method::Read) => unary(mock, MockSpanner::read, req).await,
+                Some(Method::StreamingRead) => streaming(mock, MockSpanner::streaming_read, req).await,
+                Some(Method::BeginTransaction) => unary(mock, MockSpanner::begin_transaction, req).await,
+                Some(Method::Commit) => unary(mock, MockSpanner::commit, req).await,
+                Some(Method::Rollback) => unary(mock, MockSpanner::rollback, req).await,
+                // UNIMPLEMENTED
+                None => http::Response::builder()
+                    .status(200)
+                    .header("grpc-status", "12")
+                    .header("content-type", "application/grpc")
+                    .body(empty_body())
+                    .unwrap(),
+            };
+            Ok(response)
+        })
+    }
+}
+
+impl NamedService for MockSpanner {
+    const NAME: &'static str = SERVICE_NAME;
+}
@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Formatter};
 
 use base64::prelude::*;
 use prost_types::value::Kind;
@@ -8,11 +9,14 @@ use time::format_description::well_known::Rfc3339;
 use time::macros::format_description;
 use time::{Date, OffsetDateTime};
 
+use google_cloud_gax::grpc::{Code, Status};
 use google_cloud_googleapis::spanner::v1::struct_type::Field;
 use google_cloud_googleapis::spanner::v1::{StructType, Type, TypeAnnotationCode, TypeCode};
 
+use crate::apiv1::spanner_client::{RedactionPolicy, TraceConfig};
 use crate::bigdecimal::BigDecimal;
-use crate::value::CommitTimestamp;
+use crate::row::{RedactedField, TypedValue};
+use crate::value::{CommitTimestamp, Interval, COMMIT_TIMESTAMP};
 
 /// A Statement is a SQL query with named parameters.
 ///
@@ -24,6 +28,10 @@ use crate::value::CommitTimestamp;
 /// statement with unbound parameters. On the other hand, it is allowable to
 /// bind parameter names that are not used.
 ///
+/// The parameters are always the values. The identifiers like the table names and the column names
+/// can't be bound, so quote them with `quote_identifier` or `Statement::with_table` to build the SQL
+/// dynamically. Binding the parameter used as the table name is rejected before the statement is sent.
+///
 /// See the documentation of the Row type for how Go types are mapped to Cloud
 /// Spanner types.
 #[derive(Clone)]
@@ -45,11 +53,44 @@ impl Statement {
 
     /// add_params add the bind parameter.
     /// Implement the ToKind trait to use non-predefined types.
+    ///
+    /// `Option<T>` is bound as a typed NULL when the value is `None`,
+    /// so the parameter type is always sent with the value.
+    /// ```
+    /// use google_cloud_spanner::statement::Statement;
+    ///
+    /// let mut stmt = Statement::new("SELECT * FROM User WHERE UserId = @UserId OR @UserId IS NULL");
+    /// stmt.add_param("UserId", &None::<String>);
+    /// ```
     pub fn add_param<T>(&mut self, name: &str, value: &T)
     where
         T: ToKind,
     {
-        self.param_types.insert(name.to_string(), T::get_type());
+        self.add_param_with_type(name, value, T::get_type());
+    }
+
+    /// add_param_with_type add the bind parameter with the explicit type.
+    /// Use this when the type can't be inferred from `T`,
+    /// such as PostgreSQL NUMERIC / JSONB or a NULL of the type not implementing ToKind.
+    /// The type is either the `Type` proto or the `SpannerType`.
+    /// ```
+    /// use google_cloud_googleapis::spanner::v1::{TypeAnnotationCode, TypeCode};
+    /// use google_cloud_spanner::bigdecimal::BigDecimal;
+    /// use google_cloud_spanner::statement::{annotated_type, SpannerType, Statement};
+    ///
+    /// let mut stmt = Statement::new("UPDATE users SET score = $1 WHERE id = $2");
+    /// stmt.add_param_with_type("p1", &None::<BigDecimal>, annotated_type(TypeCode::Numeric, TypeAnnotationCode::PgNumeric));
+    /// stmt.add_param("p2", &1_i64);
+    ///
+    /// let mut stmt = Statement::new("SELECT * FROM User WHERE UserId IN UNNEST(@ids)");
+    /// stmt.add_param_with_type("ids", &Vec::<i64>::new(), SpannerType::array(SpannerType::Int64));
+    /// ```
+    pub fn add_param_with_type<T, P>(&mut self, name: &str, value: &T, param_type: P)
+    where
+        T: ToKind,
+        P: Into<Type>,
+    {
+        self.param_types.insert(name.to_string(), param_type.into());
         self.params.insert(
             name.to_string(),
             Value {
@@ -57,6 +98,361 @@ impl Statement {
             },
         );
     }
+
+    /// param_type overrides the type of the bound parameter.
+    /// The type is inferred from `T` of `add_param`, so use this for the parameters whose type
+    /// can't be determined by the server, like the untyped literals of the PostgreSQL dialect.
+    /// Call this after `add_param` because `add_param` replaces the type.
+    /// ```
+    /// use google_cloud_spanner::statement::{SpannerType, Statement};
+    ///
+    /// let mut stmt = Statement::new("SELECT * FROM User WHERE UserId IN UNNEST(@ids)");
+    /// stmt.add_param("ids", &Vec::<i64>::new());
+    /// stmt.param_type("ids", SpannerType::array(SpannerType::Int64));
+    /// ```
+    pub fn param_type(&mut self, name: &str, param_type: SpannerType) -> &mut Self {
+        self.param_types.insert(name.to_string(), param_type.into());
+        self
+    }
+
+    /// with_table returns the GoogleSQL statement whose `{table}` placeholder is replaced with the quoted table name.
+    /// The table name is an identifier, so it can't be bound as the parameter like `FROM @table`.
+    /// The values are still bound as the parameters.
+    /// ```
+    /// use google_cloud_spanner::statement::Statement;
+    ///
+    /// let table = format!("User_{}", "tenant_1");
+    /// let mut stmt = Statement::with_table("SELECT * FROM {table} WHERE UserId = @UserId", &table).unwrap();
+    /// stmt.add_param("UserId", &"user1");
+    /// ```
+    pub fn with_table(template: &str, table: &str) -> Result<Self, IdentifierError> {
+        Self::with_table_in_dialect(template, table, Dialect::GoogleSql)
+    }
+
+    /// with_table_in_dialect is the same as `with_table` except that the table name is quoted for the dialect.
+    pub fn with_table_in_dialect(template: &str, table: &str, dialect: Dialect) -> Result<Self, IdentifierError> {
+        if !template.contains(TABLE_PLACEHOLDER) {
+            return Err(IdentifierError::PlaceholderNotFound);
+        }
+        let table = quote_identifier(table, dialect)?;
+        Ok(Self::new(template.replace(TABLE_PLACEHOLDER, &table)))
+    }
+
+    /// validate returns an error if the statement can't be executed regardless of the database.
+    #[allow(clippy::result_large_err)]
+    pub(crate) fn validate(&self) -> Result<(), Status> {
+        self.check_commit_timestamp()?;
+        self.check_identifier_params()
+    }
+
+    /// check_identifier_params returns an error if the bound parameter is used as the table name.
+    /// The parameters are always the values, so the identifiers must be quoted with `quote_identifier`.
+    #[allow(clippy::result_large_err)]
+    fn check_identifier_params(&self) -> Result<(), Status> {
+        let mut words = self.sql.split_whitespace();
+        while let Some(word) = words.next() {
+            if !IDENTIFIER_KEYWORDS.iter().any(|k| word.eq_ignore_ascii_case(k)) {
+                continue;
+            }
+            let name = match words.next() {
+                Some(next) if next.starts_with(['@', '$']) => next[1..]
+                    .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .next()
+                    .unwrap_or_default(),
+                _ => continue,
+            };
+            let bound = self.params.contains_key(name) || self.params.contains_key(&format!("p{name}"));
+            if bound {
+                return Err(Status::new(
+                    Code::InvalidArgument,
+                    format!(
+                        "parameter {name} is used as the identifier after {word}: parameters are values only, quote the identifier with statement::quote_identifier or Statement::with_table instead"
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// check_commit_timestamp returns an error if `CommitTimestamp` is bound to the parameter,
+    /// because the placeholder of the commit timestamp is available only in the mutations.
+    #[allow(clippy::result_large_err)]
+    fn check_commit_timestamp(&self) -> Result<(), Status> {
+        for (name, value) in &self.params {
+            if contains_commit_timestamp(value, self.param_types.get(name)) {
+                return Err(Status::new(
+                    Code::InvalidArgument,
+                    format!(
+                        "CommitTimestamp can't be bound to the parameter {name}: use PENDING_COMMIT_TIMESTAMP() in the DML instead"
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Debug prints the statement according to `RedactionPolicy::default()`, so neither the SQL nor the values are printed
+/// like `Statement { sql: <redacted>, params: {"UserId": STRING <redacted>} }`.
+/// Use `TraceConfig::debug_statement` to print it according to the policy of the client.
+impl Debug for Statement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        TraceConfig::default().debug_statement(self).fmt(f)
+    }
+}
+
+impl Statement {
+    /// debug_with_values returns the Debug printing the SQL and the values of the parameters as they are
+    /// in the same way as `RedactionPolicy::FullStatement`.
+    /// Don't write it to the logs if the parameters may contain personal data.
+    /// ```
+    /// use google_cloud_spanner::statement::Statement;
+    ///
+    /// let mut stmt = Statement::new("SELECT * FROM User WHERE UserId = @UserId");
+    /// stmt.add_param("UserId", &"user1");
+    /// assert_eq!(format!("{stmt:?}"), r#"Statement { sql: <redacted>, params: {"UserId": STRING <redacted>} }"#);
+    /// assert_eq!(format!("{:?}", stmt.debug_with_values()), r#"Statement { sql: "SELECT * FROM User WHERE UserId = @UserId", params: {"UserId": STRING "user1"} }"#);
+    /// ```
+    pub fn debug_with_values(&self) -> impl Debug + '_ {
+        let config = TraceConfig {
+            redaction: RedactionPolicy::FullStatement,
+            max_statement_length: None,
+        };
+        config.debug_statement(self)
+    }
+
+    /// redacted returns the statement without the values of the parameters according to `RedactionPolicy::default()`.
+    /// Use `TraceConfig::redact_statement` to redact it according to the policy of the client.
+    pub fn redacted(&self) -> RedactedStatement {
+        TraceConfig::default().redact_statement(self)
+    }
+
+    /// fmt_debug prints the SQL formatted by `TraceConfig` and the parameters.
+    /// The SQL is printed as `<redacted>` when it is None.
+    pub(crate) fn fmt_debug(&self, f: &mut Formatter<'_>, sql: Option<&str>, with_values: bool) -> std::fmt::Result {
+        let fields: Vec<Field> = self
+            .params
+            .keys()
+            .map(|name| Field {
+                name: name.to_string(),
+                r#type: self.param_types.get(name).cloned(),
+            })
+            .collect();
+        let params = DebugParams(
+            fields
+                .iter()
+                .zip(self.params.values())
+                .map(|(field, value)| (&field.name, TypedValue::new(field, with_values.then_some(value))))
+                .collect(),
+        );
+        let mut debug = f.debug_struct("Statement");
+        match sql {
+            Some(sql) => debug.field("sql", &sql),
+            None => debug.field("sql", &format_args!("<redacted>")),
+        };
+        debug.field("params", &params).finish()
+    }
+
+    pub(crate) fn redacted_params(&self) -> Vec<RedactedField> {
+        self.params
+            .keys()
+            .map(|name| RedactedField::new(name, self.param_types.get(name)))
+            .collect()
+    }
+}
+
+struct DebugParams<'a>(Vec<(&'a String, TypedValue<'a>)>);
+
+impl Debug for DebugParams<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.0.iter().map(|(k, v)| (k, v))).finish()
+    }
+}
+
+/// RedactedStatement is the statement without the values of the parameters.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RedactedStatement {
+    /// The SQL formatted by `TraceConfig::statement`. None if the policy records no SQL.
+    pub sql: Option<String>,
+    pub params: Vec<RedactedField>,
+}
+
+/// The placeholder of the table name replaced by `Statement::with_table`.
+pub const TABLE_PLACEHOLDER: &str = "{table}";
+
+/// The max length of the identifier of Cloud Spanner.
+const MAX_IDENTIFIER_LENGTH: usize = 128;
+
+/// The keywords followed by the table name.
+const IDENTIFIER_KEYWORDS: [&str; 5] = ["FROM", "JOIN", "INTO", "UPDATE", "TABLE"];
+
+/// Dialect is the SQL dialect of the database used to quote the identifiers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Dialect {
+    #[default]
+    GoogleSql,
+    PostgreSql,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum IdentifierError {
+    #[error("identifier is empty")]
+    Empty,
+    #[error("identifier is longer than 128 characters: {0}")]
+    TooLong(String),
+    #[error("identifier contains the control character: {0:?}")]
+    ControlCharacter(String),
+    #[error("template has no {{table}} placeholder")]
+    PlaceholderNotFound,
+}
+
+/// quote_identifier validates the identifier like the table name or the column name and quotes it for the dialect.
+/// GoogleSQL uses the backticks and PostgreSQL uses the double quotes.
+/// The quote characters in the identifier are escaped.
+/// The qualified name like `schema.table` must be quoted per part.
+///
+/// Use this to build the SQL with the identifiers chosen at runtime.
+/// The identifiers can't be bound with `Statement::add_param`, because the parameters are always the values.
+/// ```
+/// use google_cloud_spanner::statement::{quote_identifier, Dialect, Statement};
+///
+/// let column = quote_identifier("Quantity", Dialect::GoogleSql).unwrap();
+/// let mut stmt = Statement::new(format!("SELECT {column} FROM UserItem WHERE UserId = @UserId"));
+/// stmt.add_param("UserId", &"user1");
+///
+/// assert_eq!(quote_identifier("users", Dialect::PostgreSql).unwrap(), "\"users\"");
+/// assert!(quote_identifier("User\n", Dialect::GoogleSql).is_err());
+/// ```
+pub fn quote_identifier(name: &str, dialect: Dialect) -> Result<String, IdentifierError> {
+    if name.is_empty() {
+        return Err(IdentifierError::Empty);
+    }
+    if name.chars().count() > MAX_IDENTIFIER_LENGTH {
+        return Err(IdentifierError::TooLong(name.to_string()));
+    }
+    if name.chars().any(char::is_control) {
+        return Err(IdentifierError::ControlCharacter(name.to_string()));
+    }
+    Ok(match dialect {
+        Dialect::GoogleSql => format!("`{}`", name.replace('\\', "\\\\").replace('`', "\\`")),
+        Dialect::PostgreSql => format!("\"{}\"", name.replace('"', "\"\"")),
+    })
+}
+
+/// ident quotes the GoogleSQL identifier. It is the shorthand of `quote_identifier`.
+pub fn ident(name: &str) -> Result<String, IdentifierError> {
+    quote_identifier(name, Dialect::GoogleSql)
+}
+
+fn contains_commit_timestamp(value: &Value, tp: Option<&Type>) -> bool {
+    let tp = match tp {
+        Some(tp) => tp,
+        None => return false,
+    };
+    match (tp.code(), value.kind.as_ref()) {
+        (TypeCode::Timestamp, Some(Kind::StringValue(v))) => v == COMMIT_TIMESTAMP,
+        (TypeCode::Array, Some(Kind::ListValue(v))) => v
+            .values
+            .iter()
+            .any(|v| contains_commit_timestamp(v, tp.array_element_type.as_deref())),
+        (TypeCode::Struct, Some(Kind::StructValue(v))) => tp
+            .struct_type
+            .iter()
+            .flat_map(|t| t.fields.iter())
+            .any(|f| matches!(v.fields.get(&f.name), Some(v) if contains_commit_timestamp(v, f.r#type.as_ref()))),
+        _ => false,
+    }
+}
+
+/// SpannerType is the type of Cloud Spanner passed to `Statement::param_type` and `Statement::add_param_with_type`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpannerType {
+    Bool,
+    Int64,
+    Float64,
+    Numeric,
+    String,
+    Bytes,
+    Json,
+    Timestamp,
+    Date,
+    Interval,
+    /// NUMERIC of the PostgreSQL dialect.
+    PgNumeric,
+    /// JSONB of the PostgreSQL dialect.
+    PgJsonb,
+    Array(Box<SpannerType>),
+    /// Fields of the STRUCT in declared order.
+    Struct(Vec<(String, SpannerType)>),
+}
+
+impl SpannerType {
+    pub fn array(element: SpannerType) -> Self {
+        SpannerType::Array(Box::new(element))
+    }
+}
+
+impl From<SpannerType> for Type {
+    fn from(value: SpannerType) -> Self {
+        match value {
+            SpannerType::Bool => single_type(TypeCode::Bool),
+            SpannerType::Int64 => single_type(TypeCode::Int64),
+            SpannerType::Float64 => single_type(TypeCode::Float64),
+            SpannerType::Numeric => single_type(TypeCode::Numeric),
+            SpannerType::String => single_type(TypeCode::String),
+            SpannerType::Bytes => single_type(TypeCode::Bytes),
+            SpannerType::Json => single_type(TypeCode::Json),
+            SpannerType::Timestamp => single_type(TypeCode::Timestamp),
+            SpannerType::Date => single_type(TypeCode::Date),
+            SpannerType::Interval => single_type(TypeCode::Interval),
+            SpannerType::PgNumeric => annotated_type(TypeCode::Numeric, TypeAnnotationCode::PgNumeric),
+            SpannerType::PgJsonb => annotated_type(TypeCode::Json, TypeAnnotationCode::PgJsonb),
+            SpannerType::Array(element) => Type {
+                code: TypeCode::Array.into(),
+                array_element_type: Some(Box::new((*element).into())),
+                struct_type: None,
+                type_annotation: TypeAnnotationCode::Unspecified.into(),
+            },
+            SpannerType::Struct(fields) => Type {
+                code: TypeCode::Struct.into(),
+                array_element_type: None,
+                struct_type: Some(StructType {
+                    fields: fields
+                        .into_iter()
+                        .map(|(name, tp)| Field {
+                            name,
+                            r#type: Some(tp.into()),
+                        })
+                        .collect(),
+                }),
+                type_annotation: TypeAnnotationCode::Unspecified.into(),
+            },
+        }
+    }
+}
+
+/// with_param_type_hint adds the hint to the error caused by the parameter whose type is missing or ambiguous.
+/// The code, the details and the metadata of the error are kept.
+pub(crate) fn with_param_type_hint(status: Status) -> Status {
+    let message = status.message().to_ascii_lowercase();
+    let missing_type = message.contains("infer type")
+        || message.contains("could not determine data type")
+        || message.contains("cannot be determined")
+        || message.contains("is ambiguous");
+    if status.code() != Code::InvalidArgument || !missing_type {
+        return status;
+    }
+    let message = format!(
+        "{}: specify the parameter type with Statement::param_type or Statement::add_param_with_type",
+        status.message()
+    );
+    Status::with_details_and_metadata(
+        status.code(),
+        message,
+        status.details().to_vec().into(),
+        status.metadata().clone(),
+    )
 }
 
 pub fn single_type<T>(code: T) -> Type
@@ -72,6 +468,20 @@ where
     }
 }
 
+/// annotated_type returns the type with the annotation like PostgreSQL NUMERIC.
+pub fn annotated_type<T>(code: T, annotation: TypeAnnotationCode) -> Type
+where
+    T: Into<i32>,
+{
+    Type {
+        code: code.into(),
+        array_element_type: None,
+        struct_type: None,
+        type_annotation: annotation.into(),
+    }
+}
+
+#[diagnostic::on_unimplemented(message = "`{Self}` can't be encoded as a Spanner value")]
 pub trait ToKind {
     fn to_kind(&self) -> value::Kind;
     fn get_type() -> Type
@@ -79,9 +489,26 @@ pub trait ToKind {
         Self: Sized;
 }
 
+/// TableMetadata is the table name and the columns of the struct.
+/// The derive macro implements it with `#[spanner(table = "...")]`.
+pub trait TableMetadata {
+    const TABLE_NAME: &'static str;
+    /// The columns read into the struct in the order of the fields.
+    const COLUMNS: &'static [&'static str];
+
+    /// select_sql returns `SELECT <COLUMNS> FROM <TABLE_NAME>`.
+    fn select_sql() -> String {
+        format!("SELECT {} FROM {}", Self::COLUMNS.join(", "), Self::TABLE_NAME)
+    }
+}
+
 pub type Kinds = Vec<(&'static str, Kind)>;
 pub type Types = Vec<(&'static str, Type)>;
 
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be encoded as a Spanner STRUCT",
+    note = "derive `Table` for `{Self}`, or implement `ToStruct`"
+)]
 pub trait ToStruct {
     fn to_kinds(&self) -> Kinds;
     fn get_types() -> Types
@@ -172,13 +599,22 @@ impl ToKind for OffsetDateTime {
 
 impl ToKind for CommitTimestamp {
     fn to_kind(&self) -> Kind {
-        "spanner.commit_timestamp()".to_kind()
+        COMMIT_TIMESTAMP.to_kind()
     }
     fn get_type() -> Type {
         single_type(TypeCode::Timestamp)
     }
 }
 
+impl ToKind for Interval {
+    fn to_kind(&self) -> Kind {
+        self.to_string().to_kind()
+    }
+    fn get_type() -> Type {
+        single_type(TypeCode::Interval)
+    }
+}
+
 impl ToKind for &[u8] {
     fn to_kind(&self) -> Kind {
         BASE64_STANDARD.encode(self).to_kind()
@@ -273,3 +709,275 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use prost_types::value::Kind;
+    use time::{Date, OffsetDateTime};
+
+    use google_cloud_gax::grpc::metadata::MetadataMap;
+    use google_cloud_gax::grpc::{Code, Status};
+    use google_cloud_googleapis::spanner::v1::{Type, TypeAnnotationCode, TypeCode};
+
+    use crate::apiv1::spanner_client::{RedactionPolicy, TraceConfig};
+    use crate::bigdecimal::BigDecimal;
+
+    use crate::statement::{
+        annotated_type, ident, quote_identifier, with_param_type_hint, Dialect, IdentifierError, SpannerType,
+        Statement, ToKind,
+    };
+    use crate::value::{CommitTimestamp, Interval};
+
+    fn assert_null_param<T: ToKind>(expected: TypeCode) {
+        let mut stmt = Statement::new("SELECT @p");
+        stmt.add_param("p", &None::<T>);
+        assert!(matches!(stmt.params["p"].kind, Some(Kind::NullValue(_))));
+        assert_eq!(stmt.param_types["p"].code(), expected);
+
+        let mut stmt = Statement::new("SELECT @p");
+        stmt.add_param("p", &None::<Vec<T>>);
+        assert!(matches!(stmt.params["p"].kind, Some(Kind::NullValue(_))));
+        let tp = &stmt.param_types["p"];
+        assert_eq!(tp.code(), TypeCode::Array);
+        assert_eq!(tp.array_element_type.as_ref().unwrap().code(), expected);
+    }
+
+    #[test]
+    fn test_null_param() {
+        assert_null_param::<String>(TypeCode::String);
+        assert_null_param::<&str>(TypeCode::String);
+        assert_null_param::<i64>(TypeCode::Int64);
+        assert_null_param::<f64>(TypeCode::Float64);
+        assert_null_param::<bool>(TypeCode::Bool);
+        assert_null_param::<Date>(TypeCode::Date);
+        assert_null_param::<OffsetDateTime>(TypeCode::Timestamp);
+        assert_null_param::<Interval>(TypeCode::Interval);
+        assert_null_param::<CommitTimestamp>(TypeCode::Timestamp);
+        assert_null_param::<Vec<u8>>(TypeCode::Bytes);
+        assert_null_param::<BigDecimal>(TypeCode::Numeric);
+    }
+
+    #[test]
+    fn test_null_param_postgresql() {
+        let mut stmt = Statement::new("SELECT $1, $2, $3");
+        stmt.add_param_with_type(
+            "p1",
+            &None::<BigDecimal>,
+            annotated_type(TypeCode::Numeric, TypeAnnotationCode::PgNumeric),
+        );
+        stmt.add_param_with_type(
+            "p2",
+            &None::<String>,
+            annotated_type(TypeCode::Json, TypeAnnotationCode::PgJsonb),
+        );
+        stmt.add_param("p3", &None::<i64>);
+        for name in ["p1", "p2", "p3"] {
+            assert!(matches!(stmt.params[name].kind, Some(Kind::NullValue(_))));
+        }
+        assert_eq!(stmt.param_types["p1"].code(), TypeCode::Numeric);
+        assert_eq!(stmt.param_types["p1"].type_annotation(), TypeAnnotationCode::PgNumeric);
+        assert_eq!(stmt.param_types["p2"].code(), TypeCode::Json);
+        assert_eq!(stmt.param_types["p2"].type_annotation(), TypeAnnotationCode::PgJsonb);
+        assert_eq!(stmt.param_types["p3"].code(), TypeCode::Int64);
+    }
+
+    #[test]
+    fn test_add_param_with_type() {
+        let array_of_json = Type {
+            code: TypeCode::Array.into(),
+            array_element_type: Some(Box::new(annotated_type(TypeCode::Json, TypeAnnotationCode::Unspecified))),
+            struct_type: None,
+            type_annotation: TypeAnnotationCode::Unspecified.into(),
+        };
+        let mut stmt = Statement::new("SELECT @p");
+        stmt.add_param_with_type("p", &None::<Vec<String>>, array_of_json.clone());
+        assert_eq!(stmt.param_types["p"], array_of_json);
+
+        // The type is overwritten when the same parameter is added again.
+        stmt.add_param("p", &Some(1_i64));
+        assert_eq!(stmt.param_types["p"].code(), TypeCode::Int64);
+        assert_eq!(stmt.params["p"].kind, Some(Kind::StringValue("1".to_string())));
+    }
+
+    #[test]
+    fn test_param_type() {
+        let mut stmt = Statement::new("SELECT @ids, @s, @n");
+        stmt.add_param("ids", &Vec::<i64>::new());
+        stmt.add_param("s", &None::<String>);
+        stmt.param_type("ids", SpannerType::array(SpannerType::PgNumeric))
+            .param_type("s", SpannerType::Struct(vec![("a".to_string(), SpannerType::Interval)]));
+        stmt.add_param_with_type("n", &None::<String>, SpannerType::Json);
+        assert_eq!(stmt.param_types["n"].code(), TypeCode::Json);
+        let tp = &stmt.param_types["ids"];
+        assert_eq!(tp.code(), TypeCode::Array);
+        let element = tp.array_element_type.as_ref().unwrap();
+        assert_eq!(element.code(), TypeCode::Numeric);
+        assert_eq!(element.type_annotation(), TypeAnnotationCode::PgNumeric);
+        let tp = &stmt.param_types["s"];
+        assert_eq!(tp.code(), TypeCode::Struct);
+        let field = &tp.struct_type.as_ref().unwrap().fields[0];
+        assert_eq!(field.name, "a");
+        assert_eq!(field.r#type.as_ref().unwrap().code(), TypeCode::Interval);
+    }
+
+    #[test]
+    fn test_with_param_type_hint() {
+        let mut metadata = MetadataMap::new();
+        metadata.insert("x-test", "value".parse().unwrap());
+        let status = Status::with_details_and_metadata(
+            Code::InvalidArgument,
+            "Unable to infer type for parameter p",
+            vec![1_u8, 2, 3].into(),
+            metadata,
+        );
+        let status = with_param_type_hint(status);
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(status.message().starts_with("Unable to infer type for parameter p: "));
+        assert!(status.message().contains("Statement::param_type"));
+        assert_eq!(status.details(), &[1_u8, 2, 3]);
+        assert_eq!(status.metadata().get("x-test").unwrap(), "value");
+
+        for message in [
+            "could not determine data type of parameter $1",
+            "Query parameter type of @p cannot be determined",
+            "The type of parameter p is ambiguous",
+        ] {
+            let status = with_param_type_hint(Status::new(Code::InvalidArgument, message));
+            assert!(status.message().contains("Statement::param_type"), "{message}");
+        }
+
+        // the other errors of the statement with parameters are kept as they are.
+        let status = with_param_type_hint(Status::new(Code::InvalidArgument, "Syntax error: Unexpected @p"));
+        assert_eq!(status.message(), "Syntax error: Unexpected @p");
+        let status = with_param_type_hint(Status::new(Code::NotFound, "Unable to infer type"));
+        assert_eq!(status.message(), "Unable to infer type");
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("User", Dialect::GoogleSql).unwrap(), "`User`");
+        assert_eq!(quote_identifier("a`b\\c", Dialect::GoogleSql).unwrap(), "`a\\`b\\\\c`");
+        assert_eq!(ident("User").unwrap(), "`User`");
+        assert_eq!(quote_identifier("User", Dialect::PostgreSql).unwrap(), "\"User\"");
+        assert_eq!(quote_identifier("a\"b", Dialect::PostgreSql).unwrap(), "\"a\"\"b\"");
+        assert_eq!(quote_identifier("", Dialect::GoogleSql), Err(IdentifierError::Empty));
+        assert!(matches!(
+            quote_identifier("a\u{0}b", Dialect::PostgreSql),
+            Err(IdentifierError::ControlCharacter(_))
+        ));
+        assert!(matches!(ident(&"a".repeat(129)), Err(IdentifierError::TooLong(_))));
+        assert!(ident(&"a".repeat(128)).is_ok());
+    }
+
+    #[test]
+    fn test_with_table() {
+        let stmt = Statement::with_table("SELECT * FROM {table} WHERE UserId = @UserId", "User`1").unwrap();
+        assert_eq!(stmt.sql, "SELECT * FROM `User\\`1` WHERE UserId = @UserId");
+        let stmt = Statement::with_table_in_dialect("DELETE FROM {table}", "user", Dialect::PostgreSql).unwrap();
+        assert_eq!(stmt.sql, "DELETE FROM \"user\"");
+        assert!(matches!(
+            Statement::with_table("SELECT 1", "User"),
+            Err(IdentifierError::PlaceholderNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_check_identifier_params() {
+        let mut stmt = Statement::new("SELECT * FROM @table WHERE UserId = @UserId");
+        stmt.add_param("UserId", &"user1");
+        assert!(stmt.validate().is_ok());
+        stmt.add_param("table", &"User");
+        let status = stmt.validate().unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(status.message().contains("quote_identifier"));
+
+        let mut stmt = Statement::new("DELETE FROM $1 WHERE id = $2");
+        stmt.add_param("p1", &"users");
+        assert!(stmt.validate().is_err());
+
+        let mut stmt = Statement::new("SELECT * FROM UNNEST(@ids)");
+        stmt.add_param("ids", &vec![1_i64]);
+        assert!(stmt.validate().is_ok());
+    }
+
+    #[test]
+    fn test_check_commit_timestamp() {
+        let mut stmt = Statement::new("UPDATE User SET UpdatedAt = @ts WHERE UserId = @id");
+        stmt.add_param("id", &"spanner.commit_timestamp()");
+        stmt.add_param("ts", &OffsetDateTime::now_utc());
+        assert!(stmt.check_commit_timestamp().is_ok());
+
+        stmt.add_param("ts", &CommitTimestamp::new());
+        let status = stmt.check_commit_timestamp().unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(status.message().contains("PENDING_COMMIT_TIMESTAMP()"));
+
+        let mut stmt = Statement::new("SELECT @ts");
+        stmt.add_param("ts", &vec![Some(CommitTimestamp::new())]);
+        assert!(stmt.check_commit_timestamp().is_err());
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut stmt = Statement::new("SELECT * FROM User WHERE UserId = @UserId AND Level IN UNNEST(@levels)");
+        stmt.add_param("UserId", &"user1");
+        stmt.add_param("levels", &vec![1_i64, 2]);
+        let debug = format!("{stmt:?}");
+        assert_eq!(
+            debug,
+            r#"Statement { sql: <redacted>, params: {"UserId": STRING <redacted>, "levels": ARRAY<INT64> <redacted>} }"#
+        );
+        let debug = format!("{:?}", stmt.debug_with_values());
+        assert!(
+            debug.ends_with(r#"params: {"UserId": STRING "user1", "levels": ARRAY<INT64> [1, 2]} }"#),
+            "{debug}"
+        );
+    }
+
+    #[test]
+    fn test_debug_redaction_policy() {
+        let mut stmt = Statement::new("SELECT * FROM User WHERE UserId = @UserId AND Name = 'name1'");
+        stmt.add_param("UserId", &"user1");
+        let debug = |redaction| {
+            let config = TraceConfig {
+                redaction,
+                max_statement_length: None,
+            };
+            format!("{:?}", config.debug_statement(&stmt))
+        };
+        assert_eq!(format!("{stmt:?}"), debug(RedactionPolicy::default()));
+        assert_eq!(
+            debug(RedactionPolicy::OperationOnly),
+            r#"Statement { sql: <redacted>, params: {"UserId": STRING <redacted>} }"#
+        );
+        assert_eq!(
+            debug(RedactionPolicy::SanitizedStatement),
+            r#"Statement { sql: "SELECT * FROM User WHERE UserId = @UserId AND Name = ?", params: {"UserId": STRING <redacted>} }"#
+        );
+        assert_eq!(
+            debug(RedactionPolicy::FullStatement),
+            r#"Statement { sql: "SELECT * FROM User WHERE UserId = @UserId AND Name = 'name1'", params: {"UserId": STRING "user1"} }"#
+        );
+
+        assert_eq!(stmt.redacted().sql, None);
+        let config = TraceConfig {
+            redaction: RedactionPolicy::SanitizedStatement,
+            max_statement_length: None,
+        };
+        assert_eq!(
+            config.redact_statement(&stmt).sql.as_deref(),
+            Some("SELECT * FROM User WHERE UserId = @UserId AND Name = ?")
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_redacted_serialize() {
+        let mut stmt = Statement::new("SELECT * FROM User WHERE UserId = @UserId");
+        stmt.add_param("UserId", &"user1");
+        assert_eq!(
+            serde_json::to_string(&stmt.redacted()).unwrap(),
+            r#"{"sql":null,"params":[{"name":"UserId","type":"STRING"}]}"#
+        );
+    }
+}
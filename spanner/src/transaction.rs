@@ -7,21 +7,62 @@ use google_cloud_gax::grpc::Status;
 use google_cloud_gax::retry::RetrySetting;
 use google_cloud_googleapis::spanner::v1::request_options::Priority;
 use google_cloud_googleapis::spanner::v1::{
-    execute_sql_request::QueryMode, execute_sql_request::QueryOptions as ExecuteQueryOptions, ExecuteSqlRequest,
-    ReadRequest, RequestOptions, TransactionSelector,
+    execute_sql_request::QueryMode, execute_sql_request::QueryOptions as ExecuteQueryOptions, result_set_stats,
+    ExecuteSqlRequest, ReadRequest, RequestOptions, ResultSetStats, TransactionSelector,
 };
 
 use crate::key::{Key, KeySet};
 use crate::reader::{Reader, RowIterator, StatementReader, TableReader};
 use crate::row::Row;
 use crate::session::ManagedSession;
-use crate::statement::Statement;
+use crate::statement::{with_param_type_hint, Statement};
 
-#[derive(Clone, Default)]
+/// RowCount is the number of rows modified by a DML statement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RowCount {
+    /// Standard DML returns an exact count of rows that were modified.
+    Exact(i64),
+    /// Partitioned DML does not offer exactly-once semantics, so it
+    /// returns a lower bound of the rows modified.
+    LowerBound(i64),
+}
+
+impl RowCount {
+    /// value returns the count regardless of whether it is exact or a lower bound.
+    pub fn value(&self) -> i64 {
+        match self {
+            RowCount::Exact(v) => *v,
+            RowCount::LowerBound(v) => *v,
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        matches!(self, RowCount::Exact(_))
+    }
+
+    pub(crate) fn from_stats(stats: Option<&ResultSetStats>) -> Option<RowCount> {
+        stats.and_then(|s| s.row_count.clone()).map(RowCount::from)
+    }
+}
+
+impl From<result_set_stats::RowCount> for RowCount {
+    fn from(value: result_set_stats::RowCount) -> Self {
+        match value {
+            result_set_stats::RowCount::RowCountExact(v) => RowCount::Exact(v),
+            result_set_stats::RowCount::RowCountLowerBound(v) => RowCount::LowerBound(v),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct CallOptions {
     /// Priority is the RPC priority to use for the read operation.
     pub priority: Option<Priority>,
     pub retry: Option<RetrySetting>,
+    /// Custom gRPC metadata sent with each attempt of the request in addition to the standard headers.
+    /// The keys must be lowercase ASCII and must not overwrite the standard headers.
+    /// The value of the key ending with `-bin` is base64 encoded on the wire.
+    pub extra_metadata: Vec<(String, String)>,
 }
 
 #[derive(Clone)]
@@ -33,6 +74,9 @@ pub struct ReadOptions {
     /// The maximum number of rows to read. A limit value less than 1 means no limit.
     pub limit: i64,
     pub call_options: CallOptions,
+    /// The number of PartialResultSets read ahead of the consumer in the background task.
+    /// None reads the next PartialResultSet only when the consumer requires it.
+    pub prefetch: Option<usize>,
 }
 
 impl Default for ReadOptions {
@@ -41,11 +85,12 @@ impl Default for ReadOptions {
             index: "".to_string(),
             limit: 0,
             call_options: CallOptions::default(),
+            prefetch: None,
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct QueryOptions {
     pub mode: QueryMode,
     pub optimizer_options: Option<ExecuteQueryOptions>,
@@ -85,6 +130,11 @@ pub struct QueryOptions {
     ///   }
     /// }
     pub enable_resume: bool,
+    /// The number of PartialResultSets read ahead of the consumer in the background task.
+    /// The larger value reduces the latency of the narrow rows, and the smaller value bounds
+    /// the memory usage of the wide rows when the consumer is slow.
+    /// None reads the next PartialResultSet only when the consumer requires it.
+    pub prefetch: Option<usize>,
 }
 
 impl Default for QueryOptions {
@@ -94,6 +144,7 @@ impl Default for QueryOptions {
             optimizer_options: None,
             call_options: CallOptions::default(),
             enable_resume: true,
+            prefetch: None,
         }
     }
 }
@@ -103,6 +154,8 @@ pub struct Transaction {
     // for returning ownership of session on before destroy
     pub(crate) sequence_number: AtomicI64,
     pub(crate) transaction_selector: TransactionSelector,
+    /// true for the read-write and partitioned DML transactions.
+    pub(crate) route_to_leader: bool,
 }
 
 impl Transaction {
@@ -131,28 +184,17 @@ impl Transaction {
         statement: Statement,
         options: QueryOptions,
     ) -> Result<RowIterator<'_, impl Reader>, Status> {
-        let request = ExecuteSqlRequest {
-            session: self.session.as_ref().unwrap().session.name.to_string(),
-            transaction: Some(self.transaction_selector.clone()),
-            sql: statement.sql,
-            params: Some(Struct {
-                fields: statement.params,
-            }),
-            param_types: statement.param_types,
-            resume_token: vec![],
-            query_mode: options.mode.into(),
-            partition_token: vec![],
-            seqno: 0,
-            query_options: options.optimizer_options,
-            request_options: Transaction::create_request_options(options.call_options.priority),
-            data_boost_enabled: false,
-        };
+        statement.validate()?;
+        let request = query_request(self.get_session_name(), self.transaction_selector.clone(), statement, &options);
         let session = self.session.as_mut().unwrap().deref_mut();
         let reader = StatementReader {
             enable_resume: options.enable_resume,
             request,
+            route_to_leader: self.route_to_leader,
         };
-        RowIterator::new(session, reader, Some(options.call_options)).await
+        RowIterator::new_with_prefetch(session, reader, Some(options.call_options), options.prefetch)
+            .await
+            .map_err(with_param_type_hint)
     }
 
     /// read returns a RowIterator for reading multiple rows from the database.
@@ -193,23 +235,21 @@ impl Transaction {
         key_set: impl Into<KeySet>,
         options: ReadOptions,
     ) -> Result<RowIterator<'_, impl Reader>, Status> {
-        let request = ReadRequest {
-            session: self.get_session_name(),
-            transaction: Some(self.transaction_selector.clone()),
-            table: table.to_string(),
-            index: options.index,
-            columns: columns.iter().map(|x| x.to_string()).collect(),
-            key_set: Some(key_set.into().inner),
-            limit: options.limit,
-            resume_token: vec![],
-            partition_token: vec![],
-            request_options: Transaction::create_request_options(options.call_options.priority),
-            data_boost_enabled: false,
-        };
-
+        let request = read_request(
+            self.get_session_name(),
+            self.transaction_selector.clone(),
+            table,
+            columns,
+            key_set.into(),
+            &options,
+        );
+        let route_to_leader = self.route_to_leader;
         let session = self.as_mut_session();
-        let reader = TableReader { request };
-        RowIterator::new(session, reader, Some(options.call_options)).await
+        let reader = TableReader {
+            request,
+            route_to_leader,
+        };
+        RowIterator::new_with_prefetch(session, reader, Some(options.call_options), options.prefetch).await
     }
 
     /// read returns a RowIterator for reading multiple rows from the database.
@@ -245,6 +285,48 @@ impl Transaction {
         reader.next().await
     }
 
+    /// read_rows reads the rows with the given primary keys and collects them into a Vec.
+    /// Keys that do not exist are skipped, so the result may be shorter than `keys`.
+    /// ```
+    /// use google_cloud_spanner::key::Key;
+    /// use google_cloud_spanner::client::Client;
+    /// use google_cloud_spanner::client::Error;
+    ///
+    /// async fn run(client: Client) -> Result<(), Error> {
+    ///     let mut tx = client.single().await?;
+    ///     let rows = tx.read_rows("Guild", &["GuildID", "OwnerUserID"], vec![
+    ///         Key::new(&"guild1"),
+    ///         Key::new(&"guild2")
+    ///     ]).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn read_rows(&mut self, table: &str, columns: &[&str], keys: Vec<Key>) -> Result<Vec<Row>, Status> {
+        self.read_rows_with_option(table, columns, keys, ReadOptions::default())
+            .await
+    }
+
+    /// read_rows reads the rows with the given primary keys and collects them into a Vec.
+    pub async fn read_rows_with_option(
+        &mut self,
+        table: &str,
+        columns: &[&str],
+        keys: Vec<Key>,
+        options: ReadOptions,
+    ) -> Result<Vec<Row>, Status> {
+        if keys.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut reader = self
+            .read_with_option(table, columns, KeySet::from(keys), options)
+            .await?;
+        let mut rows = vec![];
+        while let Some(row) = reader.next().await? {
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+
     pub(crate) fn get_session_name(&self) -> String {
         return self.session.as_ref().unwrap().session.name.to_string();
     }
@@ -259,3 +341,86 @@ impl Transaction {
         self.session.take()
     }
 }
+
+/// query_request creates the request of the query executed by the transaction.
+pub(crate) fn query_request(
+    session: String,
+    transaction_selector: TransactionSelector,
+    statement: Statement,
+    options: &QueryOptions,
+) -> ExecuteSqlRequest {
+    ExecuteSqlRequest {
+        session,
+        transaction: Some(transaction_selector),
+        sql: statement.sql,
+        params: Some(Struct {
+            fields: statement.params,
+        }),
+        param_types: statement.param_types,
+        resume_token: vec![],
+        query_mode: options.mode.into(),
+        partition_token: vec![],
+        seqno: 0,
+        query_options: options.optimizer_options.clone(),
+        request_options: Transaction::create_request_options(options.call_options.priority),
+        data_boost_enabled: false,
+        last_statement: false,
+    }
+}
+
+/// read_request creates the request of the read executed by the transaction.
+pub(crate) fn read_request(
+    session: String,
+    transaction_selector: TransactionSelector,
+    table: &str,
+    columns: &[&str],
+    key_set: KeySet,
+    options: &ReadOptions,
+) -> ReadRequest {
+    ReadRequest {
+        session,
+        transaction: Some(transaction_selector),
+        table: table.to_string(),
+        index: options.index.clone(),
+        columns: columns.iter().map(|x| x.to_string()).collect(),
+        key_set: Some(key_set.inner),
+        limit: options.limit,
+        resume_token: vec![],
+        partition_token: vec![],
+        request_options: Transaction::create_request_options(options.call_options.priority),
+        data_boost_enabled: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use google_cloud_googleapis::spanner::v1::{result_set_stats, ResultSetStats};
+
+    use crate::transaction::RowCount;
+
+    fn stats(row_count: Option<result_set_stats::RowCount>) -> ResultSetStats {
+        ResultSetStats {
+            query_plan: None,
+            query_stats: None,
+            row_count,
+        }
+    }
+
+    #[test]
+    fn test_row_count_from_stats() {
+        let exact = stats(Some(result_set_stats::RowCount::RowCountExact(10)));
+        let row_count = RowCount::from_stats(Some(&exact)).unwrap();
+        assert_eq!(row_count, RowCount::Exact(10));
+        assert!(row_count.is_exact());
+        assert_eq!(row_count.value(), 10);
+
+        let lower_bound = stats(Some(result_set_stats::RowCount::RowCountLowerBound(5)));
+        let row_count = RowCount::from_stats(Some(&lower_bound)).unwrap();
+        assert_eq!(row_count, RowCount::LowerBound(5));
+        assert!(!row_count.is_exact());
+        assert_eq!(row_count.value(), 5);
+
+        assert_eq!(RowCount::from_stats(Some(&stats(None))), None);
+        assert_eq!(RowCount::from_stats(None), None);
+    }
+}
@@ -1,5 +1,6 @@
 use std::ops::{Deref, DerefMut};
 use std::sync::atomic::AtomicI64;
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use time::OffsetDateTime;
@@ -10,11 +11,12 @@ use google_cloud_googleapis::spanner::v1::{
     PartitionQueryRequest, PartitionReadRequest, ReadRequest, TransactionOptions, TransactionSelector,
 };
 
-use crate::key::KeySet;
+use crate::key::{Key, KeySet};
 use crate::reader::{Reader, RowIterator, StatementReader, TableReader};
-use crate::session::ManagedSession;
-use crate::statement::Statement;
-use crate::transaction::{CallOptions, QueryOptions, ReadOptions, Transaction};
+use crate::row::Row;
+use crate::session::{ManagedSession, SharedSession};
+use crate::statement::{with_param_type_hint, Statement};
+use crate::transaction::{query_request, read_request, CallOptions, QueryOptions, ReadOptions, Transaction};
 use crate::value::TimestampBound;
 
 /// ReadOnlyTransaction provides a snapshot transaction with guaranteed
@@ -58,9 +60,12 @@ impl ReadOnlyTransaction {
                 sequence_number: AtomicI64::new(0),
                 transaction_selector: TransactionSelector {
                     selector: Some(transaction_selector::Selector::SingleUse(TransactionOptions {
+                        exclude_txn_from_change_streams: false,
+                        isolation_level: transaction_options::IsolationLevel::Unspecified.into(),
                         mode: Some(transaction_options::Mode::ReadOnly(tb.into())),
                     })),
                 },
+                route_to_leader: false,
             },
             rts: None,
         })
@@ -75,6 +80,8 @@ impl ReadOnlyTransaction {
         let request = BeginTransactionRequest {
             session: session.session.name.to_string(),
             options: Some(TransactionOptions {
+                exclude_txn_from_change_streams: false,
+                isolation_level: transaction_options::IsolationLevel::Unspecified.into(),
                 mode: Some(transaction_options::Mode::ReadOnly(tb.into())),
             }),
             request_options: Transaction::create_request_options(options.priority),
@@ -93,6 +100,7 @@ impl ReadOnlyTransaction {
                         transaction_selector: TransactionSelector {
                             selector: Some(transaction_selector::Selector::Id(tx.id)),
                         },
+                        route_to_leader: false,
                     },
                     rts: Some(OffsetDateTime::from(st)),
                 })
@@ -102,6 +110,151 @@ impl ReadOnlyTransaction {
     }
 }
 
+impl ReadOnlyTransaction {
+    /// into_shared converts the snapshot transaction into the transaction shared by the concurrent tasks.
+    pub(crate) fn into_shared(mut self) -> SharedReadOnlyTransaction {
+        SharedReadOnlyTransaction {
+            inner: Arc::new(SharedInner {
+                session: SharedSession::new(self.base_tx.take_session().unwrap()),
+                transaction_selector: self.base_tx.transaction_selector.clone(),
+                rts: self.rts,
+            }),
+        }
+    }
+}
+
+struct SharedInner {
+    session: SharedSession,
+    transaction_selector: TransactionSelector,
+    rts: Option<OffsetDateTime>,
+}
+
+/// SharedReadOnlyTransaction is the snapshot read-only transaction shared by the concurrent tasks.
+/// The queries and the reads take `&self` and the clones are cheap, so the reads at the same
+/// timestamp can run concurrently. Each stream uses its own copy of the session, and the session is
+/// returned to the pool when all the clones and the RowIterators are dropped.
+/// ```
+/// use futures_util::future::join_all;
+/// use google_cloud_spanner::client::{Client, Error};
+/// use google_cloud_spanner::statement::Statement;
+///
+/// async fn run(client: Client) -> Result<(), Error> {
+///     let tx = client.shared_read_only_transaction().await?;
+///     let tasks = (0..10_i64).map(|i| {
+///         let tx = tx.clone();
+///         async move {
+///             let mut stmt = Statement::new("SELECT * FROM UserItem WHERE ItemId = @ItemId");
+///             stmt.add_param("ItemId", &i);
+///             let mut rows = tx.query(stmt).await?;
+///             let mut count = 0;
+///             while let Some(_row) = rows.next().await? {
+///                 count += 1;
+///             }
+///             Ok::<_, Error>(count)
+///         }
+///     });
+///     let counts = join_all(tasks).await;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SharedReadOnlyTransaction {
+    inner: Arc<SharedInner>,
+}
+
+impl SharedReadOnlyTransaction {
+    /// rts returns the read timestamp of the snapshot.
+    pub fn rts(&self) -> Option<OffsetDateTime> {
+        self.inner.rts
+    }
+
+    /// query executes a query against the database at the snapshot. It returns a RowIterator for
+    /// retrieving the resulting rows.
+    pub async fn query(&self, statement: Statement) -> Result<RowIterator<'static, StatementReader>, Status> {
+        self.query_with_option(statement, QueryOptions::default()).await
+    }
+
+    /// query executes a query against the database at the snapshot. It returns a RowIterator for
+    /// retrieving the resulting rows.
+    pub async fn query_with_option(
+        &self,
+        statement: Statement,
+        options: QueryOptions,
+    ) -> Result<RowIterator<'static, StatementReader>, Status> {
+        statement.validate()?;
+        let request = query_request(
+            self.inner.session.name().to_string(),
+            self.inner.transaction_selector.clone(),
+            statement,
+            &options,
+        );
+        let reader = StatementReader {
+            enable_resume: options.enable_resume,
+            request,
+            route_to_leader: false,
+        };
+        let session = self.inner.session.detach();
+        RowIterator::new_detached(session, reader, Some(options.call_options), options.prefetch)
+            .await
+            .map_err(with_param_type_hint)
+    }
+
+    /// read returns a RowIterator for reading multiple rows from the database at the snapshot.
+    pub async fn read(
+        &self,
+        table: &str,
+        columns: &[&str],
+        key_set: impl Into<KeySet>,
+    ) -> Result<RowIterator<'static, TableReader>, Status> {
+        self.read_with_option(table, columns, key_set, ReadOptions::default())
+            .await
+    }
+
+    /// read returns a RowIterator for reading multiple rows from the database at the snapshot.
+    pub async fn read_with_option(
+        &self,
+        table: &str,
+        columns: &[&str],
+        key_set: impl Into<KeySet>,
+        options: ReadOptions,
+    ) -> Result<RowIterator<'static, TableReader>, Status> {
+        let request = read_request(
+            self.inner.session.name().to_string(),
+            self.inner.transaction_selector.clone(),
+            table,
+            columns,
+            key_set.into(),
+            &options,
+        );
+        let reader = TableReader {
+            request,
+            route_to_leader: false,
+        };
+        let session = self.inner.session.detach();
+        RowIterator::new_detached(session, reader, Some(options.call_options), options.prefetch).await
+    }
+
+    /// read_row reads the row with the given primary key at the snapshot.
+    pub async fn read_row(&self, table: &str, columns: &[&str], key: Key) -> Result<Option<Row>, Status> {
+        let mut reader = self.read(table, columns, KeySet::from(key)).await?;
+        reader.next().await
+    }
+
+    /// read_rows reads the rows with the given primary keys at the snapshot and collects them into a Vec.
+    /// Keys that do not exist are skipped, so the result may be shorter than `keys`.
+    pub async fn read_rows(&self, table: &str, columns: &[&str], keys: Vec<Key>) -> Result<Vec<Row>, Status> {
+        if keys.is_empty() {
+            return Ok(vec![]);
+        }
+        let mut reader = self.read(table, columns, KeySet::from(keys)).await?;
+        let mut rows = vec![];
+        while let Some(row) = reader.next().await? {
+            rows.push(row);
+        }
+        Ok(rows)
+    }
+}
+
 pub struct Partition<T: Reader> {
     pub reader: T,
 }
@@ -202,6 +355,7 @@ impl BatchReadOnlyTransaction {
                             request_options: Transaction::create_request_options(ro.call_options.priority),
                             data_boost_enabled,
                         },
+                        route_to_leader: false,
                     },
                 })
                 .collect()),
@@ -224,6 +378,7 @@ impl BatchReadOnlyTransaction {
         qo: QueryOptions,
         data_boost_enabled: bool,
     ) -> Result<Vec<Partition<StatementReader>>, Status> {
+        stmt.validate()?;
         let request = PartitionQueryRequest {
             session: self.get_session_name(),
             transaction: Some(self.transaction_selector.clone()),
@@ -262,7 +417,9 @@ impl BatchReadOnlyTransaction {
                             query_options: qo.optimizer_options.clone(),
                             request_options: Transaction::create_request_options(qo.call_options.priority),
                             data_boost_enabled,
+                            last_statement: false,
                         },
+                        route_to_leader: false,
                     },
                 })
                 .collect()),
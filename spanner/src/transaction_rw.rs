@@ -1,6 +1,8 @@
 use std::ops::Deref;
 use std::ops::DerefMut;
 use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 use prost_types::Struct;
 
@@ -8,20 +10,26 @@ use google_cloud_gax::grpc::{Code, Status};
 use google_cloud_gax::retry::{RetrySetting, TryAs};
 use google_cloud_googleapis::spanner::v1::commit_request::Transaction::TransactionId;
 use google_cloud_googleapis::spanner::v1::{
-    commit_request, execute_batch_dml_request, result_set_stats, transaction_options, transaction_selector,
-    BeginTransactionRequest, CommitRequest, CommitResponse, ExecuteBatchDmlRequest, ExecuteSqlRequest, Mutation,
-    ResultSetStats, RollbackRequest, TransactionOptions, TransactionSelector,
+    commit_request, execute_batch_dml_request, transaction_options, transaction_selector, BeginTransactionRequest,
+    CommitRequest, CommitResponse, ExecuteBatchDmlRequest, ExecuteSqlRequest, Mutation, ResultSetStats,
+    RollbackRequest, TransactionOptions, TransactionSelector,
 };
 
+use crate::retry::server_retry_delay;
 use crate::session::ManagedSession;
-use crate::statement::Statement;
-use crate::transaction::{CallOptions, QueryOptions, Transaction};
+use crate::statement::{with_param_type_hint, Statement};
+use crate::transaction::{CallOptions, QueryOptions, RowCount, Transaction};
 use crate::value::Timestamp;
 
+/// CommitHook is invoked with the mutations right before they are sent by each commit attempt,
+/// for example to write the audit log with `MutationRecord`. The retried transaction invokes it again.
+pub type CommitHook = Arc<dyn Fn(&[Mutation]) + Send + Sync>;
+
 #[derive(Clone, Default)]
 pub struct CommitOptions {
     pub return_commit_stats: bool,
     pub call_options: CallOptions,
+    pub on_commit: Option<CommitHook>,
 }
 
 /// ReadWriteTransaction provides a locking read-write transaction.
@@ -96,6 +104,46 @@ impl DerefMut for ReadWriteTransaction {
     }
 }
 
+/// IsolationLevel is the isolation level of the read-write transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum IsolationLevel {
+    /// The isolation level is not sent, so the server uses SERIALIZABLE.
+    #[default]
+    Unspecified,
+    Serializable,
+    /// The reads observe the snapshot at the beginning of the transaction and only the write-write
+    /// conflicts are detected, which reduces the aborts of the read-mostly transactions.
+    /// The emulator and some backends don't support it.
+    RepeatableRead,
+}
+
+impl From<IsolationLevel> for transaction_options::IsolationLevel {
+    fn from(value: IsolationLevel) -> Self {
+        match value {
+            IsolationLevel::Unspecified => transaction_options::IsolationLevel::Unspecified,
+            IsolationLevel::Serializable => transaction_options::IsolationLevel::Serializable,
+            IsolationLevel::RepeatableRead => transaction_options::IsolationLevel::RepeatableRead,
+        }
+    }
+}
+
+/// with_isolation_level_hint adds the hint to the error caused by the isolation level the server doesn't support.
+pub(crate) fn with_isolation_level_hint(status: Status, isolation_level: IsolationLevel) -> Status {
+    let unsupported = matches!(status.code(), Code::Unimplemented | Code::InvalidArgument);
+    if unsupported && isolation_level != IsolationLevel::Unspecified {
+        Status::new(
+            status.code(),
+            format!(
+                "{}: the isolation level {:?} may not be supported by the server, unset the isolation level of the transaction",
+                status.message(),
+                isolation_level
+            ),
+        )
+    } else {
+        status
+    }
+}
+
 pub struct BeginError {
     pub status: Status,
     pub session: ManagedSession,
@@ -103,10 +151,20 @@ pub struct BeginError {
 
 impl ReadWriteTransaction {
     pub async fn begin(session: ManagedSession, options: CallOptions) -> Result<ReadWriteTransaction, BeginError> {
+        ReadWriteTransaction::begin_with_isolation_level(session, options, IsolationLevel::Unspecified).await
+    }
+
+    /// begin_with_isolation_level starts the read-write transaction with the isolation level.
+    pub async fn begin_with_isolation_level(
+        session: ManagedSession,
+        options: CallOptions,
+        isolation_level: IsolationLevel,
+    ) -> Result<ReadWriteTransaction, BeginError> {
         ReadWriteTransaction::begin_internal(
             session,
             transaction_options::Mode::ReadWrite(transaction_options::ReadWrite::default()),
             options,
+            isolation_level,
         )
         .await
     }
@@ -119,6 +177,7 @@ impl ReadWriteTransaction {
             session,
             transaction_options::Mode::PartitionedDml(transaction_options::PartitionedDml {}),
             options,
+            IsolationLevel::Unspecified,
         )
         .await
     }
@@ -127,17 +186,25 @@ impl ReadWriteTransaction {
         mut session: ManagedSession,
         mode: transaction_options::Mode,
         options: CallOptions,
+        isolation_level: IsolationLevel,
     ) -> Result<ReadWriteTransaction, BeginError> {
         let request = BeginTransactionRequest {
             session: session.session.name.to_string(),
-            options: Some(TransactionOptions { mode: Some(mode) }),
+            options: Some(TransactionOptions {
+                exclude_txn_from_change_streams: false,
+                isolation_level: transaction_options::IsolationLevel::from(isolation_level).into(),
+                mode: Some(mode),
+            }),
             request_options: Transaction::create_request_options(options.priority),
         };
         let result = session.spanner_client.begin_transaction(request, options.retry).await;
         let response = match session.invalidate_if_needed(result).await {
             Ok(response) => response,
             Err(err) => {
-                return Err(BeginError { status: err, session });
+                return Err(BeginError {
+                    status: with_isolation_level_hint(err, isolation_level),
+                    session,
+                });
             }
         };
         let tx = response.into_inner();
@@ -148,6 +215,7 @@ impl ReadWriteTransaction {
                 transaction_selector: TransactionSelector {
                     selector: Some(transaction_selector::Selector::Id(tx.id.clone())),
                 },
+                route_to_leader: true,
             },
             tx_id: tx.id,
             wb: vec![],
@@ -158,36 +226,63 @@ impl ReadWriteTransaction {
         self.wb.extend_from_slice(&ms)
     }
 
-    pub async fn update(&mut self, stmt: Statement) -> Result<i64, Status> {
+    pub async fn update(&mut self, stmt: Statement) -> Result<RowCount, Status> {
         self.update_with_option(stmt, QueryOptions::default()).await
     }
 
-    pub async fn update_with_option(&mut self, stmt: Statement, options: QueryOptions) -> Result<i64, Status> {
-        let request = ExecuteSqlRequest {
-            session: self.get_session_name(),
-            transaction: Some(self.transaction_selector.clone()),
-            sql: stmt.sql.to_string(),
-            data_boost_enabled: false,
-            params: Some(prost_types::Struct { fields: stmt.params }),
-            param_types: stmt.param_types,
-            resume_token: vec![],
-            query_mode: options.mode.into(),
-            partition_token: vec![],
-            seqno: self.sequence_number.fetch_add(1, Ordering::Relaxed),
-            query_options: options.optimizer_options,
-            request_options: Transaction::create_request_options(options.call_options.priority),
-        };
+    pub async fn update_with_option(&mut self, stmt: Statement, options: QueryOptions) -> Result<RowCount, Status> {
+        self.execute_update(stmt, options, false).await
+    }
+
+    /// update_last executes the DML statement as the last statement of the transaction.
+    /// Spanner can begin the commit processing while the statement is executed, which reduces the commit latency.
+    ///
+    /// The caller must commit the transaction immediately after this call:
+    /// any other read, query or DML against this transaction is rejected by the server.
+    /// Some errors (e.g. unique constraint violations) may be deferred until the commit,
+    /// so the result of the statement should not be trusted until the commit succeeds.
+    pub async fn update_last(&mut self, stmt: Statement) -> Result<RowCount, Status> {
+        self.update_last_with_option(stmt, QueryOptions::default()).await
+    }
+
+    /// update_last_with_option is the same as update_last but with options.
+    pub async fn update_last_with_option(
+        &mut self,
+        stmt: Statement,
+        options: QueryOptions,
+    ) -> Result<RowCount, Status> {
+        self.execute_update(stmt, options, true).await
+    }
+
+    async fn execute_update(
+        &mut self,
+        stmt: Statement,
+        options: QueryOptions,
+        last_statement: bool,
+    ) -> Result<RowCount, Status> {
+        stmt.validate()?;
+        let request = update_request(
+            self.get_session_name(),
+            self.transaction_selector.clone(),
+            self.sequence_number.fetch_add(1, Ordering::Relaxed),
+            stmt,
+            &options,
+            last_statement,
+        );
 
         let session = self.as_mut_session();
         let result = session
             .spanner_client
-            .execute_sql(request, options.call_options.retry)
+            .execute_sql_with_routing(request, options.call_options.retry, true, &options.call_options.extra_metadata)
             .await;
-        let response = session.invalidate_if_needed(result).await?;
+        let response = session
+            .invalidate_if_needed(result)
+            .await
+            .map_err(with_param_type_hint)?;
         Ok(extract_row_count(response.into_inner().stats))
     }
 
-    pub async fn batch_update(&mut self, stmt: Vec<Statement>) -> Result<Vec<i64>, Status> {
+    pub async fn batch_update(&mut self, stmt: Vec<Statement>) -> Result<Vec<RowCount>, Status> {
         self.batch_update_with_option(stmt, QueryOptions::default()).await
     }
 
@@ -195,28 +290,52 @@ impl ReadWriteTransaction {
         &mut self,
         stmt: Vec<Statement>,
         options: QueryOptions,
-    ) -> Result<Vec<i64>, Status> {
-        let request = ExecuteBatchDmlRequest {
-            session: self.get_session_name(),
-            transaction: Some(self.transaction_selector.clone()),
-            seqno: self.sequence_number.fetch_add(1, Ordering::Relaxed),
-            request_options: Transaction::create_request_options(options.call_options.priority),
-            statements: stmt
-                .into_iter()
-                .map(|x| execute_batch_dml_request::Statement {
-                    sql: x.sql,
-                    params: Some(Struct { fields: x.params }),
-                    param_types: x.param_types,
-                })
-                .collect(),
-        };
+    ) -> Result<Vec<RowCount>, Status> {
+        self.execute_batch_update(stmt, options, false).await
+    }
+
+    /// batch_update_last executes the DML statements as the last statements of the transaction.
+    /// The same contract as update_last applies: commit the transaction immediately after this call.
+    pub async fn batch_update_last(&mut self, stmt: Vec<Statement>) -> Result<Vec<RowCount>, Status> {
+        self.batch_update_last_with_option(stmt, QueryOptions::default()).await
+    }
+
+    /// batch_update_last_with_option is the same as batch_update_last but with options.
+    pub async fn batch_update_last_with_option(
+        &mut self,
+        stmt: Vec<Statement>,
+        options: QueryOptions,
+    ) -> Result<Vec<RowCount>, Status> {
+        self.execute_batch_update(stmt, options, true).await
+    }
+
+    async fn execute_batch_update(
+        &mut self,
+        stmt: Vec<Statement>,
+        options: QueryOptions,
+        last_statements: bool,
+    ) -> Result<Vec<RowCount>, Status> {
+        for stmt in &stmt {
+            stmt.validate()?;
+        }
+        let request = batch_update_request(
+            self.get_session_name(),
+            self.transaction_selector.clone(),
+            self.sequence_number.fetch_add(1, Ordering::Relaxed),
+            stmt,
+            &options,
+            last_statements,
+        );
 
         let session = self.as_mut_session();
         let result = session
             .spanner_client
-            .execute_batch_dml(request, options.call_options.retry)
+            .execute_batch_dml_with_metadata(request, options.call_options.retry, &options.call_options.extra_metadata)
             .await;
-        let response = session.invalidate_if_needed(result).await?;
+        let response = session
+            .invalidate_if_needed(result)
+            .await
+            .map_err(with_param_type_hint)?;
         Ok(response
             .into_inner()
             .result_sets
@@ -315,12 +434,116 @@ impl ReadWriteTransaction {
     }
 }
 
+/// TxError is the error of the transaction committed or rolled back by `TxHandle`.
+#[derive(thiserror::Error, Debug)]
+pub enum TxError {
+    /// Spanner aborted the transaction. Nothing is written, so the transaction can be replayed
+    /// from the beginning with a new `TxHandle` after `retry_delay` suggested by the server.
+    #[error("transaction aborted: retry_delay={retry_delay:?}, status={status}")]
+    Aborted {
+        status: Status,
+        retry_delay: Option<Duration>,
+    },
+    #[error(transparent)]
+    GRPC(Status),
+}
+
+impl From<Status> for TxError {
+    fn from(status: Status) -> Self {
+        if status.code() == Code::Aborted {
+            let retry_delay = server_retry_delay(&status);
+            TxError::Aborted { status, retry_delay }
+        } else {
+            TxError::GRPC(status)
+        }
+    }
+}
+
+impl TxError {
+    /// is_aborted returns true if the transaction can be replayed.
+    pub fn is_aborted(&self) -> bool {
+        matches!(self, TxError::Aborted { .. })
+    }
+}
+
+/// TxHandle is the read-write transaction committed or rolled back explicitly instead of the closure.
+/// It is created by `Client::begin_read_write` and owns the session until it is committed or rolled back.
+///
+/// The queries, the reads and the DMLs are available through `ReadWriteTransaction`.
+/// Dropping the handle without `commit` or `rollback` rolls back the transaction in the background
+/// and returns the session to the pool.
+///
+/// ABORTED is not replayed automatically. Convert the errors of the statements with `TxError::from`
+/// and replay the transaction with a new handle when `TxError::is_aborted` is true.
+pub struct TxHandle {
+    tx: Option<ReadWriteTransaction>,
+    commit_options: CommitOptions,
+}
+
+impl TxHandle {
+    pub(crate) fn new(tx: ReadWriteTransaction, commit_options: CommitOptions) -> Self {
+        Self {
+            tx: Some(tx),
+            commit_options,
+        }
+    }
+
+    /// commit commits the transaction and returns the commit timestamp.
+    pub async fn commit(mut self) -> Result<Option<Timestamp>, TxError> {
+        let mut tx = self.tx.take().unwrap();
+        // The transaction is not rolled back on the commit error, the same as `ReadWriteTransaction::end`.
+        let response = tx.commit(self.commit_options.clone()).await?;
+        Ok(response.commit_timestamp.map(|e| e.into()))
+    }
+
+    /// rollback rolls back the transaction.
+    pub async fn rollback(mut self) -> Result<(), TxError> {
+        let mut tx = self.tx.take().unwrap();
+        tx.rollback(self.commit_options.call_options.retry.clone()).await?;
+        Ok(())
+    }
+}
+
+impl Deref for TxHandle {
+    type Target = ReadWriteTransaction;
+
+    fn deref(&self) -> &Self::Target {
+        self.tx.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for TxHandle {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.tx.as_mut().unwrap()
+    }
+}
+
+impl Drop for TxHandle {
+    fn drop(&mut self) {
+        let mut tx = match self.tx.take() {
+            Some(tx) => tx,
+            None => return,
+        };
+        // Without the runtime, the session is returned to the pool and the transaction expires on the server.
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                if let Err(e) = tx.rollback(None).await {
+                    tracing::debug!("failed to rollback the dropped transaction: {e:?}");
+                }
+            });
+        }
+    }
+}
+
 pub(crate) async fn commit(
     session: &mut ManagedSession,
     ms: Vec<Mutation>,
     tx: commit_request::Transaction,
     commit_options: CommitOptions,
 ) -> Result<CommitResponse, Status> {
+    if let Some(hook) = &commit_options.on_commit {
+        hook(&ms);
+    }
     let request = CommitRequest {
         session: session.session.name.to_string(),
         mutations: ms,
@@ -330,7 +553,11 @@ pub(crate) async fn commit(
     };
     let result = session
         .spanner_client
-        .commit(request, commit_options.call_options.retry)
+        .commit_with_metadata(
+            request,
+            commit_options.call_options.retry,
+            &commit_options.call_options.extra_metadata,
+        )
         .await;
     let response = session.invalidate_if_needed(result).await;
     match response {
@@ -339,15 +566,146 @@ pub(crate) async fn commit(
     }
 }
 
-fn extract_row_count(rs: Option<ResultSetStats>) -> i64 {
-    match rs {
-        Some(o) => match o.row_count {
-            Some(o) => match o {
-                result_set_stats::RowCount::RowCountExact(v) => v,
-                result_set_stats::RowCount::RowCountLowerBound(v) => v,
-            },
-            None => 0,
-        },
-        None => 0,
+fn update_request(
+    session: String,
+    transaction: TransactionSelector,
+    seqno: i64,
+    stmt: Statement,
+    options: &QueryOptions,
+    last_statement: bool,
+) -> ExecuteSqlRequest {
+    ExecuteSqlRequest {
+        session,
+        transaction: Some(transaction),
+        sql: stmt.sql.to_string(),
+        data_boost_enabled: false,
+        params: Some(prost_types::Struct { fields: stmt.params }),
+        param_types: stmt.param_types,
+        resume_token: vec![],
+        query_mode: options.mode.into(),
+        partition_token: vec![],
+        seqno,
+        query_options: options.optimizer_options.clone(),
+        request_options: Transaction::create_request_options(options.call_options.priority),
+        last_statement,
+    }
+}
+
+fn batch_update_request(
+    session: String,
+    transaction: TransactionSelector,
+    seqno: i64,
+    stmt: Vec<Statement>,
+    options: &QueryOptions,
+    last_statements: bool,
+) -> ExecuteBatchDmlRequest {
+    ExecuteBatchDmlRequest {
+        session,
+        transaction: Some(transaction),
+        seqno,
+        request_options: Transaction::create_request_options(options.call_options.priority),
+        statements: stmt
+            .into_iter()
+            .map(|x| execute_batch_dml_request::Statement {
+                sql: x.sql,
+                params: Some(Struct { fields: x.params }),
+                param_types: x.param_types,
+            })
+            .collect(),
+        last_statements,
+    }
+}
+
+fn extract_row_count(rs: Option<ResultSetStats>) -> RowCount {
+    RowCount::from_stats(rs.as_ref()).unwrap_or(RowCount::Exact(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use google_cloud_gax::grpc::{Code, Status};
+    use google_cloud_googleapis::spanner::v1::{transaction_options, TransactionSelector};
+
+    use crate::statement::Statement;
+    use crate::transaction::QueryOptions;
+    use crate::transaction_rw::{batch_update_request, update_request, with_isolation_level_hint, IsolationLevel};
+
+    #[test]
+    fn test_isolation_level() {
+        let level = transaction_options::IsolationLevel::from(IsolationLevel::RepeatableRead);
+        assert_eq!(level.as_str_name(), "REPEATABLE_READ");
+        assert_eq!(
+            i32::from(transaction_options::IsolationLevel::from(IsolationLevel::default())),
+            0
+        );
+
+        let status = Status::new(Code::InvalidArgument, "unsupported field");
+        let status = with_isolation_level_hint(status, IsolationLevel::RepeatableRead);
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(status.message().contains("unset the isolation level"), "{}", status.message());
+
+        let status = Status::new(Code::Unimplemented, "unsupported");
+        let status = with_isolation_level_hint(status, IsolationLevel::Unspecified);
+        assert_eq!(status.message(), "unsupported");
+
+        let status = Status::new(Code::Aborted, "aborted");
+        let status = with_isolation_level_hint(status, IsolationLevel::Serializable);
+        assert_eq!(status.message(), "aborted");
+    }
+
+    #[test]
+    fn test_update_request_last_statement() {
+        let options = QueryOptions::default();
+        let request = update_request(
+            "session".to_string(),
+            TransactionSelector::default(),
+            1,
+            Statement::new("UPDATE User SET Premium = true"),
+            &options,
+            false,
+        );
+        assert!(!request.last_statement);
+        assert_eq!(request.seqno, 1);
+
+        let request = update_request(
+            "session".to_string(),
+            TransactionSelector::default(),
+            2,
+            Statement::new("UPDATE User SET Premium = true"),
+            &options,
+            true,
+        );
+        assert!(request.last_statement);
+        assert_eq!(request.sql, "UPDATE User SET Premium = true");
+    }
+
+    #[test]
+    fn test_batch_update_request_last_statements() {
+        let options = QueryOptions::default();
+        let stmts = || {
+            vec![
+                Statement::new("UPDATE User SET Premium = true"),
+                Statement::new("DELETE FROM User WHERE Premium = false"),
+            ]
+        };
+        let request = batch_update_request(
+            "session".to_string(),
+            TransactionSelector::default(),
+            1,
+            stmts(),
+            &options,
+            false,
+        );
+        assert!(!request.last_statements);
+        assert_eq!(request.statements.len(), 2);
+
+        let request = batch_update_request(
+            "session".to_string(),
+            TransactionSelector::default(),
+            2,
+            stmts(),
+            &options,
+            true,
+        );
+        assert!(request.last_statements);
     }
 }
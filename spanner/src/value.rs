@@ -1,9 +1,21 @@
+use std::fmt::{Display, Formatter};
 use std::ops::Deref;
+use std::str::FromStr;
 use std::time::Duration;
 
+use base64::prelude::*;
+use prost_types::value::Kind;
+use prost_types::{ListValue, Struct};
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+use time::{Date, OffsetDateTime};
+
 use google_cloud_googleapis::spanner::v1::transaction_options::read_only::TimestampBound as InternalTimestampBound;
 use google_cloud_googleapis::spanner::v1::transaction_options::ReadOnly;
 
+use crate::bigdecimal::BigDecimal;
+use crate::statement::ToKind;
+
 #[derive(Clone, PartialEq, Eq)]
 pub struct Timestamp {
     /// Represents seconds of UTC time since Unix epoch
@@ -35,7 +47,21 @@ impl From<prost_types::Timestamp> for Timestamp {
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+/// COMMIT_TIMESTAMP is the placeholder value replaced with the commit timestamp by Cloud Spanner.
+pub const COMMIT_TIMESTAMP: &str = "spanner.commit_timestamp()";
+
+/// CommitTimestamp writes the commit timestamp of the transaction to the TIMESTAMP column
+/// with the `allow_commit_timestamp` option in the mutations.
+/// It is encoded as `COMMIT_TIMESTAMP` regardless of the timestamp, and the timestamp is set when it is read from the row.
+///
+/// Use `PENDING_COMMIT_TIMESTAMP()` in the DML instead, the statement with CommitTimestamp parameters fails with INVALID_ARGUMENT.
+/// ```
+/// use google_cloud_spanner::mutation::insert;
+/// use google_cloud_spanner::value::CommitTimestamp;
+///
+/// let mutation = insert("User", &["UserId", "UpdatedAt"], &[&"user1", &CommitTimestamp::new()]);
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
 pub struct CommitTimestamp {
     pub(crate) timestamp: time::OffsetDateTime,
 }
@@ -68,6 +94,20 @@ impl From<CommitTimestamp> for time::OffsetDateTime {
     }
 }
 
+/// TimestampColumn is implemented by the field types of the TIMESTAMP column.
+/// The field with `#[spanner(commit_timestamp)]` of the derive macro must implement it.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a TIMESTAMP column type",
+    note = "`#[spanner(commit_timestamp)]` requires `OffsetDateTime`, `CommitTimestamp` or `Option` of them"
+)]
+pub trait TimestampColumn {}
+
+impl TimestampColumn for time::OffsetDateTime {}
+
+impl TimestampColumn for CommitTimestamp {}
+
+impl<T: TimestampColumn> TimestampColumn for Option<T> {}
+
 #[derive(Clone)]
 pub struct TimestampBound {
     inner: InternalTimestampBound,
@@ -109,3 +149,443 @@ impl From<TimestampBound> for ReadOnly {
         }
     }
 }
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+const NANOS_PER_MINUTE: i128 = 60 * NANOS_PER_SECOND;
+const NANOS_PER_HOUR: i128 = 60 * NANOS_PER_MINUTE;
+const NANOS_PER_DAY: i128 = 24 * NANOS_PER_HOUR;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum IntervalError {
+    #[error("invalid interval: {0}")]
+    Parse(String),
+    #[error("the interval has the months component which has no fixed length")]
+    HasMonths,
+    #[error("the interval is out of range")]
+    OutOfRange,
+}
+
+/// Interval is the INTERVAL type of Spanner.
+/// The months, the days and the nanoseconds are kept separately because a month and a day have no fixed length,
+/// so it is not representable by `Duration`.
+/// It is encoded in the ISO8601 duration format like `P1Y2M3DT4H5M6.5S`.
+/// ```
+/// use google_cloud_spanner::value::Interval;
+///
+/// let interval: Interval = "P1Y2M3DT4H5M6.5S".parse().unwrap();
+/// assert_eq!(interval, Interval::new(14, 3, 14_706_500_000_000));
+/// assert_eq!(interval.to_string(), "P1Y2M3DT4H5M6.5S");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub nanos: i128,
+}
+
+impl Interval {
+    pub fn new(months: i32, days: i32, nanos: i128) -> Self {
+        Self { months, days, nanos }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.months == 0 && self.days == 0 && self.nanos == 0
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "P0Y");
+        }
+        write!(f, "P")?;
+        let (years, months) = (self.months / 12, self.months % 12);
+        if years != 0 {
+            write!(f, "{years}Y")?;
+        }
+        if months != 0 {
+            write!(f, "{months}M")?;
+        }
+        if self.days != 0 {
+            write!(f, "{}D", self.days)?;
+        }
+        if self.nanos != 0 {
+            write!(f, "T")?;
+            let hours = self.nanos / NANOS_PER_HOUR;
+            let minutes = self.nanos % NANOS_PER_HOUR / NANOS_PER_MINUTE;
+            let rest = self.nanos % NANOS_PER_MINUTE;
+            if hours != 0 {
+                write!(f, "{hours}H")?;
+            }
+            if minutes != 0 {
+                write!(f, "{minutes}M")?;
+            }
+            if rest != 0 {
+                let sign = if rest < 0 { "-" } else { "" };
+                let (seconds, fraction) = ((rest / NANOS_PER_SECOND).abs(), (rest % NANOS_PER_SECOND).abs());
+                write!(f, "{sign}{seconds}")?;
+                if fraction != 0 {
+                    write!(f, ".{}", format!("{fraction:09}").trim_end_matches('0'))?;
+                }
+                write!(f, "S")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Interval {
+    type Err = IntervalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || IntervalError::Parse(s.to_string());
+        let rest = s.strip_prefix('P').ok_or_else(err)?;
+        let (date, time) = match rest.split_once('T') {
+            Some((_, "")) => return Err(err()),
+            Some((date, time)) => (date, time),
+            None => (rest, ""),
+        };
+        if date.is_empty() && time.is_empty() {
+            return Err(err());
+        }
+
+        let mut months: i64 = 0;
+        let mut days: i64 = 0;
+        let mut nanos: i128 = 0;
+        for (is_date, part, units) in [(true, date, "YMD"), (false, time, "HMS")] {
+            let mut last_unit = None;
+            let mut remaining = part;
+            while !remaining.is_empty() {
+                let end = remaining.find(|c: char| c.is_ascii_alphabetic()).ok_or_else(err)?;
+                let (number, unit) = (&remaining[..end], remaining.as_bytes()[end] as char);
+                remaining = &remaining[end + 1..];
+                let position = units.find(unit).ok_or_else(err)?;
+                if last_unit.is_some_and(|last| position <= last) {
+                    return Err(err());
+                }
+                last_unit = Some(position);
+
+                let (whole, fraction) = match number.split_once(['.', ',']) {
+                    Some((_, fraction)) if unit != 'S' || fraction.is_empty() || fraction.len() > 9 => {
+                        return Err(err())
+                    }
+                    Some((whole, fraction)) => (whole, Some(fraction)),
+                    None => (number, None),
+                };
+                let negative = whole.starts_with('-');
+                let digits = whole.trim_start_matches(['-', '+']);
+                if digits.is_empty() && fraction.is_none() || whole.len() - digits.len() > 1 {
+                    return Err(err());
+                }
+                let mut value: i64 = if digits.is_empty() {
+                    0
+                } else {
+                    digits.parse::<i64>().map_err(|_| IntervalError::OutOfRange)?
+                };
+                if negative {
+                    value = -value;
+                }
+                let (months_delta, days_delta, nanos_delta) = match (is_date, unit) {
+                    (true, 'Y') => (value.checked_mul(12).ok_or(IntervalError::OutOfRange)?, 0, 0),
+                    (true, 'M') => (value, 0, 0),
+                    (true, _) => (0, value, 0),
+                    (false, 'H') => (0, 0, value as i128 * NANOS_PER_HOUR),
+                    (false, 'M') => (0, 0, value as i128 * NANOS_PER_MINUTE),
+                    (false, _) => {
+                        let fraction_nanos = match fraction {
+                            Some(f) => format!("{f:0<9}").parse::<i128>().map_err(|_| err())?,
+                            None => 0,
+                        };
+                        let fraction_nanos = if negative { -fraction_nanos } else { fraction_nanos };
+                        (0, 0, value as i128 * NANOS_PER_SECOND + fraction_nanos)
+                    }
+                };
+                months = months.checked_add(months_delta).ok_or(IntervalError::OutOfRange)?;
+                days = days.checked_add(days_delta).ok_or(IntervalError::OutOfRange)?;
+                nanos += nanos_delta;
+            }
+        }
+        Ok(Interval {
+            months: i32::try_from(months).map_err(|_| IntervalError::OutOfRange)?,
+            days: i32::try_from(days).map_err(|_| IntervalError::OutOfRange)?,
+            nanos,
+        })
+    }
+}
+
+impl From<time::Duration> for Interval {
+    fn from(value: time::Duration) -> Self {
+        Interval::new(0, 0, value.whole_nanoseconds())
+    }
+}
+
+/// The interval with the months can't be converted. A day is treated as 24 hours.
+impl TryFrom<Interval> for time::Duration {
+    type Error = IntervalError;
+
+    fn try_from(value: Interval) -> Result<Self, Self::Error> {
+        if value.months != 0 {
+            return Err(IntervalError::HasMonths);
+        }
+        let nanos = value.days as i128 * NANOS_PER_DAY + value.nanos;
+        let seconds = i64::try_from(nanos / NANOS_PER_SECOND).map_err(|_| IntervalError::OutOfRange)?;
+        Ok(time::Duration::new(seconds, (nanos % NANOS_PER_SECOND) as i32))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Interval {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Interval {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Value is a dynamically typed Spanner value.
+/// It is useful to handle the row whose schema is not known at compile time.
+/// ```
+/// use google_cloud_spanner::client::{Client, Error};
+/// use google_cloud_spanner::statement::Statement;
+///
+/// async fn run(client: Client) -> Result<(), Error> {
+///     let mut tx = client.single().await?;
+///     let mut iter = tx.query(Statement::new("SELECT * FROM Guild")).await?;
+///     while let Some(row) = iter.next().await? {
+///         for (column, value) in row.try_to_map()? {
+///             println!("{column}={value}");
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int64(i64),
+    Float64(f64),
+    Numeric(BigDecimal),
+    String(String),
+    Bytes(Vec<u8>),
+    /// JSON text.
+    Json(String),
+    Timestamp(OffsetDateTime),
+    Date(Date),
+    Interval(Interval),
+    Array(Vec<Value>),
+    /// Fields of the STRUCT in declared order.
+    Struct(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// field returns the value of the STRUCT field by name.
+    /// None is returned if the value is not a STRUCT or the field is not found.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Value::Struct(fields) => fields.iter().find(|(n, _)| n == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// to_kind encodes the value in the same way as `ToKind` of the corresponding Rust type.
+    pub fn to_kind(&self) -> Kind {
+        match self {
+            Value::Null => Kind::NullValue(prost_types::NullValue::NullValue.into()),
+            Value::Bool(v) => v.to_kind(),
+            Value::Int64(v) => v.to_kind(),
+            Value::Float64(v) => v.to_kind(),
+            Value::Numeric(v) => v.to_kind(),
+            Value::String(v) => v.to_kind(),
+            Value::Bytes(v) => v.to_kind(),
+            Value::Json(v) => v.to_kind(),
+            Value::Timestamp(v) => v.to_kind(),
+            Value::Date(v) => v.to_kind(),
+            Value::Interval(v) => v.to_kind(),
+            Value::Array(values) => Kind::ListValue(ListValue {
+                values: values
+                    .iter()
+                    .map(|v| prost_types::Value {
+                        kind: Some(v.to_kind()),
+                    })
+                    .collect(),
+            }),
+            Value::Struct(fields) => Kind::StructValue(Struct {
+                fields: fields
+                    .iter()
+                    .map(|(name, v)| {
+                        (
+                            name.to_string(),
+                            prost_types::Value {
+                                kind: Some(v.to_kind()),
+                            },
+                        )
+                    })
+                    .collect(),
+            }),
+        }
+    }
+
+    pub(crate) fn fmt_nested(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(v) => write!(f, "{v:?}"),
+            v => write!(f, "{v}"),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "NULL"),
+            Value::Bool(v) => write!(f, "{v}"),
+            Value::Int64(v) => write!(f, "{v}"),
+            Value::Float64(v) => write!(f, "{v}"),
+            Value::Numeric(v) => write!(f, "{v}"),
+            Value::String(v) => write!(f, "{v}"),
+            Value::Bytes(v) => write!(f, "{}", BASE64_STANDARD.encode(v)),
+            Value::Json(v) => write!(f, "{v}"),
+            Value::Timestamp(v) => write!(f, "{}", v.format(&Rfc3339).map_err(|_| std::fmt::Error)?),
+            Value::Date(v) => write!(
+                f,
+                "{}",
+                v.format(format_description!("[year]-[month]-[day]"))
+                    .map_err(|_| std::fmt::Error)?
+            ),
+            Value::Interval(v) => write!(f, "{v}"),
+            Value::Array(values) => {
+                write!(f, "[")?;
+                for (i, v) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    v.fmt_nested(f)?;
+                }
+                write!(f, "]")
+            }
+            Value::Struct(fields) => {
+                write!(f, "{{")?;
+                for (i, (name, v)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{name}: ")?;
+                    v.fmt_nested(f)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// TIMESTAMP, DATE, NUMERIC and BYTES are serialized as string like the Spanner JSON encoding.
+/// JSON is serialized as the parsed JSON value.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::{SerializeMap, SerializeSeq};
+        match self {
+            Value::Null => serializer.serialize_none(),
+            Value::Bool(v) => serializer.serialize_bool(*v),
+            Value::Int64(v) => serializer.serialize_i64(*v),
+            Value::Float64(v) => serializer.serialize_f64(*v),
+            Value::Json(v) => match serde_json::from_str::<serde_json::Value>(v) {
+                Ok(json) => json.serialize(serializer),
+                Err(_) => serializer.serialize_str(v),
+            },
+            Value::Array(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for v in values {
+                    seq.serialize_element(v)?;
+                }
+                seq.end()
+            }
+            Value::Struct(fields) => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for (name, v) in fields {
+                    map.serialize_entry(name, v)?;
+                }
+                map.end()
+            }
+            v => serializer.collect_str(v),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::value::{Interval, IntervalError};
+
+    #[test]
+    fn test_interval_parse_and_format() {
+        let cases = [
+            ("P0Y", Interval::new(0, 0, 0), "P0Y"),
+            ("P1Y2M3D", Interval::new(14, 3, 0), "P1Y2M3D"),
+            ("P1Y2M3DT4H5M6.5S", Interval::new(14, 3, 14_706_500_000_000), "P1Y2M3DT4H5M6.5S"),
+            ("PT0.000000001S", Interval::new(0, 0, 1), "PT0.000000001S"),
+            ("P-1Y-2M", Interval::new(-14, 0, 0), "P-1Y-2M"),
+            ("PT-1.5S", Interval::new(0, 0, -1_500_000_000), "PT-1.5S"),
+            ("PT-0.5S", Interval::new(0, 0, -500_000_000), "PT-0.5S"),
+            ("PT90M", Interval::new(0, 0, 5_400_000_000_000), "PT1H30M"),
+            ("P1MT1M", Interval::new(1, 0, 60_000_000_000), "P1MT1M"),
+        ];
+        for (input, expected, formatted) in cases {
+            let interval = Interval::from_str(input).unwrap();
+            assert_eq!(interval, expected, "{input}");
+            assert_eq!(interval.to_string(), formatted, "{input}");
+        }
+
+        for invalid in [
+            "",
+            "P",
+            "PT",
+            "P1W",
+            "1Y",
+            "P1",
+            "P1M1Y",
+            "PT1.5M",
+            "PT1.0000000001S",
+            "P--1Y",
+            "P1YT",
+        ] {
+            assert_eq!(
+                Interval::from_str(invalid),
+                Err(IntervalError::Parse(invalid.to_string())),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_interval_duration() {
+        let duration = time::Duration::new(90, 5);
+        let interval = Interval::from(duration);
+        assert_eq!(time::Duration::try_from(interval), Ok(duration));
+        assert_eq!(
+            time::Duration::try_from(Interval::new(0, 1, -1)),
+            Ok(time::Duration::new(86_399, 999_999_999))
+        );
+        assert_eq!(time::Duration::try_from(Interval::new(1, 0, 0)), Err(IntervalError::HasMonths));
+    }
+}
@@ -1,3 +1,5 @@
+use std::sync::{Arc, Mutex};
+
 use serial_test::serial;
 use time::OffsetDateTime;
 
@@ -5,13 +7,20 @@ use common::*;
 use google_cloud_gax::conn::Environment;
 use google_cloud_gax::grpc::{Code, Status};
 use google_cloud_gax::retry::TryAs;
-use google_cloud_spanner::client::{Client, ClientConfig, Error};
+use google_cloud_googleapis::spanner::v1::request_options::Priority;
+use google_cloud_googleapis::spanner::v1::Mutation;
+use google_cloud_spanner::client::{
+    ApplyAtLeastOnceOption, ApplyInChunksOption, Client, ClientConfig, Error, ReadRowOption, ReadWriteTransactionOption,
+};
 use google_cloud_spanner::key::Key;
-use google_cloud_spanner::retry::TransactionRetry;
+use google_cloud_spanner::mutation::{MutationOperation, MutationRecord};
+use google_cloud_spanner::retry::{TransactionRetry, TransactionRetrySetting};
 use google_cloud_spanner::row::Row;
 use google_cloud_spanner::session::SessionError;
 use google_cloud_spanner::statement::Statement;
-use google_cloud_spanner::value::Timestamp;
+use google_cloud_spanner::transaction::{CallOptions, RowCount};
+use google_cloud_spanner::transaction_rw::{CommitOptions, IsolationLevel};
+use google_cloud_spanner::value::{Timestamp, TimestampBound, Value};
 
 mod common;
 
@@ -77,7 +86,7 @@ async fn test_read_write_transaction() {
                     tx.buffer_write(ms);
                     let mut stmt = Statement::new("Insert Into UserItem (UserId,ItemId,Quantity,UpdatedAt) VALUES(@UserId,1,1,PENDING_COMMIT_TIMESTAMP())");
                     stmt.add_param("UserId", &user_id);
-                    let updated = tx.update(stmt).await?;
+                    let updated = tx.update(stmt).await?.value();
                     if updated == 0 {
                         Err(DomainError::UpdateInvalid)
                     }else {
@@ -138,6 +147,28 @@ async fn test_apply() {
     }
 }
 
+#[tokio::test]
+#[serial]
+async fn test_apply_in_chunks() {
+    let users: Vec<String> = (0..5).map(|x| format!("user_client_chunk_{x}")).collect();
+    let client = Client::new(DATABASE, ClientConfig::default()).await.unwrap();
+    let now = OffsetDateTime::now_utc();
+    let ms = users.iter().map(|id| create_user_mutation(id, &now)).collect();
+    let option = ApplyInChunksOption {
+        max_mutations_per_chunk: user_columns().len() * 2,
+        ..Default::default()
+    };
+    let results = client.apply_in_chunks(ms, option).await;
+    assert_eq!(results.iter().map(|r| r.mutations.len()).collect::<Vec<_>>(), vec![2, 2, 1]);
+    assert!(results.iter().all(|r| r.is_committed()));
+
+    let mut ro = client.read_only_transaction().await.unwrap();
+    for x in users {
+        let record = ro.read("User", &user_columns(), Key::new(&x)).await.unwrap();
+        assert_eq!(all_rows(record).await.unwrap().len(), 1);
+    }
+}
+
 #[tokio::test]
 #[serial]
 async fn test_apply_at_least_once() {
@@ -159,6 +190,187 @@ async fn test_apply_at_least_once() {
     }
 }
 
+#[tokio::test]
+#[serial]
+async fn test_apply_at_least_once_with_option() {
+    let user_id = format!("user_client_y_{}", OffsetDateTime::now_utc().unix_timestamp());
+    let client = Client::new(DATABASE, ClientConfig::default()).await.unwrap();
+    let now = OffsetDateTime::now_utc();
+    let option = ApplyAtLeastOnceOption {
+        commit_options: CommitOptions {
+            call_options: CallOptions {
+                priority: Some(Priority::Low),
+                retry: None,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        retry: TransactionRetrySetting::default(),
+        ..Default::default()
+    };
+    let value = client
+        .apply_at_least_once_with_option(vec![create_user_mutation(&user_id, &now)], option)
+        .await
+        .unwrap()
+        .unwrap();
+    let ts = OffsetDateTime::from_unix_timestamp(value.seconds)
+        .unwrap()
+        .replace_nanosecond(value.nanos as u32)
+        .unwrap();
+
+    let mut ro = client.read_only_transaction().await.unwrap();
+    let record = ro.read("User", &user_columns(), Key::new(&user_id)).await.unwrap();
+    let row = all_rows(record).await.unwrap().pop().unwrap();
+    assert_user_row(&row, &user_id, &now, &ts);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_apply_with_commit_hook() {
+    let user_id = format!("user_client_hook_{}", OffsetDateTime::now_utc().unix_timestamp());
+    let client = Client::new(DATABASE, ClientConfig::default()).await.unwrap();
+    let now = OffsetDateTime::now_utc();
+    let audit_log = Arc::new(Mutex::new(vec![]));
+    let log = audit_log.clone();
+    let option = ReadWriteTransactionOption {
+        commit_options: CommitOptions {
+            on_commit: Some(Arc::new(move |ms: &[Mutation]| {
+                let records = ms.iter().map(|m| MutationRecord::try_from(m).unwrap());
+                log.lock().unwrap().extend(records);
+            })),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mutation = create_user_mutation(&user_id, &now);
+    client.apply_with_option(vec![mutation.clone()], option).await.unwrap();
+
+    let audit_log = audit_log.lock().unwrap();
+    assert_eq!(audit_log.len(), 1);
+    assert_eq!(audit_log[0].operation, MutationOperation::InsertOrUpdate);
+    assert_eq!(audit_log[0].table, "User");
+    assert_eq!(audit_log[0].values[0][0], Value::String(user_id));
+    assert_eq!(Mutation::from(audit_log[0].clone()), mutation);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_query_and_read_single_use() {
+    let user_id = format!("user_client_su_{}", OffsetDateTime::now_utc().unix_timestamp());
+    let client = Client::new(DATABASE, ClientConfig::default()).await.unwrap();
+    let now = OffsetDateTime::now_utc();
+    client.apply(vec![create_user_mutation(&user_id, &now)]).await.unwrap();
+
+    let mut stmt = Statement::new("SELECT UserId FROM User WHERE UserId = @UserId");
+    stmt.add_param("UserId", &user_id);
+    let (rows, read_timestamp) = client
+        .query_single_use(stmt, TimestampBound::strong_read())
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+    assert!(read_timestamp.unwrap() >= now);
+
+    let bounds = [
+        TimestampBound::exact_staleness(std::time::Duration::from_secs(600)),
+        TimestampBound::max_staleness(std::time::Duration::from_secs(10)),
+    ];
+    let (stale, _) = client
+        .read_single_use("User", &["UserId"], Key::new(&user_id), bounds[0].clone())
+        .await
+        .unwrap();
+    assert!(stale.is_empty());
+    let (rows, read_timestamp) = client
+        .read_single_use("User", &["UserId"], Key::new(&user_id), bounds[1].clone())
+        .await
+        .unwrap();
+    assert!(rows.len() <= 1);
+    assert!(read_timestamp.is_some());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_execute_and_query() {
+    let user_id = format!("user_client_ex_{}", OffsetDateTime::now_utc().unix_timestamp());
+    let client = Client::new(DATABASE, ClientConfig::default()).await.unwrap();
+    let now = OffsetDateTime::now_utc();
+    client.apply(vec![create_user_mutation(&user_id, &now)]).await.unwrap();
+
+    let mut stmt = Statement::new(
+        "INSERT INTO UserItem (UserId,ItemId,Quantity,UpdatedAt) VALUES(@UserId,1,10,PENDING_COMMIT_TIMESTAMP()),(@UserId,2,20,PENDING_COMMIT_TIMESTAMP())",
+    );
+    stmt.add_param("UserId", &user_id);
+    assert_eq!(client.execute(stmt).await.unwrap(), RowCount::Exact(2));
+
+    let mut stmt = Statement::new("SELECT Quantity FROM UserItem WHERE UserId = @UserId AND ItemId = 1");
+    stmt.add_param("UserId", &user_id);
+    let row: Row = client.query_one(stmt.clone()).await.unwrap();
+    assert_eq!(row.column::<i64>(0).unwrap(), 10);
+    assert!(client.query_opt::<Row>(stmt).await.unwrap().is_some());
+
+    let mut stmt = Statement::new("SELECT Quantity FROM UserItem WHERE UserId = @UserId ORDER BY ItemId");
+    stmt.add_param("UserId", &user_id);
+    let rows: Vec<Row> = client.query_all(stmt.clone()).await.unwrap();
+    assert_eq!(rows.len(), 2);
+    assert!(matches!(client.query_one::<Row>(stmt).await, Err(Error::TooManyRows)));
+
+    let mut stmt = Statement::new("SELECT Quantity FROM UserItem WHERE UserId = @UserId AND ItemId = 3");
+    stmt.add_param("UserId", &user_id);
+    assert!(client.query_opt::<Row>(stmt.clone()).await.unwrap().is_none());
+    assert!(matches!(client.query_one::<Row>(stmt).await, Err(Error::NoRows)));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_read_row() {
+    let user_id = format!("user_client_r_{}", OffsetDateTime::now_utc().unix_timestamp());
+    let client = Client::new(DATABASE, ClientConfig::default()).await.unwrap();
+    let now = OffsetDateTime::now_utc();
+    let ts = client
+        .apply(vec![create_user_mutation(&user_id, &now)])
+        .await
+        .unwrap()
+        .unwrap();
+    let ts = OffsetDateTime::from_unix_timestamp(ts.seconds)
+        .unwrap()
+        .replace_nanosecond(ts.nanos as u32)
+        .unwrap();
+
+    let row = client
+        .read_row("User", &user_columns(), Key::new(&user_id))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_user_row(&row, &user_id, &now, &ts);
+
+    let option = ReadRowOption {
+        timestamp_bound: TimestampBound::max_staleness(std::time::Duration::from_secs(10)),
+        ..Default::default()
+    };
+    let row = client
+        .read_row_with_option("User", &user_columns(), Key::new(&"not_found_user"), option)
+        .await
+        .unwrap();
+    assert!(row.is_none());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_read_rows() {
+    let users: Vec<String> = (0..2).map(|x| format!("user_client_rs_{x}")).collect();
+    let client = Client::new(DATABASE, ClientConfig::default()).await.unwrap();
+    let now = OffsetDateTime::now_utc();
+    let ms = users.iter().map(|id| create_user_mutation(id, &now)).collect();
+    client.apply(ms).await.unwrap();
+
+    let mut keys: Vec<Key> = users.iter().map(|id| Key::new(id)).collect();
+    keys.push(Key::new(&"not_found_user"));
+    let rows = client.read_rows("User", &["UserId"], keys).await.unwrap();
+    assert_eq!(users.len(), rows.len());
+
+    let rows = client.read_rows("User", &["UserId"], vec![]).await.unwrap();
+    assert!(rows.is_empty());
+}
+
 #[tokio::test]
 #[serial]
 async fn test_partitioned_update() {
@@ -174,7 +386,8 @@ async fn test_partitioned_update() {
     // test
     let client = Client::new(DATABASE, ClientConfig::default()).await.unwrap();
     let stmt = Statement::new("UPDATE User SET NullableString = 'aaa' WHERE NullableString IS NOT NULL");
-    client.partitioned_update(stmt).await.unwrap();
+    let row_count = client.partitioned_update(stmt).await.unwrap();
+    assert!(!row_count.is_exact());
 
     let mut single = client.single().await.unwrap();
     let rows = single
@@ -241,3 +454,34 @@ async fn test_with_auth() {
         unreachable!()
     }
 }
+
+#[tokio::test]
+#[serial]
+async fn test_read_write_transaction_isolation_level() {
+    let client = Client::new(DATABASE, ClientConfig::default()).await.unwrap();
+    let options = ReadWriteTransactionOption {
+        isolation_level: Some(IsolationLevel::RepeatableRead),
+        ..Default::default()
+    };
+    let result: Result<(Option<Timestamp>, ()), Error> = client
+        .read_write_transaction_with_option(
+            |tx| {
+                Box::pin(async move {
+                    let stmt = Statement::new("SELECT 1");
+                    let mut rows = tx.query(stmt).await?;
+                    while rows.next().await?.is_some() {}
+                    Ok(())
+                })
+            },
+            options,
+        )
+        .await;
+    // The emulator may not support the isolation level.
+    match result {
+        Ok(_) => {}
+        Err(Error::GRPC(status)) => {
+            assert!(status.message().contains("unset the isolation level"), "{status:?}")
+        }
+        Err(e) => panic!("unexpected error {e:?}"),
+    }
+}
@@ -0,0 +1,238 @@
+#![cfg(feature = "spanner-test")]
+
+use std::time::Duration;
+
+use google_cloud_gax::grpc::{Code, Status};
+use google_cloud_googleapis::spanner::v1::TypeCode;
+use google_cloud_spanner::apiv1::spanner_client::RedactionPolicy;
+use google_cloud_spanner::client::{Client, Error};
+use google_cloud_spanner::key::Key;
+use google_cloud_spanner::mutation::insert;
+use google_cloud_spanner::spanner_test::{result_set, Method, MockSpanner, DATABASE};
+use google_cloud_spanner::statement::Statement;
+use google_cloud_spanner::value::Value;
+
+fn users(count: i64) -> Vec<Vec<Value>> {
+    (0..count)
+        .map(|i| vec![Value::String(format!("user{i}")), Value::Int64(i)])
+        .collect()
+}
+
+const USER_COLUMNS: [(&str, TypeCode); 2] = [("UserId", TypeCode::String), ("NotNull", TypeCode::Int64)];
+
+#[tokio::test]
+async fn test_query() {
+    let mock = MockSpanner::new();
+    mock.put_query_result("SELECT * FROM User", result_set(&USER_COLUMNS, users(3)));
+    let server = mock.start().await.unwrap();
+    let client = server.client().await.unwrap();
+
+    let mut tx = client.single().await.unwrap();
+    let mut iter = tx.query(Statement::new("SELECT * FROM User")).await.unwrap();
+    let mut rows = vec![];
+    while let Some(row) = iter.next().await.unwrap() {
+        rows.push((
+            row.column_by_name::<String>("UserId").unwrap(),
+            row.column_by_name::<i64>("NotNull").unwrap(),
+        ));
+    }
+    assert_eq!(
+        rows,
+        vec![
+            ("user0".to_string(), 0),
+            ("user1".to_string(), 1),
+            ("user2".to_string(), 2)
+        ]
+    );
+
+    // unknown sql
+    let mut tx = client.single().await.unwrap();
+    match tx.query(Statement::new("SELECT * FROM Unknown")).await {
+        Err(status) => assert_eq!(status.code(), Code::InvalidArgument),
+        Ok(_) => unreachable!("unknown sql must fail"),
+    }
+}
+
+#[tokio::test]
+async fn test_query_resume_retry_reconnect_error() {
+    let mock = MockSpanner::new();
+    mock.put_query_result("SELECT * FROM User", result_set(&USER_COLUMNS, users(5)));
+    mock.inject_stream_error(Method::ExecuteStreamingSql, 2, Status::unavailable("injected"));
+    let server = mock.start().await.unwrap();
+    let client = server.client().await.unwrap();
+
+    let mut tx = client.single().await.unwrap();
+    let mut iter = tx.query(Statement::new("SELECT * FROM User")).await.unwrap();
+    // re-establishing the broken stream fails once.
+    mock.inject_error(Method::ExecuteStreamingSql, Status::internal("injected"));
+    let mut ids = vec![];
+    while let Some(row) = iter.next().await.unwrap() {
+        ids.push(row.column_by_name::<i64>("NotNull").unwrap());
+    }
+    assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+    assert_eq!(mock.call_count(Method::ExecuteStreamingSql), 3);
+}
+
+#[tokio::test]
+async fn test_read_resume_on_unavailable() {
+    let mock = MockSpanner::new();
+    mock.put_query_result("SELECT * FROM User", result_set(&USER_COLUMNS, users(5)));
+    mock.put_read_result("User", result_set(&USER_COLUMNS, users(5)));
+    mock.inject_stream_error(Method::ExecuteStreamingSql, 2, Status::unavailable("injected"));
+    mock.inject_stream_error(Method::StreamingRead, 3, Status::unavailable("injected"));
+    let server = mock.start().await.unwrap();
+    let client = server.client().await.unwrap();
+
+    let mut tx = client.single().await.unwrap();
+    let mut iter = tx.query(Statement::new("SELECT * FROM User")).await.unwrap();
+    let mut ids = vec![];
+    while let Some(row) = iter.next().await.unwrap() {
+        ids.push(row.column_by_name::<i64>("NotNull").unwrap());
+    }
+    assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+    assert_eq!(mock.call_count(Method::ExecuteStreamingSql), 2);
+
+    let mut tx = client.single().await.unwrap();
+    let mut iter = tx
+        .read("User", &["UserId", "NotNull"], Key::new(&"user0"))
+        .await
+        .unwrap();
+    let mut ids = vec![];
+    while let Some(row) = iter.next().await.unwrap() {
+        ids.push(row.column_by_name::<i64>("NotNull").unwrap());
+    }
+    assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+    assert_eq!(mock.call_count(Method::StreamingRead), 2);
+}
+
+#[tokio::test]
+async fn test_commit_retry_on_aborted() {
+    let mock = MockSpanner::new();
+    mock.put_update_count("UPDATE User SET NotNull = 1 WHERE true", 3);
+    mock.inject_error(Method::Commit, Status::aborted("injected"));
+    let server = mock.start().await.unwrap();
+    let client = server.client().await.unwrap();
+
+    let ms = vec![insert("User", &["UserId", "NotNull"], &[&"user1", &1_i64])];
+    let result = client.apply(ms.clone()).await.unwrap();
+    assert!(result.is_some());
+    assert_eq!(mock.call_count(Method::BeginTransaction), 2);
+    assert_eq!(mock.call_count(Method::Commit), 2);
+    assert_eq!(mock.mutations(), ms);
+
+    let (_, count) = client
+        .read_write_transaction(|tx| {
+            Box::pin(async move {
+                let count = tx
+                    .update(Statement::new("UPDATE User SET NotNull = 1 WHERE true"))
+                    .await?
+                    .value();
+                Ok::<i64, Error>(count)
+            })
+        })
+        .await
+        .unwrap();
+    assert_eq!(count, 3);
+    assert_eq!(mock.commits().len(), 2);
+}
+
+#[tokio::test]
+async fn test_commit_error() {
+    let mock = MockSpanner::new();
+    mock.inject_error(Method::Commit, Status::permission_denied("injected"));
+    let server = mock.start().await.unwrap();
+    let client = server.client().await.unwrap();
+
+    let err = client
+        .apply(vec![insert("User", &["UserId"], &[&"user1"])])
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err, Error::GRPC(s) if s.code() == Code::PermissionDenied));
+    assert_eq!(mock.call_count(Method::Commit), 1);
+    assert!(mock.mutations().is_empty());
+}
+
+#[tokio::test]
+async fn test_slow_operation() {
+    let mock = MockSpanner::new();
+    mock.put_update_count("UPDATE User SET NotNull = 1 WHERE UserId = 'user1'", 1);
+    let server = mock.start().await.unwrap();
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let mut config = server.config().on_slow_operation(Duration::ZERO, move |operation| {
+        let _ = sender.send(operation);
+    });
+    config.trace_config.redaction = RedactionPolicy::SanitizedStatement;
+    let client = Client::new(DATABASE, config).await.unwrap();
+
+    client
+        .read_write_transaction(|tx| {
+            Box::pin(async move {
+                let count = tx
+                    .update(Statement::new("UPDATE User SET NotNull = 1 WHERE UserId = 'user1'"))
+                    .await?
+                    .value();
+                Ok::<i64, Error>(count)
+            })
+        })
+        .await
+        .unwrap();
+
+    let update = receiver.recv().await.unwrap();
+    assert_eq!(update.method, "ExecuteSql");
+    assert_eq!(
+        update.statement.as_deref(),
+        Some("UPDATE User SET NotNull = ? WHERE UserId = ?")
+    );
+    assert_eq!(update.status.code(), Code::Ok);
+    assert!(!update.session_id.is_empty());
+
+    let commit = receiver.recv().await.unwrap();
+    assert_eq!(commit.method, "Commit");
+    assert_eq!(commit.session_id, update.session_id);
+    assert!(commit.transaction_id.is_some());
+    assert!(commit.statement.is_none());
+    client.close().await;
+}
+
+#[tokio::test]
+async fn test_slow_operation_streaming() {
+    let mock = MockSpanner::new();
+    mock.put_query_result("SELECT * FROM User WHERE NotNull > 0", result_set(&USER_COLUMNS, users(3)));
+    mock.inject_stream_error(Method::ExecuteStreamingSql, 1, Status::unavailable("injected"));
+    let server = mock.start().await.unwrap();
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let mut config = server.config().on_slow_operation(Duration::ZERO, move |operation| {
+        let _ = sender.send(operation);
+    });
+    config.trace_config.redaction = RedactionPolicy::SanitizedStatement;
+    let client = Client::new(DATABASE, config).await.unwrap();
+
+    let mut tx = client.single().await.unwrap();
+    let mut iter = tx
+        .query(Statement::new("SELECT * FROM User WHERE NotNull > 0"))
+        .await
+        .unwrap();
+    assert!(iter.next().await.unwrap().is_some());
+    // the stream is measured until it is completed.
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(receiver.try_recv().is_err());
+
+    let mut rows = 1;
+    while iter.next().await.unwrap().is_some() {
+        rows += 1;
+    }
+    assert_eq!(rows, 3);
+    assert_eq!(mock.call_count(Method::ExecuteStreamingSql), 2);
+
+    let query = receiver.recv().await.unwrap();
+    assert_eq!(query.method, "ExecuteStreamingSql");
+    assert_eq!(query.statement.as_deref(), Some("SELECT * FROM User WHERE NotNull > ?"));
+    assert_eq!(query.status.code(), Code::Ok);
+    assert!(!query.session_id.is_empty());
+    drop(iter);
+    drop(tx);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(receiver.try_recv().is_err());
+    client.close().await;
+}
@@ -0,0 +1,100 @@
+#![cfg(all(feature = "trace", feature = "spanner-test"))]
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::Layer;
+
+use google_cloud_googleapis::spanner::v1::TypeCode;
+use google_cloud_spanner::apiv1::spanner_client::RedactionPolicy;
+use google_cloud_spanner::client::Client;
+use google_cloud_spanner::spanner_test::{result_set, MockSpanner, DATABASE};
+use google_cloud_spanner::statement::Statement;
+use google_cloud_spanner::value::Value;
+
+const SQL: &str = "SELECT * FROM User WHERE UserId = @UserId AND Level > 10 -- 'comment'";
+
+/// Records the fields of the ExecuteStreamingSql spans.
+#[derive(Clone, Default)]
+struct CaptureLayer {
+    spans: Arc<Mutex<Vec<HashMap<String, String>>>>,
+}
+
+struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for CaptureLayer {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        let mut fields = HashMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        if fields.get("db.operation").map(|v| v.as_str()) == Some("ExecuteStreamingSql") {
+            self.spans.lock().unwrap().push(fields);
+        }
+    }
+}
+
+async fn query_fields(redaction: RedactionPolicy) -> HashMap<String, String> {
+    let mock = MockSpanner::new();
+    mock.put_query_result(
+        SQL,
+        result_set(&[("UserId", TypeCode::String)], vec![vec![Value::String("user1".to_string())]]),
+    );
+    let server = mock.start().await.unwrap();
+    let mut config = server.config();
+    config.trace_config.redaction = redaction;
+    let client = Client::new(DATABASE, config).await.unwrap();
+
+    let layer = CaptureLayer::default();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(layer.clone()));
+    let mut stmt = Statement::new(SQL);
+    stmt.add_param("UserId", &"user1");
+    let mut tx = client.single().await.unwrap();
+    let mut iter = tx.query(stmt).await.unwrap();
+    while iter.next().await.unwrap().is_some() {}
+    client.close().await;
+
+    let spans = layer.spans.lock().unwrap();
+    assert_eq!(spans.len(), 1);
+    spans[0].clone()
+}
+
+#[tokio::test]
+async fn test_operation_only() {
+    let fields = query_fields(RedactionPolicy::OperationOnly).await;
+    assert_eq!(fields.get("db.system").map(|v| v.as_str()), Some("spanner"));
+    assert!(!fields.contains_key("db.statement"));
+    assert!(!fields.contains_key("db.statement.params"));
+}
+
+#[tokio::test]
+async fn test_sanitized_statement() {
+    let fields = query_fields(RedactionPolicy::SanitizedStatement).await;
+    assert_eq!(
+        fields.get("db.statement").map(|v| v.as_str()),
+        Some("SELECT * FROM User WHERE UserId = @UserId AND Level > ? ")
+    );
+    assert!(!fields.contains_key("db.statement.params"));
+}
+
+#[tokio::test]
+async fn test_full_statement() {
+    let fields = query_fields(RedactionPolicy::FullStatement).await;
+    assert_eq!(fields.get("db.statement").map(|v| v.as_str()), Some(SQL));
+    assert_eq!(
+        fields.get("db.statement.params").map(|v| v.as_str()),
+        Some(r#"@UserId="user1""#)
+    );
+}
@@ -383,3 +383,40 @@ async fn test_big_decimal() {
         row.column::<BigDecimal>(6).unwrap().to_string()
     );
 }
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn test_shared_read_only_transaction() {
+    //set up test data
+    let now = OffsetDateTime::now_utc();
+    let user_ids: Vec<String> = (0..10).map(|i| format!("user_shared_{i}")).collect();
+    let mutations = user_ids.iter().map(|u| create_user_mutation(u, &now)).collect();
+    let data_client = create_data_client().await;
+    let _ = data_client.apply(mutations).await.unwrap();
+
+    // test
+    let tx = data_client.shared_read_only_transaction().await.unwrap();
+    assert!(tx.rts().is_some());
+    let tasks = user_ids.iter().cloned().map(|user_id| {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let mut stmt = Statement::new("SELECT UserId FROM User WHERE UserId = @UserId");
+            stmt.add_param("UserId", &user_id);
+            let rows = all_rows(tx.query(stmt).await?).await?;
+            let row = tx.read_row("User", &["UserId"], Key::new(&user_id)).await?;
+            Ok::<_, google_cloud_gax::grpc::Status>((rows.len(), row.is_some()))
+        })
+    });
+    for result in futures_util::future::join_all(tasks).await {
+        assert_eq!(result.unwrap().unwrap(), (1, true));
+    }
+
+    // the snapshot doesn't see the rows written after the transaction began.
+    let later = "user_shared_later";
+    let _ = data_client
+        .apply(vec![create_user_mutation(later, &now)])
+        .await
+        .unwrap();
+    let row = tx.read_row("User", &["UserId"], Key::new(&later)).await.unwrap();
+    assert!(row.is_none());
+}
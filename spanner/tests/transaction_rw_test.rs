@@ -2,9 +2,12 @@ use serial_test::serial;
 use time::OffsetDateTime;
 
 use common::*;
+use google_cloud_spanner::client::Client;
 use google_cloud_spanner::key::Key;
 use google_cloud_spanner::row::Row;
 use google_cloud_spanner::statement::Statement;
+use google_cloud_spanner::transaction::RowCount;
+use google_cloud_spanner::transaction_rw::TxError;
 
 mod common;
 
@@ -161,3 +164,148 @@ async fn assert_data(
     );
     assert!(user_characters.is_empty());
 }
+
+#[tokio::test]
+#[serial]
+async fn test_then_return_row_count() {
+    //set up test data
+    let now = OffsetDateTime::now_utc();
+    let data_client = create_data_client().await;
+    let past_user = format!("user_{}", now.unix_timestamp());
+    data_client
+        .apply(vec![create_user_mutation(&past_user, &now)])
+        .await
+        .unwrap();
+
+    //test
+    let mut tx = data_client.begin_read_write_transaction().await.unwrap();
+    let result = async {
+        let mut stmt = Statement::new(
+            "INSERT INTO UserItem (UserId,ItemId,Quantity,UpdatedAt) VALUES(@UserId,20,100,PENDING_COMMIT_TIMESTAMP()) THEN RETURN Quantity",
+        );
+        stmt.add_param("UserId", &past_user);
+        let mut rows = tx.query(stmt).await?;
+        let mut quantities = vec![];
+        while let Some(row) = rows.next().await? {
+            quantities.push(row.column::<i64>(0).unwrap());
+        }
+        Ok::<_, google_cloud_gax::grpc::Status>((quantities, rows.row_count()))
+    }
+    .await;
+    let (_, (quantities, row_count)) = tx.end(result, None).await.unwrap();
+    assert_eq!(quantities, vec![100]);
+    assert_eq!(row_count, Some(RowCount::Exact(1)));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_query_metadata_and_stats() {
+    //set up test data
+    let now = OffsetDateTime::now_utc();
+    let data_client = create_data_client().await;
+    let past_user = format!("user_{}", now.unix_timestamp());
+    data_client
+        .apply(vec![create_user_mutation(&past_user, &now)])
+        .await
+        .unwrap();
+
+    //test
+    let mut tx = data_client.begin_read_write_transaction().await.unwrap();
+    let result = async {
+        let mut stmt = Statement::new(
+            "INSERT INTO UserItem (UserId,ItemId,Quantity,UpdatedAt) VALUES(@UserId,30,100,PENDING_COMMIT_TIMESTAMP()) THEN RETURN Quantity",
+        );
+        stmt.add_param("UserId", &past_user);
+        let mut rows = tx.query(stmt).await?;
+        let mut count = 0;
+        while rows.next().await?.is_some() {
+            assert!(rows.stats().is_none());
+            count += 1;
+        }
+        let columns = rows
+            .metadata()
+            .and_then(|m| m.row_type.as_ref())
+            .map(|t| t.fields.iter().map(|f| f.name.clone()).collect::<Vec<_>>());
+        let has_stats = rows.stats().is_some();
+        Ok::<_, google_cloud_gax::grpc::Status>((count, columns, has_stats, rows.transaction_id().is_none()))
+    }
+    .await;
+    let (_, (count, columns, has_stats, no_transaction_id)) = tx.end(result, None).await.unwrap();
+    assert_eq!(count, 1);
+    assert_eq!(columns, Some(vec!["Quantity".to_string()]));
+    assert!(has_stats);
+    assert!(no_transaction_id);
+}
+
+async fn count_user_items(data_client: &Client, user_id: &str, item_id: i64) -> usize {
+    let mut stmt = Statement::new("SELECT Quantity FROM UserItem WHERE UserId = @UserId AND ItemId = @ItemId");
+    stmt.add_param("UserId", &user_id);
+    stmt.add_param("ItemId", &item_id);
+    let mut tx = data_client.single().await.unwrap();
+    all_rows(tx.query(stmt).await.unwrap()).await.unwrap().len()
+}
+
+fn insert_user_item(user_id: &str, item_id: i64) -> Statement {
+    let mut stmt = Statement::new(
+        "INSERT INTO UserItem (UserId,ItemId,Quantity,UpdatedAt) VALUES(@UserId,@ItemId,1,PENDING_COMMIT_TIMESTAMP())",
+    );
+    stmt.add_param("UserId", &user_id);
+    stmt.add_param("ItemId", &item_id);
+    stmt
+}
+
+#[tokio::test]
+#[serial]
+async fn test_tx_handle_commit_and_rollback() {
+    let now = OffsetDateTime::now_utc();
+    let data_client = create_data_client().await;
+    let past_user = format!("user_{}", now.unix_timestamp());
+    data_client
+        .apply(vec![create_user_mutation(&past_user, &now)])
+        .await
+        .unwrap();
+
+    // commit
+    let mut tx = data_client.begin_read_write(Default::default()).await.unwrap();
+    assert_eq!(tx.update(insert_user_item(&past_user, 30)).await.unwrap(), RowCount::Exact(1));
+    assert!(tx.commit().await.unwrap().is_some());
+    assert_eq!(count_user_items(&data_client, &past_user, 30).await, 1);
+
+    // rollback
+    let mut tx = data_client.begin_read_write(Default::default()).await.unwrap();
+    assert_eq!(tx.update(insert_user_item(&past_user, 31)).await.unwrap(), RowCount::Exact(1));
+    tx.rollback().await.unwrap();
+    assert_eq!(count_user_items(&data_client, &past_user, 31).await, 0);
+
+    // the duplicated key
+    let mut tx = data_client.begin_read_write(Default::default()).await.unwrap();
+    let err = TxError::from(tx.update(insert_user_item(&past_user, 30)).await.unwrap_err());
+    assert!(!err.is_aborted());
+    tx.rollback().await.unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_tx_handle_drop() {
+    let now = OffsetDateTime::now_utc();
+    let data_client = create_data_client().await;
+    let past_user = format!("user_{}", now.unix_timestamp());
+    data_client
+        .apply(vec![create_user_mutation(&past_user, &now)])
+        .await
+        .unwrap();
+
+    {
+        let mut tx = data_client.begin_read_write(Default::default()).await.unwrap();
+        assert_eq!(tx.update(insert_user_item(&past_user, 32)).await.unwrap(), RowCount::Exact(1));
+    }
+    // wait for the rollback in the background
+    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    assert_eq!(count_user_items(&data_client, &past_user, 32).await, 0);
+
+    // the session is returned to the pool and the rows are not locked
+    let mut tx = data_client.begin_read_write(Default::default()).await.unwrap();
+    assert_eq!(tx.update(insert_user_item(&past_user, 32)).await.unwrap(), RowCount::Exact(1));
+    tx.commit().await.unwrap();
+    assert_eq!(count_user_items(&data_client, &past_user, 32).await, 1);
+}
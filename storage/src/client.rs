@@ -4,11 +4,18 @@ use ring::{rand, signature};
 
 use google_cloud_token::{NopeTokenSourceProvider, TokenSourceProvider};
 
-use crate::http::service_account_client::ServiceAccountClient;
+use crate::http::checksum::ChecksumConfig;
+use crate::http::retry::RetryConfig;
 use crate::http::storage_client::StorageClient;
 use crate::sign::SignBy::PrivateKey;
-use crate::sign::{create_signed_buffer, RsaKeyPair, SignBy, SignedURLError, SignedURLOptions};
+use crate::sign::{
+    create_post_policy, create_signed_buffer, PostPolicyV4, PostPolicyV4Options, RsaKeyPair, SignBy, SignedURLError,
+    SignedURLOptions,
+};
 
+///
+/// The client retries the idempotent operations by `ClientConfig::retry`.
+/// The retry middleware retries the requests regardless of their idempotency.
 ///
 /// #### Example building a client configuration with a custom retry strategy as middleware:
 /// ```rust
@@ -41,24 +48,61 @@ use crate::sign::{create_signed_buffer, RsaKeyPair, SignBy, SignedURLError, Sign
 #[derive(Debug)]
 pub struct ClientConfig {
     pub http: Option<reqwest_middleware::ClientWithMiddleware>,
+    /// The base URL of the JSON API. The operations are sent to `{storage_endpoint}/storage/v1`.
     pub storage_endpoint: String,
+    /// The base URL of the upload API. The uploads are sent to `{upload_endpoint}/upload/storage/v1`.
+    /// The default is `storage_endpoint`.
+    pub upload_endpoint: Option<String>,
+    /// The base URL of IAM Service Account Credentials API used to sign without the private key.
     pub service_account_endpoint: String,
     pub token_source_provider: Option<Box<dyn TokenSourceProvider>>,
     pub default_google_access_id: Option<String>,
     pub default_sign_by: Option<SignBy>,
     pub project_id: Option<String>,
+    /// The retries of the operations on the transient failures. Only the idempotent operations are retried by default.
+    pub retry: RetryConfig,
+    /// The project billed for the operations. It is required to access the requester pays buckets.
+    /// It is overridden per call with `StorageClient::with_user_project`.
+    pub user_project: Option<String>,
+    /// The checksums verified by `StorageClient::download_object` when the whole object is downloaded.
+    /// It is overridden per call with `StorageClient::with_checksum`.
+    pub checksum: ChecksumConfig,
+    /// Whether the endpoints are the storage emulator like fake-gcs-server or the testbench.
+    /// The credentials are not resolved and the signed URLs can't be generated.
+    pub emulator: bool,
+    /// The user impersonated by the service account with the domain-wide delegation, used by `with_auth` and
+    /// `with_credentials`. The token is always minted by the token endpoint instead of the self-signed JWT.
+    pub subject: Option<String>,
+}
+
+/// default_http_client uses rustls rather than the native TLS when both TLS features are enabled.
+fn default_http_client() -> reqwest::Client {
+    let builder = reqwest::Client::builder();
+    #[cfg(feature = "rustls-tls")]
+    let builder = builder.use_rustls_tls();
+    builder.build().unwrap()
 }
 
 impl Default for ClientConfig {
     fn default() -> Self {
-        Self {
+        let config = Self {
             http: None,
             storage_endpoint: "https://storage.googleapis.com".to_string(),
+            upload_endpoint: None,
             token_source_provider: Some(Box::new(NopeTokenSourceProvider {})),
             service_account_endpoint: "https://iamcredentials.googleapis.com".to_string(),
             default_google_access_id: None,
             default_sign_by: None,
             project_id: None,
+            retry: RetryConfig::default(),
+            user_project: None,
+            checksum: ChecksumConfig::default(),
+            emulator: false,
+            subject: None,
+        };
+        match emulator_endpoint() {
+            Some(endpoint) => config.with_emulator(&endpoint),
+            None => config,
         }
     }
 }
@@ -68,6 +112,34 @@ impl ClientConfig {
         self.token_source_provider = None;
         self
     }
+
+    /// with_emulator sends the requests to the storage emulator without the credentials.
+    /// The host is like `localhost:4443` or `http://localhost:4443`.
+    /// It is applied by default when the `STORAGE_EMULATOR_HOST` environment variable is set.
+    pub fn with_emulator(mut self, host: &str) -> Self {
+        let endpoint = normalize_emulator_host(host);
+        self.storage_endpoint = endpoint.clone();
+        self.upload_endpoint = Some(endpoint);
+        self.emulator = true;
+        self.anonymous()
+    }
+}
+
+/// emulator_endpoint returns the endpoint of the storage emulator set by `STORAGE_EMULATOR_HOST`.
+pub(crate) fn emulator_endpoint() -> Option<String> {
+    match std::env::var("STORAGE_EMULATOR_HOST") {
+        Ok(host) if !host.trim().is_empty() => Some(normalize_emulator_host(&host)),
+        _ => None,
+    }
+}
+
+fn normalize_emulator_host(host: &str) -> String {
+    let host = host.trim().trim_end_matches('/');
+    if host.starts_with("http://") || host.starts_with("https://") {
+        host.to_string()
+    } else {
+        format!("http://{host}")
+    }
 }
 
 #[cfg(feature = "auth")]
@@ -75,8 +147,12 @@ pub use google_cloud_auth;
 
 #[cfg(feature = "auth")]
 impl ClientConfig {
+    /// with_auth resolves the credentials by ADC. It does nothing when the emulator is used.
     pub async fn with_auth(self) -> Result<Self, google_cloud_auth::error::Error> {
-        let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new(Self::auth_config()).await?;
+        if self.emulator {
+            return Ok(self);
+        }
+        let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new(self.auth_config()).await?;
         Ok(self.with_token_source(ts).await)
     }
 
@@ -84,8 +160,11 @@ impl ClientConfig {
         self,
         credentials: google_cloud_auth::credentials::CredentialsFile,
     ) -> Result<Self, google_cloud_auth::error::Error> {
+        if self.emulator {
+            return Ok(self);
+        }
         let ts = google_cloud_auth::token::DefaultTokenSourceProvider::new_with_credentials(
-            Self::auth_config(),
+            self.auth_config(),
             Box::new(credentials),
         )
         .await?;
@@ -95,13 +174,7 @@ impl ClientConfig {
     async fn with_token_source(mut self, ts: google_cloud_auth::token::DefaultTokenSourceProvider) -> Self {
         match &ts.source_credentials {
             // Credential file is used.
-            Some(cred) => {
-                self.project_id = cred.project_id.clone();
-                if let Some(pk) = &cred.private_key {
-                    self.default_sign_by = Some(PrivateKey(pk.clone().into_bytes()));
-                }
-                self.default_google_access_id = cred.client_email.clone();
-            }
+            Some(cred) => self.set_credentials(cred),
             // On Google Cloud
             None => {
                 self.project_id = Some(google_cloud_metadata::project_id().await);
@@ -113,21 +186,72 @@ impl ClientConfig {
         self
     }
 
-    fn auth_config() -> google_cloud_auth::project::Config<'static> {
+    /// with_bootstrap uses the token source, the project id and the `storage` endpoint override shared by the bootstrap.
+    /// The quota project is used as `user_project` unless it is already set.
+    /// It connects to the emulator instead if the bootstrap has its host.
+    pub fn with_bootstrap(mut self, bootstrap: &google_cloud_auth::bootstrap::Bootstrap) -> Self {
+        if let Some(endpoint) = bootstrap.endpoint("storage") {
+            self.storage_endpoint = endpoint.to_string();
+        }
+        if let Some(host) = bootstrap.emulator_host("storage") {
+            return self.with_emulator(host);
+        }
+        if self.emulator {
+            return self;
+        }
+        if let Some(cred) = bootstrap.credentials() {
+            self.set_credentials(cred);
+        }
+        self.project_id = bootstrap.project_id().map(String::from).or(self.project_id);
+        self.user_project = self.user_project.or(bootstrap.quota_project().map(String::from));
+        self.token_source_provider = Some(bootstrap.token_source_provider());
+        self
+    }
+
+    fn set_credentials(&mut self, cred: &google_cloud_auth::credentials::CredentialsFile) {
+        self.project_id = cred.project_id.clone();
+        // Without the private key like the workload identity federation, sign with IAM signBlob API.
+        self.default_sign_by = match &cred.private_key {
+            Some(pk) => Some(PrivateKey(pk.clone().into_bytes())),
+            None => Some(SignBy::SignBytes),
+        };
+        self.default_google_access_id = cred.client_email.clone().or_else(|| {
+            cred.service_account_impersonation_url
+                .as_deref()
+                .and_then(impersonated_email)
+        });
+    }
+
+    fn auth_config(&self) -> google_cloud_auth::project::Config<'_> {
         google_cloud_auth::project::Config {
             audience: None,
             scopes: Some(&crate::http::storage_client::SCOPES),
-            sub: None,
+            sub: self.subject.as_deref(),
         }
     }
 }
 
+/// impersonated_email returns the email of the service account from the URL like
+/// `https://iamcredentials.googleapis.com/v1/projects/-/serviceAccounts/{email}:generateAccessToken`.
+#[cfg(feature = "auth")]
+fn impersonated_email(url: &str) -> Option<String> {
+    let (_, resource) = url.rsplit_once("/serviceAccounts/")?;
+    let email = resource.split(':').next()?;
+    if email.is_empty() {
+        None
+    } else {
+        Some(email.to_string())
+    }
+}
+
 #[derive(Clone)]
 pub struct Client {
     default_google_access_id: Option<String>,
     default_sign_by: Option<SignBy>,
+    emulator: bool,
     storage_client: StorageClient,
-    service_account_client: ServiceAccountClient,
+    #[cfg(feature = "auth")]
+    iam_client: Option<google_cloud_iam::client::Client>,
 }
 
 impl Deref for Client {
@@ -150,17 +274,33 @@ impl Client {
         };
         let http = config
             .http
-            .unwrap_or_else(|| reqwest_middleware::ClientBuilder::new(reqwest::Client::default()).build());
-
-        let service_account_client =
-            ServiceAccountClient::new(ts.clone(), config.service_account_endpoint.as_str(), http.clone());
-        let storage_client = StorageClient::new(ts, config.storage_endpoint.as_str(), http);
+            .unwrap_or_else(|| reqwest_middleware::ClientBuilder::new(default_http_client()).build());
+
+        #[cfg(feature = "auth")]
+        let iam_client = ts.clone().map(|ts| {
+            let config = google_cloud_iam::client::ClientConfig::default()
+                .with_endpoint(config.service_account_endpoint.as_str())
+                .with_http_client(http.clone());
+            google_cloud_iam::client::Client::new_with_token_source(ts, config)
+        });
+        let upload_endpoint = config.upload_endpoint.as_ref().unwrap_or(&config.storage_endpoint);
+        let storage_client = StorageClient::new(
+            ts,
+            config.storage_endpoint.as_str(),
+            upload_endpoint.as_str(),
+            http,
+            config.retry,
+            config.user_project,
+            config.checksum,
+        );
 
         Self {
             default_google_access_id: config.default_google_access_id,
             default_sign_by: config.default_sign_by,
+            emulator: config.emulator,
             storage_client,
-            service_account_client,
+            #[cfg(feature = "auth")]
+            iam_client,
         }
     }
 
@@ -208,38 +348,93 @@ impl Client {
         sign_by: Option<SignBy>,
         opts: SignedURLOptions,
     ) -> Result<String, SignedURLError> {
-        // use the one from the options or the default one or error out
+        let (google_access_id, sign_by) = self.signer(google_access_id, sign_by)?;
 
-        let google_access_id = match &google_access_id {
-            Some(overwritten_gai) => overwritten_gai.to_owned(),
-            None => {
-                let default_gai = &self
-                    .default_google_access_id
-                    .clone()
-                    .ok_or(SignedURLError::InvalidOption("No default google_access_id is found"))?;
+        let (signed_buffer, mut builder) = create_signed_buffer(bucket, object, &google_access_id, &opts)?;
+        tracing::trace!("signed_buffer={:?}", String::from_utf8_lossy(&signed_buffer));
 
-                default_gai.to_owned()
-            }
-        };
+        let signature = self.sign(&google_access_id, &sign_by, &signed_buffer).await?;
+        builder
+            .query_pairs_mut()
+            .append_pair("X-Goog-Signature", &hex::encode(signature));
+        Ok(builder.to_string())
+    }
 
-        // use the one from the options or the default one or error out
-        let sign_by = match &sign_by {
-            Some(overwritten_sign_by) => overwritten_sign_by.to_owned(),
-            None => {
-                let default_sign_by = &self
-                    .default_sign_by
-                    .clone()
-                    .ok_or(SignedURLError::InvalidOption("No default sign_by is found"))?;
+    /// Generate the V4 POST policy document.
+    /// The policy allows the browser to upload the object with the HTML form without the Google account.
+    /// The form must be posted to the returned url with the returned fields and the `file` field at the last.
+    /// See https://cloud.google.com/storage/docs/xml-api/post-object-forms.
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::sign::{PostPolicyV4Condition, PostPolicyV4Fields, PostPolicyV4Options};
+    ///
+    /// async fn run(client: Client) {
+    ///     let policy = client.generate_post_policy_v4("bucket", "file.txt", None, None, PostPolicyV4Options {
+    ///         fields: PostPolicyV4Fields {
+    ///             content_type: Some("text/plain".to_string()),
+    ///             ..Default::default()
+    ///         },
+    ///         conditions: vec![PostPolicyV4Condition::ContentLengthRange(0, 1024 * 1024)],
+    ///         ..Default::default()
+    ///     }).await.unwrap();
+    ///     println!("url={} fields={:?}", policy.url, policy.fields);
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn generate_post_policy_v4(
+        &self,
+        bucket: &str,
+        object: &str,
+        google_access_id: Option<String>,
+        sign_by: Option<SignBy>,
+        opts: PostPolicyV4Options,
+    ) -> Result<PostPolicyV4, SignedURLError> {
+        let (google_access_id, sign_by) = self.signer(google_access_id, sign_by)?;
+
+        let (policy, mut post_policy) = create_post_policy(bucket, object, &google_access_id, &opts)?;
+        let signature = self.sign(&google_access_id, &sign_by, &policy).await?;
+        post_policy
+            .fields
+            .insert("x-goog-signature".to_string(), hex::encode(signature));
+        Ok(post_policy)
+    }
 
-                default_sign_by.to_owned()
-            }
+    /// signer returns the google_access_id and the sign_by overwritten or the default ones.
+    fn signer(
+        &self,
+        google_access_id: Option<String>,
+        sign_by: Option<SignBy>,
+    ) -> Result<(String, SignBy), SignedURLError> {
+        // the emulators don't verify the signature
+        if self.emulator {
+            return Err(SignedURLError::InvalidOption(
+                "Signing is not supported by the storage emulator",
+            ));
+        }
+        // use the one from the options or the default one or error out
+        let google_access_id = match google_access_id {
+            Some(overwritten_gai) => overwritten_gai,
+            None => self
+                .default_google_access_id
+                .clone()
+                .ok_or(SignedURLError::InvalidOption("No default google_access_id is found"))?,
         };
 
-        let (signed_buffer, mut builder) = create_signed_buffer(bucket, object, &google_access_id, &opts)?;
-        tracing::trace!("signed_buffer={:?}", String::from_utf8_lossy(&signed_buffer));
+        // use the one from the options or the default one or error out
+        let sign_by = match sign_by {
+            Some(overwritten_sign_by) => overwritten_sign_by,
+            None => self
+                .default_sign_by
+                .clone()
+                .ok_or(SignedURLError::InvalidOption("No default sign_by is found"))?,
+        };
+        Ok((google_access_id, sign_by))
+    }
 
-        // create signature
-        let signature = match &sign_by {
+    /// sign signs the buffer with the private key or with the IAM signBlob API when only ADC is available.
+    async fn sign(&self, google_access_id: &str, sign_by: &SignBy, buffer: &[u8]) -> Result<Vec<u8>, SignedURLError> {
+        let signature = match sign_by {
             PrivateKey(private_key) => {
                 // if sign_by is a collection of private keys we check that at least one is present
                 if private_key.is_empty() {
@@ -248,27 +443,25 @@ impl Client {
                 let key_pair = &RsaKeyPair::try_from(private_key)?;
                 let mut signed = vec![0; key_pair.public().modulus_len()];
                 key_pair
-                    .sign(
-                        &signature::RSA_PKCS1_SHA256,
-                        &rand::SystemRandom::new(),
-                        signed_buffer.as_slice(),
-                        &mut signed,
-                    )
+                    .sign(&signature::RSA_PKCS1_SHA256, &rand::SystemRandom::new(), buffer, &mut signed)
                     .map_err(|e| SignedURLError::CertError(e.to_string()))?;
                 signed
             }
+            #[cfg(feature = "auth")]
             SignBy::SignBytes => {
-                let path = format!("projects/-/serviceAccounts/{}", google_access_id);
-                self.service_account_client
-                    .sign_blob(&path, signed_buffer.as_slice())
-                    .await
-                    .map_err(SignedURLError::SignBlob)?
+                let iam_client = self
+                    .iam_client
+                    .as_ref()
+                    .ok_or(SignedURLError::InvalidOption("Signing with IAM requires the credentials"))?;
+                iam_client.sign_blob(google_access_id, buffer).await?.signed_blob
+            }
+            #[cfg(not(feature = "auth"))]
+            SignBy::SignBytes => {
+                let _ = google_access_id;
+                return Err(SignedURLError::InvalidOption("Signing with IAM requires the `auth` feature"));
             }
         };
-        builder
-            .query_pairs_mut()
-            .append_pair("X-Goog-Signature", &hex::encode(signature));
-        Ok(builder.to_string())
+        Ok(signature)
     }
 }
 
@@ -279,9 +472,21 @@ mod test {
 
     use crate::client::{Client, ClientConfig};
     use crate::http::buckets::get::GetBucketRequest;
+    use crate::http::objects::get::GetObjectRequest;
 
     use crate::http::storage_client::test::bucket_name;
-    use crate::sign::{SignedURLMethod, SignedURLOptions};
+    use crate::sign::{
+        PostPolicyV4Condition, PostPolicyV4Fields, PostPolicyV4Options, SignedURLError, SignedURLMethod,
+        SignedURLOptions,
+    };
+
+    #[cfg(feature = "auth")]
+    #[test]
+    fn test_impersonated_email() {
+        let url = "https://iamcredentials.googleapis.com/v1/projects/-/serviceAccounts/sa@p.iam:generateAccessToken";
+        assert_eq!(crate::client::impersonated_email(url).as_deref(), Some("sa@p.iam"));
+        assert_eq!(crate::client::impersonated_email("https://example.com/token"), None);
+    }
 
     async fn create_client() -> (Client, String) {
         let config = ClientConfig::default().with_auth().await.unwrap();
@@ -402,6 +607,52 @@ mod test {
         assert_eq!(result, data);
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_post_policy() {
+        let (client, project) = create_client().await;
+        let bucket_name = bucket_name(&project, "object");
+        let data = "aiueo";
+
+        let opts = PostPolicyV4Options {
+            fields: PostPolicyV4Fields {
+                content_type: Some("text/plain".to_string()),
+                ..Default::default()
+            },
+            conditions: vec![PostPolicyV4Condition::ContentLengthRange(0, 10)],
+            ..Default::default()
+        };
+        let policy = client
+            .generate_post_policy_v4(&bucket_name, "signed_post_policy_test", None, None, opts)
+            .await
+            .unwrap();
+        assert_eq!(policy.url, format!("https://storage.googleapis.com/{bucket_name}/"));
+        let mut form = reqwest::multipart::Form::new();
+        for (k, v) in policy.fields {
+            form = form.text(k, v);
+        }
+        form = form.part("file", reqwest::multipart::Part::bytes(data.as_bytes()));
+        let result = reqwest::Client::default()
+            .post(policy.url)
+            .multipart(form)
+            .send()
+            .await
+            .unwrap();
+        let status = result.status();
+        assert!(status.is_success(), "{:?}", result.text().await.unwrap());
+
+        let object = client
+            .get_object(&GetObjectRequest {
+                bucket: bucket_name,
+                object: "signed_post_policy_test".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(object.content_type.unwrap(), "text/plain");
+        assert_eq!(object.size, data.len() as i64);
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_anonymous() {
@@ -419,4 +670,64 @@ mod test {
             .unwrap();
         assert_eq!(result.name, bucket);
     }
+
+    #[tokio::test]
+    async fn test_emulator() {
+        let config = ClientConfig::default().with_emulator("localhost:4443/");
+        assert_eq!(config.storage_endpoint, "http://localhost:4443");
+        assert_eq!(config.upload_endpoint.as_deref(), Some("http://localhost:4443"));
+        assert!(config.token_source_provider.is_none());
+        let config = config.with_auth().await.unwrap();
+        assert!(config.token_source_provider.is_none());
+
+        let client = Client::new(config);
+        let result = client
+            .signed_url("bucket", "object", None, None, SignedURLOptions::default())
+            .await;
+        assert!(matches!(result, Err(SignedURLError::InvalidOption(_))), "{result:?}");
+
+        let config = ClientConfig::default().with_emulator("https://testbench:9000");
+        assert_eq!(config.storage_endpoint, "https://testbench:9000");
+    }
+
+    #[cfg(feature = "auth")]
+    #[tokio::test]
+    #[serial]
+    async fn test_with_bootstrap() {
+        use google_cloud_auth::bootstrap::ClientBootstrap;
+        use google_cloud_token::NopeTokenSourceProvider;
+
+        let original = std::env::var("STORAGE_EMULATOR_HOST").ok();
+        std::env::remove_var("STORAGE_EMULATOR_HOST");
+        let bootstrap = ClientBootstrap::new()
+            .with_token_provider(Box::new(NopeTokenSourceProvider {}))
+            .with_project_id("project")
+            .with_quota_project("quota-project")
+            .with_endpoint_overrides([("storage", "https://storage.example.com")])
+            .build()
+            .await
+            .unwrap();
+        let config = ClientConfig::default().with_bootstrap(&bootstrap);
+        assert!(!config.emulator);
+        assert!(config.token_source_provider.is_some());
+        assert_eq!(config.storage_endpoint, "https://storage.example.com");
+        assert_eq!(config.project_id.as_deref(), Some("project"));
+        assert_eq!(config.user_project.as_deref(), Some("quota-project"));
+
+        std::env::set_var("STORAGE_EMULATOR_HOST", "localhost:4443");
+        let bootstrap = ClientBootstrap::new()
+            .with_token_provider(Box::new(NopeTokenSourceProvider {}))
+            .build()
+            .await
+            .unwrap();
+        std::env::remove_var("STORAGE_EMULATOR_HOST");
+        let config = ClientConfig::default().with_bootstrap(&bootstrap);
+        assert!(config.emulator);
+        assert!(config.token_source_provider.is_none());
+        assert_eq!(config.storage_endpoint, "http://localhost:4443");
+
+        if let Some(original) = original {
+            std::env::set_var("STORAGE_EMULATOR_HOST", original);
+        }
+    }
 }
@@ -3,7 +3,9 @@ use std::collections::HashMap;
 use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
 
 use crate::http::bucket_access_controls::{BucketAccessControl, PredefinedBucketAcl};
-use crate::http::buckets::{Billing, Cors, Encryption, IamConfiguration, Lifecycle, Logging, Versioning, Website};
+use crate::http::buckets::{
+    Billing, Cors, Encryption, IamConfiguration, Lifecycle, Logging, SoftDeletePolicy, Versioning, Website,
+};
 use crate::http::object_access_controls::insert::ObjectAccessControlCreationConfig;
 use crate::http::object_access_controls::{PredefinedObjectAcl, Projection};
 
@@ -56,6 +58,9 @@ pub struct BucketCreationConfig {
     /// The bucket's retention policy, which defines the minimum age
     /// an object in the bucket must have to be deleted or replaced.
     pub retention_policy: Option<RetentionPolicyCreationConfig>,
+    /// The bucket's soft delete policy. The bucket has the default policy if it is not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soft_delete_policy: Option<SoftDeletePolicy>,
     /// The bucket's IAM configuration.
     pub iam_configuration: Option<IamConfiguration>,
     /// The recovery point objective for cross-region replication of the bucket.
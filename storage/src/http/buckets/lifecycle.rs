@@ -0,0 +1,401 @@
+use time::Date;
+
+use crate::http::buckets::lifecycle::rule::{Action, ActionType, Condition};
+
+/// The storage classes accepted by the lifecycle rules.
+const STORAGE_CLASSES: [&str; 7] = [
+    "STANDARD",
+    "NEARLINE",
+    "COLDLINE",
+    "ARCHIVE",
+    "MULTI_REGIONAL",
+    "REGIONAL",
+    "DURABLE_REDUCED_AVAILABILITY",
+];
+
+/// A lifecycle Rule, combining an action to take on an object and a
+/// condition which will trigger that action.
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    /// The action to take.
+    pub action: Option<rule::Action>,
+    /// The condition(s) under which the action will be taken.
+    pub condition: Option<rule::Condition>,
+}
+/// Nested message and enum types in `Rule`.
+pub mod rule {
+    use time::Date;
+
+    // RFC3339 Date part, in format YYYY-MM-DD
+    time::serde::format_description!(date_format, Date, "[year]-[month]-[day]");
+
+    #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    pub enum ActionType {
+        /// Deletes a Bucket.
+        Delete,
+        /// Sets the `storage_class` of a Bucket.
+        SetStorageClass,
+        /// Aborts the incomplete XML API multipart uploads.
+        AbortIncompleteMultipartUpload,
+    }
+    /// An action to take on an object.
+    #[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Action {
+        pub r#type: ActionType,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub storage_class: Option<String>,
+    }
+    /// A condition of an object which triggers some action.
+    /// The unset conditions are omitted in the request, `age: Some(0)` matches all the objects.
+    #[derive(Clone, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Condition {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub age: Option<i32>,
+        #[serde(default, with = "date_format::option", skip_serializing_if = "Option::is_none")]
+        pub created_before: Option<Date>,
+        #[serde(default, with = "date_format::option", skip_serializing_if = "Option::is_none")]
+        pub custom_time_before: Option<Date>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub days_since_custom_time: Option<i32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub days_since_noncurrent_time: Option<i32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub is_live: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub matches_storage_class: Option<Vec<String>>,
+        #[serde(default, with = "date_format::option", skip_serializing_if = "Option::is_none")]
+        pub noncurrent_time_before: Option<Date>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub num_newer_versions: Option<i32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub matches_prefix: Option<Vec<String>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub matches_suffix: Option<Vec<String>>,
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum LifecycleRuleError {
+    #[error("lifecycle rule must have at least one condition")]
+    NoCondition,
+    #[error("condition {0} is not allowed for the action {1:?}")]
+    ConditionNotAllowed(&'static str, ActionType),
+    #[error("invalid storage class: {0}")]
+    InvalidStorageClass(String),
+    #[error("condition {0} must not be negative: {1}")]
+    NegativeValue(&'static str, i32),
+    #[error("condition {0} must not be empty")]
+    EmptyList(&'static str),
+}
+
+/// LifecycleRule builds the validated lifecycle `Rule`.
+///
+/// ```
+/// use google_cloud_storage::http::buckets::lifecycle::LifecycleRule;
+///
+/// let rule = LifecycleRule::set_storage_class("NEARLINE")
+///     .with_age(30)
+///     .with_matches_prefix(vec!["logs/".to_string()])
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LifecycleRule {
+    action: Action,
+    condition: Condition,
+}
+
+impl LifecycleRule {
+    /// Deletes the objects matching the conditions.
+    pub fn delete() -> Self {
+        Self::new(ActionType::Delete, None)
+    }
+
+    /// Changes the storage class of the objects matching the conditions.
+    pub fn set_storage_class(storage_class: impl Into<String>) -> Self {
+        Self::new(ActionType::SetStorageClass, Some(storage_class.into()))
+    }
+
+    /// Aborts the incomplete multipart uploads matching the conditions.
+    /// Only `age`, `matches_prefix` and `matches_suffix` are allowed.
+    pub fn abort_incomplete_multipart_upload() -> Self {
+        Self::new(ActionType::AbortIncompleteMultipartUpload, None)
+    }
+
+    fn new(r#type: ActionType, storage_class: Option<String>) -> Self {
+        Self {
+            action: Action { r#type, storage_class },
+            condition: Condition::default(),
+        }
+    }
+
+    pub fn with_age(mut self, days: i32) -> Self {
+        self.condition.age = Some(days);
+        self
+    }
+
+    pub fn with_created_before(mut self, date: Date) -> Self {
+        self.condition.created_before = Some(date);
+        self
+    }
+
+    pub fn with_custom_time_before(mut self, date: Date) -> Self {
+        self.condition.custom_time_before = Some(date);
+        self
+    }
+
+    pub fn with_days_since_custom_time(mut self, days: i32) -> Self {
+        self.condition.days_since_custom_time = Some(days);
+        self
+    }
+
+    pub fn with_days_since_noncurrent_time(mut self, days: i32) -> Self {
+        self.condition.days_since_noncurrent_time = Some(days);
+        self
+    }
+
+    pub fn with_is_live(mut self, is_live: bool) -> Self {
+        self.condition.is_live = Some(is_live);
+        self
+    }
+
+    pub fn with_matches_storage_class(mut self, storage_classes: Vec<String>) -> Self {
+        self.condition.matches_storage_class = Some(storage_classes);
+        self
+    }
+
+    pub fn with_noncurrent_time_before(mut self, date: Date) -> Self {
+        self.condition.noncurrent_time_before = Some(date);
+        self
+    }
+
+    pub fn with_num_newer_versions(mut self, versions: i32) -> Self {
+        self.condition.num_newer_versions = Some(versions);
+        self
+    }
+
+    pub fn with_matches_prefix(mut self, prefixes: Vec<String>) -> Self {
+        self.condition.matches_prefix = Some(prefixes);
+        self
+    }
+
+    pub fn with_matches_suffix(mut self, suffixes: Vec<String>) -> Self {
+        self.condition.matches_suffix = Some(suffixes);
+        self
+    }
+
+    /// Validates the conditions and returns the rule.
+    pub fn build(self) -> Result<Rule, LifecycleRuleError> {
+        self.validate()?;
+        Ok(Rule {
+            action: Some(self.action),
+            condition: Some(self.condition),
+        })
+    }
+
+    fn validate(&self) -> Result<(), LifecycleRuleError> {
+        let c = &self.condition;
+        let set = [
+            ("age", c.age.is_some()),
+            ("createdBefore", c.created_before.is_some()),
+            ("customTimeBefore", c.custom_time_before.is_some()),
+            ("daysSinceCustomTime", c.days_since_custom_time.is_some()),
+            ("daysSinceNoncurrentTime", c.days_since_noncurrent_time.is_some()),
+            ("isLive", c.is_live.is_some()),
+            ("matchesStorageClass", c.matches_storage_class.is_some()),
+            ("noncurrentTimeBefore", c.noncurrent_time_before.is_some()),
+            ("numNewerVersions", c.num_newer_versions.is_some()),
+            ("matchesPrefix", c.matches_prefix.is_some()),
+            ("matchesSuffix", c.matches_suffix.is_some()),
+        ];
+        if !set.iter().any(|(_, is_set)| *is_set) {
+            return Err(LifecycleRuleError::NoCondition);
+        }
+        if self.action.r#type == ActionType::AbortIncompleteMultipartUpload {
+            let allowed = ["age", "matchesPrefix", "matchesSuffix"];
+            if let Some((name, _)) = set.iter().find(|(name, is_set)| *is_set && !allowed.contains(name)) {
+                return Err(LifecycleRuleError::ConditionNotAllowed(name, self.action.r#type.clone()));
+            }
+        }
+        if let Some(class) = &self.action.storage_class {
+            validate_storage_class(class)?;
+        }
+        for (name, value) in [
+            ("age", c.age),
+            ("daysSinceCustomTime", c.days_since_custom_time),
+            ("daysSinceNoncurrentTime", c.days_since_noncurrent_time),
+            ("numNewerVersions", c.num_newer_versions),
+        ] {
+            match value {
+                Some(v) if v < 0 => return Err(LifecycleRuleError::NegativeValue(name, v)),
+                _ => {}
+            }
+        }
+        for (name, values) in [
+            ("matchesStorageClass", &c.matches_storage_class),
+            ("matchesPrefix", &c.matches_prefix),
+            ("matchesSuffix", &c.matches_suffix),
+        ] {
+            if values.as_ref().is_some_and(|v| v.is_empty()) {
+                return Err(LifecycleRuleError::EmptyList(name));
+            }
+        }
+        if let Some(classes) = &c.matches_storage_class {
+            classes.iter().try_for_each(|v| validate_storage_class(v))?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_storage_class(class: &str) -> Result<(), LifecycleRuleError> {
+    if STORAGE_CLASSES.contains(&class) {
+        Ok(())
+    } else {
+        Err(LifecycleRuleError::InvalidStorageClass(class.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+    use time::macros::date;
+
+    use crate::http::buckets::lifecycle::rule::{Action, ActionType, Condition};
+    use crate::http::buckets::lifecycle::{LifecycleRule, LifecycleRuleError, Rule};
+    use crate::http::buckets::Lifecycle;
+
+    fn round_trip(fixture: Value) -> Lifecycle {
+        let lifecycle: Lifecycle = serde_json::from_value(fixture.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&lifecycle).unwrap(), fixture);
+        lifecycle
+    }
+
+    #[test]
+    fn test_round_trip() {
+        // The lifecycle returned from buckets.get
+        let lifecycle = round_trip(json!({
+            "rule": [
+                {
+                    "action": {"type": "Delete"},
+                    "condition": {"age": 365, "isLive": true}
+                },
+                {
+                    "action": {"type": "SetStorageClass", "storageClass": "COLDLINE"},
+                    "condition": {
+                        "createdBefore": "2023-01-15",
+                        "matchesStorageClass": ["STANDARD", "NEARLINE"],
+                        "matchesPrefix": ["logs/"],
+                        "matchesSuffix": [".log", ".txt"]
+                    }
+                },
+                {
+                    "action": {"type": "Delete"},
+                    "condition": {
+                        "daysSinceNoncurrentTime": 7,
+                        "noncurrentTimeBefore": "2022-12-31",
+                        "numNewerVersions": 2,
+                        "isLive": false
+                    }
+                },
+                {
+                    "action": {"type": "Delete"},
+                    "condition": {"customTimeBefore": "2024-02-29", "daysSinceCustomTime": 0}
+                },
+                {
+                    "action": {"type": "AbortIncompleteMultipartUpload"},
+                    "condition": {"age": 0}
+                }
+            ]
+        }));
+        let condition = lifecycle.rule[1].condition.as_ref().unwrap();
+        assert_eq!(condition.created_before, Some(date!(2023 - 01 - 15)));
+        assert_eq!(condition.matches_suffix, Some(vec![".log".to_string(), ".txt".to_string()]));
+        assert_eq!(lifecycle.rule[3].condition.as_ref().unwrap().days_since_custom_time, Some(0));
+        assert_eq!(lifecycle.rule[4].condition.as_ref().unwrap().age, Some(0));
+        assert_eq!(
+            lifecycle.rule[4].action.as_ref().unwrap().r#type,
+            ActionType::AbortIncompleteMultipartUpload
+        );
+
+        round_trip(json!({"rule": []}));
+    }
+
+    #[test]
+    fn test_build() {
+        let rule = LifecycleRule::set_storage_class("ARCHIVE")
+            .with_created_before(date!(2023 - 01 - 15))
+            .with_matches_storage_class(vec!["COLDLINE".to_string()])
+            .build()
+            .unwrap();
+        assert_eq!(
+            rule,
+            Rule {
+                action: Some(Action {
+                    r#type: ActionType::SetStorageClass,
+                    storage_class: Some("ARCHIVE".to_string()),
+                }),
+                condition: Some(Condition {
+                    created_before: Some(date!(2023 - 01 - 15)),
+                    matches_storage_class: Some(vec!["COLDLINE".to_string()]),
+                    ..Default::default()
+                }),
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&rule).unwrap(),
+            json!({
+                "action": {"type": "SetStorageClass", "storageClass": "ARCHIVE"},
+                "condition": {"createdBefore": "2023-01-15", "matchesStorageClass": ["COLDLINE"]}
+            })
+        );
+
+        let rule = LifecycleRule::abort_incomplete_multipart_upload()
+            .with_age(7)
+            .with_matches_prefix(vec!["tmp/".to_string()])
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&rule).unwrap(),
+            json!({
+                "action": {"type": "AbortIncompleteMultipartUpload"},
+                "condition": {"age": 7, "matchesPrefix": ["tmp/"]}
+            })
+        );
+    }
+
+    #[test]
+    fn test_build_invalid() {
+        assert_eq!(LifecycleRule::delete().build(), Err(LifecycleRuleError::NoCondition));
+        assert_eq!(
+            LifecycleRule::abort_incomplete_multipart_upload()
+                .with_age(1)
+                .with_is_live(true)
+                .build(),
+            Err(LifecycleRuleError::ConditionNotAllowed(
+                "isLive",
+                ActionType::AbortIncompleteMultipartUpload
+            ))
+        );
+        assert_eq!(
+            LifecycleRule::set_storage_class("nearline").with_age(1).build(),
+            Err(LifecycleRuleError::InvalidStorageClass("nearline".to_string()))
+        );
+        assert_eq!(
+            LifecycleRule::delete()
+                .with_matches_storage_class(vec!["STANDARD".to_string(), "COLD".to_string()])
+                .build(),
+            Err(LifecycleRuleError::InvalidStorageClass("COLD".to_string()))
+        );
+        assert_eq!(
+            LifecycleRule::delete().with_num_newer_versions(-1).build(),
+            Err(LifecycleRuleError::NegativeValue("numNewerVersions", -1))
+        );
+        assert_eq!(
+            LifecycleRule::delete().with_matches_suffix(vec![]).build(),
+            Err(LifecycleRuleError::EmptyList("matchesSuffix"))
+        );
+    }
+}
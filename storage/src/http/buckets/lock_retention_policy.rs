@@ -11,10 +11,10 @@ pub struct LockRetentionPolicyRequest {
     pub bucket: String,
     /// Makes the operation conditional on whether bucket's current metageneration
     /// matches the given value. Must be positive.
+    /// It is required because the lock is irreversible, so the policy must be the one the caller has seen.
     pub if_metageneration_match: i64,
 }
 
-#[allow(dead_code)]
 pub(crate) fn build(base_url: &str, client: &Client, req: &LockRetentionPolicyRequest) -> RequestBuilder {
     let url = format!("{}/b/{}/lockRetentionPolicy", base_url, req.bucket.escape());
     client.post(url).query(&req)
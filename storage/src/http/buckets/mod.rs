@@ -8,6 +8,7 @@ pub mod delete;
 pub mod get;
 pub mod get_iam_policy;
 pub mod insert;
+pub mod lifecycle;
 pub mod list;
 pub mod list_channels;
 pub mod lock_retention_policy;
@@ -120,6 +121,8 @@ pub struct Bucket {
     /// Attempting to remove or decrease period of a locked retention policy will
     /// result in a PERMISSION_DENIED error.
     pub retention_policy: Option<RetentionPolicy>,
+    /// The bucket's soft delete policy. The deleted objects are kept restorable for its retention duration.
+    pub soft_delete_policy: Option<SoftDeletePolicy>,
     /// The location type of the bucket (region, dual-region, multi-region, etc).
     pub location_type: String,
     /// The recovery point objective for cross-region replication of the bucket.
@@ -212,65 +215,14 @@ pub mod iam_configuration {
 }
 /// Lifecycle properties of a bucket.
 /// For more information, see <https://cloud.google.com/storage/docs/lifecycle.>
-#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug)]
+/// The rules are built with `lifecycle::LifecycleRule` to validate them.
+#[derive(Clone, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Lifecycle {
     /// A lifecycle management rule, which is made of an action to take and the
     /// condition(s) under which the action will be taken.
     pub rule: Vec<lifecycle::Rule>,
 }
-/// Nested message and enum types in `Lifecycle`.
-pub mod lifecycle {
-    /// A lifecycle Rule, combining an action to take on an object and a
-    /// condition which will trigger that action.
-    #[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug)]
-    #[serde(rename_all = "camelCase")]
-    pub struct Rule {
-        /// The action to take.
-        pub action: Option<rule::Action>,
-        /// The condition(s) under which the action will be taken.
-        pub condition: Option<rule::Condition>,
-    }
-    /// Nested message and enum types in `Rule`.
-    pub mod rule {
-        use time::Date;
-
-        // RFC3339 Date part, in format YYYY-MM-DD
-        time::serde::format_description!(date_format, Date, "[year]-[month]-[day]");
-
-        #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
-        pub enum ActionType {
-            /// Deletes a Bucket.
-            Delete,
-            /// Sets the `storage_class` of a Bucket.
-            SetStorageClass,
-        }
-        /// An action to take on an object.
-        #[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug)]
-        #[serde(rename_all = "camelCase")]
-        pub struct Action {
-            pub r#type: ActionType,
-            pub storage_class: Option<String>,
-        }
-        /// A condition of an object which triggers some action.
-        #[derive(Clone, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize, Debug)]
-        #[serde(rename_all = "camelCase")]
-        pub struct Condition {
-            pub age: i32,
-            #[serde(default, with = "date_format::option")]
-            pub created_before: Option<Date>,
-            #[serde(default, with = "date_format::option")]
-            pub custom_time_before: Option<Date>,
-            pub days_since_custom_time: Option<i32>,
-            pub days_since_noncurrent_time: Option<i32>,
-            pub is_live: Option<bool>,
-            pub matches_storage_class: Option<Vec<String>>,
-            #[serde(default, with = "date_format::option")]
-            pub noncurrent_time_before: Option<Date>,
-            pub num_newer_versions: Option<i32>,
-        }
-    }
-}
 /// Logging-related properties of a bucket.
 #[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -298,6 +250,23 @@ pub struct RetentionPolicy {
     #[serde(deserialize_with = "crate::http::from_str")]
     pub retention_period: u64,
 }
+/// Soft delete policy properties of a bucket.
+/// https://cloud.google.com/storage/docs/soft-delete
+#[derive(Clone, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SoftDeletePolicy {
+    /// The duration in seconds that the soft-deleted objects are retained.
+    /// It must be 0 to disable the soft delete, or between 7 and 90 days.
+    #[serde(deserialize_with = "crate::http::from_str")]
+    pub retention_duration_seconds: u64,
+    /// Server-determined value that indicates the time from which the policy is effective.
+    #[serde(
+        default,
+        skip_serializing,
+        deserialize_with = "time::serde::rfc3339::option::deserialize"
+    )]
+    pub effective_time: Option<OffsetDateTime>,
+}
 /// Properties of a bucket related to versioning.
 /// For more on GCS versioning, see
 /// <https://cloud.google.com/storage/docs/object-versioning.>
@@ -4,7 +4,9 @@ use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
 
 use crate::http::bucket_access_controls::{BucketAccessControl, PredefinedBucketAcl};
 use crate::http::buckets::insert::RetentionPolicyCreationConfig;
-use crate::http::buckets::{Billing, Cors, Encryption, IamConfiguration, Lifecycle, Logging, Versioning, Website};
+use crate::http::buckets::{
+    Billing, Cors, Encryption, IamConfiguration, Lifecycle, Logging, SoftDeletePolicy, Versioning, Website,
+};
 use crate::http::object_access_controls::insert::ObjectAccessControlCreationConfig;
 use crate::http::object_access_controls::{PredefinedObjectAcl, Projection};
 use crate::http::Escape;
@@ -54,6 +56,9 @@ pub struct BucketPatchConfig {
     /// The bucket's retention policy, which defines the minimum age
     /// an object in the bucket must have to be deleted or replaced.
     pub retention_policy: Option<RetentionPolicyCreationConfig>,
+    /// The bucket's soft delete policy. The bucket has the default policy if it is not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub soft_delete_policy: Option<SoftDeletePolicy>,
     /// The bucket's IAM configuration.
     pub iam_configuration: Option<IamConfiguration>,
     /// The recovery point objective for cross-region replication of the bucket.
@@ -96,3 +101,250 @@ pub(crate) fn build(base_url: &str, client: &Client, req: &PatchBucketRequest) -
         builder
     }
 }
+
+/// BucketPatch is the bucket metadata patching only the fields explicitly set.
+/// Unlike `BucketPatchConfig`, the fields not set are omitted from the request body,
+/// so the fields managed by the others are not overwritten. The `clear_*` methods send null to remove the field.
+/// ```
+/// use google_cloud_storage::http::buckets::patch::BucketPatch;
+/// use google_cloud_storage::http::buckets::Versioning;
+///
+/// let patch = BucketPatch::default()
+///     .with_versioning(Versioning { enabled: true })
+///     .with_label("env", "prod")
+///     .clear_website();
+/// assert_eq!(
+///     serde_json::to_value(&patch).unwrap(),
+///     serde_json::json!({"versioning": {"enabled": true}, "labels": {"env": "prod"}, "website": null})
+/// );
+/// ```
+#[derive(Clone, PartialEq, Default, serde::Serialize, Debug)]
+#[serde(transparent)]
+pub struct BucketPatch {
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl BucketPatch {
+    fn set<T: serde::Serialize>(mut self, name: &str, value: &T) -> Self {
+        let value = serde_json::to_value(value).unwrap_or(serde_json::Value::Null);
+        self.fields.insert(name.to_string(), value);
+        self
+    }
+
+    fn clear(mut self, name: &str) -> Self {
+        self.fields.insert(name.to_string(), serde_json::Value::Null);
+        self
+    }
+
+    fn set_label(mut self, key: &str, value: serde_json::Value) -> Self {
+        let labels = self
+            .fields
+            .entry("labels")
+            .or_insert_with(|| serde_json::Value::Object(Default::default()));
+        if !labels.is_object() {
+            *labels = serde_json::Value::Object(Default::default());
+        }
+        if let Some(labels) = labels.as_object_mut() {
+            labels.insert(key.to_string(), value);
+        }
+        self
+    }
+
+    /// is_empty returns true if no field is set.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn with_acl(self, value: &[BucketAccessControl]) -> Self {
+        self.set("acl", &value)
+    }
+    pub fn with_default_object_acl(self, value: &[ObjectAccessControlCreationConfig]) -> Self {
+        self.set("defaultObjectAcl", &value)
+    }
+    pub fn with_lifecycle(self, value: &Lifecycle) -> Self {
+        self.set("lifecycle", value)
+    }
+    pub fn clear_lifecycle(self) -> Self {
+        self.clear("lifecycle")
+    }
+    pub fn with_cors(self, value: &[Cors]) -> Self {
+        self.set("cors", &value)
+    }
+    pub fn clear_cors(self) -> Self {
+        self.clear("cors")
+    }
+    pub fn with_storage_class(self, value: &str) -> Self {
+        self.set("storageClass", &value)
+    }
+    pub fn with_default_event_based_hold(self, value: bool) -> Self {
+        self.set("defaultEventBasedHold", &value)
+    }
+    /// with_label adds or updates the label. The other labels are not changed.
+    pub fn with_label(self, key: &str, value: &str) -> Self {
+        self.set_label(key, serde_json::Value::String(value.to_string()))
+    }
+    /// remove_label removes the label. The other labels are not changed.
+    pub fn remove_label(self, key: &str) -> Self {
+        self.set_label(key, serde_json::Value::Null)
+    }
+    /// clear_labels removes all the labels.
+    pub fn clear_labels(self) -> Self {
+        self.clear("labels")
+    }
+    pub fn with_website(self, value: &Website) -> Self {
+        self.set("website", value)
+    }
+    pub fn clear_website(self) -> Self {
+        self.clear("website")
+    }
+    pub fn with_versioning(self, value: Versioning) -> Self {
+        self.set("versioning", &value)
+    }
+    pub fn with_logging(self, value: &Logging) -> Self {
+        self.set("logging", value)
+    }
+    pub fn clear_logging(self) -> Self {
+        self.clear("logging")
+    }
+    pub fn with_encryption(self, value: &Encryption) -> Self {
+        self.set("encryption", value)
+    }
+    pub fn clear_encryption(self) -> Self {
+        self.clear("encryption")
+    }
+    pub fn with_billing(self, value: &Billing) -> Self {
+        self.set("billing", value)
+    }
+    pub fn with_retention_policy(self, value: &RetentionPolicyCreationConfig) -> Self {
+        self.set("retentionPolicy", value)
+    }
+    pub fn clear_retention_policy(self) -> Self {
+        self.clear("retentionPolicy")
+    }
+    pub fn with_soft_delete_policy(self, value: &SoftDeletePolicy) -> Self {
+        self.set("softDeletePolicy", value)
+    }
+    pub fn with_iam_configuration(self, value: &IamConfiguration) -> Self {
+        self.set("iamConfiguration", value)
+    }
+    pub fn with_rpo(self, value: &str) -> Self {
+        self.set("rpo", &value)
+    }
+}
+
+/// BucketConditions is the preconditions of the bucket metadata update.
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BucketConditions {
+    pub if_metageneration_match: Option<i64>,
+    pub if_metageneration_not_match: Option<i64>,
+}
+
+impl BucketConditions {
+    /// metageneration_match makes the operation succeed only if the metageneration of the bucket matches.
+    pub fn metageneration_match(metageneration: i64) -> Self {
+        Self {
+            if_metageneration_match: Some(metageneration),
+            ..Default::default()
+        }
+    }
+}
+
+pub(crate) fn build_fields(
+    base_url: &str,
+    client: &Client,
+    bucket: &str,
+    patch: &BucketPatch,
+    conditions: &BucketConditions,
+) -> RequestBuilder {
+    let url = format!("{}/b/{}", base_url, bucket.to_string().escape());
+    client.patch(url).query(conditions).json(patch)
+}
+
+/// Patches only the lifecycle of the bucket with the metageneration precondition.
+/// `BucketPatchConfig` can't be used because it sends the other fields as null.
+pub(crate) fn build_lifecycle(
+    base_url: &str,
+    client: &Client,
+    bucket: &str,
+    lifecycle: &Lifecycle,
+    if_metageneration_match: i64,
+) -> RequestBuilder {
+    let url = format!("{}/b/{}", base_url, bucket.to_string().escape());
+    client
+        .patch(url)
+        .query(&[("ifMetagenerationMatch", if_metageneration_match)])
+        .json(&serde_json::json!({ "lifecycle": lifecycle }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::http::buckets::patch::{build_fields, BucketConditions, BucketPatch};
+    use crate::http::buckets::{Cors, Versioning};
+
+    #[test]
+    fn test_bucket_patch() {
+        assert_eq!(serde_json::to_string(&BucketPatch::default()).unwrap(), "{}");
+
+        let patch = BucketPatch::default().with_versioning(Versioning { enabled: true });
+        assert_eq!(
+            serde_json::to_value(&patch).unwrap(),
+            serde_json::json!({"versioning": {"enabled": true}})
+        );
+
+        let patch = BucketPatch::default()
+            .with_label("env", "prod")
+            .remove_label("owner")
+            .clear_website()
+            .clear_cors();
+        assert_eq!(
+            serde_json::to_value(&patch).unwrap(),
+            serde_json::json!({"labels": {"env": "prod", "owner": null}, "website": null, "cors": null})
+        );
+
+        let patch = BucketPatch::default().clear_cors().with_cors(&[Cors {
+            origin: vec!["*".to_string()],
+            method: vec!["GET".to_string()],
+            response_header: vec![],
+            max_age_seconds: 3600,
+        }]);
+        let json = serde_json::to_value(&patch).unwrap();
+        assert_eq!(json.as_object().unwrap().len(), 1);
+        assert_eq!(json["cors"][0]["maxAgeSeconds"], 3600);
+    }
+
+    #[test]
+    fn test_build_fields() {
+        let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new()).build();
+        let patch = BucketPatch::default().with_label("env", "prod");
+        let request = build_fields(
+            "https://storage.googleapis.com/storage/v1",
+            &client,
+            "bucket",
+            &patch,
+            &BucketConditions::metageneration_match(3),
+        )
+        .build()
+        .unwrap();
+        assert_eq!(request.method(), reqwest::Method::PATCH);
+        assert_eq!(request.url().query(), Some("ifMetagenerationMatch=3"));
+        let body: HashMap<String, serde_json::Value> =
+            serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(body.len(), 1);
+        assert_eq!(body["labels"], serde_json::json!({"env": "prod"}));
+
+        let request = build_fields(
+            "https://storage.googleapis.com/storage/v1",
+            &client,
+            "bucket",
+            &BucketPatch::default(),
+            &BucketConditions::default(),
+        )
+        .build()
+        .unwrap();
+        assert_eq!(request.url().query(), None);
+        assert_eq!(request.body().unwrap().as_bytes().unwrap(), b"{}");
+    }
+}
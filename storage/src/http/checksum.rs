@@ -0,0 +1,338 @@
+use base64::prelude::*;
+use md5::{Digest, Md5};
+use reqwest::header::HeaderMap;
+
+use crate::http::objects::{download, Object};
+use crate::http::Error;
+
+/// The polynomial of CRC32C (Castagnoli) in the reversed representation.
+const CRC32C_POLYNOMIAL: u32 = 0x82F6_3B78;
+const CRC32C_TABLE: [u32; 256] = crc32c_table();
+
+/// ChecksumConfig selects the checksums computed on the uploads and verified on the downloads.
+/// The checksums detect the data corrupted between the client and the server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChecksumConfig {
+    /// CRC32C is available for all the objects including the composite objects.
+    pub crc32c: bool,
+    /// MD5 is not available for the composite objects, so it is verified only if the server returns it.
+    pub md5: bool,
+}
+
+impl Default for ChecksumConfig {
+    fn default() -> Self {
+        Self {
+            crc32c: true,
+            md5: false,
+        }
+    }
+}
+
+impl ChecksumConfig {
+    /// disabled returns the configuration skipping the checksums for the performance.
+    pub fn disabled() -> Self {
+        Self {
+            crc32c: false,
+            md5: false,
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.crc32c || self.md5
+    }
+}
+
+/// Hasher computes the checksums of the data incrementally.
+#[derive(Clone)]
+pub(crate) struct Hasher {
+    crc32c: Option<u32>,
+    md5: Option<Md5>,
+}
+
+impl Hasher {
+    pub(crate) fn new(config: &ChecksumConfig) -> Self {
+        Self {
+            crc32c: config.crc32c.then_some(0),
+            md5: config.md5.then(Md5::new),
+        }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        if let Some(crc32c) = self.crc32c.as_mut() {
+            *crc32c = crc32c_append(*crc32c, data);
+        }
+        if let Some(md5) = self.md5.as_mut() {
+            md5.update(data);
+        }
+    }
+
+    pub(crate) fn finish(self) -> Checksums {
+        Checksums {
+            crc32c: self.crc32c,
+            md5: self.md5.map(|v| v.finalize().into()),
+        }
+    }
+}
+
+/// Checksums is the checksums of the whole object.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Checksums {
+    pub crc32c: Option<u32>,
+    pub md5: Option<[u8; 16]>,
+}
+
+impl Checksums {
+    /// from_headers returns the checksums from the `x-goog-hash` headers like `crc32c=n03x6A==,md5=...`.
+    /// It is `None` if the object is served decompressed, because the checksums are the ones of the stored data.
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        if download::is_transcoded(headers) {
+            return None;
+        }
+        let mut checksums = Self::default();
+        let hashes = headers
+            .get_all("x-goog-hash")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','));
+        for hash in hashes {
+            match hash.trim().split_once('=') {
+                Some(("crc32c", v)) => checksums.crc32c = decode(v).map(u32::from_be_bytes),
+                Some(("md5", v)) => checksums.md5 = decode(v),
+                _ => {}
+            }
+        }
+        Some(checksums)
+    }
+
+    /// from_object returns the checksums in the metadata of the object.
+    pub(crate) fn from_object(object: &Object) -> Self {
+        Self {
+            crc32c: object.crc32c.as_deref().and_then(decode).map(u32::from_be_bytes),
+            md5: object.md5_hash.as_deref().and_then(decode),
+        }
+    }
+
+    pub(crate) fn crc32c_base64(&self) -> Option<String> {
+        self.crc32c.map(|v| BASE64_STANDARD.encode(v.to_be_bytes()))
+    }
+
+    pub(crate) fn md5_base64(&self) -> Option<String> {
+        self.md5.map(|v| BASE64_STANDARD.encode(v))
+    }
+
+    /// header_value returns the value of the `x-goog-hash` header validated by the server.
+    pub(crate) fn header_value(&self) -> Option<String> {
+        let hashes: Vec<String> = [
+            self.crc32c_base64().map(|v| format!("crc32c={v}")),
+            self.md5_base64().map(|v| format!("md5={v}")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        (!hashes.is_empty()).then(|| hashes.join(","))
+    }
+
+    /// verify returns the error if the checksums don't match the expected ones.
+    /// Only the checksums both of them have are compared.
+    pub(crate) fn verify(&self, expected: &Checksums) -> Result<(), Error> {
+        if let (Some(expected), Some(actual)) = (expected.crc32c, self.crc32c) {
+            if expected != actual {
+                return Err(Error::Crc32cMismatch { expected, actual });
+            }
+        }
+        if let (Some(expected_md5), Some(actual_md5)) = (expected.md5, self.md5) {
+            if expected_md5 != actual_md5 {
+                return Err(Error::Md5Mismatch {
+                    expected: expected.md5_base64().unwrap_or_default(),
+                    actual: self.md5_base64().unwrap_or_default(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn decode<const N: usize>(v: &str) -> Option<[u8; N]> {
+    BASE64_STANDARD.decode(v).ok().and_then(|v| v.try_into().ok())
+}
+
+const fn crc32c_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ CRC32C_POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// crc32c_append returns the CRC32C of the data appended to the data of crc.
+pub(crate) fn crc32c_append(crc: u32, data: &[u8]) -> u32 {
+    let mut crc = !crc;
+    for b in data {
+        crc = CRC32C_TABLE[((crc ^ *b as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// crc32c_combine returns the CRC32C of the concatenated data from the CRC32C of each data
+/// and the length of the second one, like the composite object of the server.
+pub(crate) fn crc32c_combine(crc1: u32, crc2: u32, len2: u64) -> u32 {
+    if len2 == 0 {
+        return crc1;
+    }
+    // The operator to append a zero bit.
+    let mut odd = [0u32; 32];
+    odd[0] = CRC32C_POLYNOMIAL;
+    for (i, v) in odd.iter_mut().enumerate().skip(1) {
+        *v = 1 << (i - 1);
+    }
+    // The operators to append two and four zero bits.
+    let mut even = gf2_matrix_square(&odd);
+    odd = gf2_matrix_square(&even);
+
+    // Append len2 zero bytes to crc1 by squaring the operator for each bit of len2.
+    let mut crc1 = crc1;
+    let mut len2 = len2;
+    loop {
+        even = gf2_matrix_square(&odd);
+        if len2 & 1 == 1 {
+            crc1 = gf2_matrix_times(&even, crc1);
+        }
+        len2 >>= 1;
+        if len2 == 0 {
+            break;
+        }
+        odd = gf2_matrix_square(&even);
+        if len2 & 1 == 1 {
+            crc1 = gf2_matrix_times(&odd, crc1);
+        }
+        len2 >>= 1;
+        if len2 == 0 {
+            break;
+        }
+    }
+    crc1 ^ crc2
+}
+
+fn gf2_matrix_times(matrix: &[u32; 32], mut vec: u32) -> u32 {
+    let mut sum = 0;
+    let mut i = 0;
+    while vec != 0 {
+        if vec & 1 == 1 {
+            sum ^= matrix[i];
+        }
+        vec >>= 1;
+        i += 1;
+    }
+    sum
+}
+
+fn gf2_matrix_square(matrix: &[u32; 32]) -> [u32; 32] {
+    matrix.map(|v| gf2_matrix_times(matrix, v))
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{HeaderMap, HeaderValue};
+
+    use crate::http::checksum::{crc32c_append, crc32c_combine, ChecksumConfig, Checksums, Hasher};
+    use crate::http::objects::Object;
+    use crate::http::Error;
+
+    #[test]
+    fn test_crc32c() {
+        assert_eq!(crc32c_append(0, b""), 0);
+        assert_eq!(crc32c_append(0, b"123456789"), 0xE306_9283);
+        assert_eq!(crc32c_append(crc32c_append(0, b"12345"), b"6789"), 0xE306_9283);
+    }
+
+    #[test]
+    fn test_crc32c_combine() {
+        let crc = |v: &[u8]| crc32c_append(0, v);
+        assert_eq!(crc32c_combine(crc(b"12345"), crc(b"6789"), 4), 0xE306_9283);
+        assert_eq!(crc32c_combine(crc(b"123456789"), crc(b""), 0), 0xE306_9283);
+        assert_eq!(crc32c_combine(crc(b""), crc(b"123456789"), 9), 0xE306_9283);
+        let data: Vec<u8> = (0..100_000).map(|i| (i % 251) as u8).collect();
+        let (a, b) = data.split_at(65_537);
+        assert_eq!(crc32c_combine(crc(a), crc(b), b.len() as u64), crc(&data));
+    }
+
+    #[test]
+    fn test_hasher() {
+        let mut hasher = Hasher::new(&ChecksumConfig {
+            crc32c: true,
+            md5: true,
+        });
+        hasher.update(b"hello ");
+        hasher.update(b"world");
+        let checksums = hasher.finish();
+        assert_eq!(checksums.crc32c_base64().unwrap(), "yZRlqg==");
+        assert_eq!(checksums.md5_base64().unwrap(), "XrY7u+Ae7tCTyyK7j1rNww==");
+        assert_eq!(
+            checksums.header_value().unwrap(),
+            "crc32c=yZRlqg==,md5=XrY7u+Ae7tCTyyK7j1rNww=="
+        );
+
+        let checksums = Hasher::new(&ChecksumConfig::default()).finish();
+        assert_eq!(checksums.header_value().unwrap(), "crc32c=AAAAAA==");
+        assert!(checksums.md5.is_none());
+        assert!(Hasher::new(&ChecksumConfig::disabled())
+            .finish()
+            .header_value()
+            .is_none());
+    }
+
+    #[test]
+    fn test_checksums_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append("x-goog-hash", HeaderValue::from_static("crc32c=yZRlqg=="));
+        headers.append("x-goog-hash", HeaderValue::from_static("md5=XrY7u+Ae7tCTyyK7j1rNww=="));
+        let expected = Checksums::from_headers(&headers).unwrap();
+        assert_eq!(expected.crc32c, Some(0xC994_65AA));
+        assert!(expected.md5.is_some());
+
+        let mut hasher = Hasher::new(&ChecksumConfig {
+            crc32c: true,
+            md5: true,
+        });
+        hasher.update(b"hello world");
+        assert!(hasher.finish().verify(&expected).is_ok());
+
+        let mut hasher = Hasher::new(&ChecksumConfig {
+            crc32c: false,
+            md5: true,
+        });
+        hasher.update(b"hello world!");
+        assert!(matches!(hasher.finish().verify(&expected), Err(Error::Md5Mismatch { .. })));
+
+        let mut hasher = Hasher::new(&ChecksumConfig::default());
+        hasher.update(b"hello world!");
+        assert!(matches!(hasher.finish().verify(&expected), Err(Error::Crc32cMismatch { .. })));
+
+        // the transcoded object can't be verified
+        headers.insert("x-goog-stored-content-encoding", HeaderValue::from_static("gzip"));
+        assert!(Checksums::from_headers(&headers).is_none());
+    }
+
+    #[test]
+    fn test_checksums_from_object() {
+        let object = Object {
+            crc32c: Some("yZRlqg==".to_string()),
+            ..Default::default()
+        };
+        let checksums = Checksums::from_object(&object);
+        assert_eq!(checksums.crc32c, Some(0xC994_65AA));
+        assert!(checksums.md5.is_none());
+    }
+}
@@ -28,6 +28,13 @@ impl ErrorResponse {
     pub fn is_retriable(&self) -> bool {
         matches!(self.code, 408 | 429 | 500..=599)
     }
+
+    /// Returns `true` if the bucket is a requester pays bucket and the request has no billing project.
+    pub fn is_user_project_missing(&self) -> bool {
+        self.code == 400
+            && (self.errors.iter().any(|v| v.reason == "userProjectMissing")
+                || self.message.to_ascii_lowercase().contains("no user project provided"))
+    }
 }
 
 impl fmt::Display for ErrorResponse {
@@ -79,3 +86,23 @@ impl fmt::Display for ErrorResponseItem {
 pub(crate) struct ErrorWrapper {
     pub(crate) error: ErrorResponse,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::http::error::ErrorWrapper;
+
+    #[test]
+    fn test_is_user_project_missing() {
+        let body = r#"{"error":{"code":400,"message":"Bucket is a requester pays bucket but no user project provided.",
+            "errors":[{"message":"Bucket is a requester pays bucket but no user project provided.",
+            "domain":"global","reason":"required"}]}}"#;
+        let wrapper: ErrorWrapper = serde_json::from_str(body).unwrap();
+        assert!(wrapper.error.is_user_project_missing());
+        assert!(!wrapper.error.is_retriable());
+
+        let body = r#"{"error":{"code":400,"message":"Invalid argument.",
+            "errors":[{"message":"Invalid argument.","domain":"global","reason":"invalid"}]}}"#;
+        let wrapper: ErrorWrapper = serde_json::from_str(body).unwrap();
+        assert!(!wrapper.error.is_user_project_missing());
+    }
+}
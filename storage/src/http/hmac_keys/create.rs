@@ -1,7 +1,7 @@
 use reqwest::header::CONTENT_LENGTH;
 use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
 
-use crate::http::hmac_keys::HmacKeyMetadata;
+use crate::http::hmac_keys::{HmacKeyMetadata, HmacKeySecret};
 use crate::http::Escape;
 
 #[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
@@ -19,8 +19,8 @@ pub struct CreateHmacKeyRequest {
 pub struct CreateHmacKeyResponse {
     /// Key metadata.
     pub metadata: HmacKeyMetadata,
-    /// HMAC key secret material.
-    pub secret: String,
+    /// HMAC key secret material. It is not returned again, so it must be stored by the caller.
+    pub secret: HmacKeySecret,
 }
 
 pub(crate) fn build(base_url: &str, client: &Client, req: &CreateHmacKeyRequest) -> RequestBuilder {
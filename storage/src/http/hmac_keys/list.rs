@@ -7,6 +7,7 @@ use crate::http::Escape;
 #[serde(rename_all = "camelCase")]
 pub struct ListHmacKeysRequest {
     /// Required. The project id to list HMAC keys for.
+    #[serde(skip_serializing)]
     pub project_id: String,
     /// An optional filter to only return HMAC keys for one service account.
     pub service_account_email: Option<String>,
@@ -31,5 +32,5 @@ pub struct ListHmacKeysResponse {
 
 pub(crate) fn build(base_url: &str, client: &Client, req: &ListHmacKeysRequest) -> RequestBuilder {
     let url = format!("{}/projects/{}/hmacKeys", base_url, req.project_id.escape());
-    client.get(url)
+    client.get(url).query(&req)
 }
@@ -1,3 +1,5 @@
+use std::fmt::{Debug, Formatter};
+
 use time::OffsetDateTime;
 
 pub mod create;
@@ -18,8 +20,8 @@ pub struct HmacKeyMetadata {
     pub project_id: String,
     /// Email of the service account the key authenticates as.
     pub service_account_email: String,
-    /// State of the key. One of ACTIVE, INACTIVE, or DELETED.
-    pub state: String,
+    /// State of the key. Only the inactive key can be deleted.
+    pub state: HmacKeyState,
     /// The creation time of the HMAC key in RFC 3339 format.
     #[serde(default, with = "time::serde::rfc3339::option")]
     pub time_created: Option<OffsetDateTime>,
@@ -29,3 +31,57 @@ pub struct HmacKeyMetadata {
     /// Tag updated with each key update.
     pub etag: String,
 }
+
+/// The state of the HMAC key.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HmacKeyState {
+    /// The key can be used to authenticate the requests.
+    #[default]
+    Active,
+    /// The key can't be used. It must be inactive to be deleted.
+    Inactive,
+    /// The key is deleted and will be wiped out.
+    Deleted,
+}
+
+/// HmacKeySecret is the secret of the HMAC key. It is returned only by `create_hmac_key`,
+/// so it must be stored by the caller. It is never shown in `Debug`.
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Default)]
+#[serde(transparent)]
+pub struct HmacKeySecret(String);
+
+impl HmacKeySecret {
+    /// expose returns the base64 encoded secret.
+    pub fn expose(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl Debug for HmacKeySecret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("HmacKeySecret(REDACTED)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::hmac_keys::create::CreateHmacKeyResponse;
+    use crate::http::hmac_keys::HmacKeyState;
+
+    #[test]
+    fn test_create_hmac_key_response() {
+        let body = r#"{"kind":"storage#hmacKey","secret":"c2VjcmV0",
+            "metadata":{"id":"p/GOOG1","accessId":"GOOG1","projectId":"p",
+            "serviceAccountEmail":"sa@p.iam.gserviceaccount.com","state":"ACTIVE","etag":"e"}}"#;
+        let response: CreateHmacKeyResponse = serde_json::from_str(body).unwrap();
+        assert_eq!(response.secret.expose(), "c2VjcmV0");
+        assert_eq!(response.metadata.state, HmacKeyState::Active);
+        let debug = format!("{response:?}");
+        assert!(!debug.contains("c2VjcmV0"), "{debug}");
+        assert!(debug.contains("REDACTED"), "{debug}");
+
+        let state = serde_json::to_string(&HmacKeyState::Inactive).unwrap();
+        assert_eq!(state, r#""INACTIVE""#);
+    }
+}
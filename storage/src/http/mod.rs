@@ -1,5 +1,6 @@
 use std::fmt::Display;
 use std::str::FromStr;
+use std::time::Duration;
 
 use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use reqwest::Response;
@@ -10,6 +11,7 @@ use serde_json::Value;
 pub mod bucket_access_controls;
 pub mod buckets;
 pub mod channels;
+pub mod checksum;
 pub mod default_object_access_controls;
 pub mod error;
 pub mod hmac_keys;
@@ -17,7 +19,8 @@ pub mod notifications;
 pub mod object_access_controls;
 pub mod objects;
 pub mod resumable_upload_client;
-pub mod service_account_client;
+pub mod retry;
+pub mod service_account;
 pub mod storage_client;
 
 #[derive(thiserror::Error, Debug)]
@@ -26,6 +29,21 @@ pub enum Error {
     #[error(transparent)]
     Response(#[from] error::ErrorResponse),
 
+    /// The bucket is a requester pays bucket and the request has no billing project.
+    #[error("{0}: set the billing project with `ClientConfig::user_project` or `StorageClient::with_user_project`")]
+    UserProjectMissing(error::ErrorResponse),
+
+    /// The HMAC key can't be deleted because it is not inactive.
+    #[error("hmac key {access_id} must be INACTIVE to be deleted: state={state:?}")]
+    HmacKeyNotInactive {
+        access_id: String,
+        state: hmac_keys::HmacKeyState,
+    },
+
+    /// The inserted notification is not listed yet after waiting for it.
+    #[error("notification {id} of the bucket {bucket} is not listed")]
+    NotificationNotListed { bucket: String, id: String },
+
     /// An error from the underlying HTTP client.
     #[error(transparent)]
     HttpClient(#[from] reqwest::Error),
@@ -37,6 +55,30 @@ pub enum Error {
     /// An error from a token source.
     #[error("token source failed: {0}")]
     TokenSource(Box<dyn std::error::Error + Send + Sync>),
+
+    /// An error from the chunked upload of the resumable upload.
+    #[error(transparent)]
+    Chunk(#[from] resumable_upload_client::ChunkError),
+
+    /// The lifecycle rule is invalid.
+    #[error(transparent)]
+    LifecycleRule(#[from] buckets::lifecycle::LifecycleRuleError),
+
+    /// The checksum of the downloaded data doesn't match the one of the object.
+    #[error("crc32c mismatch: expected={expected:08x} actual={actual:08x}")]
+    Crc32cMismatch { expected: u32, actual: u32 },
+
+    /// The range can't be downloaded because the gzip encoded object is served decompressed.
+    #[error("range of the decompressed gzip object can't be downloaded, disable decompressive transcoding")]
+    TranscodedRange,
+
+    /// The MD5 of the data doesn't match the one of the object. The values are base64 encoded.
+    #[error("md5 mismatch: expected={expected} actual={actual}")]
+    Md5Mismatch { expected: String, actual: String },
+
+    /// The composition has more source objects than `MAX_COMPOSE_SOURCES`.
+    #[error("too many source objects to compose: {0} > {max}", max = objects::compose::MAX_COMPOSE_SOURCES)]
+    TooManyComposeSources(usize),
 }
 
 impl From<reqwest_middleware::Error> for Error {
@@ -60,10 +102,35 @@ pub(crate) async fn check_response_status(response: Response) -> Result<Response
     Err(response
         .json::<error::ErrorWrapper>()
         .await
-        .map(|wrapper| Error::Response(wrapper.error))
+        .map(|wrapper| {
+            if wrapper.error.is_user_project_missing() {
+                Error::UserProjectMissing(wrapper.error)
+            } else {
+                Error::Response(wrapper.error)
+            }
+        })
         .unwrap_or(Error::HttpClient(error)))
 }
 
+/// The max delay between the retries of the transient failures.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(32);
+
+/// is_transient returns true if the request may succeed by retrying it.
+pub(crate) fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::Response(e) => e.is_retriable(),
+        Error::HttpClient(e) => {
+            e.is_timeout() || e.is_connect() || e.status().is_some_and(|v| matches!(v.as_u16(), 408 | 429 | 500..=599))
+        }
+        _ => false,
+    }
+}
+
+/// retry_delay returns the exponential backoff delay before the retry.
+pub(crate) fn retry_delay(retries: usize) -> Duration {
+    Duration::from_secs(1 << retries.min(5)).min(MAX_RETRY_DELAY)
+}
+
 pub(crate) trait Escape {
     fn escape(&self) -> String;
 }
@@ -104,26 +171,3 @@ where
 pub fn is_i64_zero(num: &i64) -> bool {
     *num == 0
 }
-
-/// Provides serialization and deserialization for base64 encoded fields.
-mod base64 {
-    use base64::prelude::*;
-    use serde::{Deserialize, Deserializer, Serialize, Serializer};
-
-    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        T: AsRef<[u8]>,
-        S: Serializer,
-    {
-        BASE64_STANDARD.encode(value.as_ref()).serialize(serializer)
-    }
-
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        BASE64_STANDARD
-            .decode(String::deserialize(deserializer)?)
-            .map_err(serde::de::Error::custom)
-    }
-}
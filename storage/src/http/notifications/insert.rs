@@ -9,15 +9,20 @@ use crate::http::Escape;
 pub struct NotificationCreationConfig {
     /// The Cloud PubSub topic to which this subscription publishes. Formatted as:
     /// '//pubsub.googleapis.com/projects/{project-identifier}/topics/{my-topic}'
+    /// The GCS service agent returned by `StorageClient::get_service_account` must have
+    /// the `roles/pubsub.publisher` role on the topic.
     pub topic: String,
     /// If present, only send notifications about listed event types. If empty,
     /// sent notifications for all event types.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub event_types: Option<Vec<EventType>>,
     /// An optional list of additional attributes to attach to each Cloud PubSub
     /// message published for this notification subscription.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub custom_attributes: HashMap<String, String>,
     /// If present, only apply this notification configuration to object names that
     /// begin with this prefix.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub object_name_prefix: Option<String>,
     /// The desired content of the Payload.
     pub payload_format: PayloadFormat,
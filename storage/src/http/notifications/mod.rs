@@ -29,7 +29,7 @@ pub struct Notification {
     pub id: String,
 }
 
-#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize, Debug)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum EventType {
     /// Sent when a new object (or a new generation of an existing object) is successfully created in the bucket. This includes copying or rewriting an existing object. A failed upload does not trigger this event.
@@ -42,7 +42,7 @@ pub enum EventType {
     ObjectArchive,
 }
 
-#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum PayloadFormat {
     /// The payload will be a UTF-8 string containing the resource representation of the object’s metadata.
@@ -56,3 +56,44 @@ impl Default for PayloadFormat {
         Self::JsonApiV1
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::http::notifications::insert::NotificationCreationConfig;
+    use crate::http::notifications::{EventType, Notification, PayloadFormat};
+
+    #[test]
+    fn test_notification() {
+        let json = r#"{
+            "kind": "storage#notification",
+            "selfLink": "https://www.googleapis.com/storage/v1/b/bucket/notificationConfigs/3",
+            "id": "3",
+            "topic": "//pubsub.googleapis.com/projects/project/topics/topic",
+            "event_types": ["OBJECT_FINALIZE", "OBJECT_ARCHIVE"],
+            "object_name_prefix": "logs/",
+            "payload_format": "NONE",
+            "etag": "3"
+        }"#;
+        let notification: Notification = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            notification.event_types,
+            Some(vec![EventType::ObjectFinalize, EventType::ObjectArchive])
+        );
+        assert_eq!(notification.object_name_prefix.as_deref(), Some("logs/"));
+        assert_eq!(notification.payload_format, PayloadFormat::None);
+
+        let config = NotificationCreationConfig {
+            topic: "projects/project/topics/topic".to_string(),
+            event_types: Some(vec![EventType::ObjectMetadataUpdate, EventType::ObjectDelete]),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(&config).unwrap(),
+            serde_json::json!({
+                "topic": "projects/project/topics/topic",
+                "event_types": ["OBJECT_METADATA_UPDATE", "OBJECT_DELETE"],
+                "payload_format": "JSON_API_V1"
+            })
+        );
+    }
+}
@@ -4,6 +4,9 @@ use crate::http::object_access_controls::PredefinedObjectAcl;
 use crate::http::objects::{Encryption, Object, SourceObjects};
 use crate::http::Escape;
 
+/// The max number of the source objects of a composition request.
+pub const MAX_COMPOSE_SOURCES: usize = 32;
+
 /// Request message for ComposeObject.
 #[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
@@ -42,6 +45,7 @@ pub struct ComposingTargets {
     /// Properties of the resulting object.
     pub destination: Option<Object>,
     /// The list of source objects that will be concatenated into a single object.
+    /// There can be up to `MAX_COMPOSE_SOURCES` source objects.
     pub source_objects: Vec<SourceObjects>,
 }
 
@@ -44,8 +44,12 @@ pub struct CopyObjectRequest {
     #[serde(skip_serializing)]
     pub metadata: Option<Object>,
 
+    /// Destination encryption setting
     #[serde(skip_serializing)]
     pub encryption: Option<Encryption>,
+    /// Source encryption setting. It is required if the source object is encrypted with a customer-supplied key.
+    #[serde(skip_serializing)]
+    pub source_encryption: Option<Encryption>,
 }
 
 pub(crate) fn build(base_url: &str, client: &Client, req: &CopyObjectRequest) -> RequestBuilder {
@@ -57,9 +61,12 @@ pub(crate) fn build(base_url: &str, client: &Client, req: &CopyObjectRequest) ->
         req.destination_bucket.escape(),
         req.destination_object.escape()
     );
-    let builder = client.post(url).query(&req).json(&req.metadata);
+    let mut builder = client.post(url).query(&req).json(&req.metadata);
     if let Some(e) = &req.encryption {
-        e.with_headers(builder)
+        builder = e.with_headers(builder)
+    }
+    if let Some(e) = &req.source_encryption {
+        e.with_copy_source_headers(builder)
     } else {
         builder
     }
@@ -1,5 +1,6 @@
 use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
 
+use crate::http::objects::Conditions;
 use crate::http::Escape;
 
 /// Request message for GetObject.
@@ -32,6 +33,19 @@ pub struct DeleteObjectRequest {
     pub if_metageneration_not_match: Option<i64>,
 }
 
+impl DeleteObjectRequest {
+    /// with_conditions replaces the preconditions of the request.
+    pub fn with_conditions(self, conditions: Conditions) -> Self {
+        Self {
+            if_generation_match: conditions.if_generation_match,
+            if_generation_not_match: conditions.if_generation_not_match,
+            if_metageneration_match: conditions.if_metageneration_match,
+            if_metageneration_not_match: conditions.if_metageneration_not_match,
+            ..self
+        }
+    }
+}
+
 pub(crate) fn build(base_url: &str, client: &Client, req: &DeleteObjectRequest) -> RequestBuilder {
     let url = format!("{}/b/{}/o/{}", base_url, req.bucket.escape(), req.object.escape());
     client.delete(url).query(&req)
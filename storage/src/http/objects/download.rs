@@ -1,11 +1,68 @@
+use reqwest::header::{HeaderMap, ACCEPT_ENCODING, CONTENT_ENCODING};
+use reqwest::Response;
 use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
 
+use crate::http::checksum::ChecksumConfig;
 use crate::http::objects::get::GetObjectRequest;
 use crate::http::Escape;
 
 #[derive(Default)]
 pub struct Range(pub Option<u64>, pub Option<u64>);
 
+/// DownloadConfig is the configuration of `StorageClient::download_object_stream`.
+#[derive(Clone, Debug)]
+pub struct DownloadConfig {
+    /// The position of the first byte to download.
+    pub offset: u64,
+    /// The number of the bytes to download. All the bytes after the offset are downloaded if it is `None`.
+    pub length: Option<u64>,
+    /// The max number of the consecutive retries on the transient failures.
+    pub max_retries: usize,
+    /// The checksums verified when the whole object is downloaded.
+    pub checksum: ChecksumConfig,
+    /// Whether the server decompresses the object stored with `Content-Encoding: gzip`.
+    /// If it is false, `Accept-Encoding: gzip` is sent and the exact stored bytes are downloaded and verified.
+    /// If it is true, the decompressed bytes are downloaded, but they can't be verified by the checksums of the
+    /// stored data. The server ignores the `Range` header of the decompressed download, so `offset` and `length`
+    /// are rejected with `Error::TranscodedRange` and the interrupted download can't be resumed.
+    /// The objects without the gzip encoding are not affected.
+    /// https://cloud.google.com/storage/docs/transcoding
+    pub decompressive_transcoding: bool,
+}
+
+impl Default for DownloadConfig {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            length: None,
+            max_retries: 5,
+            checksum: ChecksumConfig::default(),
+            decompressive_transcoding: true,
+        }
+    }
+}
+
+impl DownloadConfig {
+    /// range returns the range from the position to the end of the download.
+    pub(crate) fn range(&self, position: u64) -> Range {
+        match self.length {
+            Some(length) => Range(Some(position), Some(self.offset + length - 1)),
+            None if position == 0 => Range::default(),
+            None => Range(Some(position), None),
+        }
+    }
+
+    /// is_partial returns true if only the part of the object is downloaded.
+    pub(crate) fn is_partial(&self) -> bool {
+        self.offset > 0 || self.length.is_some()
+    }
+
+    /// verifies_checksum returns true if the whole object is downloaded and its checksums are verified.
+    pub(crate) fn verifies_checksum(&self) -> bool {
+        self.offset == 0 && self.length.is_none() && self.checksum.is_enabled()
+    }
+}
+
 impl Range {
     /// Range: bytes=0-1999 (first 2000 bytes)
     /// Range: bytes=-2000 (last 2000 bytes)
@@ -25,12 +82,90 @@ impl Range {
     }
 }
 
-pub(crate) fn build(base_url: &str, client: &Client, req: &GetObjectRequest, range: &Range) -> RequestBuilder {
+pub(crate) fn build(
+    base_url: &str,
+    client: &Client,
+    req: &GetObjectRequest,
+    range: &Range,
+    decompressive_transcoding: bool,
+) -> RequestBuilder {
     let url = format!("{}/b/{}/o/{}?alt=media", base_url, req.bucket.escape(), req.object.escape());
     let builder = range.with_header(client.get(url).query(&req));
+    let builder = if decompressive_transcoding {
+        builder
+    } else {
+        builder.header(ACCEPT_ENCODING, "gzip")
+    };
     if let Some(e) = &req.encryption {
         e.with_headers(builder)
     } else {
         builder
     }
 }
+
+/// generation returns the generation of the downloaded object.
+pub(crate) fn generation(response: &Response) -> Option<i64> {
+    response
+        .headers()
+        .get("x-goog-generation")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+}
+
+/// is_transcoded returns true if the gzip encoded object is served decompressed.
+pub(crate) fn is_transcoded(headers: &HeaderMap) -> bool {
+    let is_gzip = |name: &str| headers.get(name).is_some_and(|v| v.as_bytes() == b"gzip");
+    is_gzip("x-goog-stored-content-encoding") && !is_gzip(CONTENT_ENCODING.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reqwest::header::{HeaderMap, HeaderValue, CONTENT_ENCODING};
+
+    use crate::http::checksum::ChecksumConfig;
+    use crate::http::objects::download::{is_transcoded, DownloadConfig, Range};
+    use crate::http::retry_delay;
+
+    #[test]
+    fn test_download_config_range() {
+        let to_tuple = |v: Range| (v.0, v.1);
+        let config = DownloadConfig::default();
+        assert!(config.verifies_checksum());
+        assert_eq!(to_tuple(config.range(0)), (None, None));
+        assert_eq!(to_tuple(config.range(100)), (Some(100), None));
+
+        let config = DownloadConfig {
+            offset: 10,
+            length: Some(20),
+            ..Default::default()
+        };
+        assert!(!config.verifies_checksum());
+        assert_eq!(to_tuple(config.range(10)), (Some(10), Some(29)));
+        assert_eq!(to_tuple(config.range(25)), (Some(25), Some(29)));
+
+        let config = DownloadConfig {
+            checksum: ChecksumConfig::disabled(),
+            ..Default::default()
+        };
+        assert!(!config.verifies_checksum());
+    }
+
+    #[test]
+    fn test_is_transcoded() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_transcoded(&headers));
+        headers.insert("x-goog-stored-content-encoding", HeaderValue::from_static("gzip"));
+        assert!(is_transcoded(&headers));
+        headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+        assert!(!is_transcoded(&headers));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(0), Duration::from_secs(1));
+        assert_eq!(retry_delay(3), Duration::from_secs(8));
+        assert_eq!(retry_delay(100), Duration::from_secs(32));
+    }
+}
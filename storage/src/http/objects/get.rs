@@ -34,6 +34,8 @@ pub struct GetObjectRequest {
     pub if_metageneration_not_match: Option<i64>,
     /// Set of properties to return. Defaults to `NO_ACL`.
     pub projection: Option<Projection>,
+    /// If true, gets the soft-deleted object. `generation` is required.
+    pub soft_deleted: Option<bool>,
     /// A set of parameters common to Storage API requests concerning an object.
     #[serde(skip_serializing)]
     pub encryption: Option<Encryption>,
@@ -53,6 +53,28 @@ pub struct ListObjectsRequest {
     /// increasing generation number. The default value for versions is false.
     /// For more information, see Object Versioning.
     pub versions: Option<bool>,
+    /// If true, only the soft-deleted objects are listed with their generations and `soft_delete_time`.
+    /// It can't be used with `versions`.
+    pub soft_deleted: Option<bool>,
+    /// Filter results to objects and prefixes that match this glob pattern like `**/*.txt`.
+    /// See https://cloud.google.com/storage/docs/json_api/v1/objects/list#list-objects-and-prefixes-using-glob.
+    pub match_glob: Option<String>,
+    /// Selector specifying which fields to include in a partial response like `items(name,size),prefixes`.
+    /// `nextPageToken` must be included to page through the results. `list_objects_stream` adds it if missing.
+    pub fields: Option<String>,
+}
+
+impl ListObjectsRequest {
+    /// fields_with_page_token returns the fields including `nextPageToken`, which is required for paging.
+    pub(crate) fn fields_with_page_token(&self) -> Option<String> {
+        self.fields.as_ref().map(|fields| {
+            if fields.split(',').any(|v| v.trim() == "nextPageToken") {
+                fields.to_string()
+            } else {
+                format!("{fields},nextPageToken")
+            }
+        })
+    }
 }
 
 /// The result of a call to Objects.ListObjects
@@ -69,7 +91,42 @@ pub struct ListObjectsResponse {
     pub next_page_token: Option<String>,
 }
 
+/// ListObjectsItem is an entry listed by `StorageClient::list_objects_stream`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ListObjectsItem {
+    /// The object matching the request.
+    Object(Box<Object>),
+    /// The prefix truncated after the delimiter like `dir/`, which is the "folder" containing the objects.
+    /// It is listed only when the delimiter is set.
+    Prefix(String),
+}
+
 pub(crate) fn build(base_url: &str, client: &Client, req: &ListObjectsRequest) -> RequestBuilder {
     let url = format!("{}/b/{}/o", base_url, req.bucket.escape());
     client.get(url).query(&req)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::http::objects::list::ListObjectsRequest;
+
+    #[test]
+    fn test_fields_with_page_token() {
+        let fields = |fields: Option<&str>| {
+            ListObjectsRequest {
+                fields: fields.map(|v| v.to_string()),
+                ..Default::default()
+            }
+            .fields_with_page_token()
+        };
+        assert_eq!(fields(None), None);
+        assert_eq!(
+            fields(Some("items(name,size),prefixes")).unwrap(),
+            "items(name,size),prefixes,nextPageToken"
+        );
+        assert_eq!(
+            fields(Some("nextPageToken, items(name)")).unwrap(),
+            "nextPageToken, items(name)"
+        );
+    }
+}
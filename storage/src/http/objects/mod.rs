@@ -1,6 +1,9 @@
 use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
 
+use base64::prelude::*;
 use reqwest_middleware::RequestBuilder;
+use sha2::{Digest, Sha256};
 
 use time::OffsetDateTime;
 
@@ -12,14 +15,17 @@ pub mod delete;
 pub mod download;
 pub mod get;
 pub mod list;
+pub mod parallel_upload;
 pub mod patch;
+pub mod restore;
 pub mod rewrite;
 pub mod upload;
 pub mod watch_all;
 
 /// An object.
+/// The fields not included in the partial response of the `fields` selector are the default values.
 #[derive(Clone, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize, Debug)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct Object {
     /// The link to this object.
     #[serde(skip_serializing_if = "String::is_empty")]
@@ -77,6 +83,9 @@ pub struct Object {
     #[serde(skip_serializing_if = "crate::http::is_i64_zero")]
     #[serde(deserialize_with = "crate::http::from_str")]
     pub size: i64,
+    /// The number of the source objects of the composite object. It is `None` if the object is not composed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub component_count: Option<i32>,
     /// The creation time of the object.
     /// Attempting to set or update this field will result in a
     /// \[FieldViolation][google.rpc.BadRequest.FieldViolation\].
@@ -146,6 +155,14 @@ pub struct Object {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default, with = "time::serde::rfc3339::option")]
     pub retention_expiration_time: Option<OffsetDateTime>,
+    /// The time at which the object became soft-deleted. It is set only for the soft-deleted object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub soft_delete_time: Option<OffsetDateTime>,
+    /// The time at which the soft-deleted object will be permanently deleted and can no longer be restored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub hard_delete_time: Option<OffsetDateTime>,
     /// User-provided metadata, in key/value pairs.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<HashMap<String, String>>,
@@ -242,8 +259,66 @@ pub struct ObjectPreconditions {
     pub if_generation_match: Option<i64>,
 }
 
+/// Conditions are the preconditions on the generation and the metageneration of the object.
+/// The mutation with `if_generation_match` or `if_metageneration_match` is applied at most once,
+/// so it is retried by `RetryPolicy::IfIdempotent`.
+///
+/// ```
+/// use google_cloud_storage::http::objects::Conditions;
+/// use google_cloud_storage::http::objects::delete::DeleteObjectRequest;
+///
+/// let req = DeleteObjectRequest {
+///     bucket: "bucket".to_string(),
+///     object: "object".to_string(),
+///     ..Default::default()
+/// }
+/// .with_conditions(Conditions::generation_match(1));
+/// assert_eq!(req.if_generation_match, Some(1));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Conditions {
+    pub if_generation_match: Option<i64>,
+    pub if_generation_not_match: Option<i64>,
+    pub if_metageneration_match: Option<i64>,
+    pub if_metageneration_not_match: Option<i64>,
+}
+
+impl Conditions {
+    /// does_not_exist makes the operation succeed only if there are no live versions of the object.
+    pub fn does_not_exist() -> Self {
+        Self::generation_match(0)
+    }
+
+    /// generation_match makes the operation succeed only if the generation of the live object matches.
+    pub fn generation_match(generation: i64) -> Self {
+        Self {
+            if_generation_match: Some(generation),
+            ..Default::default()
+        }
+    }
+
+    /// metageneration_match makes the operation succeed only if the metageneration of the live object matches.
+    pub fn metageneration_match(metageneration: i64) -> Self {
+        Self {
+            if_metageneration_match: Some(metageneration),
+            ..Default::default()
+        }
+    }
+
+    /// unchanged makes the operation succeed only if the object is not changed since it was read.
+    pub fn unchanged(object: &Object) -> Self {
+        Self {
+            if_generation_match: Some(object.generation),
+            if_metageneration_match: Some(object.metageneration),
+            ..Default::default()
+        }
+    }
+}
+
 /// Parameters that can be passed to any object request.
-#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+/// The encryption key is a customer-supplied AES-256 key. It is never shown in `Debug`.
+/// See https://cloud.google.com/storage/docs/encryption/customer-supplied-keys.
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Encryption {
     /// Encryption algorithm used with Customer-Supplied Encryption Keys feature.
@@ -256,10 +331,90 @@ pub struct Encryption {
 }
 
 impl Encryption {
+    /// new returns the encryption setting of the AES-256 key with its base64 encoded SHA256 digest.
+    ///
+    /// ```
+    /// use google_cloud_storage::http::objects::Encryption;
+    ///
+    /// let encryption = Encryption::new(b"01234567890123456789012345678901");
+    /// assert_eq!(encryption.encryption_algorithm, "AES256");
+    /// ```
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            encryption_algorithm: "AES256".to_string(),
+            encryption_key: BASE64_STANDARD.encode(key),
+            encryption_key_sha256: BASE64_STANDARD.encode(Sha256::digest(key)),
+        }
+    }
+
     pub(crate) fn with_headers(&self, builder: RequestBuilder) -> RequestBuilder {
         builder
             .header("X-Goog-Encryption-Algorithm", &self.encryption_algorithm)
             .header("X-Goog-Encryption-Key", &self.encryption_key)
             .header("X-Goog-Encryption-Key-Sha256", &self.encryption_key_sha256)
     }
+
+    /// with_copy_source_headers sets the key of the source object of the copy and the rewrite.
+    pub(crate) fn with_copy_source_headers(&self, builder: RequestBuilder) -> RequestBuilder {
+        builder
+            .header("X-Goog-Copy-Source-Encryption-Algorithm", &self.encryption_algorithm)
+            .header("X-Goog-Copy-Source-Encryption-Key", &self.encryption_key)
+            .header("X-Goog-Copy-Source-Encryption-Key-Sha256", &self.encryption_key_sha256)
+    }
+}
+
+impl Debug for Encryption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Encryption")
+            .field("encryption_algorithm", &self.encryption_algorithm)
+            .field("encryption_key", &"<redacted>")
+            .field("encryption_key_sha256", &self.encryption_key_sha256)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::objects::{Conditions, Encryption, Object};
+
+    #[test]
+    fn test_conditions() {
+        assert_eq!(Conditions::does_not_exist().if_generation_match, Some(0));
+        assert_eq!(Conditions::metageneration_match(2).if_metageneration_match, Some(2));
+        let object = Object {
+            generation: 10,
+            metageneration: 3,
+            ..Default::default()
+        };
+        let conditions = Conditions::unchanged(&object);
+        assert_eq!(conditions.if_generation_match, Some(10));
+        assert_eq!(conditions.if_metageneration_match, Some(3));
+        assert_eq!(conditions.if_generation_not_match, None);
+    }
+
+    #[test]
+    fn test_partial_object() {
+        let object: Object = serde_json::from_str(r#"{"name":"dir/a.txt","size":"3"}"#).unwrap();
+        assert_eq!(object.name, "dir/a.txt");
+        assert_eq!(object.size, 3);
+        assert_eq!(object.generation, 0);
+        assert!(object.bucket.is_empty());
+    }
+
+    #[test]
+    fn test_encryption() {
+        let encryption = Encryption::new(b"01234567890123456789012345678901");
+        assert_eq!(encryption.encryption_algorithm, "AES256");
+        assert_eq!(encryption.encryption_key, "MDEyMzQ1Njc4OTAxMjM0NTY3ODkwMTIzNDU2Nzg5MDE=");
+        assert_eq!(encryption.encryption_key_sha256, "hhAJ7E1Zn6sfQKvHbm+JiAz/WDPHnFSMmfkEXxkc2Qs=");
+
+        let encryption = Encryption::new(&[0; 32]);
+        assert_eq!(encryption.encryption_key, "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=");
+        assert_eq!(encryption.encryption_key_sha256, "Zmh6rfhivXdsj8GLjp+OIAiXFIVu4jOzkCpZHQ1fKSU=");
+
+        // the key is never logged
+        let debug = format!("{encryption:?}");
+        assert!(!debug.contains(&encryption.encryption_key), "{debug}");
+        assert!(debug.contains(&encryption.encryption_key_sha256), "{debug}");
+    }
 }
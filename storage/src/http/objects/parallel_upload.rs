@@ -0,0 +1,116 @@
+use std::ops::Range;
+
+use time::OffsetDateTime;
+
+/// ParallelUploadConfig is the configuration of `StorageClient::upload_parallel`.
+/// The data is split into the parts uploaded concurrently as the temporary objects,
+/// and the parts are composed into the destination object.
+/// More than `MAX_COMPOSE_SOURCES` parts are composed in cascade through the intermediate temporary objects.
+/// https://cloud.google.com/storage/docs/parallel-composite-uploads
+#[derive(Clone, Debug)]
+pub struct ParallelUploadConfig {
+    /// The number of the parts. It is reduced if the parts are smaller than `min_part_size`.
+    pub parts: usize,
+    /// The min size of a part.
+    pub min_part_size: u64,
+    /// The max number of the parts uploaded at the same time. The parts being uploaded are buffered in memory.
+    pub concurrency: usize,
+    /// The max number of the consecutive retries of a part on the transient failures.
+    pub max_retries: usize,
+    /// The name prefix of the temporary objects in the destination bucket.
+    /// The default is `{object}.parallel-upload-{timestamp}/`.
+    pub temporary_prefix: Option<String>,
+}
+
+impl Default for ParallelUploadConfig {
+    fn default() -> Self {
+        Self {
+            parts: 8,
+            min_part_size: 8 * 1024 * 1024,
+            concurrency: 4,
+            max_retries: 3,
+            temporary_prefix: None,
+        }
+    }
+}
+
+impl ParallelUploadConfig {
+    /// part_ranges returns the byte ranges of the parts. There is at least one part even if the size is zero.
+    pub(crate) fn part_ranges(&self, size: u64) -> Vec<Range<u64>> {
+        let parts = self.parts.max(1) as u64;
+        let part_size = size.div_ceil(parts).max(self.min_part_size).max(1);
+        (0..size.div_ceil(part_size).max(1))
+            .map(|i| i * part_size..((i + 1) * part_size).min(size))
+            .collect()
+    }
+
+    /// temporary_prefix returns the name prefix of the temporary objects of the object.
+    pub(crate) fn temporary_prefix(&self, object: &str) -> String {
+        match &self.temporary_prefix {
+            Some(prefix) => prefix.to_string(),
+            None => format!("{object}.parallel-upload-{}/", OffsetDateTime::now_utc().unix_timestamp_nanos()),
+        }
+    }
+}
+
+/// part_name returns the name of the temporary object of the part.
+pub(crate) fn part_name(prefix: &str, index: usize) -> String {
+    format!("{prefix}part-{index:05}")
+}
+
+/// compose_name returns the name of the intermediate object composed at the level of the cascade.
+pub(crate) fn compose_name(prefix: &str, level: usize, index: usize) -> String {
+    format!("{prefix}compose-{level}-{index:05}")
+}
+
+/// ParallelUploadProgress is notified every time a part is uploaded.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParallelUploadProgress {
+    /// The size of the uploaded parts.
+    pub uploaded_size: u64,
+    /// The number of the uploaded parts.
+    pub uploaded_parts: usize,
+    /// The size of the object.
+    pub total_object_size: u64,
+    /// The number of the parts.
+    pub total_parts: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::objects::parallel_upload::{compose_name, part_name, ParallelUploadConfig};
+
+    #[test]
+    fn test_part_ranges() {
+        let config = ParallelUploadConfig {
+            parts: 4,
+            min_part_size: 1,
+            ..Default::default()
+        };
+        assert_eq!(config.part_ranges(10), vec![0..3, 3..6, 6..9, 9..10]);
+        assert_eq!(config.part_ranges(2), vec![0..1, 1..2]);
+        assert_eq!(config.part_ranges(0), vec![0..0]);
+
+        let config = ParallelUploadConfig {
+            parts: 4,
+            min_part_size: 5,
+            ..Default::default()
+        };
+        assert_eq!(config.part_ranges(12), vec![0..5, 5..10, 10..12]);
+        assert_eq!(config.part_ranges(3), vec![0..3]);
+    }
+
+    #[test]
+    fn test_temporary_names() {
+        let config = ParallelUploadConfig {
+            temporary_prefix: Some("tmp/".to_string()),
+            ..Default::default()
+        };
+        let prefix = config.temporary_prefix("object");
+        assert_eq!(part_name(&prefix, 3), "tmp/part-00003");
+        assert_eq!(compose_name(&prefix, 1, 12), "tmp/compose-1-00012");
+        assert!(ParallelUploadConfig::default()
+            .temporary_prefix("dir/object")
+            .starts_with("dir/object.parallel-upload-"));
+    }
+}
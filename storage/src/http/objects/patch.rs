@@ -2,9 +2,35 @@ use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
 
 use crate::http::bucket_access_controls::PredefinedBucketAcl;
 use crate::http::object_access_controls::Projection;
-use crate::http::objects::{Encryption, Object};
+use crate::http::objects::{Conditions, Encryption, Object};
 use crate::http::Escape;
 
+/// ObjectHold is the hold on the object, which protects it against the deletion and the overwrite.
+/// https://cloud.google.com/storage/docs/object-holds
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ObjectHold {
+    /// The temporary hold doesn't affect the retention expiration time of the object.
+    Temporary,
+    /// The retention period of the bucket starts when the event-based hold is released.
+    EventBased,
+}
+
+impl ObjectHold {
+    /// metadata returns the metadata patching only the hold.
+    pub(crate) fn metadata(&self, enabled: bool) -> Object {
+        match self {
+            Self::Temporary => Object {
+                temporary_hold: Some(enabled),
+                ..Default::default()
+            },
+            Self::EventBased => Object {
+                event_based_hold: Some(enabled),
+                ..Default::default()
+            },
+        }
+    }
+}
+
 /// Request message for PatchObject.
 #[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
@@ -45,6 +71,19 @@ pub struct PatchObjectRequest {
     pub encryption: Option<Encryption>,
 }
 
+impl PatchObjectRequest {
+    /// with_conditions replaces the preconditions of the request.
+    pub fn with_conditions(self, conditions: Conditions) -> Self {
+        Self {
+            if_generation_match: conditions.if_generation_match,
+            if_generation_not_match: conditions.if_generation_not_match,
+            if_metageneration_match: conditions.if_metageneration_match,
+            if_metageneration_not_match: conditions.if_metageneration_not_match,
+            ..self
+        }
+    }
+}
+
 pub(crate) fn build(base_url: &str, client: &Client, req: &PatchObjectRequest) -> RequestBuilder {
     let url = format!("{}/b/{}/o/{}", base_url, req.bucket.escape(), req.object.escape());
     let builder = client.patch(url).query(&req).json(&req.metadata);
@@ -54,3 +93,18 @@ pub(crate) fn build(base_url: &str, client: &Client, req: &PatchObjectRequest) -
         builder
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::http::objects::patch::ObjectHold;
+
+    #[test]
+    fn test_object_hold_metadata() {
+        let json = |hold: ObjectHold, enabled| serde_json::to_value(hold.metadata(enabled)).unwrap();
+        assert_eq!(json(ObjectHold::Temporary, true), serde_json::json!({"temporaryHold": true}));
+        assert_eq!(
+            json(ObjectHold::EventBased, false),
+            serde_json::json!({"eventBasedHold": false})
+        );
+    }
+}
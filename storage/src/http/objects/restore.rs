@@ -0,0 +1,53 @@
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+use crate::http::object_access_controls::Projection;
+use crate::http::objects::Encryption;
+use crate::http::Escape;
+
+/// Request message for RestoreObject.
+/// https://cloud.google.com/storage/docs/json_api/v1/objects/restore
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreObjectRequest {
+    /// Required. Name of the bucket in which the object resides.
+    #[serde(skip_serializing)]
+    pub bucket: String,
+    /// Required. Name of the soft-deleted object.
+    #[serde(skip_serializing)]
+    pub object: String,
+    /// Required. The generation of the soft-deleted object listed with `ListObjectsRequest::soft_deleted`.
+    pub generation: i64,
+    /// If true, copies the ACL of the soft-deleted object to the restored object.
+    /// Otherwise the restored object has the default object ACL of the bucket.
+    pub copy_source_acl: Option<bool>,
+    /// Makes the operation conditional on whether the object's current generation
+    /// matches the given value. Setting to 0 makes the operation succeed only if
+    /// there are no live versions of the object.
+    pub if_generation_match: Option<i64>,
+    /// Makes the operation conditional on whether the object's current generation
+    /// does not match the given value. If no live object exists, the precondition
+    /// fails. Setting to 0 makes the operation succeed only if there is a live
+    /// version of the object.
+    pub if_generation_not_match: Option<i64>,
+    /// Makes the operation conditional on whether the object's current
+    /// metageneration matches the given value.
+    pub if_metageneration_match: Option<i64>,
+    /// Makes the operation conditional on whether the object's current
+    /// metageneration does not match the given value.
+    pub if_metageneration_not_match: Option<i64>,
+    /// Set of properties to return. Defaults to `NO_ACL`.
+    pub projection: Option<Projection>,
+    /// A set of parameters common to Storage API requests concerning an object.
+    #[serde(skip_serializing)]
+    pub encryption: Option<Encryption>,
+}
+
+pub(crate) fn build(base_url: &str, client: &Client, req: &RestoreObjectRequest) -> RequestBuilder {
+    let url = format!("{}/b/{}/o/{}/restore", base_url, req.bucket.escape(), req.object.escape());
+    let builder = client.post(url).query(&req);
+    if let Some(e) = &req.encryption {
+        e.with_headers(builder)
+    } else {
+        builder
+    }
+}
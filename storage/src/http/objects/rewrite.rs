@@ -23,6 +23,17 @@ pub struct RewriteObjectRequest {
     /// to be path safe, see Encoding URI path parts.
     #[serde(skip_serializing)]
     pub source_object: String,
+    /// Makes the operation conditional on there being a live destination object with a generation number
+    /// that matches the given value. Setting ifGenerationMatch to 0 makes the operation succeed only
+    /// if there is no live destination object.
+    pub if_generation_match: Option<i64>,
+    /// Makes the operation conditional on there being a live destination object with a generation number
+    /// that does not match the given value.
+    pub if_generation_not_match: Option<i64>,
+    /// Makes the operation conditional on whether the source object's generation matches the given value.
+    pub if_source_generation_match: Option<i64>,
+    /// Makes the operation conditional on whether the source object's generation does not match the given value.
+    pub if_source_generation_not_match: Option<i64>,
     /// If set, only deletes the bucket if its metageneration matches this value.
     pub if_destination_metageneration_match: Option<i64>,
     /// If set, only deletes the bucket if its metageneration does not match this
@@ -103,6 +114,24 @@ pub struct RewriteObjectResponse {
     pub resource: Option<Object>,
 }
 
+/// RewriteProgress is notified every time a rewrite request returns.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RewriteProgress {
+    /// The total bytes written so far.
+    pub total_bytes_rewritten: i64,
+    /// The total size of the object being copied in bytes.
+    pub object_size: i64,
+}
+
+impl From<&RewriteObjectResponse> for RewriteProgress {
+    fn from(response: &RewriteObjectResponse) -> Self {
+        Self {
+            total_bytes_rewritten: response.total_bytes_rewritten,
+            object_size: response.object_size,
+        }
+    }
+}
+
 pub(crate) fn build(base_url: &str, client: &Client, req: &RewriteObjectRequest) -> RequestBuilder {
     let url = format!(
         "{}/b/{}/o/{}/rewriteTo/b/{}/o/{}",
@@ -117,10 +146,7 @@ pub(crate) fn build(base_url: &str, client: &Client, req: &RewriteObjectRequest)
         builder = e.with_headers(builder)
     }
     if let Some(e) = &req.source_encryption {
-        builder
-            .header("X-Goog-Copy-Source-Encryption-Algorithm", &e.encryption_algorithm)
-            .header("X-Goog-Copy-Source-Encryption-Key", &e.encryption_key)
-            .header("X-Goog-Copy-Source-Encryption-Key-Sha256", &e.encryption_key_sha256)
+        e.with_copy_source_headers(builder)
     } else {
         builder
     }
@@ -4,8 +4,10 @@ use reqwest::header::{CONTENT_LENGTH, CONTENT_TYPE};
 use reqwest::multipart::{Form, Part};
 use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
 
+use crate::http::checksum::ChecksumConfig;
 use crate::http::object_access_controls::{PredefinedObjectAcl, Projection};
-use crate::http::objects::{Encryption, Object};
+use crate::http::objects::{Conditions, Encryption, Object};
+use crate::http::resumable_upload_client::ChunkConfig;
 use crate::http::{Error, Escape};
 
 #[derive(Clone, Debug)]
@@ -29,6 +31,10 @@ impl Media {
 pub enum UploadType {
     Simple(Media),
     Multipart(Box<Object>),
+    /// Resumable uploads the object in chunks with the resumable upload protocol.
+    /// The failed chunks are retried from the size persisted by the server.
+    /// https://cloud.google.com/storage/docs/performing-resumable-uploads
+    Resumable(Box<Object>, ChunkConfig),
 }
 
 #[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
@@ -77,6 +83,24 @@ pub struct UploadObjectRequest {
     pub projection: Option<Projection>,
     #[serde(skip_serializing)]
     pub encryption: Option<Encryption>,
+    /// The checksums of the simple and multipart uploads. The server rejects the data not matching them.
+    /// The checksums of the streamed data are verified after the upload, and the corrupted object is deleted.
+    /// The resumable uploads use `ChunkConfig::checksum` instead.
+    #[serde(skip)]
+    pub checksum: ChecksumConfig,
+}
+
+impl UploadObjectRequest {
+    /// with_conditions replaces the preconditions of the request.
+    pub fn with_conditions(self, conditions: Conditions) -> Self {
+        Self {
+            if_generation_match: conditions.if_generation_match,
+            if_generation_not_match: conditions.if_generation_not_match,
+            if_metageneration_match: conditions.if_metageneration_match,
+            if_metageneration_not_match: conditions.if_metageneration_not_match,
+            ..self
+        }
+    }
 }
 
 pub(crate) fn build<T: Into<reqwest::Body>>(
@@ -1,10 +1,18 @@
 use std::fmt;
 
-use reqwest::header::{CONTENT_LENGTH, CONTENT_RANGE};
-use reqwest::{Body, Response};
-use reqwest_middleware::ClientWithMiddleware as Client;
+use bytes::{Bytes, BytesMut};
+use futures_util::{TryStream, TryStreamExt};
+use reqwest::header::{CONTENT_LENGTH, CONTENT_RANGE, RANGE};
+use reqwest::{Body, Method, Response};
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
 
-use crate::http::{check_response_status, objects::Object, Error};
+use crate::http::checksum::{ChecksumConfig, Hasher};
+use crate::http::{check_response_status, is_transient, objects::Object, retry_delay, Error};
+
+/// The size of the chunks except the last one must be a multiple of this.
+pub const CHUNK_SIZE_MULTIPLE: usize = 256 * 1024;
+/// The default size of the chunks of `ResumableUploadClient::upload_chunks`.
+pub const DEFAULT_CHUNK_SIZE: usize = 16 * 1024 * 1024;
 
 #[derive(thiserror::Error, Debug)]
 pub enum ChunkError {
@@ -14,6 +22,16 @@ pub enum ChunkError {
     ZeroTotalObjectSize,
     #[error("last byte must be less than total object size: last={0} total={1}")]
     InvalidLastBytes(u64, u64),
+    #[error("chunk size must be a non-zero multiple of {CHUNK_SIZE_MULTIPLE} bytes: {0}")]
+    InvalidChunkSize(usize),
+    #[error("data size doesn't match the total object size: data={0} total={1}")]
+    TotalObjectSizeMismatch(u64, u64),
+    #[error("persisted size is out of the uploading chunk: persisted={0} first={1} last={2}")]
+    UnexpectedPersistedSize(u64, u64, u64),
+    #[error("streamed body can't be uploaded in chunks, use upload_streamed_object instead")]
+    StreamedBody,
+    #[error("failed to read the data: {0}")]
+    Read(Box<dyn std::error::Error + Send + Sync>),
 }
 
 #[derive(PartialEq, Debug)]
@@ -23,6 +41,58 @@ pub enum UploadStatus {
     ResumeIncomplete,
 }
 
+/// SessionStatus is the status of the upload session including the size persisted by the server.
+#[derive(PartialEq, Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum SessionStatus {
+    Completed(Object),
+    Incomplete { persisted_size: u64 },
+}
+
+/// ChunkConfig is the configuration of `ResumableUploadClient::upload_chunks`.
+#[derive(Clone, Debug)]
+pub struct ChunkConfig {
+    /// The size of the chunks. It must be a multiple of 256 KiB.
+    /// The larger chunk is faster, but more data is buffered and resent on failure.
+    pub chunk_size: usize,
+    /// The size of the object. If it is `None`, the size is determined at the end of the data.
+    pub total_object_size: Option<u64>,
+    /// The max number of the retries of a chunk on the transient failures like 5xx and timeout.
+    pub max_retries: usize,
+    /// The checksums computed while the data is read and validated by the server with the last chunk.
+    /// They are computed only when the upload starts from the offset 0.
+    pub checksum: ChecksumConfig,
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            total_object_size: None,
+            max_retries: 5,
+            checksum: ChecksumConfig::default(),
+        }
+    }
+}
+
+impl ChunkConfig {
+    pub(crate) fn validate(&self) -> Result<(), ChunkError> {
+        if self.chunk_size == 0 || !self.chunk_size.is_multiple_of(CHUNK_SIZE_MULTIPLE) {
+            return Err(ChunkError::InvalidChunkSize(self.chunk_size));
+        }
+        Ok(())
+    }
+}
+
+/// UploadProgress is notified every time a chunk is persisted by the server.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct UploadProgress {
+    /// The size of the object persisted by the server.
+    pub persisted_size: u64,
+    /// The size of the object if it is known.
+    pub total_object_size: Option<u64>,
+}
+
 #[derive(Clone, Debug)]
 pub struct ChunkSize {
     first_byte: u64,
@@ -67,6 +137,7 @@ impl ChunkSize {
 pub struct ResumableUploadClient {
     session_url: String,
     http: Client,
+    user_project: Option<String>,
 }
 
 impl ResumableUploadClient {
@@ -75,14 +146,32 @@ impl ResumableUploadClient {
     }
 
     pub fn new(session_url: String, http: Client) -> Self {
-        Self { session_url, http }
+        Self {
+            session_url,
+            http,
+            user_project: None,
+        }
+    }
+
+    /// with_user_project bills the requests of the session to the project.
+    /// It must be the same project as the one initiating the session of the requester pays bucket.
+    pub fn with_user_project(mut self, user_project: Option<String>) -> Self {
+        self.user_project = user_project;
+        self
+    }
+
+    fn request(&self, method: Method) -> RequestBuilder {
+        let builder = self.http.request(method, &self.session_url);
+        match &self.user_project {
+            Some(user_project) => builder.query(&[("userProject", user_project)]),
+            None => builder,
+        }
     }
 
     /// https://cloud.google.com/storage/docs/performing-resumable-uploads#single-chunk-upload
     pub async fn upload_single_chunk<T: Into<Body>>(&self, data: T, size: usize) -> Result<(), Error> {
         let response = self
-            .http
-            .put(&self.session_url)
+            .request(Method::PUT)
             .header(CONTENT_LENGTH, size)
             .body(data)
             .send()
@@ -95,8 +184,7 @@ impl ResumableUploadClient {
     /// https://cloud.google.com/storage/docs/performing-resumable-uploads#resume-upload
     pub async fn upload_multiple_chunk<T: Into<Body>>(&self, data: T, size: &ChunkSize) -> Result<UploadStatus, Error> {
         let response = self
-            .http
-            .put(&self.session_url)
+            .request(Method::PUT)
             .header(CONTENT_RANGE, size.to_string())
             .header(CONTENT_LENGTH, size.size())
             .body(data)
@@ -107,30 +195,174 @@ impl ResumableUploadClient {
 
     /// https://cloud.google.com/storage/docs/performing-resumable-uploads#status-check
     pub async fn status(&self, object_size: Option<u64>) -> Result<UploadStatus, Error> {
+        let response = self.status_response(object_size).await?;
+        Self::map_resume_response(response).await
+    }
+
+    /// session_status is the same as `status` but returns the size persisted by the server too.
+    /// The upload is resumed from the persisted size, even in another process
+    /// with the session url and `StorageClient::get_resumable_upload`.
+    pub async fn session_status(&self, object_size: Option<u64>) -> Result<SessionStatus, Error> {
+        let response = self.status_response(object_size).await?;
+        Self::map_session_response(response).await
+    }
+
+    async fn status_response(&self, object_size: Option<u64>) -> Result<Response, Error> {
         let mut content_range = "bytes */".to_owned();
         match object_size {
             Some(object_size) => content_range.push_str(&object_size.to_string()),
             None => content_range.push('*'),
         };
         let response = self
-            .http
-            .put(&self.session_url)
+            .request(Method::PUT)
             .header(CONTENT_RANGE, content_range)
             .header(CONTENT_LENGTH, 0)
             .body(Vec::new())
             .send()
             .await?;
-        Self::map_resume_response(response).await
+        Ok(response)
+    }
+
+    /// upload_chunks uploads the data in chunks of `config.chunk_size` and returns the uploaded object.
+    ///
+    /// `offset` is the position of the first byte of the data in the object. It is 0 for the new session.
+    /// To resume the session, pass the `persisted_size` of `session_status` and the data after it.
+    ///
+    /// A chunk failed by the transient error is retried up to `config.max_retries` times with backoff.
+    /// Before the retry, the persisted size is queried so that only the bytes not persisted are resent.
+    /// `progress` is called every time a chunk is persisted.
+    ///
+    /// The checksums of `config.checksum` are sent with the last chunk when the whole data is uploaded
+    /// by this call, so the server rejects the corrupted data.
+    ///
+    /// https://cloud.google.com/storage/docs/performing-resumable-uploads#chunked-upload
+    pub async fn upload_chunks<S>(
+        &self,
+        data: S,
+        offset: u64,
+        config: &ChunkConfig,
+        mut progress: impl FnMut(UploadProgress),
+    ) -> Result<Object, Error>
+    where
+        S: TryStream,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+        Bytes: From<S::Ok>,
+    {
+        config.validate()?;
+        let mut data = Box::pin(data.into_stream());
+        let mut buffer = BytesMut::new();
+        let mut finished = false;
+        // The checksums can't be computed without the bytes uploaded before the offset.
+        let mut hasher = (offset == 0 && config.checksum.is_enabled()).then(|| Hasher::new(&config.checksum));
+        let mut offset = offset;
+        loop {
+            // One more byte than the chunk is read to know whether the chunk is the last one.
+            while !finished && buffer.len() <= config.chunk_size {
+                match data.try_next().await.map_err(|e| ChunkError::Read(e.into()))? {
+                    Some(v) => buffer.extend_from_slice(&Bytes::from(v)),
+                    None => finished = true,
+                }
+            }
+            let last = finished && buffer.len() <= config.chunk_size;
+            let chunk = buffer.split_to(buffer.len().min(config.chunk_size)).freeze();
+            let end = offset + chunk.len() as u64;
+            let total_object_size = if last {
+                if let Some(total) = config.total_object_size.filter(|v| *v != end) {
+                    return Err(ChunkError::TotalObjectSizeMismatch(end, total).into());
+                }
+                Some(end)
+            } else {
+                config.total_object_size
+            };
+            if let Some(hasher) = hasher.as_mut() {
+                hasher.update(&chunk);
+            }
+            let hash = if last {
+                hasher.take().and_then(|v| v.finish().header_value())
+            } else {
+                None
+            };
+            let status = self
+                .upload_chunk_with_retry(chunk, offset, total_object_size, last, hash, config.max_retries)
+                .await?;
+            progress(UploadProgress {
+                persisted_size: end,
+                total_object_size,
+            });
+            match status {
+                SessionStatus::Completed(object) => return Ok(object),
+                SessionStatus::Incomplete { .. } => offset = end,
+            }
+        }
+    }
+
+    /// upload_chunk_with_retry uploads the chunk starting at offset until all the bytes are persisted.
+    /// `hash` is the `x-goog-hash` header of the last chunk.
+    async fn upload_chunk_with_retry(
+        &self,
+        chunk: Bytes,
+        offset: u64,
+        total_object_size: Option<u64>,
+        last: bool,
+        hash: Option<String>,
+        max_retries: usize,
+    ) -> Result<SessionStatus, Error> {
+        let end = offset + chunk.len() as u64;
+        let check_persisted_size = |persisted_size: u64| {
+            if persisted_size < offset || persisted_size > end {
+                Err(ChunkError::UnexpectedPersistedSize(persisted_size, offset, end))
+            } else {
+                Ok(persisted_size)
+            }
+        };
+        let mut retries = 0;
+        // None means the persisted size is unknown after the failure.
+        let mut persisted = Some(offset);
+        loop {
+            let result = match persisted {
+                Some(start) if start == end && !last => return Ok(SessionStatus::Incomplete { persisted_size: end }),
+                Some(start) => {
+                    let body = chunk.slice((start - offset) as usize..);
+                    let range = if body.is_empty() {
+                        ChunkSize::new(start, start, total_object_size)
+                    } else {
+                        ChunkSize::new(start, end - 1, total_object_size)
+                    };
+                    self.upload_chunk(body, &range, hash.as_deref()).await
+                }
+                None => self.session_status(total_object_size).await,
+            };
+            match result {
+                Ok(SessionStatus::Completed(object)) => return Ok(SessionStatus::Completed(object)),
+                Ok(SessionStatus::Incomplete { persisted_size }) => {
+                    persisted = Some(check_persisted_size(persisted_size)?);
+                }
+                Err(e) if retries < max_retries && is_transient(&e) => {
+                    tracing::debug!("retry the chunk {offset}-{end}: {e}");
+                    tokio::time::sleep(retry_delay(retries)).await;
+                    retries += 1;
+                    persisted = None;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn upload_chunk(&self, data: Bytes, size: &ChunkSize, hash: Option<&str>) -> Result<SessionStatus, Error> {
+        let mut builder = self
+            .request(Method::PUT)
+            .header(CONTENT_RANGE, size.to_string())
+            .header(CONTENT_LENGTH, size.size());
+        if let Some(hash) = hash {
+            builder = builder.header("x-goog-hash", hash);
+        }
+        let response = builder.body(data).send().await?;
+        Self::map_session_response(response).await
     }
 
     /// https://cloud.google.com/storage/docs/performing-resumable-uploads#cancel-upload
     pub async fn cancel(self) -> Result<(), Error> {
-        let response = self
-            .http
-            .delete(&self.session_url)
-            .header(CONTENT_LENGTH, 0)
-            .send()
-            .await?;
+        let response = self.request(Method::DELETE).header(CONTENT_LENGTH, 0).send().await?;
         if response.status() == 499 {
             Ok(())
         } else {
@@ -139,6 +371,18 @@ impl ResumableUploadClient {
         }
     }
 
+    async fn map_session_response(response: Response) -> Result<SessionStatus, Error> {
+        if response.status() == 308 {
+            let range = response.headers().get(RANGE).and_then(|v| v.to_str().ok());
+            Ok(SessionStatus::Incomplete {
+                persisted_size: parse_persisted_size(range),
+            })
+        } else {
+            let response = check_response_status(response).await?;
+            Ok(SessionStatus::Completed(response.json::<Object>().await?))
+        }
+    }
+
     async fn map_resume_response(response: Response) -> Result<UploadStatus, Error> {
         if response.status() == 308 {
             Ok(UploadStatus::ResumeIncomplete)
@@ -148,3 +392,52 @@ impl ResumableUploadClient {
         }
     }
 }
+
+/// parse_persisted_size returns the size persisted by the server from the Range header like `bytes=0-42`.
+/// No Range header means no bytes are persisted yet.
+fn parse_persisted_size(range: Option<&str>) -> u64 {
+    range
+        .and_then(|v| v.strip_prefix("bytes=0-"))
+        .and_then(|v| v.parse::<u64>().ok())
+        .map_or(0, |last| last + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::resumable_upload_client::{
+        parse_persisted_size, ChunkConfig, ChunkError, ChunkSize, CHUNK_SIZE_MULTIPLE,
+    };
+
+    #[test]
+    fn test_parse_persisted_size() {
+        assert_eq!(parse_persisted_size(None), 0);
+        assert_eq!(parse_persisted_size(Some("bytes=0-0")), 1);
+        assert_eq!(parse_persisted_size(Some("bytes=0-262143")), 262144);
+        assert_eq!(parse_persisted_size(Some("invalid")), 0);
+    }
+
+    #[test]
+    fn test_chunk_config() {
+        assert!(ChunkConfig::default().validate().is_ok());
+        for chunk_size in [0, 1, CHUNK_SIZE_MULTIPLE + 1] {
+            let config = ChunkConfig {
+                chunk_size,
+                ..Default::default()
+            };
+            assert!(matches!(config.validate(), Err(ChunkError::InvalidChunkSize(v)) if v == chunk_size));
+        }
+        let config = ChunkConfig {
+            chunk_size: CHUNK_SIZE_MULTIPLE * 4,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_chunk_size() {
+        assert_eq!(ChunkSize::new(0, 9, Some(20)).to_string(), "bytes 0-9/20");
+        assert_eq!(ChunkSize::new(0, 9, None).to_string(), "bytes 0-9/*");
+        assert_eq!(ChunkSize::new(20, 20, Some(20)).to_string(), "bytes */20");
+        assert_eq!(ChunkSize::new(20, 20, Some(20)).size(), 0);
+    }
+}
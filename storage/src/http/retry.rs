@@ -0,0 +1,74 @@
+/// RetryPolicy decides which operations are retried on the transient failures.
+/// https://cloud.google.com/storage/docs/retry-strategy
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RetryPolicy {
+    /// Retries the idempotent operations like the reads, and the conditionally idempotent operations
+    /// only if they have the precondition like `if_generation_match`, `if_metageneration_match` or `etag`.
+    #[default]
+    IfIdempotent,
+    /// Retries all the operations. The non idempotent operations may be applied twice.
+    Always,
+    /// Never retries.
+    Never,
+}
+
+impl RetryPolicy {
+    pub(crate) fn is_retryable(&self, idempotency: Idempotency) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::IfIdempotent => matches!(idempotency, Idempotency::Idempotent | Idempotency::Conditional(true)),
+        }
+    }
+}
+
+/// RetryConfig is the configuration of the retries of `StorageClient`.
+/// It is overridden per call with `StorageClient::with_retry`.
+///
+/// The requests with the streamed body, including the multipart uploads, can't be resent, so they are never retried.
+/// The resumable uploads and the downloads of `download_object_stream` are retried by their own configurations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryConfig {
+    pub policy: RetryPolicy,
+    /// The max number of the retries of a request.
+    pub max_retries: usize,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            policy: RetryPolicy::default(),
+            max_retries: 3,
+        }
+    }
+}
+
+/// Idempotency is whether the operation can be applied more than once safely.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Idempotency {
+    /// The operation can be always retried like the reads.
+    Idempotent,
+    /// The operation is idempotent only with the precondition. The value is true if it has one.
+    Conditional(bool),
+    /// The operation may be applied twice by the retry like creating the ACL.
+    NonIdempotent,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::retry::{Idempotency, RetryPolicy};
+
+    #[test]
+    fn test_is_retryable() {
+        let idempotencies = [
+            Idempotency::Idempotent,
+            Idempotency::Conditional(true),
+            Idempotency::Conditional(false),
+            Idempotency::NonIdempotent,
+        ];
+        let retryable = |policy: RetryPolicy| idempotencies.map(|v| policy.is_retryable(v));
+        assert_eq!(retryable(RetryPolicy::IfIdempotent), [true, true, false, false]);
+        assert_eq!(retryable(RetryPolicy::Always), [true, true, true, true]);
+        assert_eq!(retryable(RetryPolicy::Never), [false, false, false, false]);
+    }
+}
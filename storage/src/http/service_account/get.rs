@@ -0,0 +1,16 @@
+use reqwest_middleware::{ClientWithMiddleware as Client, RequestBuilder};
+
+use crate::http::Escape;
+
+/// Request message for GetServiceAccount.
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct GetServiceAccountRequest {
+    /// Required. Project ID.
+    pub project_id: String,
+}
+
+pub(crate) fn build(base_url: &str, client: &Client, req: &GetServiceAccountRequest) -> RequestBuilder {
+    let url = format!("{}/projects/{}/serviceAccount", base_url, req.project_id.escape());
+    client.get(url)
+}
@@ -0,0 +1,12 @@
+pub mod get;
+
+/// The Google Cloud Storage service agent of the project.
+/// It publishes the Pub/Sub notifications and uses the Cloud KMS keys on behalf of the project.
+#[derive(Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize, Debug)]
+pub struct ServiceAccount {
+    /// The email address of the service agent like
+    /// `service-{project-number}@gs-project-accounts.iam.gserviceaccount.com`.
+    pub email_address: String,
+    /// The kind of item this is. For the service account, this is always `storage#serviceAccount`.
+    pub kind: String,
+}
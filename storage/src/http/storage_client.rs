@@ -1,9 +1,12 @@
-use std::sync::Arc;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 
-use futures_util::{Stream, TryStream, TryStreamExt};
+use futures_util::{Stream, StreamExt, TryStream, TryStreamExt};
 use reqwest::header::{HeaderValue, CONTENT_LENGTH, LOCATION};
-use reqwest::{Body, Request};
+use reqwest::{Body, Response};
 use reqwest_middleware::RequestBuilder;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio_util::io::StreamReader;
 
 use google_cloud_token::TokenSource;
 
@@ -18,10 +21,12 @@ use crate::http::buckets::get::GetBucketRequest;
 use crate::http::buckets::get_iam_policy::GetIamPolicyRequest;
 use crate::http::buckets::insert::InsertBucketRequest;
 use crate::http::buckets::list::{ListBucketsRequest, ListBucketsResponse};
-use crate::http::buckets::patch::PatchBucketRequest;
+use crate::http::buckets::lock_retention_policy::LockRetentionPolicyRequest;
+use crate::http::buckets::patch::{BucketConditions, BucketPatch, PatchBucketRequest};
 use crate::http::buckets::set_iam_policy::SetIamPolicyRequest;
 use crate::http::buckets::test_iam_permissions::{TestIamPermissionsRequest, TestIamPermissionsResponse};
-use crate::http::buckets::{Bucket, Policy};
+use crate::http::buckets::{lifecycle, Bucket, Policy};
+use crate::http::checksum::{crc32c_combine, ChecksumConfig, Checksums, Hasher};
 use crate::http::default_object_access_controls::delete::DeleteDefaultObjectAccessControlRequest;
 use crate::http::default_object_access_controls::get::GetDefaultObjectAccessControlRequest;
 use crate::http::default_object_access_controls::insert::InsertDefaultObjectAccessControlRequest;
@@ -34,7 +39,7 @@ use crate::http::hmac_keys::delete::DeleteHmacKeyRequest;
 use crate::http::hmac_keys::get::GetHmacKeyRequest;
 use crate::http::hmac_keys::list::{ListHmacKeysRequest, ListHmacKeysResponse};
 use crate::http::hmac_keys::update::UpdateHmacKeyRequest;
-use crate::http::hmac_keys::HmacKeyMetadata;
+use crate::http::hmac_keys::{HmacKeyMetadata, HmacKeyState};
 use crate::http::notifications::delete::DeleteNotificationRequest;
 use crate::http::notifications::get::GetNotificationRequest;
 use crate::http::notifications::insert::InsertNotificationRequest;
@@ -46,22 +51,30 @@ use crate::http::object_access_controls::insert::InsertObjectAccessControlReques
 use crate::http::object_access_controls::list::ListObjectAccessControlsRequest;
 use crate::http::object_access_controls::patch::PatchObjectAccessControlRequest;
 use crate::http::object_access_controls::ObjectAccessControl;
-use crate::http::objects::compose::ComposeObjectRequest;
+use crate::http::objects::compose::{ComposeObjectRequest, ComposingTargets, MAX_COMPOSE_SOURCES};
 use crate::http::objects::copy::CopyObjectRequest;
 use crate::http::objects::delete::DeleteObjectRequest;
-use crate::http::objects::download::Range;
+use crate::http::objects::download::{DownloadConfig, Range};
 use crate::http::objects::get::GetObjectRequest;
-use crate::http::objects::list::{ListObjectsRequest, ListObjectsResponse};
-use crate::http::objects::patch::PatchObjectRequest;
-use crate::http::objects::rewrite::{RewriteObjectRequest, RewriteObjectResponse};
-use crate::http::objects::upload::{UploadObjectRequest, UploadType};
-use crate::http::objects::Object;
-use crate::http::resumable_upload_client::ResumableUploadClient;
+use crate::http::objects::list::{ListObjectsItem, ListObjectsRequest, ListObjectsResponse};
+use crate::http::objects::parallel_upload::{ParallelUploadConfig, ParallelUploadProgress};
+use crate::http::objects::patch::{ObjectHold, PatchObjectRequest};
+use crate::http::objects::restore::RestoreObjectRequest;
+use crate::http::objects::rewrite::{RewriteObjectRequest, RewriteObjectResponse, RewriteProgress};
+use crate::http::objects::upload::{Media, UploadObjectRequest, UploadType};
+use crate::http::objects::{parallel_upload, Object, ObjectPreconditions, SourceObjects};
+use crate::http::resumable_upload_client::{ChunkError, ResumableUploadClient};
+use crate::http::retry::{Idempotency, RetryConfig, RetryPolicy};
+use crate::http::service_account::get::GetServiceAccountRequest;
+use crate::http::service_account::ServiceAccount;
 use crate::http::{
-    bucket_access_controls, buckets, check_response_status, default_object_access_controls, hmac_keys, notifications,
-    object_access_controls, objects, Error,
+    bucket_access_controls, buckets, check_response_status, default_object_access_controls, hmac_keys, is_transient,
+    notifications, object_access_controls, objects, retry_delay, service_account, Error,
 };
 
+/// The number of the attempts to find the inserted notification in the list.
+const NOTIFICATION_LIST_ATTEMPTS: usize = 6;
+
 pub const SCOPES: [&str; 2] = [
     "https://www.googleapis.com/auth/cloud-platform",
     "https://www.googleapis.com/auth/devstorage.full_control",
@@ -73,19 +86,109 @@ pub struct StorageClient {
     v1_endpoint: String,
     v1_upload_endpoint: String,
     http: reqwest_middleware::ClientWithMiddleware,
+    retry: RetryConfig,
+    user_project: Option<String>,
+    checksum: ChecksumConfig,
+}
+
+/// bytes_body creates the body of the stream of `Bytes`. It is separated from the generic upload methods
+/// because their `Bytes: From<S::Ok>` bound hides `impl From<Bytes> for Bytes` from the type inference.
+fn bytes_body<S>(data: S) -> Body
+where
+    S: TryStream<Ok = bytes::Bytes> + Send + Sync + 'static,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    Body::wrap_stream(data)
 }
 
 impl StorageClient {
     pub(crate) fn new(
         ts: Option<Arc<dyn TokenSource>>,
         endpoint: &str,
+        upload_endpoint: &str,
         http: reqwest_middleware::ClientWithMiddleware,
+        retry: RetryConfig,
+        user_project: Option<String>,
+        checksum: ChecksumConfig,
     ) -> Self {
         Self {
             ts,
             v1_endpoint: format!("{endpoint}/storage/v1"),
-            v1_upload_endpoint: format!("{endpoint}/upload/storage/v1"),
+            v1_upload_endpoint: format!("{upload_endpoint}/upload/storage/v1"),
             http,
+            retry,
+            user_project,
+            checksum,
+        }
+    }
+
+    /// with_retry returns the client retrying the operations with the config instead of the one of `ClientConfig`.
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::objects::delete::DeleteObjectRequest;
+    /// use google_cloud_storage::http::retry::{RetryConfig, RetryPolicy};
+    ///
+    /// async fn run(client:Client) {
+    ///     // The delete without the generation is not retried by default.
+    ///     let result = client.with_retry(RetryConfig {
+    ///         policy: RetryPolicy::Always,
+    ///         ..Default::default()
+    ///     }).delete_object(&DeleteObjectRequest {
+    ///         bucket: "bucket".to_string(),
+    ///         object: "object".to_string(),
+    ///         ..Default::default()
+    ///     }).await;
+    /// }
+    /// ```
+    pub fn with_retry(&self, retry: RetryConfig) -> Self {
+        Self { retry, ..self.clone() }
+    }
+
+    /// with_user_project returns the client billing the operations to the project instead of the one of `ClientConfig`.
+    /// The project is required to access the requester pays buckets.
+    /// https://cloud.google.com/storage/docs/requester-pays
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::objects::get::GetObjectRequest;
+    ///
+    /// async fn run(client:Client) {
+    ///     let result = client.with_user_project(Some("billing-project".to_string())).get_object(&GetObjectRequest {
+    ///         bucket: "requester-pays-bucket".to_string(),
+    ///         object: "object".to_string(),
+    ///         ..Default::default()
+    ///     }).await;
+    /// }
+    /// ```
+    pub fn with_user_project(&self, user_project: Option<String>) -> Self {
+        Self {
+            user_project,
+            ..self.clone()
+        }
+    }
+
+    /// with_checksum returns the client verifying the checksums of `download_object` with the config instead of
+    /// the one of `ClientConfig`.
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::checksum::ChecksumConfig;
+    /// use google_cloud_storage::http::objects::get::GetObjectRequest;
+    /// use google_cloud_storage::http::objects::download::Range;
+    ///
+    /// async fn run(client:Client) {
+    ///     let result = client.with_checksum(ChecksumConfig::disabled()).download_object(&GetObjectRequest {
+    ///         bucket: "bucket".to_string(),
+    ///         object: "object".to_string(),
+    ///         ..Default::default()
+    ///     }, &Range::default()).await;
+    /// }
+    /// ```
+    pub fn with_checksum(&self, checksum: ChecksumConfig) -> Self {
+        Self {
+            checksum,
+            ..self.clone()
         }
     }
 
@@ -106,7 +209,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn delete_bucket(&self, req: &DeleteBucketRequest) -> Result<(), Error> {
         let builder = buckets::delete::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send_get_empty(builder).await
+        self.send_get_empty(builder, Idempotency::Idempotent).await
     }
 
     /// Inserts the bucket.
@@ -130,7 +233,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn insert_bucket(&self, req: &InsertBucketRequest) -> Result<Bucket, Error> {
         let builder = buckets::insert::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Gets the bucket.
@@ -150,7 +253,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn get_bucket(&self, req: &GetBucketRequest) -> Result<Bucket, Error> {
         let builder = buckets::get::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Patches the bucket.
@@ -176,7 +279,100 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn patch_bucket(&self, req: &PatchBucketRequest) -> Result<Bucket, Error> {
         let builder = buckets::patch::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Conditional(req.if_metageneration_match.is_some()))
+            .await
+    }
+
+    /// Patches only the fields set in the `BucketPatch`.
+    /// The other fields of the bucket are not sent, so they are not overwritten by the concurrent updates.
+    /// Use `BucketConditions::metageneration_match` to patch only the bucket the caller has seen,
+    /// the patch fails with the status 412 when the metageneration doesn't match.
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::buckets::patch::{BucketConditions, BucketPatch};
+    /// use google_cloud_storage::http::buckets::Versioning;
+    ///
+    /// async fn run(client:Client) {
+    ///     let patch = BucketPatch::default().with_versioning(Versioning { enabled: true }).clear_website();
+    ///     let result = client.patch_bucket_fields("bucket", &patch, &BucketConditions::metageneration_match(1)).await;
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn patch_bucket_fields(
+        &self,
+        bucket: &str,
+        patch: &BucketPatch,
+        conditions: &BucketConditions,
+    ) -> Result<Bucket, Error> {
+        let builder = buckets::patch::build_fields(self.v1_endpoint.as_str(), &self.http, bucket, patch, conditions);
+        self.send(builder, Idempotency::Conditional(conditions.if_metageneration_match.is_some()))
+            .await
+    }
+
+    /// Updates the lifecycle rules of the bucket.
+    /// The current rules are fetched and modified by `f`, then patched back only if the bucket is not changed
+    /// after it was fetched. The patch fails with the status 412 when the bucket is changed concurrently.
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::buckets::lifecycle::LifecycleRule;
+    /// use google_cloud_storage::http::Error;
+    ///
+    /// async fn run(client:Client) -> Result<(), Error> {
+    ///     let rule = LifecycleRule::delete().with_age(30).with_is_live(false).build()?;
+    ///     let bucket = client.update_lifecycle_rules("bucket", |rules| rules.push(rule)).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn update_lifecycle_rules<F>(&self, bucket: &str, f: F) -> Result<Bucket, Error>
+    where
+        F: FnOnce(&mut Vec<lifecycle::Rule>),
+    {
+        let current = self
+            .get_bucket(&GetBucketRequest {
+                bucket: bucket.to_string(),
+                ..Default::default()
+            })
+            .await?;
+        let mut lifecycle = current.lifecycle.unwrap_or_default();
+        f(&mut lifecycle.rule);
+        let builder = buckets::patch::build_lifecycle(
+            self.v1_endpoint.as_str(),
+            &self.http,
+            bucket,
+            &lifecycle,
+            current.metageneration,
+        );
+        self.send(builder, Idempotency::Conditional(true)).await
+    }
+
+    /// Locks the retention policy of the bucket.
+    /// The lock is irreversible, and the retention period can't be reduced or removed afterwards.
+    /// The metageneration is required to lock only the policy the caller has seen.
+    /// https://cloud.google.com/storage/docs/json_api/v1/buckets/lockRetentionPolicy
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::buckets::get::GetBucketRequest;
+    /// use google_cloud_storage::http::buckets::lock_retention_policy::LockRetentionPolicyRequest;
+    ///
+    /// async fn run(client:Client) {
+    ///     let bucket = client.get_bucket(&GetBucketRequest {
+    ///         bucket: "bucket".to_string(),
+    ///         ..Default::default()
+    ///     }).await.unwrap();
+    ///     let result = client.lock_retention_policy(&LockRetentionPolicyRequest {
+    ///         bucket: "bucket".to_string(),
+    ///         if_metageneration_match: bucket.metageneration,
+    ///     }).await;
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn lock_retention_policy(&self, req: &LockRetentionPolicyRequest) -> Result<Bucket, Error> {
+        let builder = buckets::lock_retention_policy::build(self.v1_endpoint.as_str(), &self.http, req);
+        self.send(builder, Idempotency::Conditional(true)).await
     }
 
     /// Lists the bucket.
@@ -196,7 +392,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn list_buckets(&self, req: &ListBucketsRequest) -> Result<ListBucketsResponse, Error> {
         let builder = buckets::list::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Sets the iam policy.
@@ -225,7 +421,8 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn set_iam_policy(&self, req: &SetIamPolicyRequest) -> Result<Policy, Error> {
         let builder = buckets::set_iam_policy::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Conditional(!req.policy.etag.is_empty()))
+            .await
     }
 
     /// Gets the iam policy.
@@ -246,7 +443,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn get_iam_policy(&self, req: &GetIamPolicyRequest) -> Result<Policy, Error> {
         let builder = buckets::get_iam_policy::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Tests the iam permissions.
@@ -269,7 +466,7 @@ impl StorageClient {
         req: &TestIamPermissionsRequest,
     ) -> Result<TestIamPermissionsResponse, Error> {
         let builder = buckets::test_iam_permissions::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Lists the default object ACL.
@@ -293,7 +490,7 @@ impl StorageClient {
         req: &ListDefaultObjectAccessControlsRequest,
     ) -> Result<ListDefaultObjectAccessControlsResponse, Error> {
         let builder = default_object_access_controls::list::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Gets the default object ACL.
@@ -316,7 +513,7 @@ impl StorageClient {
         req: &GetDefaultObjectAccessControlRequest,
     ) -> Result<ObjectAccessControl, Error> {
         let builder = default_object_access_controls::get::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Inserts the default object ACL.
@@ -344,7 +541,7 @@ impl StorageClient {
         req: &InsertDefaultObjectAccessControlRequest,
     ) -> Result<ObjectAccessControl, Error> {
         let builder = default_object_access_controls::insert::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::NonIdempotent).await
     }
 
     /// Patches the default object ACL.
@@ -374,7 +571,7 @@ impl StorageClient {
         req: &PatchDefaultObjectAccessControlRequest,
     ) -> Result<ObjectAccessControl, Error> {
         let builder = default_object_access_controls::patch::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::NonIdempotent).await
     }
 
     /// Deletes the default object ACL.
@@ -397,7 +594,7 @@ impl StorageClient {
         req: &DeleteDefaultObjectAccessControlRequest,
     ) -> Result<(), Error> {
         let builder = default_object_access_controls::delete::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send_get_empty(builder).await
+        self.send_get_empty(builder, Idempotency::NonIdempotent).await
     }
 
     /// Lists the bucket ACL.
@@ -419,7 +616,7 @@ impl StorageClient {
         req: &ListBucketAccessControlsRequest,
     ) -> Result<ListBucketAccessControlsResponse, Error> {
         let builder = bucket_access_controls::list::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Gets the bucket ACL.
@@ -442,7 +639,7 @@ impl StorageClient {
         req: &GetBucketAccessControlRequest,
     ) -> Result<BucketAccessControl, Error> {
         let builder = bucket_access_controls::get::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Inserts the bucket ACL.
@@ -469,7 +666,7 @@ impl StorageClient {
         req: &InsertBucketAccessControlRequest,
     ) -> Result<BucketAccessControl, Error> {
         let builder = bucket_access_controls::insert::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::NonIdempotent).await
     }
 
     /// Patches the bucket ACL.
@@ -498,7 +695,7 @@ impl StorageClient {
         req: &PatchBucketAccessControlRequest,
     ) -> Result<BucketAccessControl, Error> {
         let builder = bucket_access_controls::patch::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::NonIdempotent).await
     }
 
     /// Deletes the bucket ACL.
@@ -517,7 +714,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn delete_bucket_access_control(&self, req: &DeleteBucketAccessControlRequest) -> Result<(), Error> {
         let builder = bucket_access_controls::delete::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send_get_empty(builder).await
+        self.send_get_empty(builder, Idempotency::NonIdempotent).await
     }
 
     /// Lists the object ACL.
@@ -541,7 +738,7 @@ impl StorageClient {
         req: &ListObjectAccessControlsRequest,
     ) -> Result<ListBucketAccessControlsResponse, Error> {
         let builder = object_access_controls::list::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Gets the object ACL.
@@ -568,7 +765,7 @@ impl StorageClient {
         req: &GetObjectAccessControlRequest,
     ) -> Result<ObjectAccessControl, Error> {
         let builder = object_access_controls::get::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Inserts the object ACL.
@@ -599,7 +796,7 @@ impl StorageClient {
         req: &InsertObjectAccessControlRequest,
     ) -> Result<ObjectAccessControl, Error> {
         let builder = object_access_controls::insert::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::NonIdempotent).await
     }
 
     /// Patches the bucket ACL.
@@ -631,7 +828,7 @@ impl StorageClient {
         req: &PatchObjectAccessControlRequest,
     ) -> Result<ObjectAccessControl, Error> {
         let builder = object_access_controls::patch::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::NonIdempotent).await
     }
 
     /// Deletes the bucket ACL.
@@ -654,7 +851,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn delete_object_access_control(&self, req: &DeleteObjectAccessControlRequest) -> Result<(), Error> {
         let builder = object_access_controls::delete::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send_get_empty(builder).await
+        self.send_get_empty(builder, Idempotency::NonIdempotent).await
     }
 
     /// Lists the notification.
@@ -674,7 +871,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn list_notifications(&self, req: &ListNotificationsRequest) -> Result<ListNotificationsResponse, Error> {
         let builder = notifications::list::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Gets the notification.
@@ -696,7 +893,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn get_notification(&self, req: &GetNotificationRequest) -> Result<Notification, Error> {
         let builder = notifications::get::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Inserts the notification.
@@ -723,7 +920,48 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn insert_notification(&self, req: &InsertNotificationRequest) -> Result<Notification, Error> {
         let builder = notifications::insert::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::NonIdempotent).await
+    }
+
+    /// Waits until the inserted notification appears in `list_notifications`.
+    /// The notification may not be listed for a while after it is inserted because the list is eventually consistent.
+    /// Returns `Error::NotificationNotListed` if it is still not listed after about 30 seconds.
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::notifications::insert::{InsertNotificationRequest, NotificationCreationConfig};
+    /// use google_cloud_storage::http::Error;
+    ///
+    /// async fn run(client:Client) -> Result<(), Error> {
+    ///     let notification = client.insert_notification(&InsertNotificationRequest {
+    ///         bucket: "bucket".to_string(),
+    ///         notification: NotificationCreationConfig {
+    ///             topic: "projects/project/topics/topic".to_string(),
+    ///             ..Default::default()
+    ///         }
+    ///     }).await?;
+    ///     client.wait_for_notification("bucket", &notification.id).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn wait_for_notification(&self, bucket: &str, id: &str) -> Result<Notification, Error> {
+        let req = ListNotificationsRequest {
+            bucket: bucket.to_string(),
+        };
+        for attempt in 0..NOTIFICATION_LIST_ATTEMPTS {
+            if attempt > 0 {
+                tokio::time::sleep(retry_delay(attempt - 1)).await;
+            }
+            let listed = self.list_notifications(&req).await?.items.unwrap_or_default();
+            if let Some(notification) = listed.into_iter().find(|v| v.id == id) {
+                return Ok(notification);
+            }
+        }
+        Err(Error::NotificationNotListed {
+            bucket: bucket.to_string(),
+            id: id.to_string(),
+        })
     }
 
     /// Deletes the notification.
@@ -745,7 +983,27 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn delete_notification(&self, req: &DeleteNotificationRequest) -> Result<(), Error> {
         let builder = notifications::delete::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send_get_empty(builder).await
+        self.send_get_empty(builder, Idempotency::Idempotent).await
+    }
+
+    /// Gets the Google Cloud Storage service agent of the project.
+    /// The service agent must be granted `roles/pubsub.publisher` on the topic before inserting the notification.
+    /// https://cloud.google.com/storage/docs/json_api/v1/projects/serviceAccount/get
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::service_account::get::GetServiceAccountRequest;
+    ///
+    /// async fn run(client:Client) {
+    ///     let result = client.get_service_account(&GetServiceAccountRequest {
+    ///         project_id: "project_id".to_string(),
+    ///     }).await;
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn get_service_account(&self, req: &GetServiceAccountRequest) -> Result<ServiceAccount, Error> {
+        let builder = service_account::get::build(self.v1_endpoint.as_str(), &self.http, req);
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Lists the hmac keys.
@@ -767,7 +1025,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn list_hmac_keys(&self, req: &ListHmacKeysRequest) -> Result<ListHmacKeysResponse, Error> {
         let builder = hmac_keys::list::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Gets the hmac keys.
@@ -789,7 +1047,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn get_hmac_key(&self, req: &GetHmacKeyRequest) -> Result<HmacKeyMetadata, Error> {
         let builder = hmac_keys::get::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Creates the hmac key.
@@ -811,7 +1069,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn create_hmac_key(&self, req: &CreateHmacKeyRequest) -> Result<CreateHmacKeyResponse, Error> {
         let builder = hmac_keys::create::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::NonIdempotent).await
     }
 
     /// Updates the hmac key.
@@ -819,7 +1077,7 @@ impl StorageClient {
     ///
     /// ```
     /// use google_cloud_storage::client::Client;
-    /// use google_cloud_storage::http::hmac_keys::HmacKeyMetadata;
+    /// use google_cloud_storage::http::hmac_keys::{HmacKeyMetadata, HmacKeyState};
     /// use google_cloud_storage::http::hmac_keys::update::UpdateHmacKeyRequest;
     ///
     ///
@@ -829,7 +1087,7 @@ impl StorageClient {
     ///         access_id: "access_id".to_string(),
     ///         project_id: "project_id".to_string(),
     ///         metadata: HmacKeyMetadata {
-    ///             state: "INACTIVE".to_string(),
+    ///             state: HmacKeyState::Inactive,
     ///             ..Default::default()
     ///         },
     ///     }).await;
@@ -838,10 +1096,13 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn update_hmac_key(&self, req: &UpdateHmacKeyRequest) -> Result<HmacKeyMetadata, Error> {
         let builder = hmac_keys::update::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Conditional(!req.metadata.etag.is_empty()))
+            .await
     }
 
     /// Deletes the hmac key.
+    /// Only the inactive key can be deleted, so the key must be updated to `HmacKeyState::Inactive` first.
+    /// The error is `Error::HmacKeyNotInactive` if the key is not inactive.
     /// https://cloud.google.com/storage/docs/json_api/v1/projects/hmacKeys/delete
     ///
     /// ```
@@ -860,7 +1121,25 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn delete_hmac_key(&self, req: &DeleteHmacKeyRequest) -> Result<(), Error> {
         let builder = hmac_keys::delete::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send_get_empty(builder).await
+        match self.send_get_empty(builder, Idempotency::Idempotent).await {
+            Err(Error::Response(e)) if e.code == 400 => {
+                // The state is fetched to tell the failed precondition from the other invalid requests.
+                let key = self
+                    .get_hmac_key(&GetHmacKeyRequest {
+                        access_id: req.access_id.to_string(),
+                        project_id: req.project_id.to_string(),
+                    })
+                    .await;
+                match key {
+                    Ok(key) if key.state == HmacKeyState::Active => Err(Error::HmacKeyNotInactive {
+                        access_id: key.access_id,
+                        state: key.state,
+                    }),
+                    _ => Err(Error::Response(e)),
+                }
+            }
+            result => result,
+        }
     }
 
     /// Lists the objects.
@@ -882,7 +1161,78 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn list_objects(&self, req: &ListObjectsRequest) -> Result<ListObjectsResponse, Error> {
         let builder = objects::list::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
+    }
+
+    /// Lists the objects lazily page by page.
+    /// The prefixes are listed before the objects of each page when the delimiter is set.
+    /// Each page is retried on the transient failures by the retry config, or by the one of the client if it is `None`.
+    ///
+    /// ```
+    /// use futures_util::StreamExt;
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::objects::list::{ListObjectsItem, ListObjectsRequest};
+    ///
+    /// async fn run(client:Client) {
+    ///     let stream = client.list_objects_stream(&ListObjectsRequest{
+    ///         bucket: "bucket".to_string(),
+    ///         prefix: Some("logs/".to_string()),
+    ///         delimiter: Some("/".to_string()),
+    ///         fields: Some("items(name,size),prefixes".to_string()),
+    ///         ..Default::default()
+    ///     }, None);
+    ///     futures_util::pin_mut!(stream);
+    ///     while let Some(item) = stream.next().await {
+    ///         match item.unwrap() {
+    ///             ListObjectsItem::Object(o) => println!("object {} {}", o.name, o.size),
+    ///             ListObjectsItem::Prefix(p) => println!("folder {p}"),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn list_objects_stream(
+        &self,
+        req: &ListObjectsRequest,
+        retry: Option<RetryConfig>,
+    ) -> impl Stream<Item = Result<ListObjectsItem, Error>> + Send + 'static {
+        let retry = retry.unwrap_or_else(|| self.retry.clone());
+        let max_retries = if retry.policy.is_retryable(Idempotency::Idempotent) {
+            retry.max_retries
+        } else {
+            0
+        };
+        // The pages are retried by the stream including the failures while reading the response.
+        let this = self.with_retry(RetryConfig {
+            policy: RetryPolicy::Never,
+            ..retry
+        });
+        let mut req = req.clone();
+        req.fields = req.fields_with_page_token();
+        async_stream::try_stream! {
+            let mut retries = 0;
+            loop {
+                let response = match this.list_objects(&req).await {
+                    Err(e) if retries < max_retries && is_transient(&e) => {
+                        tracing::debug!("retry the listing of {}: {e}", req.bucket);
+                        tokio::time::sleep(retry_delay(retries)).await;
+                        retries += 1;
+                        continue;
+                    }
+                    result => result?,
+                };
+                retries = 0;
+                for prefix in response.prefixes.unwrap_or_default() {
+                    yield ListObjectsItem::Prefix(prefix);
+                }
+                for item in response.items.unwrap_or_default() {
+                    yield ListObjectsItem::Object(Box::new(item));
+                }
+                match response.next_page_token {
+                    Some(token) if !token.is_empty() => req.page_token = Some(token),
+                    _ => break,
+                }
+            }
+        }
     }
 
     /// Gets the object.
@@ -903,7 +1253,7 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn get_object(&self, req: &GetObjectRequest) -> Result<Object, Error> {
         let builder = objects::get::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Idempotent).await
     }
 
     /// Copy the object.
@@ -927,12 +1277,15 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn copy_object(&self, req: &CopyObjectRequest) -> Result<Object, Error> {
         let builder = objects::copy::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Conditional(req.if_generation_match.is_some()))
+            .await
     }
 
     /// Download the object.
     /// https://cloud.google.com/storage/docs/json_api/v1/objects/get
     /// alt is always media
+    /// The checksums of `ClientConfig::checksum` are verified when the whole object is downloaded.
+    /// The gzip encoded object is decompressed, use `download_object_stream` to download the stored bytes.
     ///
     /// ```
     /// use google_cloud_storage::client::Client;
@@ -951,11 +1304,17 @@ impl StorageClient {
     /// ```
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn download_object(&self, req: &GetObjectRequest, range: &Range) -> Result<Vec<u8>, Error> {
-        let builder = objects::download::build(self.v1_endpoint.as_str(), &self.http, req, range);
-        let request = self.with_headers(builder).await?;
-        let response = request.send().await?;
-        let response = check_response_status(response).await?;
-        Ok(response.bytes().await?.to_vec())
+        let builder = objects::download::build(self.v1_endpoint.as_str(), &self.http, req, range, true);
+        let response = self.execute(builder, Idempotency::Idempotent).await?;
+        let expected = match range {
+            Range(None, None) => Checksums::from_headers(response.headers()),
+            _ => None,
+        };
+        let data = response.bytes().await?;
+        if let (Some(expected), Some(actual)) = (expected, checksums(&self.checksum, &data)) {
+            actual.verify(&expected)?;
+        }
+        Ok(data.to_vec())
     }
 
     /// Download the object.
@@ -985,11 +1344,160 @@ impl StorageClient {
         req: &GetObjectRequest,
         range: &Range,
     ) -> Result<impl Stream<Item = Result<bytes::Bytes, Error>>, Error> {
-        let builder = objects::download::build(self.v1_endpoint.as_str(), &self.http, req, range);
+        let builder = objects::download::build(self.v1_endpoint.as_str(), &self.http, req, range, true);
+        let response = self.execute(builder, Idempotency::Idempotent).await?;
+        Ok(response.bytes_stream().map_err(Error::from))
+    }
+
+    /// Downloads the object as a stream without buffering it.
+    /// https://cloud.google.com/storage/docs/json_api/v1/objects/get
+    ///
+    /// The download is resumed from the last received byte on the transient failures.
+    /// The resumed requests are conditional on the generation of the first response,
+    /// so the data of different generations are never mixed.
+    /// The checksums of `DownloadConfig::checksum` are verified at the end when the whole object is downloaded.
+    /// The gzip encoded object is decompressed by default, and the decompressed data can't be verified or resumed.
+    /// Set `DownloadConfig::decompressive_transcoding` to false to download the stored bytes.
+    ///
+    /// ```
+    /// use futures_util::StreamExt;
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::objects::get::GetObjectRequest;
+    /// use google_cloud_storage::http::objects::download::DownloadConfig;
+    ///
+    /// async fn run(client:Client) {
+    ///     let mut stream = client.download_object_stream(&GetObjectRequest{
+    ///         bucket: "bucket".to_string(),
+    ///         object: "object".to_string(),
+    ///         ..Default::default()
+    ///     }, &DownloadConfig {
+    ///         offset: 1024,
+    ///         length: Some(4096),
+    ///         ..Default::default()
+    ///     }).await.unwrap();
+    ///     while let Some(v) = stream.next().await {
+    ///         let d: bytes::Bytes = v.unwrap();
+    ///     }
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn download_object_stream(
+        &self,
+        req: &GetObjectRequest,
+        config: &DownloadConfig,
+    ) -> Result<impl Stream<Item = Result<bytes::Bytes, Error>> + Send + Unpin + 'static, Error> {
+        let config = config.clone();
+        let mut req = req.clone();
+        let response = if config.length == Some(0) {
+            None
+        } else {
+            Some(self.open_download(&req, &config.range(config.offset), &config).await?)
+        };
+        // The server ignores the range of the decompressed download, so it can't be resumed either.
+        let transcoded = response
+            .as_ref()
+            .is_some_and(|v| objects::download::is_transcoded(v.headers()));
+        if transcoded && config.is_partial() {
+            return Err(Error::TranscodedRange);
+        }
+        let expected = response
+            .as_ref()
+            .filter(|_| config.verifies_checksum())
+            .and_then(|v| Checksums::from_headers(v.headers()));
+        if req.generation.is_none() && req.if_generation_match.is_none() {
+            req.if_generation_match = response.as_ref().and_then(objects::download::generation);
+        }
+        let this = self.clone();
+        Ok(Box::pin(async_stream::try_stream! {
+            let mut body = response.map(|v| Box::pin(v.bytes_stream()));
+            let mut position = config.offset;
+            let mut hasher = expected.as_ref().map(|_| Hasher::new(&config.checksum));
+            let mut retries = 0;
+            loop {
+                let result = if let Some(stream) = body.as_mut() {
+                    match stream.next().await {
+                        Some(result) => result.map_err(Error::from),
+                        None => break,
+                    }
+                } else if config.length == Some(0) {
+                    break;
+                } else {
+                    match this.open_download(&req, &config.range(position), &config).await {
+                        Ok(response) => {
+                            body = Some(Box::pin(response.bytes_stream()));
+                            continue;
+                        }
+                        Err(e) => Err(e),
+                    }
+                };
+                match result {
+                    Ok(chunk) => {
+                        position += chunk.len() as u64;
+                        if let Some(hasher) = hasher.as_mut() {
+                            hasher.update(&chunk);
+                        }
+                        retries = 0;
+                        yield chunk;
+                    }
+                    Err(e) if !transcoded && retries < config.max_retries && is_transient_download(&e) => {
+                        tracing::debug!("resume the download from {position}: {e}");
+                        tokio::time::sleep(retry_delay(retries)).await;
+                        retries += 1;
+                        body = None;
+                    }
+                    Err(e) => Err::<(), _>(e)?,
+                }
+            }
+            if let (Some(expected), Some(hasher)) = (expected, hasher) {
+                hasher.finish().verify(&expected)?;
+            }
+        }))
+    }
+
+    /// Downloads the object as an `AsyncRead`. See `download_object_stream` for the details.
+    ///
+    /// ```
+    /// use tokio::io::AsyncReadExt;
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::objects::get::GetObjectRequest;
+    /// use google_cloud_storage::http::objects::download::DownloadConfig;
+    ///
+    /// async fn run(client:Client) {
+    ///     let mut reader = client.download_object_reader(&GetObjectRequest{
+    ///         bucket: "bucket".to_string(),
+    ///         object: "object".to_string(),
+    ///         ..Default::default()
+    ///     }, &DownloadConfig::default()).await.unwrap();
+    ///     let mut buf = vec![0; 8192];
+    ///     let n = reader.read(&mut buf).await.unwrap();
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn download_object_reader(
+        &self,
+        req: &GetObjectRequest,
+        config: &DownloadConfig,
+    ) -> Result<impl AsyncRead + Send + Unpin + 'static, Error> {
+        let stream = self.download_object_stream(req, config).await?;
+        Ok(StreamReader::new(stream.map_err(std::io::Error::other)))
+    }
+
+    async fn open_download(
+        &self,
+        req: &GetObjectRequest,
+        range: &Range,
+        config: &DownloadConfig,
+    ) -> Result<reqwest::Response, Error> {
+        let builder = objects::download::build(
+            self.v1_endpoint.as_str(),
+            &self.http,
+            req,
+            range,
+            config.decompressive_transcoding,
+        );
         let request = self.with_headers(builder).await?;
         let response = request.send().await?;
-        let response = check_response_status(response).await?;
-        Ok(response.bytes_stream().map_err(Error::from))
+        check_response_status(response).await
     }
 
     /// Uploads the object.
@@ -1032,26 +1540,45 @@ impl StorageClient {
         upload_type: &UploadType,
     ) -> Result<Object, Error> {
         match upload_type {
+            UploadType::Resumable(_, config) => {
+                config.validate()?;
+                let body = data.into();
+                let data = body.as_bytes().ok_or(ChunkError::StreamedBody)?;
+                let data = futures_util::stream::once(async { Ok::<_, Error>(bytes::Bytes::copy_from_slice(data)) });
+                let uploader = self.prepare_resumable_upload(req, upload_type).await?;
+                uploader.upload_chunks(data, 0, config, |_| {}).await
+            }
             UploadType::Multipart(meta) => {
+                let body = data.into();
+                let mut meta = meta.clone();
+                // The server validates the checksums in the metadata.
+                if let Some(computed) = body.as_bytes().and_then(|v| checksums(&req.checksum, v)) {
+                    meta.crc32c = meta.crc32c.or_else(|| computed.crc32c_base64());
+                    meta.md5_hash = meta.md5_hash.or_else(|| computed.md5_base64());
+                }
                 let builder =
-                    objects::upload::build_multipart(self.v1_upload_endpoint.as_str(), &self.http, req, meta, data)?;
-                self.send(builder).await
+                    objects::upload::build_multipart(self.v1_upload_endpoint.as_str(), &self.http, req, &meta, body)?;
+                self.send(builder, Idempotency::Conditional(req.if_generation_match.is_some()))
+                    .await
             }
             UploadType::Simple(media) => {
-                let builder = objects::upload::build(self.v1_upload_endpoint.as_str(), &self.http, req, media, data);
-                let builder = self.with_headers(builder).await?;
-                let mut request = builder.build()?;
+                let body = data.into();
+                let hash = body
+                    .as_bytes()
+                    .and_then(|v| checksums(&req.checksum, v))
+                    .and_then(|v| v.header_value());
+                let is_empty = body.as_bytes().is_some_and(|v| v.is_empty());
+                let mut builder =
+                    objects::upload::build(self.v1_upload_endpoint.as_str(), &self.http, req, media, body);
+                if let Some(hash) = hash {
+                    builder = builder.header("x-goog-hash", hash);
+                }
                 // In the case of not streamed and 0 bytes, Content-Length=0 must be explicitly specified.
-                if !request.headers().contains_key(CONTENT_LENGTH) {
-                    if let Some(Some(is_empty)) = request.body().map(|b| b.as_bytes().map(|b| b.is_empty())) {
-                        if is_empty {
-                            request
-                                .headers_mut()
-                                .insert(CONTENT_LENGTH, HeaderValue::from_static("0"));
-                        }
-                    }
+                if is_empty && media.content_length.is_none() {
+                    builder = builder.header(CONTENT_LENGTH, HeaderValue::from_static("0"));
                 }
-                self.send_request(request).await
+                self.send(builder, Idempotency::Conditional(req.if_generation_match.is_some()))
+                    .await
             }
         }
     }
@@ -1060,7 +1587,7 @@ impl StorageClient {
     ///
     /// Assumes URL is correct, if not, `ResumableUploadClient` is not guaranteed to perform correctly.
     pub fn get_resumable_upload(&self, url: String) -> ResumableUploadClient {
-        ResumableUploadClient::new(url, self.http.clone())
+        ResumableUploadClient::new(url, self.http.clone()).with_user_project(self.user_project.clone())
     }
 
     /// Perform resumable uploads
@@ -1071,7 +1598,7 @@ impl StorageClient {
     /// use google_cloud_storage::client::Client;
     /// use google_cloud_storage::http::objects::Object;
     /// use google_cloud_storage::http::objects::upload::{Media, UploadObjectRequest, UploadType};
-    /// use google_cloud_storage::http::resumable_upload_client::{ChunkSize, UploadStatus};
+    /// use google_cloud_storage::http::resumable_upload_client::{ChunkConfig, ChunkSize, SessionStatus, UploadStatus};
     ///
     /// async fn run_simple(client:Client) {
     ///     let upload_type = UploadType::Simple(Media::new("filename"));
@@ -1114,6 +1641,35 @@ impl StorageClient {
     ///     let status2 = uploader.upload_multiple_chunk(chunk2_data.clone(), &chunk2).await.unwrap();
     ///     assert!(matches!(status2, UploadStatus::Ok(_)));
     /// }
+    ///
+    /// async fn run_chunked(client:Client, data: Vec<bytes::Bytes>) {
+    ///     let config = ChunkConfig {
+    ///         chunk_size: 32 * 1024 * 1024,
+    ///         ..Default::default()
+    ///     };
+    ///     let upload_type = UploadType::Resumable(Box::new(Object {
+    ///         name: "export.dump".to_string(),
+    ///         ..Default::default()
+    ///     }), config.clone());
+    ///     let uploader = client.prepare_resumable_upload(&UploadObjectRequest{
+    ///         bucket: "bucket".to_string(),
+    ///         ..Default::default()
+    ///     }, &upload_type).await.unwrap();
+    ///
+    ///     // Save the session URL to resume the upload in another process.
+    ///     let session_url = uploader.url().to_string();
+    ///     let uploader = client.get_resumable_upload(session_url);
+    ///     let offset = match uploader.session_status(None).await.unwrap() {
+    ///         SessionStatus::Completed(_) => return,
+    ///         SessionStatus::Incomplete { persisted_size } => persisted_size,
+    ///     };
+    ///
+    ///     // The data must start at the offset.
+    ///     let stream = futures_util::stream::iter(data.into_iter().map(Ok::<_, std::io::Error>));
+    ///     let object = uploader.upload_chunks(stream, offset, &config, |progress| {
+    ///         println!("uploaded {} bytes", progress.persisted_size);
+    ///     }).await.unwrap();
+    /// }
     /// ```
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn prepare_resumable_upload(
@@ -1122,12 +1678,14 @@ impl StorageClient {
         upload_type: &UploadType,
     ) -> Result<ResumableUploadClient, Error> {
         let request = match upload_type {
-            UploadType::Multipart(meta) => objects::upload::build_resumable_session_metadata(
-                self.v1_upload_endpoint.as_str(),
-                &self.http,
-                req,
-                meta,
-            ),
+            UploadType::Multipart(meta) | UploadType::Resumable(meta, _) => {
+                objects::upload::build_resumable_session_metadata(
+                    self.v1_upload_endpoint.as_str(),
+                    &self.http,
+                    req,
+                    meta,
+                )
+            }
             UploadType::Simple(media) => objects::upload::build_resumable_session_simple(
                 self.v1_upload_endpoint.as_str(),
                 &self.http,
@@ -1135,9 +1693,10 @@ impl StorageClient {
                 media,
             ),
         };
-        self.send_get_url(request)
+        // The session doesn't change the object until the data is uploaded.
+        self.send_get_url(request, Idempotency::Idempotent)
             .await
-            .map(|url| ResumableUploadClient::new(url, self.http.clone()))
+            .map(|url| self.get_resumable_upload(url))
     }
 
     /// Uploads the streamed object.
@@ -1173,37 +1732,351 @@ impl StorageClient {
         S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
         bytes::Bytes: From<S::Ok>,
     {
-        //TODO resumable upload
-        self.upload_object(req, Body::wrap_stream(data), upload_type).await
+        match upload_type {
+            UploadType::Resumable(_, config) => {
+                config.validate()?;
+                let uploader = self.prepare_resumable_upload(req, upload_type).await?;
+                uploader.upload_chunks(data, 0, config, |_| {}).await
+            }
+            _ if !req.checksum.is_enabled() => self.upload_object(req, Body::wrap_stream(data), upload_type).await,
+            _ => {
+                // The checksums of the streamed data are known only after the upload.
+                let hasher = Arc::new(Mutex::new(Hasher::new(&req.checksum)));
+                let data = {
+                    let hasher = hasher.clone();
+                    data.into_stream().map_ok(move |v| {
+                        let v = bytes::Bytes::from(v);
+                        hasher.lock().unwrap().update(&v);
+                        v
+                    })
+                };
+                let object = self.upload_object(req, bytes_body(data), upload_type).await?;
+                let actual = hasher.lock().unwrap().clone().finish();
+                if let Err(e) = actual.verify(&Checksums::from_object(&object)) {
+                    self.delete_corrupted_object(&object).await;
+                    return Err(e);
+                }
+                Ok(object)
+            }
+        }
     }
 
-    /// Patches the object.
-    /// https://cloud.google.com/storage/docs/json_api/v1/objects/patch
+    /// Uploads the object by the parallel composite upload.
+    /// The data is split into the parts by `ParallelUploadConfig`, and the parts are uploaded concurrently as the
+    /// temporary objects and composed into the object with the metadata. The temporary objects are deleted after the
+    /// composition, and also on the failure as much as possible.
+    /// The parts are composed on the generations uploaded, and the CRC32C of the composite object is verified with
+    /// the one combined from the parts when `UploadObjectRequest::checksum` has crc32c.
+    /// The composite object has no MD5 hash.
+    /// https://cloud.google.com/storage/docs/parallel-composite-uploads
     ///
     /// ```
     /// use google_cloud_storage::client::Client;
-    /// use google_cloud_storage::http::objects::patch::PatchObjectRequest;
-    ///
+    /// use google_cloud_storage::http::objects::Object;
+    /// use google_cloud_storage::http::objects::parallel_upload::ParallelUploadConfig;
+    /// use google_cloud_storage::http::objects::upload::UploadObjectRequest;
     ///
     /// async fn run(client:Client) {
-    ///
-    ///     let result = client.patch_object(&PatchObjectRequest{
+    ///     let data = vec![0u8; 64 * 1024 * 1024];
+    ///     let size = data.len() as u64;
+    ///     let result = client.upload_parallel(&UploadObjectRequest {
     ///         bucket: "bucket".to_string(),
-    ///         object: "object".to_string(),
     ///         ..Default::default()
+    ///     }, &Object {
+    ///         name: "object".to_string(),
+    ///         ..Default::default()
+    ///     }, data.as_slice(), size, &ParallelUploadConfig::default(), |progress| {
+    ///         println!("{}/{}", progress.uploaded_size, progress.total_object_size);
     ///     }).await;
     /// }
     /// ```
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
-    pub async fn patch_object(&self, req: &PatchObjectRequest) -> Result<Object, Error> {
-        let builder = objects::patch::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
-    }
-
-    /// Deletes the object.
-    /// https://cloud.google.com/storage/docs/json_api/v1/objects/delete
-    ///
-    /// ```
+    pub async fn upload_parallel<R>(
+        &self,
+        req: &UploadObjectRequest,
+        metadata: &Object,
+        reader: R,
+        size: u64,
+        config: &ParallelUploadConfig,
+        progress: impl FnMut(ParallelUploadProgress),
+    ) -> Result<Object, Error>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let prefix = config.temporary_prefix(&metadata.name);
+        let mut temporaries = vec![];
+        let result = self
+            .upload_parallel_parts(req, metadata, reader, size, config, progress, &prefix, &mut temporaries)
+            .await;
+        for name in temporaries {
+            let delete = DeleteObjectRequest {
+                bucket: req.bucket.to_string(),
+                object: name,
+                ..Default::default()
+            };
+            if let Err(e) = self.delete_object(&delete).await {
+                tracing::warn!("failed to delete the temporary object {}: {e}", delete.object);
+            }
+        }
+        result
+    }
+
+    /// Uploads the file by the parallel composite upload. See `upload_parallel` for the details.
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::objects::Object;
+    /// use google_cloud_storage::http::objects::parallel_upload::ParallelUploadConfig;
+    /// use google_cloud_storage::http::objects::upload::UploadObjectRequest;
+    ///
+    /// async fn run(client:Client) {
+    ///     let result = client.upload_parallel_from_path(&UploadObjectRequest {
+    ///         bucket: "bucket".to_string(),
+    ///         ..Default::default()
+    ///     }, &Object {
+    ///         name: "object".to_string(),
+    ///         ..Default::default()
+    ///     }, "/tmp/large_file", &ParallelUploadConfig::default(), |_| {}).await;
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn upload_parallel_from_path(
+        &self,
+        req: &UploadObjectRequest,
+        metadata: &Object,
+        path: impl AsRef<Path>,
+        config: &ParallelUploadConfig,
+        progress: impl FnMut(ParallelUploadProgress),
+    ) -> Result<Object, Error> {
+        let file = tokio::fs::File::open(path)
+            .await
+            .map_err(|e| ChunkError::Read(e.into()))?;
+        let size = file.metadata().await.map_err(|e| ChunkError::Read(e.into()))?.len();
+        self.upload_parallel(req, metadata, file, size, config, progress).await
+    }
+
+    /// upload_parallel_parts uploads and composes the parts, and records the names of the temporary objects.
+    #[allow(clippy::too_many_arguments)]
+    async fn upload_parallel_parts<R>(
+        &self,
+        req: &UploadObjectRequest,
+        metadata: &Object,
+        mut reader: R,
+        size: u64,
+        config: &ParallelUploadConfig,
+        mut progress: impl FnMut(ParallelUploadProgress),
+        prefix: &str,
+        temporaries: &mut Vec<String>,
+    ) -> Result<Object, Error>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let ranges = config.part_ranges(size);
+        let total_parts = ranges.len();
+        // The parts are uploaded to the names never used, so they are always safe to retry.
+        temporaries.extend((0..total_parts).map(|i| parallel_upload::part_name(prefix, i)));
+        let part_req = UploadObjectRequest {
+            bucket: req.bucket.to_string(),
+            kms_key_name: req.kms_key_name.clone(),
+            encryption: req.encryption.clone(),
+            checksum: req.checksum,
+            ..Default::default()
+        };
+        let this = self.with_retry(RetryConfig {
+            policy: RetryPolicy::Never,
+            ..self.retry.clone()
+        });
+        let parts = async_stream::try_stream! {
+            for (index, range) in ranges.into_iter().enumerate() {
+                let mut data = vec![0; (range.end - range.start) as usize];
+                reader.read_exact(&mut data).await.map_err(|e| ChunkError::Read(e.into()))?;
+                yield (index, bytes::Bytes::from(data));
+            }
+        };
+        let mut parts = Box::pin(
+            parts
+                .map_ok(|(index, data)| {
+                    let mut media = Media::new(parallel_upload::part_name(prefix, index));
+                    media.content_length = Some(data.len() as u64);
+                    let (this, part_req) = (&this, &part_req);
+                    async move {
+                        let upload_type = UploadType::Simple(media);
+                        let mut retries = 0;
+                        loop {
+                            match this.upload_object(part_req, data.clone(), &upload_type).await {
+                                Err(e) if retries < config.max_retries && is_transient(&e) => {
+                                    tracing::debug!("retry the part {index}: {e}");
+                                    tokio::time::sleep(retry_delay(retries)).await;
+                                    retries += 1;
+                                }
+                                result => return result.map(|v| (index, v)),
+                            }
+                        }
+                    }
+                })
+                .try_buffer_unordered(config.concurrency.max(1)),
+        );
+        let mut uploaded = vec![None; total_parts];
+        let mut uploaded_size = 0;
+        let mut uploaded_parts = 0;
+        while let Some((index, part)) = parts.try_next().await? {
+            uploaded_size += part.size as u64;
+            uploaded_parts += 1;
+            uploaded[index] = Some(part);
+            progress(ParallelUploadProgress {
+                uploaded_size,
+                uploaded_parts,
+                total_object_size: size,
+                total_parts,
+            });
+        }
+        let mut sources: Vec<Object> = uploaded.into_iter().flatten().collect();
+        let expected = req.checksum.crc32c.then(|| {
+            sources.iter().fold(0, |crc, part| {
+                let part_crc = Checksums::from_object(part).crc32c.unwrap_or_default();
+                crc32c_combine(crc, part_crc, part.size as u64)
+            })
+        });
+
+        // Compose the parts in cascade because a composition has at most MAX_COMPOSE_SOURCES sources.
+        let mut level = 0;
+        while sources.len() > MAX_COMPOSE_SOURCES {
+            level += 1;
+            let mut composed = vec![];
+            for (index, group) in sources.chunks(MAX_COMPOSE_SOURCES).enumerate() {
+                let name = parallel_upload::compose_name(prefix, level, index);
+                temporaries.push(name.to_string());
+                let compose = ComposeObjectRequest {
+                    bucket: req.bucket.to_string(),
+                    destination_object: name,
+                    composing_targets: composing_targets(group, None),
+                    if_generation_match: Some(0),
+                    kms_key_name: req.kms_key_name.clone(),
+                    encryption: req.encryption.clone(),
+                    ..Default::default()
+                };
+                composed.push(self.compose_object(&compose).await?);
+            }
+            sources = composed;
+        }
+        let compose = ComposeObjectRequest {
+            bucket: req.bucket.to_string(),
+            destination_object: metadata.name.to_string(),
+            destination_predefined_acl: req.predefined_acl,
+            composing_targets: composing_targets(&sources, Some(metadata.clone())),
+            if_generation_match: req.if_generation_match,
+            if_metageneration_match: req.if_metageneration_match,
+            kms_key_name: req.kms_key_name.clone(),
+            encryption: req.encryption.clone(),
+        };
+        let object = self.compose_object(&compose).await?;
+        if let Some(expected) = expected {
+            let actual = Checksums::from_object(&object).crc32c.unwrap_or_default();
+            if actual != expected {
+                self.delete_corrupted_object(&object).await;
+                return Err(Error::Crc32cMismatch { expected, actual });
+            }
+        }
+        Ok(object)
+    }
+
+    /// delete_corrupted_object deletes the object failed to be verified if it is not changed.
+    async fn delete_corrupted_object(&self, object: &Object) {
+        let delete = DeleteObjectRequest {
+            bucket: object.bucket.to_string(),
+            object: object.name.to_string(),
+            if_generation_match: Some(object.generation),
+            ..Default::default()
+        };
+        if let Err(e) = self.delete_object(&delete).await {
+            tracing::warn!("failed to delete the corrupted object {}: {e}", object.name);
+        }
+    }
+
+    /// Patches the object.
+    /// https://cloud.google.com/storage/docs/json_api/v1/objects/patch
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::objects::patch::PatchObjectRequest;
+    ///
+    ///
+    /// async fn run(client:Client) {
+    ///
+    ///     let result = client.patch_object(&PatchObjectRequest{
+    ///         bucket: "bucket".to_string(),
+    ///         object: "object".to_string(),
+    ///         ..Default::default()
+    ///     }).await;
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn patch_object(&self, req: &PatchObjectRequest) -> Result<Object, Error> {
+        let builder = objects::patch::build(self.v1_endpoint.as_str(), &self.http, req);
+        self.send(builder, Idempotency::Conditional(req.if_metageneration_match.is_some()))
+            .await
+    }
+
+    /// Places or releases the hold on the object. Only the hold is changed.
+    /// https://cloud.google.com/storage/docs/object-holds
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::objects::patch::ObjectHold;
+    ///
+    /// async fn run(client:Client) {
+    ///     let result = client.set_object_hold("bucket", "object", ObjectHold::EventBased, true).await;
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn set_object_hold(
+        &self,
+        bucket: &str,
+        object: &str,
+        hold: ObjectHold,
+        enabled: bool,
+    ) -> Result<Object, Error> {
+        let req = PatchObjectRequest {
+            bucket: bucket.to_string(),
+            object: object.to_string(),
+            metadata: Some(hold.metadata(enabled)),
+            ..Default::default()
+        };
+        let builder = objects::patch::build(self.v1_endpoint.as_str(), &self.http, &req);
+        // Setting the hold to the same value again has no effect.
+        self.send(builder, Idempotency::Idempotent).await
+    }
+
+    /// Restores the soft-deleted object as the live object.
+    /// The soft-deleted objects and their generations are listed with `ListObjectsRequest::soft_deleted`.
+    /// https://cloud.google.com/storage/docs/json_api/v1/objects/restore
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::objects::restore::RestoreObjectRequest;
+    ///
+    /// async fn run(client:Client) {
+    ///     let result = client.restore_object(&RestoreObjectRequest {
+    ///         bucket: "bucket".to_string(),
+    ///         object: "object".to_string(),
+    ///         generation: 1,
+    ///         copy_source_acl: Some(true),
+    ///         if_generation_match: Some(0),
+    ///         ..Default::default()
+    ///     }).await;
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn restore_object(&self, req: &RestoreObjectRequest) -> Result<Object, Error> {
+        let builder = objects::restore::build(self.v1_endpoint.as_str(), &self.http, req);
+        self.send(builder, Idempotency::Conditional(req.if_generation_match.is_some()))
+            .await
+    }
+
+    /// Deletes the object.
+    /// https://cloud.google.com/storage/docs/json_api/v1/objects/delete
+    ///
+    /// ```
     /// use google_cloud_storage::client::Client;
     /// use google_cloud_storage::http::objects::delete::DeleteObjectRequest;
     ///
@@ -1220,7 +2093,11 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn delete_object(&self, req: &DeleteObjectRequest) -> Result<(), Error> {
         let builder = objects::delete::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send_get_empty(builder).await
+        self.send_get_empty(
+            builder,
+            Idempotency::Conditional(req.if_generation_match.is_some() || req.generation.is_some()),
+        )
+        .await
     }
 
     /// Rewrites the object.
@@ -1253,17 +2130,100 @@ impl StorageClient {
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn rewrite_object(&self, req: &RewriteObjectRequest) -> Result<RewriteObjectResponse, Error> {
         let builder = objects::rewrite::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Conditional(req.if_generation_match.is_some()))
+            .await
+    }
+
+    /// Rewrites the object looping the rewrite token until the rewrite is done.
+    /// The progress is notified every time a rewrite request returns.
+    /// https://cloud.google.com/storage/docs/json_api/v1/objects/rewrite
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    /// use google_cloud_storage::http::objects::rewrite::RewriteObjectRequest;
+    ///
+    /// async fn run(client:Client) {
+    ///     let result = client.rewrite_object_until_done(&RewriteObjectRequest{
+    ///         source_bucket: "bucket1".to_string(),
+    ///         source_object: "object".to_string(),
+    ///         destination_bucket: "bucket2".to_string(),
+    ///         destination_object: "object1".to_string(),
+    ///         destination_kms_key_name: Some("projects/p/locations/l/keyRings/r/cryptoKeys/k".to_string()),
+    ///         if_generation_match: Some(0),
+    ///         ..Default::default()
+    ///     }, |progress| println!("{}/{}", progress.total_bytes_rewritten, progress.object_size)).await;
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn rewrite_object_until_done(
+        &self,
+        req: &RewriteObjectRequest,
+        mut progress: impl FnMut(RewriteProgress),
+    ) -> Result<Object, Error> {
+        let mut req = req.clone();
+        loop {
+            let response = self.rewrite_object(&req).await?;
+            progress(RewriteProgress::from(&response));
+            if response.done {
+                return match response.resource {
+                    Some(resource) => Ok(resource),
+                    None => {
+                        self.get_object(&GetObjectRequest {
+                            bucket: req.destination_bucket.to_string(),
+                            object: req.destination_object.to_string(),
+                            encryption: req.destination_encryption.clone(),
+                            ..Default::default()
+                        })
+                        .await
+                    }
+                };
+            }
+            req.rewrite_token = response.rewrite_token;
+        }
+    }
+
+    /// Copies the object with the rewrite requests, so the object can be copied across the locations
+    /// and the storage classes unlike `copy_object`.
+    /// Setting if_generation_match to 0 makes the copy succeed only if there is no live destination object.
+    ///
+    /// ```
+    /// use google_cloud_storage::client::Client;
+    ///
+    /// async fn run(client:Client) {
+    ///     let result = client.copy_object_with_rewrite("bucket1", "object", "bucket2", "object1", Some(0)).await;
+    /// }
+    /// ```
+    #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
+    pub async fn copy_object_with_rewrite(
+        &self,
+        source_bucket: &str,
+        source_object: &str,
+        destination_bucket: &str,
+        destination_object: &str,
+        if_generation_match: Option<i64>,
+    ) -> Result<Object, Error> {
+        let req = RewriteObjectRequest {
+            source_bucket: source_bucket.to_string(),
+            source_object: source_object.to_string(),
+            destination_bucket: destination_bucket.to_string(),
+            destination_object: destination_object.to_string(),
+            if_generation_match,
+            ..Default::default()
+        };
+        self.rewrite_object_until_done(&req, |_| {}).await
     }
 
     /// Composes the object.
+    /// Up to `MAX_COMPOSE_SOURCES` source objects in the same bucket are concatenated into the destination object.
+    /// More source objects are rejected with `Error::TooManyComposeSources` without sending the request.
+    /// Each source object can be guarded with its generation by `ObjectPreconditions`.
     /// https://cloud.google.com/storage/docs/json_api/v1/objects/compose
     ///
     /// ```
     /// use google_cloud_storage::client::Client;
     /// use google_cloud_storage::http::objects::compose::{ComposeObjectRequest, ComposingTargets};
     /// use google_cloud_storage::http::objects::rewrite::RewriteObjectRequest;
-    /// use google_cloud_storage::http::objects::SourceObjects;
+    /// use google_cloud_storage::http::objects::{ObjectPreconditions, SourceObjects};
     ///
     /// async fn run(client:Client) {
     ///     let result = client.compose_object(&ComposeObjectRequest{
@@ -1272,24 +2232,38 @@ impl StorageClient {
     ///         composing_targets: ComposingTargets {
     ///             source_objects: vec![SourceObjects {
     ///                 name: "src".to_string(),
+    ///                 object_preconditions: Some(ObjectPreconditions {
+    ///                     if_generation_match: Some(1),
+    ///                 }),
     ///                 ..Default::default()
     ///             }],
     ///             ..Default::default()
     ///         },
+    ///         if_generation_match: Some(0),
     ///         ..Default::default()
     ///     }).await;
     /// }
     /// ```
     #[cfg_attr(feature = "trace", tracing::instrument(skip_all))]
     pub async fn compose_object(&self, req: &ComposeObjectRequest) -> Result<Object, Error> {
+        let sources = req.composing_targets.source_objects.len();
+        if sources > MAX_COMPOSE_SOURCES {
+            return Err(Error::TooManyComposeSources(sources));
+        }
         let builder = objects::compose::build(self.v1_endpoint.as_str(), &self.http, req);
-        self.send(builder).await
+        self.send(builder, Idempotency::Conditional(req.if_generation_match.is_some()))
+            .await
     }
 
+    /// with_headers sets the authorization and the billing project of the request.
     async fn with_headers(&self, builder: RequestBuilder) -> Result<RequestBuilder, Error> {
         let builder = builder
             .header("X-Goog-Api-Client", "rust")
             .header(reqwest::header::USER_AGENT, "google-cloud-storage");
+        let builder = match &self.user_project {
+            Some(user_project) => builder.query(&[("userProject", user_project)]),
+            None => builder,
+        };
         let builder = match &self.ts {
             Some(ts) => {
                 let token = ts.token().await.map_err(Error::TokenSource)?;
@@ -1300,52 +2274,103 @@ impl StorageClient {
         Ok(builder)
     }
 
-    async fn send_request<T>(&self, request: Request) -> Result<T, Error>
-    where
-        T: serde::de::DeserializeOwned,
-    {
-        let response = self.http.execute(request).await?;
-        let response = check_response_status(response).await?;
-        Ok(response.json().await?)
+    /// execute sends the request and retries it on the transient failures if `self.retry` allows it.
+    /// The request with the streamed body can't be cloned, so it is never retried.
+    async fn execute(&self, builder: RequestBuilder, idempotency: Idempotency) -> Result<Response, Error> {
+        let retryable = self.retry.policy.is_retryable(idempotency);
+        let mut builder = builder;
+        let mut retries = 0;
+        loop {
+            let next = if retryable && retries < self.retry.max_retries {
+                builder.try_clone()
+            } else {
+                None
+            };
+            let result = match self.with_headers(builder).await {
+                Ok(builder) => match builder.send().await {
+                    Ok(response) => check_response_status(response).await,
+                    Err(e) => Err(e.into()),
+                },
+                Err(e) => Err(e),
+            };
+            match (result, next) {
+                (Err(e), Some(next)) if is_transient(&e) => {
+                    tracing::debug!("retry the request: {e}");
+                    tokio::time::sleep(retry_delay(retries)).await;
+                    retries += 1;
+                    builder = next;
+                }
+                (result, _) => return result,
+            }
+        }
     }
 
-    async fn send<T>(&self, builder: RequestBuilder) -> Result<T, Error>
+    async fn send<T>(&self, builder: RequestBuilder, idempotency: Idempotency) -> Result<T, Error>
     where
         T: serde::de::DeserializeOwned,
     {
-        let builder = self.with_headers(builder).await?;
-        let response = builder.send().await?;
-        let response = check_response_status(response).await?;
+        let response = self.execute(builder, idempotency).await?;
         Ok(response.json().await?)
     }
 
-    async fn send_get_empty(&self, builder: RequestBuilder) -> Result<(), Error> {
-        let builder = self.with_headers(builder).await?;
-        let response = builder.send().await?;
-        check_response_status(response).await?;
+    async fn send_get_empty(&self, builder: RequestBuilder, idempotency: Idempotency) -> Result<(), Error> {
+        self.execute(builder, idempotency).await?;
         Ok(())
     }
 
-    async fn send_get_url(&self, builder: RequestBuilder) -> Result<String, Error> {
-        let builder = self.with_headers(builder).await?;
-        let response = builder.send().await?;
-        let response = check_response_status(response).await?;
+    async fn send_get_url(&self, builder: RequestBuilder, idempotency: Idempotency) -> Result<String, Error> {
+        let response = self.execute(builder, idempotency).await?;
         Ok(String::from_utf8_lossy(response.headers()[LOCATION].as_bytes()).into_owned())
     }
 }
 
+/// is_transient_download returns true if the download may be resumed.
+/// The connection closed while receiving the body is resumed too.
+fn is_transient_download(error: &Error) -> bool {
+    is_transient(error) || matches!(error, Error::HttpClient(e) if e.is_body() || e.is_decode())
+}
+
+/// composing_targets returns the sources guarded by their generations.
+fn composing_targets(sources: &[Object], destination: Option<Object>) -> ComposingTargets {
+    ComposingTargets {
+        destination,
+        source_objects: sources
+            .iter()
+            .map(|v| SourceObjects {
+                name: v.name.to_string(),
+                generation: Some(v.generation),
+                object_preconditions: Some(ObjectPreconditions {
+                    if_generation_match: Some(v.generation),
+                }),
+            })
+            .collect(),
+    }
+}
+
+/// checksums returns the checksums of the data sent with the upload. It is `None` if they are disabled.
+fn checksums(config: &ChecksumConfig, data: &[u8]) -> Option<Checksums> {
+    if !config.is_enabled() {
+        return None;
+    }
+    let mut hasher = Hasher::new(config);
+    hasher.update(data);
+    Some(hasher.finish())
+}
+
 #[cfg(test)]
 pub(crate) mod test {
     use std::collections::HashMap;
 
     use bytes::Buf;
-    use futures_util::StreamExt;
+    use futures_util::{StreamExt, TryStreamExt};
     use serial_test::serial;
+    use tokio::io::AsyncReadExt;
 
     use google_cloud_auth::project::Config;
     use google_cloud_auth::token::DefaultTokenSourceProvider;
     use google_cloud_token::TokenSourceProvider;
 
+    use crate::client::emulator_endpoint;
     use crate::http::bucket_access_controls::delete::DeleteBucketAccessControlRequest;
     use crate::http::bucket_access_controls::get::GetBucketAccessControlRequest;
     use crate::http::bucket_access_controls::insert::{
@@ -1360,11 +2385,16 @@ pub(crate) mod test {
     use crate::http::buckets::insert::{
         BucketCreationConfig, InsertBucketParam, InsertBucketRequest, RetentionPolicyCreationConfig,
     };
+    use crate::http::buckets::lifecycle::LifecycleRule;
     use crate::http::buckets::list::ListBucketsRequest;
-    use crate::http::buckets::patch::{BucketPatchConfig, PatchBucketRequest};
+    use crate::http::buckets::lock_retention_policy::LockRetentionPolicyRequest;
+    use crate::http::buckets::patch::{BucketConditions, BucketPatch, BucketPatchConfig, PatchBucketRequest};
     use crate::http::buckets::set_iam_policy::SetIamPolicyRequest;
     use crate::http::buckets::test_iam_permissions::TestIamPermissionsRequest;
-    use crate::http::buckets::{lifecycle, Billing, Binding, Cors, IamConfiguration, Lifecycle, Website};
+    use crate::http::buckets::{
+        Billing, Binding, Cors, IamConfiguration, Lifecycle, SoftDeletePolicy, Versioning, Website,
+    };
+    use crate::http::checksum::ChecksumConfig;
     use crate::http::default_object_access_controls::delete::DeleteDefaultObjectAccessControlRequest;
     use crate::http::default_object_access_controls::get::GetDefaultObjectAccessControlRequest;
     use crate::http::default_object_access_controls::insert::InsertDefaultObjectAccessControlRequest;
@@ -1374,7 +2404,7 @@ pub(crate) mod test {
     use crate::http::hmac_keys::get::GetHmacKeyRequest;
     use crate::http::hmac_keys::list::ListHmacKeysRequest;
     use crate::http::hmac_keys::update::UpdateHmacKeyRequest;
-    use crate::http::hmac_keys::HmacKeyMetadata;
+    use crate::http::hmac_keys::{HmacKeyMetadata, HmacKeyState};
     use crate::http::notifications::delete::DeleteNotificationRequest;
     use crate::http::notifications::get::GetNotificationRequest;
     use crate::http::notifications::insert::{InsertNotificationRequest, NotificationCreationConfig};
@@ -1387,17 +2417,23 @@ pub(crate) mod test {
     };
     use crate::http::object_access_controls::list::ListObjectAccessControlsRequest;
     use crate::http::object_access_controls::ObjectACLRole;
-    use crate::http::objects::compose::{ComposeObjectRequest, ComposingTargets};
+    use crate::http::objects::compose::{ComposeObjectRequest, ComposingTargets, MAX_COMPOSE_SOURCES};
     use crate::http::objects::copy::CopyObjectRequest;
     use crate::http::objects::delete::DeleteObjectRequest;
-    use crate::http::objects::download::Range;
+    use crate::http::objects::download::{DownloadConfig, Range};
     use crate::http::objects::get::GetObjectRequest;
-    use crate::http::objects::list::ListObjectsRequest;
+    use crate::http::objects::list::{ListObjectsItem, ListObjectsRequest};
+    use crate::http::objects::parallel_upload::ParallelUploadConfig;
+    use crate::http::objects::patch::{ObjectHold, PatchObjectRequest};
+    use crate::http::objects::restore::RestoreObjectRequest;
     use crate::http::objects::rewrite::RewriteObjectRequest;
     use crate::http::objects::upload::{Media, UploadObjectRequest, UploadType};
-    use crate::http::objects::{Object, SourceObjects};
-    use crate::http::resumable_upload_client::{ChunkSize, UploadStatus};
+    use crate::http::objects::{Conditions, Encryption, Object, ObjectPreconditions, SourceObjects};
+    use crate::http::resumable_upload_client::{ChunkConfig, ChunkError, ChunkSize, SessionStatus, UploadStatus};
+    use crate::http::retry::{RetryConfig, RetryPolicy};
+    use crate::http::service_account::get::GetServiceAccountRequest;
     use crate::http::storage_client::{StorageClient, SCOPES};
+    use crate::http::Error;
 
     #[ctor::ctor]
     fn init() {
@@ -1411,6 +2447,20 @@ pub(crate) mod test {
     }
 
     async fn client() -> (StorageClient, String, String) {
+        // the buckets must be created in the emulator in advance
+        if let Some(endpoint) = emulator_endpoint() {
+            let client = StorageClient::new(
+                None,
+                &endpoint,
+                &endpoint,
+                reqwest_middleware::ClientBuilder::new(reqwest::Client::default()).build(),
+                RetryConfig::default(),
+                None,
+                ChecksumConfig::default(),
+            );
+            let project = std::env::var("STORAGE_EMULATOR_PROJECT").unwrap_or_else(|_| "test-project".to_string());
+            return (client, project, "test@example.com".to_string());
+        }
         let tsp = DefaultTokenSourceProvider::new(Config {
             audience: None,
             scopes: Some(&SCOPES),
@@ -1423,7 +2473,11 @@ pub(crate) mod test {
         let client = StorageClient::new(
             Some(ts),
             "https://storage.googleapis.com",
+            "https://storage.googleapis.com",
             reqwest_middleware::ClientBuilder::new(reqwest::Client::default()).build(),
+            RetryConfig::default(),
+            None,
+            ChecksumConfig::default(),
         );
         let cred = cred.unwrap();
         (client, cred.project_id.unwrap(), cred.client_email.unwrap())
@@ -1491,17 +2545,11 @@ pub(crate) mod test {
                         max_age_seconds: 100,
                     }]),
                     lifecycle: Some(Lifecycle {
-                        rule: vec![lifecycle::Rule {
-                            action: Some(lifecycle::rule::Action {
-                                r#type: lifecycle::rule::ActionType::Delete,
-                                storage_class: None,
-                            }),
-                            condition: Some(lifecycle::rule::Condition {
-                                age: 365,
-                                is_live: Some(true),
-                                ..Default::default()
-                            }),
-                        }],
+                        rule: vec![LifecycleRule::delete()
+                            .with_age(365)
+                            .with_is_live(true)
+                            .build()
+                            .unwrap()],
                     }),
                     rpo: None,
                     ..Default::default()
@@ -1542,6 +2590,54 @@ pub(crate) mod test {
         assert_eq!(default_object_acl[0].role, ObjectACLRole::READER);
         assert_eq!(found.storage_class.as_str(), patched.storage_class.as_str());
         assert_eq!(found.location.as_str(), patched.location.as_str());
+        assert_eq!(found.lifecycle.unwrap().rule[0].condition.as_ref().unwrap().age, Some(365));
+
+        let rule = LifecycleRule::set_storage_class("COLDLINE")
+            .with_days_since_noncurrent_time(30)
+            .build()
+            .unwrap();
+        let abort = LifecycleRule::abort_incomplete_multipart_upload()
+            .with_age(7)
+            .with_matches_prefix(vec!["tmp/".to_string()])
+            .build()
+            .unwrap();
+        let updated = client
+            .update_lifecycle_rules(&bucket.name, |rules| {
+                rules.clear();
+                rules.push(rule.clone());
+                rules.push(abort.clone());
+            })
+            .await
+            .unwrap();
+        assert!(updated.metageneration > patched.metageneration);
+        assert_eq!(updated.lifecycle.unwrap().rule, vec![rule, abort]);
+
+        let patch = BucketPatch::default()
+            .with_versioning(Versioning { enabled: true })
+            .with_label("env", "test")
+            .clear_website();
+        let stale = client
+            .patch_bucket_fields(
+                &bucket.name,
+                &patch,
+                &BucketConditions::metageneration_match(patched.metageneration),
+            )
+            .await;
+        assert!(stale.is_err());
+        let fields_patched = client
+            .patch_bucket_fields(
+                &bucket.name,
+                &patch,
+                &BucketConditions::metageneration_match(updated.metageneration),
+            )
+            .await
+            .unwrap();
+        assert!(fields_patched.versioning.unwrap().enabled);
+        assert!(fields_patched.website.is_none());
+        let labels = fields_patched.labels.unwrap();
+        assert_eq!(labels["env"], "test");
+        assert_eq!(labels["labelkey"], "labelvalue");
+        assert_eq!(fields_patched.cors, found.cors);
 
         client
             .delete_bucket(&DeleteBucketRequest {
@@ -1554,60 +2650,190 @@ pub(crate) mod test {
 
     #[tokio::test]
     #[serial]
-    async fn set_get_test_iam() {
-        let (client, project, email) = client().await;
-        let bucket_name = bucket_name(&project, "test_iam");
-        let mut policy = client
-            .get_iam_policy(&GetIamPolicyRequest {
-                resource: bucket_name.to_string(),
-                options_requested_policy_version: None,
+    pub async fn retention_holds_and_soft_delete() {
+        let (client, project, _) = client().await;
+        let name = bucket_name(
+            &project,
+            &format!("retention-{}", time::OffsetDateTime::now_utc().unix_timestamp()),
+        );
+        let bucket = client
+            .insert_bucket(&InsertBucketRequest {
+                name: name.to_string(),
+                param: InsertBucketParam {
+                    project,
+                    ..Default::default()
+                },
+                bucket: BucketCreationConfig {
+                    location: "ASIA-NORTHEAST1".to_string(),
+                    retention_policy: Some(RetentionPolicyCreationConfig { retention_period: 1 }),
+                    soft_delete_policy: Some(SoftDeletePolicy {
+                        retention_duration_seconds: 7 * 24 * 60 * 60,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
             })
             .await
             .unwrap();
-        policy.bindings.push(Binding {
-            role: "roles/storage.objectViewer".to_string(),
-            members: vec![format!("serviceAccount:{}", email)],
-            condition: None,
-        });
+        assert_eq!(
+            bucket.soft_delete_policy.as_ref().unwrap().retention_duration_seconds,
+            7 * 24 * 60 * 60
+        );
 
-        let mut result = client
-            .set_iam_policy(&SetIamPolicyRequest {
-                resource: bucket_name.to_string(),
-                policy,
+        // The stale metageneration can't lock the policy.
+        let stale = LockRetentionPolicyRequest {
+            bucket: name.to_string(),
+            if_metageneration_match: bucket.metageneration + 1,
+        };
+        assert!(client.lock_retention_policy(&stale).await.is_err());
+        let locked = client
+            .lock_retention_policy(&LockRetentionPolicyRequest {
+                bucket: name.to_string(),
+                if_metageneration_match: bucket.metageneration,
             })
             .await
             .unwrap();
-        assert_eq!(result.bindings.len(), 5);
-        assert_eq!(result.bindings.pop().unwrap().role, "roles/storage.objectViewer");
+        assert_eq!(locked.retention_policy.unwrap().is_locked, Some(true));
 
-        let permissions = client
-            .test_iam_permissions(&TestIamPermissionsRequest {
-                resource: bucket_name.to_string(),
-                permissions: vec!["storage.buckets.get".to_string()],
-            })
+        let object = client
+            .upload_object(
+                &UploadObjectRequest {
+                    bucket: name.to_string(),
+                    ..Default::default()
+                },
+                "hold",
+                &UploadType::Simple(Media::new("hold")),
+            )
             .await
             .unwrap();
-        assert_eq!(permissions.permissions[0], "storage.buckets.get");
-    }
+        assert!(object.retention_expiration_time.is_some());
 
-    #[tokio::test]
-    #[serial]
-    pub async fn crud_default_object_controls() {
-        let (client, project, email) = client().await;
-        let bucket_name = bucket_name(&project, "default_object_acl");
-        let entity = format!("user-{}", email);
+        let held = client
+            .set_object_hold(&name, "hold", ObjectHold::Temporary, true)
+            .await
+            .unwrap();
+        assert_eq!(held.temporary_hold, Some(true));
+        let held = client
+            .set_object_hold(&name, "hold", ObjectHold::EventBased, true)
+            .await
+            .unwrap();
+        assert_eq!(held.event_based_hold, Some(true));
+        assert!(held.retention_expiration_time.is_none());
+        let delete = DeleteObjectRequest {
+            bucket: name.to_string(),
+            object: "hold".to_string(),
+            ..Default::default()
+        };
+        assert!(client.delete_object(&delete).await.is_err());
 
         client
-            .insert_default_object_access_control(&InsertDefaultObjectAccessControlRequest {
-                bucket: bucket_name.to_string(),
-                object_access_control: ObjectAccessControlCreationConfig {
-                    entity: entity.to_string(),
-                    role: ObjectACLRole::READER,
-                },
-            })
+            .set_object_hold(&name, "hold", ObjectHold::Temporary, false)
             .await
             .unwrap();
-
+        let released = client
+            .set_object_hold(&name, "hold", ObjectHold::EventBased, false)
+            .await
+            .unwrap();
+        assert!(released.retention_expiration_time.is_some());
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        client.delete_object(&delete).await.unwrap();
+
+        let deleted = client
+            .list_objects(&ListObjectsRequest {
+                bucket: name.to_string(),
+                soft_deleted: Some(true),
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .items
+            .unwrap();
+        assert_eq!(deleted.len(), 1);
+        assert_eq!(deleted[0].generation, object.generation);
+        assert!(deleted[0].soft_delete_time.is_some());
+        assert!(deleted[0].hard_delete_time.is_some());
+
+        let restored = client
+            .restore_object(&RestoreObjectRequest {
+                bucket: name.to_string(),
+                object: "hold".to_string(),
+                generation: object.generation,
+                copy_source_acl: Some(true),
+                if_generation_match: Some(0),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(restored.name, "hold");
+        assert!(restored.soft_delete_time.is_none());
+
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        client.delete_object(&delete).await.unwrap();
+        client
+            .delete_bucket(&DeleteBucketRequest {
+                bucket: name,
+                param: Default::default(),
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn set_get_test_iam() {
+        let (client, project, email) = client().await;
+        let bucket_name = bucket_name(&project, "test_iam");
+        let mut policy = client
+            .get_iam_policy(&GetIamPolicyRequest {
+                resource: bucket_name.to_string(),
+                options_requested_policy_version: None,
+            })
+            .await
+            .unwrap();
+        policy.bindings.push(Binding {
+            role: "roles/storage.objectViewer".to_string(),
+            members: vec![format!("serviceAccount:{}", email)],
+            condition: None,
+        });
+
+        let mut result = client
+            .set_iam_policy(&SetIamPolicyRequest {
+                resource: bucket_name.to_string(),
+                policy,
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.bindings.len(), 5);
+        assert_eq!(result.bindings.pop().unwrap().role, "roles/storage.objectViewer");
+
+        let permissions = client
+            .test_iam_permissions(&TestIamPermissionsRequest {
+                resource: bucket_name.to_string(),
+                permissions: vec!["storage.buckets.get".to_string()],
+            })
+            .await
+            .unwrap();
+        assert_eq!(permissions.permissions[0], "storage.buckets.get");
+    }
+
+    #[tokio::test]
+    #[serial]
+    pub async fn crud_default_object_controls() {
+        let (client, project, email) = client().await;
+        let bucket_name = bucket_name(&project, "default_object_acl");
+        let entity = format!("user-{}", email);
+
+        client
+            .insert_default_object_access_control(&InsertDefaultObjectAccessControlRequest {
+                bucket: bucket_name.to_string(),
+                object_access_control: ObjectAccessControlCreationConfig {
+                    entity: entity.to_string(),
+                    role: ObjectACLRole::READER,
+                },
+            })
+            .await
+            .unwrap();
+
         let found = client
             .get_default_object_access_control(&GetDefaultObjectAccessControlRequest {
                 bucket: bucket_name.to_string(),
@@ -1782,6 +3008,33 @@ pub(crate) mod test {
             .unwrap();
         assert_eq!(found.id, post.id);
         assert_eq!(found.event_types.unwrap().len(), 2);
+
+        let listed = client.wait_for_notification(&bucket_name, &post.id).await.unwrap();
+        assert_eq!(listed.object_name_prefix.as_deref(), Some("notification-test"));
+
+        client
+            .delete_notification(&DeleteNotificationRequest {
+                bucket: bucket_name.to_string(),
+                notification: post.id.to_string(),
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    #[serial]
+    pub async fn get_service_account() {
+        let (client, project, _) = client().await;
+        let account = client
+            .get_service_account(&GetServiceAccountRequest {
+                project_id: project.to_string(),
+            })
+            .await
+            .unwrap();
+        assert!(account
+            .email_address
+            .ends_with("@gs-project-accounts.iam.gserviceaccount.com"));
+        assert_eq!(account.kind, "storage#serviceAccount");
     }
 
     #[tokio::test]
@@ -1792,10 +3045,11 @@ pub(crate) mod test {
         let post = client
             .create_hmac_key(&CreateHmacKeyRequest {
                 project_id: project_id.clone(),
-                service_account_email: email,
+                service_account_email: email.to_string(),
             })
             .await
             .unwrap();
+        assert!(!post.secret.expose().is_empty());
 
         let found = client
             .get_hmac_key(&GetHmacKeyRequest {
@@ -1805,29 +3059,57 @@ pub(crate) mod test {
             .await
             .unwrap();
         assert_eq!(found.id, post.metadata.id);
-        assert_eq!(found.state, "ACTIVE");
+        assert_eq!(found.state, HmacKeyState::Active);
 
-        let keys = client
-            .list_hmac_keys(&ListHmacKeysRequest {
+        // The active key can't be deleted.
+        let err = client
+            .delete_hmac_key(&DeleteHmacKeyRequest {
+                access_id: found.access_id.to_string(),
                 project_id: project_id.clone(),
-                ..Default::default()
             })
             .await
-            .unwrap();
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::HmacKeyNotInactive {
+                state: HmacKeyState::Active,
+                ..
+            }
+        ));
 
-        for n in keys.items.unwrap_or_default() {
+        let mut keys = vec![];
+        let mut req = ListHmacKeysRequest {
+            project_id: project_id.clone(),
+            service_account_email: Some(email.to_string()),
+            max_results: Some(1),
+            ..Default::default()
+        };
+        loop {
+            let response = client.list_hmac_keys(&req).await.unwrap();
+            let items = response.items.unwrap_or_default();
+            assert!(items.len() <= 1);
+            keys.extend(items);
+            match response.next_page_token {
+                Some(token) => req.page_token = Some(token),
+                None => break,
+            }
+        }
+        assert!(keys.iter().any(|v| v.access_id == found.access_id));
+
+        for n in keys {
+            assert_eq!(n.service_account_email, email);
             let result = client
                 .update_hmac_key(&UpdateHmacKeyRequest {
                     access_id: n.access_id.to_string(),
                     project_id: n.project_id.to_string(),
                     metadata: HmacKeyMetadata {
-                        state: "INACTIVE".to_string(),
+                        state: HmacKeyState::Inactive,
                         ..n.clone()
                     },
                 })
                 .await
                 .unwrap();
-            assert_eq!(result.state, "INACTIVE");
+            assert_eq!(result.state, HmacKeyState::Inactive);
 
             client
                 .delete_hmac_key(&DeleteHmacKeyRequest {
@@ -2032,6 +3314,274 @@ pub(crate) mod test {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn compose_object_too_many_sources() {
+        // The request is rejected before it is sent to the unreachable endpoint.
+        let client = StorageClient::new(
+            None,
+            "http://localhost:0",
+            "http://localhost:0",
+            reqwest_middleware::ClientBuilder::new(reqwest::Client::default()).build(),
+            RetryConfig::default(),
+            None,
+            ChecksumConfig::default(),
+        );
+        let req = ComposeObjectRequest {
+            bucket: "bucket".to_string(),
+            destination_object: "composed".to_string(),
+            composing_targets: ComposingTargets {
+                destination: None,
+                source_objects: (0..=MAX_COMPOSE_SOURCES)
+                    .map(|i| SourceObjects {
+                        name: format!("part{i}"),
+                        ..Default::default()
+                    })
+                    .collect(),
+            },
+            ..Default::default()
+        };
+        let err = client.compose_object(&req).await.unwrap_err();
+        assert!(
+            matches!(err, Error::TooManyComposeSources(v) if v == MAX_COMPOSE_SOURCES + 1),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    pub async fn rewrite_and_compose_object() {
+        let (client, project, _) = client().await;
+        let bucket_name = bucket_name(&project, "object");
+        let prefix = format!("rewrite_{}", time::OffsetDateTime::now_utc().unix_timestamp());
+
+        let mut sources = vec![];
+        for i in 0..2 {
+            let uploaded = client
+                .upload_object(
+                    &UploadObjectRequest {
+                        bucket: bucket_name.to_string(),
+                        ..Default::default()
+                    },
+                    vec![i as u8; 3],
+                    &UploadType::Simple(Media::new(format!("{prefix}_{i}"))),
+                )
+                .await
+                .unwrap();
+            sources.push(uploaded);
+        }
+
+        // rewrite
+        let mut progress = vec![];
+        let rewritten = client
+            .rewrite_object_until_done(
+                &RewriteObjectRequest {
+                    destination_bucket: bucket_name.to_string(),
+                    destination_object: format!("{prefix}_rewrite"),
+                    source_bucket: bucket_name.to_string(),
+                    source_object: sources[0].name.to_string(),
+                    if_generation_match: Some(0),
+                    ..Default::default()
+                },
+                |v| progress.push(v),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rewritten.size, 3);
+        assert_eq!(progress.last().unwrap().total_bytes_rewritten, 3);
+        assert_eq!(progress.last().unwrap().object_size, 3);
+
+        // the destination already exists
+        let result = client
+            .copy_object_with_rewrite(&bucket_name, &sources[1].name, &bucket_name, &rewritten.name, Some(0))
+            .await;
+        match result.unwrap_err() {
+            Error::Response(e) => assert_eq!(e.code, 412),
+            e => panic!("unexpected error {e:?}"),
+        }
+        let copied = client
+            .copy_object_with_rewrite(
+                &bucket_name,
+                &sources[1].name,
+                &bucket_name,
+                &rewritten.name,
+                Some(rewritten.generation),
+            )
+            .await
+            .unwrap();
+        assert_ne!(copied.generation, rewritten.generation);
+
+        // compose
+        let compose = |generations: &[i64]| ComposeObjectRequest {
+            bucket: bucket_name.to_string(),
+            destination_object: format!("{prefix}_composed"),
+            composing_targets: ComposingTargets {
+                source_objects: sources
+                    .iter()
+                    .zip(generations)
+                    .map(|(o, generation)| SourceObjects {
+                        name: o.name.to_string(),
+                        object_preconditions: Some(ObjectPreconditions {
+                            if_generation_match: Some(*generation),
+                        }),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+            if_generation_match: Some(0),
+            ..Default::default()
+        };
+        assert!(client.compose_object(&compose(&[1, 1])).await.is_err());
+        let generations: Vec<i64> = sources.iter().map(|o| o.generation).collect();
+        let composed = client.compose_object(&compose(&generations)).await.unwrap();
+        assert_eq!(composed.size, 6);
+        // the destination already exists
+        assert!(client.compose_object(&compose(&generations)).await.is_err());
+    }
+
+    #[tokio::test]
+    #[serial]
+    pub async fn customer_supplied_encryption_key() {
+        let (client, project, _) = client().await;
+        let bucket_name = bucket_name(&project, "object");
+        let name = format!("csek_{}", time::OffsetDateTime::now_utc().unix_timestamp());
+        let key1 = Encryption::new(&[1; 32]);
+        let key2 = Encryption::new(&[2; 32]);
+
+        let uploaded = client
+            .upload_object(
+                &UploadObjectRequest {
+                    bucket: bucket_name.to_string(),
+                    encryption: Some(key1.clone()),
+                    ..Default::default()
+                },
+                vec![1, 2, 3],
+                &UploadType::Simple(Media::new(name.to_string())),
+            )
+            .await
+            .unwrap();
+        assert_eq!(uploaded.customer_encryption.unwrap().key_sha256, key1.encryption_key_sha256);
+
+        let download = |encryption: Option<Encryption>, object: String| {
+            let client = client.clone();
+            let bucket = bucket_name.to_string();
+            async move {
+                client
+                    .download_object(
+                        &GetObjectRequest {
+                            bucket,
+                            object,
+                            encryption,
+                            ..Default::default()
+                        },
+                        &Range::default(),
+                    )
+                    .await
+            }
+        };
+        assert!(download(None, name.to_string()).await.is_err());
+        assert!(download(Some(key2.clone()), name.to_string()).await.is_err());
+        assert_eq!(download(Some(key1.clone()), name.to_string()).await.unwrap(), vec![1, 2, 3]);
+
+        // rotate the key
+        let rewritten = client
+            .rewrite_object_until_done(
+                &RewriteObjectRequest {
+                    destination_bucket: bucket_name.to_string(),
+                    destination_object: format!("{name}_rotated"),
+                    source_bucket: bucket_name.to_string(),
+                    source_object: name.to_string(),
+                    source_encryption: Some(key1.clone()),
+                    destination_encryption: Some(key2.clone()),
+                    ..Default::default()
+                },
+                |_| {},
+            )
+            .await
+            .unwrap();
+        assert_eq!(rewritten.customer_encryption.unwrap().key_sha256, key2.encryption_key_sha256);
+        assert_eq!(download(Some(key2), rewritten.name).await.unwrap(), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    #[serial]
+    pub async fn list_objects_stream() {
+        let (client, project, _) = client().await;
+        let bucket_name = bucket_name(&project, "object");
+        let prefix = format!("list_{}/", time::OffsetDateTime::now_utc().unix_timestamp());
+        for name in ["a.txt", "b.csv", "dir1/c.txt", "dir2/d.txt"] {
+            client
+                .upload_object(
+                    &UploadObjectRequest {
+                        bucket: bucket_name.to_string(),
+                        ..Default::default()
+                    },
+                    vec![1, 2, 3],
+                    &UploadType::Simple(Media::new(format!("{prefix}{name}"))),
+                )
+                .await
+                .unwrap();
+        }
+        let prefix = &prefix;
+        let list = |req: ListObjectsRequest| {
+            let stream = client.list_objects_stream(
+                &req,
+                Some(RetryConfig {
+                    max_retries: 3,
+                    ..Default::default()
+                }),
+            );
+            async move {
+                stream
+                    .map_ok(|item| match item {
+                        ListObjectsItem::Object(o) => o.name.trim_start_matches(prefix.as_str()).to_string(),
+                        ListObjectsItem::Prefix(p) => format!("prefix:{}", p.trim_start_matches(prefix.as_str())),
+                    })
+                    .try_collect::<Vec<String>>()
+                    .await
+                    .unwrap()
+            }
+        };
+
+        // one object per page
+        let all = list(ListObjectsRequest {
+            bucket: bucket_name.to_string(),
+            prefix: Some(prefix.to_string()),
+            max_results: Some(1),
+            fields: Some("items(name)".to_string()),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(all, vec!["a.txt", "b.csv", "dir1/c.txt", "dir2/d.txt"]);
+
+        let folders = list(ListObjectsRequest {
+            bucket: bucket_name.to_string(),
+            prefix: Some(prefix.to_string()),
+            delimiter: Some("/".to_string()),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(folders, vec!["prefix:dir1/", "prefix:dir2/", "a.txt", "b.csv"]);
+
+        let ranged = list(ListObjectsRequest {
+            bucket: bucket_name.to_string(),
+            start_offset: Some(format!("{prefix}b")),
+            end_offset: Some(format!("{prefix}dir2")),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(ranged, vec!["b.csv", "dir1/c.txt"]);
+
+        let globbed = list(ListObjectsRequest {
+            bucket: bucket_name.to_string(),
+            match_glob: Some(format!("{prefix}**.txt")),
+            versions: Some(true),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(globbed, vec!["a.txt", "dir1/c.txt", "dir2/d.txt"]);
+    }
+
     #[tokio::test]
     #[serial]
     pub async fn streamed_object() {
@@ -2318,4 +3868,465 @@ pub(crate) mod test {
         chunk1_data.extend(chunk2_data);
         assert_eq!(chunk1_data, download);
     }
+
+    #[tokio::test]
+    #[serial]
+    pub async fn resumable_chunked_upload() {
+        let (client, project, _) = client().await;
+        let bucket_name = bucket_name(&project, "object");
+        let file_name = format!("resumable_chunked{}", time::OffsetDateTime::now_utc().unix_timestamp());
+
+        let config = ChunkConfig {
+            chunk_size: 256 * 1024,
+            ..Default::default()
+        };
+        let upload_type = UploadType::Resumable(
+            Box::new(Object {
+                name: file_name.to_string(),
+                content_type: Some("video/mp4".to_string()),
+                ..Default::default()
+            }),
+            config.clone(),
+        );
+        let req = UploadObjectRequest {
+            bucket: bucket_name.to_string(),
+            ..Default::default()
+        };
+        let data: Vec<u8> = (0..256 * 1024 * 2 + 50).map(|i| (i % 256) as u8).collect();
+
+        // The first chunk is uploaded, and the rest is uploaded by another client with the session url.
+        let uploader = client.prepare_resumable_upload(&req, &upload_type).await.unwrap();
+        let status = uploader
+            .upload_multiple_chunk(data[..256 * 1024].to_vec(), &ChunkSize::new(0, 256 * 1024 - 1, None))
+            .await
+            .unwrap();
+        assert_eq!(status, UploadStatus::ResumeIncomplete);
+
+        let uploader = client.get_resumable_upload(uploader.url().to_string());
+        let offset = match uploader.session_status(None).await.unwrap() {
+            SessionStatus::Incomplete { persisted_size } => persisted_size,
+            SessionStatus::Completed(_) => unreachable!(),
+        };
+        assert_eq!(offset, 256 * 1024);
+        let rest: Vec<Result<_, std::io::Error>> =
+            data[offset as usize..].chunks(1000).map(|v| Ok(v.to_vec())).collect();
+        let mut progress = vec![];
+        let object = uploader
+            .upload_chunks(futures_util::stream::iter(rest), offset, &config, |v| {
+                progress.push(v.persisted_size)
+            })
+            .await
+            .unwrap();
+        assert_eq!(object.size, data.len() as i64);
+        assert_eq!(progress, vec![256 * 1024 * 2, data.len() as u64]);
+
+        let get_request = &GetObjectRequest {
+            bucket: bucket_name.to_string(),
+            object: file_name.to_string(),
+            ..Default::default()
+        };
+        let download = client.download_object(get_request, &Range::default()).await.unwrap();
+        assert_eq!(data, download);
+
+        // The whole data is uploaded by upload_object and upload_streamed_object.
+        let object = client.upload_object(&req, data.clone(), &upload_type).await.unwrap();
+        assert_eq!(object.size, data.len() as i64);
+        let chunks: Vec<Result<_, std::io::Error>> = data.chunks(1000).map(|v| Ok(v.to_vec())).collect();
+        let object = client
+            .upload_streamed_object(&req, futures_util::stream::iter(chunks), &upload_type)
+            .await
+            .unwrap();
+        assert_eq!(object.size, data.len() as i64);
+        let download = client.download_object(get_request, &Range::default()).await.unwrap();
+        assert_eq!(data, download);
+
+        let config = ChunkConfig {
+            chunk_size: 1000,
+            ..Default::default()
+        };
+        let upload_type = UploadType::Resumable(Box::default(), config);
+        let err = client.upload_object(&req, data, &upload_type).await.unwrap_err();
+        assert!(matches!(err, Error::Chunk(ChunkError::InvalidChunkSize(1000))), "{err:?}");
+    }
+
+    #[tokio::test]
+    #[serial]
+    pub async fn download_object_stream() {
+        let (client, project, _) = client().await;
+        let bucket_name = bucket_name(&project, "object");
+        let file_name = format!("download_stream{}", time::OffsetDateTime::now_utc().unix_timestamp());
+        let data: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+        let object = client
+            .upload_object(
+                &UploadObjectRequest {
+                    bucket: bucket_name.to_string(),
+                    ..Default::default()
+                },
+                data.clone(),
+                &UploadType::Simple(Media::new(file_name.to_string())),
+            )
+            .await
+            .unwrap();
+        let req = GetObjectRequest {
+            bucket: bucket_name.to_string(),
+            object: file_name.to_string(),
+            ..Default::default()
+        };
+
+        let downloaded: Vec<bytes::Bytes> = client
+            .download_object_stream(&req, &DownloadConfig::default())
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(data, downloaded.concat());
+
+        let config = DownloadConfig {
+            offset: 1000,
+            length: Some(5000),
+            ..Default::default()
+        };
+        let downloaded: Vec<bytes::Bytes> = client
+            .download_object_stream(&req, &config)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(&data[1000..6000], downloaded.concat().as_slice());
+
+        let mut reader = client
+            .download_object_reader(&req, &DownloadConfig::default())
+            .await
+            .unwrap();
+        let mut buf = vec![];
+        reader.read_to_end(&mut buf).await.unwrap();
+        assert_eq!(data, buf);
+
+        // The generation mismatch is not retried.
+        let req = GetObjectRequest {
+            if_generation_match: Some(object.generation + 1),
+            ..req
+        };
+        assert!(client
+            .download_object_stream(&req, &DownloadConfig::default())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    #[serial]
+    pub async fn download_gzip_object() {
+        let (client, project, _) = client().await;
+        let bucket_name = bucket_name(&project, "object");
+        let file_name = format!("gzip{}", time::OffsetDateTime::now_utc().unix_timestamp());
+        // gzip of "hello gzip"
+        let stored: Vec<u8> = vec![
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0x48, 0xaf,
+            0xca, 0x2c, 0x00, 0x00, 0x19, 0x6a, 0xd2, 0xdf, 0x0a, 0x00, 0x00, 0x00,
+        ];
+        client
+            .upload_object(
+                &UploadObjectRequest {
+                    bucket: bucket_name.to_string(),
+                    ..Default::default()
+                },
+                stored.clone(),
+                &UploadType::Multipart(Box::new(Object {
+                    name: file_name.to_string(),
+                    content_type: Some("text/plain".to_string()),
+                    content_encoding: Some("gzip".to_string()),
+                    ..Default::default()
+                })),
+            )
+            .await
+            .unwrap();
+        let req = GetObjectRequest {
+            bucket: bucket_name.to_string(),
+            object: file_name.to_string(),
+            ..Default::default()
+        };
+
+        let decoded: Vec<bytes::Bytes> = client
+            .download_object_stream(&req, &DownloadConfig::default())
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(decoded.concat(), b"hello gzip");
+
+        let config = DownloadConfig {
+            decompressive_transcoding: false,
+            checksum: ChecksumConfig {
+                crc32c: true,
+                md5: true,
+            },
+            ..Default::default()
+        };
+        let downloaded: Vec<bytes::Bytes> = client
+            .download_object_stream(&req, &config)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(downloaded.concat(), stored);
+
+        let config = DownloadConfig {
+            offset: 1,
+            ..Default::default()
+        };
+        let err = client.download_object_stream(&req, &config).await.err().unwrap();
+        assert!(matches!(err, Error::TranscodedRange), "{err:?}");
+
+        let config = DownloadConfig {
+            offset: 10,
+            decompressive_transcoding: false,
+            ..Default::default()
+        };
+        let downloaded: Vec<bytes::Bytes> = client
+            .download_object_stream(&req, &config)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(downloaded.concat(), &stored[10..]);
+    }
+
+    #[tokio::test]
+    #[serial]
+    pub async fn upload_parallel() {
+        let (client, project, _) = client().await;
+        let bucket_name = bucket_name(&project, "object");
+        let file_name = format!("parallel{}", time::OffsetDateTime::now_utc().unix_timestamp());
+        let data: Vec<u8> = (0..100_000).map(|i| (i % 251) as u8).collect();
+        let prefix = format!("{file_name}.tmp/");
+        // 40 parts are composed in cascade.
+        let config = ParallelUploadConfig {
+            parts: 40,
+            min_part_size: 1,
+            concurrency: 8,
+            temporary_prefix: Some(prefix.to_string()),
+            ..Default::default()
+        };
+        let mut notified = vec![];
+        let object = client
+            .upload_parallel(
+                &UploadObjectRequest {
+                    bucket: bucket_name.to_string(),
+                    ..Default::default()
+                },
+                &Object {
+                    name: file_name.to_string(),
+                    content_type: Some("application/octet-stream".to_string()),
+                    ..Default::default()
+                },
+                data.as_slice(),
+                data.len() as u64,
+                &config,
+                |v| notified.push(v),
+            )
+            .await
+            .unwrap();
+        assert_eq!(object.size, data.len() as i64);
+        assert_eq!(object.component_count, Some(40));
+        assert_eq!(notified.len(), 40);
+        assert_eq!(notified.last().unwrap().uploaded_size, data.len() as u64);
+        assert_eq!(notified.last().unwrap().uploaded_parts, 40);
+
+        let downloaded = client
+            .download_object(
+                &GetObjectRequest {
+                    bucket: bucket_name.to_string(),
+                    object: file_name.to_string(),
+                    ..Default::default()
+                },
+                &Range::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(downloaded, data);
+
+        let temporaries = client
+            .list_objects(&ListObjectsRequest {
+                bucket: bucket_name.to_string(),
+                prefix: Some(prefix),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(temporaries.items.unwrap_or_default().is_empty());
+
+        // The temporary objects are deleted on the failure.
+        let err = client
+            .upload_parallel(
+                &UploadObjectRequest {
+                    bucket: bucket_name.to_string(),
+                    if_generation_match: Some(0),
+                    ..Default::default()
+                },
+                &Object {
+                    name: file_name.to_string(),
+                    ..Default::default()
+                },
+                data.as_slice(),
+                data.len() as u64,
+                &config,
+                |_| {},
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, Error::Response(e) if e.code == 412), "{err:?}");
+        let temporaries = client
+            .list_objects(&ListObjectsRequest {
+                bucket: bucket_name.to_string(),
+                prefix: Some(format!("{file_name}.tmp/")),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(temporaries.items.unwrap_or_default().is_empty());
+    }
+
+    #[tokio::test]
+    #[serial]
+    pub async fn upload_and_download_with_checksums() {
+        let (client, project, _) = client().await;
+        let bucket_name = bucket_name(&project, "object");
+        let file_name = format!("checksums{}", time::OffsetDateTime::now_utc().unix_timestamp());
+        let checksum = ChecksumConfig {
+            crc32c: true,
+            md5: true,
+        };
+        let req = UploadObjectRequest {
+            bucket: bucket_name.to_string(),
+            checksum,
+            ..Default::default()
+        };
+        let data: Vec<u8> = (0..256 * 1024 + 50).map(|i| (i % 251) as u8).collect();
+        let chunks = || {
+            let chunks: Vec<Result<_, std::io::Error>> = data.chunks(1000).map(|v| Ok(v.to_vec())).collect();
+            futures_util::stream::iter(chunks)
+        };
+        let multipart = UploadType::Multipart(Box::new(Object {
+            name: file_name.to_string(),
+            ..Default::default()
+        }));
+        let resumable = UploadType::Resumable(
+            Box::new(Object {
+                name: file_name.to_string(),
+                ..Default::default()
+            }),
+            ChunkConfig {
+                chunk_size: 256 * 1024,
+                checksum,
+                ..Default::default()
+            },
+        );
+        let simple = UploadType::Simple(Media::new(file_name.to_string()));
+
+        let mut objects = vec![];
+        for upload_type in [&simple, &multipart, &resumable] {
+            objects.push(client.upload_object(&req, data.clone(), upload_type).await.unwrap());
+            objects.push(
+                client
+                    .upload_streamed_object(&req, chunks(), upload_type)
+                    .await
+                    .unwrap(),
+            );
+        }
+        let expected = objects[0].clone();
+        assert!(expected.crc32c.is_some());
+        assert!(expected.md5_hash.is_some());
+        for object in objects {
+            assert_eq!(object.crc32c, expected.crc32c);
+            assert_eq!(object.md5_hash, expected.md5_hash);
+        }
+
+        let get_request = GetObjectRequest {
+            bucket: bucket_name.to_string(),
+            object: file_name.to_string(),
+            ..Default::default()
+        };
+        let config = DownloadConfig {
+            checksum,
+            ..Default::default()
+        };
+        let downloaded: Vec<bytes::Bytes> = client
+            .download_object_stream(&get_request, &config)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(data, downloaded.concat());
+        let downloaded = client
+            .with_checksum(checksum)
+            .download_object(&get_request, &Range::default())
+            .await
+            .unwrap();
+        assert_eq!(data, downloaded);
+    }
+
+    #[tokio::test]
+    #[serial]
+    pub async fn upload_and_delete_with_conditions() {
+        let (client, project, _) = client().await;
+        let bucket_name = bucket_name(&project, "object");
+        let file_name = format!("conditions{}", time::OffsetDateTime::now_utc().unix_timestamp());
+        let upload_type = UploadType::Simple(Media::new(file_name.to_string()));
+        let req = UploadObjectRequest {
+            bucket: bucket_name.to_string(),
+            ..Default::default()
+        }
+        .with_conditions(Conditions::does_not_exist());
+        let object = client.upload_object(&req, "hello", &upload_type).await.unwrap();
+
+        // The object already exists.
+        let err = client.upload_object(&req, "hello", &upload_type).await.unwrap_err();
+        assert!(matches!(err, Error::Response(e) if e.code == 412));
+
+        let object = client
+            .patch_object(
+                &PatchObjectRequest {
+                    bucket: bucket_name.to_string(),
+                    object: file_name.to_string(),
+                    metadata: Some(Object {
+                        content_type: Some("text/plain".to_string()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }
+                .with_conditions(Conditions::unchanged(&object)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(object.content_type.as_deref(), Some("text/plain"));
+
+        let delete = DeleteObjectRequest {
+            bucket: bucket_name.to_string(),
+            object: file_name.to_string(),
+            ..Default::default()
+        };
+        let err = client
+            .delete_object(
+                &delete
+                    .clone()
+                    .with_conditions(Conditions::generation_match(object.generation + 1)),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Response(e) if e.code == 412));
+        client
+            .with_retry(RetryConfig {
+                policy: RetryPolicy::Never,
+                ..Default::default()
+            })
+            .delete_object(&delete.with_conditions(Conditions::unchanged(&object)))
+            .await
+            .unwrap();
+    }
 }
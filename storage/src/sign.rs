@@ -5,6 +5,7 @@ use std::time::{Duration, SystemTime};
 
 use base64::prelude::*;
 use once_cell::sync::Lazy;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
 use pkcs8::der::pem::PemLabel;
 use pkcs8::SecretDocument;
 use regex::Regex;
@@ -23,6 +24,10 @@ static SPACE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r" +").unwrap());
 static TAB_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"[\t]+").unwrap());
 static ONE_WEEK_IN_SECONDS: u64 = 604801;
 
+/// The unreserved characters of RFC 3986 are not escaped in the canonical request.
+const V4_QUERY_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+const V4_PATH_ENCODE_SET: &AsciiSet = &V4_QUERY_ENCODE_SET.remove(b'/');
+
 pub enum SignedURLMethod {
     DELETE,
     GET,
@@ -48,6 +53,7 @@ pub trait URLStyle {
     fn path(&self, bucket: &str, object: &str) -> String;
 }
 
+/// PathStyle puts the bucket in the path like `https://storage.googleapis.com/bucket/object`.
 pub struct PathStyle {}
 
 const HOST: &str = "storage.googleapis.com";
@@ -66,6 +72,35 @@ impl URLStyle for PathStyle {
     }
 }
 
+/// VirtualHostedStyle puts the bucket in the host like `https://bucket.storage.googleapis.com/object`.
+pub struct VirtualHostedStyle {}
+
+impl URLStyle for VirtualHostedStyle {
+    fn host(&self, bucket: &str) -> String {
+        format!("{bucket}.{HOST}")
+    }
+
+    fn path(&self, _bucket: &str, object: &str) -> String {
+        object.to_string()
+    }
+}
+
+/// BucketBoundHostname uses the custom domain bound to the bucket like `https://mybucket.example.com/object`.
+/// See https://cloud.google.com/storage/docs/request-endpoints#cname.
+pub struct BucketBoundHostname {
+    pub hostname: String,
+}
+
+impl URLStyle for BucketBoundHostname {
+    fn host(&self, _bucket: &str) -> String {
+        self.hostname.to_string()
+    }
+
+    fn path(&self, _bucket: &str, object: &str) -> String {
+        object.to_string()
+    }
+}
+
 #[derive(Clone)]
 pub enum SignBy {
     PrivateKey(Vec<u8>),
@@ -163,6 +198,10 @@ pub enum SignedURLError {
     CertError(String),
     #[error(transparent)]
     SignBlob(#[from] http::Error),
+    /// The signBlob of IAM Service Account Credentials API failed.
+    #[cfg(feature = "auth")]
+    #[error(transparent)]
+    Iam(#[from] google_cloud_iam::client::Error),
 }
 
 pub(crate) fn create_signed_buffer(
@@ -200,32 +239,39 @@ pub(crate) fn create_signed_buffer(
         header_names.join(";")
     };
 
-    const CONFIG: EncodedConfig = well_known::iso8601::Config::DEFAULT
-        .set_use_separators(false)
-        .set_time_precision(TimePrecision::Second { decimal_digits: None })
-        .encode();
-
-    let timestamp = start_time.format(&Iso8601::<CONFIG>).unwrap();
-    let credential_scope = format!(
-        "{}/auto/storage/goog4_request",
-        start_time.format(format_description!("[year][month][day]")).unwrap()
-    );
-
-    // append query parameters
-    {
-        let mut query = builder.query_pairs_mut();
-        query.append_pair("X-Goog-Algorithm", "GOOG4-RSA-SHA256");
-        query.append_pair("X-Goog-Credential", &format!("{}/{}", google_access_id, credential_scope));
-        query.append_pair("X-Goog-Date", &timestamp);
-        query.append_pair("X-Goog-Expires", opts.expires.as_secs().to_string().as_str());
-        query.append_pair("X-Goog-SignedHeaders", &signed_headers);
+    let (timestamp, credential_scope) = v4_credential_scope(start_time);
+
+    // append query parameters sorted by the name as the canonical query string
+    let escaped_query = {
+        let mut query = vec![
+            ("X-Goog-Algorithm", "GOOG4-RSA-SHA256".to_string()),
+            ("X-Goog-Credential", format!("{}/{}", google_access_id, credential_scope)),
+            ("X-Goog-Date", timestamp.clone()),
+            ("X-Goog-Expires", opts.expires.as_secs().to_string()),
+            ("X-Goog-SignedHeaders", signed_headers.clone()),
+        ];
         for (k, values) in &opts.query_parameters {
             for value in values {
-                query.append_pair(k.as_str(), value.as_str());
+                query.push((k.as_str(), value.to_string()));
             }
         }
-    }
-    let escaped_query = builder.query().unwrap().replace('+', "%20");
+        let mut query: Vec<(String, String)> = query
+            .iter()
+            .map(|(k, v)| {
+                (
+                    utf8_percent_encode(k, V4_QUERY_ENCODE_SET).to_string(),
+                    utf8_percent_encode(v, V4_QUERY_ENCODE_SET).to_string(),
+                )
+            })
+            .collect();
+        query.sort();
+        query
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .collect::<Vec<String>>()
+            .join("&")
+    };
+    builder.set_query(Some(&escaped_query));
     tracing::trace!("escaped_query={}", escaped_query);
 
     // create header with value
@@ -241,7 +287,7 @@ pub(crate) fn create_signed_buffer(
         header_with_value.sort();
         header_with_value
     };
-    let path = opts.style.path(bucket, name);
+    let path = format!("/{}", utf8_percent_encode(&opts.style.path(bucket, name), V4_PATH_ENCODE_SET));
     builder.set_path(&path);
 
     // create raw buffer
@@ -249,7 +295,7 @@ pub(crate) fn create_signed_buffer(
         let mut buffer = format!(
             "{}\n{}\n{}\n{}\n\n{}\n",
             opts.method.as_str(),
-            builder.path().replace('+', "%20"),
+            path,
             escaped_query,
             header_with_value.join("\n"),
             signed_headers
@@ -283,6 +329,195 @@ pub(crate) fn create_signed_buffer(
     Ok((signed_buffer, builder))
 }
 
+/// v4_credential_scope returns the timestamp and the credential scope of the V4 signature.
+fn v4_credential_scope(start_time: OffsetDateTime) -> (String, String) {
+    const CONFIG: EncodedConfig = well_known::iso8601::Config::DEFAULT
+        .set_use_separators(false)
+        .set_time_precision(TimePrecision::Second { decimal_digits: None })
+        .encode();
+
+    let timestamp = start_time.format(&Iso8601::<CONFIG>).unwrap();
+    let credential_scope = format!(
+        "{}/auto/storage/goog4_request",
+        start_time.format(format_description!("[year][month][day]")).unwrap()
+    );
+    (timestamp, credential_scope)
+}
+
+/// PostPolicyV4Condition is a condition the form fields of the upload must satisfy.
+/// See https://cloud.google.com/storage/docs/authentication/signatures#policy-document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PostPolicyV4Condition {
+    /// The field must be the value like `{"acl": "public-read"}`.
+    Exact(String, String),
+    /// The field must start with the prefix like `["starts-with", "$key", "uploads/"]`.
+    StartsWith(String, String),
+    /// The size of the object in bytes must be within the range like `["content-length-range", 0, 1024]`.
+    ContentLengthRange(u64, u64),
+}
+
+impl PostPolicyV4Condition {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Self::Exact(field, value) => serde_json::json!({ field: value }),
+            Self::StartsWith(field, prefix) => serde_json::json!(["starts-with", format!("${field}"), prefix]),
+            Self::ContentLengthRange(min, max) => serde_json::json!(["content-length-range", min, max]),
+        }
+    }
+}
+
+/// PostPolicyV4Fields is the form fields of the upload. Each of them is added to the conditions too.
+#[derive(Clone, Debug, Default)]
+pub struct PostPolicyV4Fields {
+    pub acl: Option<String>,
+    pub cache_control: Option<String>,
+    pub content_type: Option<String>,
+    pub content_disposition: Option<String>,
+    pub content_encoding: Option<String>,
+    /// The custom metadata of the object. Each key is sent as `x-goog-meta-{key}`.
+    pub metadata: HashMap<String, String>,
+    /// The URL the browser is redirected to after the successful upload.
+    pub redirect_to_url_on_success: Option<String>,
+    /// The status code returned after the successful upload. It is ignored if `redirect_to_url_on_success` is set.
+    pub status_code_on_success: Option<u16>,
+}
+
+impl PostPolicyV4Fields {
+    fn to_pairs(&self) -> Vec<(String, String)> {
+        let mut pairs = vec![];
+        let fields = [
+            ("acl", &self.acl),
+            ("cache-control", &self.cache_control),
+            ("content-type", &self.content_type),
+            ("content-disposition", &self.content_disposition),
+            ("content-encoding", &self.content_encoding),
+            ("success_action_redirect", &self.redirect_to_url_on_success),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                pairs.push((name.to_string(), value.to_string()));
+            }
+        }
+        if let Some(status) = self.status_code_on_success {
+            pairs.push(("success_action_status".to_string(), status.to_string()));
+        }
+        let mut metadata: Vec<(String, String)> = self
+            .metadata
+            .iter()
+            .map(|(k, v)| (format!("x-goog-meta-{k}"), v.to_string()))
+            .collect();
+        metadata.sort();
+        pairs.extend(metadata);
+        pairs
+    }
+}
+
+/// PostPolicyV4Options is the options of the policy document for the HTML form upload.
+pub struct PostPolicyV4Options {
+    /// StartTime is the time at which the policy starts being valid.
+    /// Defaults to the current time.
+    pub start_time: Option<SystemTime>,
+
+    /// Expires is the duration of time, beginning at StartTime, within which
+    /// the policy is valid. It may be no more than 604800 seconds (7 days).
+    pub expires: Duration,
+
+    /// Fields is the form fields which are added to the returned fields and the conditions.
+    pub fields: PostPolicyV4Fields,
+
+    /// Conditions is the additional conditions the upload must satisfy.
+    pub conditions: Vec<PostPolicyV4Condition>,
+
+    /// Style provides options for the type of URL to use.
+    pub style: Box<dyn URLStyle + Send + Sync>,
+
+    /// Insecure determines whether the URL should use HTTPS (default) or HTTP.
+    pub insecure: bool,
+}
+
+impl Default for PostPolicyV4Options {
+    fn default() -> Self {
+        Self {
+            start_time: None,
+            expires: Duration::from_secs(600),
+            fields: PostPolicyV4Fields::default(),
+            conditions: vec![],
+            style: Box::new(PathStyle {}),
+            insecure: false,
+        }
+    }
+}
+
+/// PostPolicyV4 is the URL and the form fields of the HTML form uploading an object.
+#[derive(Clone, Debug)]
+pub struct PostPolicyV4 {
+    /// The URL the form is posted to.
+    pub url: String,
+    /// The form fields. All of them must be sent before the `file` field.
+    pub fields: HashMap<String, String>,
+}
+
+/// create_post_policy returns the base64 encoded policy document to sign and the policy without the signature.
+pub(crate) fn create_post_policy(
+    bucket: &str,
+    name: &str,
+    google_access_id: &str,
+    opts: &PostPolicyV4Options,
+) -> Result<(Vec<u8>, PostPolicyV4), SignedURLError> {
+    validate_expires(opts.expires)?;
+    let start_time = opts.start_time.unwrap_or_else(SystemTime::now);
+    let expiration: OffsetDateTime = (start_time + opts.expires).into();
+    let (timestamp, credential_scope) = v4_credential_scope(start_time.into());
+    let credential = format!("{google_access_id}/{credential_scope}");
+
+    let mut fields = opts.fields.to_pairs();
+    fields.extend([
+        ("bucket".to_string(), bucket.to_string()),
+        ("key".to_string(), name.to_string()),
+        ("x-goog-date".to_string(), timestamp),
+        ("x-goog-credential".to_string(), credential),
+        ("x-goog-algorithm".to_string(), "GOOG4-RSA-SHA256".to_string()),
+    ]);
+
+    let mut conditions: Vec<serde_json::Value> = opts.conditions.iter().map(|c| c.to_json()).collect();
+    conditions.extend(fields.iter().map(|(k, v)| serde_json::json!({ k: v })));
+    let policy = serde_json::json!({
+        "conditions": conditions,
+        "expiration": expiration.format(format_description!("[year]-[month]-[day]T[hour]:[minute]:[second]Z")).unwrap(),
+    });
+    let policy = BASE64_STANDARD.encode(escape_non_ascii(&policy.to_string()));
+    tracing::trace!("policy={}", policy);
+
+    let scheme = if opts.insecure { "http" } else { "https" };
+    let path = opts.style.path(bucket, "");
+    let url = if path.is_empty() {
+        format!("{scheme}://{}/", opts.style.host(bucket))
+    } else {
+        format!("{scheme}://{}/{path}/", opts.style.host(bucket))
+    };
+
+    // the bucket is identified by the URL
+    let mut fields: HashMap<String, String> = fields.into_iter().filter(|(k, _)| k != "bucket").collect();
+    fields.insert("policy".to_string(), policy.clone());
+    Ok((policy.into_bytes(), PostPolicyV4 { url, fields }))
+}
+
+/// escape_non_ascii escapes the non-ASCII characters of the JSON as `\uXXXX` as the server expects.
+fn escape_non_ascii(json: &str) -> String {
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        if c.is_ascii() {
+            escaped.push(c);
+        } else {
+            let mut buf = [0u16; 2];
+            for unit in c.encode_utf16(&mut buf) {
+                escaped.push_str(&format!("\\u{unit:04x}"));
+            }
+        }
+    }
+    escaped
+}
+
 fn v4_sanitize_headers(hdrs: &[String]) -> Vec<String> {
     let mut sanitized = HashMap::<String, Vec<String>>::new();
     for hdr in hdrs {
@@ -316,9 +551,6 @@ fn extract_header_names(kvs: &[String]) -> Vec<&str> {
 }
 
 fn validate_options(opts: &SignedURLOptions) -> Result<(), SignedURLError> {
-    if opts.expires.is_zero() {
-        return Err(InvalidOption("storage: expires cannot be zero"));
-    }
     if let Some(md5) = &opts.md5 {
         match BASE64_STANDARD.decode(md5) {
             Ok(v) => {
@@ -329,7 +561,14 @@ fn validate_options(opts: &SignedURLOptions) -> Result<(), SignedURLError> {
             Err(_e) => return Err(InvalidOption("storage: invalid MD5 checksum")),
         }
     }
-    if opts.expires > Duration::from_secs(ONE_WEEK_IN_SECONDS) {
+    validate_expires(opts.expires)
+}
+
+fn validate_expires(expires: Duration) -> Result<(), SignedURLError> {
+    if expires.is_zero() {
+        return Err(InvalidOption("storage: expires cannot be zero"));
+    }
+    if expires > Duration::from_secs(ONE_WEEK_IN_SECONDS) {
         return Err(InvalidOption("storage: expires must be within seven days from now"));
     }
     Ok(())
@@ -369,12 +608,23 @@ mod test {
     use std::collections::HashMap;
     use std::time::Duration;
 
+    use base64::prelude::*;
     use serial_test::serial;
 
     use crate::http::storage_client::test::bucket_name;
     use google_cloud_auth::credentials::CredentialsFile;
 
-    use crate::sign::{create_signed_buffer, SignedURLOptions};
+    use crate::sign::{
+        create_post_policy, create_signed_buffer, BucketBoundHostname, PostPolicyV4Condition, PostPolicyV4Fields,
+        PostPolicyV4Options, SignedURLOptions, VirtualHostedStyle,
+    };
+
+    const GOOGLE_ACCESS_ID: &str = "test-iam-credentials@dummy-project-id.iam.gserviceaccount.com";
+
+    fn start_time() -> Option<std::time::SystemTime> {
+        // 2019-02-01T09:00:00Z
+        Some(std::time::UNIX_EPOCH + Duration::from_secs(1549011600))
+    }
 
     #[tokio::test]
     #[serial]
@@ -400,4 +650,118 @@ mod test {
         .unwrap();
         assert_eq!(signed_buffer.len(), 134)
     }
+
+    #[test]
+    fn create_signed_buffer_conformance_test() {
+        let opts = SignedURLOptions {
+            start_time: start_time(),
+            expires: Duration::from_secs(10),
+            ..Default::default()
+        };
+        let (signed_buffer, builder) =
+            create_signed_buffer("test-bucket", "test-object", GOOGLE_ACCESS_ID, &opts).unwrap();
+        assert_eq!(
+            String::from_utf8(signed_buffer).unwrap(),
+            "GOOG4-RSA-SHA256\n20190201T090000Z\n20190201/auto/storage/goog4_request\n\
+             00e2fb794ea93d7adb703edaebdd509821fcc7d4f1a79ac5c8d2b394df109320"
+        );
+        assert_eq!(
+            builder.to_string(),
+            "https://storage.googleapis.com/test-bucket/test-object?X-Goog-Algorithm=GOOG4-RSA-SHA256\
+             &X-Goog-Credential=test-iam-credentials%40dummy-project-id.iam.gserviceaccount.com\
+             %2F20190201%2Fauto%2Fstorage%2Fgoog4_request&X-Goog-Date=20190201T090000Z&X-Goog-Expires=10\
+             &X-Goog-SignedHeaders=host"
+        );
+
+        let opts = SignedURLOptions {
+            start_time: start_time(),
+            expires: Duration::from_secs(10),
+            style: Box::new(VirtualHostedStyle {}),
+            ..Default::default()
+        };
+        let (signed_buffer, builder) =
+            create_signed_buffer("test-bucket", "test-object", GOOGLE_ACCESS_ID, &opts).unwrap();
+        assert!(String::from_utf8(signed_buffer)
+            .unwrap()
+            .ends_with("89eeae48258eccdcb1f592fb908008e3f5d36a949c002c1e614c94356dc18fc6"));
+        assert!(builder
+            .to_string()
+            .starts_with("https://test-bucket.storage.googleapis.com/test-object?"));
+    }
+
+    #[test]
+    fn create_signed_buffer_escape_test() {
+        let opts = SignedURLOptions {
+            start_time: start_time(),
+            query_parameters: HashMap::from([
+                ("b".to_string(), vec!["1 2".to_string()]),
+                ("a~".to_string(), vec!["*".to_string()]),
+            ]),
+            style: Box::new(BucketBoundHostname {
+                hostname: "mydomain.tld".to_string(),
+            }),
+            ..Default::default()
+        };
+        let (_signed_buffer, builder) =
+            create_signed_buffer("test-bucket", "a+b/c d", GOOGLE_ACCESS_ID, &opts).unwrap();
+        assert_eq!(builder.host_str(), Some("mydomain.tld"));
+        assert_eq!(builder.path(), "/a%2Bb/c%20d");
+        // the query parameters are sorted and only the unreserved characters are not escaped
+        assert!(builder
+            .query()
+            .unwrap()
+            .ends_with("X-Goog-SignedHeaders=host&a~=%2A&b=1%202"));
+    }
+
+    #[test]
+    fn create_post_policy_test() {
+        let opts = PostPolicyV4Options {
+            start_time: start_time(),
+            expires: Duration::from_secs(10),
+            fields: PostPolicyV4Fields {
+                content_type: Some("text/plain".to_string()),
+                metadata: HashMap::from([("name".to_string(), "ñ".to_string())]),
+                status_code_on_success: Some(201),
+                ..Default::default()
+            },
+            conditions: vec![
+                PostPolicyV4Condition::StartsWith("key".to_string(), "".to_string()),
+                PostPolicyV4Condition::ContentLengthRange(0, 20),
+            ],
+            ..Default::default()
+        };
+        let (policy, post_policy) = create_post_policy("test-bucket", "test-object", GOOGLE_ACCESS_ID, &opts).unwrap();
+        assert_eq!(post_policy.url, "https://storage.googleapis.com/test-bucket/");
+        assert_eq!(post_policy.fields["key"], "test-object");
+        assert_eq!(post_policy.fields["content-type"], "text/plain");
+        assert_eq!(post_policy.fields["success_action_status"], "201");
+        assert_eq!(post_policy.fields["x-goog-meta-name"], "ñ");
+        assert_eq!(post_policy.fields["x-goog-date"], "20190201T090000Z");
+        assert_eq!(post_policy.fields["x-goog-algorithm"], "GOOG4-RSA-SHA256");
+        assert_eq!(
+            post_policy.fields["x-goog-credential"],
+            format!("{GOOGLE_ACCESS_ID}/20190201/auto/storage/goog4_request")
+        );
+        assert!(!post_policy.fields.contains_key("bucket"));
+        assert_eq!(post_policy.fields["policy"].as_bytes(), policy.as_slice());
+
+        let decoded = String::from_utf8(BASE64_STANDARD.decode(&policy).unwrap()).unwrap();
+        assert_eq!(
+            decoded,
+            concat!(
+                r#"{"conditions":[["starts-with","$key",""],["content-length-range",0,20],"#,
+                r#"{"content-type":"text/plain"},{"success_action_status":"201"},{"x-goog-meta-name":"\u00f1"},"#,
+                r#"{"bucket":"test-bucket"},{"key":"test-object"},{"x-goog-date":"20190201T090000Z"},"#,
+                r#"{"x-goog-credential":"test-iam-credentials@dummy-project-id.iam.gserviceaccount.com"#,
+                r#"/20190201/auto/storage/goog4_request"},{"x-goog-algorithm":"GOOG4-RSA-SHA256"}],"#,
+                r#""expiration":"2019-02-01T09:00:10Z"}"#
+            )
+        );
+
+        let opts = PostPolicyV4Options {
+            expires: Duration::ZERO,
+            ..Default::default()
+        };
+        assert!(create_post_policy("test-bucket", "test-object", GOOGLE_ACCESS_ID, &opts).is_err());
+    }
 }